@@ -0,0 +1,20 @@
+-- Audit trail of currency conversions
+
+CREATE TABLE conversion_records (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    account_id VARCHAR(255) NOT NULL,
+    from_currency VARCHAR(10) NOT NULL,
+    to_currency VARCHAR(10) NOT NULL,
+    original_amount BIGINT NOT NULL CHECK (original_amount >= 0),
+    converted_amount BIGINT NOT NULL CHECK (converted_amount >= 0),
+    exchange_rate DOUBLE PRECISION NOT NULL,
+    fees BIGINT NOT NULL DEFAULT 0,
+    slippage DOUBLE PRECISION NOT NULL DEFAULT 0,
+    provider VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    metadata JSONB NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX idx_conversion_records_account_created ON conversion_records(account_id, created_at);
@@ -0,0 +1,7 @@
+-- Which roles may see each transaction; see TransactionVisibility
+
+ALTER TABLE transactions
+    ADD COLUMN visibility VARCHAR(20) NOT NULL DEFAULT 'public',
+    ADD CONSTRAINT valid_visibility CHECK (visibility IN ('public', 'auditor_only', 'admin_only'));
+
+CREATE INDEX idx_transactions_visibility ON transactions(visibility);
@@ -1,13 +1,22 @@
 //! User account management module
 
+use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Utc};
-use ed25519_dalek::PublicKey;
+use ed25519_dalek::{PublicKey, PUBLIC_KEY_LENGTH};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use uuid::Uuid;
 
+use crate::admin::AdminManager;
+use crate::central_bank::DEFAULT_CURRENCY;
+use crate::conversion::{ConversionResult, ConversionService};
 use crate::errors::AstorError;
-use crate::security::Signature;
+use crate::receipts::{ReceiptChannel, TransactionReceipt};
+use crate::regulatory::RiskRating;
+use crate::security::{Permission, SecurityValidator, Signature, TransactionLimits};
 
 /// User account information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,127 +26,2474 @@ pub struct Account {
     pub balance: u64,
     pub created_at: DateTime<Utc>,
     pub last_transaction: Option<DateTime<Utc>>,
-    pub is_frozen: bool,
+    #[serde(default)]
+    pub status: AccountStatus,
+    #[serde(default)]
+    pub freeze_reason: Option<String>,
+    /// Admin who last froze, unfroze or closed the account
+    #[serde(default)]
+    pub status_changed_by: Option<String>,
+    /// Balances held in currencies other than the default, which is `balance`
+    #[serde(default)]
+    pub currency_balances: HashMap<String, u64>,
+    /// Funds reserved against `balance` for pending debits
+    #[serde(default)]
+    pub holds: Vec<BalanceHold>,
+    #[serde(default)]
+    pub account_type: AccountType,
+    #[serde(default)]
+    pub risk_rating: RiskRating,
+    /// Thresholds the holder is notified about when the balance crosses them
+    #[serde(default)]
+    pub balance_alerts: Vec<BalanceAlertRule>,
+    /// When the account was marked dormant for inactivity
+    #[serde(default)]
+    pub dormant_since: Option<DateTime<Utc>>,
+    /// Incremented whenever the account's balances or holds change
+    ///
+    /// A caller that checks the balance before transferring passes the
+    /// version it read to `transfer_at_version`, which rejects the transfer
+    /// if anything changed in between.
+    #[serde(default)]
+    pub version: u64,
+    /// Nonce the next signed transfer from this account must carry
+    ///
+    /// Each accepted `transfer_signed` increments it, so an authorization
+    /// can be used only once.
+    #[serde(default)]
+    pub transfer_nonce: u64,
 }
 
+/// Where an account is in its lifecycle
+///
+/// Frozen accounts keep their balance but cannot send or receive funds until
+/// an admin unfreezes them. Closing is permanent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AccountStatus {
+    #[default]
+    Active,
+    Frozen,
+    Closed,
+}
+
+/// Kind of holder an account belongs to, used to pick its transaction limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AccountType {
+    #[default]
+    Individual,
+    Business,
+    Merchant,
+}
+
+/// Condition on the default-currency balance that notifies the holder
+///
+/// Threshold rules fire when a balance change crosses the threshold, not on
+/// every change while the balance stays past it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BalanceAlertRule {
+    /// Balance drops below `threshold`
+    Below { threshold: u64 },
+    /// Balance rises above `threshold`
+    Above { threshold: u64 },
+    /// A single change of at least `amount` in either direction
+    LargeChange { amount: u64 },
+}
+
+impl BalanceAlertRule {
+    /// Whether a change from `previous` to `current` triggers this rule
+    pub fn is_triggered(&self, previous: u64, current: u64) -> bool {
+        match *self {
+            BalanceAlertRule::Below { threshold } => previous >= threshold && current < threshold,
+            BalanceAlertRule::Above { threshold } => previous <= threshold && current > threshold,
+            BalanceAlertRule::LargeChange { amount } => previous.abs_diff(current) >= amount,
+        }
+    }
+}
+
+/// A balance alert that fired
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceNotification {
+    pub account_id: String,
+    pub rule: BalanceAlertRule,
+    pub previous_balance: u64,
+    pub balance: u64,
+    pub triggered_at: DateTime<Utc>,
+}
+
+/// Delivers notifications to account holders, e.g. through an SMTP relay
+/// or SMS gateway
+pub trait Notifier: Send + Sync {
+    fn notify(&self, notification: &BalanceNotification) -> Result<(), AstorError>;
+
+    /// Deliver a signed transfer receipt over the channel the holder chose
+    fn send_receipt(
+        &self,
+        channel: &ReceiptChannel,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), AstorError>;
+}
+
+/// Writes notifications to the service log when no delivery channel is set up
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify(&self, notification: &BalanceNotification) -> Result<(), AstorError> {
+        tracing::info!(
+            "Balance alert for {}: {:?} ({} -> {})",
+            notification.account_id,
+            notification.rule,
+            notification.previous_balance,
+            notification.balance
+        );
+        Ok(())
+    }
+
+    fn send_receipt(
+        &self,
+        channel: &ReceiptChannel,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), AstorError> {
+        tracing::info!(
+            "Receipt for {} to {:?}: {}",
+            receipt.details.transaction_id,
+            channel,
+            receipt.verification_url
+        );
+        Ok(())
+    }
+}
+
+/// Funds reserved on an account, e.g. for an authorized but uncaptured payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceHold {
+    pub hold_id: String,
+    pub amount: u64,
+    pub reason: String,
+    pub placed_at: DateTime<Utc>,
+}
+
+impl Account {
+    /// Balance held in the given currency
+    pub fn currency_balance(&self, currency: &str) -> u64 {
+        if currency == DEFAULT_CURRENCY {
+            self.balance
+        } else {
+            self.currency_balances.get(currency).copied().unwrap_or(0)
+        }
+    }
+
+    /// Total reserved by holds
+    pub fn held_amount(&self) -> u64 {
+        self.holds
+            .iter()
+            .fold(0u64, |total, hold| total.saturating_add(hold.amount))
+    }
+
+    /// Ledger balance minus holds; what can actually be spent
+    pub fn available_balance(&self) -> u64 {
+        self.balance.saturating_sub(self.held_amount())
+    }
+
+    /// Last time the account was used, or when it was opened if never
+    pub fn last_activity(&self) -> DateTime<Utc> {
+        self.last_transaction.unwrap_or(self.created_at)
+    }
+
+    pub fn is_dormant(&self) -> bool {
+        self.dormant_since.is_some()
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.status == AccountStatus::Frozen
+    }
+
+    /// Fail unless the account may have its balance changed
+    pub fn ensure_active(&self) -> Result<(), AstorError> {
+        match self.status {
+            AccountStatus::Active => Ok(()),
+            AccountStatus::Frozen => Err(AstorError::AccountFrozen(self.id.clone())),
+            AccountStatus::Closed => Err(AstorError::AccountClosed(self.id.clone())),
+        }
+    }
+
+    /// Dormant accounts cannot be debited until the holder re-verifies
+    fn ensure_not_dormant(&self) -> Result<(), AstorError> {
+        if self.is_dormant() {
+            return Err(AstorError::AccountDormant(self.id.clone()));
+        }
+        Ok(())
+    }
+
+    /// Fail unless `amount` can be spent without dipping into held funds
+    ///
+    /// A shortfall in the ledger balance itself is reported as
+    /// `InsufficientFunds`; one caused only by holds reports both figures.
+    fn ensure_available(&self, amount: u64) -> Result<(), AstorError> {
+        if self.balance < amount {
+            return Err(AstorError::InsufficientFunds);
+        }
+
+        let available = self.available_balance();
+        if available < amount {
+            return Err(AstorError::InsufficientAvailableBalance {
+                requested: amount,
+                available,
+                ledger_balance: self.balance,
+            });
+        }
+        Ok(())
+    }
+
+    /// Alerts triggered by the balance having changed from `previous_balance`
+    fn triggered_alerts(&self, previous_balance: u64) -> Vec<BalanceNotification> {
+        if previous_balance == self.balance {
+            return Vec::new();
+        }
+
+        let now = Utc::now();
+        self.balance_alerts
+            .iter()
+            .filter(|rule| rule.is_triggered(previous_balance, self.balance))
+            .map(|rule| BalanceNotification {
+                account_id: self.id.clone(),
+                rule: rule.clone(),
+                previous_balance,
+                balance: self.balance,
+                triggered_at: now,
+            })
+            .collect()
+    }
+
+    fn set_currency_balance(&mut self, currency: &str, amount: u64) {
+        if currency == DEFAULT_CURRENCY {
+            self.balance = amount;
+        } else {
+            self.currency_balances.insert(currency.to_string(), amount);
+        }
+        self.version += 1;
+    }
+
+    fn ensure_version(&self, expected_version: Option<u64>) -> Result<(), AstorError> {
+        match expected_version {
+            Some(expected) if expected != self.version => {
+                Err(AstorError::ConcurrentModification(format!(
+                    "Account {} changed from version {} to {}; retry the transfer",
+                    self.id, expected, self.version
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn ensure_transfer_nonce(&self, nonce: Option<u64>) -> Result<(), AstorError> {
+        match nonce {
+            Some(received) if received != self.transfer_nonce => Err(AstorError::NonceMismatch {
+                expected: self.transfer_nonce,
+                received,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Longest caller-chosen account id accepted by a bulk import
+const MAX_ACCOUNT_ID_LEN: usize = 64;
+
+/// One account to create in a bulk import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSpec {
+    /// Id to open the account under; a UUID is assigned when absent
+    #[serde(default)]
+    pub account_id: Option<String>,
+    /// Base64-encoded Ed25519 public key
+    #[serde(default)]
+    pub public_key: Option<String>,
+    #[serde(default)]
+    pub initial_balance: u64,
+}
+
+/// How a bulk import treats invalid rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportMode {
+    /// Create nothing unless every row is valid
+    AllOrNothing,
+    /// Create the valid rows and report the invalid ones
+    BestEffort,
+}
+
+/// Outcome of one row of a bulk import
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ImportRowStatus {
+    Created {
+        account_id: String,
+    },
+    Invalid {
+        reason: String,
+    },
+    /// Valid, but not created because another row failed in all-or-nothing mode
+    NotImported,
+}
+
+/// Per-row results of a bulk import, in input order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub mode: ImportMode,
+    pub rows: Vec<ImportRowStatus>,
+}
+
+impl ImportResult {
+    /// Ids of the accounts that were created
+    pub fn created(&self) -> Vec<&str> {
+        self.rows
+            .iter()
+            .filter_map(|row| match row {
+                ImportRowStatus::Created { account_id } => Some(account_id.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Row numbers and reasons for rows that failed validation
+    pub fn failures(&self) -> Vec<(usize, &str)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter_map(|(row, status)| match status {
+                ImportRowStatus::Invalid { reason } => Some((row, reason.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.rows
+            .iter()
+            .all(|row| matches!(row, ImportRowStatus::Created { .. }))
+    }
+}
+
+/// Number of independently locked account shards
+const ACCOUNT_SHARDS: usize = 16;
+
+type Shard = HashMap<String, Account>;
+
 /// Manages user accounts and balances
+///
+/// Accounts are spread over shards, each behind its own lock, so the manager
+/// can be shared between threads. An operation holds the locks of every shard
+/// it touches for its whole duration, always acquired in ascending shard
+/// order, so readers never observe a partially-applied transfer.
 pub struct AccountManager {
-    accounts: HashMap<String, Account>,
+    shards: Vec<RwLock<Shard>>,
+    /// Enforces per-transaction caps on transfers; unlimited when unset
+    limit_validator: Option<SecurityValidator>,
+    /// Receives balance alerts; alerts are not sent when unset
+    notifier: Option<Arc<dyn Notifier>>,
+}
+
+/// Write locks on the one or two shards a transfer touches
+struct LockedShards<'a> {
+    low_index: usize,
+    low: RwLockWriteGuard<'a, Shard>,
+    high: Option<RwLockWriteGuard<'a, Shard>>,
+}
+
+impl LockedShards<'_> {
+    fn shard(&mut self, index: usize) -> &mut Shard {
+        match &mut self.high {
+            Some(high) if index != self.low_index => high,
+            _ => &mut self.low,
+        }
+    }
+}
+
+/// Message a recipient signs to acknowledge receipt of transfer `tx_id`
+pub fn receipt_acknowledgment_message(tx_id: &str) -> String {
+    format!("acknowledge_receipt_{}", tx_id)
+}
+
+/// Message the holder of `from` signs to authorize `transfer_signed`
+///
+/// `nonce` is the account's current `transfer_nonce` and `tx_id` the ID the
+/// transfer will be recorded under, so a signature authorizes one transfer.
+pub fn transfer_message(from: &str, to: &str, amount: u64, nonce: u64, tx_id: &str) -> String {
+    format!("transfer_{}_{}_{}_{}_{}", from, to, amount, nonce, tx_id)
+}
+
+/// Message a holder signs to reactivate dormant account `account_id`
+///
+/// `dormant_since` is the account's current `dormant_since`, so a signature
+/// only lifts the dormancy it was made for and cannot be replayed against a
+/// later one.
+pub fn reactivation_message(account_id: &str, dormant_since: DateTime<Utc>) -> String {
+    format!(
+        "reactivate_account_{}_{}",
+        account_id,
+        dormant_since.timestamp_micros()
+    )
+}
+
+/// Message an administrator signs to apply status `action` (e.g. "freeze")
+/// to `account_id`
+///
+/// `version` is the account's current version, which every status change
+/// bumps, so a signature authorizes one change.
+pub fn account_status_message(action: &str, account_id: &str, version: u64) -> String {
+    format!("{}_account_{}_{}", action, account_id, version)
+}
+
+/// Check that `admin_id` holds `permission` and signed `action` for the
+/// account at `version`
+fn authorize_status_change(
+    admins: &AdminManager,
+    admin_id: &str,
+    permission: &Permission,
+    action: &str,
+    account_id: &str,
+    version: u64,
+    signature: &Signature,
+) -> Result<(), AstorError> {
+    let admin = admins.get_admin(admin_id)?;
+    if !admin.role.has_permission(permission) {
+        return Err(AstorError::Unauthorized(format!(
+            "Administrator {} may not {} accounts",
+            admin_id, action
+        )));
+    }
+    let message = account_status_message(action, account_id, version);
+    admins.verify_admin_action(admin_id, message.as_bytes(), signature)
+}
+
+/// Parse raw Ed25519 public key bytes, checking length before decoding
+pub fn parse_public_key(bytes: &[u8]) -> Result<PublicKey, AstorError> {
+    if bytes.len() != PUBLIC_KEY_LENGTH {
+        return Err(AstorError::CryptographicError(format!(
+            "Public key must be {} bytes, got {}",
+            PUBLIC_KEY_LENGTH,
+            bytes.len()
+        )));
+    }
+
+    PublicKey::from_bytes(bytes).map_err(|_| {
+        AstorError::CryptographicError("Public key is not a valid Ed25519 key".to_string())
+    })
+}
+
+fn shard_index(account_id: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    account_id.hash(&mut hasher);
+    (hasher.finish() % ACCOUNT_SHARDS as u64) as usize
+}
+
+fn new_account(account_id: &str, public_key: Option<PublicKey>) -> Account {
+    Account {
+        id: account_id.to_string(),
+        public_key,
+        balance: 0,
+        created_at: Utc::now(),
+        last_transaction: None,
+        status: AccountStatus::Active,
+        freeze_reason: None,
+        status_changed_by: None,
+        currency_balances: HashMap::new(),
+        holds: Vec::new(),
+        account_type: AccountType::default(),
+        risk_rating: RiskRating::default(),
+        balance_alerts: Vec::new(),
+        dormant_since: None,
+        version: 0,
+        transfer_nonce: 0,
+    }
 }
 
 impl AccountManager {
     /// Create a new account manager
     pub fn new() -> Self {
         Self {
-            accounts: HashMap::new(),
+            shards: (0..ACCOUNT_SHARDS)
+                .map(|_| RwLock::new(HashMap::new()))
+                .collect(),
+            limit_validator: None,
+            notifier: None,
         }
     }
 
-    /// Create a new user account
-    pub fn create_account(&mut self, public_key: Option<PublicKey>) -> String {
-        let account_id = Uuid::new_v4().to_string();
+    /// Enforce per-transaction limits by account type and risk rating on
+    /// transfers
+    pub fn set_transaction_limits(&mut self, limits: TransactionLimits) {
+        let mut validator = SecurityValidator::new();
+        validator.set_transaction_limits(limits);
+        self.limit_validator = Some(validator);
+    }
 
-        let account = Account {
-            id: account_id.clone(),
-            public_key,
-            balance: 0,
-            created_at: Utc::now(),
-            last_transaction: None,
-            is_frozen: false,
+    pub fn transaction_limits(&self) -> Option<&TransactionLimits> {
+        self.limit_validator
+            .as_ref()
+            .map(SecurityValidator::transaction_limits)
+    }
+
+    /// Deliver balance alerts through `notifier`
+    pub fn set_notifier(&mut self, notifier: Arc<dyn Notifier>) {
+        self.notifier = Some(notifier);
+    }
+
+    /// Send alerts once the account locks are released
+    ///
+    /// The balance change has already been applied, so a delivery failure is
+    /// logged rather than returned.
+    fn send_balance_alerts(&self, notifications: Vec<BalanceNotification>) {
+        let Some(notifier) = &self.notifier else {
+            return;
         };
+        for notification in &notifications {
+            if let Err(e) = notifier.notify(notification) {
+                tracing::warn!(
+                    "Failed to send balance alert {:?} for account {}: {}",
+                    notification.rule,
+                    notification.account_id,
+                    e
+                );
+            }
+        }
+    }
 
-        self.accounts.insert(account_id.clone(), account);
-        account_id
+    // Every update is validated before it is applied, so a panic while a lock
+    // is held cannot leave an account half-updated; a poisoned lock is safe
+    // to keep using.
+    fn read_shard(&self, index: usize) -> RwLockReadGuard<'_, Shard> {
+        self.shards[index]
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_shard(&self, index: usize) -> RwLockWriteGuard<'_, Shard> {
+        self.shards[index]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
     }
 
-    /// Get account by ID
-    pub fn get_account(&self, account_id: &str) -> Result<&Account, AstorError> {
-        self.accounts
+    /// Lock the shards holding two accounts, in ascending shard order
+    fn lock_pair(&self, first: usize, second: usize) -> LockedShards<'_> {
+        let (low_index, high_index) = (first.min(second), first.max(second));
+        let low = self.write_shard(low_index);
+        let high = (high_index != low_index).then(|| self.write_shard(high_index));
+        LockedShards {
+            low_index,
+            low,
+            high,
+        }
+    }
+
+    fn with_account<R>(
+        &self,
+        account_id: &str,
+        f: impl FnOnce(&Account) -> Result<R, AstorError>,
+    ) -> Result<R, AstorError> {
+        let shard = self.read_shard(shard_index(account_id));
+        let account = shard
             .get(account_id)
-            .ok_or_else(|| AstorError::AccountNotFound(account_id.to_string()))
+            .ok_or_else(|| AstorError::AccountNotFound(account_id.to_string()))?;
+        f(account)
     }
 
-    /// Get mutable account by ID
-    fn get_account_mut(&mut self, account_id: &str) -> Result<&mut Account, AstorError> {
-        self.accounts
+    fn with_account_mut<R>(
+        &self,
+        account_id: &str,
+        f: impl FnOnce(&mut Account) -> Result<R, AstorError>,
+    ) -> Result<R, AstorError> {
+        let mut shard = self.write_shard(shard_index(account_id));
+        let account = shard
             .get_mut(account_id)
-            .ok_or_else(|| AstorError::AccountNotFound(account_id.to_string()))
+            .ok_or_else(|| AstorError::AccountNotFound(account_id.to_string()))?;
+        f(account)
     }
 
-    /// Credit account with amount
-    pub fn credit_account(&mut self, account_id: &str, amount: u64) -> Result<(), AstorError> {
-        let account = self.get_account_mut(account_id)?;
+    /// Create a new user account
+    pub fn create_account(&self, public_key: Option<PublicKey>) -> String {
+        let account_id = Uuid::new_v4().to_string();
+        self.write_shard(shard_index(&account_id))
+            .insert(account_id.clone(), new_account(&account_id, public_key));
+        account_id
+    }
 
-        if account.is_frozen {
-            return Err(AstorError::Unauthorized("Account is frozen".to_string()));
-        }
+    /// Create an account from raw Ed25519 public key bytes
+    ///
+    /// For keys arriving over the API or CLI. Malformed key material is
+    /// rejected with `CryptographicError` and no account is created.
+    pub fn create_account_from_bytes(&self, public_key: &[u8]) -> Result<String, AstorError> {
+        let public_key = parse_public_key(public_key)?;
+        Ok(self.create_account(Some(public_key)))
+    }
 
-        account.balance = account.balance.checked_add(amount).ok_or_else(|| {
-            AstorError::TransactionValidationFailed("Balance overflow".to_string())
-        })?;
-        account.last_transaction = Some(Utc::now());
+    /// Create many accounts at once
+    ///
+    /// Every spec is validated before anything is created, including
+    /// duplicate ids within the batch. In `AllOrNothing` mode a single invalid
+    /// row means no account is created; in `BestEffort` mode the valid rows
+    /// are created and the invalid ones reported. All shards stay locked for
+    /// the import, so no account can appear between validation and creation.
+    ///
+    /// `record` writes each non-zero opening balance to the ledger before its
+    /// account is created; a row whose balance cannot be recorded is reported
+    /// invalid and not created.
+    pub fn import_accounts(
+        &self,
+        specs: Vec<AccountSpec>,
+        mode: ImportMode,
+        mut record: impl FnMut(&str, u64) -> Result<(), AstorError>,
+    ) -> ImportResult {
+        let mut shards: Vec<RwLockWriteGuard<'_, Shard>> =
+            (0..ACCOUNT_SHARDS).map(|i| self.write_shard(i)).collect();
 
-        Ok(())
+        let mut seen_ids = HashSet::new();
+        let validated: Vec<Result<(String, Option<PublicKey>, u64), String>> = specs
+            .into_iter()
+            .map(|spec| {
+                Self::validate_spec(spec, &mut seen_ids, |id| {
+                    shards[shard_index(id)].contains_key(id)
+                })
+            })
+            .collect();
+
+        let any_invalid = validated.iter().any(Result::is_err);
+        let rows = validated
+            .into_iter()
+            .map(|row| match row {
+                Err(reason) => ImportRowStatus::Invalid { reason },
+                Ok(_) if any_invalid && mode == ImportMode::AllOrNothing => {
+                    ImportRowStatus::NotImported
+                }
+                Ok((account_id, public_key, initial_balance)) => {
+                    if initial_balance > 0 {
+                        if let Err(e) = record(&account_id, initial_balance) {
+                            return ImportRowStatus::Invalid {
+                                reason: format!("Opening balance not recorded: {}", e),
+                            };
+                        }
+                    }
+                    let mut account = new_account(&account_id, public_key);
+                    account.balance = initial_balance;
+                    shards[shard_index(&account_id)].insert(account_id.clone(), account);
+                    ImportRowStatus::Created { account_id }
+                }
+            })
+            .collect();
+
+        let result = ImportResult { mode, rows };
+        tracing::info!(
+            "Imported {} of {} accounts ({:?})",
+            result.created().len(),
+            result.rows.len(),
+            mode
+        );
+        result
     }
 
-    /// Debit account with amount
-    pub fn debit_account(&mut self, account_id: &str, amount: u64) -> Result<(), AstorError> {
-        let account = self.get_account_mut(account_id)?;
+    /// Check one import row, resolving its id and decoding its key
+    fn validate_spec(
+        spec: AccountSpec,
+        seen_ids: &mut HashSet<String>,
+        account_exists: impl Fn(&str) -> bool,
+    ) -> Result<(String, Option<PublicKey>, u64), String> {
+        let account_id = match spec.account_id {
+            Some(id) => {
+                if id.is_empty() || id.len() > MAX_ACCOUNT_ID_LEN {
+                    return Err(format!(
+                        "Account id must be 1 to {} characters",
+                        MAX_ACCOUNT_ID_LEN
+                    ));
+                }
+                if !id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    return Err(format!("Account id '{}' contains invalid characters", id));
+                }
+                id
+            }
+            None => Uuid::new_v4().to_string(),
+        };
 
-        if account.is_frozen {
-            return Err(AstorError::Unauthorized("Account is frozen".to_string()));
+        if account_exists(&account_id) {
+            return Err(format!("Account '{}' already exists", account_id));
+        }
+        if !seen_ids.insert(account_id.clone()) {
+            return Err(format!("Duplicate account id '{}' in batch", account_id));
         }
 
-        if account.balance < amount {
-            return Err(AstorError::InsufficientFunds);
+        let public_key = spec
+            .public_key
+            .map(|encoded| {
+                let bytes = general_purpose::STANDARD
+                    .decode(encoded)
+                    .map_err(|_| "Public key must be base64 encoded".to_string())?;
+                parse_public_key(&bytes).map_err(|e| e.to_string())
+            })
+            .transpose()?;
+
+        // Balances are persisted as signed 64-bit integers
+        if spec.initial_balance > i64::MAX as u64 {
+            return Err(format!(
+                "Initial balance {} exceeds the maximum account balance",
+                spec.initial_balance
+            ));
         }
 
-        account.balance -= amount;
-        account.last_transaction = Some(Utc::now());
+        Ok((account_id, public_key, spec.initial_balance))
+    }
 
-        Ok(())
+    /// Check whether an account with this ID exists
+    pub fn account_exists(&self, account_id: &str) -> bool {
+        self.read_shard(shard_index(account_id))
+            .contains_key(account_id)
     }
 
-    /// Check if account has sufficient balance
-    pub fn has_sufficient_balance(
+    /// Make sure an account exists before funds are sent to it
+    ///
+    /// Unknown accounts are rejected unless `create_if_missing` is set, in
+    /// which case an empty account is opened under the given ID.
+    pub fn ensure_account(
         &self,
         account_id: &str,
+        create_if_missing: bool,
+    ) -> Result<(), AstorError> {
+        let mut shard = self.write_shard(shard_index(account_id));
+        Self::ensure_in_shard(&mut shard, account_id, create_if_missing)
+    }
+
+    fn ensure_in_shard(
+        shard: &mut Shard,
+        account_id: &str,
+        create_if_missing: bool,
+    ) -> Result<(), AstorError> {
+        if shard.contains_key(account_id) {
+            return Ok(());
+        }
+
+        if !create_if_missing {
+            return Err(AstorError::AccountNotFound(account_id.to_string()));
+        }
+
+        shard.insert(account_id.to_string(), new_account(account_id, None));
+        tracing::info!("Created account {} on first transfer", account_id);
+        Ok(())
+    }
+
+    /// Move funds between two accounts
+    ///
+    /// The source must already exist. A missing destination is an error
+    /// unless `create_if_missing` is set. Both legs are checked before either
+    /// balance changes, and both accounts stay locked until both legs are
+    /// applied.
+    pub fn transfer(
+        &self,
+        from_account: &str,
+        to_account: &str,
         amount: u64,
-    ) -> Result<bool, AstorError> {
-        let account = self.get_account(account_id)?;
-        Ok(account.balance >= amount)
+        create_if_missing: bool,
+    ) -> Result<(), AstorError> {
+        self.transfer_checked(
+            from_account,
+            to_account,
+            amount,
+            create_if_missing,
+            None,
+            None,
+        )
     }
 
-    /// Verify transfer authorization (signature check)
+    /// Move funds on the source holder's signed authorization
+    ///
+    /// The holder signs `transfer_message(from, to, amount, nonce, tx_id)`
+    /// with the key registered on `from`, where `nonce` is the account's
+    /// current `transfer_nonce`. Fails with `AccountNotFound` for an unknown
+    /// account, `InsufficientFunds` (or `InsufficientAvailableBalance` when
+    /// holds are the cause) for a short balance, `InvalidSignature` for a
+    /// signature that does not verify, and `NonceMismatch` for an
+    /// authorization that was already used. The debit and credit are
+    /// validated before either is applied, so a failure never leaves one
+    /// without the other.
+    pub fn transfer_signed(
+        &self,
+        from_account: &str,
+        to_account: &str,
+        amount: u64,
+        nonce: u64,
+        tx_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.verify_transfer_authorization(
+            from_account,
+            to_account,
+            amount,
+            nonce,
+            tx_id,
+            signature,
+        )?;
+        self.transfer_checked(from_account, to_account, amount, false, None, Some(nonce))
+    }
+
+    /// Check a holder's signed transfer authorization without moving funds
+    ///
+    /// Fails as `transfer_signed` does for an unknown account, a signature
+    /// that does not verify, or a nonce that was already used.
     pub fn verify_transfer_authorization(
         &self,
-        account_id: &str,
+        from_account: &str,
+        to_account: &str,
+        amount: u64,
+        nonce: u64,
+        tx_id: &str,
         signature: &Signature,
     ) -> Result<(), AstorError> {
-        let account = self.get_account(account_id)?;
+        let public_key = self.with_account(from_account, |account| {
+            account.ensure_transfer_nonce(Some(nonce))?;
+            account.public_key.ok_or_else(|| {
+                AstorError::Unauthorized("Account has no public key for verification".to_string())
+            })
+        })?;
+        signature.verify(
+            &public_key,
+            transfer_message(from_account, to_account, amount, nonce, tx_id).as_bytes(),
+        )
+    }
 
-        if let Some(public_key) = &account.public_key {
-            let message = format!("transfer_from_{}", account_id);
-            signature.verify(public_key, message.as_bytes())?;
-        } else {
-            return Err(AstorError::Unauthorized(
-                "Account has no public key for verification".to_string(),
+    /// Nonce the next signed transfer from `account_id` must carry
+    pub fn transfer_nonce(&self, account_id: &str) -> Result<u64, AstorError> {
+        self.with_account(account_id, |account| Ok(account.transfer_nonce))
+    }
+
+    /// Move funds only if the source is still at `expected_version`
+    ///
+    /// For callers that check the balance before transferring: another
+    /// change to the source since it was read fails with
+    /// `ConcurrentModification` and the caller should re-read and retry.
+    pub fn transfer_at_version(
+        &self,
+        from_account: &str,
+        to_account: &str,
+        amount: u64,
+        expected_version: u64,
+        create_if_missing: bool,
+    ) -> Result<(), AstorError> {
+        self.transfer_checked(
+            from_account,
+            to_account,
+            amount,
+            create_if_missing,
+            Some(expected_version),
+            None,
+        )
+    }
+
+    /// Current version of an account; see `Account::version`
+    pub fn account_version(&self, account_id: &str) -> Result<u64, AstorError> {
+        self.with_account(account_id, |account| Ok(account.version))
+    }
+
+    fn transfer_checked(
+        &self,
+        from_account: &str,
+        to_account: &str,
+        amount: u64,
+        create_if_missing: bool,
+        expected_version: Option<u64>,
+        expected_nonce: Option<u64>,
+    ) -> Result<(), AstorError> {
+        let (from_index, to_index) = (shard_index(from_account), shard_index(to_account));
+        let mut shards = self.lock_pair(from_index, to_index);
+
+        if !shards.shard(from_index).contains_key(from_account) {
+            return Err(AstorError::AccountNotFound(from_account.to_string()));
+        }
+        if from_account == to_account {
+            return Err(AstorError::TransactionValidationFailed(
+                "Cannot transfer to the same account".to_string(),
             ));
         }
+        Self::ensure_in_shard(shards.shard(to_index), to_account, create_if_missing)?;
+
+        let source = Self::account_in(shards.shard(from_index), from_account)?;
+        source.ensure_version(expected_version)?;
+        source.ensure_transfer_nonce(expected_nonce)?;
+        source.ensure_active()?;
+        source.ensure_not_dormant()?;
+        if let Some(validator) = &self.limit_validator {
+            validator.validate_account_transaction_limits(
+                amount,
+                source.account_type,
+                source.risk_rating,
+            )?;
+        }
+        source.ensure_available(amount)?;
+
+        let destination = Self::account_in(shards.shard(to_index), to_account)?;
+        destination.ensure_active()?;
+        let credited = destination.balance.checked_add(amount).ok_or_else(|| {
+            AstorError::TransactionValidationFailed("Balance overflow".to_string())
+        })?;
+
+        let now = Utc::now();
+        let source = Self::account_in(shards.shard(from_index), from_account)?;
+        let previous_balance = source.balance;
+        source.balance -= amount;
+        source.last_transaction = Some(now);
+        source.version += 1;
+        if expected_nonce.is_some() {
+            source.transfer_nonce += 1;
+        }
+        let mut notifications = source.triggered_alerts(previous_balance);
+
+        let destination = Self::account_in(shards.shard(to_index), to_account)?;
+        let previous_balance = destination.balance;
+        destination.balance = credited;
+        destination.last_transaction = Some(now);
+        destination.version += 1;
+        notifications.extend(destination.triggered_alerts(previous_balance));
 
+        drop(shards);
+        self.send_balance_alerts(notifications);
         Ok(())
     }
 
-    /// Freeze/unfreeze account
-    pub fn set_account_frozen(&mut self, account_id: &str, frozen: bool) -> Result<(), AstorError> {
-        let account = self.get_account_mut(account_id)?;
-        account.is_frozen = frozen;
+    /// Move the funds reserved by a hold to another account, consuming the hold
+    ///
+    /// The hold is only removed if the transfer goes through. Returns the
+    /// amount moved. Like `transfer`, this only moves balances; the caller
+    /// records the transfer in the ledger.
+    pub fn transfer_held(
+        &self,
+        from_account: &str,
+        hold_id: &str,
+        to_account: &str,
+    ) -> Result<u64, AstorError> {
+        let (from_index, to_index) = (shard_index(from_account), shard_index(to_account));
+        let mut shards = self.lock_pair(from_index, to_index);
+
+        if from_account == to_account {
+            return Err(AstorError::TransactionValidationFailed(
+                "Cannot transfer to the same account".to_string(),
+            ));
+        }
+        Self::ensure_in_shard(shards.shard(to_index), to_account, false)?;
+
+        let source = Self::account_in(shards.shard(from_index), from_account)?;
+        source.ensure_active()?;
+        source.ensure_not_dormant()?;
+        let hold_index = source
+            .holds
+            .iter()
+            .position(|hold| hold.hold_id == hold_id)
+            .ok_or_else(|| {
+                AstorError::InvalidOperation(format!(
+                    "Hold {} not found on account {}",
+                    hold_id, from_account
+                ))
+            })?;
+        let amount = source.holds[hold_index].amount;
+        let debited = source
+            .balance
+            .checked_sub(amount)
+            .ok_or(AstorError::InsufficientFunds)?;
+
+        let destination = Self::account_in(shards.shard(to_index), to_account)?;
+        destination.ensure_active()?;
+        let credited = destination.balance.checked_add(amount).ok_or_else(|| {
+            AstorError::TransactionValidationFailed("Balance overflow".to_string())
+        })?;
+
+        let now = Utc::now();
+        let source = Self::account_in(shards.shard(from_index), from_account)?;
+        let previous_balance = source.balance;
+        source.holds.remove(hold_index);
+        source.balance = debited;
+        source.last_transaction = Some(now);
+        source.version += 1;
+        let mut notifications = source.triggered_alerts(previous_balance);
+
+        let destination = Self::account_in(shards.shard(to_index), to_account)?;
+        let previous_balance = destination.balance;
+        destination.balance = credited;
+        destination.last_transaction = Some(now);
+        destination.version += 1;
+        notifications.extend(destination.triggered_alerts(previous_balance));
+
+        drop(shards);
+        self.send_balance_alerts(notifications);
+        Ok(amount)
+    }
+
+    /// Move funds between accounts, converting them in flight
+    ///
+    /// The sender is debited `amount` in `from_currency` and the recipient
+    /// credited the converted amount, net of fees, in `to_currency`. The
+    /// conversion is priced before either account is touched, and both legs
+    /// are applied under the same locks.
+    pub fn transfer_with_conversion(
+        &self,
+        from_account: &str,
+        to_account: &str,
+        from_currency: &str,
+        to_currency: &str,
+        amount: u64,
+        conversion: &ConversionService,
+    ) -> Result<ConversionResult, AstorError> {
+        let from = from_currency.to_uppercase();
+        let to = to_currency.to_uppercase();
+        if from == to {
+            return Err(AstorError::TransactionValidationFailed(
+                "Same-currency transfers need no conversion".to_string(),
+            ));
+        }
+        if from_account == to_account {
+            return Err(AstorError::TransactionValidationFailed(
+                "Cannot transfer to the same account".to_string(),
+            ));
+        }
+
+        let result = conversion.quote_conversion(amount, &from, &to)?;
+        if result.converted_amount == 0 {
+            return Err(AstorError::TransactionValidationFailed(
+                "Conversion amount too small to cover fees".to_string(),
+            ));
+        }
+
+        let (from_index, to_index) = (shard_index(from_account), shard_index(to_account));
+        let mut shards = self.lock_pair(from_index, to_index);
+
+        let source = Self::account_in(shards.shard(from_index), from_account)?;
+        source.ensure_active()?;
+        source.ensure_not_dormant()?;
+        // Holds only reserve default-currency funds
+        if from == DEFAULT_CURRENCY {
+            source.ensure_available(amount)?;
+        } else if source.currency_balance(&from) < amount {
+            return Err(AstorError::InsufficientFunds);
+        }
+        let debited = source.currency_balance(&from) - amount;
+
+        let destination = Self::account_in(shards.shard(to_index), to_account)?;
+        destination.ensure_active()?;
+        let credited = destination
+            .currency_balance(&to)
+            .checked_add(result.converted_amount)
+            .ok_or_else(|| {
+                AstorError::TransactionValidationFailed("Balance overflow".to_string())
+            })?;
+
+        let now = Utc::now();
+        let source = Self::account_in(shards.shard(from_index), from_account)?;
+        let previous_balance = source.balance;
+        source.set_currency_balance(&from, debited);
+        source.last_transaction = Some(now);
+        let mut notifications = source.triggered_alerts(previous_balance);
+
+        let destination = Self::account_in(shards.shard(to_index), to_account)?;
+        let previous_balance = destination.balance;
+        destination.set_currency_balance(&to, credited);
+        destination.last_transaction = Some(now);
+        notifications.extend(destination.triggered_alerts(previous_balance));
+
+        drop(shards);
+        self.send_balance_alerts(notifications);
+
+        tracing::info!(
+            "Transferred {} {} from {} as {} {} to {} (rate {}, fees {})",
+            amount,
+            from,
+            from_account,
+            result.converted_amount,
+            to,
+            to_account,
+            result.exchange_rate,
+            result.fees.total
+        );
+        Ok(result)
+    }
+
+    /// Undo a `transfer_with_conversion` that could not be recorded
+    ///
+    /// Restores both balances exactly, rather than converting back at a new
+    /// rate. Fails if the recipient no longer holds the converted amount.
+    pub fn reverse_transfer_with_conversion(
+        &self,
+        from_account: &str,
+        to_account: &str,
+        from_currency: &str,
+        to_currency: &str,
+        result: &ConversionResult,
+    ) -> Result<(), AstorError> {
+        let from = from_currency.to_uppercase();
+        let to = to_currency.to_uppercase();
+        let (from_index, to_index) = (shard_index(from_account), shard_index(to_account));
+        let mut shards = self.lock_pair(from_index, to_index);
+
+        let destination = Self::account_in(shards.shard(to_index), to_account)?;
+        let reclaimed = destination
+            .currency_balance(&to)
+            .checked_sub(result.converted_amount)
+            .ok_or(AstorError::InsufficientFunds)?;
+        let source = Self::account_in(shards.shard(from_index), from_account)?;
+        let refunded = source
+            .currency_balance(&from)
+            .checked_add(result.original_amount)
+            .ok_or_else(|| {
+                AstorError::TransactionValidationFailed("Balance overflow".to_string())
+            })?;
+
+        source.set_currency_balance(&from, refunded);
+        Self::account_in(shards.shard(to_index), to_account)?.set_currency_balance(&to, reclaimed);
         Ok(())
     }
 
-    /// Get account balance
-    pub fn get_balance(&self, account_id: &str) -> Result<u64, AstorError> {
-        let account = self.get_account(account_id)?;
-        Ok(account.balance)
+    fn account_in<'a>(
+        shard: &'a mut Shard,
+        account_id: &str,
+    ) -> Result<&'a mut Account, AstorError> {
+        shard
+            .get_mut(account_id)
+            .ok_or_else(|| AstorError::AccountNotFound(account_id.to_string()))
+    }
+
+    /// Get a snapshot of an account by ID
+    pub fn get_account(&self, account_id: &str) -> Result<Account, AstorError> {
+        self.with_account(account_id, |account| Ok(account.clone()))
+    }
+
+    /// Credit account with amount
+    pub fn credit_account(&self, account_id: &str, amount: u64) -> Result<(), AstorError> {
+        let notifications = self.with_account_mut(account_id, |account| {
+            account.ensure_active()?;
+
+            let previous_balance = account.balance;
+            account.balance = account.balance.checked_add(amount).ok_or_else(|| {
+                AstorError::TransactionValidationFailed("Balance overflow".to_string())
+            })?;
+            account.last_transaction = Some(Utc::now());
+            account.version += 1;
+
+            Ok(account.triggered_alerts(previous_balance))
+        })?;
+
+        self.send_balance_alerts(notifications);
+        Ok(())
+    }
+
+    /// Debit account with amount
+    pub fn debit_account(&self, account_id: &str, amount: u64) -> Result<(), AstorError> {
+        let notifications = self.with_account_mut(account_id, |account| {
+            account.ensure_active()?;
+
+            account.ensure_not_dormant()?;
+            account.ensure_available(amount)?;
+
+            let previous_balance = account.balance;
+            account.balance -= amount;
+            account.last_transaction = Some(Utc::now());
+            account.version += 1;
+
+            Ok(account.triggered_alerts(previous_balance))
+        })?;
+
+        self.send_balance_alerts(notifications);
+        Ok(())
+    }
+
+    /// Check if account has sufficient available balance
+    pub fn has_sufficient_balance(
+        &self,
+        account_id: &str,
+        amount: u64,
+    ) -> Result<bool, AstorError> {
+        self.with_account(account_id, |account| {
+            Ok(account.available_balance() >= amount)
+        })
+    }
+
+    /// Get the balance not reserved by holds
+    pub fn get_available_balance(&self, account_id: &str) -> Result<u64, AstorError> {
+        self.with_account(account_id, |account| Ok(account.available_balance()))
+    }
+
+    /// Reserve funds for a pending debit, returning the hold ID
+    pub fn place_hold(
+        &self,
+        account_id: &str,
+        amount: u64,
+        reason: &str,
+    ) -> Result<String, AstorError> {
+        self.with_account_mut(account_id, |account| {
+            account.ensure_active()?;
+            account.ensure_available(amount)?;
+
+            let hold_id = Uuid::new_v4().to_string();
+            account.holds.push(BalanceHold {
+                hold_id: hold_id.clone(),
+                amount,
+                reason: reason.to_string(),
+                placed_at: Utc::now(),
+            });
+            account.version += 1;
+            Ok(hold_id)
+        })
+    }
+
+    /// Release a hold, returning its funds to the available balance
+    pub fn release_hold(&self, account_id: &str, hold_id: &str) -> Result<u64, AstorError> {
+        self.with_account_mut(account_id, |account| {
+            let index = account
+                .holds
+                .iter()
+                .position(|hold| hold.hold_id == hold_id)
+                .ok_or_else(|| {
+                    AstorError::InvalidOperation(format!(
+                        "Hold {} not found on account {}",
+                        hold_id, account_id
+                    ))
+                })?;
+            account.version += 1;
+            Ok(account.holds.remove(index).amount)
+        })
+    }
+
+    /// Verify transfer authorization (signature check)
+    pub fn verify_transfer_authorization(
+        &self,
+        account_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.with_account(account_id, |account| {
+            if let Some(public_key) = &account.public_key {
+                let message = format!("transfer_from_{}", account_id);
+                signature.verify(public_key, message.as_bytes())?;
+            } else {
+                return Err(AstorError::Unauthorized(
+                    "Account has no public key for verification".to_string(),
+                ));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Verify a recipient's signed acknowledgment of a transfer
+    pub fn verify_receipt_acknowledgment(
+        &self,
+        account_id: &str,
+        tx_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.with_account(account_id, |account| {
+            let public_key = account.public_key.as_ref().ok_or_else(|| {
+                AstorError::Unauthorized("Account has no public key for verification".to_string())
+            })?;
+            signature.verify(public_key, receipt_acknowledgment_message(tx_id).as_bytes())
+        })
+    }
+
+    /// Set the account type and risk rating that select its transaction limits
+    pub fn set_account_profile(
+        &self,
+        account_id: &str,
+        account_type: AccountType,
+        risk_rating: RiskRating,
+    ) -> Result<(), AstorError> {
+        self.with_account_mut(account_id, |account| {
+            account.account_type = account_type;
+            account.risk_rating = risk_rating;
+            Ok(())
+        })
+    }
+
+    /// Notify the holder whenever a balance change triggers `rule`
+    ///
+    /// Rules are evaluated after every change to the account's balance.
+    pub fn set_balance_alert(
+        &self,
+        account_id: &str,
+        rule: BalanceAlertRule,
+    ) -> Result<(), AstorError> {
+        self.with_account_mut(account_id, |account| {
+            if !account.balance_alerts.contains(&rule) {
+                account.balance_alerts.push(rule);
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove every balance alert from an account
+    pub fn clear_balance_alerts(&self, account_id: &str) -> Result<(), AstorError> {
+        self.with_account_mut(account_id, |account| {
+            account.balance_alerts.clear();
+            Ok(())
+        })
+    }
+
+    /// Mark accounts with no activity since `inactive_since` as dormant
+    ///
+    /// Dormant accounts can still receive funds but cannot be debited until
+    /// the holder re-verifies with `reactivate_account`. Returns the IDs of
+    /// accounts newly marked dormant.
+    pub fn mark_dormant_accounts(&self, inactive_since: DateTime<Utc>) -> Vec<String> {
+        let now = Utc::now();
+        let mut marked = Vec::new();
+
+        for index in 0..ACCOUNT_SHARDS {
+            for account in self.write_shard(index).values_mut() {
+                if !account.is_dormant() && account.last_activity() < inactive_since {
+                    account.dormant_since = Some(now);
+                    marked.push(account.id.clone());
+                }
+            }
+        }
+
+        if !marked.is_empty() {
+            tracing::info!(
+                "Marked {} accounts dormant (inactive since {})",
+                marked.len(),
+                inactive_since
+            );
+        }
+        marked
+    }
+
+    /// Reactivate a dormant account once the holder has re-verified
+    ///
+    /// The holder proves control of the account by signing
+    /// `reactivation_message` with the account key.
+    pub fn reactivate_account(
+        &self,
+        account_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.with_account_mut(account_id, |account| {
+            let Some(dormant_since) = account.dormant_since else {
+                return Err(AstorError::InvalidOperation(format!(
+                    "Account {} is not dormant",
+                    account_id
+                )));
+            };
+            let public_key = account.public_key.as_ref().ok_or_else(|| {
+                AstorError::Unauthorized("Account has no public key for verification".to_string())
+            })?;
+            signature.verify(
+                public_key,
+                reactivation_message(account_id, dormant_since).as_bytes(),
+            )?;
+
+            account.dormant_since = None;
+            account.last_transaction = Some(Utc::now());
+            Ok(())
+        })?;
+
+        tracing::info!("Account {} reactivated after re-verification", account_id);
+        Ok(())
+    }
+
+    /// Freeze account pending review on an administrator's signed authority
+    ///
+    /// The administrator must hold `FreezeAccounts` and sign
+    /// `account_status_message("freeze", ..)` for the account's current
+    /// version.
+    pub fn freeze_account(
+        &self,
+        account_id: &str,
+        admins: &AdminManager,
+        admin_id: &str,
+        reason: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.set_frozen(account_id, admin_id, reason, |version| {
+            authorize_status_change(
+                admins,
+                admin_id,
+                &Permission::FreezeAccounts,
+                "freeze",
+                account_id,
+                version,
+                signature,
+            )
+        })
+    }
+
+    /// Freeze account on behalf of an automated check such as fraud
+    /// screening, which names itself as `actor`
+    pub(crate) fn freeze_for_review(
+        &self,
+        account_id: &str,
+        actor: &str,
+        reason: &str,
+    ) -> Result<(), AstorError> {
+        self.set_frozen(account_id, actor, reason, |_| Ok(()))
+    }
+
+    fn set_frozen(
+        &self,
+        account_id: &str,
+        actor: &str,
+        reason: &str,
+        authorize: impl FnOnce(u64) -> Result<(), AstorError>,
+    ) -> Result<(), AstorError> {
+        self.with_account_mut(account_id, |account| {
+            authorize(account.version)?;
+            if account.status == AccountStatus::Closed {
+                return Err(AstorError::AccountClosed(account_id.to_string()));
+            }
+            account.status = AccountStatus::Frozen;
+            account.freeze_reason = Some(reason.to_string());
+            account.status_changed_by = Some(actor.to_string());
+            account.version += 1;
+            Ok(())
+        })?;
+
+        tracing::warn!("Account {} frozen by {}: {}", account_id, actor, reason);
+        Ok(())
+    }
+
+    /// Lift a freeze after manual review, on an administrator's signed
+    /// authority as for `freeze_account`
+    pub fn unfreeze_account(
+        &self,
+        account_id: &str,
+        admins: &AdminManager,
+        admin_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        let reason = self.with_account_mut(account_id, |account| {
+            authorize_status_change(
+                admins,
+                admin_id,
+                &Permission::FreezeAccounts,
+                "unfreeze",
+                account_id,
+                account.version,
+                signature,
+            )?;
+            match account.status {
+                AccountStatus::Frozen => {}
+                AccountStatus::Closed => {
+                    return Err(AstorError::AccountClosed(account_id.to_string()))
+                }
+                AccountStatus::Active => {
+                    return Err(AstorError::InvalidOperation(format!(
+                        "Account {} is not frozen",
+                        account_id
+                    )))
+                }
+            }
+            account.status = AccountStatus::Active;
+            account.status_changed_by = Some(admin_id.to_string());
+            account.version += 1;
+            Ok(account.freeze_reason.take())
+        })?;
+
+        tracing::info!(
+            "Account {} unfrozen by {} (was frozen for: {})",
+            account_id,
+            admin_id,
+            reason.as_deref().unwrap_or("unspecified")
+        );
+        Ok(())
+    }
+
+    /// Permanently close an account on the signed authority of an
+    /// administrator holding `ManageAccounts`
+    ///
+    /// Only an account holding nothing in any currency, with no holds, can be
+    /// closed.
+    pub fn close_account(
+        &self,
+        account_id: &str,
+        admins: &AdminManager,
+        admin_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.with_account_mut(account_id, |account| {
+            authorize_status_change(
+                admins,
+                admin_id,
+                &Permission::ManageAccounts,
+                "close",
+                account_id,
+                account.version,
+                signature,
+            )?;
+            if account.status == AccountStatus::Closed {
+                return Err(AstorError::AccountClosed(account_id.to_string()));
+            }
+            let has_funds = account.balance > 0
+                || account.currency_balances.values().any(|amount| *amount > 0)
+                || !account.holds.is_empty();
+            if has_funds {
+                return Err(AstorError::InvalidOperation(format!(
+                    "Account {} must have a zero balance to be closed",
+                    account_id
+                )));
+            }
+            account.status = AccountStatus::Closed;
+            account.status_changed_by = Some(admin_id.to_string());
+            account.version += 1;
+            Ok(())
+        })?;
+
+        tracing::info!("Account {} closed by {}", account_id, admin_id);
+        Ok(())
+    }
+
+    /// Get account balance
+    pub fn get_balance(&self, account_id: &str) -> Result<u64, AstorError> {
+        self.with_account(account_id, |account| Ok(account.balance))
+    }
+
+    /// Balances of several accounts read as one consistent snapshot
+    ///
+    /// Every shard involved is read-locked at once, so the result never
+    /// reflects only one leg of a transfer between the accounts.
+    pub fn get_balances(&self, account_ids: &[&str]) -> Result<Vec<u64>, AstorError> {
+        let mut indices: Vec<usize> = account_ids.iter().map(|id| shard_index(id)).collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let guards: HashMap<usize, RwLockReadGuard<'_, Shard>> = indices
+            .into_iter()
+            .map(|index| (index, self.read_shard(index)))
+            .collect();
+
+        account_ids
+            .iter()
+            .map(|id| {
+                guards[&shard_index(id)]
+                    .get(*id)
+                    .map(|account| account.balance)
+                    .ok_or_else(|| AstorError::AccountNotFound(id.to_string()))
+            })
+            .collect()
+    }
+
+    /// Get account balance in a specific currency
+    pub fn get_currency_balance(
+        &self,
+        account_id: &str,
+        currency: &str,
+    ) -> Result<u64, AstorError> {
+        self.with_account(account_id, |account| {
+            Ok(account.currency_balance(&currency.to_uppercase()))
+        })
+    }
+
+    /// Convert part of an account's balance from one currency to another
+    ///
+    /// The conversion is priced and every check is made before either balance
+    /// changes, so a failure leaves the account exactly as it was.
+    pub fn convert_balance(
+        &self,
+        account_id: &str,
+        from_currency: &str,
+        to_currency: &str,
+        amount: u64,
+        conversion: &ConversionService,
+    ) -> Result<ConversionResult, AstorError> {
+        let from = from_currency.to_uppercase();
+        let to = to_currency.to_uppercase();
+
+        if from == to {
+            return Err(AstorError::TransactionValidationFailed(
+                "Cannot convert a currency into itself".to_string(),
+            ));
+        }
+
+        let result = conversion.quote_conversion(amount, &from, &to)?;
+        if result.converted_amount == 0 {
+            return Err(AstorError::TransactionValidationFailed(
+                "Conversion amount too small to cover fees".to_string(),
+            ));
+        }
+
+        let notifications = self.with_account_mut(account_id, |account| {
+            account.ensure_active()?;
+
+            account.ensure_not_dormant()?;
+
+            let remaining = account
+                .currency_balance(&from)
+                .checked_sub(amount)
+                .ok_or(AstorError::InsufficientFunds)?;
+            let credited = account
+                .currency_balance(&to)
+                .checked_add(result.converted_amount)
+                .ok_or_else(|| {
+                    AstorError::TransactionValidationFailed("Balance overflow".to_string())
+                })?;
+
+            // Both legs are applied together only once nothing can fail
+            let previous_balance = account.balance;
+            account.set_currency_balance(&from, remaining);
+            account.set_currency_balance(&to, credited);
+            account.last_transaction = Some(Utc::now());
+            Ok(account.triggered_alerts(previous_balance))
+        })?;
+        self.send_balance_alerts(notifications);
+
+        tracing::info!(
+            "Account {} converted {} {} to {} {} (fees: {})",
+            account_id,
+            amount,
+            from,
+            result.converted_amount,
+            to,
+            result.fees.total
+        );
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::ExchangeRate;
+    use crate::ledger::{Ledger, LedgerEntryType};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn conversion_service(quoted_at: DateTime<Utc>) -> ConversionService {
+        let mut service = ConversionService::new();
+        service.update_exchange_rate(ExchangeRate {
+            from_currency: "ASTOR".to_string(),
+            to_currency: "USD".to_string(),
+            rate: 2.0,
+            bid: 2.0,
+            ask: 2.0,
+            timestamp: quoted_at,
+            source: "test".to_string(),
+            volatility: 0.0,
+            daily_change: 0.0,
+        });
+        service
+    }
+
+    fn funded_account(manager: &AccountManager, amount: u64) -> String {
+        let account_id = manager.create_account(None);
+        manager.credit_account(&account_id, amount).unwrap();
+        account_id
+    }
+
+    #[test]
+    fn test_convert_balance_moves_funds_between_currencies() {
+        let manager = AccountManager::new();
+        let account_id = funded_account(&manager, 10_000);
+        let service = conversion_service(Utc::now());
+
+        let result = manager
+            .convert_balance(&account_id, "astor", "usd", 1_000, &service)
+            .unwrap();
+
+        assert_eq!(manager.get_balance(&account_id).unwrap(), 9_000);
+        assert_eq!(
+            manager.get_currency_balance(&account_id, "USD").unwrap(),
+            result.converted_amount
+        );
+        assert_eq!(result.converted_amount, 2_000 - result.fees.total);
+        assert!(result.fees.total > 0);
+    }
+
+    #[test]
+    fn test_convert_balance_is_atomic_on_failure() {
+        let manager = AccountManager::new();
+        let account_id = funded_account(&manager, 10_000);
+        let service = conversion_service(Utc::now());
+
+        // The destination credit overflows after the source debit is checked
+        manager
+            .write_shard(shard_index(&account_id))
+            .get_mut(&account_id)
+            .unwrap()
+            .currency_balances
+            .insert("USD".to_string(), u64::MAX - 10);
+
+        assert!(manager
+            .convert_balance(&account_id, "ASTOR", "USD", 1_000, &service)
+            .is_err());
+        assert_eq!(manager.get_balance(&account_id).unwrap(), 10_000);
+        assert_eq!(
+            manager.get_currency_balance(&account_id, "USD").unwrap(),
+            u64::MAX - 10
+        );
+    }
+
+    #[test]
+    fn test_convert_balance_rejects_stale_rate_and_insufficient_funds() {
+        let manager = AccountManager::new();
+        let account_id = funded_account(&manager, 500);
+
+        let stale = conversion_service(Utc::now() - chrono::Duration::hours(1));
+        assert!(manager
+            .convert_balance(&account_id, "ASTOR", "USD", 100, &stale)
+            .is_err());
+
+        let fresh = conversion_service(Utc::now());
+        assert!(matches!(
+            manager.convert_balance(&account_id, "ASTOR", "USD", 1_000, &fresh),
+            Err(AstorError::InsufficientFunds)
+        ));
+
+        assert_eq!(manager.get_balance(&account_id).unwrap(), 500);
+        assert_eq!(manager.get_currency_balance(&account_id, "USD").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_transfer_from_unknown_account_fails() {
+        let manager = AccountManager::new();
+        let to_account = funded_account(&manager, 0);
+
+        let result = manager.transfer("missing-source", &to_account, 10, true);
+
+        assert!(matches!(result, Err(AstorError::AccountNotFound(id)) if id == "missing-source"));
+        assert!(!manager.account_exists("missing-source"));
+    }
+
+    #[test]
+    fn test_transfer_to_unknown_account_fails_without_opt_in() {
+        let manager = AccountManager::new();
+        let from_account = funded_account(&manager, 100);
+
+        let result = manager.transfer(&from_account, "typo-account", 10, false);
+
+        assert!(matches!(result, Err(AstorError::AccountNotFound(id)) if id == "typo-account"));
+        assert!(!manager.account_exists("typo-account"));
+        assert_eq!(manager.get_balance(&from_account).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_transfer_creates_missing_recipient_on_opt_in() {
+        let manager = AccountManager::new();
+        let from_account = funded_account(&manager, 100);
+
+        manager
+            .transfer(&from_account, "new-account", 40, true)
+            .unwrap();
+
+        assert!(manager.account_exists("new-account"));
+        assert_eq!(manager.get_balance("new-account").unwrap(), 40);
+        assert_eq!(manager.get_balance(&from_account).unwrap(), 60);
+    }
+
+    #[test]
+    fn test_hold_reduces_available_balance_for_transfer() {
+        let manager = AccountManager::new();
+        let from_account = funded_account(&manager, 100);
+        let to_account = funded_account(&manager, 0);
+
+        let hold_id = manager
+            .place_hold(&from_account, 70, "card authorization")
+            .unwrap();
+        assert_eq!(manager.get_balance(&from_account).unwrap(), 100);
+        assert_eq!(manager.get_available_balance(&from_account).unwrap(), 30);
+
+        let result = manager.transfer(&from_account, &to_account, 50, false);
+        assert!(matches!(
+            result,
+            Err(AstorError::InsufficientAvailableBalance {
+                requested: 50,
+                available: 30,
+                ledger_balance: 100,
+            })
+        ));
+        assert_eq!(manager.get_balance(&to_account).unwrap(), 0);
+
+        assert_eq!(manager.release_hold(&from_account, &hold_id).unwrap(), 70);
+        manager
+            .transfer(&from_account, &to_account, 50, false)
+            .unwrap();
+        assert_eq!(manager.get_available_balance(&from_account).unwrap(), 50);
+    }
+
+    fn import_specs(existing_id: &str) -> Vec<AccountSpec> {
+        let public_key = general_purpose::STANDARD
+            .encode(crate::security::KeyPair::generate().public_key().as_bytes());
+        let spec = |id: &str, public_key: Option<String>, initial_balance: u64| AccountSpec {
+            account_id: Some(id.to_string()),
+            public_key,
+            initial_balance,
+        };
+
+        vec![
+            spec("acct-1", Some(public_key), 500),
+            spec("acct-2", None, 0),
+            spec("acct-1", None, 10),
+            spec(existing_id, None, 10),
+            spec("acct-3", Some("not base64!".to_string()), 10),
+            spec("acct-4", None, u64::MAX),
+        ]
+    }
+
+    #[test]
+    fn test_all_or_nothing_import_creates_nothing_on_invalid_row() {
+        let manager = AccountManager::new();
+        let existing = funded_account(&manager, 0);
+
+        let result =
+            manager.import_accounts(import_specs(&existing), ImportMode::AllOrNothing, |_, _| {
+                panic!("nothing is recorded when nothing is imported")
+            });
+
+        assert!(result.created().is_empty());
+        assert!(!result.is_complete());
+        assert_eq!(result.rows[0], ImportRowStatus::NotImported);
+        assert_eq!(
+            result
+                .failures()
+                .iter()
+                .map(|(row, _)| *row)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4, 5]
+        );
+        assert!(!manager.account_exists("acct-1"));
+        assert!(!manager.account_exists("acct-2"));
+    }
+
+    #[test]
+    fn test_best_effort_import_creates_valid_rows() {
+        let manager = AccountManager::new();
+        let existing = funded_account(&manager, 0);
+
+        let mut recorded = Vec::new();
+        let result = manager.import_accounts(
+            import_specs(&existing),
+            ImportMode::BestEffort,
+            |id, amount| {
+                recorded.push((id.to_string(), amount));
+                Ok(())
+            },
+        );
+
+        assert_eq!(result.created(), vec!["acct-1", "acct-2"]);
+        assert_eq!(recorded, vec![("acct-1".to_string(), 500)]);
+        assert_eq!(result.failures().len(), 4);
+        assert!(result.failures()[0].1.contains("Duplicate"));
+        assert_eq!(manager.get_balance("acct-1").unwrap(), 500);
+        assert!(manager.get_account("acct-1").unwrap().public_key.is_some());
+        assert_eq!(manager.get_balance("acct-2").unwrap(), 0);
+        assert!(!manager.account_exists("acct-3"));
+
+        // A fully valid batch is complete in either mode
+        let result = manager.import_accounts(
+            vec![AccountSpec {
+                account_id: None,
+                public_key: None,
+                initial_balance: 25,
+            }],
+            ImportMode::AllOrNothing,
+            |_, _| Ok(()),
+        );
+        assert!(result.is_complete());
+        assert_eq!(manager.get_balance(result.created()[0]).unwrap(), 25);
+    }
+
+    #[test]
+    fn test_import_row_not_created_when_opening_balance_not_recorded() {
+        let manager = AccountManager::new();
+
+        let result = manager.import_accounts(
+            vec![
+                AccountSpec {
+                    account_id: Some("funded".to_string()),
+                    public_key: None,
+                    initial_balance: 100,
+                },
+                AccountSpec {
+                    account_id: Some("empty".to_string()),
+                    public_key: None,
+                    initial_balance: 0,
+                },
+            ],
+            ImportMode::BestEffort,
+            |_, _| Err(AstorError::LedgerError("disk full".to_string())),
+        );
+
+        assert_eq!(result.created(), vec!["empty"]);
+        assert!(result.failures()[0].1.contains("disk full"));
+        assert!(!manager.account_exists("funded"));
+    }
+
+    #[test]
+    fn test_concurrent_reader_never_sees_half_applied_transfer() {
+        let manager = Arc::new(AccountManager::new());
+        let alice = funded_account(&manager, 1_000);
+        let bob = funded_account(&manager, 1_000);
+        let done = Arc::new(AtomicBool::new(false));
+
+        let writer = {
+            let (manager, done) = (manager.clone(), done.clone());
+            let (alice, bob) = (alice.clone(), bob.clone());
+            std::thread::spawn(move || {
+                for round in 0..5_000 {
+                    let (from, to) = if round % 2 == 0 {
+                        (&alice, &bob)
+                    } else {
+                        (&bob, &alice)
+                    };
+                    manager.transfer(from, to, 700, false).unwrap();
+                }
+                done.store(true, Ordering::SeqCst);
+            })
+        };
+
+        let mut snapshots = 0;
+        while !done.load(Ordering::SeqCst) || snapshots == 0 {
+            let balances = manager.get_balances(&[&alice, &bob]).unwrap();
+            assert_eq!(balances.iter().sum::<u64>(), 2_000, "saw {:?}", balances);
+            snapshots += 1;
+        }
+        writer.join().unwrap();
+
+        assert_eq!(manager.get_balance(&alice).unwrap(), 1_000);
+        assert_eq!(manager.get_balance(&bob).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_cross_currency_transfer_records_fx_entry() {
+        let manager = AccountManager::new();
+        let alice = funded_account(&manager, 10_000);
+        let bob = manager.create_account(None);
+        let service = conversion_service(Utc::now());
+        let mut ledger = Ledger::new();
+        ledger
+            .record_issuance("tx-0".to_string(), "central_bank", &alice, 10_000)
+            .unwrap();
+
+        let result = manager
+            .transfer_with_conversion(&alice, &bob, "astor", "usd", 1_000, &service)
+            .unwrap();
+        ledger
+            .record_cross_currency_transfer(
+                "tx-1".to_string(),
+                &alice,
+                &bob,
+                "astor",
+                "usd",
+                &result,
+            )
+            .unwrap();
+
+        assert_eq!(manager.get_balance(&alice).unwrap(), 9_000);
+        assert_eq!(manager.get_balance(&bob).unwrap(), 0);
+        assert_eq!(
+            manager.get_currency_balance(&bob, "USD").unwrap(),
+            result.converted_amount
+        );
+        assert_eq!(ledger.get_account_balance(&alice), 9_000);
+
+        let linked: Vec<_> = ledger
+            .iter()
+            .filter(|entry| match &entry.entry_type {
+                LedgerEntryType::CrossCurrencyTransfer { transaction_id, .. }
+                | LedgerEntryType::FxConversion { transaction_id, .. } => transaction_id == "tx-1",
+                _ => false,
+            })
+            .collect();
+        assert_eq!(linked.len(), 2);
+
+        match &ledger
+            .fx_conversion_for("tx-1")
+            .unwrap()
+            .unwrap()
+            .entry_type
+        {
+            LedgerEntryType::FxConversion {
+                from_currency,
+                to_currency,
+                source_amount,
+                converted_amount,
+                exchange_rate,
+                fees,
+                ..
+            } => {
+                assert_eq!(
+                    (from_currency.as_str(), to_currency.as_str()),
+                    ("ASTOR", "USD")
+                );
+                assert_eq!(*source_amount, 1_000);
+                assert_eq!(*converted_amount, result.converted_amount);
+                assert!((*exchange_rate - 2.0).abs() < f64::EPSILON);
+                assert_eq!(*fees, result.fees.total);
+            }
+            other => panic!("unexpected entry {:?}", other),
+        }
+
+        // A failed conversion leaves both accounts untouched
+        assert!(manager
+            .transfer_with_conversion(&bob, &alice, "usd", "usd", 10, &service)
+            .is_err());
+        assert!(manager
+            .transfer_with_conversion(&alice, &bob, "astor", "usd", 50_000, &service)
+            .is_err());
+        assert_eq!(manager.get_balance(&alice).unwrap(), 9_000);
+
+        // A transfer that cannot be recorded is reversed at its original rate
+        manager
+            .reverse_transfer_with_conversion(&alice, &bob, "astor", "usd", &result)
+            .unwrap();
+        assert_eq!(manager.get_balance(&alice).unwrap(), 10_000);
+        assert_eq!(manager.get_currency_balance(&bob, "USD").unwrap(), 0);
+        assert!(manager
+            .reverse_transfer_with_conversion(&alice, &bob, "astor", "usd", &result)
+            .is_err());
+        assert_eq!(manager.get_balance(&alice).unwrap(), 10_000);
+    }
+
+    #[test]
+    fn test_rejected_currency_leg_leaves_ledger_untouched() {
+        let service = conversion_service(Utc::now());
+        let mut ledger = Ledger::new();
+        ledger
+            .record_issuance("tx-0".to_string(), "central_bank", "alice", 1_000)
+            .unwrap();
+        ledger
+            .record_issuance("tx-1".to_string(), "central_bank", "bob", 1)
+            .unwrap();
+        let mut result = service.quote_conversion(100, "ASTOR", "USD").unwrap();
+        result.converted_amount = u64::MAX;
+
+        // The debit is valid but the credit overflows, so neither applies
+        assert!(ledger
+            .check_cross_currency_transfer("alice", "bob", "astor", "astor", &result)
+            .is_err());
+        assert!(ledger
+            .record_cross_currency_transfer(
+                "tx-2".to_string(),
+                "alice",
+                "bob",
+                "astor",
+                "astor",
+                &result
+            )
+            .is_err());
+        assert_eq!(ledger.get_account_balance("alice"), 1_000);
+        assert_eq!(ledger.get_account_balance("bob"), 1);
+        assert!(ledger.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_create_account_from_malformed_key_bytes() {
+        let manager = AccountManager::new();
+        let public_key = crate::security::KeyPair::generate().public_key();
+
+        let account_id = manager
+            .create_account_from_bytes(public_key.as_bytes())
+            .unwrap();
+        assert_eq!(
+            manager.get_account(&account_id).unwrap().public_key,
+            Some(public_key)
+        );
+
+        let truncated = &public_key.as_bytes()[..31];
+        match manager.create_account_from_bytes(truncated) {
+            Err(AstorError::CryptographicError(message)) => {
+                assert!(message.contains("32 bytes, got 31"))
+            }
+            other => panic!("expected a cryptographic error, got {:?}", other),
+        }
+        assert!(matches!(
+            manager.create_account_from_bytes(&[]),
+            Err(AstorError::CryptographicError(_))
+        ));
+
+        // Right length, but not a point on the curve
+        let mut invalid = None;
+        for byte in 0..=u8::MAX {
+            let candidate = [byte; PUBLIC_KEY_LENGTH];
+            if PublicKey::from_bytes(&candidate).is_err() {
+                invalid = Some(candidate);
+                break;
+            }
+        }
+        let invalid = invalid.expect("some repeated-byte encoding is not a valid point");
+        assert!(matches!(
+            manager.create_account_from_bytes(&invalid),
+            Err(AstorError::CryptographicError(_))
+        ));
+
+        let account_count: usize = (0..ACCOUNT_SHARDS)
+            .map(|i| manager.read_shard(i).len())
+            .sum();
+        assert_eq!(account_count, 1);
+    }
+
+    #[test]
+    fn test_transaction_limit_depends_on_account_type_and_risk() {
+        let mut manager = AccountManager::new();
+        manager.set_transaction_limits(TransactionLimits::default());
+
+        let merchant = manager.create_account(None);
+        let individual = manager.create_account(None);
+        let recipient = manager.create_account(None);
+        manager
+            .set_account_profile(&merchant, AccountType::Merchant, RiskRating::Low)
+            .unwrap();
+        manager
+            .set_account_profile(&individual, AccountType::Individual, RiskRating::High)
+            .unwrap();
+
+        let amount = 50_000_00;
+        manager.credit_account(&merchant, amount).unwrap();
+        manager.credit_account(&individual, amount).unwrap();
+
+        manager
+            .transfer(&merchant, &recipient, amount, false)
+            .unwrap();
+        assert!(matches!(
+            manager.transfer(&individual, &recipient, amount, false),
+            Err(AstorError::TransactionLimitExceeded {
+                limit: 10_000_00,
+                ..
+            })
+        ));
+        assert_eq!(manager.get_balance(&individual).unwrap(), amount);
+        assert_eq!(manager.get_balance(&recipient).unwrap(), amount);
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: std::sync::Mutex<Vec<BalanceNotification>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, notification: &BalanceNotification) -> Result<(), AstorError> {
+            self.sent.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+
+        fn send_receipt(
+            &self,
+            _channel: &ReceiptChannel,
+            _receipt: &TransactionReceipt,
+        ) -> Result<(), AstorError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_above_threshold_alert_fires_once_when_crossed() {
+        let mut manager = AccountManager::new();
+        let notifier = Arc::new(RecordingNotifier::default());
+        manager.set_notifier(notifier.clone());
+
+        let payer = funded_account(&manager, 10_000);
+        let account_id = funded_account(&manager, 500);
+        manager
+            .set_balance_alert(&account_id, BalanceAlertRule::Above { threshold: 1_000 })
+            .unwrap();
+
+        manager.transfer(&payer, &account_id, 400, false).unwrap();
+        assert!(notifier.sent.lock().unwrap().is_empty());
+
+        manager.transfer(&payer, &account_id, 2_000, false).unwrap();
+        manager.credit_account(&account_id, 300).unwrap();
+        manager.transfer(&payer, &account_id, 50, false).unwrap();
+
+        let sent = notifier.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].account_id, account_id);
+        assert_eq!(sent[0].previous_balance, 900);
+        assert_eq!(sent[0].balance, 2_900);
+    }
+
+    #[test]
+    fn test_inactive_account_is_marked_dormant() {
+        let manager = AccountManager::new();
+        let keypair = crate::security::KeyPair::generate();
+        let idle = manager.create_account(Some(keypair.public_key()));
+        let active = funded_account(&manager, 1_000);
+
+        let threshold = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        manager.credit_account(&active, 10).unwrap();
+
+        assert_eq!(manager.mark_dormant_accounts(threshold), vec![idle.clone()]);
+        assert!(manager.get_account(&idle).unwrap().is_dormant());
+        assert!(!manager.get_account(&active).unwrap().is_dormant());
+        // Already-dormant accounts are not reported again
+        assert!(manager.mark_dormant_accounts(threshold).is_empty());
+
+        // Deposits are accepted, but debits wait for re-verification
+        manager.transfer(&active, &idle, 100, false).unwrap();
+        assert!(matches!(
+            manager.transfer(&idle, &active, 50, false),
+            Err(AstorError::AccountDormant(_))
+        ));
+
+        let dormant_since = manager.get_account(&idle).unwrap().dormant_since.unwrap();
+        let signature = keypair.sign(reactivation_message(&idle, dormant_since).as_bytes());
+        manager.reactivate_account(&idle, &signature).unwrap();
+        manager.transfer(&idle, &active, 50, false).unwrap();
+
+        // The signature lifted that dormancy only, not a later one
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        assert!(manager.mark_dormant_accounts(Utc::now()).contains(&idle));
+        assert!(manager.reactivate_account(&idle, &signature).is_err());
+        assert!(manager.get_account(&idle).unwrap().is_dormant());
+    }
+
+    #[test]
+    fn test_concurrent_transfers_exceeding_balance_only_one_succeeds() {
+        let manager = Arc::new(AccountManager::new());
+        let alice = funded_account(&manager, 1_000);
+        let recipients = [manager.create_account(None), manager.create_account(None)];
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+
+        let handles: Vec<_> = recipients
+            .iter()
+            .cloned()
+            .map(|recipient| {
+                let (manager, barrier, alice) = (manager.clone(), barrier.clone(), alice.clone());
+                std::thread::spawn(move || {
+                    // Both read the balance and version before either transfers
+                    let source = manager.get_account(&alice).unwrap();
+                    assert!(source.available_balance() >= 700);
+                    barrier.wait();
+                    manager.transfer_at_version(&alice, &recipient, 700, source.version, false)
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert!(results
+            .iter()
+            .any(|r| matches!(r, Err(AstorError::ConcurrentModification(_)))));
+        assert_eq!(manager.get_balance(&alice).unwrap(), 300);
+        assert_eq!(
+            manager
+                .get_balances(&[&recipients[0], &recipients[1]])
+                .unwrap()
+                .iter()
+                .sum::<u64>(),
+            700
+        );
+    }
+
+    #[test]
+    fn test_signed_transfer_reports_distinct_errors_and_conserves_funds() {
+        let manager = AccountManager::new();
+        let keypair = crate::security::KeyPair::generate();
+        let alice = manager.create_account(Some(keypair.public_key()));
+        manager.credit_account(&alice, 500).unwrap();
+        let bob = funded_account(&manager, 100);
+        let sign = |to: &str, amount, nonce, tx_id: &str| {
+            keypair.sign(transfer_message(&alice, to, amount, nonce, tx_id).as_bytes())
+        };
+
+        assert!(matches!(
+            manager.transfer_signed(&alice, "unknown", 100, 0, "tx-1", &sign("unknown", 100, 0, "tx-1")),
+            Err(AstorError::AccountNotFound(id)) if id == "unknown"
+        ));
+        assert!(matches!(
+            manager.transfer_signed(&alice, &bob, 600, 0, "tx-1", &sign(&bob, 600, 0, "tx-1")),
+            Err(AstorError::InsufficientFunds)
+        ));
+        // Signed for a different amount, transaction, or by someone else
+        assert!(matches!(
+            manager.transfer_signed(&alice, &bob, 200, 0, "tx-1", &sign(&bob, 100, 0, "tx-1")),
+            Err(AstorError::InvalidSignature)
+        ));
+        assert!(matches!(
+            manager.transfer_signed(&alice, &bob, 200, 0, "tx-2", &sign(&bob, 200, 0, "tx-1")),
+            Err(AstorError::InvalidSignature)
+        ));
+        let intruder = crate::security::KeyPair::generate();
+        assert!(matches!(
+            manager.transfer_signed(
+                &alice,
+                &bob,
+                200,
+                0,
+                "tx-1",
+                &intruder.sign(transfer_message(&alice, &bob, 200, 0, "tx-1").as_bytes())
+            ),
+            Err(AstorError::InvalidSignature)
+        ));
+        assert_eq!(
+            manager.get_balances(&[&alice, &bob]).unwrap(),
+            vec![500, 100]
+        );
+        assert_eq!(manager.transfer_nonce(&alice).unwrap(), 0);
+
+        let signature = sign(&bob, 200, 0, "tx-1");
+        manager
+            .transfer_signed(&alice, &bob, 200, 0, "tx-1", &signature)
+            .unwrap();
+        assert_eq!(
+            manager.get_balances(&[&alice, &bob]).unwrap(),
+            vec![300, 300]
+        );
+        assert_eq!(manager.transfer_nonce(&alice).unwrap(), 1);
+
+        // The same authorization cannot be replayed
+        assert!(matches!(
+            manager.transfer_signed(&alice, &bob, 200, 0, "tx-1", &signature),
+            Err(AstorError::NonceMismatch {
+                expected: 1,
+                received: 0
+            })
+        ));
+        assert_eq!(
+            manager.get_balances(&[&alice, &bob]).unwrap(),
+            vec![300, 300]
+        );
+    }
+
+    /// Administrators "admin-1" (root) and "admin-2" (bank admin) with the
+    /// keys they sign with
+    fn test_admins() -> (
+        AdminManager,
+        crate::security::KeyPair,
+        crate::security::KeyPair,
+    ) {
+        let root = crate::security::KeyPair::generate();
+        let bank_admin = crate::security::KeyPair::generate();
+        let mut admins = AdminManager::new();
+        admins
+            .add_admin("admin-1".to_string(), root.public_key())
+            .unwrap();
+        admins
+            .add_admin("admin-2".to_string(), bank_admin.public_key())
+            .unwrap();
+        (admins, root, bank_admin)
+    }
+
+    fn sign_status_change(
+        manager: &AccountManager,
+        keypair: &crate::security::KeyPair,
+        action: &str,
+        account_id: &str,
+    ) -> Signature {
+        let version = manager.get_account(account_id).unwrap().version;
+        keypair.sign(account_status_message(action, account_id, version).as_bytes())
+    }
+
+    #[test]
+    fn test_status_changes_require_a_signed_admin_authorization() {
+        let manager = AccountManager::new();
+        let alice = funded_account(&manager, 500);
+        let (admins, root, bank_admin) = test_admins();
+
+        // Unknown administrators cannot act, whatever they sign with
+        let signature = sign_status_change(&manager, &root, "freeze", &alice);
+        assert!(matches!(
+            manager.freeze_account(&alice, &admins, "fraud-detection", "spoofed", &signature),
+            Err(AstorError::AdminNotFound(_))
+        ));
+        // A bank administrator lacks FreezeAccounts
+        let signature = sign_status_change(&manager, &bank_admin, "freeze", &alice);
+        assert!(matches!(
+            manager.freeze_account(&alice, &admins, "admin-2", "court order", &signature),
+            Err(AstorError::Unauthorized(_))
+        ));
+        // A signature by another key does not verify
+        assert!(manager
+            .freeze_account(&alice, &admins, "admin-1", "court order", &signature)
+            .is_err());
+        // Nor does one made for another action
+        let signature = sign_status_change(&manager, &root, "unfreeze", &alice);
+        assert!(manager
+            .freeze_account(&alice, &admins, "admin-1", "court order", &signature)
+            .is_err());
+        assert_eq!(
+            manager.get_account(&alice).unwrap().status,
+            AccountStatus::Active
+        );
+
+        // Automated checks freeze without an administrator
+        manager
+            .freeze_for_review(&alice, "fraud-detection", "suspected fraud")
+            .unwrap();
+        let account = manager.get_account(&alice).unwrap();
+        assert_eq!(account.status, AccountStatus::Frozen);
+        assert_eq!(
+            account.status_changed_by.as_deref(),
+            Some("fraud-detection")
+        );
+    }
+
+    #[test]
+    fn test_frozen_account_rejects_balance_changes_until_unfrozen() {
+        let manager = AccountManager::new();
+        let alice = funded_account(&manager, 500);
+        let bob = funded_account(&manager, 100);
+        let (admins, root, _) = test_admins();
+
+        let signature = sign_status_change(&manager, &root, "unfreeze", &alice);
+        assert!(matches!(
+            manager.unfreeze_account(&alice, &admins, "admin-1", &signature),
+            Err(AstorError::InvalidOperation(_))
+        ));
+        let version = manager.get_account(&alice).unwrap().version;
+        let signature = sign_status_change(&manager, &root, "freeze", &alice);
+        manager
+            .freeze_account(&alice, &admins, "admin-1", "court order", &signature)
+            .unwrap();
+        // The signature covered one version, so it cannot be replayed
+        assert!(manager
+            .freeze_account(&alice, &admins, "admin-1", "court order", &signature)
+            .is_err());
+        let account = manager.get_account(&alice).unwrap();
+        assert_eq!(account.status, AccountStatus::Frozen);
+        assert_eq!(account.status_changed_by.as_deref(), Some("admin-1"));
+        assert_eq!(account.version, version + 1);
+
+        for result in [
+            manager.transfer(&alice, &bob, 100, false),
+            manager.transfer(&bob, &alice, 100, false),
+            manager.credit_account(&alice, 100),
+        ] {
+            assert!(matches!(result, Err(AstorError::AccountFrozen(id)) if id == alice));
+        }
+        assert_eq!(
+            manager.get_balances(&[&alice, &bob]).unwrap(),
+            vec![500, 100]
+        );
+
+        let signature = sign_status_change(&manager, &root, "unfreeze", &alice);
+        manager
+            .unfreeze_account(&alice, &admins, "admin-1", &signature)
+            .unwrap();
+        assert_eq!(manager.get_account(&alice).unwrap().version, version + 2);
+        manager.transfer(&alice, &bob, 100, false).unwrap();
+        assert_eq!(
+            manager.get_balances(&[&alice, &bob]).unwrap(),
+            vec![400, 200]
+        );
+    }
+
+    #[test]
+    fn test_account_can_only_be_closed_at_zero_balance() {
+        let manager = AccountManager::new();
+        let alice = funded_account(&manager, 500);
+        let bob = manager.create_account(None);
+        let (admins, root, bank_admin) = test_admins();
+
+        let signature = sign_status_change(&manager, &bank_admin, "close", &alice);
+        assert!(matches!(
+            manager.close_account(&alice, &admins, "admin-2", &signature),
+            Err(AstorError::InvalidOperation(_))
+        ));
+        manager.transfer(&alice, &bob, 500, false).unwrap();
+        let signature = sign_status_change(&manager, &bank_admin, "close", &alice);
+        manager
+            .close_account(&alice, &admins, "admin-2", &signature)
+            .unwrap();
+        assert_eq!(
+            manager.get_account(&alice).unwrap().status,
+            AccountStatus::Closed
+        );
+
+        assert!(matches!(
+            manager.transfer(&bob, &alice, 100, false),
+            Err(AstorError::AccountClosed(_))
+        ));
+        let signature = sign_status_change(&manager, &root, "unfreeze", &alice);
+        assert!(matches!(
+            manager.unfreeze_account(&alice, &admins, "admin-1", &signature),
+            Err(AstorError::AccountClosed(_))
+        ));
     }
 }
@@ -55,6 +55,25 @@ impl AdminManager {
         Ok(())
     }
 
+    /// Register an administrator with a known key and role, replacing the
+    /// key and role of one already registered under `admin_id`
+    pub fn set_admin(&mut self, admin_id: String, public_key: PublicKey, role: Role) {
+        let created_at = self
+            .admins
+            .get(&admin_id)
+            .map_or_else(Utc::now, |admin| admin.created_at);
+        self.admins.insert(
+            admin_id.clone(),
+            Administrator {
+                id: admin_id,
+                public_key,
+                role,
+                created_at,
+                is_active: true,
+            },
+        );
+    }
+
     /// Remove an administrator
     pub fn remove_admin(&mut self, admin_id: &str, requester_id: &str) -> Result<(), AstorError> {
         let requester = self.get_admin(requester_id)?;
@@ -1,21 +1,130 @@
 //! Advanced Analytics and Reporting for Astor Currency
 //! Provides real-time insights and business intelligence
 
-use crate::errors::AstorResult;
+use crate::config::AlertThresholds;
+use crate::errors::{AstorError, AstorResult};
+use crate::security::{KeyPair, Signature};
 use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::PublicKey;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 
 // pub mod metrics;
 // pub mod reports;
 // pub mod ml_models;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct AnalyticsEngine {
     transaction_metrics: metrics::TransactionMetrics,
     user_analytics: metrics::UserAnalytics,
     network_health: metrics::NetworkHealth,
     ml_predictor: ml_models::PredictionEngine,
+    signing_key: KeyPair,
+    alert_thresholds: AlertThresholds,
+    insight_policy: InsightPolicy,
+    privacy_policy: ReportPrivacyPolicy,
+    /// When each insight was last surfaced, shared between clones so
+    /// suppression holds across every handle to the engine
+    surfaced_insights: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+}
+
+/// Controls which insights reach reports and dashboards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsightPolicy {
+    /// Insights below this confidence are dropped
+    pub min_confidence: f64,
+    /// An insight identical to one surfaced within this many seconds is
+    /// dropped; zero disables suppression
+    pub suppression_window_secs: i64,
+}
+
+impl Default for InsightPolicy {
+    fn default() -> Self {
+        Self {
+            min_confidence: 0.8,
+            suppression_window_secs: 3600,
+        }
+    }
+}
+
+/// How amounts appear in a report shared outside the operator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AmountObfuscation {
+    /// Amounts are exported as stored
+    #[default]
+    Exact,
+    /// Amounts are replaced by the `{"min", "max"}` range of width
+    /// `bucket_size` that contains them
+    Range { bucket_size: u64 },
+}
+
+impl AmountObfuscation {
+    fn apply(&self, amount: &serde_json::Value) -> serde_json::Value {
+        match (self, amount.as_f64()) {
+            (Self::Range { bucket_size }, Some(value)) if *bucket_size > 0 => {
+                let min = (value.max(0.0) as u64 / bucket_size) * bucket_size;
+                serde_json::json!({"min": min, "max": min + bucket_size})
+            }
+            _ => amount.clone(),
+        }
+    }
+}
+
+/// Which report fields are amounts, and how each report type exports them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportPrivacyPolicy {
+    /// Keys in report data whose numeric values are amounts, at any depth
+    pub amount_fields: Vec<String>,
+    /// Report types not listed are exported exactly
+    pub obfuscation: HashMap<ReportType, AmountObfuscation>,
+}
+
+impl Default for ReportPrivacyPolicy {
+    fn default() -> Self {
+        Self {
+            amount_fields: [
+                "amount",
+                "total_amount",
+                "average_amount",
+                "total_volume",
+                "largest_transaction",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            obfuscation: HashMap::new(),
+        }
+    }
+}
+
+impl ReportPrivacyPolicy {
+    pub fn obfuscation_for(&self, report_type: &ReportType) -> AmountObfuscation {
+        self.obfuscation
+            .get(report_type)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn obfuscate(&self, data: &mut serde_json::Value, obfuscation: AmountObfuscation) {
+        match data {
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields.iter_mut() {
+                    if value.is_number() && self.amount_fields.contains(key) {
+                        *value = obfuscation.apply(value);
+                    } else {
+                        self.obfuscate(value, obfuscation);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.obfuscate(item, obfuscation);
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,9 +135,47 @@ pub struct AnalyticsReport {
     pub data: serde_json::Value,
     pub generated_at: DateTime<Utc>,
     pub insights: Vec<Insight>,
+    /// Set on exports whose amounts were replaced by ranges
+    #[serde(default)]
+    pub amounts_obfuscated: bool,
+    /// Base64 Ed25519 signature over the report with this field and
+    /// `signing_key_id` cleared
+    pub signature: Option<String>,
+    pub signing_key_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl AnalyticsReport {
+    /// Canonical bytes covered by the report signature
+    fn signing_payload(&self) -> AstorResult<Vec<u8>> {
+        let mut unsigned = self.clone();
+        unsigned.signature = None;
+        unsigned.signing_key_id = None;
+        serde_json::to_vec(&unsigned).map_err(AstorError::from)
+    }
+}
+
+/// Sign a report in place with the given key
+pub fn sign_report(report: &mut AnalyticsReport, signing_key: &KeyPair) -> AstorResult<()> {
+    let payload = report.signing_payload()?;
+    let signature = signing_key.sign(&payload);
+
+    report.signature = Some(signature.to_base64());
+    report.signing_key_id = Some(signing_key.key_id().to_string());
+    Ok(())
+}
+
+/// Verify that a report was signed by the given key and has not been modified
+pub fn verify_report(report: &AnalyticsReport, public_key: &PublicKey) -> AstorResult<()> {
+    let encoded = report.signature.as_deref().ok_or_else(|| {
+        AstorError::CryptographicError("Analytics report is not signed".to_string())
+    })?;
+    let signature =
+        Signature::from_base64(encoded, report.signing_key_id.clone().unwrap_or_default())?;
+
+    signature.verify_ignoring_age(public_key, &report.signing_payload()?)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ReportType {
     TransactionVolume,
     UserGrowth,
@@ -61,15 +208,59 @@ pub enum InsightSeverity {
 }
 
 impl AnalyticsEngine {
-    pub fn new() -> Self {
+    /// Create an analytics engine that signs reports with the system key
+    pub fn new(signing_key: KeyPair) -> Self {
         Self {
             transaction_metrics: metrics::TransactionMetrics::new(),
             user_analytics: metrics::UserAnalytics::new(),
             network_health: metrics::NetworkHealth::new(),
             ml_predictor: ml_models::PredictionEngine::new(),
+            signing_key,
+            alert_thresholds: AlertThresholds::default(),
+            insight_policy: InsightPolicy::default(),
+            privacy_policy: ReportPrivacyPolicy::default(),
+            surfaced_insights: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Tune the confidence cutoff and duplicate suppression for insights
+    pub fn set_insight_policy(&mut self, policy: InsightPolicy) {
+        self.insight_policy = policy;
+    }
+
+    /// Choose how each report type's amounts appear in exports
+    pub fn set_report_privacy_policy(&mut self, policy: ReportPrivacyPolicy) {
+        self.privacy_policy = policy;
+    }
+
+    /// Copy of `report` for sharing outside the operator
+    ///
+    /// Amounts are obfuscated as configured for the report type and the copy
+    /// is re-signed; `report` itself keeps its exact values.
+    pub fn export_report(&self, report: &AnalyticsReport) -> AstorResult<AnalyticsReport> {
+        let obfuscation = self.privacy_policy.obfuscation_for(&report.report_type);
+        if obfuscation == AmountObfuscation::Exact {
+            return Ok(report.clone());
+        }
+
+        let mut exported = report.clone();
+        self.privacy_policy
+            .obfuscate(&mut exported.data, obfuscation);
+        exported.amounts_obfuscated = true;
+        sign_report(&mut exported, &self.signing_key)?;
+        Ok(exported)
+    }
+
+    /// Tune what the analysis reports as anomalous for this deployment
+    pub fn set_alert_thresholds(&mut self, thresholds: AlertThresholds) {
+        self.alert_thresholds = thresholds;
+    }
+
+    /// Public key that verifies reports produced by this engine
+    pub fn verifying_key(&self) -> PublicKey {
+        self.signing_key.public_key()
+    }
+
     pub async fn generate_report(
         &self,
         report_type: ReportType,
@@ -105,14 +296,52 @@ impl AnalyticsEngine {
             }
         };
 
-        Ok(AnalyticsReport {
+        let mut report = AnalyticsReport {
             id: report_id,
             report_type,
             period,
             data,
             generated_at: Utc::now(),
-            insights,
-        })
+            insights: self.surface_insights(insights, Utc::now()),
+            amounts_obfuscated: false,
+            signature: None,
+            signing_key_id: None,
+        };
+        sign_report(&mut report, &self.signing_key)?;
+
+        Ok(report)
+    }
+
+    /// Drop insights below the confidence cutoff and those identical to one
+    /// surfaced within the suppression window
+    fn surface_insights(&self, insights: Vec<Insight>, now: DateTime<Utc>) -> Vec<Insight> {
+        let window = Duration::seconds(self.insight_policy.suppression_window_secs.max(0));
+        let mut surfaced = self
+            .surfaced_insights
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        surfaced.retain(|_, at| now - *at < window);
+
+        insights
+            .into_iter()
+            .filter(|insight| insight.confidence >= self.insight_policy.min_confidence)
+            .filter(|insight| {
+                if window.is_zero() {
+                    return true;
+                }
+                let key = format!(
+                    "{}|{:?}|{}",
+                    insight.category, insight.severity, insight.message
+                );
+                match surfaced.get(&key) {
+                    Some(_) => false,
+                    None => {
+                        surfaced.insert(key, now);
+                        true
+                    }
+                }
+            })
+            .collect()
     }
 
     async fn analyze_transaction_patterns(
@@ -123,7 +352,7 @@ impl AnalyticsEngine {
 
         // Analyze transaction volume trends
         if let Some(volume_trend) = data.get("volume_trend").and_then(|v| v.as_f64()) {
-            if volume_trend > 0.2 {
+            if volume_trend > self.alert_thresholds.volume_increase {
                 insights.push(Insight {
                     category: "Transaction Volume".to_string(),
                     message: format!(
@@ -137,7 +366,7 @@ impl AnalyticsEngine {
                         "Monitor network performance metrics closely".to_string(),
                     ],
                 });
-            } else if volume_trend < -0.1 {
+            } else if volume_trend < -self.alert_thresholds.volume_decrease {
                 insights.push(Insight {
                     category: "Transaction Volume".to_string(),
                     message: format!(
@@ -182,7 +411,7 @@ impl AnalyticsEngine {
         let mut insights = Vec::new();
 
         if let Some(latency) = data.get("avg_latency_ms").and_then(|v| v.as_f64()) {
-            if latency > 1000.0 {
+            if latency > self.alert_thresholds.network_latency_ms as f64 {
                 insights.push(Insight {
                     category: "Network Performance".to_string(),
                     message: format!("High network latency detected: {:.0}ms", latency),
@@ -205,20 +434,19 @@ impl AnalyticsEngine {
 
         if let Some(predictions) = data.get("predictions").and_then(|v| v.as_array()) {
             for prediction in predictions {
+                // Low-confidence predictions are filtered by the insight policy
                 if let Some(confidence) = prediction.get("confidence").and_then(|v| v.as_f64()) {
-                    if confidence > 0.8 {
-                        if let Some(message) = prediction.get("message").and_then(|v| v.as_str()) {
-                            insights.push(Insight {
-                                category: "Predictive Analysis".to_string(),
-                                message: message.to_string(),
-                                severity: InsightSeverity::Info,
-                                confidence,
-                                recommendations: vec![
-                                    "Monitor predicted trends closely".to_string(),
-                                    "Prepare contingency plans if needed".to_string(),
-                                ],
-                            });
-                        }
+                    if let Some(message) = prediction.get("message").and_then(|v| v.as_str()) {
+                        insights.push(Insight {
+                            category: "Predictive Analysis".to_string(),
+                            message: message.to_string(),
+                            severity: InsightSeverity::Info,
+                            confidence,
+                            recommendations: vec![
+                                "Monitor predicted trends closely".to_string(),
+                                "Prepare contingency plans if needed".to_string(),
+                            ],
+                        });
                     }
                 }
             }
@@ -254,3 +482,192 @@ impl AnalyticsEngine {
         Ok(vec![])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> AnalyticsReport {
+        let now = Utc::now();
+        AnalyticsReport {
+            id: "report-1".to_string(),
+            report_type: ReportType::ComplianceReport,
+            period: TimePeriod {
+                start: now - Duration::days(1),
+                end: now,
+            },
+            data: serde_json::json!({"flagged_transactions": 3, "total_volume": 125000}),
+            generated_at: now,
+            insights: vec![],
+            amounts_obfuscated: false,
+            signature: None,
+            signing_key_id: None,
+        }
+    }
+
+    #[test]
+    fn test_signed_report_verifies() {
+        let keypair = KeyPair::generate();
+        let mut report = sample_report();
+
+        sign_report(&mut report, &keypair).unwrap();
+
+        assert!(verify_report(&report, &keypair.public_key()).is_ok());
+        assert!(verify_report(&report, &KeyPair::generate().public_key()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_report_fails_verification() {
+        let keypair = KeyPair::generate();
+        let mut report = sample_report();
+        sign_report(&mut report, &keypair).unwrap();
+
+        report.data["flagged_transactions"] = serde_json::json!(0);
+
+        assert!(verify_report(&report, &keypair.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_report_fails_verification() {
+        let keypair = KeyPair::generate();
+        assert!(verify_report(&sample_report(), &keypair.public_key()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_custom_latency_threshold_changes_insights() {
+        let mut engine = AnalyticsEngine::new(KeyPair::generate());
+        let data = serde_json::json!({"avg_latency_ms": 400.0});
+
+        assert!(engine
+            .analyze_network_health(&data)
+            .await
+            .unwrap()
+            .is_empty());
+
+        engine.set_alert_thresholds(AlertThresholds {
+            network_latency_ms: 250,
+            ..AlertThresholds::default()
+        });
+        let insights = engine.analyze_network_health(&data).await.unwrap();
+        assert_eq!(insights.len(), 1);
+        assert!(matches!(insights[0].severity, InsightSeverity::Critical));
+    }
+
+    #[tokio::test]
+    async fn test_custom_volume_threshold_changes_insights() {
+        let mut engine = AnalyticsEngine::new(KeyPair::generate());
+        let data = serde_json::json!({"volume_trend": 0.15});
+
+        assert!(engine
+            .analyze_transaction_patterns(&data)
+            .await
+            .unwrap()
+            .is_empty());
+
+        engine.set_alert_thresholds(AlertThresholds {
+            volume_increase: 0.1,
+            ..AlertThresholds::default()
+        });
+        assert_eq!(
+            engine
+                .analyze_transaction_patterns(&data)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    fn predictions() -> serde_json::Value {
+        serde_json::json!({"predictions": [
+            {"message": "Volume up 12% next week", "confidence": 0.91},
+            {"message": "Merchant churn rising", "confidence": 0.72},
+            {"message": "Weekend dip expected", "confidence": 0.55},
+        ]})
+    }
+
+    #[tokio::test]
+    async fn test_lower_confidence_threshold_surfaces_more_insights() {
+        let mut engine = AnalyticsEngine::new(KeyPair::generate());
+        let insights = engine.analyze_predictions(&predictions()).await.unwrap();
+        assert_eq!(engine.surface_insights(insights, Utc::now()).len(), 1);
+
+        engine.set_insight_policy(InsightPolicy {
+            min_confidence: 0.5,
+            suppression_window_secs: 0,
+        });
+        let insights = engine.analyze_predictions(&predictions()).await.unwrap();
+        assert_eq!(engine.surface_insights(insights, Utc::now()).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_insight_suppressed_within_window() {
+        let mut engine = AnalyticsEngine::new(KeyPair::generate());
+        engine.set_insight_policy(InsightPolicy {
+            min_confidence: 0.8,
+            suppression_window_secs: 600,
+        });
+        let now = Utc::now();
+        let insights = engine.analyze_predictions(&predictions()).await.unwrap();
+
+        assert_eq!(engine.surface_insights(insights.clone(), now).len(), 1);
+        assert!(engine
+            .surface_insights(insights.clone(), now + Duration::minutes(5))
+            .is_empty());
+        // A clone shares the suppression history
+        assert!(engine
+            .clone()
+            .surface_insights(insights.clone(), now + Duration::minutes(9))
+            .is_empty());
+
+        assert_eq!(
+            engine
+                .surface_insights(insights, now + Duration::minutes(11))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_export_obfuscates_amounts_per_report_type() {
+        let mut engine = AnalyticsEngine::new(KeyPair::generate());
+        let mut policy = ReportPrivacyPolicy::default();
+        policy.obfuscation.insert(
+            ReportType::ComplianceReport,
+            AmountObfuscation::Range {
+                bucket_size: 10_000,
+            },
+        );
+        engine.set_report_privacy_policy(policy);
+
+        let mut report = sample_report();
+        report.data["transactions"] = serde_json::json!([{"id": "tx-1", "amount": 4_321}]);
+        sign_report(&mut report, &engine.signing_key).unwrap();
+
+        let exported = engine.export_report(&report).unwrap();
+        assert!(exported.amounts_obfuscated);
+        assert_eq!(
+            exported.data["total_volume"],
+            serde_json::json!({"min": 120_000, "max": 130_000})
+        );
+        assert_eq!(
+            exported.data["transactions"][0]["amount"],
+            serde_json::json!({"min": 0, "max": 10_000})
+        );
+        // Counts are not amounts
+        assert_eq!(exported.data["flagged_transactions"], 3);
+        verify_report(&exported, &engine.verifying_key()).unwrap();
+
+        // The internal report keeps exact values
+        assert!(!report.amounts_obfuscated);
+        assert_eq!(report.data["total_volume"], 125_000);
+        assert_eq!(report.data["transactions"][0]["amount"], 4_321);
+        verify_report(&report, &engine.verifying_key()).unwrap();
+
+        // Report types without a policy export exactly
+        report.report_type = ReportType::TransactionVolume;
+        let exported = engine.export_report(&report).unwrap();
+        assert!(!exported.amounts_obfuscated);
+        assert_eq!(exported.data["total_volume"], 125_000);
+    }
+}
@@ -5,8 +5,10 @@ use axum::{
     http::StatusCode,
     response::Json,
 };
+use base64::{engine::general_purpose, Engine as _};
 use uuid::Uuid;
 
+use crate::accounts::parse_public_key;
 use crate::api::{
     models::{
         AccountResponse, ApiResponse, CreateAccountRequest, PaginatedResponse, PaginationQuery,
@@ -21,13 +23,21 @@ pub async fn create_account(
     State(state): State<AppState>,
     Json(request): Json<CreateAccountRequest>,
 ) -> Result<Json<ApiResponse<AccountResponse>>, StatusCode> {
-    let repo = AccountRepository::new(state.database.pool().clone());
-
-    // Decode public key if provided
-    let public_key = if let Some(key_str) = request.public_key {
-        Some(base64::decode(&key_str).map_err(|_| StatusCode::BAD_REQUEST)?)
-    } else {
-        None
+    let repo = AccountRepository::from_database(&state.database);
+
+    // Only store keys that decode to a valid Ed25519 public key
+    let public_key = match request.public_key {
+        Some(key_str) => {
+            let bytes = general_purpose::STANDARD
+                .decode(&key_str)
+                .map_err(|_| StatusCode::BAD_REQUEST)?;
+            parse_public_key(&bytes).map_err(|e| {
+                tracing::debug!("Rejected account public key: {}", e);
+                StatusCode::BAD_REQUEST
+            })?;
+            Some(bytes)
+        }
+        None => None,
     };
 
     let account_type = request.account_type.unwrap_or_else(|| "user".to_string());
@@ -57,7 +67,7 @@ pub async fn get_account(
     State(state): State<AppState>,
     Path(account_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<AccountResponse>>, StatusCode> {
-    let repo = AccountRepository::new(state.database.pool().clone());
+    let repo = AccountRepository::from_database(&state.database);
 
     match repo.get_account(account_id).await {
         Ok(account) => {
@@ -81,7 +91,7 @@ pub async fn list_accounts(
     State(state): State<AppState>,
     Query(pagination): Query<PaginationQuery>,
 ) -> Result<Json<ApiResponse<PaginatedResponse<AccountResponse>>>, StatusCode> {
-    let repo = AccountRepository::new(state.database.pool().clone());
+    let repo = AccountRepository::from_database(&state.database);
 
     let page = pagination.page.unwrap_or(1).max(1);
     let per_page = pagination.per_page.unwrap_or(20).min(100).max(1);
@@ -125,7 +135,7 @@ pub async fn get_balance(
     State(state): State<AppState>,
     Path(account_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<i64>>, StatusCode> {
-    let repo = AccountRepository::new(state.database.pool().clone());
+    let repo = AccountRepository::from_database(&state.database);
 
     match repo.get_account(account_id).await {
         Ok(account) => Ok(Json(ApiResponse::success(account.balance))),
@@ -139,7 +149,7 @@ pub async fn update_account(
     Path(account_id): Path<Uuid>,
     Json(request): Json<UpdateAccountRequest>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
-    let repo = AccountRepository::new(state.database.pool().clone());
+    let repo = AccountRepository::from_database(&state.database);
 
     if let Some(frozen) = request.is_frozen {
         match repo.set_frozen(account_id, frozen).await {
@@ -156,7 +166,7 @@ pub async fn freeze_account(
     State(state): State<AppState>,
     Path(account_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
-    let repo = AccountRepository::new(state.database.pool().clone());
+    let repo = AccountRepository::from_database(&state.database);
 
     match repo.set_frozen(account_id, true).await {
         Ok(_) => Ok(Json(ApiResponse::success(()))),
@@ -169,7 +179,7 @@ pub async fn unfreeze_account(
     State(state): State<AppState>,
     Path(account_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
-    let repo = AccountRepository::new(state.database.pool().clone());
+    let repo = AccountRepository::from_database(&state.database);
 
     match repo.set_frozen(account_id, false).await {
         Ok(_) => Ok(Json(ApiResponse::success(()))),
@@ -11,7 +11,7 @@ use std::collections::HashMap;
 use crate::{
     admin::{AdminManager, Administrator},
     api::{models::*, AppState},
-    central_bank::CentralBank,
+    central_bank::{CentralBank, DEFAULT_CURRENCY},
     errors::AstorError,
     security::{Role, Signature},
 };
@@ -42,6 +42,7 @@ pub struct SystemStatsResponse {
     pub total_admins: usize,
     pub active_admins: usize,
     pub total_money_supply: u64,
+    pub money_supply_by_currency: HashMap<String, u64>,
     pub base_interest_rate: f64,
     pub registered_banks: usize,
     pub active_banks: usize,
@@ -205,7 +206,8 @@ pub async fn system_stats(
     Ok(Json(SystemStatsResponse {
         total_admins: active_admins.len(),
         active_admins: active_admins.len(),
-        total_money_supply: money_stats.total_supply,
+        total_money_supply: money_stats.supply_of(DEFAULT_CURRENCY),
+        money_supply_by_currency: money_stats.supply_by_currency.clone(),
         base_interest_rate: money_stats.base_interest_rate,
         registered_banks: network_stats.total_registered_banks,
         active_banks: network_stats.active_banks,
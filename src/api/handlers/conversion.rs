@@ -13,6 +13,7 @@ use crate::{
 
 #[derive(Debug, Deserialize)]
 pub struct ConvertRequest {
+    pub customer_id: String,
     pub from_currency: String,
     pub to_currency: String,
     pub amount: u64,
@@ -59,6 +60,7 @@ pub async fn convert_currency(
 
     match conversion_service
         .convert_with_fees(
+            &request.customer_id,
             request.amount,
             &request.from_currency,
             &request.to_currency,
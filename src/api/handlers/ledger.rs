@@ -1,6 +1,8 @@
 use crate::{
+    api::middleware::auth::CallerRole,
     errors::AstorError,
     ledger::{Ledger, LedgerEntry},
+    transactions::TransactionManager,
     AppState,
 };
 use axum::{
@@ -24,9 +26,11 @@ pub struct LedgerResponse {
 
 pub async fn get_ledger_entries(
     State(state): State<AppState>,
+    CallerRole(role): CallerRole,
     Query(query): Query<LedgerQuery>,
 ) -> Result<ResponseJson<LedgerResponse>, AstorError> {
     let ledger = Ledger::new();
+    let transaction_manager = TransactionManager::new();
 
     let entries = if let Some(account_id) = query.account_id {
         ledger.get_account_history(&account_id)?
@@ -37,8 +41,12 @@ pub async fn get_ledger_entries(
     let limit = query.limit.unwrap_or(100);
     let offset = query.offset.unwrap_or(0);
 
-    let paginated_entries: Vec<LedgerEntry> =
-        entries.into_iter().skip(offset).take(limit).collect();
+    let paginated_entries: Vec<LedgerEntry> = entries
+        .into_iter()
+        .filter(|entry| transaction_manager.ledger_entry_visible_to(entry, &role))
+        .skip(offset)
+        .take(limit)
+        .collect();
 
     Ok(ResponseJson(LedgerResponse {
         total_count: paginated_entries.len(),
@@ -48,12 +56,16 @@ pub async fn get_ledger_entries(
 
 pub async fn get_ledger_entry(
     State(state): State<AppState>,
+    CallerRole(role): CallerRole,
     Path(entry_id): Path<String>,
 ) -> Result<ResponseJson<LedgerEntry>, AstorError> {
     let ledger = Ledger::new();
+    let transaction_manager = TransactionManager::new();
 
+    // An entry hidden from the caller is reported as missing
     let entry = ledger
         .get_entry(&entry_id)?
+        .filter(|entry| transaction_manager.ledger_entry_visible_to(entry, &role))
         .ok_or(AstorError::NotFound("Ledger entry not found".to_string()))?;
 
     Ok(ResponseJson(entry))
@@ -1,4 +1,5 @@
 use crate::{
+    api::middleware::auth::CallerRole,
     errors::AstorError,
     transactions::{Transaction, TransactionManager, TransactionType},
     AppState,
@@ -51,16 +52,27 @@ pub async fn create_transaction(
 
 pub async fn get_transactions(
     State(state): State<AppState>,
+    CallerRole(role): CallerRole,
     Query(query): Query<TransactionQuery>,
 ) -> Result<ResponseJson<TransactionResponse>, AstorError> {
     let transaction_manager = TransactionManager::new();
 
-    let transactions = transaction_manager.get_transactions(
-        query.account_id.as_deref(),
-        query.transaction_type,
-        query.limit.unwrap_or(100),
-        query.offset.unwrap_or(0),
-    )?;
+    // Only transactions the caller's role may see are listed or counted
+    let visible = match query.account_id.as_deref() {
+        Some(account_id) => transaction_manager.account_statement(account_id, &role),
+        None => transaction_manager.transactions_visible_to(&role),
+    };
+    let transactions: Vec<Transaction> = visible
+        .into_iter()
+        .filter(|t| {
+            query.transaction_type.as_ref().map_or(true, |wanted| {
+                std::mem::discriminant(&t.transaction_type) == std::mem::discriminant(wanted)
+            })
+        })
+        .skip(query.offset.unwrap_or(0))
+        .take(query.limit.unwrap_or(100))
+        .cloned()
+        .collect();
 
     Ok(ResponseJson(TransactionResponse {
         total_count: transactions.len(),
@@ -70,12 +82,15 @@ pub async fn get_transactions(
 
 pub async fn get_transaction(
     State(state): State<AppState>,
+    CallerRole(role): CallerRole,
     Path(transaction_id): Path<String>,
 ) -> Result<ResponseJson<Transaction>, AstorError> {
     let transaction_manager = TransactionManager::new();
 
+    // A transaction hidden from the caller is reported as missing
     let transaction = transaction_manager
-        .get_transaction(&transaction_id)?
+        .get_transaction_for(&transaction_id, &role)
+        .cloned()
         .ok_or(AstorError::NotFound("Transaction not found".to_string()))?;
 
     Ok(ResponseJson(transaction))
@@ -1,18 +1,24 @@
 //! Authentication middleware
 
 use axum::{
-    extract::{Request, State},
-    http::{header::AUTHORIZATION, StatusCode},
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode},
     middleware::Next,
     response::Response,
 };
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use uuid::Uuid;
 
 use crate::api::AppState;
+use crate::security::{ApiKeyPrincipal, Role};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Header carrying a service API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: Uuid,    // Subject (user ID)
     pub role: String, // User role
@@ -20,34 +26,102 @@ pub struct Claims {
     pub iat: i64,     // Issued at
 }
 
+impl Claims {
+    /// Access role named in the token; unknown names get `Role::User`
+    pub fn access_role(&self) -> Role {
+        match self.role.as_str() {
+            "root" => Role::RootAdmin,
+            "admin" => Role::CentralBankAdmin,
+            "bank_admin" => Role::BankAdmin,
+            "auditor" => Role::Auditor,
+            "operator" => Role::Operator,
+            _ => Role::User,
+        }
+    }
+}
+
+/// Role of the authenticated caller, from its API key or bearer token
+///
+/// Requests that carry neither get `Role::User`, the least privileged role.
+#[derive(Debug, Clone)]
+pub struct CallerRole(pub Role);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CallerRole {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extensions = &parts.extensions;
+        let role = if let Some(principal) = extensions.get::<ApiKeyPrincipal>() {
+            principal.role.clone()
+        } else if let Some(claims) = extensions.get::<Claims>() {
+            claims.access_role()
+        } else {
+            Role::User
+        };
+        Ok(Self(role))
+    }
+}
+
 /// JWT authentication middleware
 pub async fn auth_middleware(
     State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let auth_header = request
-        .headers()
+    let claims = bearer_claims(request.headers(), &state.config.security.jwt_secret)?;
+
+    // Add claims to request extensions for use in handlers
+    request.extensions_mut().insert(claims);
+
+    Ok(next.run(request).await)
+}
+
+/// Decode and validate the request's `Authorization: Bearer` token
+pub fn bearer_claims(headers: &HeaderMap, jwt_secret: &str) -> Result<Claims, StatusCode> {
+    let auth_header = headers
         .get(AUTHORIZATION)
         .and_then(|header| header.to_str().ok())
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    let token = &auth_header[7..];
+    let token = auth_header
+        .strip_prefix("Bearer ")
+        .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    let claims = decode::<Claims>(
+    decode::<Claims>(
         token,
-        &DecodingKey::from_secret(state.config.security.jwt_secret.as_ref()),
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
         &Validation::new(Algorithm::HS256),
     )
-    .map_err(|_| StatusCode::UNAUTHORIZED)?
-    .claims;
+    .map(|data| data.claims)
+    .map_err(|_| StatusCode::UNAUTHORIZED)
+}
 
-    // Add claims to request extensions for use in handlers
-    request.extensions_mut().insert(claims);
+/// API key authentication middleware for service-to-service callers
+///
+/// On success the key's `ApiKeyPrincipal` is added to the request extensions.
+pub async fn api_key_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let presented = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let principal = state
+        .api_keys
+        .write()
+        .await
+        .validate_key(presented)
+        .map_err(|e| {
+            tracing::warn!("Rejected API key: {}", e);
+            StatusCode::UNAUTHORIZED
+        })?;
+
+    request.extensions_mut().insert(principal);
 
     Ok(next.run(request).await)
 }
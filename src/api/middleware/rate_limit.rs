@@ -1,18 +1,98 @@
 //! Rate limiting middleware
+//!
+//! Requests are counted per client IP. Internal service accounts listed as
+//! exempt bypass the limiter entirely; unlike the IP whitelist, exemptions
+//! follow the authenticated principal. The limiter runs ahead of the auth
+//! middleware, so it validates the caller's API key or bearer token itself
+//! to find that principal.
 
 use axum::{extract::Request, http::StatusCode, middleware::Next, response::Response};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
+use tokio::sync::RwLock;
 use tower::{Layer, Service};
 
+use super::auth::{bearer_claims, Claims, API_KEY_HEADER};
+use crate::config::RateLimitingConfig;
+use crate::security::{ApiKeyManager, ApiKeyPrincipal, Role};
+
+/// Authenticated principals that are never rate limited
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitExemptions {
+    /// API key IDs and JWT subjects
+    pub principals: HashSet<String>,
+    /// Roles of API keys, e.g. `Role::Operator` service accounts
+    pub roles: HashSet<Role>,
+}
+
+impl RateLimitExemptions {
+    /// Whether the request was authenticated as an exempt principal
+    pub fn is_exempt(&self, request: &Request) -> bool {
+        let extensions = request.extensions();
+        if let Some(principal) = extensions.get::<ApiKeyPrincipal>() {
+            if self.roles.contains(&principal.role) || self.principals.contains(&principal.key_id) {
+                return true;
+            }
+        }
+        extensions
+            .get::<Claims>()
+            .is_some_and(|claims| self.principals.contains(&claims.sub.to_string()))
+    }
+}
+
+impl From<&RateLimitingConfig> for RateLimitExemptions {
+    fn from(config: &RateLimitingConfig) -> Self {
+        Self {
+            principals: config.exempt_principals.iter().cloned().collect(),
+            roles: config.exempt_roles.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Credentials the limiter checks to identify exempt callers
+///
+/// Invalid credentials are not an error here; the caller is just not exempt,
+/// and rejecting the request is left to the auth middleware.
+#[derive(Clone)]
+pub struct RateLimitCredentials {
+    pub api_keys: Arc<RwLock<ApiKeyManager>>,
+    pub jwt_secret: String,
+}
+
+impl RateLimitCredentials {
+    /// Add the principal behind a valid API key or bearer token to the
+    /// request extensions
+    async fn authenticate(&self, request: &mut Request) {
+        if request.extensions().get::<ApiKeyPrincipal>().is_none() {
+            let presented = request
+                .headers()
+                .get(API_KEY_HEADER)
+                .and_then(|header| header.to_str().ok())
+                .map(str::to_string);
+            if let Some(presented) = presented {
+                if let Ok(principal) = self.api_keys.write().await.validate_key(&presented) {
+                    request.extensions_mut().insert(principal);
+                }
+            }
+        }
+        if request.extensions().get::<Claims>().is_none() {
+            if let Ok(claims) = bearer_claims(request.headers(), &self.jwt_secret) {
+                request.extensions_mut().insert(claims);
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct RateLimitLayer {
     max_requests: u32,
     window: Duration,
     store: Arc<Mutex<HashMap<String, (u32, Instant)>>>,
+    exemptions: Arc<RateLimitExemptions>,
+    credentials: Option<RateLimitCredentials>,
 }
 
 impl RateLimitLayer {
@@ -21,8 +101,25 @@ impl RateLimitLayer {
             max_requests,
             window,
             store: Arc::new(Mutex::new(HashMap::new())),
+            exemptions: Arc::new(RateLimitExemptions::default()),
+            credentials: None,
         }
     }
+
+    /// Let the given principals bypass the limiter
+    ///
+    /// Without `with_credentials`, only principals already in the request
+    /// extensions are recognised.
+    pub fn with_exemptions(mut self, exemptions: RateLimitExemptions) -> Self {
+        self.exemptions = Arc::new(exemptions);
+        self
+    }
+
+    /// Validate callers' credentials to identify exempt principals
+    pub fn with_credentials(mut self, credentials: RateLimitCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
 }
 
 impl<S> Layer<S> for RateLimitLayer {
@@ -34,6 +131,8 @@ impl<S> Layer<S> for RateLimitLayer {
             max_requests: self.max_requests,
             window: self.window,
             store: self.store.clone(),
+            exemptions: self.exemptions.clone(),
+            credentials: self.credentials.clone(),
         }
     }
 }
@@ -44,6 +143,34 @@ pub struct RateLimitService<S> {
     max_requests: u32,
     window: Duration,
     store: Arc<Mutex<HashMap<String, (u32, Instant)>>>,
+    exemptions: Arc<RateLimitExemptions>,
+    credentials: Option<RateLimitCredentials>,
+}
+
+impl<S> RateLimitService<S> {
+    /// Count a request from the client, returning false once it is over
+    /// the limit for the current window
+    fn admit(&self, request: &Request) -> bool {
+        let client_ip = request
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|hv| hv.to_str().ok())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let mut store = self.store.lock().unwrap();
+        let now = Instant::now();
+
+        // Clean up expired entries
+        store.retain(|_, (_, timestamp)| now.duration_since(*timestamp) < self.window);
+
+        let (count, _) = store.entry(client_ip).or_insert((0, now));
+        if *count >= self.max_requests {
+            return false;
+        }
+        *count += 1;
+        true
+    }
 }
 
 impl<S> Service<Request> for RateLimitService<S>
@@ -64,36 +191,94 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, request: Request) -> Self::Future {
-        let client_ip = request
-            .headers()
-            .get("x-forwarded-for")
-            .and_then(|hv| hv.to_str().ok())
-            .unwrap_or("unknown")
-            .to_string();
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        let limiter = self.clone();
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            if let Some(credentials) = &limiter.credentials {
+                credentials.authenticate(&mut request).await;
+            }
+            if !limiter.exemptions.is_exempt(&request) && !limiter.admit(&request) {
+                return Ok(Response::builder()
+                    .status(StatusCode::TOO_MANY_REQUESTS)
+                    .body(axum::body::Body::empty())
+                    .unwrap());
+            }
+            inner.call(request).await
+        })
+    }
+}
 
-        let mut store = self.store.lock().unwrap();
-        let now = Instant::now();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use std::convert::Infallible;
+    use std::task::{Context, Poll};
 
-        // Clean up expired entries
-        store.retain(|_, (_, timestamp)| now.duration_since(*timestamp) < self.window);
+    #[derive(Clone)]
+    struct Ok200;
 
-        let (count, _) = store.entry(client_ip.clone()).or_insert((0, now));
+    impl Service<Request> for Ok200 {
+        type Response = Response;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Response, Infallible>>;
 
-        if *count >= self.max_requests {
-            drop(store);
-            return Box::pin(async move {
-                Ok(Response::builder()
-                    .status(StatusCode::TOO_MANY_REQUESTS)
-                    .body(axum::body::Body::empty())
-                    .unwrap())
-            });
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
         }
 
-        *count += 1;
-        drop(store);
+        fn call(&mut self, _request: Request) -> Self::Future {
+            std::future::ready(Ok(Response::new(Body::empty())))
+        }
+    }
 
-        let mut inner = self.inner.clone();
-        Box::pin(async move { inner.call(request).await })
+    fn request(principal: ApiKeyPrincipal) -> Request {
+        let mut request = Request::builder()
+            .header("x-forwarded-for", "10.0.0.7")
+            .body(Body::empty())
+            .unwrap();
+        request.extensions_mut().insert(principal);
+        request
+    }
+
+    fn principal(key_id: &str, role: Role) -> ApiKeyPrincipal {
+        ApiKeyPrincipal {
+            key_id: key_id.to_string(),
+            role,
+            scopes: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exempt_principal_is_never_rate_limited() {
+        let exemptions = RateLimitExemptions {
+            principals: HashSet::new(),
+            roles: HashSet::from([Role::Operator]),
+        };
+        let mut service = RateLimitLayer::new(3, Duration::from_secs(60))
+            .with_exemptions(exemptions)
+            .layer(Ok200);
+
+        for _ in 0..10 {
+            let response = service
+                .call(request(principal("settlement", Role::Operator)))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let mut statuses = Vec::new();
+        for _ in 0..5 {
+            let response = service
+                .call(request(principal("partner", Role::User)))
+                .await
+                .unwrap();
+            statuses.push(response.status());
+        }
+        assert_eq!(&statuses[..3], &[StatusCode::OK; 3]);
+        assert!(statuses[3..]
+            .iter()
+            .all(|status| *status == StatusCode::TOO_MANY_REQUESTS));
     }
 }
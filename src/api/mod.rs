@@ -7,26 +7,35 @@ pub mod models;
 pub mod routes;
 
 use axum::{
-    http::{Method, StatusCode},
-    response::Json,
+    body::Body,
+    http::{header, Method, StatusCode},
+    response::{IntoResponse, Json, Response},
     Router,
 };
 use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
 
+use self::middleware::rate_limit::{RateLimitCredentials, RateLimitExemptions};
 use crate::config::Config;
 use crate::database::Database;
+use crate::errors::AstorError;
+use crate::security::ApiKeyManager;
 
 /// API application state
 #[derive(Clone)]
 pub struct AppState {
     pub database: Database,
     pub config: Config,
+    pub api_keys: Arc<RwLock<ApiKeyManager>>,
 }
 
 /// Create the main API router
@@ -47,14 +56,110 @@ pub fn create_router(state: AppState) -> Router {
                 .layer(middleware::timeout::TimeoutLayer::new(Duration::from_secs(
                     30,
                 )))
-                .layer(middleware::rate_limit::RateLimitLayer::new(
-                    100,
-                    Duration::from_secs(60),
-                )),
+                .layer(
+                    middleware::rate_limit::RateLimitLayer::new(100, Duration::from_secs(60))
+                        .with_exemptions(RateLimitExemptions::from(
+                            &state.config.security.rate_limiting,
+                        ))
+                        .with_credentials(RateLimitCredentials {
+                            api_keys: state.api_keys.clone(),
+                            jwt_secret: state.config.security.jwt_secret.clone(),
+                        }),
+                ),
         )
         .with_state(state)
 }
 
+/// A running API server and the background work it depends on
+pub struct ApiServer {
+    pub local_addr: SocketAddr,
+    server: JoinHandle<()>,
+    database_health_checks: JoinHandle<()>,
+}
+
+impl ApiServer {
+    /// Stop serving requests and checking the database
+    pub fn stop(self) {
+        self.server.abort();
+        self.database_health_checks.abort();
+    }
+}
+
+/// Connect to the configured database and serve the API on `bind_addr`
+///
+/// The database's replica health is checked on the configured interval for
+/// as long as the server runs.
+pub async fn create_server(config: Config, bind_addr: SocketAddr) -> Result<ApiServer, AstorError> {
+    let database = Database::from_config(&config.database).await?;
+    let database_health_checks = database.spawn_health_checks(Duration::from_secs(
+        config.database.replica_health_check_interval,
+    ));
+
+    let listener = match tokio::net::TcpListener::bind(bind_addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            database_health_checks.abort();
+            return Err(AstorError::NetworkError(format!(
+                "Failed to bind API server to {}: {}",
+                bind_addr, e
+            )));
+        }
+    };
+    let local_addr = listener.local_addr().unwrap_or(bind_addr);
+
+    let state = AppState {
+        database,
+        api_keys: Arc::new(RwLock::new(ApiKeyManager::new(
+            config.security.api_key_length,
+        ))),
+        config,
+    };
+    let router = create_router(state);
+    let server = tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            tracing::error!("API server stopped: {}", e);
+        }
+    });
+
+    Ok(ApiServer {
+        local_addr,
+        server,
+        database_health_checks,
+    })
+}
+
+/// Response for an `AstorError::ServiceBusy` rejection: 503 with `Retry-After`
+pub fn service_busy_response(retry_after_secs: u64) -> Response {
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header(header::RETRY_AFTER, retry_after_secs.to_string())
+        .body(Body::empty())
+        .unwrap()
+}
+
+impl IntoResponse for AstorError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AstorError::ServiceBusy { retry_after_secs } => {
+                return service_busy_response(*retry_after_secs);
+            }
+            AstorError::Unauthorized(_) => StatusCode::FORBIDDEN,
+            AstorError::AccountNotFound(_) | AstorError::AdminNotFound(_) => StatusCode::NOT_FOUND,
+            AstorError::NodeSyncing | AstorError::TransactionQueueFull(_) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            AstorError::ConcurrentModification(_) => StatusCode::CONFLICT,
+            AstorError::DatabaseError(_)
+            | AstorError::NetworkError(_)
+            | AstorError::ConfigurationError(_)
+            | AstorError::CryptographicError(_)
+            | AstorError::LedgerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
 /// Health check endpoint
 async fn health_check() -> Result<Json<Value>, StatusCode> {
     Ok(Json(json!({
@@ -74,3 +179,105 @@ async fn metrics() -> Result<Json<Value>, StatusCode> {
         "active_connections": 0
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::Role;
+    use axum::http::Request;
+    use base64::{engine::general_purpose, Engine as _};
+    use sqlx::postgres::PgPoolOptions;
+    use tower::ServiceExt;
+
+    fn health_request(api_key: &str) -> Request<Body> {
+        Request::builder()
+            .uri("/health")
+            .header("x-forwarded-for", "10.0.0.7")
+            .header(middleware::auth::API_KEY_HEADER, api_key)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_account_rejects_invalid_public_key() {
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgresql://localhost/astor")
+            .unwrap();
+        let router = create_router(AppState {
+            database: Database::from_pools(pool, None),
+            config: Config::default(),
+            api_keys: Arc::new(RwLock::new(ApiKeyManager::new(32))),
+        });
+
+        // Valid base64, but 31 bytes is not an Ed25519 public key
+        let short_key = general_purpose::STANDARD.encode([7u8; 31]);
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/v1/accounts")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({ "public_key": short_key, "account_type": "user" }).to_string(),
+            ))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_service_busy_maps_to_503_with_retry_after() {
+        let response = AstorError::ServiceBusy {
+            retry_after_secs: 7,
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "7");
+
+        let response = AstorError::InsufficientFunds.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_exempt_api_key_bypasses_router_rate_limit() {
+        let mut api_keys = ApiKeyManager::new(32);
+        let (service_key, _) = api_keys
+            .create_key("settlement".to_string(), Role::Operator, Vec::new(), None)
+            .unwrap();
+        let mut config = Config::default();
+        config.security.rate_limiting.exempt_roles = vec![Role::Operator];
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgresql://localhost/astor")
+            .unwrap();
+        let router = create_router(AppState {
+            database: Database::from_pools(pool, None),
+            config,
+            api_keys: Arc::new(RwLock::new(api_keys)),
+        });
+
+        for _ in 0..150 {
+            let response = router
+                .clone()
+                .oneshot(health_request(&service_key))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        // A key that does not validate is limited like any other caller
+        let forged = format!("{}0", service_key);
+        let mut statuses = Vec::new();
+        for _ in 0..101 {
+            let response = router
+                .clone()
+                .oneshot(health_request(&forged))
+                .await
+                .unwrap();
+            statuses.push(response.status());
+        }
+        assert!(statuses[..100]
+            .iter()
+            .all(|status| *status == StatusCode::OK));
+        assert_eq!(statuses[100], StatusCode::TOO_MANY_REQUESTS);
+    }
+}
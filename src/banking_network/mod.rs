@@ -11,16 +11,197 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::admin::Administrator;
 use crate::central_bank::CentralBank;
 use crate::commercial_banking::CommercialBank;
 use crate::errors::AstorError;
+use crate::security::{Permission, Signature};
+
+/// Default number of admin approvals required to suspend a bank
+pub const DEFAULT_SUSPENSION_QUORUM: usize = 2;
+
+/// How often and how patiently bank API endpoints are probed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BankHealthConfig {
+    pub poll_interval_secs: u64,
+    /// A probe taking longer than this counts as a failure
+    pub timeout_secs: u64,
+    /// Consecutive failed probes before a bank is flagged for review
+    pub failures_before_review: u32,
+}
+
+impl Default for BankHealthConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 60,
+            timeout_secs: 5,
+            failures_before_review: 3,
+        }
+    }
+}
+
+/// Last known reachability of a bank's API endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BankHealth {
+    /// False after a failed probe, until a probe succeeds again
+    pub reachable: bool,
+    pub last_seen: Option<DateTime<Utc>>,
+    pub last_checked: Option<DateTime<Utc>>,
+    pub consecutive_failures: u32,
+    /// Set once failures reach the review threshold; cleared by an operator
+    pub flagged_for_review: bool,
+}
+
+impl Default for BankHealth {
+    /// Banks are assumed reachable until a probe says otherwise
+    fn default() -> Self {
+        Self {
+            reachable: true,
+            last_seen: None,
+            last_checked: None,
+            consecutive_failures: 0,
+            flagged_for_review: false,
+        }
+    }
+}
+
+/// Checks whether a bank's API endpoint is responding
+#[async_trait::async_trait]
+pub trait BankEndpointProbe: Send + Sync {
+    async fn probe(&self, api_endpoint: &str) -> Result<(), AstorError>;
+}
+
+/// GETs `{api_endpoint}/health` and expects a success status
+pub struct HttpEndpointProbe {
+    http_client: reqwest::Client,
+}
+
+impl HttpEndpointProbe {
+    pub fn new(timeout: std::time::Duration) -> Self {
+        Self {
+            http_client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BankEndpointProbe for HttpEndpointProbe {
+    async fn probe(&self, api_endpoint: &str) -> Result<(), AstorError> {
+        self.http_client
+            .get(format!("{}/health", api_endpoint.trim_end_matches('/')))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AstorError::NetworkError(format!("Health probe failed: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Settlement held back because one of its banks was unreachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeferredSettlement {
+    pub deferral_id: String,
+    pub from_bank: String,
+    pub to_bank: String,
+    pub amount: u64,
+    pub reference: String,
+    pub deferred_at: DateTime<Utc>,
+}
+
+/// What `BankingNetwork::process_settlement` did with a settlement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SettlementOutcome {
+    /// Handed to the settlement engine under this settlement ID
+    Submitted(String),
+    /// Held under this deferral ID until both banks are reachable; see
+    /// `BankingNetwork::deferred_settlements`
+    Deferred(String),
+}
+
+impl SettlementOutcome {
+    /// The settlement ID, unless the settlement was deferred
+    pub fn settlement_id(&self) -> Option<&str> {
+        match self {
+            SettlementOutcome::Submitted(settlement_id) => Some(settlement_id),
+            SettlementOutcome::Deferred(_) => None,
+        }
+    }
+}
+
+/// What happened to one deferred settlement when the queue was retried
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DeferredRelease {
+    Released {
+        deferral_id: String,
+        settlement_id: String,
+    },
+    /// One of its banks is still unreachable
+    StillDeferred { deferral_id: String },
+    /// Submission failed; the settlement stays queued for the next poll
+    Failed { deferral_id: String, reason: String },
+}
 
 /// Banking network coordinator
+///
+/// Clones share banks, health, suspensions, deferred settlements and
+/// settlement state; configuration set on one clone afterwards is not seen
+/// by the others.
+#[derive(Clone)]
 pub struct BankingNetwork {
     registered_banks: Arc<RwLock<HashMap<String, RegisteredBank>>>,
     central_bank: Arc<RwLock<CentralBank>>,
     settlement_engine: settlement::SettlementEngine,
     oversight_system: oversight::OversightSystem,
+    suspension_proposals: Arc<RwLock<HashMap<String, SuspensionProposal>>>,
+    suspension_log: Arc<RwLock<Vec<SuspensionRecord>>>,
+    suspension_quorum: usize,
+    health_config: BankHealthConfig,
+    endpoint_probe: Arc<dyn BankEndpointProbe>,
+    /// Executes submitted settlements; without one they stay pending until
+    /// completed or reported failed
+    settlement_rail: Option<Arc<dyn settlement::SettlementRail>>,
+    deferred_settlements: Arc<RwLock<Vec<DeferredSettlement>>>,
+}
+
+/// Pending request to suspend a bank, awaiting admin approvals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspensionProposal {
+    pub proposal_id: String,
+    pub bank_id: String,
+    pub reason: String,
+    pub proposed_at: DateTime<Utc>,
+    pub approvals: Vec<String>, // Admin IDs
+    pub status: ProposalStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProposalStatus {
+    Pending,
+    Executed,
+}
+
+/// How a suspension took effect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SuspensionKind {
+    /// Approved by a quorum of central bank admins
+    Quorum { proposal_id: String },
+    /// Emergency freeze by a single admin with elevated authority
+    EmergencyFreeze { admin_id: String },
+    /// Automatic suspension after repeated settlement failures
+    SettlementFailures { consecutive_failures: u32 },
+}
+
+/// Record of a bank suspension that took effect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspensionRecord {
+    pub bank_id: String,
+    pub reason: String,
+    pub kind: SuspensionKind,
+    pub approved_by: Vec<String>,
+    pub suspended_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +215,101 @@ pub struct RegisteredBank {
     pub public_key: String,
     pub compliance_rating: ComplianceRating,
     pub services_offered: Vec<BankingService>,
+    #[serde(default)]
+    pub health: BankHealth,
+}
+
+/// Read-only view of the bank registry for other subsystems
+#[derive(Clone)]
+pub struct BankDirectory {
+    registered_banks: Arc<RwLock<HashMap<String, RegisteredBank>>>,
+}
+
+impl BankDirectory {
+    /// Whether a bank with this name is registered and not suspended or revoked
+    pub async fn is_eligible_bank(&self, bank_name: &str) -> bool {
+        self.registered_banks.read().await.values().any(|bank| {
+            bank.bank_name == bank_name
+                && !matches!(bank.status, BankStatus::Suspended | BankStatus::Revoked)
+        })
+    }
+}
+
+/// Public listing of a registered bank
+///
+/// Omits the compliance rating, which is supervisory information.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankDirectoryEntry {
+    pub bank_id: String,
+    pub bank_name: String,
+    pub license_number: String,
+    pub status: BankStatus,
+    pub api_endpoint: String,
+    pub public_key: String,
+    pub services_offered: Vec<BankingService>,
+    pub registration_date: DateTime<Utc>,
+}
+
+impl From<&RegisteredBank> for BankDirectoryEntry {
+    fn from(bank: &RegisteredBank) -> Self {
+        Self {
+            bank_id: bank.bank_id.clone(),
+            bank_name: bank.bank_name.clone(),
+            license_number: bank.license_number.clone(),
+            status: bank.status.clone(),
+            api_endpoint: bank.api_endpoint.clone(),
+            public_key: bank.public_key.clone(),
+            services_offered: bank.services_offered.clone(),
+            registration_date: bank.registration_date,
+        }
+    }
+}
+
+impl BankDirectoryEntry {
+    const CSV_HEADER: &'static str =
+        "bank_id,bank_name,license_number,status,api_endpoint,public_key,services_offered,registration_date";
+
+    fn to_csv_row(&self) -> String {
+        let services = self
+            .services_offered
+            .iter()
+            .map(|service| format!("{:?}", service))
+            .collect::<Vec<_>>()
+            .join(";");
+
+        [
+            self.bank_id.clone(),
+            self.bank_name.clone(),
+            self.license_number.clone(),
+            format!("{:?}", self.status),
+            self.api_endpoint.clone(),
+            self.public_key.clone(),
+            services,
+            self.registration_date.to_rfc3339(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Output formats for the public bank directory
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DirectoryFormat {
+    /// Pretty-printed JSON array of directory entries
+    Json,
+    /// RFC 4180 CSV with a header row; services are `;`-separated
+    Csv,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,7 +347,239 @@ impl BankingNetwork {
             central_bank: Arc::new(RwLock::new(central_bank)),
             settlement_engine: settlement::SettlementEngine::new(),
             oversight_system: oversight::OversightSystem::new(),
+            suspension_proposals: Arc::new(RwLock::new(HashMap::new())),
+            suspension_log: Arc::new(RwLock::new(Vec::new())),
+            suspension_quorum: DEFAULT_SUSPENSION_QUORUM,
+            endpoint_probe: Arc::new(HttpEndpointProbe::new(std::time::Duration::from_secs(
+                BankHealthConfig::default().timeout_secs,
+            ))),
+            health_config: BankHealthConfig::default(),
+            settlement_rail: None,
+            deferred_settlements: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub fn health_config(&self) -> &BankHealthConfig {
+        &self.health_config
+    }
+
+    /// Apply health polling settings; the HTTP probe picks up the new timeout
+    pub fn set_health_config(&mut self, config: BankHealthConfig) {
+        self.endpoint_probe = Arc::new(HttpEndpointProbe::new(std::time::Duration::from_secs(
+            config.timeout_secs,
+        )));
+        self.health_config = config;
+    }
+
+    /// Probe bank endpoints with something other than HTTP
+    pub fn set_endpoint_probe(&mut self, probe: Arc<dyn BankEndpointProbe>) {
+        self.endpoint_probe = probe;
+    }
+
+    /// Execute submitted settlements on `rail`
+    pub fn set_settlement_rail(&mut self, rail: Arc<dyn settlement::SettlementRail>) {
+        self.settlement_rail = Some(rail);
+    }
+
+    /// Shared read-only view of registered banks
+    pub fn directory(&self) -> BankDirectory {
+        BankDirectory {
+            registered_banks: self.registered_banks.clone(),
+        }
+    }
+
+    /// Set the number of admin approvals required to suspend a bank
+    pub fn set_suspension_quorum(&mut self, quorum: usize) -> Result<(), AstorError> {
+        if quorum == 0 {
+            return Err(AstorError::BankingNetworkError(
+                "Suspension quorum must be at least 1".to_string(),
+            ));
         }
+        self.suspension_quorum = quorum;
+        Ok(())
+    }
+
+    /// Message an admin signs to approve a suspension proposal
+    pub fn suspension_approval_message(proposal_id: &str) -> Vec<u8> {
+        format!("approve_suspension:{}", proposal_id).into_bytes()
+    }
+
+    /// Message an admin signs to emergency-freeze a bank
+    pub fn emergency_freeze_message(bank_id: &str) -> Vec<u8> {
+        format!("emergency_freeze:{}", bank_id).into_bytes()
+    }
+
+    /// Propose suspending a bank; takes effect once a quorum of admins approves
+    pub async fn propose_suspension(
+        &self,
+        bank_id: &str,
+        reason: String,
+    ) -> Result<String, AstorError> {
+        if !self.registered_banks.read().await.contains_key(bank_id) {
+            return Err(AstorError::BankingNetworkError(format!(
+                "Bank {} not found",
+                bank_id
+            )));
+        }
+
+        let proposal_id = uuid::Uuid::new_v4().to_string();
+        let proposal = SuspensionProposal {
+            proposal_id: proposal_id.clone(),
+            bank_id: bank_id.to_string(),
+            reason,
+            proposed_at: Utc::now(),
+            approvals: Vec::new(),
+            status: ProposalStatus::Pending,
+        };
+
+        self.suspension_proposals
+            .write()
+            .await
+            .insert(proposal_id.clone(), proposal);
+
+        tracing::info!("Suspension of bank {} proposed: {}", bank_id, proposal_id);
+        Ok(proposal_id)
+    }
+
+    /// Approve a suspension proposal
+    ///
+    /// Returns true once the approval completes the quorum and the bank is
+    /// suspended.
+    pub async fn approve_suspension(
+        &self,
+        proposal_id: &str,
+        admin: &Administrator,
+        signature: &Signature,
+    ) -> Result<bool, AstorError> {
+        Self::authorize_admin(admin, &Permission::FreezeAccounts)?;
+        signature.verify(
+            &admin.public_key,
+            &Self::suspension_approval_message(proposal_id),
+        )?;
+
+        let mut proposals = self.suspension_proposals.write().await;
+        let proposal = proposals.get_mut(proposal_id).ok_or_else(|| {
+            AstorError::BankingNetworkError(format!(
+                "Suspension proposal {} not found",
+                proposal_id
+            ))
+        })?;
+
+        if proposal.status != ProposalStatus::Pending {
+            return Err(AstorError::BankingNetworkError(format!(
+                "Suspension proposal {} is no longer pending",
+                proposal_id
+            )));
+        }
+
+        if proposal.approvals.contains(&admin.id) {
+            return Err(AstorError::BankingNetworkError(format!(
+                "Administrator {} already approved proposal {}",
+                admin.id, proposal_id
+            )));
+        }
+
+        proposal.approvals.push(admin.id.clone());
+
+        if proposal.approvals.len() < self.suspension_quorum {
+            return Ok(false);
+        }
+
+        proposal.status = ProposalStatus::Executed;
+        let record = SuspensionRecord {
+            bank_id: proposal.bank_id.clone(),
+            reason: proposal.reason.clone(),
+            kind: SuspensionKind::Quorum {
+                proposal_id: proposal_id.to_string(),
+            },
+            approved_by: proposal.approvals.clone(),
+            suspended_at: Utc::now(),
+        };
+        drop(proposals);
+
+        self.apply_suspension(record).await?;
+        Ok(true)
+    }
+
+    /// Immediately freeze a bank, bypassing the approval quorum
+    ///
+    /// Requires emergency authority and is logged separately from
+    /// quorum-approved suspensions.
+    pub async fn emergency_freeze_bank(
+        &self,
+        bank_id: &str,
+        admin: &Administrator,
+        signature: &Signature,
+        reason: String,
+    ) -> Result<(), AstorError> {
+        Self::authorize_admin(admin, &Permission::EmergencyShutdown)?;
+        signature.verify(&admin.public_key, &Self::emergency_freeze_message(bank_id))?;
+
+        tracing::warn!(
+            "EMERGENCY FREEZE of bank {} by administrator {}: {}",
+            bank_id,
+            admin.id,
+            reason
+        );
+
+        self.apply_suspension(SuspensionRecord {
+            bank_id: bank_id.to_string(),
+            reason,
+            kind: SuspensionKind::EmergencyFreeze {
+                admin_id: admin.id.clone(),
+            },
+            approved_by: vec![admin.id.clone()],
+            suspended_at: Utc::now(),
+        })
+        .await
+    }
+
+    /// Get a suspension proposal
+    pub async fn get_suspension_proposal(&self, proposal_id: &str) -> Option<SuspensionProposal> {
+        self.suspension_proposals
+            .read()
+            .await
+            .get(proposal_id)
+            .cloned()
+    }
+
+    /// Get the history of suspensions that took effect
+    pub async fn get_suspension_log(&self) -> Vec<SuspensionRecord> {
+        self.suspension_log.read().await.clone()
+    }
+
+    fn authorize_admin(admin: &Administrator, permission: &Permission) -> Result<(), AstorError> {
+        if !admin.is_active {
+            return Err(AstorError::Unauthorized(
+                "Administrator is inactive".to_string(),
+            ));
+        }
+        if !admin.role.has_permission(permission) {
+            return Err(AstorError::Unauthorized(format!(
+                "Administrator {} lacks {:?} permission",
+                admin.id, permission
+            )));
+        }
+        Ok(())
+    }
+
+    async fn apply_suspension(&self, record: SuspensionRecord) -> Result<(), AstorError> {
+        {
+            let mut banks = self.registered_banks.write().await;
+            let bank = banks.get_mut(&record.bank_id).ok_or_else(|| {
+                AstorError::BankingNetworkError(format!("Bank {} not found", record.bank_id))
+            })?;
+            bank.status = BankStatus::Suspended;
+        }
+
+        tracing::info!(
+            "Bank {} suspended ({:?}): {}",
+            record.bank_id,
+            record.kind,
+            record.reason
+        );
+        self.suspension_log.write().await.push(record);
+        Ok(())
     }
 
     /// Register a new commercial bank
@@ -95,6 +603,7 @@ impl BankingNetwork {
             public_key,
             compliance_rating: ComplianceRating::Satisfactory,
             services_offered,
+            health: BankHealth::default(),
         };
 
         let mut banks = self.registered_banks.write().await;
@@ -122,17 +631,399 @@ impl BankingNetwork {
         }
     }
 
+    /// All registered banks, ordered by name
+    pub async fn list_banks(&self) -> Vec<RegisteredBank> {
+        let mut banks: Vec<RegisteredBank> = self
+            .registered_banks
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect();
+        banks.sort_by(|a, b| {
+            a.bank_name
+                .cmp(&b.bank_name)
+                .then_with(|| a.bank_id.cmp(&b.bank_id))
+        });
+        banks
+    }
+
+    /// Look up a registered bank by ID
+    pub async fn get_bank(&self, bank_id: &str) -> Option<RegisteredBank> {
+        self.registered_banks.read().await.get(bank_id).cloned()
+    }
+
+    /// Export the public bank directory
+    pub async fn export_directory(&self, format: DirectoryFormat) -> Result<String, AstorError> {
+        let entries: Vec<BankDirectoryEntry> = self
+            .list_banks()
+            .await
+            .iter()
+            .map(BankDirectoryEntry::from)
+            .collect();
+
+        match format {
+            DirectoryFormat::Json => {
+                serde_json::to_string_pretty(&entries).map_err(AstorError::from)
+            }
+            DirectoryFormat::Csv => Ok(std::iter::once(BankDirectoryEntry::CSV_HEADER.to_string())
+                .chain(entries.iter().map(BankDirectoryEntry::to_csv_row))
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+
+    pub fn settlement_engine(&self) -> &settlement::SettlementEngine {
+        &self.settlement_engine
+    }
+
+    /// Configure settlement currencies, corridor fees and FX routing
+    pub fn settlement_engine_mut(&mut self) -> &mut settlement::SettlementEngine {
+        &mut self.settlement_engine
+    }
+
+    /// Probe every active bank's endpoint once
+    ///
+    /// Updates each bank's `health`, flags banks for review once their
+    /// failures reach the configured threshold, and releases deferred
+    /// settlements whose banks are reachable again. Returns what happened to
+    /// each deferred settlement.
+    pub async fn poll_bank_health(&self) -> Vec<DeferredRelease> {
+        let endpoints: Vec<(String, String)> = self
+            .registered_banks
+            .read()
+            .await
+            .values()
+            .filter(|bank| matches!(bank.status, BankStatus::Active))
+            .map(|bank| (bank.bank_id.clone(), bank.api_endpoint.clone()))
+            .collect();
+
+        for (bank_id, api_endpoint) in endpoints {
+            let result = self.endpoint_probe.probe(&api_endpoint).await;
+            let now = Utc::now();
+
+            let mut banks = self.registered_banks.write().await;
+            let Some(bank) = banks.get_mut(&bank_id) else {
+                continue;
+            };
+            bank.health.last_checked = Some(now);
+            match result {
+                Ok(()) => {
+                    if !bank.health.reachable {
+                        tracing::info!("Bank {} endpoint is reachable again", bank_id);
+                    }
+                    bank.health.reachable = true;
+                    bank.health.last_seen = Some(now);
+                    bank.health.consecutive_failures = 0;
+                }
+                Err(e) => {
+                    bank.health.reachable = false;
+                    bank.health.consecutive_failures += 1;
+                    tracing::warn!(
+                        "Bank {} endpoint unreachable ({} consecutive failures): {}",
+                        bank_id,
+                        bank.health.consecutive_failures,
+                        e
+                    );
+                    if !bank.health.flagged_for_review
+                        && bank.health.consecutive_failures
+                            >= self.health_config.failures_before_review
+                    {
+                        bank.health.flagged_for_review = true;
+                        tracing::error!(
+                            "ALERT: bank {} flagged for review after {} failed health probes",
+                            bank_id,
+                            bank.health.consecutive_failures
+                        );
+                    }
+                }
+            }
+        }
+
+        self.release_deferred_settlements().await
+    }
+
+    /// Poll bank health every `poll_interval_secs` until the task is aborted
+    pub fn spawn_health_polling(network: Arc<BankingNetwork>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(
+                network.health_config.poll_interval_secs.max(1),
+            ));
+            loop {
+                ticker.tick().await;
+                for release in network.poll_bank_health().await {
+                    if let DeferredRelease::Failed {
+                        deferral_id,
+                        reason,
+                    } = release
+                    {
+                        tracing::error!(
+                            "Deferred settlement {} could not be released: {}",
+                            deferral_id,
+                            reason
+                        );
+                    }
+                }
+            }
+        })
+    }
+
+    /// Clear a bank's review flag once an operator has looked into it
+    pub async fn clear_health_review(&self, bank_id: &str) -> Result<(), AstorError> {
+        let mut banks = self.registered_banks.write().await;
+        let bank = banks.get_mut(bank_id).ok_or_else(|| {
+            AstorError::BankingNetworkError(format!("Bank {} not found", bank_id))
+        })?;
+        bank.health.flagged_for_review = false;
+        Ok(())
+    }
+
+    /// Settlements waiting for an unreachable bank, oldest first
+    pub async fn deferred_settlements(&self) -> Vec<DeferredSettlement> {
+        self.deferred_settlements.read().await.clone()
+    }
+
+    async fn is_reachable(&self, bank_id: &str) -> bool {
+        self.registered_banks
+            .read()
+            .await
+            .get(bank_id)
+            .map_or(true, |bank| bank.health.reachable)
+    }
+
+    /// Submit each deferred settlement whose banks are reachable again
+    ///
+    /// Every entry is attempted; those that stay deferred or fail to submit
+    /// go back on the queue in their original order.
+    async fn release_deferred_settlements(&self) -> Vec<DeferredRelease> {
+        let deferred = std::mem::take(&mut *self.deferred_settlements.write().await);
+
+        let mut report = Vec::with_capacity(deferred.len());
+        let mut still_deferred = Vec::new();
+        for settlement in deferred {
+            let deferral_id = settlement.deferral_id.clone();
+            if !self.is_reachable(&settlement.from_bank).await
+                || !self.is_reachable(&settlement.to_bank).await
+            {
+                report.push(DeferredRelease::StillDeferred { deferral_id });
+                still_deferred.push(settlement);
+                continue;
+            }
+
+            tracing::info!(
+                "Releasing deferred settlement {} ({})",
+                deferral_id,
+                settlement.reference
+            );
+            let submitted = match self
+                .ensure_can_settle(&settlement.from_bank, &settlement.to_bank)
+                .await
+            {
+                Ok(()) => {
+                    self.submit_settlement(
+                        &settlement.from_bank,
+                        &settlement.to_bank,
+                        settlement.amount,
+                        settlement.reference.clone(),
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            match submitted {
+                Ok(settlement_id) => report.push(DeferredRelease::Released {
+                    deferral_id,
+                    settlement_id,
+                }),
+                Err(e) => {
+                    tracing::warn!(
+                        "Deferred settlement {} failed to submit, keeping it queued: {}",
+                        deferral_id,
+                        e
+                    );
+                    report.push(DeferredRelease::Failed {
+                        deferral_id,
+                        reason: e.to_string(),
+                    });
+                    still_deferred.push(settlement);
+                }
+            }
+        }
+
+        // Settlements deferred while this pass ran were queued behind it
+        let mut queue = self.deferred_settlements.write().await;
+        let newly_deferred = std::mem::replace(&mut *queue, still_deferred);
+        queue.extend(newly_deferred);
+        report
+    }
+
+    /// Reject settlements involving a suspended or revoked bank
+    async fn ensure_can_settle(&self, from_bank: &str, to_bank: &str) -> Result<(), AstorError> {
+        let banks = self.registered_banks.read().await;
+        for bank_id in [from_bank, to_bank] {
+            if let Some(bank) = banks.get(bank_id) {
+                if matches!(bank.status, BankStatus::Suspended | BankStatus::Revoked) {
+                    return Err(AstorError::BankingNetworkError(format!(
+                        "Bank {} is {:?} and cannot settle",
+                        bank_id, bank.status
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Process inter-bank settlement
+    ///
+    /// Banks in different currency zones settle through the corridor between
+    /// them; see `settlement::SettlementEngine`. Settlements involving a
+    /// suspended or revoked bank are rejected. Settlements involving a bank
+    /// whose endpoint is unreachable are deferred until the next successful
+    /// health poll.
     pub async fn process_settlement(
         &self,
         from_bank: &str,
         to_bank: &str,
         amount: u64,
         reference: String,
+    ) -> Result<SettlementOutcome, AstorError> {
+        self.ensure_can_settle(from_bank, to_bank).await?;
+
+        if !self.is_reachable(from_bank).await || !self.is_reachable(to_bank).await {
+            let deferral_id = uuid::Uuid::new_v4().to_string();
+            tracing::warn!(
+                "Deferring settlement {} from {} to {}: bank endpoint unreachable",
+                reference,
+                from_bank,
+                to_bank
+            );
+            self.deferred_settlements
+                .write()
+                .await
+                .push(DeferredSettlement {
+                    deferral_id: deferral_id.clone(),
+                    from_bank: from_bank.to_string(),
+                    to_bank: to_bank.to_string(),
+                    amount,
+                    reference,
+                    deferred_at: Utc::now(),
+                });
+            return Ok(SettlementOutcome::Deferred(deferral_id));
+        }
+
+        self.submit_settlement(from_bank, to_bank, amount, reference)
+            .await
+            .map(SettlementOutcome::Submitted)
+    }
+
+    /// Hand a settlement to the engine and, with a rail configured, execute
+    /// it in the background
+    async fn submit_settlement(
+        &self,
+        from_bank: &str,
+        to_bank: &str,
+        amount: u64,
+        reference: String,
     ) -> Result<String, AstorError> {
-        self.settlement_engine
+        let settlement_id = self
+            .settlement_engine
             .process_settlement(from_bank, to_bank, amount, reference)
+            .await?;
+        if let Some(rail) = self.settlement_rail.clone() {
+            tokio::spawn(self.clone().execute_settlement(rail, settlement_id.clone()));
+        }
+        Ok(settlement_id)
+    }
+
+    /// Execute a pending settlement on `rail`, completing it or charging the
+    /// failure to the bank responsible
+    async fn execute_settlement(
+        self,
+        rail: Arc<dyn settlement::SettlementRail>,
+        settlement_id: String,
+    ) {
+        let Some(settlement) = self.settlement_engine.get_settlement(&settlement_id).await else {
+            return;
+        };
+        let receiving_endpoint = self
+            .registered_banks
+            .read()
             .await
+            .get(&settlement.to_bank)
+            .map(|bank| bank.api_endpoint.clone())
+            .unwrap_or_default();
+
+        let result = match rail.execute(&settlement, &receiving_endpoint).await {
+            Ok(()) => {
+                self.settlement_engine
+                    .complete_settlement(&settlement_id)
+                    .await
+            }
+            Err(failure) => self
+                .report_settlement_failure(&settlement_id, &failure.bank_id, &failure.reason)
+                .await
+                .map(|_| ()),
+        };
+        if let Err(e) = result {
+            tracing::error!("Settlement {} could not be finalized: {}", settlement_id, e);
+        }
+    }
+
+    /// Record that a settlement failed because of `bank_id`
+    ///
+    /// A bank reaching the engine's consecutive failure threshold is
+    /// suspended automatically and an alert is raised. Returns true if this
+    /// failure suspended the bank.
+    pub async fn report_settlement_failure(
+        &self,
+        settlement_id: &str,
+        bank_id: &str,
+        reason: &str,
+    ) -> Result<bool, AstorError> {
+        let failures = self
+            .settlement_engine
+            .fail_settlement(settlement_id, bank_id, reason)
+            .await?;
+        if !self
+            .settlement_engine
+            .exceeds_failure_threshold(bank_id)
+            .await
+        {
+            return Ok(false);
+        }
+
+        let already_suspended = self
+            .registered_banks
+            .read()
+            .await
+            .get(bank_id)
+            .map_or(true, |bank| {
+                matches!(bank.status, BankStatus::Suspended | BankStatus::Revoked)
+            });
+        if already_suspended {
+            return Ok(false);
+        }
+
+        tracing::error!(
+            "ALERT: bank {} auto-suspended after {} consecutive settlement failures (last: {})",
+            bank_id,
+            failures,
+            reason
+        );
+        self.apply_suspension(SuspensionRecord {
+            bank_id: bank_id.to_string(),
+            reason: format!(
+                "{} consecutive settlement failures; last: {}",
+                failures, reason
+            ),
+            kind: SuspensionKind::SettlementFailures {
+                consecutive_failures: failures,
+            },
+            approved_by: Vec::new(),
+            suspended_at: Utc::now(),
+        })
+        .await?;
+        Ok(true)
     }
 
     /// Get network statistics
@@ -166,3 +1057,447 @@ pub struct NetworkStats {
     pub pending_approvals: usize,
     pub suspended_banks: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::central_bank::{CentralBankConfig, DEFAULT_CURRENCY};
+    use crate::security::{KeyPair, Role};
+
+    fn test_network() -> BankingNetwork {
+        BankingNetwork::new(CentralBank::new(CentralBankConfig {
+            base_interest_rate: 0.025,
+            reserve_requirement_ratio: 0.10,
+            inflation_target: 0.02,
+            money_supply_growth_target: 0.03,
+            emergency_lending_rate: 0.05,
+        }))
+    }
+
+    fn admin(id: &str, role: Role, keypair: &KeyPair) -> Administrator {
+        Administrator {
+            id: id.to_string(),
+            public_key: keypair.public_key(),
+            role,
+            created_at: Utc::now(),
+            is_active: true,
+        }
+    }
+
+    async fn register_active_bank(network: &BankingNetwork) -> String {
+        let bank_id = network
+            .register_bank(
+                "First Astoria Bank".to_string(),
+                "LIC-001".to_string(),
+                "https://bank.example".to_string(),
+                "pk".to_string(),
+                vec![BankingService::DepositAccounts],
+            )
+            .await
+            .unwrap();
+        network.approve_bank(&bank_id).await.unwrap();
+        bank_id
+    }
+
+    #[tokio::test]
+    async fn test_suspension_requires_two_approvals() {
+        let network = test_network();
+        let bank_id = register_active_bank(&network).await;
+
+        let key_a = KeyPair::generate();
+        let key_b = KeyPair::generate();
+        let admin_a = admin("cb-admin-a", Role::CentralBankAdmin, &key_a);
+        let admin_b = admin("cb-admin-b", Role::CentralBankAdmin, &key_b);
+
+        let proposal_id = network
+            .propose_suspension(&bank_id, "Reserve shortfall".to_string())
+            .await
+            .unwrap();
+        let message = BankingNetwork::suspension_approval_message(&proposal_id);
+
+        let executed = network
+            .approve_suspension(&proposal_id, &admin_a, &key_a.sign(&message))
+            .await
+            .unwrap();
+        assert!(!executed);
+        assert_eq!(network.get_network_stats().await.suspended_banks, 0);
+
+        // The same admin cannot approve twice
+        assert!(network
+            .approve_suspension(&proposal_id, &admin_a, &key_a.sign(&message))
+            .await
+            .is_err());
+
+        let executed = network
+            .approve_suspension(&proposal_id, &admin_b, &key_b.sign(&message))
+            .await
+            .unwrap();
+        assert!(executed);
+        assert_eq!(network.get_network_stats().await.suspended_banks, 1);
+
+        let log = network.get_suspension_log().await;
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].approved_by, vec!["cb-admin-a", "cb-admin-b"]);
+    }
+
+    #[tokio::test]
+    async fn test_emergency_freeze_requires_elevated_authority() {
+        let network = test_network();
+        let bank_id = register_active_bank(&network).await;
+        let message = BankingNetwork::emergency_freeze_message(&bank_id);
+
+        let cb_key = KeyPair::generate();
+        let cb_admin = admin("cb-admin", Role::CentralBankAdmin, &cb_key);
+        assert!(network
+            .emergency_freeze_bank(
+                &bank_id,
+                &cb_admin,
+                &cb_key.sign(&message),
+                "Fraud".to_string()
+            )
+            .await
+            .is_err());
+
+        let root_key = KeyPair::generate();
+        let root = admin("root", Role::RootAdmin, &root_key);
+        network
+            .emergency_freeze_bank(
+                &bank_id,
+                &root,
+                &root_key.sign(&message),
+                "Fraud".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let log = network.get_suspension_log().await;
+        assert_eq!(
+            log[0].kind,
+            SuspensionKind::EmergencyFreeze {
+                admin_id: "root".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_directory_lists_banks_with_status_and_services() {
+        let network = test_network();
+        let active_id = register_active_bank(&network).await;
+        let pending_id = network
+            .register_bank(
+                "Bank of the Coast, Ltd".to_string(),
+                "LIC-002".to_string(),
+                "https://coast.example".to_string(),
+                "pk2".to_string(),
+                vec![BankingService::Loans, BankingService::ForeignExchange],
+            )
+            .await
+            .unwrap();
+
+        let banks = network.list_banks().await;
+        assert_eq!(banks.len(), 2);
+        assert_eq!(banks[0].bank_id, pending_id);
+        assert!(matches!(banks[0].status, BankStatus::UnderReview));
+        assert_eq!(banks[1].bank_id, active_id);
+        assert!(matches!(banks[1].status, BankStatus::Active));
+
+        let pending = network.get_bank(&pending_id).await.unwrap();
+        assert!(matches!(
+            pending.services_offered.as_slice(),
+            [BankingService::Loans, BankingService::ForeignExchange]
+        ));
+        assert!(network.get_bank("unknown").await.is_none());
+
+        let json = network
+            .export_directory(DirectoryFormat::Json)
+            .await
+            .unwrap();
+        let exported: Vec<BankDirectoryEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(exported.len(), 2);
+        assert!(!json.contains("compliance_rating"));
+
+        let csv = network
+            .export_directory(DirectoryFormat::Csv)
+            .await
+            .unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("bank_id,bank_name"));
+        assert!(lines[1].contains("\"Bank of the Coast, Ltd\""));
+        assert!(lines[1].contains(",UnderReview,"));
+        assert!(lines[1].contains(",Loans;ForeignExchange,"));
+        assert!(lines[2].contains(",Active,"));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_settlement_failures_suspend_bank() {
+        let mut network = test_network();
+        let payer = register_active_bank(&network).await;
+        let failing = register_active_bank(&network).await;
+        network.settlement_engine_mut().set_failure_threshold(3);
+
+        for attempt in 1..=3 {
+            let outcome = network
+                .process_settlement(&payer, &failing, 1_000, format!("INV-{}", attempt))
+                .await
+                .unwrap();
+            let settlement_id = outcome.settlement_id().unwrap();
+            let suspended = network
+                .report_settlement_failure(settlement_id, &failing, "endpoint unreachable")
+                .await
+                .unwrap();
+            assert_eq!(suspended, attempt == 3);
+        }
+
+        // Failed settlements leave no net position behind
+        assert_eq!(
+            network
+                .settlement_engine()
+                .net_position(&failing, DEFAULT_CURRENCY)
+                .await,
+            0
+        );
+
+        let log = network.get_suspension_log().await;
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].bank_id, failing);
+        assert_eq!(
+            log[0].kind,
+            SuspensionKind::SettlementFailures {
+                consecutive_failures: 3
+            }
+        );
+
+        assert!(network
+            .process_settlement(&payer, &failing, 1_000, "INV-4".to_string())
+            .await
+            .is_err());
+        assert!(network
+            .process_settlement(&failing, &payer, 1_000, "INV-5".to_string())
+            .await
+            .is_err());
+    }
+
+    /// Rail that delivers only to banks not listed as failing
+    struct MockRail {
+        failing: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl settlement::SettlementRail for MockRail {
+        async fn execute(
+            &self,
+            settlement: &settlement::Settlement,
+            _receiving_endpoint: &str,
+        ) -> Result<(), settlement::SettlementFailure> {
+            if self.failing.lock().unwrap().contains(&settlement.to_bank) {
+                Err(settlement::SettlementFailure {
+                    bank_id: settlement.to_bank.clone(),
+                    reason: "endpoint unreachable".to_string(),
+                })
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    async fn wait_until_settled(network: &BankingNetwork, settlement_id: &str) {
+        for _ in 0..100 {
+            let settled = network
+                .settlement_engine()
+                .get_settlement(settlement_id)
+                .await
+                .is_some_and(|s| !matches!(s.status, settlement::SettlementStatus::Pending));
+            if settled {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("settlement {} was never executed", settlement_id);
+    }
+
+    #[tokio::test]
+    async fn test_rail_failures_suspend_bank_and_completions_reset_count() {
+        let mut network = test_network();
+        let rail = Arc::new(MockRail {
+            failing: std::sync::Mutex::new(Vec::new()),
+        });
+        network.set_settlement_rail(rail.clone());
+        network.settlement_engine_mut().set_failure_threshold(2);
+        let payer = register_active_bank(&network).await;
+        let payee = register_active_bank(&network).await;
+
+        // A failure followed by a completion starts the count over
+        for (reference, fails) in [("INV-1", true), ("INV-2", false), ("INV-3", true)] {
+            *rail.failing.lock().unwrap() = if fails {
+                vec![payee.clone()]
+            } else {
+                Vec::new()
+            };
+            let outcome = network
+                .process_settlement(&payer, &payee, 1_000, reference.to_string())
+                .await
+                .unwrap();
+            wait_until_settled(&network, outcome.settlement_id().unwrap()).await;
+        }
+        assert_eq!(
+            network
+                .settlement_engine()
+                .consecutive_failures(&payee)
+                .await,
+            1
+        );
+        assert!(network.get_suspension_log().await.is_empty());
+
+        let outcome = network
+            .process_settlement(&payer, &payee, 1_000, "INV-4".to_string())
+            .await
+            .unwrap();
+        wait_until_settled(&network, outcome.settlement_id().unwrap()).await;
+        for _ in 0..100 {
+            if !network.get_suspension_log().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(matches!(
+            network.get_bank(&payee).await.unwrap().status,
+            BankStatus::Suspended
+        ));
+    }
+
+    /// Endpoint that answers until switched off
+    struct MockEndpoint {
+        up: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl BankEndpointProbe for MockEndpoint {
+        async fn probe(&self, api_endpoint: &str) -> Result<(), AstorError> {
+            if self.up.load(std::sync::atomic::Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(AstorError::NetworkError(format!(
+                    "{} connection refused",
+                    api_endpoint
+                )))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_bank_is_flagged_and_settlements_deferred() {
+        let mut network = test_network();
+        let endpoint = Arc::new(MockEndpoint {
+            up: std::sync::atomic::AtomicBool::new(true),
+        });
+        network.set_endpoint_probe(endpoint.clone());
+        network.health_config.failures_before_review = 2;
+        let payer = register_active_bank(&network).await;
+        let payee = register_active_bank(&network).await;
+
+        network.poll_bank_health().await;
+        let health = network.get_bank(&payee).await.unwrap().health;
+        assert!(health.reachable);
+        assert!(health.last_seen.is_some());
+
+        endpoint
+            .up
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+        network.poll_bank_health().await;
+        let health = network.get_bank(&payee).await.unwrap().health;
+        assert!(!health.reachable);
+        assert_eq!(health.consecutive_failures, 1);
+        assert!(!health.flagged_for_review);
+
+        network.poll_bank_health().await;
+        let health = network.get_bank(&payee).await.unwrap().health;
+        assert_eq!(health.consecutive_failures, 2);
+        assert!(health.flagged_for_review);
+        assert!(health.last_seen < health.last_checked);
+
+        let outcome = network
+            .process_settlement(&payer, &payee, 1_000, "INV-1".to_string())
+            .await
+            .unwrap();
+        let SettlementOutcome::Deferred(deferral_id) = outcome else {
+            panic!("expected the settlement to be deferred, got {:?}", outcome);
+        };
+        let deferred = network.deferred_settlements().await;
+        assert_eq!(deferred.len(), 1);
+        assert_eq!(deferred[0].deferral_id, deferral_id);
+        assert_eq!(
+            network
+                .settlement_engine()
+                .net_position(&payee, DEFAULT_CURRENCY)
+                .await,
+            0
+        );
+
+        // Recovery releases the deferred settlement but keeps the review flag
+        endpoint.up.store(true, std::sync::atomic::Ordering::SeqCst);
+        let released = network.poll_bank_health().await;
+        let [DeferredRelease::Released {
+            deferral_id: released_id,
+            settlement_id,
+        }] = released.as_slice()
+        else {
+            panic!("expected one released settlement, got {:?}", released);
+        };
+        assert_eq!(released_id, &deferral_id);
+        assert!(network.deferred_settlements().await.is_empty());
+        assert!(network
+            .settlement_engine()
+            .get_settlement(settlement_id)
+            .await
+            .is_some());
+        let health = network.get_bank(&payee).await.unwrap().health;
+        assert!(health.reachable);
+        assert!(health.flagged_for_review);
+    }
+
+    #[tokio::test]
+    async fn test_failed_release_keeps_settlement_queued_and_continues() {
+        let mut network = test_network();
+        let endpoint = Arc::new(MockEndpoint {
+            up: std::sync::atomic::AtomicBool::new(false),
+        });
+        network.set_endpoint_probe(endpoint.clone());
+        let payer = register_active_bank(&network).await;
+        let foreign = register_active_bank(&network).await;
+        let domestic = register_active_bank(&network).await;
+        // No conversion service is configured, so the EUR corridor fails
+        network
+            .settlement_engine_mut()
+            .set_bank_currency(&foreign, "EUR");
+        network.poll_bank_health().await;
+
+        let mut deferral_ids = Vec::new();
+        for (payee, reference) in [(&foreign, "INV-1"), (&domestic, "INV-2")] {
+            match network
+                .process_settlement(&payer, payee, 1_000, reference.to_string())
+                .await
+                .unwrap()
+            {
+                SettlementOutcome::Deferred(deferral_id) => deferral_ids.push(deferral_id),
+                other => panic!("expected a deferral, got {:?}", other),
+            }
+        }
+
+        endpoint.up.store(true, std::sync::atomic::Ordering::SeqCst);
+        let report = network.poll_bank_health().await;
+        assert_eq!(report.len(), 2);
+        assert!(matches!(
+            &report[0],
+            DeferredRelease::Failed { deferral_id, .. } if deferral_id == &deferral_ids[0]
+        ));
+        assert!(matches!(
+            &report[1],
+            DeferredRelease::Released { deferral_id, .. } if deferral_id == &deferral_ids[1]
+        ));
+
+        let deferred = network.deferred_settlements().await;
+        assert_eq!(deferred.len(), 1);
+        assert_eq!(deferred[0].deferral_id, deferral_ids[0]);
+    }
+}
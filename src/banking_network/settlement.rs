@@ -1,4 +1,15 @@
 //! Inter-bank settlement system
+//!
+//! Each bank settles in its own currency. When the two sides of a settlement
+//! are in different currency zones, the amount is converted through the
+//! corridor between them: the sender's position is debited in its currency,
+//! and the receiver's is credited the converted amount net of conversion and
+//! corridor fees.
+//!
+//! Submitted settlements stay pending until the settlement rail executes
+//! them. Failures are charged to the bank responsible for them. A bank
+//! reaching the configured number of consecutive failures is suspended by
+//! the banking network; any completed settlement resets its count.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -6,11 +17,102 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::central_bank::DEFAULT_CURRENCY;
+use crate::config::SettlementConfig;
+use crate::conversion::{ConversionService, FeeBreakdown};
 use crate::errors::AstorError;
+use crate::fee_calculator::FeeCalculator;
 
 pub struct SettlementEngine {
     pending_settlements: Arc<RwLock<HashMap<String, Settlement>>>,
     settlement_history: Arc<RwLock<Vec<Settlement>>>,
+    /// Bank ID -> settlement currency; unlisted banks settle in the default
+    bank_currencies: HashMap<String, String>,
+    /// Corridor fees in basis points, keyed by "FROM/TO" currency pair
+    corridor_fees_bps: HashMap<String, u32>,
+    fee_calculator: FeeCalculator,
+    conversion: Option<Arc<RwLock<ConversionService>>>,
+    /// Bank ID -> currency -> net position; negative means the bank owes
+    net_positions: Arc<RwLock<HashMap<String, HashMap<String, i64>>>>,
+    /// Bank ID -> settlement failures since its last completed settlement
+    consecutive_failures: Arc<RwLock<HashMap<String, u32>>>,
+    /// Consecutive failures that suspend a bank; 0 disables suspension
+    failure_threshold: u32,
+}
+
+/// Consecutive settlement failures that suspend a bank unless configured
+pub const DEFAULT_SETTLEMENT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Why a settlement could not be executed, and which bank is responsible
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettlementFailure {
+    pub bank_id: String,
+    pub reason: String,
+}
+
+/// Moves the funds of a submitted settlement between the two banks
+#[async_trait::async_trait]
+pub trait SettlementRail: Send + Sync {
+    async fn execute(
+        &self,
+        settlement: &Settlement,
+        receiving_endpoint: &str,
+    ) -> Result<(), SettlementFailure>;
+}
+
+/// POSTs the settlement to `{receiving_endpoint}/settlements`; a failed
+/// delivery is charged to the receiving bank
+pub struct HttpSettlementRail {
+    http_client: reqwest::Client,
+}
+
+impl HttpSettlementRail {
+    pub fn new(timeout: std::time::Duration) -> Self {
+        Self {
+            http_client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementRail for HttpSettlementRail {
+    async fn execute(
+        &self,
+        settlement: &Settlement,
+        receiving_endpoint: &str,
+    ) -> Result<(), SettlementFailure> {
+        self.http_client
+            .post(format!(
+                "{}/settlements",
+                receiving_endpoint.trim_end_matches('/')
+            ))
+            .json(settlement)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| SettlementFailure {
+                bank_id: settlement.to_bank.clone(),
+                reason: format!("Settlement delivery failed: {}", e),
+            })?;
+        Ok(())
+    }
+}
+
+/// FX leg of a settlement between banks in different currency zones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementFx {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub exchange_rate: f64,
+    /// Converted amount net of conversion fees, before the corridor fee
+    pub converted_amount: u64,
+    pub conversion_fees: FeeBreakdown,
+    pub corridor_fee: u64,
+    /// Amount credited to the receiving bank
+    pub credited_amount: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,10 +121,20 @@ pub struct Settlement {
     pub from_bank: String,
     pub to_bank: String,
     pub amount: u64,
+    /// Currency `amount` is debited in
+    #[serde(default = "default_currency")]
+    pub currency: String,
     pub reference: String,
     pub status: SettlementStatus,
     pub created_at: DateTime<Utc>,
     pub settled_at: Option<DateTime<Utc>>,
+    /// Set when the banks settle in different currencies
+    #[serde(default)]
+    pub fx: Option<SettlementFx>,
+}
+
+fn default_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,7 +151,267 @@ impl SettlementEngine {
         Self {
             pending_settlements: Arc::new(RwLock::new(HashMap::new())),
             settlement_history: Arc::new(RwLock::new(Vec::new())),
+            bank_currencies: HashMap::new(),
+            corridor_fees_bps: HashMap::new(),
+            fee_calculator: FeeCalculator::default(),
+            conversion: None,
+            net_positions: Arc::new(RwLock::new(HashMap::new())),
+            consecutive_failures: Arc::new(RwLock::new(HashMap::new())),
+            failure_threshold: DEFAULT_SETTLEMENT_FAILURE_THRESHOLD,
+        }
+    }
+
+    /// Suspend banks after `threshold` consecutive settlement failures;
+    /// 0 disables automatic suspension
+    pub fn set_failure_threshold(&mut self, threshold: u32) {
+        self.failure_threshold = threshold;
+    }
+
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+    }
+
+    /// Settlement failures charged to a bank since its last completed one
+    pub async fn consecutive_failures(&self, bank_id: &str) -> u32 {
+        self.consecutive_failures
+            .read()
+            .await
+            .get(bank_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Whether a bank's consecutive failures have reached the threshold
+    pub async fn exceeds_failure_threshold(&self, bank_id: &str) -> bool {
+        self.failure_threshold > 0
+            && self.consecutive_failures(bank_id).await >= self.failure_threshold
+    }
+
+    /// Fail a pending settlement and charge the failure to `bank_id`, one of
+    /// its two banks
+    ///
+    /// The settlement's net position changes are reversed. Returns the bank's
+    /// consecutive failure count.
+    pub async fn fail_settlement(
+        &self,
+        settlement_id: &str,
+        bank_id: &str,
+        reason: &str,
+    ) -> Result<u32, AstorError> {
+        let mut settlement = {
+            let mut pending = self.pending_settlements.write().await;
+            let settlement = pending.get(settlement_id).ok_or_else(|| {
+                AstorError::BankingNetworkError(format!(
+                    "Settlement {} is not pending",
+                    settlement_id
+                ))
+            })?;
+            if settlement.from_bank != bank_id && settlement.to_bank != bank_id {
+                return Err(AstorError::BankingNetworkError(format!(
+                    "Bank {} is not party to settlement {}",
+                    bank_id, settlement_id
+                )));
+            }
+
+            let credited_amount = settlement
+                .fx
+                .as_ref()
+                .map_or(settlement.amount, |fx| fx.credited_amount);
+            let to_currency = settlement
+                .fx
+                .as_ref()
+                .map_or(settlement.currency.as_str(), |fx| fx.to_currency.as_str());
+            self.shift_positions(
+                (&settlement.to_bank, to_currency, credited_amount),
+                (
+                    &settlement.from_bank,
+                    &settlement.currency,
+                    settlement.amount,
+                ),
+            )
+            .await?;
+            pending.remove(settlement_id).unwrap()
+        };
+
+        let failures = {
+            let mut counts = self.consecutive_failures.write().await;
+            let count = counts.entry(bank_id.to_string()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        tracing::warn!(
+            "Settlement {} failed ({} consecutive for bank {}): {}",
+            settlement_id,
+            failures,
+            bank_id,
+            reason
+        );
+
+        settlement.status = SettlementStatus::Failed;
+        self.settlement_history.write().await.push(settlement);
+        Ok(failures)
+    }
+
+    /// Apply the configured bank currencies and corridor fees
+    pub fn configure(&mut self, config: &SettlementConfig) -> Result<(), AstorError> {
+        for (corridor, fee_bps) in &config.corridor_fees_bps {
+            let Some((from, to)) = corridor.split_once('/') else {
+                return Err(AstorError::ConfigurationError(format!(
+                    "Settlement corridor '{}' must be written FROM/TO",
+                    corridor
+                )));
+            };
+            if *fee_bps > 10_000 {
+                return Err(AstorError::ConfigurationError(format!(
+                    "Corridor fee of {} bps for {} exceeds 100%",
+                    fee_bps, corridor
+                )));
+            }
+            self.set_corridor_fee(from.trim(), to.trim(), *fee_bps);
+        }
+        for (bank_id, currency) in &config.bank_currencies {
+            self.set_bank_currency(bank_id, currency);
+        }
+        Ok(())
+    }
+
+    /// Set the currency a bank settles in
+    pub fn set_bank_currency(&mut self, bank_id: &str, currency: &str) {
+        self.bank_currencies
+            .insert(bank_id.to_string(), currency.to_uppercase());
+    }
+
+    pub fn bank_currency(&self, bank_id: &str) -> &str {
+        self.bank_currencies
+            .get(bank_id)
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_CURRENCY)
+    }
+
+    /// Charge `fee_bps` on settlements routed from `from` to `to`
+    pub fn set_corridor_fee(&mut self, from: &str, to: &str, fee_bps: u32) {
+        self.corridor_fees_bps
+            .insert(corridor_key(from, to), fee_bps);
+    }
+
+    /// Round corridor fees the same way as every other fee
+    pub fn set_fee_calculator(&mut self, fee_calculator: FeeCalculator) {
+        self.fee_calculator = fee_calculator;
+    }
+
+    /// Price cross-currency settlements through `conversion`
+    pub fn set_conversion_service(&mut self, conversion: Arc<RwLock<ConversionService>>) {
+        self.conversion = Some(conversion);
+    }
+
+    /// Debit one bank's net position and credit another's, each given as
+    /// (bank, currency, amount), changing neither if a result would not fit
+    async fn shift_positions(
+        &self,
+        (debit_bank, debit_currency, debit_amount): (&str, &str, u64),
+        (credit_bank, credit_currency, credit_amount): (&str, &str, u64),
+    ) -> Result<(), AstorError> {
+        let overflow = || {
+            AstorError::BankingNetworkError(format!(
+                "Settlement of {} {} overflows the net positions of {} and {}",
+                debit_amount, debit_currency, debit_bank, credit_bank
+            ))
+        };
+        let mut positions = self.net_positions.write().await;
+        let position =
+            |positions: &HashMap<String, HashMap<String, i64>>, bank: &str, currency: &str| {
+                positions
+                    .get(bank)
+                    .and_then(|positions| positions.get(currency))
+                    .copied()
+                    .unwrap_or(0)
+            };
+
+        let debited = i64::try_from(debit_amount)
+            .ok()
+            .and_then(|amount| position(&positions, debit_bank, debit_currency).checked_sub(amount))
+            .ok_or_else(overflow)?;
+        let before_credit = if (credit_bank, credit_currency) == (debit_bank, debit_currency) {
+            debited
+        } else {
+            position(&positions, credit_bank, credit_currency)
+        };
+        let credited = i64::try_from(credit_amount)
+            .ok()
+            .and_then(|amount| before_credit.checked_add(amount))
+            .ok_or_else(overflow)?;
+
+        positions
+            .entry(debit_bank.to_string())
+            .or_default()
+            .insert(debit_currency.to_string(), debited);
+        positions
+            .entry(credit_bank.to_string())
+            .or_default()
+            .insert(credit_currency.to_string(), credited);
+        Ok(())
+    }
+
+    /// Net position of a bank in one currency; negative means it owes
+    pub async fn net_position(&self, bank_id: &str, currency: &str) -> i64 {
+        self.net_positions
+            .read()
+            .await
+            .get(bank_id)
+            .and_then(|positions| positions.get(&currency.to_uppercase()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Settlement still pending or already settled
+    pub async fn get_settlement(&self, settlement_id: &str) -> Option<Settlement> {
+        if let Some(settlement) = self.pending_settlements.read().await.get(settlement_id) {
+            return Some(settlement.clone());
         }
+        self.settlement_history
+            .read()
+            .await
+            .iter()
+            .find(|settlement| settlement.settlement_id == settlement_id)
+            .cloned()
+    }
+
+    /// Convert a settlement amount through the corridor between two zones
+    async fn route_through_corridor(
+        &self,
+        amount: u64,
+        from_currency: &str,
+        to_currency: &str,
+    ) -> Result<SettlementFx, AstorError> {
+        let conversion = self.conversion.as_ref().ok_or_else(|| {
+            AstorError::BankingNetworkError(format!(
+                "No conversion service for the {}/{} settlement corridor",
+                from_currency, to_currency
+            ))
+        })?;
+        let quote = conversion
+            .read()
+            .await
+            .quote_conversion(amount, from_currency, to_currency)?;
+
+        let fee_bps = self
+            .corridor_fees_bps
+            .get(&corridor_key(from_currency, to_currency))
+            .copied()
+            .unwrap_or(0);
+        let corridor_fee = self
+            .fee_calculator
+            .percentage_fee(quote.converted_amount, fee_bps as f64 / 10_000.0);
+
+        Ok(SettlementFx {
+            from_currency: from_currency.to_string(),
+            to_currency: to_currency.to_string(),
+            exchange_rate: quote.exchange_rate,
+            converted_amount: quote.converted_amount,
+            conversion_fees: quote.fees,
+            corridor_fee,
+            credited_amount: quote.converted_amount - corridor_fee,
+        })
     }
 
     pub async fn process_settlement(
@@ -50,39 +422,81 @@ impl SettlementEngine {
         reference: String,
     ) -> Result<String, AstorError> {
         let settlement_id = uuid::Uuid::new_v4().to_string();
+        let from_currency = self.bank_currency(from_bank).to_string();
+        let to_currency = self.bank_currency(to_bank).to_string();
+
+        let fx = if from_currency == to_currency {
+            None
+        } else {
+            Some(
+                self.route_through_corridor(amount, &from_currency, &to_currency)
+                    .await?,
+            )
+        };
+        let credited_amount = fx.as_ref().map_or(amount, |fx| fx.credited_amount);
+
+        self.shift_positions(
+            (from_bank, &from_currency, amount),
+            (to_bank, &to_currency, credited_amount),
+        )
+        .await?;
+
+        if let Some(fx) = &fx {
+            tracing::info!(
+                "Settlement {} routed {} {} -> {} {} via the {}/{} corridor (fee {})",
+                settlement_id,
+                amount,
+                from_currency,
+                fx.credited_amount,
+                to_currency,
+                from_currency,
+                to_currency,
+                fx.corridor_fee
+            );
+        }
 
         let settlement = Settlement {
             settlement_id: settlement_id.clone(),
             from_bank: from_bank.to_string(),
             to_bank: to_bank.to_string(),
             amount,
+            currency: from_currency,
             reference,
             status: SettlementStatus::Pending,
             created_at: Utc::now(),
             settled_at: None,
+            fx,
         };
 
         let mut pending = self.pending_settlements.write().await;
         pending.insert(settlement_id.clone(), settlement);
 
-        // In production, this would trigger actual settlement processing
-        tokio::spawn(self.clone().execute_settlement(settlement_id.clone()));
-
         Ok(settlement_id)
     }
 
-    async fn execute_settlement(self, settlement_id: String) -> Result<(), AstorError> {
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await; // Simulate processing
+    /// Mark a pending settlement executed, resetting both banks'
+    /// consecutive failure counts
+    pub async fn complete_settlement(&self, settlement_id: &str) -> Result<(), AstorError> {
+        let mut settlement = self
+            .pending_settlements
+            .write()
+            .await
+            .remove(settlement_id)
+            .ok_or_else(|| {
+                AstorError::BankingNetworkError(format!(
+                    "Settlement {} is not pending",
+                    settlement_id
+                ))
+            })?;
+        settlement.status = SettlementStatus::Completed;
+        settlement.settled_at = Some(Utc::now());
 
-        let mut pending = self.pending_settlements.write().await;
-        if let Some(mut settlement) = pending.remove(&settlement_id) {
-            settlement.status = SettlementStatus::Completed;
-            settlement.settled_at = Some(Utc::now());
-
-            let mut history = self.settlement_history.write().await;
-            history.push(settlement);
-        }
+        let mut failures = self.consecutive_failures.write().await;
+        failures.remove(&settlement.from_bank);
+        failures.remove(&settlement.to_bank);
+        drop(failures);
 
+        self.settlement_history.write().await.push(settlement);
         Ok(())
     }
 }
@@ -92,6 +506,124 @@ impl Clone for SettlementEngine {
         Self {
             pending_settlements: Arc::clone(&self.pending_settlements),
             settlement_history: Arc::clone(&self.settlement_history),
+            bank_currencies: self.bank_currencies.clone(),
+            corridor_fees_bps: self.corridor_fees_bps.clone(),
+            fee_calculator: self.fee_calculator,
+            conversion: self.conversion.clone(),
+            net_positions: Arc::clone(&self.net_positions),
+            consecutive_failures: Arc::clone(&self.consecutive_failures),
+            failure_threshold: self.failure_threshold,
         }
     }
 }
+
+fn corridor_key(from: &str, to: &str) -> String {
+    format!("{}/{}", from.to_uppercase(), to.to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::ExchangeRate;
+
+    #[tokio::test]
+    async fn test_usd_to_eur_settlement_converts_net_position() {
+        let mut conversion = ConversionService::new();
+        conversion.update_exchange_rate(ExchangeRate {
+            from_currency: "USD".to_string(),
+            to_currency: "EUR".to_string(),
+            rate: 0.9,
+            bid: 0.9,
+            ask: 0.9,
+            timestamp: Utc::now(),
+            source: "test".to_string(),
+            volatility: 0.0,
+            daily_change: 0.0,
+        });
+        let quote = conversion
+            .quote_conversion(1_000_000, "USD", "EUR")
+            .unwrap();
+        let conversion = Arc::new(RwLock::new(conversion));
+
+        let mut engine = SettlementEngine::new();
+        engine.set_bank_currency("us-bank", "usd");
+        engine.set_bank_currency("eu-bank", "EUR");
+        engine.set_corridor_fee("USD", "EUR", 25);
+        engine.set_conversion_service(conversion);
+
+        let settlement_id = engine
+            .process_settlement("us-bank", "eu-bank", 1_000_000, "INV-7".to_string())
+            .await
+            .unwrap();
+
+        // 900,000 EUR less the 0.12% conversion fee, then 0.25% corridor fee
+        assert_eq!(quote.converted_amount, 898_920);
+        let expected_credit = 898_920 - 2_247;
+        assert_eq!(engine.net_position("us-bank", "USD").await, -1_000_000);
+        assert_eq!(engine.net_position("eu-bank", "EUR").await, expected_credit);
+        assert_eq!(engine.net_position("eu-bank", "USD").await, 0);
+
+        let settlement = engine.get_settlement(&settlement_id).await.unwrap();
+        assert_eq!(settlement.currency, "USD");
+        let fx = settlement.fx.unwrap();
+        assert_eq!(fx.corridor_fee, 2_247);
+        assert_eq!(fx.credited_amount as i64, expected_credit);
+        assert_eq!(fx.exchange_rate, 0.9);
+
+        // The corridor fee is rounded by the shared fee calculator
+        engine.set_fee_calculator(FeeCalculator::new(crate::fee_calculator::FeeRounding::Up));
+        let settlement_id = engine
+            .process_settlement("us-bank", "eu-bank", 1_000_000, "INV-8".to_string())
+            .await
+            .unwrap();
+        let fx = engine
+            .get_settlement(&settlement_id)
+            .await
+            .unwrap()
+            .fx
+            .unwrap();
+        assert_eq!(fx.corridor_fee, 2_248);
+    }
+
+    #[test]
+    fn test_configure_routes_banks_and_rejects_bad_corridors() {
+        let mut config = SettlementConfig::default();
+        config
+            .bank_currencies
+            .insert("eu-bank".to_string(), "eur".to_string());
+        config.corridor_fees_bps.insert("USD/EUR".to_string(), 25);
+
+        let mut engine = SettlementEngine::new();
+        engine.configure(&config).unwrap();
+        assert_eq!(engine.bank_currency("eu-bank"), "EUR");
+        assert_eq!(engine.bank_currency("us-bank"), DEFAULT_CURRENCY);
+        assert_eq!(engine.corridor_fees_bps[&corridor_key("usd", "eur")], 25);
+
+        config.corridor_fees_bps.insert("USD-GBP".to_string(), 25);
+        assert!(matches!(
+            SettlementEngine::new().configure(&config),
+            Err(AstorError::ConfigurationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_overflowing_settlement_leaves_positions_unchanged() {
+        let engine = SettlementEngine::new();
+        engine
+            .process_settlement("bank-a", "bank-b", 1_000, "INV-1".to_string())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            engine
+                .process_settlement("bank-a", "bank-b", u64::MAX, "INV-2".to_string())
+                .await,
+            Err(AstorError::BankingNetworkError(_))
+        ));
+        assert_eq!(
+            engine.net_position("bank-a", DEFAULT_CURRENCY).await,
+            -1_000
+        );
+        assert_eq!(engine.net_position("bank-b", DEFAULT_CURRENCY).await, 1_000);
+    }
+}
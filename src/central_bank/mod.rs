@@ -4,13 +4,19 @@
 // pub mod reserve_management;
 // pub mod interest_rates;
 // pub mod money_supply;
+pub mod proof_of_reserve;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::RangeBounds;
 
+use crate::banking_network::csv_field;
 use crate::errors::AstorError;
 
+/// Currency code used when no currency is specified
+pub const DEFAULT_CURRENCY: &str = "ASTOR";
+
 /// Central bank configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CentralBankConfig {
@@ -24,10 +30,63 @@ pub struct CentralBankConfig {
 /// Central bank operations
 pub struct CentralBank {
     config: CentralBankConfig,
-    total_money_supply: u64,
-    reserve_balances: HashMap<String, u64>, // Bank ID -> Reserve Balance
-    interest_rates: HashMap<String, f64>,   // Rate type -> Rate
+    total_money_supply: HashMap<String, u64>, // Currency code -> Issued supply
+    reserve_balances: HashMap<String, u64>,   // Bank ID -> Reserve Balance
+    interest_rates: HashMap<String, f64>,     // Rate type -> Rate
     monetary_policy_decisions: Vec<MonetaryPolicyDecision>,
+    issuance_schedules: Vec<IssuanceSchedule>,
+    /// Time between tranches of a gradual issuance
+    issuance_period: Duration,
+    /// End of the last period interest on reserves was paid for
+    reserve_interest_paid_through: DateTime<Utc>,
+    /// Interest accrued to each bank but not yet paid, carried to the next
+    /// payment; within half a unit of zero
+    reserve_interest_carry: HashMap<String, f64>,
+}
+
+/// Issuance spread over equal periods instead of minted at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuanceSchedule {
+    pub schedule_id: String,
+    pub currency: String,
+    pub total_amount: u64,
+    pub per_period: u64,
+    pub periods: u32,
+    pub justification: String,
+    pub created_at: DateTime<Utc>,
+    pub next_tranche_at: DateTime<Utc>,
+    /// Tranches minted so far, oldest first
+    pub tranches: Vec<IssuanceTranche>,
+    pub status: IssuanceScheduleStatus,
+}
+
+impl IssuanceSchedule {
+    /// Amount minted so far
+    pub fn issued_amount(&self) -> u64 {
+        self.tranches.iter().map(|tranche| tranche.amount).sum()
+    }
+
+    /// Amount still to be minted, or that was left unminted on cancellation
+    pub fn remaining_amount(&self) -> u64 {
+        self.total_amount - self.issued_amount()
+    }
+}
+
+/// A single executed tranche of a gradual issuance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuanceTranche {
+    pub sequence: u32,
+    pub amount: u64,
+    /// Monetary policy decision recording the mint
+    pub decision_id: String,
+    pub executed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssuanceScheduleStatus {
+    Active,
+    Completed,
+    Cancelled { reason: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,12 +109,99 @@ pub enum PolicyDecisionType {
         new_ratio: f64,
     },
     MoneySupplyAdjustment {
+        currency: String,
         amount: i64,
     }, // Positive = increase, negative = decrease
     EmergencyMeasure {
         measure_type: String,
         details: String,
     },
+    /// Interest credited to a bank's reserve balance
+    InterestOnReserves {
+        bank_id: String,
+        rate: f64,
+        amount: u64,
+    },
+}
+
+/// Kind of a policy decision, without its parameters, for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PolicyDecisionKind {
+    InterestRateChange,
+    ReserveRequirementChange,
+    MoneySupplyAdjustment,
+    EmergencyMeasure,
+    InterestOnReserves,
+}
+
+impl PolicyDecisionType {
+    pub fn kind(&self) -> PolicyDecisionKind {
+        match self {
+            PolicyDecisionType::InterestRateChange { .. } => PolicyDecisionKind::InterestRateChange,
+            PolicyDecisionType::ReserveRequirementChange { .. } => {
+                PolicyDecisionKind::ReserveRequirementChange
+            }
+            PolicyDecisionType::MoneySupplyAdjustment { .. } => {
+                PolicyDecisionKind::MoneySupplyAdjustment
+            }
+            PolicyDecisionType::EmergencyMeasure { .. } => PolicyDecisionKind::EmergencyMeasure,
+            PolicyDecisionType::InterestOnReserves { .. } => PolicyDecisionKind::InterestOnReserves,
+        }
+    }
+
+    /// Decision parameters as `key=value` pairs separated by `;`
+    fn details(&self) -> String {
+        match self {
+            PolicyDecisionType::InterestRateChange { old_rate, new_rate } => {
+                format!("old_rate={};new_rate={}", old_rate, new_rate)
+            }
+            PolicyDecisionType::ReserveRequirementChange {
+                old_ratio,
+                new_ratio,
+            } => format!("old_ratio={};new_ratio={}", old_ratio, new_ratio),
+            PolicyDecisionType::MoneySupplyAdjustment { currency, amount } => {
+                format!("currency={};amount={}", currency, amount)
+            }
+            PolicyDecisionType::EmergencyMeasure {
+                measure_type,
+                details,
+            } => format!("measure_type={};details={}", measure_type, details),
+            PolicyDecisionType::InterestOnReserves {
+                bank_id,
+                rate,
+                amount,
+            } => format!("bank_id={};rate={};amount={}", bank_id, rate, amount),
+        }
+    }
+}
+
+impl MonetaryPolicyDecision {
+    const CSV_HEADER: &'static str =
+        "decision_id,decision_type,effective_date,details,rationale,impact_assessment";
+
+    fn to_csv_row(&self) -> String {
+        [
+            self.decision_id.clone(),
+            format!("{:?}", self.decision_type.kind()),
+            self.effective_date.to_rfc3339(),
+            self.decision_type.details(),
+            self.rationale.clone(),
+            self.impact_assessment.clone(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Output formats for published policy decisions
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DecisionExportFormat {
+    /// Pretty-printed JSON array of decisions
+    Json,
+    /// RFC 4180 CSV with a header row; parameters are in `details`
+    Csv,
 }
 
 impl CentralBank {
@@ -67,33 +213,191 @@ impl CentralBank {
 
         Self {
             config,
-            total_money_supply: 0,
+            total_money_supply: HashMap::new(),
             reserve_balances: HashMap::new(),
             interest_rates,
             monetary_policy_decisions: Vec::new(),
+            issuance_schedules: Vec::new(),
+            issuance_period: Duration::days(1),
+            reserve_interest_paid_through: Utc::now(),
+            reserve_interest_carry: HashMap::new(),
+        }
+    }
+
+    /// Time between tranches of gradual issuances scheduled from now on
+    pub fn set_issuance_period(&mut self, period: Duration) {
+        self.issuance_period = period;
+    }
+
+    /// Spread an issuance of `total` over `periods` tranches of `per_period`
+    ///
+    /// Every tranche mints `per_period` except the last, which mints the
+    /// remainder, so `total` must need exactly `periods` tranches. The first
+    /// tranche is due immediately and later ones one issuance period apart;
+    /// tranches are minted by `execute_due_tranches`.
+    pub fn schedule_gradual_issuance(
+        &mut self,
+        total: u64,
+        per_period: u64,
+        periods: u32,
+        justification: String,
+    ) -> Result<String, AstorError> {
+        if total == 0 || per_period == 0 || periods == 0 {
+            return Err(AstorError::CentralBankError(
+                "Gradual issuance needs a positive total, tranche size and period count"
+                    .to_string(),
+            ));
+        }
+
+        let covered = |n: u32| per_period.checked_mul(n as u64).unwrap_or(u64::MAX);
+        if covered(periods - 1) >= total || covered(periods) < total {
+            return Err(AstorError::CentralBankError(format!(
+                "{} tranches of {} cannot issue exactly {}",
+                periods, per_period, total
+            )));
         }
+
+        let now = Utc::now();
+        let schedule = IssuanceSchedule {
+            schedule_id: uuid::Uuid::new_v4().to_string(),
+            currency: DEFAULT_CURRENCY.to_string(),
+            total_amount: total,
+            per_period,
+            periods,
+            justification,
+            created_at: now,
+            next_tranche_at: now,
+            tranches: Vec::new(),
+            status: IssuanceScheduleStatus::Active,
+        };
+
+        tracing::info!(
+            "Scheduled gradual issuance {} of {} {} in {} tranches",
+            schedule.schedule_id,
+            total,
+            schedule.currency,
+            periods
+        );
+        let schedule_id = schedule.schedule_id.clone();
+        self.issuance_schedules.push(schedule);
+        Ok(schedule_id)
+    }
+
+    /// Mint every tranche due at or before `now`, returning the decision IDs
+    ///
+    /// Tranches missed while the scheduler was not running are caught up one
+    /// by one, each recorded as its own decision.
+    pub fn execute_due_tranches(&mut self, now: DateTime<Utc>) -> Result<Vec<String>, AstorError> {
+        let period = self.issuance_period;
+        let mut decision_ids = Vec::new();
+
+        for index in 0..self.issuance_schedules.len() {
+            loop {
+                let schedule = &self.issuance_schedules[index];
+                if schedule.status != IssuanceScheduleStatus::Active
+                    || schedule.next_tranche_at > now
+                {
+                    break;
+                }
+
+                let sequence = schedule.tranches.len() as u32 + 1;
+                let amount = schedule.per_period.min(schedule.remaining_amount());
+                let currency = schedule.currency.clone();
+                let rationale = format!(
+                    "{} (tranche {} of {}, schedule {})",
+                    schedule.justification, sequence, schedule.periods, schedule.schedule_id
+                );
+
+                let decision_id = self.issue_currency(&currency, amount, rationale)?;
+
+                let schedule = &mut self.issuance_schedules[index];
+                schedule.tranches.push(IssuanceTranche {
+                    sequence,
+                    amount,
+                    decision_id: decision_id.clone(),
+                    executed_at: now,
+                });
+                schedule.next_tranche_at += period;
+                if schedule.remaining_amount() == 0 {
+                    schedule.status = IssuanceScheduleStatus::Completed;
+                }
+                decision_ids.push(decision_id);
+            }
+        }
+
+        Ok(decision_ids)
+    }
+
+    /// Stop a gradual issuance; tranches already minted stay issued
+    ///
+    /// Returns the amount that will no longer be minted.
+    pub fn cancel_gradual_issuance(
+        &mut self,
+        schedule_id: &str,
+        reason: String,
+    ) -> Result<u64, AstorError> {
+        let schedule = self
+            .issuance_schedules
+            .iter_mut()
+            .find(|schedule| schedule.schedule_id == schedule_id)
+            .ok_or_else(|| {
+                AstorError::CentralBankError(format!("Unknown issuance schedule {}", schedule_id))
+            })?;
+
+        if schedule.status != IssuanceScheduleStatus::Active {
+            return Err(AstorError::CentralBankError(format!(
+                "Issuance schedule {} is no longer active",
+                schedule_id
+            )));
+        }
+
+        tracing::warn!(
+            "Cancelled gradual issuance {} with {} unminted: {}",
+            schedule_id,
+            schedule.remaining_amount(),
+            reason
+        );
+        schedule.status = IssuanceScheduleStatus::Cancelled { reason };
+        Ok(schedule.remaining_amount())
+    }
+
+    pub fn get_issuance_schedule(&self, schedule_id: &str) -> Option<&IssuanceSchedule> {
+        self.issuance_schedules
+            .iter()
+            .find(|schedule| schedule.schedule_id == schedule_id)
     }
 
     /// Issue new currency (monetary expansion)
     pub fn issue_currency(
         &mut self,
+        currency: &str,
         amount: u64,
         justification: String,
     ) -> Result<String, AstorError> {
+        let currency = currency.to_uppercase();
+        if currency.is_empty() {
+            return Err(AstorError::CentralBankError(
+                "Currency code is required".to_string(),
+            ));
+        }
+
+        let current_supply = self.get_money_supply(&currency);
+        let new_supply = current_supply.checked_add(amount).ok_or_else(|| {
+            AstorError::CentralBankError(format!("{} money supply overflow", currency))
+        })?;
+
         let decision = MonetaryPolicyDecision {
             decision_id: uuid::Uuid::new_v4().to_string(),
             decision_type: PolicyDecisionType::MoneySupplyAdjustment {
+                currency: currency.clone(),
                 amount: amount as i64,
             },
             effective_date: Utc::now(),
             rationale: justification,
-            impact_assessment: format!("Money supply increased by {} ASTOR", amount),
+            impact_assessment: format!("Money supply increased by {} {}", amount, currency),
         };
 
-        self.total_money_supply = self
-            .total_money_supply
-            .checked_add(amount)
-            .ok_or_else(|| AstorError::CentralBankError("Money supply overflow".to_string()))?;
+        self.total_money_supply.insert(currency, new_supply);
 
         self.monetary_policy_decisions.push(decision.clone());
         Ok(decision.decision_id)
@@ -132,15 +436,146 @@ impl CentralBank {
         Ok(())
     }
 
+    /// End of the last period interest on reserves was paid for
+    pub fn reserve_interest_paid_through(&self) -> DateTime<Utc> {
+        self.reserve_interest_paid_through
+    }
+
+    /// Pay interest on reserves accrued since the last payment
+    ///
+    /// See `pay_interest_on_reserves_at`.
+    pub fn pay_interest_on_reserves(&mut self) -> HashMap<String, u64> {
+        self.pay_interest_on_reserves_at(Utc::now())
+    }
+
+    /// Credit each bank's reserves with interest at the deposit rate for the
+    /// period ending at `now`, returning the amount paid to each bank
+    ///
+    /// Interest is simple, on an actual/365 basis, rounded to the nearest
+    /// unit. What rounding leaves unpaid or overpays is carried into the
+    /// bank's next payment, so frequent payments add up to the same interest
+    /// as infrequent ones. Each payment is recorded as a policy decision. A
+    /// rate at or below zero pays nothing; the period still ends at `now`.
+    pub fn pay_interest_on_reserves_at(&mut self, now: DateTime<Utc>) -> HashMap<String, u64> {
+        let elapsed = now - self.reserve_interest_paid_through;
+        if elapsed <= Duration::zero() {
+            return HashMap::new();
+        }
+        self.reserve_interest_paid_through = now;
+
+        let rate = self.get_interest_rate("deposit_rate").unwrap_or(0.0);
+        if rate <= 0.0 {
+            return HashMap::new();
+        }
+        let year_fraction = elapsed.num_seconds() as f64 / Duration::days(365).num_seconds() as f64;
+
+        let mut payments = HashMap::new();
+        for (bank_id, balance) in self.reserve_balances.iter_mut() {
+            let carry = self
+                .reserve_interest_carry
+                .entry(bank_id.clone())
+                .or_insert(0.0);
+            let accrued = *balance as f64 * rate * year_fraction + *carry;
+            let interest = accrued.round().max(0.0) as u64;
+            *carry = accrued - interest as f64;
+            if interest == 0 {
+                continue;
+            }
+            *balance = balance.saturating_add(interest);
+
+            self.monetary_policy_decisions.push(MonetaryPolicyDecision {
+                decision_id: uuid::Uuid::new_v4().to_string(),
+                decision_type: PolicyDecisionType::InterestOnReserves {
+                    bank_id: bank_id.clone(),
+                    rate,
+                    amount: interest,
+                },
+                effective_date: now,
+                rationale: format!("Interest on reserves for {} days", elapsed.num_days()),
+                impact_assessment: format!(
+                    "Reserves of {} increased by {} {}",
+                    bank_id, interest, DEFAULT_CURRENCY
+                ),
+            });
+            payments.insert(bank_id.clone(), interest);
+        }
+
+        payments
+    }
+
     /// Get current interest rate
     pub fn get_interest_rate(&self, rate_type: &str) -> Option<f64> {
         self.interest_rates.get(rate_type).copied()
     }
 
+    /// Get issued supply of a single currency
+    pub fn get_money_supply(&self, currency: &str) -> u64 {
+        self.total_money_supply
+            .get(&currency.to_uppercase())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Policy decisions effective within `range`, oldest first
+    pub fn decisions_in_range<R: RangeBounds<DateTime<Utc>>>(
+        &self,
+        range: R,
+    ) -> Vec<&MonetaryPolicyDecision> {
+        self.decisions_matching(range, None)
+    }
+
+    /// Policy decisions of one kind effective within `range`, oldest first
+    pub fn decisions_of_kind<R: RangeBounds<DateTime<Utc>>>(
+        &self,
+        range: R,
+        kind: PolicyDecisionKind,
+    ) -> Vec<&MonetaryPolicyDecision> {
+        self.decisions_matching(range, Some(kind))
+    }
+
+    fn decisions_matching<R: RangeBounds<DateTime<Utc>>>(
+        &self,
+        range: R,
+        kind: Option<PolicyDecisionKind>,
+    ) -> Vec<&MonetaryPolicyDecision> {
+        let mut decisions: Vec<&MonetaryPolicyDecision> = self
+            .monetary_policy_decisions
+            .iter()
+            .filter(|decision| range.contains(&decision.effective_date))
+            .filter(|decision| kind.map_or(true, |kind| decision.decision_type.kind() == kind))
+            .collect();
+        decisions.sort_by_key(|decision| decision.effective_date);
+        decisions
+    }
+
+    /// Export policy decisions for transparency publication
+    ///
+    /// `kind` restricts the export to one kind of decision.
+    pub fn export_decisions<R: RangeBounds<DateTime<Utc>>>(
+        &self,
+        range: R,
+        kind: Option<PolicyDecisionKind>,
+        format: DecisionExportFormat,
+    ) -> Result<String, AstorError> {
+        let decisions = self.decisions_matching(range, kind);
+
+        match format {
+            DecisionExportFormat::Json => {
+                serde_json::to_string_pretty(&decisions).map_err(AstorError::from)
+            }
+            DecisionExportFormat::Csv => Ok(std::iter::once(
+                MonetaryPolicyDecision::CSV_HEADER.to_string(),
+            )
+            .chain(decisions.iter().map(|decision| decision.to_csv_row()))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        }
+    }
+
     /// Get money supply statistics
     pub fn get_money_supply_stats(&self) -> MoneySupplyStats {
         MoneySupplyStats {
-            total_supply: self.total_money_supply,
+            supply_by_currency: self.total_money_supply.clone(),
             reserve_balances: self.reserve_balances.clone(),
             base_interest_rate: self.config.base_interest_rate,
             inflation_target: self.config.inflation_target,
@@ -150,8 +585,261 @@ impl CentralBank {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoneySupplyStats {
-    pub total_supply: u64,
+    pub supply_by_currency: HashMap<String, u64>, // Currency code -> Issued supply
     pub reserve_balances: HashMap<String, u64>,
     pub base_interest_rate: f64,
     pub inflation_target: f64,
 }
+
+impl MoneySupplyStats {
+    /// Issued supply of a single currency
+    pub fn supply_of(&self, currency: &str) -> u64 {
+        self.supply_by_currency
+            .get(&currency.to_uppercase())
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> CentralBankConfig {
+        CentralBankConfig {
+            base_interest_rate: 0.025,
+            reserve_requirement_ratio: 0.10,
+            inflation_target: 0.02,
+            money_supply_growth_target: 0.03,
+            emergency_lending_rate: 0.05,
+        }
+    }
+
+    #[test]
+    fn test_per_currency_supply_is_tracked_independently() {
+        let mut central_bank = CentralBank::new(test_config());
+
+        central_bank
+            .issue_currency(DEFAULT_CURRENCY, 1_000, "Initial issuance".to_string())
+            .unwrap();
+        central_bank
+            .issue_currency("EUR", 250, "Euro liquidity".to_string())
+            .unwrap();
+        central_bank
+            .issue_currency(DEFAULT_CURRENCY, 500, "Expansion".to_string())
+            .unwrap();
+
+        let stats = central_bank.get_money_supply_stats();
+        assert_eq!(stats.supply_of(DEFAULT_CURRENCY), 1_500);
+        assert_eq!(stats.supply_of("EUR"), 250);
+        assert_eq!(stats.supply_of("USD"), 0);
+        assert_eq!(stats.supply_by_currency.len(), 2);
+    }
+
+    #[test]
+    fn test_gradual_issuance_tranches_sum_to_total() {
+        let mut central_bank = CentralBank::new(test_config());
+        let schedule_id = central_bank
+            .schedule_gradual_issuance(1_000, 300, 4, "Gradual expansion".to_string())
+            .unwrap();
+
+        let start = Utc::now();
+        for day in 0..6 {
+            central_bank
+                .execute_due_tranches(start + Duration::days(day))
+                .unwrap();
+        }
+
+        let schedule = central_bank.get_issuance_schedule(&schedule_id).unwrap();
+        let amounts: Vec<u64> = schedule.tranches.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![300, 300, 300, 100]);
+        assert_eq!(schedule.status, IssuanceScheduleStatus::Completed);
+        assert_eq!(central_bank.get_money_supply(DEFAULT_CURRENCY), 1_000);
+        // Each tranche is its own recorded decision
+        assert_eq!(central_bank.monetary_policy_decisions.len(), 4);
+
+        assert!(central_bank
+            .schedule_gradual_issuance(1_000, 100, 4, "Too few tranches".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn test_cancelled_issuance_stops_minting() {
+        let mut central_bank = CentralBank::new(test_config());
+        let schedule_id = central_bank
+            .schedule_gradual_issuance(500, 100, 5, "Gradual expansion".to_string())
+            .unwrap();
+
+        let start = Utc::now();
+        central_bank.execute_due_tranches(start).unwrap();
+        central_bank
+            .execute_due_tranches(start + Duration::days(1))
+            .unwrap();
+        assert_eq!(central_bank.get_money_supply(DEFAULT_CURRENCY), 200);
+
+        let unminted = central_bank
+            .cancel_gradual_issuance(&schedule_id, "Inflation above target".to_string())
+            .unwrap();
+        assert_eq!(unminted, 300);
+
+        let minted = central_bank
+            .execute_due_tranches(start + Duration::days(10))
+            .unwrap();
+        assert!(minted.is_empty());
+        assert_eq!(central_bank.get_money_supply(DEFAULT_CURRENCY), 200);
+        assert!(central_bank
+            .cancel_gradual_issuance(&schedule_id, "again".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn test_decision_log_filters_by_range_and_kind() {
+        let mut central_bank = CentralBank::new(test_config());
+        let start = Utc::now() - Duration::days(30);
+        let decision = |day: i64, decision_type: PolicyDecisionType| MonetaryPolicyDecision {
+            decision_id: format!("decision-{}", day),
+            decision_type,
+            effective_date: start + Duration::days(day),
+            rationale: "Quarterly review, \"hawkish\"".to_string(),
+            impact_assessment: "n/a".to_string(),
+        };
+        central_bank.monetary_policy_decisions = vec![
+            decision(
+                0,
+                PolicyDecisionType::InterestRateChange {
+                    old_rate: 0.025,
+                    new_rate: 0.03,
+                },
+            ),
+            decision(
+                5,
+                PolicyDecisionType::MoneySupplyAdjustment {
+                    currency: DEFAULT_CURRENCY.to_string(),
+                    amount: 1_000,
+                },
+            ),
+            decision(
+                10,
+                PolicyDecisionType::InterestRateChange {
+                    old_rate: 0.03,
+                    new_rate: 0.035,
+                },
+            ),
+            decision(
+                20,
+                PolicyDecisionType::EmergencyMeasure {
+                    measure_type: "liquidity".to_string(),
+                    details: "overnight facility".to_string(),
+                },
+            ),
+        ];
+
+        let ids = |decisions: Vec<&MonetaryPolicyDecision>| -> Vec<String> {
+            decisions.iter().map(|d| d.decision_id.clone()).collect()
+        };
+        assert_eq!(
+            ids(central_bank
+                .decisions_in_range(start + Duration::days(5)..start + Duration::days(20))),
+            vec!["decision-5", "decision-10"]
+        );
+        assert_eq!(
+            ids(central_bank.decisions_of_kind(.., PolicyDecisionKind::InterestRateChange)),
+            vec!["decision-0", "decision-10"]
+        );
+        assert_eq!(
+            ids(central_bank.decisions_of_kind(
+                start + Duration::days(1)..,
+                PolicyDecisionKind::InterestRateChange
+            )),
+            vec!["decision-10"]
+        );
+
+        let csv = central_bank
+            .export_decisions(
+                start + Duration::days(1)..,
+                Some(PolicyDecisionKind::InterestRateChange),
+                DecisionExportFormat::Csv,
+            )
+            .unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("decision-10,InterestRateChange,"));
+        assert!(lines[1].contains("old_rate=0.03;new_rate=0.035"));
+        assert!(lines[1].contains("\"Quarterly review, \"\"hawkish\"\"\""));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &central_bank
+                .export_decisions(.., None, DecisionExportFormat::Json)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_interest_on_reserves_credits_each_bank() {
+        let mut central_bank = CentralBank::new(test_config());
+        central_bank
+            .set_interest_rate(
+                "deposit_rate".to_string(),
+                0.0365,
+                "Pay on reserves".to_string(),
+            )
+            .unwrap();
+        central_bank
+            .set_bank_reserves("bank-a".to_string(), 1_000_000)
+            .unwrap();
+        central_bank
+            .set_bank_reserves("bank-b".to_string(), 250_000)
+            .unwrap();
+
+        let period_end = central_bank.reserve_interest_paid_through() + Duration::days(10);
+        let payments = central_bank.pay_interest_on_reserves_at(period_end);
+
+        // 3.65% a year is 0.1% over ten days
+        assert_eq!(payments["bank-a"], 1_000);
+        assert_eq!(payments["bank-b"], 250);
+        let reserves = central_bank.get_money_supply_stats().reserve_balances;
+        assert_eq!(reserves["bank-a"], 1_001_000);
+        assert_eq!(reserves["bank-b"], 250_250);
+        assert_eq!(
+            central_bank
+                .decisions_of_kind(.., PolicyDecisionKind::InterestOnReserves)
+                .len(),
+            2
+        );
+
+        // The same period is never paid twice
+        assert!(central_bank
+            .pay_interest_on_reserves_at(period_end)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_frequent_interest_payments_carry_fractions_forward() {
+        let mut central_bank = CentralBank::new(test_config());
+        central_bank
+            .set_interest_rate(
+                "deposit_rate".to_string(),
+                0.0365,
+                "Pay on reserves".to_string(),
+            )
+            .unwrap();
+        central_bank
+            .set_bank_reserves("bank-a".to_string(), 100_000)
+            .unwrap();
+
+        // Ten units a day accrue under half a unit an hour, so hourly
+        // payments rounded on their own would never pay anything
+        let start = central_bank.reserve_interest_paid_through();
+        let paid: u64 = (1..=240)
+            .map(|hour| {
+                central_bank
+                    .pay_interest_on_reserves_at(start + Duration::hours(hour))
+                    .values()
+                    .sum::<u64>()
+            })
+            .sum();
+        assert_eq!(paid, 100);
+    }
+}
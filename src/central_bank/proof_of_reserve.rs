@@ -0,0 +1,180 @@
+//! Signed proof-of-reserve attestations
+//!
+//! A proof ties the issued money supply to the reserves banks hold with the
+//! central bank at a point in time, and commits to the ledger's merkle root so
+//! the figures can be checked against the published ledger state. Anyone with
+//! the system public key can verify a proof; any edit to its contents breaks
+//! the signature.
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{MoneySupplyStats, DEFAULT_CURRENCY};
+use crate::errors::AstorError;
+use crate::security::{Signature, Signer};
+
+/// Supply and reserve figures at the moment the proof was generated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofOfReserve {
+    /// Issued supply per currency; ordered so the signed bytes are stable
+    pub supply_by_currency: BTreeMap<String, u64>,
+    /// Issued supply of the default currency
+    pub total_supply: u64,
+    /// Reserves held by all banks with the central bank
+    pub total_reserves: u64,
+    pub reporting_banks: usize,
+    /// Merkle root over the ledger entry hashes
+    pub ledger_root: String,
+    pub ledger_entry_count: usize,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl ProofOfReserve {
+    pub fn new(stats: &MoneySupplyStats, ledger_root: String, ledger_entry_count: usize) -> Self {
+        Self {
+            supply_by_currency: stats
+                .supply_by_currency
+                .iter()
+                .map(|(currency, supply)| (currency.clone(), *supply))
+                .collect(),
+            total_supply: stats.supply_of(DEFAULT_CURRENCY),
+            total_reserves: stats
+                .reserve_balances
+                .values()
+                .fold(0u64, |total, reserve| total.saturating_add(*reserve)),
+            reporting_banks: stats.reserve_balances.len(),
+            ledger_root,
+            ledger_entry_count,
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Reserves as a fraction of the default currency's supply
+    pub fn reserve_coverage(&self) -> f64 {
+        if self.total_supply == 0 {
+            return f64::INFINITY;
+        }
+        self.total_reserves as f64 / self.total_supply as f64
+    }
+
+    fn signing_payload(&self) -> Result<Vec<u8>, AstorError> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Sign the proof for publication
+    pub fn sign(self, signer: &dyn Signer) -> Result<SignedProofOfReserve, AstorError> {
+        let signature = signer.sign(&self.signing_payload()?)?;
+
+        Ok(SignedProofOfReserve {
+            proof: self,
+            key_id: signer.key_id().to_string(),
+            public_key: general_purpose::STANDARD.encode(signer.public_key().as_bytes()),
+            signature: signature.to_base64(),
+        })
+    }
+}
+
+/// A published proof of reserve with the system's signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedProofOfReserve {
+    pub proof: ProofOfReserve,
+    pub key_id: String,
+    /// Base64 public key of the signer, for reference only; verify against a
+    /// key obtained out of band
+    pub public_key: String,
+    /// Base64 Ed25519 signature over the serialized proof
+    pub signature: String,
+}
+
+impl SignedProofOfReserve {
+    /// Check the signature against the system public key
+    ///
+    /// Proofs are long-lived, so the signature's age is not checked.
+    pub fn verify(&self, public_key: &PublicKey) -> Result<(), AstorError> {
+        let signature = Signature::from_base64(&self.signature, self.key_id.clone())?;
+        signature.verify_ignoring_age(public_key, &self.proof.signing_payload()?)
+    }
+
+    /// Check the signature and that the proof commits to a published ledger root
+    pub fn verify_against_root(
+        &self,
+        public_key: &PublicKey,
+        published_root: &str,
+    ) -> Result<(), AstorError> {
+        self.verify(public_key)?;
+        if self.proof.ledger_root != published_root {
+            return Err(AstorError::LedgerError(format!(
+                "Proof commits to ledger root {}, not the published root {}",
+                self.proof.ledger_root, published_root
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::KeyPair;
+    use std::collections::HashMap;
+
+    fn stats() -> MoneySupplyStats {
+        MoneySupplyStats {
+            supply_by_currency: HashMap::from([
+                (DEFAULT_CURRENCY.to_string(), 1_000_000),
+                ("EUR".to_string(), 50_000),
+            ]),
+            reserve_balances: HashMap::from([
+                ("bank-1".to_string(), 60_000),
+                ("bank-2".to_string(), 40_000),
+            ]),
+            base_interest_rate: 0.025,
+            inflation_target: 0.02,
+        }
+    }
+
+    #[test]
+    fn test_signed_proof_verifies_against_published_root() {
+        let keypair = KeyPair::generate();
+        let signed = ProofOfReserve::new(&stats(), "root-abc".to_string(), 12)
+            .sign(&keypair)
+            .unwrap();
+
+        assert_eq!(signed.proof.total_supply, 1_000_000);
+        assert_eq!(signed.proof.total_reserves, 100_000);
+        assert_eq!(signed.proof.reporting_banks, 2);
+        assert!((signed.proof.reserve_coverage() - 0.1).abs() < f64::EPSILON);
+
+        signed
+            .verify_against_root(&keypair.public_key(), "root-abc")
+            .unwrap();
+        assert!(signed
+            .verify_against_root(&keypair.public_key(), "root-other")
+            .is_err());
+        assert!(signed.verify(&KeyPair::generate().public_key()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_proof_fails_verification() {
+        let keypair = KeyPair::generate();
+        let signed = ProofOfReserve::new(&stats(), "root-abc".to_string(), 12)
+            .sign(&keypair)
+            .unwrap();
+
+        let mut inflated = signed.clone();
+        inflated.proof.total_reserves = 1_000_000;
+        assert!(matches!(
+            inflated.verify(&keypair.public_key()),
+            Err(AstorError::InvalidSignature)
+        ));
+
+        let mut rerooted = signed;
+        rerooted.proof.ledger_root = "root-forged".to_string();
+        assert!(rerooted
+            .verify_against_root(&keypair.public_key(), "root-forged")
+            .is_err());
+    }
+}
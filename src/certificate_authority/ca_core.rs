@@ -3,18 +3,22 @@
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use super::certificate::{Certificate, CertificateType};
+use super::certificate::{
+    Certificate, CertificateType, DEFAULT_CLOCK_SKEW_TOLERANCE_SECS, DEFAULT_EXPIRY_WARNING_DAYS,
+};
 use super::csr::CertificateSigningRequest;
 use crate::errors::AstorError;
-use crate::security::KeyPair;
+use crate::security::Signer;
 
 /// Certificate Authority core implementation
 #[derive(Clone)]
 pub struct CertificateAuthority {
     ca_id: uuid::Uuid,
     ca_certificate: Certificate,
-    ca_keypair: KeyPair,
+    /// CA key, possibly held in an HSM; never accessed as raw bytes
+    ca_signer: Arc<dyn Signer>,
     config: CaConfig,
     issued_certificates: HashMap<String, Certificate>,
     serial_counter: u64,
@@ -22,12 +26,12 @@ pub struct CertificateAuthority {
 
 impl CertificateAuthority {
     /// Create new root Certificate Authority
-    pub fn new_root(keypair: KeyPair, config: CaConfig) -> Result<Self, AstorError> {
+    pub fn new_root(signer: Arc<dyn Signer>, config: CaConfig) -> Result<Self, AstorError> {
         let ca_id = uuid::Uuid::new_v4();
 
         // Create self-signed root certificate
         let ca_certificate = Certificate::new_root_ca(
-            keypair.public_key(),
+            signer.public_key(),
             config.organization.clone(),
             config.country.clone(),
             config.validity_years,
@@ -36,7 +40,7 @@ impl CertificateAuthority {
         Ok(Self {
             ca_id,
             ca_certificate,
-            ca_keypair: keypair,
+            ca_signer: signer,
             config,
             issued_certificates: HashMap::new(),
             serial_counter: 1,
@@ -47,20 +51,20 @@ impl CertificateAuthority {
     pub async fn create_intermediate_ca(
         &self,
         ca_name: String,
-        keypair: KeyPair,
+        signer: Arc<dyn Signer>,
         config: CaConfig,
     ) -> Result<CertificateAuthority, AstorError> {
         let ca_id = uuid::Uuid::new_v4();
 
         // Create intermediate CA certificate signed by this CA
         let ca_certificate = self
-            .sign_intermediate_ca_certificate(keypair.public_key(), ca_name, config.validity_years)
+            .sign_intermediate_ca_certificate(signer.public_key(), ca_name, config.validity_years)
             .await?;
 
         Ok(CertificateAuthority {
             ca_id,
             ca_certificate,
-            ca_keypair: keypair,
+            ca_signer: signer,
             config,
             issued_certificates: HashMap::new(),
             serial_counter: 1,
@@ -80,7 +84,7 @@ impl CertificateAuthority {
             csr,
             serial_number,
             self.ca_certificate.clone(),
-            &self.ca_keypair,
+            self.ca_signer.as_ref(),
             certificate_type,
             validity_days,
         )?;
@@ -94,6 +98,21 @@ impl CertificateAuthority {
         Ok(certificate)
     }
 
+    /// Issue a delegated OCSP signing certificate for an online responder key
+    pub fn issue_ocsp_signing_certificate(
+        &self,
+        public_key: ed25519_dalek::PublicKey,
+        validity_days: u32,
+    ) -> Result<Certificate, AstorError> {
+        Certificate::new_ocsp_signing(
+            public_key,
+            self.ca_certificate.clone(),
+            self.ca_signer.as_ref(),
+            self.generate_serial_number(),
+            validity_days,
+        )
+    }
+
     /// Sign intermediate CA certificate
     async fn sign_intermediate_ca_certificate(
         &self,
@@ -107,7 +126,7 @@ impl CertificateAuthority {
             public_key,
             ca_name,
             self.ca_certificate.clone(),
-            &self.ca_keypair,
+            self.ca_signer.as_ref(),
             serial_number,
             validity_years,
         )
@@ -123,6 +142,11 @@ impl CertificateAuthority {
         &self.ca_certificate
     }
 
+    /// Get CA configuration
+    pub fn config(&self) -> &CaConfig {
+        &self.config
+    }
+
     /// Get CA ID
     pub fn get_ca_id(&self) -> uuid::Uuid {
         self.ca_id
@@ -130,7 +154,7 @@ impl CertificateAuthority {
 
     /// Verify certificate was issued by this CA
     pub fn verify_issued_certificate(&self, certificate: &Certificate) -> Result<bool, AstorError> {
-        certificate.verify_signature(&self.ca_certificate.public_key())
+        certificate.verify_signature(&self.ca_certificate.public_key()?)
     }
 }
 
@@ -146,6 +170,21 @@ pub struct CaConfig {
     pub validity_years: u32,
     pub key_usage: Vec<String>,
     pub extended_key_usage: Vec<String>,
+    /// Clock skew tolerated when checking validity periods, in seconds
+    #[serde(default = "default_clock_skew_tolerance_secs")]
+    pub clock_skew_tolerance_secs: i64,
+    /// Certificates expiring within this many days validate but are
+    /// reported as expiring soon
+    #[serde(default = "default_expiry_warning_days")]
+    pub expiry_warning_days: i64,
+}
+
+fn default_clock_skew_tolerance_secs() -> i64 {
+    DEFAULT_CLOCK_SKEW_TOLERANCE_SECS
+}
+
+fn default_expiry_warning_days() -> i64 {
+    DEFAULT_EXPIRY_WARNING_DAYS
 }
 
 impl Default for CaConfig {
@@ -164,6 +203,8 @@ impl Default for CaConfig {
                 "cRLSign".to_string(),
             ],
             extended_key_usage: vec!["serverAuth".to_string(), "clientAuth".to_string()],
+            clock_skew_tolerance_secs: DEFAULT_CLOCK_SKEW_TOLERANCE_SECS,
+            expiry_warning_days: DEFAULT_EXPIRY_WARNING_DAYS,
         }
     }
 }
@@ -1,16 +1,29 @@
 //! Certificate implementation for Astor Currency PKI
 
+use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Duration, Utc};
 use ed25519_dalek::PublicKey;
 use serde::{Deserialize, Serialize};
 
-use super::csr::CertificateSigningRequest;
+use super::csr::{CertificateSigningRequest, KeyAlgorithm};
 use crate::errors::AstorError;
-use crate::security::{KeyPair, Signature};
+use crate::schema::{from_versioned_json, legacy_schema_version, Versioned};
+use crate::security::{Signature, Signer};
+
+/// Default tolerance for clock differences between the issuer and the
+/// validating node, applied to both ends of the validity period
+pub const DEFAULT_CLOCK_SKEW_TOLERANCE_SECS: i64 = 300;
+
+/// Default window before `not_after` in which validation flags a
+/// certificate as due for renewal
+pub const DEFAULT_EXPIRY_WARNING_DAYS: i64 = 30;
 
 /// Digital certificate for Astor Currency operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Certificate {
+    /// Serialization format version, distinct from the X.509 `version`
+    #[serde(default = "legacy_schema_version")]
+    schema_version: u32,
     version: u8,
     serial_number: String,
     issuer: CertificateSubject,
@@ -61,6 +74,7 @@ impl Certificate {
         };
 
         Ok(Self {
+            schema_version: Self::SCHEMA_VERSION,
             version: 3,
             serial_number: "1".to_string(),
             issuer: subject.clone(), // Self-signed
@@ -81,7 +95,7 @@ impl Certificate {
         public_key: PublicKey,
         ca_name: String,
         issuer_cert: Certificate,
-        issuer_keypair: &KeyPair,
+        issuer_signer: &dyn Signer,
         serial_number: String,
         validity_years: u32,
     ) -> Result<Self, AstorError> {
@@ -113,6 +127,7 @@ impl Certificate {
         };
 
         let mut cert = Self {
+            schema_version: Self::SCHEMA_VERSION,
             version: 3,
             serial_number,
             issuer: issuer_cert.subject,
@@ -128,7 +143,64 @@ impl Certificate {
         };
 
         // Sign certificate
-        let signature = cert.sign_certificate(issuer_keypair)?;
+        let signature = cert.sign_certificate(issuer_signer)?;
+        cert.signature = signature.to_base64().into_bytes();
+
+        Ok(cert)
+    }
+
+    /// Create a delegated OCSP signing certificate
+    ///
+    /// Lets an online responder sign OCSP responses on the issuer's behalf so
+    /// the issuer key can stay offline.
+    pub fn new_ocsp_signing(
+        public_key: PublicKey,
+        issuer_cert: Certificate,
+        issuer_signer: &dyn Signer,
+        serial_number: String,
+        validity_days: u32,
+    ) -> Result<Self, AstorError> {
+        let now = Utc::now();
+        let not_after = now + Duration::days(validity_days as i64);
+
+        let subject = CertificateSubject {
+            common_name: format!("{} OCSP Responder", issuer_cert.subject.common_name),
+            organization: issuer_cert.subject.organization.clone(),
+            organizational_unit: "OCSP Responder".to_string(),
+            country: issuer_cert.subject.country.clone(),
+            state: issuer_cert.subject.state.clone(),
+            locality: issuer_cert.subject.locality.clone(),
+            email: "ocsp@astor-currency.org".to_string(),
+        };
+
+        let extensions = CertificateExtensions {
+            basic_constraints: Some(BasicConstraints {
+                is_ca: false,
+                path_length: None,
+            }),
+            key_usage: vec![KeyUsage::DigitalSignature],
+            extended_key_usage: vec![ExtendedKeyUsage::OcspSigning],
+            subject_alternative_names: vec![],
+        };
+
+        let mut cert = Self {
+            schema_version: Self::SCHEMA_VERSION,
+            version: 3,
+            serial_number,
+            issuer: issuer_cert.subject,
+            subject,
+            public_key: public_key.as_bytes().to_vec(),
+            not_before: now,
+            not_after,
+            certificate_type: CertificateType::OcspResponder,
+            extensions,
+            signature_algorithm: "Ed25519".to_string(),
+            signature: vec![],
+            status: CertificateStatus::Valid,
+        };
+
+        // Sign certificate
+        let signature = cert.sign_certificate(issuer_signer)?;
         cert.signature = signature.to_base64().into_bytes();
 
         Ok(cert)
@@ -139,7 +211,7 @@ impl Certificate {
         csr: CertificateSigningRequest,
         serial_number: String,
         issuer_cert: Certificate,
-        issuer_keypair: &KeyPair,
+        issuer_signer: &dyn Signer,
         certificate_type: CertificateType,
         validity_days: u32,
     ) -> Result<Self, AstorError> {
@@ -176,6 +248,7 @@ impl Certificate {
         };
 
         let mut cert = Self {
+            schema_version: Self::SCHEMA_VERSION,
             version: 3,
             serial_number,
             issuer: issuer_cert.subject,
@@ -191,16 +264,16 @@ impl Certificate {
         };
 
         // Sign certificate
-        let signature = cert.sign_certificate(issuer_keypair)?;
+        let signature = cert.sign_certificate(issuer_signer)?;
         cert.signature = signature.to_base64().into_bytes();
 
         Ok(cert)
     }
 
     /// Sign certificate with issuer's private key
-    fn sign_certificate(&self, issuer_keypair: &KeyPair) -> Result<Signature, AstorError> {
+    fn sign_certificate(&self, issuer_signer: &dyn Signer) -> Result<Signature, AstorError> {
         let tbs_certificate = self.to_be_signed_bytes()?;
-        Ok(issuer_keypair.sign(&tbs_certificate))
+        issuer_signer.sign(&tbs_certificate)
     }
 
     /// Get certificate data to be signed
@@ -216,8 +289,24 @@ impl Certificate {
         Ok(data)
     }
 
+    /// Scheme the issuer signed this certificate with
+    pub fn signature_scheme(&self) -> Result<KeyAlgorithm, AstorError> {
+        KeyAlgorithm::from_name(&self.signature_algorithm)
+            .ok_or_else(|| AstorError::UnsupportedSignatureScheme(self.signature_algorithm.clone()))
+    }
+
     /// Verify certificate signature
+    ///
+    /// Issuer keys are Ed25519, so a certificate declaring any other scheme
+    /// is an error rather than a failed verification.
     pub fn verify_signature(&self, issuer_public_key: &PublicKey) -> Result<bool, AstorError> {
+        let scheme = self.signature_scheme()?;
+        if scheme != KeyAlgorithm::Ed25519 {
+            return Err(AstorError::UnsupportedSignatureScheme(
+                scheme.name().to_string(),
+            ));
+        }
+
         let tbs_certificate = self.to_be_signed_bytes()?;
         let signature = Signature::from_base64(
             &String::from_utf8(self.signature.clone())?,
@@ -230,10 +319,22 @@ impl Certificate {
         }
     }
 
-    /// Check if certificate is currently valid
+    /// Check if certificate is currently valid, allowing the default clock skew
     pub fn is_valid(&self) -> bool {
-        let now = Utc::now();
-        self.status == CertificateStatus::Valid && now >= self.not_before && now <= self.not_after
+        self.is_valid_with_tolerance(Duration::seconds(DEFAULT_CLOCK_SKEW_TOLERANCE_SECS))
+    }
+
+    /// Check if certificate is currently valid, allowing `skew_tolerance`
+    /// either side of the validity period
+    pub fn is_valid_with_tolerance(&self, skew_tolerance: Duration) -> bool {
+        self.status == CertificateStatus::Valid
+            && self.is_within_validity_period(Utc::now(), skew_tolerance)
+    }
+
+    /// Whether `at` falls within `not_before..=not_after`, widened by
+    /// `skew_tolerance` on both ends
+    pub fn is_within_validity_period(&self, at: DateTime<Utc>, skew_tolerance: Duration) -> bool {
+        at >= self.not_before - skew_tolerance && at <= self.not_after + skew_tolerance
     }
 
     /// Get certificate public key
@@ -253,6 +354,20 @@ impl Certificate {
         ))
     }
 
+    /// Parse a certificate exported with [`Certificate::to_pem`]
+    pub fn from_pem(pem: &str) -> Result<Self, AstorError> {
+        let encoded = pem
+            .trim()
+            .strip_prefix("-----BEGIN CERTIFICATE-----")
+            .and_then(|rest| rest.strip_suffix("-----END CERTIFICATE-----"))
+            .ok_or_else(|| AstorError::InvalidOperation("Malformed certificate PEM".to_string()))?;
+        let cert_data = general_purpose::STANDARD
+            .decode(encoded.split_whitespace().collect::<String>())
+            .map_err(|_| AstorError::InvalidOperation("Malformed certificate PEM".to_string()))?;
+
+        from_versioned_json(&cert_data)
+    }
+
     // Getters
     pub fn serial_number(&self) -> &str {
         &self.serial_number
@@ -275,6 +390,32 @@ impl Certificate {
     pub fn status(&self) -> &CertificateStatus {
         &self.status
     }
+    pub fn extensions(&self) -> &CertificateExtensions {
+        &self.extensions
+    }
+
+    /// Check whether the certificate allows the given extended key usage
+    pub fn has_extended_key_usage(&self, usage: &ExtendedKeyUsage) -> bool {
+        self.extensions.extended_key_usage.contains(usage)
+    }
+
+    /// Serialization format version this certificate was decoded from
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+}
+
+impl Versioned for Certificate {
+    const SCHEMA_VERSION: u32 = 2;
+
+    fn migrate(
+        _from_version: u32,
+        _payload: &mut serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), AstorError> {
+        // Version 2 only introduced the version tag; the signature does not
+        // cover it, so older certificates stay verifiable unchanged
+        Ok(())
+    }
 }
 
 /// Certificate types for different Astor Currency operations
@@ -287,6 +428,7 @@ pub enum CertificateType {
     Merchant,
     User,
     ApiClient,
+    OcspResponder,
 }
 
 /// Certificate status
@@ -299,7 +441,7 @@ pub enum CertificateStatus {
 }
 
 /// Certificate subject information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CertificateSubject {
     pub common_name: String,
     pub organization: String,
@@ -336,7 +478,7 @@ pub enum KeyUsage {
     CrlSign,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ExtendedKeyUsage {
     ServerAuth,
     ClientAuth,
@@ -345,3 +487,41 @@ pub enum ExtendedKeyUsage {
     TimeStamping,
     OcspSigning,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::KeyPair;
+
+    fn root_certificate() -> Certificate {
+        Certificate::new_root_ca(
+            KeyPair::generate().public_key(),
+            "Astor".to_string(),
+            "AS".to_string(),
+            1,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_not_before_within_skew_tolerance_is_valid() {
+        let mut certificate = root_certificate();
+        // Issued by a node whose clock runs a minute ahead
+        certificate.not_before = Utc::now() + Duration::minutes(1);
+
+        assert!(certificate.is_valid());
+        assert!(!certificate.is_valid_with_tolerance(Duration::zero()));
+    }
+
+    #[test]
+    fn test_skew_beyond_tolerance_is_rejected() {
+        let mut certificate = root_certificate();
+        certificate.not_before = Utc::now() + Duration::minutes(10);
+        assert!(!certificate.is_valid());
+
+        let mut certificate = root_certificate();
+        certificate.not_after = Utc::now() - Duration::minutes(1);
+        assert!(certificate.is_valid());
+        assert!(!certificate.is_valid_with_tolerance(Duration::seconds(30)));
+    }
+}
@@ -0,0 +1,454 @@
+//! Certificate chain validation with detailed failure reporting
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::certificate::{
+    Certificate, CertificateStatus, DEFAULT_CLOCK_SKEW_TOLERANCE_SECS, DEFAULT_EXPIRY_WARNING_DAYS,
+};
+use super::crl::RevocationReason;
+use crate::errors::AstorError;
+
+/// Outcome of validating a certificate chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChainValidationResult {
+    Valid,
+    /// The chain is valid, but a certificate in it expires within the
+    /// warning window and should be renewed
+    ExpiringSoon {
+        /// Position in the chain of the certificate expiring first
+        index: usize,
+        not_after: DateTime<Utc>,
+    },
+    Invalid {
+        /// Position in the chain (0 = end-entity) of the offending certificate
+        index: usize,
+        failure: ChainValidationFailure,
+    },
+}
+
+impl ChainValidationResult {
+    /// Whether the chain validated, with or without an expiry warning
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self,
+            ChainValidationResult::Valid | ChainValidationResult::ExpiringSoon { .. }
+        )
+    }
+
+    pub fn is_expiring_soon(&self) -> bool {
+        matches!(self, ChainValidationResult::ExpiringSoon { .. })
+    }
+}
+
+/// Reason a certificate chain failed validation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChainValidationFailure {
+    /// Certificate is outside its validity period
+    Expired,
+    Revoked(RevocationReason),
+    /// Issuer is neither the next certificate in the chain nor a trust anchor,
+    /// or is not a CA
+    UntrustedIssuer,
+    /// Certificate sits deeper below a CA than its path length constraint allows
+    PathLengthExceeded,
+    SignatureInvalid,
+}
+
+/// Dry-run validator for certificate chains
+///
+/// Chains are ordered from the end-entity certificate (index 0) towards the
+/// root. The trust anchor may be included as the last element or omitted.
+/// Validation has no side effects and stops at the first failure.
+pub struct ChainValidator {
+    trust_anchors: Vec<Certificate>,
+    revocations: HashMap<String, RevocationReason>,
+    validation_time: DateTime<Utc>,
+    clock_skew_tolerance: Duration,
+    expiry_warning: Duration,
+}
+
+impl ChainValidator {
+    pub fn new(trust_anchors: Vec<Certificate>) -> Self {
+        Self {
+            trust_anchors,
+            revocations: HashMap::new(),
+            validation_time: Utc::now(),
+            clock_skew_tolerance: Duration::seconds(DEFAULT_CLOCK_SKEW_TOLERANCE_SECS),
+            expiry_warning: Duration::days(DEFAULT_EXPIRY_WARNING_DAYS),
+        }
+    }
+
+    /// Treat the certificate with this serial number as revoked
+    pub fn revoke(&mut self, serial_number: &str, reason: RevocationReason) {
+        self.revocations.insert(serial_number.to_string(), reason);
+    }
+
+    /// Validate as of the given time instead of now
+    pub fn set_validation_time(&mut self, validation_time: DateTime<Utc>) {
+        self.validation_time = validation_time;
+    }
+
+    /// Allow validity periods to be off by up to `tolerance` either way
+    pub fn set_clock_skew_tolerance(&mut self, tolerance: Duration) {
+        self.clock_skew_tolerance = tolerance;
+    }
+
+    /// Flag valid chains containing a certificate that expires within `window`
+    pub fn set_expiry_warning(&mut self, window: Duration) {
+        self.expiry_warning = window;
+    }
+
+    /// Validate a chain, reporting the first failure and where it occurred
+    ///
+    /// A valid chain is reported as `ExpiringSoon` if any certificate in it
+    /// expires within the warning window.
+    pub fn validate(&self, chain: &[Certificate]) -> Result<ChainValidationResult, AstorError> {
+        if chain.is_empty() {
+            return Err(AstorError::ValidationError(
+                "Certificate chain is empty".to_string(),
+            ));
+        }
+
+        let mut expiring_first: Option<(usize, DateTime<Utc>)> = None;
+        for (index, certificate) in chain.iter().enumerate() {
+            if let Some(failure) = self.check_certificate(certificate) {
+                return Ok(ChainValidationResult::Invalid { index, failure });
+            }
+
+            let not_after = certificate.not_after();
+            if not_after <= self.validation_time + self.expiry_warning
+                && !matches!(expiring_first, Some((_, first)) if first <= not_after)
+            {
+                expiring_first = Some((index, not_after));
+            }
+
+            // Trust anchors are self-signed; nothing above them to check
+            if self.is_trust_anchor(certificate) {
+                return Ok(Self::valid(expiring_first));
+            }
+
+            let issuer = match chain.get(index + 1) {
+                Some(issuer) => Some(issuer),
+                None => self
+                    .trust_anchors
+                    .iter()
+                    .find(|anchor| anchor.subject() == certificate.issuer()),
+            };
+
+            let issuer = match issuer {
+                Some(issuer)
+                    if issuer.subject() == certificate.issuer()
+                        && issuer
+                            .extensions()
+                            .basic_constraints
+                            .as_ref()
+                            .is_some_and(|bc| bc.is_ca) =>
+                {
+                    issuer
+                }
+                _ => {
+                    return Ok(ChainValidationResult::Invalid {
+                        index,
+                        failure: ChainValidationFailure::UntrustedIssuer,
+                    })
+                }
+            };
+
+            if !certificate.verify_signature(&issuer.public_key()?)? {
+                return Ok(ChainValidationResult::Invalid {
+                    index,
+                    failure: ChainValidationFailure::SignatureInvalid,
+                });
+            }
+
+            // Certificates 1..=index are intermediates below this issuer
+            let path_length = issuer
+                .extensions()
+                .basic_constraints
+                .as_ref()
+                .and_then(|bc| bc.path_length);
+            if let Some(path_length) = path_length {
+                let path_length = path_length as usize;
+                if index > path_length {
+                    return Ok(ChainValidationResult::Invalid {
+                        index: index - path_length,
+                        failure: ChainValidationFailure::PathLengthExceeded,
+                    });
+                }
+            }
+        }
+
+        // The last certificate's issuer was found among the trust anchors
+        Ok(Self::valid(expiring_first))
+    }
+
+    fn valid(expiring_first: Option<(usize, DateTime<Utc>)>) -> ChainValidationResult {
+        match expiring_first {
+            Some((index, not_after)) => ChainValidationResult::ExpiringSoon { index, not_after },
+            None => ChainValidationResult::Valid,
+        }
+    }
+
+    fn check_certificate(&self, certificate: &Certificate) -> Option<ChainValidationFailure> {
+        if certificate.status() == &CertificateStatus::Expired
+            || !certificate
+                .is_within_validity_period(self.validation_time, self.clock_skew_tolerance)
+        {
+            return Some(ChainValidationFailure::Expired);
+        }
+
+        self.revocations
+            .get(certificate.serial_number())
+            .map(|reason| ChainValidationFailure::Revoked(*reason))
+    }
+
+    fn is_trust_anchor(&self, certificate: &Certificate) -> bool {
+        let public_key = match certificate.public_key() {
+            Ok(public_key) => public_key,
+            Err(_) => return false,
+        };
+
+        self.trust_anchors.iter().any(|anchor| {
+            anchor.subject() == certificate.subject()
+                && anchor
+                    .public_key()
+                    .is_ok_and(|key| key.as_bytes() == public_key.as_bytes())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate_authority::certificate::{CertificateSubject, CertificateType};
+    use crate::certificate_authority::csr::{CertificateSigningRequest, CsrAttributes};
+    use crate::security::KeyPair;
+    use chrono::Duration;
+
+    struct TestPki {
+        root: Certificate,
+        intermediate: Certificate,
+        intermediate_keypair: KeyPair,
+    }
+
+    fn test_pki() -> TestPki {
+        let root_keypair = KeyPair::generate();
+        let root = Certificate::new_root_ca(
+            root_keypair.public_key(),
+            "Astoria".to_string(),
+            "AS".to_string(),
+            10,
+        )
+        .unwrap();
+
+        let intermediate_keypair = KeyPair::generate();
+        let intermediate = Certificate::new_intermediate_ca(
+            intermediate_keypair.public_key(),
+            "Operations".to_string(),
+            root.clone(),
+            &root_keypair,
+            "2".to_string(),
+            5,
+        )
+        .unwrap();
+
+        TestPki {
+            root,
+            intermediate,
+            intermediate_keypair,
+        }
+    }
+
+    fn issue_bank_certificate(
+        serial_number: &str,
+        issuer: &Certificate,
+        issuer_keypair: &KeyPair,
+    ) -> Certificate {
+        let subject = CertificateSubject {
+            common_name: "First Bank of Astoria".to_string(),
+            organization: "First Bank".to_string(),
+            organizational_unit: "Treasury".to_string(),
+            country: "AS".to_string(),
+            state: "".to_string(),
+            locality: "".to_string(),
+            email: "pki@firstbank.as".to_string(),
+        };
+        let attributes = CsrAttributes {
+            challenge_password: None,
+            unstructured_name: None,
+            requested_extensions: vec![],
+        };
+        let csr = CertificateSigningRequest::new(subject, &KeyPair::generate(), attributes, vec![])
+            .unwrap();
+
+        Certificate::from_csr(
+            csr,
+            serial_number.to_string(),
+            issuer.clone(),
+            issuer_keypair,
+            CertificateType::Bank,
+            365,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_chain() {
+        let pki = test_pki();
+        let leaf = issue_bank_certificate("3", &pki.intermediate, &pki.intermediate_keypair);
+        let validator = ChainValidator::new(vec![pki.root.clone()]);
+
+        let result = validator
+            .validate(&[leaf.clone(), pki.intermediate.clone()])
+            .unwrap();
+        assert!(result.is_valid());
+
+        let result = validator
+            .validate(&[leaf, pki.intermediate, pki.root])
+            .unwrap();
+        assert!(result.is_valid());
+    }
+
+    #[test]
+    fn test_expired_certificate() {
+        let pki = test_pki();
+        let leaf = issue_bank_certificate("3", &pki.intermediate, &pki.intermediate_keypair);
+        let mut validator = ChainValidator::new(vec![pki.root]);
+        validator.set_validation_time(Utc::now() + Duration::days(400));
+
+        let result = validator.validate(&[leaf, pki.intermediate]).unwrap();
+        assert_eq!(
+            result,
+            ChainValidationResult::Invalid {
+                index: 0,
+                failure: ChainValidationFailure::Expired,
+            }
+        );
+    }
+
+    #[test]
+    fn test_certificate_within_warning_window_is_valid_but_expiring_soon() {
+        let pki = test_pki();
+        let leaf = issue_bank_certificate("3", &pki.intermediate, &pki.intermediate_keypair);
+        let mut validator = ChainValidator::new(vec![pki.root]);
+        validator.set_expiry_warning(Duration::days(30));
+
+        validator.set_validation_time(Utc::now() + Duration::days(300));
+        let result = validator
+            .validate(&[leaf.clone(), pki.intermediate.clone()])
+            .unwrap();
+        assert_eq!(result, ChainValidationResult::Valid);
+
+        validator.set_validation_time(Utc::now() + Duration::days(350));
+        let result = validator
+            .validate(&[leaf.clone(), pki.intermediate.clone()])
+            .unwrap();
+        assert!(result.is_valid());
+        assert_eq!(
+            result,
+            ChainValidationResult::ExpiringSoon {
+                index: 0,
+                not_after: leaf.not_after(),
+            }
+        );
+
+        validator.set_validation_time(Utc::now() + Duration::days(366));
+        let result = validator.validate(&[leaf, pki.intermediate]).unwrap();
+        assert!(!result.is_valid());
+        assert_eq!(
+            result,
+            ChainValidationResult::Invalid {
+                index: 0,
+                failure: ChainValidationFailure::Expired,
+            }
+        );
+    }
+
+    #[test]
+    fn test_revoked_intermediate() {
+        let pki = test_pki();
+        let leaf = issue_bank_certificate("3", &pki.intermediate, &pki.intermediate_keypair);
+        let mut validator = ChainValidator::new(vec![pki.root]);
+        validator.revoke("2", RevocationReason::KeyCompromise);
+
+        let result = validator.validate(&[leaf, pki.intermediate]).unwrap();
+        assert_eq!(
+            result,
+            ChainValidationResult::Invalid {
+                index: 1,
+                failure: ChainValidationFailure::Revoked(RevocationReason::KeyCompromise),
+            }
+        );
+    }
+
+    #[test]
+    fn test_untrusted_issuer() {
+        let pki = test_pki();
+        let leaf = issue_bank_certificate("3", &pki.intermediate, &pki.intermediate_keypair);
+        let other_root = Certificate::new_root_ca(
+            KeyPair::generate().public_key(),
+            "Elsewhere".to_string(),
+            "EL".to_string(),
+            10,
+        )
+        .unwrap();
+        let validator = ChainValidator::new(vec![other_root]);
+
+        let result = validator.validate(&[leaf, pki.intermediate]).unwrap();
+        assert_eq!(
+            result,
+            ChainValidationResult::Invalid {
+                index: 1,
+                failure: ChainValidationFailure::UntrustedIssuer,
+            }
+        );
+    }
+
+    #[test]
+    fn test_path_length_exceeded() {
+        let pki = test_pki();
+
+        // The intermediate only allows end-entity certificates below it
+        let sub_keypair = KeyPair::generate();
+        let sub_intermediate = Certificate::new_intermediate_ca(
+            sub_keypair.public_key(),
+            "Branch".to_string(),
+            pki.intermediate.clone(),
+            &pki.intermediate_keypair,
+            "4".to_string(),
+            5,
+        )
+        .unwrap();
+        let leaf = issue_bank_certificate("5", &sub_intermediate, &sub_keypair);
+        let validator = ChainValidator::new(vec![pki.root]);
+
+        let result = validator
+            .validate(&[leaf, sub_intermediate, pki.intermediate])
+            .unwrap();
+        assert_eq!(
+            result,
+            ChainValidationResult::Invalid {
+                index: 1,
+                failure: ChainValidationFailure::PathLengthExceeded,
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let pki = test_pki();
+        let leaf = issue_bank_certificate("3", &pki.intermediate, &KeyPair::generate());
+        let validator = ChainValidator::new(vec![pki.root]);
+
+        let result = validator.validate(&[leaf, pki.intermediate]).unwrap();
+        assert_eq!(
+            result,
+            ChainValidationResult::Invalid {
+                index: 0,
+                failure: ChainValidationFailure::SignatureInvalid,
+            }
+        );
+    }
+}
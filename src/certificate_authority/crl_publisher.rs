@@ -0,0 +1,343 @@
+//! Scheduled CRL regeneration and publication
+//!
+//! The CA reissues its revocation list on a fixed interval, whether or not
+//! anything was revoked, so relying parties always hold a list whose
+//! `next_update` tells them when to fetch the next one. Each issue carries a
+//! strictly increasing CRL number and is signed with the CA key. With a state
+//! file the number keeps increasing across restarts.
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::crl::RevocationReason;
+use crate::errors::AstorError;
+use crate::security::{Signature, Signer};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevokedEntry {
+    pub serial_number: String,
+    pub reason: String,
+}
+
+/// The body of a CRL, covered by its signature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrlContents {
+    pub issuer: String,
+    pub crl_number: u64,
+    pub this_update: DateTime<Utc>,
+    pub next_update: DateTime<Utc>,
+    /// Ordered by serial number so the signed bytes are stable
+    pub revoked: Vec<RevokedEntry>,
+}
+
+/// A CRL as published to relying parties
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedCrl {
+    pub contents: CrlContents,
+    pub key_id: String,
+    /// Base64 public key of the signer, for reference only
+    pub public_key: String,
+    /// Base64 Ed25519 signature over the serialized contents
+    pub signature: String,
+}
+
+impl SignedCrl {
+    /// Check the signature against the CA public key
+    pub fn verify(&self, public_key: &PublicKey) -> Result<(), AstorError> {
+        let signature = Signature::from_base64(&self.signature, self.key_id.clone())?;
+        signature.verify_ignoring_age(public_key, &serde_json::to_vec(&self.contents)?)
+    }
+
+    /// Whether relying parties should already have fetched a newer list
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        now >= self.contents.next_update
+    }
+}
+
+/// Destination a freshly signed CRL is published to
+#[async_trait::async_trait]
+pub trait CrlSink: Send + Sync {
+    async fn publish(&self, crl: &SignedCrl) -> Result<(), AstorError>;
+}
+
+/// Writes the CRL as JSON to a path served to relying parties
+///
+/// The file is replaced atomically so readers never see a partial list.
+pub struct FileCrlSink {
+    path: PathBuf,
+}
+
+impl FileCrlSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl CrlSink for FileCrlSink {
+    async fn publish(&self, crl: &SignedCrl) -> Result<(), AstorError> {
+        let staging = self.path.with_extension("tmp");
+        tokio::fs::write(&staging, serde_json::to_vec_pretty(crl)?)
+            .await
+            .map_err(|e| AstorError::InvalidOperation(format!("Failed to write CRL: {}", e)))?;
+        tokio::fs::rename(&staging, &self.path)
+            .await
+            .map_err(|e| AstorError::InvalidOperation(format!("Failed to publish CRL: {}", e)))
+    }
+}
+
+/// POSTs the CRL as JSON to a distribution endpoint
+pub struct WebhookCrlSink {
+    url: String,
+    http_client: reqwest::Client,
+}
+
+impl WebhookCrlSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            http_client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CrlSink for WebhookCrlSink {
+    async fn publish(&self, crl: &SignedCrl) -> Result<(), AstorError> {
+        self.http_client
+            .post(&self.url)
+            .json(crl)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AstorError::NetworkError(format!("CRL webhook failed: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// The sinks a CRL is published to
+///
+/// Cloning is cheap, so callers can publish without holding the CA lock
+/// while sinks do file or network I/O.
+#[derive(Clone, Default)]
+pub struct CrlDistribution {
+    sinks: Vec<Arc<dyn CrlSink>>,
+}
+
+impl CrlDistribution {
+    /// Publish a CRL to every sink
+    ///
+    /// Every sink is attempted; the first failure is returned.
+    pub async fn publish(&self, crl: &SignedCrl) -> Result<(), AstorError> {
+        let mut first_error = None;
+        for sink in &self.sinks {
+            if let Err(e) = sink.publish(crl).await {
+                tracing::error!(
+                    "Failed to publish CRL number {}: {}",
+                    crl.contents.crl_number,
+                    e
+                );
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+/// Publisher state kept across restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CrlState {
+    crl_number: u64,
+}
+
+/// Regenerates, signs and publishes the CA's CRL
+pub struct CrlPublisher {
+    issuer: String,
+    signer: Arc<dyn Signer>,
+    update_interval: Duration,
+    crl_number: u64,
+    /// File the last CRL number is recorded in, if persisted
+    state_path: Option<PathBuf>,
+    distribution: CrlDistribution,
+    latest: Option<SignedCrl>,
+}
+
+impl CrlPublisher {
+    pub fn new(issuer: String, signer: Arc<dyn Signer>, update_interval_hours: u32) -> Self {
+        Self {
+            issuer,
+            signer,
+            update_interval: Duration::hours(update_interval_hours as i64),
+            crl_number: 0,
+            state_path: None,
+            distribution: CrlDistribution::default(),
+            latest: None,
+        }
+    }
+
+    /// Record the CRL number in `path`, continuing from the number already
+    /// there
+    pub fn open_state(&mut self, path: impl AsRef<Path>) -> Result<(), AstorError> {
+        let path = path.as_ref().to_path_buf();
+        let state: CrlState = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CrlState::default(),
+            Err(e) => {
+                return Err(AstorError::InvalidOperation(format!(
+                    "Failed to read CRL state {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        self.crl_number = self.crl_number.max(state.crl_number);
+        self.state_path = Some(path);
+        Ok(())
+    }
+
+    /// Write the CRL number to the state file, replacing it atomically
+    fn save_state(&self, crl_number: u64) -> Result<(), AstorError> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
+        let staging = path.with_extension("tmp");
+        std::fs::write(&staging, serde_json::to_vec(&CrlState { crl_number })?)
+            .and_then(|()| std::fs::rename(&staging, path))
+            .map_err(|e| {
+                AstorError::InvalidOperation(format!(
+                    "Failed to write CRL state {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    pub fn update_interval(&self) -> Duration {
+        self.update_interval
+    }
+
+    pub fn set_update_interval_hours(&mut self, hours: u32) {
+        self.update_interval = Duration::hours(hours as i64);
+    }
+
+    /// Publish every regenerated CRL to `sink` as well
+    pub fn add_sink(&mut self, sink: Arc<dyn CrlSink>) {
+        self.distribution.sinks.push(sink);
+    }
+
+    /// The sinks regenerated CRLs are published to
+    pub fn distribution(&self) -> CrlDistribution {
+        self.distribution.clone()
+    }
+
+    /// The most recently regenerated CRL
+    pub fn latest(&self) -> Option<&SignedCrl> {
+        self.latest.as_ref()
+    }
+
+    /// Sign a new CRL over `revocations` with the next CRL number
+    ///
+    /// Its `next_update` is one update interval after `now`. The number is
+    /// recorded in the state file before the CRL is returned, so no number
+    /// is ever issued twice.
+    pub fn regenerate(
+        &mut self,
+        revocations: &HashMap<String, RevocationReason>,
+        now: DateTime<Utc>,
+    ) -> Result<SignedCrl, AstorError> {
+        let mut revoked: Vec<RevokedEntry> = revocations
+            .iter()
+            .map(|(serial_number, reason)| RevokedEntry {
+                serial_number: serial_number.clone(),
+                reason: format!("{:?}", reason),
+            })
+            .collect();
+        revoked.sort_by(|a, b| a.serial_number.cmp(&b.serial_number));
+
+        let contents = CrlContents {
+            issuer: self.issuer.clone(),
+            crl_number: self.crl_number + 1,
+            this_update: now,
+            next_update: now + self.update_interval,
+            revoked,
+        };
+        let signature = self.signer.sign(&serde_json::to_vec(&contents)?)?;
+
+        let crl = SignedCrl {
+            contents,
+            key_id: self.signer.key_id().to_string(),
+            public_key: general_purpose::STANDARD.encode(self.signer.public_key().as_bytes()),
+            signature: signature.to_base64(),
+        };
+        self.save_state(crl.contents.crl_number)?;
+        self.crl_number = crl.contents.crl_number;
+        self.latest = Some(crl.clone());
+        Ok(crl)
+    }
+
+    /// Publish a CRL to every sink
+    ///
+    /// Every sink is attempted; the first failure is returned.
+    pub async fn publish(&self, crl: &SignedCrl) -> Result<(), AstorError> {
+        self.distribution.publish(crl).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::KeyPair;
+
+    #[tokio::test]
+    async fn test_crl_number_increments_on_each_regeneration() {
+        let keypair = Arc::new(KeyPair::generate());
+        let mut publisher = CrlPublisher::new("Astor Root CA".to_string(), keypair.clone(), 24);
+        let path = std::env::temp_dir().join(format!("astor-crl-{}.json", uuid::Uuid::new_v4()));
+        publisher.add_sink(Arc::new(FileCrlSink::new(path.clone())));
+
+        let mut revocations = HashMap::new();
+        let now = Utc::now();
+        let first = publisher.regenerate(&revocations, now).unwrap();
+        revocations.insert("42".to_string(), RevocationReason::KeyCompromise);
+        let second = publisher
+            .regenerate(&revocations, now + Duration::hours(24))
+            .unwrap();
+        publisher.publish(&second).await.unwrap();
+
+        assert_eq!(first.contents.crl_number, 1);
+        assert_eq!(second.contents.crl_number, 2);
+        assert_eq!(second.contents.next_update, now + Duration::hours(48));
+        assert_eq!(second.contents.revoked[0].serial_number, "42");
+        second.verify(&keypair.public_key()).unwrap();
+        assert!(first.is_stale(now + Duration::hours(24)));
+
+        let published: SignedCrl = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(published.contents.crl_number, 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_crl_number_survives_restart() {
+        let keypair = Arc::new(KeyPair::generate());
+        let path =
+            std::env::temp_dir().join(format!("astor-crl-state-{}.json", uuid::Uuid::new_v4()));
+        let revocations = HashMap::new();
+
+        let mut publisher = CrlPublisher::new("Astor Root CA".to_string(), keypair.clone(), 24);
+        publisher.open_state(&path).unwrap();
+        publisher.regenerate(&revocations, Utc::now()).unwrap();
+        publisher.regenerate(&revocations, Utc::now()).unwrap();
+
+        let mut restarted = CrlPublisher::new("Astor Root CA".to_string(), keypair, 24);
+        restarted.open_state(&path).unwrap();
+        let crl = restarted.regenerate(&revocations, Utc::now()).unwrap();
+        assert_eq!(crl.contents.crl_number, 3);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use super::certificate::CertificateSubject;
 use crate::errors::AstorError;
-use crate::security::{KeyPair, Signature};
+use crate::security::{hash_data, Signature, Signer};
 
 /// Certificate Signing Request
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,11 +23,11 @@ impl CertificateSigningRequest {
     /// Create new CSR
     pub fn new(
         subject: CertificateSubject,
-        keypair: &KeyPair,
+        signer: &dyn Signer,
         attributes: CsrAttributes,
         subject_alternative_names: Vec<String>,
     ) -> Result<Self, AstorError> {
-        let public_key = keypair.public_key().as_bytes().to_vec();
+        let public_key = signer.public_key().as_bytes().to_vec();
 
         let mut csr = Self {
             version: 1,
@@ -40,16 +40,16 @@ impl CertificateSigningRequest {
         };
 
         // Sign CSR
-        let signature = csr.sign_csr(keypair)?;
+        let signature = csr.sign_csr(signer)?;
         csr.signature = signature.to_base64().into_bytes();
 
         Ok(csr)
     }
 
     /// Sign CSR with private key
-    fn sign_csr(&self, keypair: &KeyPair) -> Result<Signature, AstorError> {
+    fn sign_csr(&self, signer: &dyn Signer) -> Result<Signature, AstorError> {
         let tbs_data = self.to_be_signed_bytes()?;
-        Ok(keypair.sign(&tbs_data))
+        signer.sign(&tbs_data)
     }
 
     /// Get CSR data to be signed
@@ -79,6 +79,12 @@ impl CertificateSigningRequest {
         }
     }
 
+    /// SHA-256 over the whole signed request, identifying resubmissions of
+    /// the exact same CSR
+    pub fn fingerprint(&self) -> Result<String, AstorError> {
+        Ok(hash_data(&serde_json::to_vec(self)?))
+    }
+
     /// Export CSR as PEM format
     pub fn to_pem(&self) -> Result<String, AstorError> {
         let csr_data = serde_json::to_vec(self)?;
@@ -99,20 +105,74 @@ pub struct CsrAttributes {
     pub requested_extensions: Vec<String>,
 }
 
+/// Key and signature algorithms, as named in certificates, CSRs and signed
+/// payloads
+///
+/// An algorithm can be known (it parses and round-trips) without being
+/// implemented; see `is_implemented`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum KeyAlgorithm {
+    #[default]
+    Ed25519,
+    /// ECDSA over NIST P-256 with SHA-256
+    EcdsaP256,
+}
+
+impl KeyAlgorithm {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Ed25519" => Some(KeyAlgorithm::Ed25519),
+            "ECDSA-P256" | "ES256" => Some(KeyAlgorithm::EcdsaP256),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::Ed25519 => "Ed25519",
+            KeyAlgorithm::EcdsaP256 => "ECDSA-P256",
+        }
+    }
+
+    /// Encoded public key length in bytes
+    pub fn public_key_length(&self) -> usize {
+        match self {
+            KeyAlgorithm::Ed25519 => 32,
+            // Uncompressed SEC1 point
+            KeyAlgorithm::EcdsaP256 => 65,
+        }
+    }
+
+    /// Whether this build can verify signatures made with this algorithm
+    pub fn is_implemented(&self) -> bool {
+        matches!(self, KeyAlgorithm::Ed25519)
+    }
+}
+
 /// CSR processor for validation and handling
 pub struct CsrProcessor {
     validation_rules: CsrValidationRules,
+    allowed_algorithms: Vec<KeyAlgorithm>,
 }
 
 impl CsrProcessor {
     pub fn new() -> Self {
         Self {
             validation_rules: CsrValidationRules::default(),
+            allowed_algorithms: vec![KeyAlgorithm::Ed25519],
         }
     }
 
+    /// Restrict which key algorithms may be certified
+    pub fn set_allowed_algorithms(&mut self, algorithms: Vec<KeyAlgorithm>) {
+        self.allowed_algorithms = algorithms;
+    }
+
     /// Validate CSR before processing
     pub fn validate_csr(&self, csr: &CertificateSigningRequest) -> Result<(), AstorError> {
+        // Reject unsupported or weak keys before touching the key material
+        let algorithm = self.validate_algorithm(csr)?;
+
         // Verify signature
         if !csr.verify_signature()? {
             return Err(AstorError::InvalidSignature);
@@ -122,7 +182,7 @@ impl CsrProcessor {
         self.validate_subject(&csr.subject)?;
 
         // Validate public key
-        self.validate_public_key(&csr.public_key)?;
+        self.validate_public_key(algorithm, &csr.public_key)?;
 
         // Additional validation rules
         self.apply_validation_rules(csr)?;
@@ -152,16 +212,48 @@ impl CsrProcessor {
         Ok(())
     }
 
-    fn validate_public_key(&self, public_key: &[u8]) -> Result<(), AstorError> {
-        if public_key.len() != 32 {
-            return Err(AstorError::ValidationError(
-                "Invalid public key length".to_string(),
-            ));
+    fn validate_algorithm(
+        &self,
+        csr: &CertificateSigningRequest,
+    ) -> Result<KeyAlgorithm, AstorError> {
+        let algorithm = KeyAlgorithm::from_name(&csr.signature_algorithm)
+            .filter(|algorithm| self.allowed_algorithms.contains(algorithm))
+            .ok_or_else(|| {
+                AstorError::InvalidOperation(format!(
+                    "Key algorithm not allowed: {}",
+                    csr.signature_algorithm
+                ))
+            })?;
+
+        // A key of the wrong size for its algorithm is malformed or weak
+        if csr.public_key.len() != algorithm.public_key_length() {
+            return Err(AstorError::InvalidOperation(format!(
+                "Invalid {} key size: {} bits",
+                algorithm.name(),
+                csr.public_key.len() * 8
+            )));
         }
 
-        // Verify it's a valid Ed25519 public key
-        PublicKey::from_bytes(public_key)
-            .map_err(|_| AstorError::ValidationError("Invalid Ed25519 public key".to_string()))?;
+        Ok(algorithm)
+    }
+
+    fn validate_public_key(
+        &self,
+        algorithm: KeyAlgorithm,
+        public_key: &[u8],
+    ) -> Result<(), AstorError> {
+        match algorithm {
+            KeyAlgorithm::Ed25519 => {
+                PublicKey::from_bytes(public_key).map_err(|_| {
+                    AstorError::ValidationError("Invalid Ed25519 public key".to_string())
+                })?;
+            }
+            KeyAlgorithm::EcdsaP256 => {
+                return Err(AstorError::UnsupportedSignatureScheme(
+                    algorithm.name().to_string(),
+                ))
+            }
+        }
 
         Ok(())
     }
@@ -191,3 +283,66 @@ impl Default for CsrValidationRules {
 pub trait CsrValidationRule: Send + Sync {
     fn validate(&self, csr: &CertificateSigningRequest) -> Result<(), AstorError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::KeyPair;
+
+    fn sample_csr() -> CertificateSigningRequest {
+        let subject = CertificateSubject {
+            common_name: "node1.astor-currency.org".to_string(),
+            organization: "First Bank".to_string(),
+            organizational_unit: "Operations".to_string(),
+            country: "AS".to_string(),
+            state: "".to_string(),
+            locality: "".to_string(),
+            email: "ops@firstbank.as".to_string(),
+        };
+        let attributes = CsrAttributes {
+            challenge_password: None,
+            unstructured_name: None,
+            requested_extensions: vec![],
+        };
+
+        CertificateSigningRequest::new(subject, &KeyPair::generate(), attributes, vec![]).unwrap()
+    }
+
+    #[test]
+    fn test_ed25519_csr_accepted() {
+        assert!(CsrProcessor::new().validate_csr(&sample_csr()).is_ok());
+    }
+
+    #[test]
+    fn test_disallowed_algorithm_rejected() {
+        let mut csr = sample_csr();
+        csr.signature_algorithm = "RSA-1024".to_string();
+
+        assert!(matches!(
+            CsrProcessor::new().validate_csr(&csr),
+            Err(AstorError::InvalidOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_algorithm_removed_from_allowlist_rejected() {
+        let mut processor = CsrProcessor::new();
+        processor.set_allowed_algorithms(vec![]);
+
+        assert!(matches!(
+            processor.validate_csr(&sample_csr()),
+            Err(AstorError::InvalidOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_truncated_key_rejected() {
+        let mut csr = sample_csr();
+        csr.public_key.truncate(16);
+
+        assert!(matches!(
+            CsrProcessor::new().validate_csr(&csr),
+            Err(AstorError::InvalidOperation(_))
+        ));
+    }
+}
@@ -5,20 +5,46 @@
 
 pub mod ca_core;
 pub mod certificate;
+pub mod chain;
 pub mod csr;
 // pub mod crl;
-// pub mod ocsp;
+pub mod crl_publisher;
+pub mod ocsp;
 // pub mod pki_hierarchy;
+pub mod subject_policy;
+pub mod trust_bundle;
 
 pub use ca_core::{CaConfig, CertificateAuthority};
 pub use certificate::{Certificate, CertificateStatus, CertificateType};
+pub use chain::{ChainValidationFailure, ChainValidationResult, ChainValidator};
 pub use crl::{CertificateRevocationList, RevocationReason};
+pub use crl_publisher::{
+    CrlDistribution, CrlPublisher, CrlSink, FileCrlSink, SignedCrl, WebhookCrlSink,
+};
 pub use csr::{CertificateSigningRequest, CsrProcessor};
-pub use ocsp::{OcspRequest, OcspResponder, OcspResponse};
+pub use ocsp::{OcspCertStatus, OcspRequest, OcspResponder, OcspResponse};
 pub use pki_hierarchy::{CaLevel, PkiHierarchy};
+pub use subject_policy::{SubjectPolicy, SubjectRule};
+pub use trust_bundle::TrustBundle;
 
+use crate::config::{CrlConfig, OcspConfig};
 use crate::errors::AstorError;
-use crate::security::KeyPair;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::security::{KeyPair, Signer};
+
+/// Validity of the delegated OCSP signing certificate unless configured
+/// otherwise
+const OCSP_SIGNER_VALIDITY_DAYS: u32 = 30;
+
+/// Days before expiry the delegated OCSP certificate is reissued unless
+/// configured otherwise
+const OCSP_SIGNER_RENEW_BEFORE_DAYS: u32 = 7;
+
+/// Hours between scheduled CRL issues unless configured otherwise
+const DEFAULT_CRL_UPDATE_INTERVAL_HOURS: u32 = 24;
 
 /// Main Certificate Authority System for Astor Currency
 pub struct AstorCertificateAuthority {
@@ -26,39 +52,224 @@ pub struct AstorCertificateAuthority {
     intermediate_cas: std::collections::HashMap<String, CertificateAuthority>,
     pki_hierarchy: PkiHierarchy,
     csr_processor: CsrProcessor,
+    subject_policy: SubjectPolicy,
     crl_manager: CertificateRevocationList,
     ocsp_responder: OcspResponder,
+    /// Key the delegated OCSP certificate is issued for, kept to renew it
+    ocsp_signer: Arc<dyn Signer>,
+    ocsp_validity_days: u32,
+    ocsp_renew_before: chrono::Duration,
+    revocations: std::collections::HashMap<String, RevocationReason>,
+    crl_publisher: CrlPublisher,
+    /// Latest certificate issued for each CSR fingerprint
+    issued_by_csr: std::collections::HashMap<String, Certificate>,
 }
 
 impl AstorCertificateAuthority {
     /// Initialize new Certificate Authority system
-    pub fn new(root_keypair: KeyPair, ca_config: CaConfig) -> Result<Self, AstorError> {
-        let root_ca = CertificateAuthority::new_root(root_keypair, ca_config.clone())?;
+    ///
+    /// The root key is only used through `root_signer`, so it may be held in
+    /// an HSM via `ExternalSigner`.
+    pub fn new(root_signer: Arc<dyn Signer>, ca_config: CaConfig) -> Result<Self, AstorError> {
+        let root_ca = CertificateAuthority::new_root(root_signer.clone(), ca_config.clone())?;
+        let crl_publisher = CrlPublisher::new(
+            root_ca.get_certificate().subject().common_name.clone(),
+            root_signer,
+            DEFAULT_CRL_UPDATE_INTERVAL_HOURS,
+        );
         let intermediate_cas = std::collections::HashMap::new();
         let pki_hierarchy = PkiHierarchy::new(root_ca.get_certificate().clone());
         let csr_processor = CsrProcessor::new();
         let crl_manager = CertificateRevocationList::new(root_ca.get_certificate().clone());
-        let ocsp_responder = OcspResponder::new(root_ca.get_certificate().clone());
+        let mut ocsp_responder = OcspResponder::new(root_ca.get_certificate().clone());
+
+        // Sign OCSP responses with a delegated key so the root key stays offline
+        let ocsp_signer: Arc<dyn Signer> = Arc::new(KeyPair::generate());
+        let ocsp_certificate = root_ca
+            .issue_ocsp_signing_certificate(ocsp_signer.public_key(), OCSP_SIGNER_VALIDITY_DAYS)?;
+        ocsp_responder.set_delegated_signer(ocsp_certificate, ocsp_signer.clone())?;
 
         Ok(Self {
             root_ca,
             intermediate_cas,
             pki_hierarchy,
             csr_processor,
+            subject_policy: SubjectPolicy::new(),
             crl_manager,
             ocsp_responder,
+            ocsp_signer,
+            ocsp_validity_days: OCSP_SIGNER_VALIDITY_DAYS,
+            ocsp_renew_before: chrono::Duration::days(OCSP_SIGNER_RENEW_BEFORE_DAYS.into()),
+            revocations: std::collections::HashMap::new(),
+            crl_publisher,
+            issued_by_csr: std::collections::HashMap::new(),
         })
     }
 
+    /// Scheduled CRL regeneration: interval and publication targets
+    pub fn crl_publisher_mut(&mut self) -> &mut CrlPublisher {
+        &mut self.crl_publisher
+    }
+
+    /// Apply the CRL interval, numbering state and publication targets
+    pub fn configure_crl(&mut self, config: &CrlConfig) -> Result<(), AstorError> {
+        self.crl_publisher
+            .set_update_interval_hours(config.update_interval_hours);
+        if let Some(path) = &config.state_path {
+            self.crl_publisher.open_state(path)?;
+        }
+        if let Some(path) = &config.publish_path {
+            self.crl_publisher
+                .add_sink(Arc::new(FileCrlSink::new(path.clone())));
+        }
+        if let Some(url) = &config.webhook_url {
+            self.crl_publisher
+                .add_sink(Arc::new(WebhookCrlSink::new(url.clone())));
+        }
+        Ok(())
+    }
+
+    /// Apply the OCSP signing key and certificate validity, delegating to
+    /// them straight away
+    pub fn configure_ocsp(&mut self, config: &OcspConfig) -> Result<(), AstorError> {
+        self.ocsp_validity_days = config.validity_days.max(1);
+        self.ocsp_renew_before = chrono::Duration::days(config.renew_before_days.into());
+        let signer: Arc<dyn Signer> = match &config.signing_key_path {
+            Some(path) => Arc::new(KeyPair::from_file(path)?),
+            None => self.ocsp_signer.clone(),
+        };
+        self.delegate_ocsp_signing(signer)
+    }
+
+    /// Issue a delegated OCSP signing certificate for `signer` and answer
+    /// status requests with it
+    pub fn delegate_ocsp_signing(&mut self, signer: Arc<dyn Signer>) -> Result<(), AstorError> {
+        let certificate = self
+            .root_ca
+            .issue_ocsp_signing_certificate(signer.public_key(), self.ocsp_validity_days)?;
+        self.set_ocsp_signer(certificate, signer)
+    }
+
+    /// Reissue the delegated OCSP certificate for the same key once `now` is
+    /// within the renewal window before it expires, returning whether it was
+    /// reissued
+    pub fn renew_ocsp_signer_if_due(
+        &mut self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, AstorError> {
+        let due = self
+            .ocsp_responder
+            .signer_certificate()
+            .map_or(true, |certificate| {
+                certificate.not_after() - self.ocsp_renew_before <= now
+            });
+        if !due {
+            return Ok(false);
+        }
+
+        self.delegate_ocsp_signing(self.ocsp_signer.clone())?;
+        Ok(true)
+    }
+
+    /// Sign a new CRL with the next CRL number, returning it with the sinks
+    /// to publish it to
+    pub fn sign_next_crl(&mut self) -> Result<(SignedCrl, CrlDistribution), AstorError> {
+        let crl = self
+            .crl_publisher
+            .regenerate(&self.revocations, chrono::Utc::now())?;
+        Ok((crl, self.crl_publisher.distribution()))
+    }
+
+    /// Sign a new CRL with the next CRL number and publish it to every sink
+    pub async fn regenerate_crl(&mut self) -> Result<SignedCrl, AstorError> {
+        let (crl, distribution) = self.sign_next_crl()?;
+        publish_crl(&distribution, &crl).await?;
+        Ok(crl)
+    }
+
+    /// Regenerate and publish the CRL every update interval until the
+    /// returned task is aborted
+    ///
+    /// The first CRL is issued immediately. Each run also renews the
+    /// delegated OCSP certificate when it is close to expiry. The CA lock is
+    /// only held while signing, never while the CRL is published.
+    pub async fn spawn_crl_task(ca: Arc<RwLock<AstorCertificateAuthority>>) -> JoinHandle<()> {
+        let update_interval = ca
+            .read()
+            .await
+            .crl_publisher
+            .update_interval()
+            .to_std()
+            .unwrap_or(std::time::Duration::from_secs(3600))
+            .max(std::time::Duration::from_secs(1));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(update_interval);
+            loop {
+                interval.tick().await;
+                let signed = {
+                    let mut ca = ca.write().await;
+                    if let Err(e) = ca.renew_ocsp_signer_if_due(chrono::Utc::now()) {
+                        tracing::error!("OCSP signing certificate renewal failed: {}", e);
+                    }
+                    ca.sign_next_crl()
+                };
+                let result = match signed {
+                    Ok((crl, distribution)) => publish_crl(&distribution, &crl).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    tracing::error!("Scheduled CRL regeneration failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Subject validation policy applied before issuance
+    pub fn subject_policy_mut(&mut self) -> &mut SubjectPolicy {
+        &mut self.subject_policy
+    }
+
     /// Issue a new certificate for currency operations
+    ///
+    /// Issuance is idempotent: resubmitting a CSR that already has a valid,
+    /// unrevoked certificate of the same type returns that certificate.
     pub async fn issue_certificate(
         &mut self,
         csr: CertificateSigningRequest,
         certificate_type: CertificateType,
         validity_days: u32,
+    ) -> Result<Certificate, AstorError> {
+        self.issue_certificate_with_options(csr, certificate_type, validity_days, false)
+            .await
+    }
+
+    /// Issue a certificate, minting a new one even for a CSR that already
+    /// has a valid certificate when `force_reissue` is set
+    pub async fn issue_certificate_with_options(
+        &mut self,
+        csr: CertificateSigningRequest,
+        certificate_type: CertificateType,
+        validity_days: u32,
+        force_reissue: bool,
     ) -> Result<Certificate, AstorError> {
         // Validate CSR
         self.csr_processor.validate_csr(&csr)?;
+        self.subject_policy
+            .validate(&certificate_type, &csr.subject)
+            .await?;
+
+        let fingerprint = csr.fingerprint()?;
+        if !force_reissue {
+            if let Some(existing) = self.existing_certificate(&fingerprint, &certificate_type) {
+                tracing::info!(
+                    "CSR {} already certified: serial={}",
+                    fingerprint,
+                    existing.serial_number()
+                );
+                return Ok(existing.clone());
+            }
+        }
 
         // Determine issuing CA based on certificate type
         let issuing_ca = match certificate_type {
@@ -79,6 +290,8 @@ impl AstorCertificateAuthority {
         // Add to PKI hierarchy
         self.pki_hierarchy.add_certificate(certificate.clone())?;
 
+        self.issued_by_csr.insert(fingerprint, certificate.clone());
+
         // Log certificate issuance
         tracing::info!(
             "Certificate issued: serial={}, type={:?}, subject={}",
@@ -90,16 +303,29 @@ impl AstorCertificateAuthority {
         Ok(certificate)
     }
 
+    /// The certificate previously issued for a CSR, if it is still usable
+    fn existing_certificate(
+        &self,
+        fingerprint: &str,
+        certificate_type: &CertificateType,
+    ) -> Option<&Certificate> {
+        self.issued_by_csr.get(fingerprint).filter(|certificate| {
+            certificate.certificate_type() == certificate_type
+                && certificate.is_valid()
+                && !self.revocations.contains_key(certificate.serial_number())
+        })
+    }
+
     /// Create intermediate Certificate Authority
     pub async fn create_intermediate_ca(
         &mut self,
         ca_name: String,
-        keypair: KeyPair,
+        signer: Arc<dyn Signer>,
         config: CaConfig,
     ) -> Result<String, AstorError> {
         let intermediate_ca = self
             .root_ca
-            .create_intermediate_ca(ca_name.clone(), keypair, config)
+            .create_intermediate_ca(ca_name.clone(), signer, config)
             .await?;
 
         let ca_id = intermediate_ca.get_ca_id().to_string();
@@ -125,6 +351,8 @@ impl AstorCertificateAuthority {
             .mark_revoked(serial_number, reason)
             .await?;
 
+        self.revocations.insert(serial_number.to_string(), reason);
+
         tracing::warn!(
             "Certificate revoked: serial={}, reason={:?}",
             serial_number,
@@ -134,11 +362,52 @@ impl AstorCertificateAuthority {
     }
 
     /// Validate certificate chain
+    ///
+    /// This is a dry run: the chain is built from the known intermediate CAs
+    /// and checked against the root, reporting the first failure without
+    /// changing any CA state.
     pub fn validate_certificate_chain(
         &self,
         certificate: &Certificate,
-    ) -> Result<bool, AstorError> {
-        self.pki_hierarchy.validate_chain(certificate)
+    ) -> Result<ChainValidationResult, AstorError> {
+        let root_certificate = self.root_ca.get_certificate().clone();
+        let mut validator = ChainValidator::new(vec![root_certificate]);
+        validator.set_clock_skew_tolerance(chrono::Duration::seconds(
+            self.root_ca.config().clock_skew_tolerance_secs,
+        ));
+        validator.set_expiry_warning(chrono::Duration::days(
+            self.root_ca.config().expiry_warning_days,
+        ));
+        for (serial_number, reason) in &self.revocations {
+            validator.revoke(serial_number, *reason);
+        }
+
+        validator.validate(&self.build_chain(certificate))
+    }
+
+    /// Collect the certificate and its intermediate issuers, leaf first
+    fn build_chain(&self, certificate: &Certificate) -> Vec<Certificate> {
+        let mut chain = vec![certificate.clone()];
+
+        // Each intermediate can appear at most once, which also bounds cycles
+        for _ in 0..self.intermediate_cas.len() {
+            let current = &chain[chain.len() - 1];
+            let issuer = self
+                .intermediate_cas
+                .values()
+                .map(|ca| ca.get_certificate())
+                .find(|cert| {
+                    cert.subject() == current.issuer()
+                        && cert.serial_number() != current.serial_number()
+                });
+
+            match issuer {
+                Some(issuer) => chain.push(issuer.clone()),
+                None => break,
+            }
+        }
+
+        chain
     }
 
     /// Get Certificate Revocation List
@@ -154,6 +423,21 @@ impl AstorCertificateAuthority {
         self.ocsp_responder.handle_request(request).await
     }
 
+    /// Replace the OCSP responder's delegated signing certificate and key
+    ///
+    /// The certificate must be issued by the root CA with the OCSP signing
+    /// extended key usage. Renewals reissue the certificate for this key.
+    pub fn set_ocsp_signer(
+        &mut self,
+        certificate: Certificate,
+        signer: Arc<dyn Signer>,
+    ) -> Result<(), AstorError> {
+        self.ocsp_responder
+            .set_delegated_signer(certificate, signer.clone())?;
+        self.ocsp_signer = signer;
+        Ok(())
+    }
+
     /// Get CA certificate for distribution
     pub fn get_root_certificate(&self) -> Certificate {
         self.root_ca.get_certificate().clone()
@@ -167,6 +451,38 @@ impl AstorCertificateAuthority {
             .ok_or_else(|| AstorError::NotFound(format!("Intermediate CA not found: {}", ca_id)))
     }
 
+    /// Export the root and all active intermediate CA certificates as a
+    /// concatenated PEM bundle, root first
+    ///
+    /// Intermediates that are revoked, expired or not yet valid are left out.
+    pub fn export_trust_bundle(&self) -> Result<String, AstorError> {
+        let mut certificates = vec![self.root_ca.get_certificate().clone()];
+        let mut intermediates: Vec<&Certificate> = self
+            .intermediate_cas
+            .values()
+            .map(|ca| ca.get_certificate())
+            .filter(|cert| cert.is_valid() && !self.revocations.contains_key(cert.serial_number()))
+            .collect();
+        intermediates.sort_by(|a, b| a.serial_number().cmp(b.serial_number()));
+        certificates.extend(intermediates.into_iter().cloned());
+
+        TrustBundle::new(certificates).to_pem()
+    }
+
+    /// Load a trust bundle produced by [`export_trust_bundle`] for verifying
+    /// certificates
+    ///
+    /// [`export_trust_bundle`]: AstorCertificateAuthority::export_trust_bundle
+    pub fn import_trust_bundle(pem: &str) -> Result<TrustBundle, AstorError> {
+        let bundle = TrustBundle::from_pem(pem)?;
+        if bundle.trust_anchors().is_empty() {
+            return Err(AstorError::InvalidOperation(
+                "Trust bundle has no root certificate".to_string(),
+            ));
+        }
+        Ok(bundle)
+    }
+
     /// List all certificates
     pub fn list_certificates(&self) -> Vec<Certificate> {
         self.pki_hierarchy.list_all_certificates()
@@ -187,6 +503,17 @@ impl AstorCertificateAuthority {
     }
 }
 
+/// Publish a freshly signed CRL and log when the next one is due
+async fn publish_crl(distribution: &CrlDistribution, crl: &SignedCrl) -> Result<(), AstorError> {
+    distribution.publish(crl).await?;
+    tracing::info!(
+        "CRL number {} published, next update at {}",
+        crl.contents.crl_number,
+        crl.contents.next_update
+    );
+    Ok(())
+}
+
 /// Certificate Authority configuration
 #[derive(Debug, Clone)]
 pub struct CertificateAuthorityConfig {
@@ -210,3 +537,99 @@ impl Default for CertificateAuthorityConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate_authority::certificate::CertificateSubject;
+    use crate::certificate_authority::csr::CsrAttributes;
+    use base64::{engine::general_purpose, Engine as _};
+
+    #[tokio::test]
+    async fn test_resubmitted_csr_returns_existing_certificate() {
+        let mut ca =
+            AstorCertificateAuthority::new(Arc::new(KeyPair::generate()), CaConfig::default())
+                .unwrap();
+        let subject = CertificateSubject {
+            common_name: "alice.astor-currency.org".to_string(),
+            organization: "Alice".to_string(),
+            organizational_unit: "".to_string(),
+            country: "AS".to_string(),
+            state: "".to_string(),
+            locality: "".to_string(),
+            email: "alice@astor-currency.org".to_string(),
+        };
+        let attributes = CsrAttributes {
+            challenge_password: None,
+            unstructured_name: None,
+            requested_extensions: vec![],
+        };
+        let csr = CertificateSigningRequest::new(subject, &KeyPair::generate(), attributes, vec![])
+            .unwrap();
+
+        let first = ca
+            .issue_certificate(csr.clone(), CertificateType::User, 365)
+            .await
+            .unwrap();
+        let second = ca
+            .issue_certificate(csr, CertificateType::User, 365)
+            .await
+            .unwrap();
+
+        assert_eq!(second.serial_number(), first.serial_number());
+        assert_eq!(second.not_before(), first.not_before());
+    }
+
+    #[tokio::test]
+    async fn test_configured_ocsp_signer_is_renewed_before_expiry() {
+        let mut ca =
+            AstorCertificateAuthority::new(Arc::new(KeyPair::generate()), CaConfig::default())
+                .unwrap();
+        let secret = crate::security::crypto::generate_secure_random(32);
+        let key_path =
+            std::env::temp_dir().join(format!("astor-ocsp-key-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&key_path, general_purpose::STANDARD.encode(&secret)).unwrap();
+
+        ca.configure_ocsp(&OcspConfig {
+            signing_key_path: Some(key_path.to_string_lossy().into_owned()),
+            validity_days: 10,
+            renew_before_days: 3,
+        })
+        .unwrap();
+        std::fs::remove_file(&key_path).unwrap();
+
+        let request = || OcspRequest {
+            serial_number: "42".to_string(),
+            nonce: None,
+        };
+        let first = ca.handle_ocsp_request(request()).await.unwrap();
+        assert_eq!(
+            first.responder_certificate.public_key().unwrap().as_bytes(),
+            KeyPair::from_bytes(&secret)
+                .unwrap()
+                .public_key()
+                .as_bytes()
+        );
+
+        let now = chrono::Utc::now();
+        assert!(!ca.renew_ocsp_signer_if_due(now).unwrap());
+        assert!(ca
+            .renew_ocsp_signer_if_due(now + chrono::Duration::days(8))
+            .unwrap());
+
+        let renewed = ca.handle_ocsp_request(request()).await.unwrap();
+        assert_ne!(
+            renewed.responder_certificate.serial_number(),
+            first.responder_certificate.serial_number()
+        );
+        assert_eq!(
+            renewed
+                .responder_certificate
+                .public_key()
+                .unwrap()
+                .as_bytes(),
+            first.responder_certificate.public_key().unwrap().as_bytes()
+        );
+        assert!(renewed.verify(&ca.get_root_certificate()).is_ok());
+    }
+}
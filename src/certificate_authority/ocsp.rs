@@ -0,0 +1,312 @@
+//! OCSP responder for online certificate status checks
+//!
+//! Responses are signed by a delegated OCSP signing certificate issued by the
+//! CA, so the CA key itself is never used in the request path.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::certificate::{Certificate, ExtendedKeyUsage};
+use super::crl::RevocationReason;
+use crate::errors::AstorError;
+use crate::security::{Signature, Signer};
+
+/// OCSP status request for a single certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcspRequest {
+    pub serial_number: String,
+    /// Echoed back in the response to prevent replay
+    pub nonce: Option<String>,
+}
+
+/// Certificate status reported by the responder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OcspCertStatus {
+    Good,
+    Revoked {
+        reason: RevocationReason,
+        revoked_at: DateTime<Utc>,
+    },
+}
+
+/// Signed OCSP response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcspResponse {
+    pub serial_number: String,
+    pub status: OcspCertStatus,
+    pub produced_at: DateTime<Utc>,
+    pub nonce: Option<String>,
+    /// Delegated certificate whose key signed this response
+    pub responder_certificate: Certificate,
+    pub signature: String,
+}
+
+impl OcspResponse {
+    /// Response data covered by the signature
+    fn to_be_signed_bytes(&self) -> Result<Vec<u8>, AstorError> {
+        let data = serde_json::json!({
+            "serial_number": self.serial_number,
+            "status": self.status,
+            "produced_at": self.produced_at,
+            "nonce": self.nonce,
+            "responder": self.responder_certificate.serial_number(),
+        });
+        Ok(serde_json::to_vec(&data)?)
+    }
+
+    /// Verify the response against the issuing CA certificate
+    ///
+    /// The responder certificate must be a valid OCSP signing delegation from
+    /// the issuer, and the response must be signed by its key.
+    pub fn verify(&self, issuer_certificate: &Certificate) -> Result<(), AstorError> {
+        validate_delegation(
+            issuer_certificate,
+            &self.responder_certificate,
+            self.produced_at,
+        )?;
+
+        let signature = Signature::from_base64(&self.signature, "ocsp_response".to_string())?;
+        signature.verify_ignoring_age(
+            &self.responder_certificate.public_key()?,
+            &self.to_be_signed_bytes()?,
+        )
+    }
+}
+
+/// Check that a certificate is authorized to sign OCSP responses for an issuer
+fn validate_delegation(
+    issuer_certificate: &Certificate,
+    responder_certificate: &Certificate,
+    at: DateTime<Utc>,
+) -> Result<(), AstorError> {
+    if responder_certificate.issuer() != issuer_certificate.subject()
+        || !responder_certificate.verify_signature(&issuer_certificate.public_key()?)?
+    {
+        return Err(AstorError::SecurityViolation(
+            "OCSP responder certificate was not issued by this CA".to_string(),
+        ));
+    }
+
+    if !responder_certificate.has_extended_key_usage(&ExtendedKeyUsage::OcspSigning) {
+        return Err(AstorError::SecurityViolation(
+            "OCSP responder certificate lacks the OCSP signing usage".to_string(),
+        ));
+    }
+
+    if at < responder_certificate.not_before() || at > responder_certificate.not_after() {
+        return Err(AstorError::SecurityViolation(
+            "OCSP responder certificate is not valid at the response time".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Delegated signing credentials for the responder
+struct OcspSigner {
+    certificate: Certificate,
+    signer: Arc<dyn Signer>,
+}
+
+/// OCSP responder answering certificate status requests
+pub struct OcspResponder {
+    issuer_certificate: Certificate,
+    signer: Option<OcspSigner>,
+    revoked: HashMap<String, (RevocationReason, DateTime<Utc>)>,
+}
+
+impl OcspResponder {
+    /// Create a responder for certificates issued by `issuer_certificate`
+    ///
+    /// A delegated signer must be configured before requests can be answered.
+    pub fn new(issuer_certificate: Certificate) -> Self {
+        Self {
+            issuer_certificate,
+            signer: None,
+            revoked: HashMap::new(),
+        }
+    }
+
+    /// Configure the delegated OCSP signing certificate and its key
+    pub fn set_delegated_signer(
+        &mut self,
+        certificate: Certificate,
+        signer: Arc<dyn Signer>,
+    ) -> Result<(), AstorError> {
+        validate_delegation(&self.issuer_certificate, &certificate, Utc::now())?;
+
+        if certificate.public_key()?.as_bytes() != signer.public_key().as_bytes() {
+            return Err(AstorError::CryptographicError(
+                "OCSP signing key does not match the responder certificate".to_string(),
+            ));
+        }
+
+        tracing::info!(
+            "OCSP responder using delegated signer: serial={}",
+            certificate.serial_number()
+        );
+        self.signer = Some(OcspSigner {
+            certificate,
+            signer,
+        });
+        Ok(())
+    }
+
+    /// Delegated certificate responses are currently signed under
+    pub fn signer_certificate(&self) -> Option<&Certificate> {
+        self.signer.as_ref().map(|signer| &signer.certificate)
+    }
+
+    /// Record a revoked certificate
+    pub async fn mark_revoked(
+        &mut self,
+        serial_number: &str,
+        reason: RevocationReason,
+    ) -> Result<(), AstorError> {
+        self.revoked
+            .insert(serial_number.to_string(), (reason, Utc::now()));
+        Ok(())
+    }
+
+    /// Answer a status request with a signed response
+    pub async fn handle_request(&self, request: OcspRequest) -> Result<OcspResponse, AstorError> {
+        let signer = self.signer.as_ref().ok_or_else(|| {
+            AstorError::ConfigurationError("OCSP responder has no signing key".to_string())
+        })?;
+
+        let status = match self.revoked.get(&request.serial_number) {
+            Some((reason, revoked_at)) => OcspCertStatus::Revoked {
+                reason: *reason,
+                revoked_at: *revoked_at,
+            },
+            None => OcspCertStatus::Good,
+        };
+
+        let mut response = OcspResponse {
+            serial_number: request.serial_number,
+            status,
+            produced_at: Utc::now(),
+            nonce: request.nonce,
+            responder_certificate: signer.certificate.clone(),
+            signature: String::new(),
+        };
+        response.signature = signer
+            .signer
+            .sign(&response.to_be_signed_bytes()?)?
+            .to_base64();
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate_authority::ca_core::{CaConfig, CertificateAuthority};
+    use crate::security::KeyPair;
+
+    fn delegated_responder() -> (CertificateAuthority, OcspResponder) {
+        let root_ca =
+            CertificateAuthority::new_root(Arc::new(KeyPair::generate()), CaConfig::default())
+                .unwrap();
+        let signer_keypair = KeyPair::generate();
+        let signer_certificate = root_ca
+            .issue_ocsp_signing_certificate(signer_keypair.public_key(), 30)
+            .unwrap();
+
+        let mut responder = OcspResponder::new(root_ca.get_certificate().clone());
+        responder
+            .set_delegated_signer(signer_certificate, Arc::new(signer_keypair))
+            .unwrap();
+
+        (root_ca, responder)
+    }
+
+    #[tokio::test]
+    async fn test_delegated_response_validates_against_chain() {
+        let (root_ca, mut responder) = delegated_responder();
+        responder
+            .mark_revoked("42", RevocationReason::KeyCompromise)
+            .await
+            .unwrap();
+
+        let response = responder
+            .handle_request(OcspRequest {
+                serial_number: "42".to_string(),
+                nonce: Some("n-1".to_string()),
+            })
+            .await
+            .unwrap();
+
+        assert!(response.verify(root_ca.get_certificate()).is_ok());
+        assert!(matches!(
+            response.status,
+            OcspCertStatus::Revoked {
+                reason: RevocationReason::KeyCompromise,
+                ..
+            }
+        ));
+        assert_eq!(response.nonce.as_deref(), Some("n-1"));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_response_fails_validation() {
+        let (root_ca, mut responder) = delegated_responder();
+        responder
+            .mark_revoked("42", RevocationReason::KeyCompromise)
+            .await
+            .unwrap();
+
+        let mut response = responder
+            .handle_request(OcspRequest {
+                serial_number: "42".to_string(),
+                nonce: None,
+            })
+            .await
+            .unwrap();
+        response.status = OcspCertStatus::Good;
+
+        assert!(response.verify(root_ca.get_certificate()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_response_from_another_ca_fails_validation() {
+        let (root_ca, _) = delegated_responder();
+        let (_, other_responder) = delegated_responder();
+
+        let response = other_responder
+            .handle_request(OcspRequest {
+                serial_number: "42".to_string(),
+                nonce: None,
+            })
+            .await
+            .unwrap();
+
+        assert!(response.verify(root_ca.get_certificate()).is_err());
+    }
+
+    #[test]
+    fn test_signer_without_ocsp_usage_rejected() {
+        let root_keypair = KeyPair::generate();
+        let root_ca =
+            CertificateAuthority::new_root(Arc::new(root_keypair.clone()), CaConfig::default())
+                .unwrap();
+        let intermediate_keypair = KeyPair::generate();
+        let intermediate = Certificate::new_intermediate_ca(
+            intermediate_keypair.public_key(),
+            "Operations".to_string(),
+            root_ca.get_certificate().clone(),
+            &root_keypair,
+            "2".to_string(),
+            5,
+        )
+        .unwrap();
+
+        let mut responder = OcspResponder::new(root_ca.get_certificate().clone());
+        assert!(responder
+            .set_delegated_signer(intermediate, Arc::new(intermediate_keypair))
+            .is_err());
+    }
+}
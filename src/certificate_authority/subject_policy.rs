@@ -0,0 +1,208 @@
+//! Subject validation before certificate issuance
+//!
+//! A CSR's subject is chosen by the requester, so it is cross-checked against
+//! the registry that owns the identity it claims: bank certificates must name
+//! a registered bank, node certificates an approved node.
+
+use std::collections::HashSet;
+
+use super::certificate::{CertificateSubject, CertificateType};
+use crate::banking_network::BankDirectory;
+use crate::errors::AstorError;
+
+/// How a certificate type's subject is checked
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubjectRule {
+    /// Issued without a registry check
+    Unrestricted,
+    /// Organization must be the name of a registered, non-suspended bank
+    OrganizationIsRegisteredBank,
+    /// Common name must be an approved node id
+    CommonNameIsApprovedNode,
+}
+
+/// Per-certificate-type subject validation policy
+pub struct SubjectPolicy {
+    rules: Vec<(CertificateType, SubjectRule)>,
+    bank_directory: Option<BankDirectory>,
+    approved_node_ids: HashSet<String>,
+}
+
+impl SubjectPolicy {
+    /// Bank and node subjects are checked; other types are unrestricted
+    pub fn new() -> Self {
+        Self {
+            rules: vec![
+                (
+                    CertificateType::Bank,
+                    SubjectRule::OrganizationIsRegisteredBank,
+                ),
+                (
+                    CertificateType::CurrencyNode,
+                    SubjectRule::CommonNameIsApprovedNode,
+                ),
+            ],
+            bank_directory: None,
+            approved_node_ids: HashSet::new(),
+        }
+    }
+
+    /// Override the rule for a certificate type
+    pub fn set_rule(&mut self, certificate_type: CertificateType, rule: SubjectRule) {
+        self.rules.retain(|(t, _)| *t != certificate_type);
+        self.rules.push((certificate_type, rule));
+    }
+
+    pub fn rule_for(&self, certificate_type: &CertificateType) -> &SubjectRule {
+        self.rules
+            .iter()
+            .find(|(t, _)| t == certificate_type)
+            .map(|(_, rule)| rule)
+            .unwrap_or(&SubjectRule::Unrestricted)
+    }
+
+    /// Registry bank subjects are checked against
+    pub fn set_bank_directory(&mut self, directory: BankDirectory) {
+        self.bank_directory = Some(directory);
+    }
+
+    /// Allow certificates to be issued for a node
+    pub fn approve_node(&mut self, node_id: String) {
+        self.approved_node_ids.insert(node_id);
+    }
+
+    pub fn revoke_node_approval(&mut self, node_id: &str) {
+        self.approved_node_ids.remove(node_id);
+    }
+
+    /// Reject a subject that does not match the registry for its type
+    pub async fn validate(
+        &self,
+        certificate_type: &CertificateType,
+        subject: &CertificateSubject,
+    ) -> Result<(), AstorError> {
+        match self.rule_for(certificate_type) {
+            SubjectRule::Unrestricted => Ok(()),
+            SubjectRule::OrganizationIsRegisteredBank => {
+                // Fail closed: without a registry nothing can be verified
+                let directory = self.bank_directory.as_ref().ok_or_else(|| {
+                    AstorError::InvalidOperation(
+                        "No bank registry configured for bank certificate subjects".to_string(),
+                    )
+                })?;
+
+                if directory.is_eligible_bank(&subject.organization).await {
+                    Ok(())
+                } else {
+                    Err(AstorError::InvalidOperation(format!(
+                        "Organization '{}' is not a registered bank",
+                        subject.organization
+                    )))
+                }
+            }
+            SubjectRule::CommonNameIsApprovedNode => {
+                if self.approved_node_ids.contains(&subject.common_name) {
+                    Ok(())
+                } else {
+                    Err(AstorError::InvalidOperation(format!(
+                        "Common name '{}' is not an approved node id",
+                        subject.common_name
+                    )))
+                }
+            }
+        }
+    }
+}
+
+impl Default for SubjectPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banking_network::{BankingNetwork, BankingService};
+    use crate::central_bank::{CentralBank, CentralBankConfig};
+
+    fn subject(common_name: &str, organization: &str) -> CertificateSubject {
+        CertificateSubject {
+            common_name: common_name.to_string(),
+            organization: organization.to_string(),
+            organizational_unit: "Treasury".to_string(),
+            country: "AS".to_string(),
+            state: String::new(),
+            locality: String::new(),
+            email: "pki@example.as".to_string(),
+        }
+    }
+
+    async fn network_with_bank(name: &str) -> BankingNetwork {
+        let network = BankingNetwork::new(CentralBank::new(CentralBankConfig {
+            base_interest_rate: 0.025,
+            reserve_requirement_ratio: 0.10,
+            inflation_target: 0.02,
+            money_supply_growth_target: 0.03,
+            emergency_lending_rate: 0.05,
+        }));
+        network
+            .register_bank(
+                name.to_string(),
+                "LIC-001".to_string(),
+                "https://bank.example.as".to_string(),
+                "key".to_string(),
+                vec![BankingService::DepositAccounts],
+            )
+            .await
+            .unwrap();
+        network
+    }
+
+    #[tokio::test]
+    async fn test_bank_subject_must_match_registered_bank() {
+        let network = network_with_bank("First Bank").await;
+        let mut policy = SubjectPolicy::new();
+        policy.set_bank_directory(network.directory());
+
+        assert!(policy
+            .validate(&CertificateType::Bank, &subject("api", "First Bank"))
+            .await
+            .is_ok());
+
+        let result = policy
+            .validate(&CertificateType::Bank, &subject("api", "Impostor Bank"))
+            .await;
+        assert!(matches!(result, Err(AstorError::InvalidOperation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_bank_subject_rejected_without_registry() {
+        let policy = SubjectPolicy::new();
+        assert!(policy
+            .validate(&CertificateType::Bank, &subject("api", "First Bank"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_node_subject_must_be_approved() {
+        let mut policy = SubjectPolicy::new();
+        policy.approve_node("node-1".to_string());
+
+        assert!(policy
+            .validate(&CertificateType::CurrencyNode, &subject("node-1", "Astor"))
+            .await
+            .is_ok());
+        assert!(policy
+            .validate(&CertificateType::CurrencyNode, &subject("node-2", "Astor"))
+            .await
+            .is_err());
+
+        policy.set_rule(CertificateType::CurrencyNode, SubjectRule::Unrestricted);
+        assert!(policy
+            .validate(&CertificateType::CurrencyNode, &subject("node-2", "Astor"))
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,189 @@
+//! PEM trust bundles for relying parties
+//!
+//! A bundle is the root certificate followed by the active intermediate CA
+//! certificates, concatenated as PEM. A relying party that imports it can
+//! validate leaf certificates without fetching each CA certificate
+//! separately: self-signed certificates in the bundle become trust anchors
+//! and the rest are used to build the path from a leaf up to them.
+
+use super::certificate::Certificate;
+use super::chain::{ChainValidationResult, ChainValidator};
+use crate::errors::AstorError;
+
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+/// CA certificates distributed together as a trust bundle
+#[derive(Debug, Clone)]
+pub struct TrustBundle {
+    certificates: Vec<Certificate>,
+}
+
+impl TrustBundle {
+    pub fn new(certificates: Vec<Certificate>) -> Self {
+        Self { certificates }
+    }
+
+    /// Parse a concatenated PEM bundle
+    pub fn from_pem(pem: &str) -> Result<Self, AstorError> {
+        let mut certificates = Vec::new();
+        let mut rest = pem;
+
+        while let Some(start) = rest.find(PEM_BEGIN) {
+            let block = &rest[start..];
+            let end = block.find(PEM_END).ok_or_else(|| {
+                AstorError::InvalidOperation("Unterminated certificate in trust bundle".to_string())
+            })? + PEM_END.len();
+            certificates.push(Certificate::from_pem(&block[..end])?);
+            rest = &block[end..];
+        }
+
+        if certificates.is_empty() {
+            return Err(AstorError::InvalidOperation(
+                "Trust bundle contains no certificates".to_string(),
+            ));
+        }
+        Ok(Self { certificates })
+    }
+
+    /// Serialize as concatenated PEM, root first
+    pub fn to_pem(&self) -> Result<String, AstorError> {
+        let blocks = self
+            .certificates
+            .iter()
+            .map(Certificate::to_pem)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(blocks.join("\n") + "\n")
+    }
+
+    pub fn certificates(&self) -> &[Certificate] {
+        &self.certificates
+    }
+
+    /// Self-signed certificates in the bundle
+    pub fn trust_anchors(&self) -> Vec<Certificate> {
+        self.certificates
+            .iter()
+            .filter(|cert| cert.subject() == cert.issuer())
+            .cloned()
+            .collect()
+    }
+
+    /// Validate a leaf certificate using only the bundle's certificates
+    pub fn validate(&self, leaf: &Certificate) -> Result<ChainValidationResult, AstorError> {
+        ChainValidator::new(self.trust_anchors()).validate(&self.build_chain(leaf))
+    }
+
+    /// Walk from the leaf up through the bundle's intermediates
+    fn build_chain(&self, leaf: &Certificate) -> Vec<Certificate> {
+        let intermediates: Vec<&Certificate> = self
+            .certificates
+            .iter()
+            .filter(|cert| cert.subject() != cert.issuer())
+            .collect();
+        let mut chain = vec![leaf.clone()];
+
+        // Each intermediate can appear at most once, which also bounds cycles
+        for _ in 0..intermediates.len() {
+            let current = &chain[chain.len() - 1];
+            let issuer = intermediates.iter().find(|cert| {
+                cert.subject() == current.issuer()
+                    && cert.serial_number() != current.serial_number()
+            });
+
+            match issuer {
+                Some(issuer) => chain.push((*issuer).clone()),
+                None => break,
+            }
+        }
+
+        chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate_authority::ca_core::CaConfig;
+    use crate::certificate_authority::certificate::{CertificateSubject, CertificateType};
+    use crate::certificate_authority::csr::{CertificateSigningRequest, CsrAttributes};
+    use crate::certificate_authority::AstorCertificateAuthority;
+    use crate::security::KeyPair;
+    use std::sync::Arc;
+
+    fn bank_certificate(issuer: &Certificate, issuer_keypair: &KeyPair) -> Certificate {
+        let subject = CertificateSubject {
+            common_name: "First Bank of Astoria".to_string(),
+            organization: "First Bank".to_string(),
+            organizational_unit: "Treasury".to_string(),
+            country: "AS".to_string(),
+            state: "".to_string(),
+            locality: "".to_string(),
+            email: "pki@firstbank.as".to_string(),
+        };
+        let attributes = CsrAttributes {
+            challenge_password: None,
+            unstructured_name: None,
+            requested_extensions: vec![],
+        };
+        let csr = CertificateSigningRequest::new(subject, &KeyPair::generate(), attributes, vec![])
+            .unwrap();
+
+        Certificate::from_csr(
+            csr,
+            "100".to_string(),
+            issuer.clone(),
+            issuer_keypair,
+            CertificateType::Bank,
+            365,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_leaf_validates_against_exported_bundle_only() {
+        let mut ca =
+            AstorCertificateAuthority::new(Arc::new(KeyPair::generate()), CaConfig::default())
+                .unwrap();
+        let intermediate_keypair = Arc::new(KeyPair::generate());
+        let ca_id = ca
+            .create_intermediate_ca(
+                "Operations".to_string(),
+                intermediate_keypair.clone(),
+                CaConfig::default(),
+            )
+            .await
+            .unwrap();
+        let intermediate = ca.get_intermediate_certificate(&ca_id).unwrap();
+        let leaf = bank_certificate(&intermediate, &intermediate_keypair);
+
+        let pem = ca.export_trust_bundle().unwrap();
+        assert_eq!(pem.matches(PEM_BEGIN).count(), 2);
+
+        // A relying party with nothing but the bundle
+        let bundle = AstorCertificateAuthority::import_trust_bundle(&pem).unwrap();
+        assert_eq!(bundle.trust_anchors().len(), 1);
+        assert!(bundle.validate(&leaf).unwrap().is_valid());
+
+        let foreign_keypair = KeyPair::generate();
+        let foreign_root = Certificate::new_root_ca(
+            foreign_keypair.public_key(),
+            "Elsewhere".to_string(),
+            "EL".to_string(),
+            10,
+        )
+        .unwrap();
+        let untrusted = bank_certificate(&foreign_root, &foreign_keypair);
+        assert!(!bundle.validate(&untrusted).unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_malformed_bundle_is_rejected() {
+        assert!(TrustBundle::from_pem("").is_err());
+        assert!(TrustBundle::from_pem("-----BEGIN CERTIFICATE-----\nAAAA").is_err());
+        assert!(TrustBundle::from_pem(
+            "-----BEGIN CERTIFICATE-----\n!!!\n-----END CERTIFICATE-----"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,318 @@
+//! Signed authorization for privileged central bank commands
+//!
+//! An administrator signs the command they intend to run together with the
+//! time it was issued. The handler checks the signature against the key held
+//! by `AdminManager`, so running the binary alone is not enough to mint money
+//! or trigger emergency operations.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::{Commands, EmergencyCommands, NetworkCommands};
+use crate::admin::AdminManager;
+use crate::errors::AstorError;
+use crate::security::{KeyPair, Permission, Signature};
+
+/// How long a signed command remains valid after it was issued
+const MAX_AUTHORIZATION_AGE_SECS: i64 = 300;
+
+/// Tolerated clock skew for authorizations issued slightly in the future
+const MAX_CLOCK_SKEW_SECS: i64 = 30;
+
+/// Administrator signature over a single command invocation
+#[derive(Debug, Clone)]
+pub struct CommandAuthorization {
+    pub admin_id: String,
+    pub issued_at: DateTime<Utc>,
+    /// Base64 Ed25519 signature over `signing_payload`
+    pub signature: String,
+}
+
+impl CommandAuthorization {
+    /// Build an authorization from CLI arguments
+    pub fn from_parts(
+        admin_id: String,
+        issued_at_unix: i64,
+        signature: String,
+    ) -> Result<Self, AstorError> {
+        let issued_at = Utc
+            .timestamp_opt(issued_at_unix, 0)
+            .single()
+            .ok_or_else(|| {
+                AstorError::Unauthorized(format!(
+                    "Invalid authorization timestamp: {}",
+                    issued_at_unix
+                ))
+            })?;
+
+        Ok(Self {
+            admin_id,
+            issued_at,
+            signature,
+        })
+    }
+
+    /// Sign a command on behalf of an administrator
+    pub fn sign(admin_id: &str, command: &Commands, keypair: &KeyPair) -> Result<Self, AstorError> {
+        let issued_at = Utc::now();
+        let payload = signing_payload(command, issued_at)?;
+
+        Ok(Self {
+            admin_id: admin_id.to_string(),
+            issued_at,
+            signature: keypair.sign(&payload).to_base64(),
+        })
+    }
+
+    /// Check the signature, its age, and the administrator's permissions
+    pub fn verify(&self, command: &Commands, admins: &AdminManager) -> Result<(), AstorError> {
+        let permission = required_permission(command).ok_or_else(|| {
+            AstorError::InvalidOperation("Command does not require authorization".to_string())
+        })?;
+
+        let age = Utc::now() - self.issued_at;
+        if age > Duration::seconds(MAX_AUTHORIZATION_AGE_SECS)
+            || age < Duration::seconds(-MAX_CLOCK_SKEW_SECS)
+        {
+            return Err(AstorError::Unauthorized(
+                "Command authorization has expired".to_string(),
+            ));
+        }
+
+        let admin = admins.get_admin(&self.admin_id)?;
+        if !admin.is_active {
+            return Err(AstorError::Unauthorized(
+                "Administrator is inactive".to_string(),
+            ));
+        }
+        if !admin.role.has_permission(&permission) {
+            return Err(AstorError::Unauthorized(format!(
+                "Administrator {} lacks {:?} permission",
+                self.admin_id, permission
+            )));
+        }
+
+        let signature = Signature::from_base64(&self.signature, self.admin_id.clone())?;
+        signature.verify_ignoring_age(
+            &admin.public_key,
+            &signing_payload(command, self.issued_at)?,
+        )
+    }
+}
+
+/// Authorizations already accepted, so each is accepted only once
+///
+/// An authorization is remembered only while it could still pass the age
+/// check in `CommandAuthorization::verify`, which keeps the store small.
+#[derive(Debug, Default)]
+pub struct AuthorizationLog {
+    used: Vec<UsedAuthorization>,
+    store_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct UsedAuthorization {
+    admin_id: String,
+    issued_at: DateTime<Utc>,
+    signature: String,
+}
+
+impl AuthorizationLog {
+    /// Log kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log persisted to `path`, loading the authorizations already there
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AstorError> {
+        let path = path.as_ref().to_path_buf();
+        let used = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(AstorError::InvalidOperation(format!(
+                    "Failed to read authorization store {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        Ok(Self {
+            used,
+            store_path: Some(path),
+        })
+    }
+
+    /// Write the log to the store, replacing the file atomically
+    fn save(&self) -> Result<(), AstorError> {
+        let Some(path) = &self.store_path else {
+            return Ok(());
+        };
+        let staging = path.with_extension("tmp");
+        std::fs::write(&staging, serde_json::to_vec_pretty(&self.used)?)
+            .and_then(|()| std::fs::rename(&staging, path))
+            .map_err(|e| {
+                AstorError::InvalidOperation(format!(
+                    "Failed to write authorization store {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    /// Record a verified authorization, failing if it was used before
+    pub fn record(&mut self, authorization: &CommandAuthorization) -> Result<(), AstorError> {
+        let expired_before = Utc::now() - Duration::seconds(MAX_AUTHORIZATION_AGE_SECS);
+        self.used.retain(|used| used.issued_at >= expired_before);
+
+        // Re-encoded so the same signature cannot pass in another spelling
+        let signature =
+            Signature::from_base64(&authorization.signature, authorization.admin_id.clone())?;
+        let used = UsedAuthorization {
+            admin_id: authorization.admin_id.clone(),
+            issued_at: authorization.issued_at,
+            signature: signature.to_base64(),
+        };
+        if self.used.contains(&used) {
+            return Err(AstorError::Unauthorized(
+                "Command authorization has already been used".to_string(),
+            ));
+        }
+        self.used.push(used);
+        self.save()
+    }
+}
+
+/// Permission an administrator needs to run a command, or `None` for
+/// read-only commands that need no authorization
+pub fn required_permission(command: &Commands) -> Option<Permission> {
+    match command {
+        Commands::Issue { .. } => Some(Permission::IssueCurrency),
+        Commands::SetRate { .. } => Some(Permission::SystemConfiguration),
+        Commands::Network { action } => match action {
+            NetworkCommands::ApproveBank { .. } | NetworkCommands::SuspendBank { .. } => {
+                Some(Permission::SystemConfiguration)
+            }
+            NetworkCommands::ListBanks | NetworkCommands::Stats => None,
+        },
+        // Pending timelocked operations are visible to everyone
+        Commands::Emergency {
+            action: EmergencyCommands::Scheduled,
+        } => None,
+        Commands::Emergency { .. } => Some(Permission::EmergencyShutdown),
+        Commands::Report { .. } | Commands::Status => None,
+    }
+}
+
+/// Short name and target of a privileged command for the audit trail
+pub fn describe_action(command: &Commands) -> (String, String) {
+    match command {
+        Commands::Issue {
+            amount, currency, ..
+        } => (
+            "issue_currency".to_string(),
+            format!("{} {}", amount, currency.to_uppercase()),
+        ),
+        Commands::SetRate {
+            rate_type, rate, ..
+        } => (
+            "set_interest_rate".to_string(),
+            format!("{}={}", rate_type, rate),
+        ),
+        Commands::Network { action } => match action {
+            NetworkCommands::ApproveBank { bank_id } => {
+                ("approve_bank".to_string(), bank_id.clone())
+            }
+            NetworkCommands::SuspendBank { bank_id, .. } => {
+                ("suspend_bank".to_string(), bank_id.clone())
+            }
+            NetworkCommands::ListBanks => ("list_banks".to_string(), String::new()),
+            NetworkCommands::Stats => ("network_stats".to_string(), String::new()),
+        },
+        Commands::Emergency { action } => {
+            let target = match action {
+                EmergencyCommands::Inject { amount, .. } => amount.to_string(),
+                EmergencyCommands::FreezeBank { bank_id } => bank_id.clone(),
+                EmergencyCommands::EmergencyHalt => "system".to_string(),
+                EmergencyCommands::Cancel { operation_id }
+                | EmergencyCommands::Approve { operation_id } => operation_id.clone(),
+                EmergencyCommands::Scheduled => String::new(),
+            };
+            (emergency_action_name(action).to_string(), target)
+        }
+        Commands::Report { .. } => ("report".to_string(), String::new()),
+        Commands::Status => ("status".to_string(), String::new()),
+    }
+}
+
+/// Audit name of an emergency operation, also used to designate timelocked
+/// operations
+pub fn emergency_action_name(command: &EmergencyCommands) -> &'static str {
+    match command {
+        EmergencyCommands::Inject { .. } => "emergency_inject",
+        EmergencyCommands::FreezeBank { .. } => "emergency_freeze_bank",
+        EmergencyCommands::EmergencyHalt => "emergency_halt",
+        EmergencyCommands::Cancel { .. } => "cancel_scheduled_operation",
+        EmergencyCommands::Approve { .. } => "approve_scheduled_operation",
+        EmergencyCommands::Scheduled => "list_scheduled_operations",
+    }
+}
+
+/// Bytes an administrator signs to authorize a command
+///
+/// Every argument is covered so a signature for one amount or bank cannot be
+/// reused for another.
+pub fn signing_payload(
+    command: &Commands,
+    issued_at: DateTime<Utc>,
+) -> Result<Vec<u8>, AstorError> {
+    let arguments = match command {
+        Commands::Issue {
+            amount,
+            currency,
+            justification,
+        } => serde_json::json!({
+            "amount": amount,
+            "currency": currency.to_uppercase(),
+            "justification": justification,
+        }),
+        Commands::SetRate {
+            rate_type,
+            rate,
+            justification,
+        } => serde_json::json!({
+            "rate_type": rate_type,
+            "rate": rate,
+            "justification": justification,
+        }),
+        Commands::Network { action } => match action {
+            NetworkCommands::ApproveBank { bank_id } => serde_json::json!({ "bank_id": bank_id }),
+            NetworkCommands::SuspendBank { bank_id, reason } => {
+                serde_json::json!({ "bank_id": bank_id, "reason": reason })
+            }
+            NetworkCommands::ListBanks | NetworkCommands::Stats => serde_json::json!({}),
+        },
+        Commands::Emergency { action } => match action {
+            EmergencyCommands::Inject { amount, reason } => {
+                serde_json::json!({ "amount": amount, "reason": reason })
+            }
+            EmergencyCommands::FreezeBank { bank_id } => serde_json::json!({ "bank_id": bank_id }),
+            EmergencyCommands::Cancel { operation_id }
+            | EmergencyCommands::Approve { operation_id } => {
+                serde_json::json!({ "operation_id": operation_id })
+            }
+            EmergencyCommands::EmergencyHalt | EmergencyCommands::Scheduled => {
+                serde_json::json!({})
+            }
+        },
+        Commands::Report { .. } | Commands::Status => serde_json::json!({}),
+    };
+
+    let (action, _) = describe_action(command);
+    Ok(serde_json::to_vec(&serde_json::json!({
+        "action": action,
+        "arguments": arguments,
+        "issued_at": issued_at.timestamp(),
+    }))?)
+}
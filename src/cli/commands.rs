@@ -23,6 +23,8 @@ pub enum Commands {
     Issue {
         #[arg(short, long)]
         amount: u64,
+        #[arg(long, default_value = "ASTOR")]
+        currency: String,
         #[arg(short, long)]
         justification: String,
     },
@@ -1,7 +1,7 @@
 //! CLI interface and handler implementation for the Astor Central Bank
 
 use crate::errors::AstorError;
-use crate::central_bank::CentralBank;
+use crate::central_bank::{CentralBank, DEFAULT_CURRENCY};
 use crate::banking_network::BankingNetwork;
 use super::commands::{Commands, NetworkCommands, ReportCommands, EmergencyCommands};
 
@@ -20,10 +20,10 @@ impl CliHandler {
 
     pub async fn handle_command(&mut self, command: Commands) -> Result<(), AstorError> {
         match command {
-            Commands::Issue { amount, justification } => {
-                let decision_id = self.central_bank.issue_currency(amount, justification)?;
+            Commands::Issue { amount, currency, justification } => {
+                let decision_id = self.central_bank.issue_currency(&currency, amount, justification)?;
                 println!("✅ Currency issued successfully. Decision ID: {}", decision_id);
-                println!("💰 Amount: {} ASTOR", amount);
+                println!("💰 Amount: {} {}", amount, currency.to_uppercase());
             }
             
             Commands::SetRate { rate_type, rate, justification } => {
@@ -86,7 +86,11 @@ impl CliHandler {
             ReportCommands::MoneySupply => {
                 let stats = self.central_bank.get_money_supply_stats();
                 println!("💰 Money Supply Report:");
-                println!("   Total Supply: {} ASTOR", stats.total_supply);
+                let mut currencies: Vec<_> = stats.supply_by_currency.iter().collect();
+                currencies.sort();
+                for (currency, supply) in currencies {
+                    println!("   Total Supply: {} {}", supply, currency);
+                }
                 println!("   Base Interest Rate: {}%", stats.base_interest_rate * 100.0);
                 println!("   Inflation Target: {}%", stats.inflation_target * 100.0);
             }
@@ -116,7 +120,7 @@ impl CliHandler {
     async fn handle_emergency_command(&mut self, command: EmergencyCommands) -> Result<(), AstorError> {
         match command {
             EmergencyCommands::Inject { amount, reason } => {
-                let decision_id = self.central_bank.issue_currency(amount, format!("EMERGENCY: {}", reason))?;
+                let decision_id = self.central_bank.issue_currency(DEFAULT_CURRENCY, amount, format!("EMERGENCY: {}", reason))?;
                 println!("🚨 Emergency currency injection completed");
                 println!("💰 Amount: {} ASTOR", amount);
                 println!("📋 Decision ID: {}", decision_id);
@@ -142,7 +146,7 @@ impl CliHandler {
         let money_stats = self.central_bank.get_money_supply_stats();
         let network_stats = self.banking_network.get_network_stats().await;
         
-        println!("💰 Money Supply: {} ASTOR", money_stats.total_supply);
+        println!("💰 Money Supply: {} {}", money_stats.supply_of(DEFAULT_CURRENCY), DEFAULT_CURRENCY);
         println!("📊 Base Rate: {}%", money_stats.base_interest_rate * 100.0);
         println!("🏦 Active Banks: {}", network_stats.active_banks);
         println!("🟢 System Status: Operational");
@@ -1,15 +1,32 @@
 //! Central Bank CLI for currency management
 
+pub mod authorization;
 pub mod commands;
 pub mod interface;
+pub mod output;
+pub mod timelock;
 
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
+use crate::admin::AdminManager;
 use crate::banking_network::BankingNetwork;
-use crate::central_bank::CentralBank;
+use crate::central_bank::{CentralBank, DEFAULT_CURRENCY};
 use crate::errors::AstorError;
+use crate::security::{SecurityAuditLogger, SecurityEvent};
+use output::{
+    BankApprovedOutput, BankFrozenOutput, BankListOutput, BankingNetworkReportOutput,
+    CommandOutput, ComplianceReportOutput, EconomicReportOutput, EmergencyHaltOutput,
+    EmergencyInjectionOutput, InterestRateOutput, IssuanceOutput, OperationApprovedOutput,
+    OperationCancelledOutput, OperationTimelockedOutput, ScheduledOperationsOutput,
+    SuspensionProposedOutput, SystemStatusOutput,
+};
+
+pub use authorization::{AuthorizationLog, CommandAuthorization};
+pub use output::OutputFormat;
+pub use timelock::{ScheduledOperation, Timelock, TimelockPolicy, TimelockRule};
 
 #[derive(Parser)]
 #[command(name = "astor-central-bank")]
@@ -23,6 +40,42 @@ pub struct CentralBankCli {
 
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// Administrator authorizing a privileged command
+    #[arg(long, global = true)]
+    pub admin_id: Option<String>,
+
+    /// Unix time at which the authorization was signed
+    #[arg(long, global = true)]
+    pub auth_timestamp: Option<i64>,
+
+    /// Base64 administrator signature over the command and timestamp
+    #[arg(long, global = true)]
+    pub auth_signature: Option<String>,
+
+    /// File holding timelocked emergency operations between invocations
+    #[arg(long, global = true, default_value = "timelock.json")]
+    pub timelock_store: PathBuf,
+
+    /// File recording accepted authorizations so none is used twice
+    #[arg(long, global = true, default_value = "authorizations.json")]
+    pub authorization_store: PathBuf,
+}
+
+impl CentralBankCli {
+    /// Signed authorization supplied on the command line, if any
+    pub fn authorization(&self) -> Result<Option<CommandAuthorization>, AstorError> {
+        match (&self.admin_id, self.auth_timestamp, &self.auth_signature) {
+            (None, None, None) => Ok(None),
+            (Some(admin_id), Some(issued_at), Some(signature)) => Ok(Some(
+                CommandAuthorization::from_parts(admin_id.clone(), issued_at, signature.clone())?,
+            )),
+            _ => Err(AstorError::Unauthorized(
+                "--admin-id, --auth-timestamp and --auth-signature must be given together"
+                    .to_string(),
+            )),
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -31,6 +84,8 @@ pub enum Commands {
     Issue {
         #[arg(short, long)]
         amount: u64,
+        #[arg(long, default_value = "ASTOR")]
+        currency: String,
         #[arg(short, long)]
         justification: String,
     },
@@ -105,7 +160,7 @@ pub enum ReportCommands {
     Economic,
 }
 
-#[derive(Subcommand)]
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize)]
 pub enum EmergencyCommands {
     /// Emergency currency injection
     Inject {
@@ -123,33 +178,122 @@ pub enum EmergencyCommands {
 
     /// System-wide emergency halt
     EmergencyHalt,
+
+    /// Cancel a timelocked operation before it executes
+    Cancel {
+        #[arg(short, long)]
+        operation_id: String,
+    },
+
+    /// Approve a timelocked operation; a super-quorum runs it early
+    Approve {
+        #[arg(short, long)]
+        operation_id: String,
+    },
+
+    /// List timelocked operations awaiting execution
+    Scheduled,
 }
 
 pub struct CliHandler {
     central_bank: CentralBank,
     banking_network: BankingNetwork,
+    admin_manager: AdminManager,
+    audit_logger: SecurityAuditLogger,
+    timelock: Timelock,
+    /// Authorizations already accepted, so a signed command runs once
+    used_authorizations: AuthorizationLog,
+    output_format: OutputFormat,
+    /// Where command results are written; standard output by default
+    output: Box<dyn Write + Send>,
 }
 
 impl CliHandler {
-    pub fn new(central_bank: CentralBank, banking_network: BankingNetwork) -> Self {
+    pub fn new(
+        central_bank: CentralBank,
+        banking_network: BankingNetwork,
+        admin_manager: AdminManager,
+    ) -> Self {
         Self {
             central_bank,
             banking_network,
+            admin_manager,
+            audit_logger: SecurityAuditLogger::new(),
+            timelock: Timelock::default(),
+            used_authorizations: AuthorizationLog::new(),
+            output_format: OutputFormat::default(),
+            output: Box::new(std::io::stdout()),
         }
     }
 
-    pub async fn handle_command(&mut self, command: Commands) -> Result<(), AstorError> {
+    /// Print command results as decorated text or JSON
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.output_format = format;
+    }
+
+    /// Write command results to `output` instead of standard output
+    pub fn set_output(&mut self, output: Box<dyn Write + Send>) {
+        self.output = output;
+    }
+
+    /// Write a command result in the configured format
+    fn emit<T: CommandOutput>(&mut self, result: &T) -> Result<(), AstorError> {
+        output::write(self.output.as_mut(), self.output_format, result)
+    }
+
+    /// Choose which emergency operations are timelocked
+    pub fn set_timelock_policy(&mut self, policy: TimelockPolicy) {
+        self.timelock.set_policy(policy);
+    }
+
+    /// Persist timelocked operations to `path`, picking up those scheduled
+    /// by earlier invocations
+    pub fn open_timelock_store(&mut self, path: impl AsRef<Path>) -> Result<(), AstorError> {
+        self.timelock = Timelock::open(self.timelock.policy().clone(), path)?;
+        Ok(())
+    }
+
+    /// Persist accepted authorizations to `path`, so one accepted by an
+    /// earlier invocation is refused
+    pub fn open_authorization_store(&mut self, path: impl AsRef<Path>) -> Result<(), AstorError> {
+        self.used_authorizations = AuthorizationLog::open(path)?;
+        Ok(())
+    }
+
+    pub fn timelock(&self) -> &Timelock {
+        &self.timelock
+    }
+
+    /// Audit trail of authorized and rejected privileged commands
+    pub fn audit_logger(&self) -> &SecurityAuditLogger {
+        &self.audit_logger
+    }
+
+    /// Execute a command
+    ///
+    /// Privileged commands (issuance, rate changes, network changes and all
+    /// emergency operations) require a valid administrator signature.
+    pub async fn handle_command(
+        &mut self,
+        command: Commands,
+        authorization: Option<&CommandAuthorization>,
+    ) -> Result<(), AstorError> {
+        self.authorize(&command, authorization).await?;
+
         match command {
             Commands::Issue {
                 amount,
+                currency,
                 justification,
             } => {
-                let decision_id = self.central_bank.issue_currency(amount, justification)?;
-                println!(
-                    "✅ Currency issued successfully. Decision ID: {}",
-                    decision_id
-                );
-                println!("💰 Amount: {} ASTOR", amount);
+                let decision_id =
+                    self.central_bank
+                        .issue_currency(&currency, amount, justification)?;
+                self.emit(&IssuanceOutput {
+                    decision_id,
+                    amount,
+                    currency: currency.to_uppercase(),
+                })?;
             }
 
             Commands::SetRate {
@@ -159,8 +303,7 @@ impl CliHandler {
             } => {
                 self.central_bank
                     .set_interest_rate(rate_type.clone(), rate, justification)?;
-                println!("✅ Interest rate set successfully");
-                println!("📊 {}: {}%", rate_type, rate * 100.0);
+                self.emit(&InterestRateOutput { rate_type, rate })?;
             }
 
             Commands::Network { action } => {
@@ -176,38 +319,92 @@ impl CliHandler {
             }
 
             Commands::Emergency { action } => {
-                self.handle_emergency_command(action).await?;
+                let admin_id = authorization
+                    .map(|a| a.admin_id.as_str())
+                    .unwrap_or_default();
+                self.handle_emergency_command(action, admin_id).await?;
             }
         }
 
         Ok(())
     }
 
+    /// Reject privileged commands without a valid signature and audit the rest
+    async fn authorize(
+        &mut self,
+        command: &Commands,
+        authorization: Option<&CommandAuthorization>,
+    ) -> Result<(), AstorError> {
+        if authorization::required_permission(command).is_none() {
+            return Ok(());
+        }
+
+        let (action, target) = authorization::describe_action(command);
+        let result = match authorization {
+            Some(authorization) => authorization
+                .verify(command, &self.admin_manager)
+                .and_then(|()| self.used_authorizations.record(authorization)),
+            None => Err(AstorError::Unauthorized(format!(
+                "{} requires a signed administrator authorization",
+                action
+            ))),
+        };
+
+        let admin_id = authorization.map(|a| a.admin_id.clone());
+        match result {
+            Ok(()) => {
+                self.audit_logger
+                    .log_security_event(SecurityEvent::AdminAction {
+                        admin_id: admin_id.unwrap_or_default(),
+                        action,
+                        target,
+                        timestamp: chrono::Utc::now(),
+                    })
+                    .await?;
+                Ok(())
+            }
+            Err(e) => {
+                tracing::warn!("Rejected unauthorized CLI command {}: {}", action, e);
+                self.audit_logger
+                    .log_security_event(SecurityEvent::PermissionDenied {
+                        user_id: admin_id.unwrap_or_else(|| "unauthenticated".to_string()),
+                        resource: target,
+                        action,
+                        timestamp: chrono::Utc::now(),
+                    })
+                    .await?;
+                Err(e)
+            }
+        }
+    }
+
     async fn handle_network_command(&mut self, command: NetworkCommands) -> Result<(), AstorError> {
         match command {
             NetworkCommands::ListBanks => {
-                // Implementation for listing banks
-                println!("📋 Registered Banks:");
-                // Would list all registered banks here
+                let banks = self.banking_network.list_banks().await;
+                self.emit(&BankListOutput { banks })?;
             }
 
             NetworkCommands::ApproveBank { bank_id } => {
                 self.banking_network.approve_bank(&bank_id).await?;
-                println!("✅ Bank {} approved successfully", bank_id);
+                self.emit(&BankApprovedOutput { bank_id })?;
             }
 
             NetworkCommands::SuspendBank { bank_id, reason } => {
-                // Implementation for suspending bank
-                println!("⚠️  Bank {} suspended. Reason: {}", bank_id, reason);
+                let proposal_id = self
+                    .banking_network
+                    .propose_suspension(&bank_id, reason.clone())
+                    .await?;
+                self.emit(&SuspensionProposedOutput {
+                    bank_id,
+                    reason,
+                    proposal_id,
+                })?;
             }
 
             NetworkCommands::Stats => {
                 let stats = self.banking_network.get_network_stats().await;
-                println!("🏦 Banking Network Statistics:");
-                println!("   Total Banks: {}", stats.total_registered_banks);
-                println!("   Active Banks: {}", stats.active_banks);
-                println!("   Pending Approvals: {}", stats.pending_approvals);
-                println!("   Suspended Banks: {}", stats.suspended_banks);
+                self.emit(&stats)?;
             }
         }
 
@@ -218,32 +415,29 @@ impl CliHandler {
         match command {
             ReportCommands::MoneySupply => {
                 let stats = self.central_bank.get_money_supply_stats();
-                println!("💰 Money Supply Report:");
-                println!("   Total Supply: {} ASTOR", stats.total_supply);
-                println!(
-                    "   Base Interest Rate: {}%",
-                    stats.base_interest_rate * 100.0
-                );
-                println!("   Inflation Target: {}%", stats.inflation_target * 100.0);
+                self.emit(&stats)?;
             }
 
             ReportCommands::BankingNetwork => {
                 let stats = self.banking_network.get_network_stats().await;
-                println!("🏦 Banking Network Report:");
-                println!("   Network Health: Active");
-                println!("   Total Banks: {}", stats.total_registered_banks);
-                println!("   Active Banks: {}", stats.active_banks);
+                self.emit(&BankingNetworkReportOutput {
+                    network_health: "Active".to_string(),
+                    total_banks: stats.total_registered_banks,
+                    active_banks: stats.active_banks,
+                })?;
             }
 
             ReportCommands::Compliance => {
-                println!("📊 Compliance Report:");
-                println!("   Overall Status: Compliant");
+                self.emit(&ComplianceReportOutput {
+                    overall_status: "Compliant".to_string(),
+                })?;
                 // Would generate detailed compliance report
             }
 
             ReportCommands::Economic => {
-                println!("📈 Economic Indicators:");
-                println!("   System Status: Operational");
+                self.emit(&EconomicReportOutput {
+                    system_status: "Operational".to_string(),
+                })?;
                 // Would show economic metrics
             }
         }
@@ -254,44 +448,457 @@ impl CliHandler {
     async fn handle_emergency_command(
         &mut self,
         command: EmergencyCommands,
+        admin_id: &str,
+    ) -> Result<(), AstorError> {
+        match command {
+            EmergencyCommands::Cancel { operation_id } => {
+                self.timelock.cancel(&operation_id, admin_id)?;
+                self.emit(&OperationCancelledOutput { operation_id })?;
+            }
+
+            EmergencyCommands::Approve { operation_id } => {
+                let approvals = self.timelock.approve(&operation_id, admin_id)?;
+                let super_quorum = self.timelock.policy().super_quorum;
+                self.emit(&OperationApprovedOutput {
+                    operation_id,
+                    approvals,
+                    super_quorum,
+                })?;
+                self.execute_due_operations(chrono::Utc::now()).await?;
+            }
+
+            EmergencyCommands::Scheduled => {
+                let scheduled = ScheduledOperationsOutput::new(&self.timelock.pending());
+                self.emit(&scheduled)?;
+            }
+
+            command => match self.timelock.policy().delay_for(&command) {
+                Some(delay) => {
+                    let operation_id =
+                        self.timelock
+                            .schedule(command, admin_id, delay, chrono::Utc::now())?;
+                    self.emit(&OperationTimelockedOutput {
+                        operation_id,
+                        delay_secs: delay.num_seconds(),
+                    })?;
+                }
+                None => self.execute_emergency_command(command).await?,
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Run timelocked operations whose delay has elapsed at `now`, or that a
+    /// super-quorum approved; returns their IDs
+    pub async fn execute_due_operations(
+        &mut self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<String>, AstorError> {
+        let mut executed = Vec::new();
+        for operation in self.timelock.take_ready(now)? {
+            tracing::info!(
+                "Executing timelocked {} {} scheduled by {}",
+                operation.action(),
+                operation.id,
+                operation.scheduled_by
+            );
+            let id = operation.id.clone();
+            match self.execute_emergency_command(operation.command).await {
+                Ok(()) => executed.push(id),
+                Err(e) => tracing::error!("Timelocked operation {} failed: {}", id, e),
+            }
+        }
+        Ok(executed)
+    }
+
+    async fn execute_emergency_command(
+        &mut self,
+        command: EmergencyCommands,
     ) -> Result<(), AstorError> {
         match command {
             EmergencyCommands::Inject { amount, reason } => {
-                let decision_id = self
-                    .central_bank
-                    .issue_currency(amount, format!("EMERGENCY: {}", reason))?;
-                println!("🚨 Emergency currency injection completed");
-                println!("💰 Amount: {} ASTOR", amount);
-                println!("📋 Decision ID: {}", decision_id);
+                let decision_id = self.central_bank.issue_currency(
+                    DEFAULT_CURRENCY,
+                    amount,
+                    format!("EMERGENCY: {}", reason),
+                )?;
+                self.emit(&EmergencyInjectionOutput {
+                    decision_id,
+                    amount,
+                    currency: DEFAULT_CURRENCY.to_string(),
+                })?;
             }
 
             EmergencyCommands::FreezeBank { bank_id } => {
-                println!("🚨 Bank {} operations frozen", bank_id);
+                self.emit(&BankFrozenOutput { bank_id })?;
                 // Would implement bank freezing logic
             }
 
             EmergencyCommands::EmergencyHalt => {
-                println!("🚨 EMERGENCY SYSTEM HALT INITIATED");
-                println!("⚠️  All operations suspended pending review");
+                self.emit(&EmergencyHaltOutput {
+                    operations_suspended: true,
+                })?;
                 // Would implement system-wide halt
             }
+
+            EmergencyCommands::Cancel { .. }
+            | EmergencyCommands::Approve { .. }
+            | EmergencyCommands::Scheduled => {
+                return Err(AstorError::InvalidOperation(
+                    "Not an executable emergency operation".to_string(),
+                ));
+            }
         }
 
         Ok(())
     }
 
-    async fn display_system_status(&self) -> Result<(), AstorError> {
-        println!("🏛️  Astor Central Bank System Status");
-        println!("================================");
-
+    async fn display_system_status(&mut self) -> Result<(), AstorError> {
         let money_stats = self.central_bank.get_money_supply_stats();
         let network_stats = self.banking_network.get_network_stats().await;
 
-        println!("💰 Money Supply: {} ASTOR", money_stats.total_supply);
-        println!("📊 Base Rate: {}%", money_stats.base_interest_rate * 100.0);
-        println!("🏦 Active Banks: {}", network_stats.active_banks);
-        println!("🟢 System Status: Operational");
+        self.emit(&SystemStatusOutput {
+            money_supply: money_stats.supply_of(DEFAULT_CURRENCY),
+            currency: DEFAULT_CURRENCY.to_string(),
+            base_interest_rate: money_stats.base_interest_rate,
+            active_banks: network_stats.active_banks,
+            status: "Operational".to_string(),
+        })
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::central_bank::CentralBankConfig;
+    use crate::security::KeyPair;
+
+    fn handler_with_root(root_keypair: &KeyPair) -> CliHandler {
+        let central_bank = CentralBank::new(CentralBankConfig {
+            base_interest_rate: 0.025,
+            reserve_requirement_ratio: 0.10,
+            inflation_target: 0.02,
+            money_supply_growth_target: 0.03,
+            emergency_lending_rate: 0.05,
+        });
+        let banking_network = BankingNetwork::new(central_bank.clone());
+        let mut admin_manager = AdminManager::new();
+        admin_manager
+            .add_admin("root".to_string(), root_keypair.public_key())
+            .unwrap();
+
+        CliHandler::new(central_bank, banking_network, admin_manager)
+    }
+
+    /// Output sink the test can read back after the handler writes to it
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn issue_command(amount: u64) -> Commands {
+        Commands::Issue {
+            amount,
+            currency: "ASTOR".to_string(),
+            justification: "liquidity".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_mode_writes_one_document_per_result() {
+        let root_keypair = KeyPair::generate();
+        let mut handler = handler_with_root(&root_keypair);
+        let buffer = SharedBuffer::default();
+        handler.set_output(Box::new(buffer.clone()));
+        handler.set_output_format(OutputFormat::Json);
+
+        let command = issue_command(1_000);
+        let authorization = CommandAuthorization::sign("root", &command, &root_keypair).unwrap();
+        handler
+            .handle_command(command, Some(&authorization))
+            .await
+            .unwrap();
+        handler
+            .handle_command(Commands::Status, None)
+            .await
+            .unwrap();
+        handler
+            .handle_command(
+                Commands::Emergency {
+                    action: EmergencyCommands::Scheduled,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let written = buffer.0.lock().unwrap().clone();
+        let documents: Vec<serde_json::Value> = serde_json::Deserializer::from_slice(&written)
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0]["amount"], 1_000);
+        assert_eq!(documents[0]["currency"], "ASTOR");
+        assert_eq!(documents[1]["money_supply"], 1_000);
+        assert_eq!(documents[1]["status"], "Operational");
+        assert_eq!(documents[2]["operations"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_privileged_command_rejected() {
+        let mut handler = handler_with_root(&KeyPair::generate());
+
+        let result = handler.handle_command(issue_command(1_000), None).await;
+        assert!(matches!(result, Err(AstorError::Unauthorized(_))));
+
+        let emergency = Commands::Emergency {
+            action: EmergencyCommands::EmergencyHalt,
+        };
+        assert!(handler.handle_command(emergency, None).await.is_err());
+
+        assert_eq!(
+            handler
+                .central_bank
+                .get_money_supply_stats()
+                .supply_of(DEFAULT_CURRENCY),
+            0
+        );
+        assert!(handler
+            .audit_logger()
+            .get_logs(None, None)
+            .iter()
+            .all(|entry| matches!(entry.event, SecurityEvent::PermissionDenied { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_signed_command_executes_and_is_audited() {
+        let root_keypair = KeyPair::generate();
+        let mut handler = handler_with_root(&root_keypair);
+
+        let command = issue_command(1_000);
+        let authorization = CommandAuthorization::sign("root", &command, &root_keypair).unwrap();
+        handler
+            .handle_command(command, Some(&authorization))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            handler
+                .central_bank
+                .get_money_supply_stats()
+                .supply_of(DEFAULT_CURRENCY),
+            1_000
+        );
+        assert!(handler
+            .audit_logger()
+            .get_logs(None, None)
+            .iter()
+            .any(|entry| matches!(
+                &entry.event,
+                SecurityEvent::AdminAction { admin_id, action, .. }
+                    if admin_id == "root" && action == "issue_currency"
+            )));
+    }
+
+    #[tokio::test]
+    async fn test_signature_bound_to_command_arguments() {
+        let root_keypair = KeyPair::generate();
+        let mut handler = handler_with_root(&root_keypair);
+
+        let authorization =
+            CommandAuthorization::sign("root", &issue_command(1_000), &root_keypair).unwrap();
+
+        let result = handler
+            .handle_command(issue_command(1_000_000), Some(&authorization))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_authorization_accepted_only_once_across_invocations() {
+        let path = std::env::temp_dir().join(format!(
+            "astor-authorizations-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        let root_keypair = KeyPair::generate();
+        let command = issue_command(1_000);
+        let authorization = CommandAuthorization::sign("root", &command, &root_keypair).unwrap();
+
+        let mut handler = handler_with_root(&root_keypair);
+        handler.open_authorization_store(&path).unwrap();
+        handler
+            .handle_command(issue_command(1_000), Some(&authorization))
+            .await
+            .unwrap();
+        assert!(matches!(
+            handler
+                .handle_command(issue_command(1_000), Some(&authorization))
+                .await,
+            Err(AstorError::Unauthorized(_))
+        ));
+
+        // A later invocation replaying the same arguments is refused too
+        let mut handler = handler_with_root(&root_keypair);
+        handler.open_authorization_store(&path).unwrap();
+        assert!(handler
+            .handle_command(command, Some(&authorization))
+            .await
+            .is_err());
+        assert_eq!(
+            handler
+                .central_bank
+                .get_money_supply_stats()
+                .supply_of(DEFAULT_CURRENCY),
+            0
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_signature_from_unknown_key_rejected() {
+        let mut handler = handler_with_root(&KeyPair::generate());
+
+        let command = issue_command(1_000);
+        let authorization =
+            CommandAuthorization::sign("root", &command, &KeyPair::generate()).unwrap();
+
+        assert!(handler
+            .handle_command(command, Some(&authorization))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timelocked_operation_waits_for_delay_and_can_be_cancelled() {
+        let root_keypair = KeyPair::generate();
+        let mut handler = handler_with_root(&root_keypair);
+        handler.set_timelock_policy(TimelockPolicy {
+            rules: vec![TimelockRule {
+                action: "emergency_inject".to_string(),
+                delay_secs: 3600,
+                min_amount: None,
+            }],
+            super_quorum: 3,
+        });
+
+        let supply = |handler: &CliHandler| {
+            handler
+                .central_bank
+                .get_money_supply_stats()
+                .supply_of(DEFAULT_CURRENCY)
+        };
+        let inject = |amount| Commands::Emergency {
+            action: EmergencyCommands::Inject {
+                amount,
+                reason: "bank run".to_string(),
+            },
+        };
+
+        for amount in [5_000, 7_000] {
+            let command = inject(amount);
+            let authorization =
+                CommandAuthorization::sign("root", &command, &root_keypair).unwrap();
+            handler
+                .handle_command(command, Some(&authorization))
+                .await
+                .unwrap();
+        }
+        assert_eq!(supply(&handler), 0);
+        let pending: Vec<String> = handler
+            .timelock()
+            .pending()
+            .iter()
+            .map(|operation| operation.id.clone())
+            .collect();
+        assert_eq!(pending.len(), 2);
+
+        let cancel = Commands::Emergency {
+            action: EmergencyCommands::Cancel {
+                operation_id: pending[1].clone(),
+            },
+        };
+        let authorization = CommandAuthorization::sign("root", &cancel, &root_keypair).unwrap();
+        handler
+            .handle_command(cancel, Some(&authorization))
+            .await
+            .unwrap();
+
+        let now = chrono::Utc::now();
+        assert!(handler
+            .execute_due_operations(now)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(supply(&handler), 0);
+
+        let executed = handler
+            .execute_due_operations(now + chrono::Duration::seconds(3601))
+            .await
+            .unwrap();
+        assert_eq!(executed, vec![pending[0].clone()]);
+        assert_eq!(supply(&handler), 5_000);
+        assert!(handler.timelock().pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_timelocked_operation_survives_restart_and_runs_when_due() {
+        let path =
+            std::env::temp_dir().join(format!("astor-timelock-{}.json", uuid::Uuid::new_v4()));
+        let root_keypair = KeyPair::generate();
+        let command = Commands::Emergency {
+            action: EmergencyCommands::Inject {
+                amount: 2_000_000,
+                reason: "bank run".to_string(),
+            },
+        };
+
+        let mut handler = handler_with_root(&root_keypair);
+        handler.open_timelock_store(&path).unwrap();
+        let authorization = CommandAuthorization::sign("root", &command, &root_keypair).unwrap();
+        handler
+            .handle_command(command, Some(&authorization))
+            .await
+            .unwrap();
+        let operation_id = handler.timelock().pending()[0].id.clone();
+
+        // A later invocation sees the pending operation and runs it once due
+        let mut handler = handler_with_root(&root_keypair);
+        handler.open_timelock_store(&path).unwrap();
+        assert_eq!(handler.timelock().pending().len(), 1);
+        let due = chrono::Utc::now() + chrono::Duration::days(2);
+        assert_eq!(
+            handler.execute_due_operations(due).await.unwrap(),
+            vec![operation_id]
+        );
+        assert_eq!(
+            handler
+                .central_bank
+                .get_money_supply_stats()
+                .supply_of(DEFAULT_CURRENCY),
+            2_000_000
+        );
+
+        let mut handler = handler_with_root(&root_keypair);
+        handler.open_timelock_store(&path).unwrap();
+        assert!(handler.timelock().pending().is_empty());
+        assert!(handler
+            .execute_due_operations(due)
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_file(path).unwrap();
     }
 }
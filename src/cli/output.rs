@@ -0,0 +1,689 @@
+//! Text and JSON output for CLI commands
+//!
+//! Commands build a serializable report and print it either as decorated
+//! text for operators or, with `--output json`, as one JSON document that
+//! scripts can parse.
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+use crate::admin::Administrator;
+use crate::banking_network::{NetworkStats, RegisteredBank};
+use crate::central_bank::MoneySupplyStats;
+use crate::errors::AstorError;
+
+use super::ScheduledOperation;
+
+/// How command results are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// A command result that can be printed in either format
+pub trait CommandOutput: Serialize {
+    /// Human-readable rendering
+    fn to_text(&self) -> String;
+}
+
+/// Render a command result in the requested format
+pub fn render<T: CommandOutput>(format: OutputFormat, output: &T) -> Result<String, AstorError> {
+    match format {
+        OutputFormat::Text => Ok(output.to_text()),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(output)?),
+    }
+}
+
+/// Print a command result in the requested format
+pub fn print<T: CommandOutput>(format: OutputFormat, output: &T) -> Result<(), AstorError> {
+    write(&mut std::io::stdout().lock(), format, output)
+}
+
+/// Write a command result in the requested format, one result per line
+/// in text mode and one document per result in JSON mode
+pub fn write<W: Write + ?Sized, T: CommandOutput>(
+    writer: &mut W,
+    format: OutputFormat,
+    output: &T,
+) -> Result<(), AstorError> {
+    writeln!(writer, "{}", render(format, output)?)
+        .map_err(|e| AstorError::InvalidOperation(format!("Failed to write output: {}", e)))
+}
+
+/// A failed command, reported without aborting the CLI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorOutput {
+    pub error: String,
+}
+
+impl ErrorOutput {
+    pub fn new(context: &str, error: &AstorError) -> Self {
+        Self {
+            error: format!("{}: {}", context, error),
+        }
+    }
+}
+
+impl CommandOutput for ErrorOutput {
+    fn to_text(&self) -> String {
+        format!("❌ {}", self.error)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceOutput {
+    pub account_id: String,
+    pub balance: u64,
+    pub currency: String,
+}
+
+impl CommandOutput for BalanceOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "Account {} balance: {} {}",
+            self.account_id, self.balance, self.currency
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatsOutput {
+    pub total_supply: u64,
+    pub ledger_entries: usize,
+    pub active_administrators: usize,
+    pub total_transactions: usize,
+    pub registered_banks: usize,
+    pub active_banks: usize,
+}
+
+impl CommandOutput for SystemStatsOutput {
+    fn to_text(&self) -> String {
+        [
+            "=== Astor System Statistics ===".to_string(),
+            format!("Total supply: {} ASTOR", self.total_supply),
+            format!("Total ledger entries: {}", self.ledger_entries),
+            format!("Active administrators: {}", self.active_administrators),
+            format!("Total transactions: {}", self.total_transactions),
+            format!("Registered banks: {}", self.registered_banks),
+            format!("Active banks: {}", self.active_banks),
+        ]
+        .join("\n")
+    }
+}
+
+impl CommandOutput for NetworkStats {
+    fn to_text(&self) -> String {
+        [
+            "🏦 Banking Network Statistics:".to_string(),
+            format!("   Total Banks: {}", self.total_registered_banks),
+            format!("   Active Banks: {}", self.active_banks),
+            format!("   Pending Approvals: {}", self.pending_approvals),
+            format!("   Suspended Banks: {}", self.suspended_banks),
+        ]
+        .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankListOutput {
+    pub banks: Vec<RegisteredBank>,
+}
+
+impl CommandOutput for BankListOutput {
+    fn to_text(&self) -> String {
+        let mut lines = vec![format!("📋 Registered Banks ({}):", self.banks.len())];
+        for bank in &self.banks {
+            let services = bank
+                .services_offered
+                .iter()
+                .map(|service| format!("{:?}", service))
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!(
+                "  {} [{}] {:?} - {} ({})",
+                bank.bank_name, bank.bank_id, bank.status, bank.license_number, services
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Result of a currency issuance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuanceOutput {
+    pub decision_id: String,
+    pub amount: u64,
+    pub currency: String,
+}
+
+impl CommandOutput for IssuanceOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "✅ Currency issued successfully. Decision ID: {}\n💰 Amount: {} {}",
+            self.decision_id, self.amount, self.currency
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountCreatedOutput {
+    pub account_id: String,
+    /// Base64 public key, when one was generated for the account
+    pub public_key: Option<String>,
+}
+
+impl CommandOutput for AccountCreatedOutput {
+    fn to_text(&self) -> String {
+        let mut text = format!("✅ Created new account: {}", self.account_id);
+        if let Some(public_key) = &self.public_key {
+            text.push_str(&format!("\nAccount public key: {}", public_key));
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminSummary {
+    pub id: String,
+    pub role: String,
+    /// Base64 public key
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminListOutput {
+    pub admins: Vec<AdminSummary>,
+}
+
+impl AdminListOutput {
+    pub fn new(admins: &[&Administrator]) -> Self {
+        Self {
+            admins: admins
+                .iter()
+                .map(|admin| AdminSummary {
+                    id: admin.id.clone(),
+                    role: admin.role.to_string(),
+                    public_key: general_purpose::STANDARD.encode(admin.public_key.as_bytes()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CommandOutput for AdminListOutput {
+    fn to_text(&self) -> String {
+        let mut lines = vec!["Active administrators:".to_string()];
+        for admin in &self.admins {
+            lines.push(format!(
+                "  - {} ({}): {}",
+                admin.id, admin.role, admin.public_key
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerVerificationOutput {
+    pub valid: bool,
+}
+
+impl CommandOutput for LedgerVerificationOutput {
+    fn to_text(&self) -> String {
+        if self.valid {
+            "✅ Ledger integrity verified".to_string()
+        } else {
+            "❌ Ledger integrity check failed".to_string()
+        }
+    }
+}
+
+impl CommandOutput for MoneySupplyStats {
+    fn to_text(&self) -> String {
+        let mut lines = vec!["💰 Money Supply Report:".to_string()];
+        let mut currencies: Vec<_> = self.supply_by_currency.iter().collect();
+        currencies.sort();
+        for (currency, supply) in currencies {
+            lines.push(format!("   Total Supply: {} {}", supply, currency));
+        }
+        lines.push(format!(
+            "   Base Interest Rate: {}%",
+            self.base_interest_rate * 100.0
+        ));
+        lines.push(format!(
+            "   Inflation Target: {}%",
+            self.inflation_target * 100.0
+        ));
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterestRateOutput {
+    pub rate_type: String,
+    pub rate: f64,
+}
+
+impl CommandOutput for InterestRateOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "✅ Interest rate set successfully\n📊 {}: {}%",
+            self.rate_type,
+            self.rate * 100.0
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankApprovedOutput {
+    pub bank_id: String,
+}
+
+impl CommandOutput for BankApprovedOutput {
+    fn to_text(&self) -> String {
+        format!("✅ Bank {} approved successfully", self.bank_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspensionProposedOutput {
+    pub bank_id: String,
+    pub reason: String,
+    pub proposal_id: String,
+}
+
+impl CommandOutput for SuspensionProposedOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "⚠️  Suspension of bank {} proposed. Reason: {}\n📋 Proposal ID: {} (awaiting admin approvals)",
+            self.bank_id, self.reason, self.proposal_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankingNetworkReportOutput {
+    pub network_health: String,
+    pub total_banks: usize,
+    pub active_banks: usize,
+}
+
+impl CommandOutput for BankingNetworkReportOutput {
+    fn to_text(&self) -> String {
+        [
+            "🏦 Banking Network Report:".to_string(),
+            format!("   Network Health: {}", self.network_health),
+            format!("   Total Banks: {}", self.total_banks),
+            format!("   Active Banks: {}", self.active_banks),
+        ]
+        .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReportOutput {
+    pub overall_status: String,
+}
+
+impl CommandOutput for ComplianceReportOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "📊 Compliance Report:\n   Overall Status: {}",
+            self.overall_status
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EconomicReportOutput {
+    pub system_status: String,
+}
+
+impl CommandOutput for EconomicReportOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "📈 Economic Indicators:\n   System Status: {}",
+            self.system_status
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationCancelledOutput {
+    pub operation_id: String,
+}
+
+impl CommandOutput for OperationCancelledOutput {
+    fn to_text(&self) -> String {
+        format!("🛑 Scheduled operation {} cancelled", self.operation_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationApprovedOutput {
+    pub operation_id: String,
+    pub approvals: usize,
+    /// Approvals needed to execute before the delay elapses
+    pub super_quorum: usize,
+}
+
+impl CommandOutput for OperationApprovedOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "✅ Scheduled operation {} approved ({}/{} for early execution)",
+            self.operation_id, self.approvals, self.super_quorum
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledOperationSummary {
+    pub id: String,
+    pub action: String,
+    pub scheduled_by: String,
+    pub executes_at: DateTime<Utc>,
+    pub approvals: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledOperationsOutput {
+    pub operations: Vec<ScheduledOperationSummary>,
+}
+
+impl ScheduledOperationsOutput {
+    pub fn new(operations: &[&ScheduledOperation]) -> Self {
+        Self {
+            operations: operations
+                .iter()
+                .map(|operation| ScheduledOperationSummary {
+                    id: operation.id.clone(),
+                    action: operation.action().to_string(),
+                    scheduled_by: operation.scheduled_by.clone(),
+                    executes_at: operation.executes_at,
+                    approvals: operation.approvals.len(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CommandOutput for ScheduledOperationsOutput {
+    fn to_text(&self) -> String {
+        let mut lines = vec![format!(
+            "⏳ Scheduled Operations ({}):",
+            self.operations.len()
+        )];
+        for operation in &self.operations {
+            lines.push(format!(
+                "  {} {} by {} - executes at {} ({} approvals)",
+                operation.id,
+                operation.action,
+                operation.scheduled_by,
+                operation.executes_at,
+                operation.approvals
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationTimelockedOutput {
+    pub operation_id: String,
+    pub delay_secs: i64,
+}
+
+impl CommandOutput for OperationTimelockedOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "⏳ Emergency operation timelocked\n📋 Operation ID: {} (executes in {}s unless cancelled)",
+            self.operation_id, self.delay_secs
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyInjectionOutput {
+    pub decision_id: String,
+    pub amount: u64,
+    pub currency: String,
+}
+
+impl CommandOutput for EmergencyInjectionOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "🚨 Emergency currency injection completed\n💰 Amount: {} {}\n📋 Decision ID: {}",
+            self.amount, self.currency, self.decision_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankFrozenOutput {
+    pub bank_id: String,
+}
+
+impl CommandOutput for BankFrozenOutput {
+    fn to_text(&self) -> String {
+        format!("🚨 Bank {} operations frozen", self.bank_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyHaltOutput {
+    pub operations_suspended: bool,
+}
+
+impl CommandOutput for EmergencyHaltOutput {
+    fn to_text(&self) -> String {
+        "🚨 EMERGENCY SYSTEM HALT INITIATED\n⚠️  All operations suspended pending review"
+            .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatusOutput {
+    pub money_supply: u64,
+    pub currency: String,
+    pub base_interest_rate: f64,
+    pub active_banks: usize,
+    pub status: String,
+}
+
+impl CommandOutput for SystemStatusOutput {
+    fn to_text(&self) -> String {
+        [
+            "🏛️  Astor Central Bank System Status".to_string(),
+            "================================".to_string(),
+            format!("💰 Money Supply: {} {}", self.money_supply, self.currency),
+            format!("📊 Base Rate: {}%", self.base_interest_rate * 100.0),
+            format!("🏦 Active Banks: {}", self.active_banks),
+            format!("🟢 System Status: {}", self.status),
+        ]
+        .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemInitializedOutput {
+    /// Base64 public key of the root administrator
+    pub root_public_key: String,
+}
+
+impl CommandOutput for SystemInitializedOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "✅ Astor system initialized successfully!\nRoot admin public key: {}",
+            self.root_public_key
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeDeployedOutput {
+    pub node_id: String,
+    pub listen_addr: String,
+    pub network_id: String,
+}
+
+impl CommandOutput for NodeDeployedOutput {
+    fn to_text(&self) -> String {
+        [
+            "✅ Network node deployed successfully!".to_string(),
+            format!("Node ID: {}", self.node_id),
+            format!("Node listening on: {}", self.listen_addr),
+            format!("Network ID: {}", self.network_id),
+            "Press Ctrl+C to stop the node...".to_string(),
+        ]
+        .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankRegisteredOutput {
+    pub bank_id: String,
+    pub bank_name: String,
+    pub status: String,
+}
+
+impl CommandOutput for BankRegisteredOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "✅ Bank '{}' registered successfully!\nBank ID: {}\nStatus: {}",
+            self.bank_name, self.bank_id, self.status
+        )
+    }
+}
+
+/// Result of a currency issuance into an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountIssuanceOutput {
+    pub transaction_id: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub currency: String,
+    /// Whether the recipient account was opened by this command
+    pub created_recipient: bool,
+}
+
+impl CommandOutput for AccountIssuanceOutput {
+    fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        if self.created_recipient {
+            lines.push(format!("Created recipient account: {}", self.recipient));
+        }
+        lines.push(format!(
+            "✅ Issued {} {} to account {}",
+            self.amount, self.currency, self.recipient
+        ));
+        lines.push(format!("Transaction ID: {}", self.transaction_id));
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferOutput {
+    pub transaction_id: String,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    pub fee: u64,
+    pub currency: String,
+}
+
+impl CommandOutput for TransferOutput {
+    fn to_text(&self) -> String {
+        format!(
+            "✅ Transferred {} {} from {} to {} (fee {})\nTransaction ID: {}",
+            self.amount, self.currency, self.from, self.to, self.fee, self.transaction_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiServerStartedOutput {
+    pub bind_addr: String,
+    pub docs_url: String,
+}
+
+impl CommandOutput for ApiServerStartedOutput {
+    fn to_text(&self) -> String {
+        [
+            format!("✅ API server started on {}", self.bind_addr),
+            format!("API documentation available at: {}", self.docs_url),
+            "Press Ctrl+C to stop the server...".to_string(),
+        ]
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_mode_emits_schema_correct_json() {
+        let stats = SystemStatsOutput {
+            total_supply: 1_000,
+            ledger_entries: 4,
+            active_administrators: 1,
+            total_transactions: 3,
+            registered_banks: 2,
+            active_banks: 1,
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Json, &stats).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "total_supply": 1_000,
+                "ledger_entries": 4,
+                "active_administrators": 1,
+                "total_transactions": 3,
+                "registered_banks": 2,
+                "active_banks": 1,
+            })
+        );
+
+        let balance = BalanceOutput {
+            account_id: "acct-1".to_string(),
+            balance: 250,
+            currency: "ASTOR".to_string(),
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Json, &balance).unwrap()).unwrap();
+        assert_eq!(json["balance"].as_u64(), Some(250));
+        assert_eq!(json["account_id"], "acct-1");
+
+        // Text mode keeps the decorated output
+        assert_eq!(
+            render(OutputFormat::Text, &balance).unwrap(),
+            "Account acct-1 balance: 250 ASTOR"
+        );
+    }
+
+    #[test]
+    fn test_issuance_json_reports_created_recipient() {
+        let issued = AccountIssuanceOutput {
+            transaction_id: "tx-1".to_string(),
+            recipient: "acct-1".to_string(),
+            amount: 500,
+            currency: "ASTOR".to_string(),
+            created_recipient: true,
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Json, &issued).unwrap()).unwrap();
+        assert_eq!(json["created_recipient"], true);
+        assert_eq!(json["transaction_id"], "tx-1");
+
+        let text = render(OutputFormat::Text, &issued).unwrap();
+        assert!(text.starts_with("Created recipient account: acct-1"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Timelocks on designated emergency operations
+//!
+//! A timelocked operation is scheduled instead of run. It executes once its
+//! delay has elapsed unless an administrator cancels it first, and every
+//! administrator can list what is pending. Approval by a super-quorum of
+//! distinct administrators lifts the delay.
+//!
+//! Each CLI invocation is a separate process, so a timelock opened on a store
+//! file writes every change through to it. Due operations run the next time
+//! the CLI starts.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::authorization::emergency_action_name;
+use super::EmergencyCommands;
+use crate::errors::AstorError;
+
+/// Delay imposed on one kind of emergency operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelockRule {
+    /// Audit name of the operation, e.g. `emergency_inject`
+    pub action: String,
+    pub delay_secs: i64,
+    /// Only injections of at least this amount are delayed
+    #[serde(default)]
+    pub min_amount: Option<u64>,
+}
+
+/// Which emergency operations are timelocked, and how they can be expedited
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelockPolicy {
+    pub rules: Vec<TimelockRule>,
+    /// Distinct administrators whose approval lets an operation run early
+    pub super_quorum: usize,
+}
+
+impl TimelockPolicy {
+    /// Delay for `command`, or `None` if it runs immediately
+    pub fn delay_for(&self, command: &EmergencyCommands) -> Option<Duration> {
+        let action = emergency_action_name(command);
+        let amount = match command {
+            EmergencyCommands::Inject { amount, .. } => Some(*amount),
+            _ => None,
+        };
+
+        self.rules
+            .iter()
+            .filter(|rule| rule.action == action)
+            .filter(|rule| match (rule.min_amount, amount) {
+                (Some(min_amount), Some(amount)) => amount >= min_amount,
+                _ => true,
+            })
+            .map(|rule| Duration::seconds(rule.delay_secs))
+            .max()
+    }
+}
+
+impl Default for TimelockPolicy {
+    /// Large injections wait a day; halts and freezes stay immediate
+    fn default() -> Self {
+        Self {
+            rules: vec![TimelockRule {
+                action: "emergency_inject".to_string(),
+                delay_secs: 24 * 3600,
+                min_amount: Some(1_000_000),
+            }],
+            super_quorum: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScheduledOperationStatus {
+    Pending,
+    Executed,
+    Cancelled { by: String },
+}
+
+/// An emergency operation waiting out its timelock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledOperation {
+    pub id: String,
+    pub command: EmergencyCommands,
+    pub scheduled_by: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub executes_at: DateTime<Utc>,
+    /// Administrators backing early execution, including the scheduler
+    pub approvals: BTreeSet<String>,
+    pub status: ScheduledOperationStatus,
+}
+
+impl ScheduledOperation {
+    pub fn action(&self) -> &'static str {
+        emergency_action_name(&self.command)
+    }
+
+    fn is_ready(&self, now: DateTime<Utc>, super_quorum: usize) -> bool {
+        self.status == ScheduledOperationStatus::Pending
+            && (now >= self.executes_at || self.approvals.len() >= super_quorum)
+    }
+}
+
+/// Emergency operations scheduled under a timelock
+pub struct Timelock {
+    policy: TimelockPolicy,
+    operations: Vec<ScheduledOperation>,
+    /// JSON file the operations are persisted to, if any
+    store_path: Option<PathBuf>,
+}
+
+impl Timelock {
+    /// Timelock kept in memory only
+    pub fn new(policy: TimelockPolicy) -> Self {
+        Self {
+            policy,
+            operations: Vec::new(),
+            store_path: None,
+        }
+    }
+
+    /// Timelock persisted to `path`, loading the operations already there
+    pub fn open(policy: TimelockPolicy, path: impl AsRef<Path>) -> Result<Self, AstorError> {
+        let path = path.as_ref().to_path_buf();
+        let operations = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(AstorError::InvalidOperation(format!(
+                    "Failed to read timelock store {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        Ok(Self {
+            policy,
+            operations,
+            store_path: Some(path),
+        })
+    }
+
+    /// Write the operations to the store, replacing the file atomically
+    fn save(&self) -> Result<(), AstorError> {
+        let Some(path) = &self.store_path else {
+            return Ok(());
+        };
+        let staging = path.with_extension("tmp");
+        std::fs::write(&staging, serde_json::to_vec_pretty(&self.operations)?)
+            .and_then(|()| std::fs::rename(&staging, path))
+            .map_err(|e| {
+                AstorError::InvalidOperation(format!(
+                    "Failed to write timelock store {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    pub fn set_policy(&mut self, policy: TimelockPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn policy(&self) -> &TimelockPolicy {
+        &self.policy
+    }
+
+    /// Schedule `command` to run once its delay has elapsed
+    pub fn schedule(
+        &mut self,
+        command: EmergencyCommands,
+        admin_id: &str,
+        delay: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<String, AstorError> {
+        let id = Uuid::new_v4().to_string();
+        self.operations.push(ScheduledOperation {
+            id: id.clone(),
+            command,
+            scheduled_by: admin_id.to_string(),
+            scheduled_at: now,
+            executes_at: now + delay,
+            approvals: BTreeSet::from([admin_id.to_string()]),
+            status: ScheduledOperationStatus::Pending,
+        });
+        if let Err(e) = self.save() {
+            self.operations.pop();
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    fn pending_mut(&mut self, operation_id: &str) -> Result<&mut ScheduledOperation, AstorError> {
+        let operation = self
+            .operations
+            .iter_mut()
+            .find(|operation| operation.id == operation_id)
+            .ok_or_else(|| {
+                AstorError::InvalidOperation(format!("No scheduled operation {}", operation_id))
+            })?;
+
+        if operation.status != ScheduledOperationStatus::Pending {
+            return Err(AstorError::InvalidOperation(format!(
+                "Scheduled operation {} is no longer pending ({:?})",
+                operation_id, operation.status
+            )));
+        }
+        Ok(operation)
+    }
+
+    /// Back early execution of a pending operation; returns the approval count
+    pub fn approve(&mut self, operation_id: &str, admin_id: &str) -> Result<usize, AstorError> {
+        let operation = self.pending_mut(operation_id)?;
+        operation.approvals.insert(admin_id.to_string());
+        let approvals = operation.approvals.len();
+        self.save()?;
+        Ok(approvals)
+    }
+
+    /// Cancel a pending operation before it executes
+    pub fn cancel(&mut self, operation_id: &str, admin_id: &str) -> Result<(), AstorError> {
+        let operation = self.pending_mut(operation_id)?;
+        operation.status = ScheduledOperationStatus::Cancelled {
+            by: admin_id.to_string(),
+        };
+        self.save()
+    }
+
+    /// Operations still waiting to execute, oldest first
+    pub fn pending(&self) -> Vec<&ScheduledOperation> {
+        self.operations
+            .iter()
+            .filter(|operation| operation.status == ScheduledOperationStatus::Pending)
+            .collect()
+    }
+
+    pub fn get(&self, operation_id: &str) -> Option<&ScheduledOperation> {
+        self.operations
+            .iter()
+            .find(|operation| operation.id == operation_id)
+    }
+
+    /// Mark operations whose delay has elapsed, or that reached the
+    /// super-quorum, as executed and return them for the caller to run
+    ///
+    /// They are marked in the store before being returned, so a crash while
+    /// running them cannot run them twice.
+    pub fn take_ready(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ScheduledOperation>, AstorError> {
+        let super_quorum = self.policy.super_quorum;
+        let ready: Vec<ScheduledOperation> = self
+            .operations
+            .iter_mut()
+            .filter(|operation| operation.is_ready(now, super_quorum))
+            .map(|operation| {
+                operation.status = ScheduledOperationStatus::Executed;
+                operation.clone()
+            })
+            .collect();
+        if ready.is_empty() {
+            return Ok(ready);
+        }
+        if let Err(e) = self.save() {
+            for operation in &mut self.operations {
+                if ready.iter().any(|taken| taken.id == operation.id) {
+                    operation.status = ScheduledOperationStatus::Pending;
+                }
+            }
+            return Err(e);
+        }
+        Ok(ready)
+    }
+}
+
+impl Default for Timelock {
+    fn default() -> Self {
+        Self::new(TimelockPolicy::default())
+    }
+}
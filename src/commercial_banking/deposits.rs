@@ -15,6 +15,39 @@ pub struct DepositAccount {
     pub interest_rate: f64,
     pub opened_date: DateTime<Utc>,
     pub last_interest_payment: DateTime<Utc>,
+    /// Fraction of a minor unit of interest accrued but not yet paid
+    #[serde(default)]
+    pub interest_residual: f64,
+}
+
+/// How accrued interest is turned into whole minor units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum InterestRounding {
+    /// Round each payment to the nearest unit; the fraction is lost
+    Nearest,
+    /// Pay whole units and carry the fraction into the next accrual, so
+    /// long-run interest matches the exact computation
+    #[default]
+    CarryResidual,
+}
+
+impl DepositAccount {
+    /// Pay one month of interest into the balance, returning the amount paid
+    fn accrue_monthly_interest(&mut self, rounding: InterestRounding) -> u64 {
+        let exact = self.balance as f64 * self.interest_rate / 12.0;
+        let interest = match rounding {
+            InterestRounding::Nearest => exact.round() as u64,
+            InterestRounding::CarryResidual => {
+                let accrued = exact + self.interest_residual;
+                let paid = accrued.floor().max(0.0);
+                self.interest_residual = accrued - paid;
+                paid as u64
+            }
+        };
+
+        self.balance += interest;
+        interest
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,15 +61,23 @@ pub enum DepositAccountType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DepositManager {
     deposits: HashMap<String, DepositAccount>,
+    #[serde(default)]
+    interest_rounding: InterestRounding,
 }
 
 impl DepositManager {
     pub fn new() -> Self {
         Self {
             deposits: HashMap::new(),
+            interest_rounding: InterestRounding::default(),
         }
     }
 
+    /// Choose how interest payments are rounded to whole units
+    pub fn set_interest_rounding(&mut self, rounding: InterestRounding) {
+        self.interest_rounding = rounding;
+    }
+
     /// Open a new deposit account
     pub fn open_account(
         &mut self,
@@ -55,6 +96,7 @@ impl DepositManager {
             interest_rate,
             opened_date: Utc::now(),
             last_interest_payment: Utc::now(),
+            interest_residual: 0.0,
         };
 
         self.deposits.insert(account_id.clone(), account);
@@ -94,12 +136,12 @@ impl DepositManager {
     /// Pay interest on all eligible accounts
     pub fn pay_interest(&mut self) -> Result<u64, AstorError> {
         let mut total_interest_paid = 0u64;
+        let rounding = self.interest_rounding;
         
         for account in self.deposits.values_mut() {
             let days_since_last_payment = (Utc::now() - account.last_interest_payment).num_days();
             if days_since_last_payment >= 30 { // Monthly interest
-                let interest = (account.balance as f64 * account.interest_rate / 12.0).round() as u64;
-                account.balance += interest;
+                let interest = account.accrue_monthly_interest(rounding);
                 account.last_interest_payment = Utc::now();
                 total_interest_paid += interest;
             }
@@ -135,3 +177,36 @@ impl Default for DepositManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pay `months` of interest, returning (paid, exact) cumulative totals
+    fn accrue(rounding: InterestRounding, months: usize) -> (u64, f64) {
+        let mut manager = DepositManager::new();
+        manager.set_interest_rounding(rounding);
+        let account_id = manager
+            .open_account("customer-1".to_string(), DepositAccountType::Savings, 1_003, 0.05)
+            .unwrap();
+
+        let (mut paid, mut exact) = (0u64, 0.0f64);
+        for _ in 0..months {
+            let account = manager.deposits.get_mut(&account_id).unwrap();
+            account.last_interest_payment = Utc::now() - Duration::days(31);
+            exact += account.balance as f64 * account.interest_rate / 12.0;
+            paid += manager.pay_interest().unwrap();
+        }
+        (paid, exact)
+    }
+
+    #[test]
+    fn test_carried_residual_matches_exact_interest() {
+        let (paid, exact) = accrue(InterestRounding::CarryResidual, 240);
+        assert!((exact - paid as f64).abs() < 1.0, "paid {} vs exact {}", paid, exact);
+
+        // Rounding each payment leaks the fraction every month
+        let (paid, exact) = accrue(InterestRounding::Nearest, 240);
+        assert!((exact - paid as f64).abs() > 1.0);
+    }
+}
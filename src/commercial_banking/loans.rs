@@ -1,7 +1,7 @@
 //! Loan management for commercial banking
 
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc, Duration};
+use chrono::{DateTime, Months, Utc};
 use std::collections::HashMap;
 
 use crate::errors::AstorError;
@@ -14,6 +14,8 @@ pub struct Loan {
     pub principal_amount: u64,
     pub outstanding_balance: u64,
     pub interest_rate: f64,
+    #[serde(default)]
+    pub compounding: CompoundingFrequency,
     pub term_months: u32,
     pub monthly_payment: u64,
     pub origination_date: DateTime<Utc>,
@@ -31,6 +33,24 @@ pub enum LoanType {
     Student,
 }
 
+/// How often interest is compounded on the outstanding balance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompoundingFrequency {
+    #[default]
+    Monthly,
+    Daily,
+}
+
+impl CompoundingFrequency {
+    /// Interest rate accrued over one monthly payment period
+    pub fn monthly_rate(&self, annual_rate: f64) -> f64 {
+        match self {
+            CompoundingFrequency::Monthly => annual_rate / 12.0,
+            CompoundingFrequency::Daily => (1.0 + annual_rate / 365.0).powf(365.0 / 12.0) - 1.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LoanStatus {
     Active,
@@ -68,12 +88,14 @@ impl LoanManager {
         amount: u64,
         term_months: u32,
         interest_rate: f64,
+        compounding: CompoundingFrequency,
     ) -> Result<String, AstorError> {
         // Credit check would happen here in production
         let loan_id = uuid::Uuid::new_v4().to_string();
         
-        let monthly_payment = self.calculate_monthly_payment(amount, interest_rate, term_months);
-        let maturity_date = Utc::now() + Duration::days((term_months * 30) as i64);
+        let monthly_payment = self.calculate_monthly_payment(amount, interest_rate, term_months, compounding);
+        let origination_date = Utc::now();
+        let maturity_date = Self::maturity_date(origination_date, term_months)?;
 
         let loan = Loan {
             loan_id: loan_id.clone(),
@@ -82,9 +104,10 @@ impl LoanManager {
             principal_amount: amount,
             outstanding_balance: amount,
             interest_rate,
+            compounding,
             term_months,
             monthly_payment,
-            origination_date: Utc::now(),
+            origination_date,
             maturity_date,
             status: LoanStatus::Active,
             payment_history: Vec::new(),
@@ -104,7 +127,8 @@ impl LoanManager {
         }
 
         // Calculate interest and principal portions
-        let monthly_interest = (loan.outstanding_balance as f64 * loan.interest_rate / 12.0).round() as u64;
+        let monthly_rate = loan.compounding.monthly_rate(loan.interest_rate);
+        let monthly_interest = (loan.outstanding_balance as f64 * monthly_rate).round() as u64;
         let principal_portion = if amount > monthly_interest {
             amount - monthly_interest
         } else {
@@ -133,17 +157,33 @@ impl LoanManager {
     }
 
     /// Calculate monthly loan payment using amortization formula
-    fn calculate_monthly_payment(&self, principal: u64, annual_rate: f64, term_months: u32) -> u64 {
+    fn calculate_monthly_payment(
+        &self,
+        principal: u64,
+        annual_rate: f64,
+        term_months: u32,
+        compounding: CompoundingFrequency,
+    ) -> u64 {
         if annual_rate == 0.0 {
             return principal / term_months as u64;
         }
 
-        let monthly_rate = annual_rate / 12.0;
+        let monthly_rate = compounding.monthly_rate(annual_rate);
         let payment = (principal as f64 * monthly_rate * (1.0 + monthly_rate).powi(term_months as i32)) 
             / ((1.0 + monthly_rate).powi(term_months as i32) - 1.0);
         payment.round() as u64
     }
 
+    /// Final payment date, counted in calendar months from origination
+    ///
+    /// Days past the end of a shorter month are clamped, so a loan
+    /// originated on Jan 31 has its first anniversary month end on Feb 28/29.
+    fn maturity_date(origination_date: DateTime<Utc>, term_months: u32) -> Result<DateTime<Utc>, AstorError> {
+        origination_date
+            .checked_add_months(Months::new(term_months))
+            .ok_or_else(|| AstorError::LoanError(format!("Loan term of {} months is out of range", term_months)))
+    }
+
     /// Get loan details
     pub fn get_loan(&self, loan_id: &str) -> Result<&Loan, AstorError> {
         self.loans.get(loan_id)
@@ -180,3 +220,37 @@ impl Default for LoanManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_thirty_year_mortgage_matches_amortization_table() {
+        let manager = LoanManager::new();
+
+        // $200,000 at 6% over 360 months amortizes to $1,199.10 per month
+        let payment = manager.calculate_monthly_payment(20_000_000, 0.06, 360, CompoundingFrequency::Monthly);
+        assert_eq!(payment, 119_910);
+
+        // Daily compounding accrues slightly more interest each period
+        let daily = manager.calculate_monthly_payment(20_000_000, 0.06, 360, CompoundingFrequency::Daily);
+        assert!(daily > payment);
+        assert_eq!(daily, 120_097);
+    }
+
+    #[test]
+    fn test_maturity_date_uses_calendar_months() {
+        let origination = Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            LoanManager::maturity_date(origination, 1).unwrap(),
+            Utc.with_ymd_and_hms(2024, 2, 29, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            LoanManager::maturity_date(origination, 360).unwrap(),
+            Utc.with_ymd_and_hms(2054, 1, 31, 12, 0, 0).unwrap()
+        );
+    }
+}
@@ -6,10 +6,16 @@ pub mod secrets;
 pub mod feature_flags;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::path::Path;
 
+use crate::banking_network::BankHealthConfig;
 use crate::errors::AstorError;
+use crate::fee_calculator::FeeRounding;
+use crate::fee_market::FeeMarketConfig;
+use crate::receipts::ReceiptConfig;
+use crate::security::{AutoFreezePolicy, FraudModelConfig, Role, TransactionLimits};
 
 /// Main application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +30,16 @@ pub struct Config {
     pub feature_flags: FeatureFlagsConfig,
     pub external_services: ExternalServicesConfig,
     pub compliance: ComplianceConfig,
+    #[serde(default)]
+    pub monetary_policy: MonetaryPolicyConfig,
+    #[serde(default)]
+    pub fees: FeeConfig,
+    #[serde(default)]
+    pub crl: CrlConfig,
+    #[serde(default)]
+    pub ocsp: OcspConfig,
+    #[serde(default)]
+    pub settlement: SettlementConfig,
 }
 
 /// Environment types
@@ -69,6 +85,42 @@ pub struct DatabaseConfig {
     pub slow_query_threshold: u64,
     pub connection_retry_attempts: u32,
     pub connection_retry_delay: u64,
+    /// Buffered transaction writes are flushed once this many are pending
+    #[serde(default = "default_write_batch_size")]
+    pub write_batch_size: usize,
+    /// Milliseconds between flushes of buffered transaction writes
+    #[serde(default = "default_write_flush_interval")]
+    pub write_flush_interval: u64,
+    /// Read replica for read-only queries; reads use the primary when unset
+    #[serde(default)]
+    pub replica_url: Option<String>,
+    /// Seconds between health checks that take a failing replica out of
+    /// read routing and bring a recovered one back
+    #[serde(default = "default_replica_health_check_interval")]
+    pub replica_health_check_interval: u64,
+    /// File older ledger entries are moved to; the whole ledger stays in
+    /// memory when unset
+    #[serde(default)]
+    pub ledger_spill_path: Option<String>,
+    /// Most recent ledger entries kept in memory when spilling
+    #[serde(default = "default_ledger_max_in_memory_entries")]
+    pub ledger_max_in_memory_entries: usize,
+}
+
+fn default_write_batch_size() -> usize {
+    500
+}
+
+fn default_ledger_max_in_memory_entries() -> usize {
+    100_000
+}
+
+fn default_write_flush_interval() -> u64 {
+    100
+}
+
+fn default_replica_health_check_interval() -> u64 {
+    10
 }
 
 /// Enhanced server configuration
@@ -111,6 +163,58 @@ pub struct SecurityConfig {
     pub api_key_length: usize,
     pub password_policy: PasswordPolicyConfig,
     pub rate_limiting: RateLimitingConfig,
+    #[serde(default)]
+    pub fraud_auto_freeze: AutoFreezePolicy,
+    /// Anomaly model scoring transfers against each sender's history
+    #[serde(default)]
+    pub fraud_model: FraudModelConfig,
+    /// Per-transaction caps by account type and risk rating
+    #[serde(default)]
+    pub transaction_limits: TransactionLimits,
+    /// Days an encryption key stays active before it is rotated
+    #[serde(default = "default_key_rotation_days")]
+    pub key_rotation_days: u32,
+    /// Seconds between checks for keys due for rotation
+    #[serde(default = "default_key_rotation_check_interval")]
+    pub key_rotation_check_interval: u64,
+    /// Administrators whose keys are trusted to sign privileged commands;
+    /// an entry for `root` replaces the key the system was started with
+    #[serde(default)]
+    pub administrators: Vec<AdministratorConfig>,
+}
+
+/// Administrator registered from configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdministratorConfig {
+    pub id: String,
+    /// Base64 Ed25519 public key
+    pub public_key: String,
+    pub role: Role,
+}
+
+impl AdministratorConfig {
+    /// Decode the configured public key
+    pub fn decode_public_key(&self) -> Result<ed25519_dalek::PublicKey, AstorError> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let bytes = general_purpose::STANDARD
+            .decode(self.public_key.trim())
+            .map_err(|_| {
+                AstorError::ConfigurationError(format!(
+                    "Public key of administrator {} must be base64 encoded",
+                    self.id
+                ))
+            })?;
+        crate::accounts::parse_public_key(&bytes)
+    }
+}
+
+fn default_key_rotation_days() -> u32 {
+    90
+}
+
+fn default_key_rotation_check_interval() -> u64 {
+    3600
 }
 
 /// Password policy configuration
@@ -133,6 +237,12 @@ pub struct RateLimitingConfig {
     pub window_size: u64,
     pub cleanup_interval: u64,
     pub whitelist_ips: Vec<String>,
+    /// API key IDs and JWT subjects of internal services that bypass the limiter
+    #[serde(default)]
+    pub exempt_principals: Vec<String>,
+    /// API key roles that bypass the limiter
+    #[serde(default)]
+    pub exempt_roles: Vec<Role>,
 }
 
 /// Redis configuration
@@ -218,6 +328,23 @@ pub struct AlertsConfig {
     pub email_recipients: Vec<String>,
     pub slack_webhook: Option<String>,
     pub thresholds: AlertThresholds,
+    /// Repeat firings of an active alert are not re-sent within this window
+    #[serde(default = "default_alert_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// An alert still firing this long after it first fired is escalated
+    #[serde(default = "default_alert_escalate_after_secs")]
+    pub escalate_after_secs: u64,
+    /// Added to `email_recipients` once an alert escalates
+    #[serde(default)]
+    pub escalation_recipients: Vec<String>,
+}
+
+fn default_alert_cooldown_secs() -> u64 {
+    300
+}
+
+fn default_alert_escalate_after_secs() -> u64 {
+    1800
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -227,6 +354,27 @@ pub struct AlertThresholds {
     pub memory_usage: f64,
     pub cpu_usage: f64,
     pub disk_usage: f64,
+    /// Average network latency above which analytics raise a critical insight
+    #[serde(default = "default_network_latency_ms")]
+    pub network_latency_ms: u64,
+    /// Period-over-period transaction volume growth reported as a surge
+    #[serde(default = "default_volume_increase")]
+    pub volume_increase: f64,
+    /// Period-over-period transaction volume decline reported as a drop
+    #[serde(default = "default_volume_decrease")]
+    pub volume_decrease: f64,
+}
+
+fn default_network_latency_ms() -> u64 {
+    1000
+}
+
+fn default_volume_increase() -> f64 {
+    0.2
+}
+
+fn default_volume_decrease() -> f64 {
+    0.1
 }
 
 /// Feature flags configuration
@@ -262,6 +410,16 @@ pub struct BankingApiConfig {
     pub timeout: u64,
     pub retry_attempts: u32,
     pub sandbox_mode: bool,
+    #[serde(default)]
+    pub health_polling: BankHealthConfig,
+    /// Consecutive settlement failures that suspend a bank; 0 disables
+    /// automatic suspension
+    #[serde(default = "default_settlement_failure_threshold")]
+    pub settlement_failure_threshold: u32,
+}
+
+fn default_settlement_failure_threshold() -> u32 {
+    crate::banking_network::settlement::DEFAULT_SETTLEMENT_FAILURE_THRESHOLD
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -269,6 +427,8 @@ pub struct NotificationConfig {
     pub email: EmailConfig,
     pub sms: Option<SmsConfig>,
     pub push: Option<PushConfig>,
+    #[serde(default)]
+    pub receipts: ReceiptConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -283,6 +443,8 @@ pub struct EmailConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmsConfig {
+    /// URL of the SMS gateway, which receives a JSON `from`/`to`/`body`
+    /// POST authenticated with `api_key`
     pub provider: String,
     pub api_key: String,
     pub from_number: String,
@@ -330,6 +492,108 @@ pub struct ComplianceConfig {
     pub encryption_at_rest: bool,
     pub encryption_in_transit: bool,
     pub audit_trail_integrity: bool,
+    /// Days without activity before an account is marked dormant; accounts
+    /// never go dormant when unset
+    #[serde(default)]
+    pub account_dormancy_days: Option<u32>,
+    /// File settled transactions are archived to once older than
+    /// `data_retention_days`; they stay in memory when unset
+    #[serde(default)]
+    pub transaction_archive_path: Option<String>,
+}
+
+/// Central bank operations run on a schedule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonetaryPolicyConfig {
+    /// Hours between payments of interest on reserves; interest is only
+    /// paid on request when unset
+    pub reserve_interest_interval_hours: Option<u32>,
+}
+
+/// Fee arithmetic shared by payments, conversions, bridges and settlements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeConfig {
+    /// How fractional fees are rounded to whole units
+    pub rounding: FeeRounding,
+    /// How the transfer base fee follows block fullness
+    pub market: FeeMarketConfig,
+    /// Account the ledger credits with every fee charged
+    pub fee_account: String,
+}
+
+/// Fee-collection account used unless configured otherwise
+pub const DEFAULT_FEE_ACCOUNT: &str = "fee-collection";
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            rounding: FeeRounding::default(),
+            market: FeeMarketConfig::default(),
+            fee_account: DEFAULT_FEE_ACCOUNT.to_string(),
+        }
+    }
+}
+
+/// Scheduled publication of the certificate revocation list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrlConfig {
+    /// Hours between CRL issues
+    pub update_interval_hours: u32,
+    /// File each signed CRL is written to for relying parties
+    pub publish_path: Option<String>,
+    /// Distribution endpoint each signed CRL is POSTed to
+    pub webhook_url: Option<String>,
+    /// File the last CRL number is kept in so numbering survives restarts
+    pub state_path: Option<String>,
+}
+
+/// Currency zones and corridors for inter-bank settlement
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettlementConfig {
+    /// Settlement currency by bank ID; unlisted banks settle in the default
+    /// currency
+    pub bank_currencies: HashMap<String, String>,
+    /// Corridor fees in basis points, keyed by "FROM/TO" currency pair
+    pub corridor_fees_bps: HashMap<String, u32>,
+}
+
+impl Default for CrlConfig {
+    fn default() -> Self {
+        Self {
+            update_interval_hours: 24,
+            publish_path: None,
+            webhook_url: None,
+            state_path: None,
+        }
+    }
+}
+
+/// Delegated key the OCSP responder signs with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcspConfig {
+    /// File holding the base64-encoded Ed25519 secret key; a key is
+    /// generated at startup when absent
+    pub signing_key_path: Option<String>,
+    /// Validity of each delegated OCSP signing certificate
+    pub validity_days: u32,
+    /// Days before expiry the certificate is reissued, checked on each CRL
+    /// issue
+    pub renew_before_days: u32,
+}
+
+impl Default for OcspConfig {
+    fn default() -> Self {
+        Self {
+            signing_key_path: None,
+            validity_days: 30,
+            renew_before_days: 7,
+        }
+    }
 }
 
 impl Config {
@@ -467,6 +731,9 @@ impl Config {
         config.security.jwt_secret = "[REDACTED]".to_string();
         config.security.encryption_key = "[REDACTED]".to_string();
         config.database.url = Self::redact_connection_string(&config.database.url);
+        if let Some(ref mut replica_url) = config.database.replica_url {
+            *replica_url = Self::redact_connection_string(replica_url);
+        }
         config.redis.url = Self::redact_connection_string(&config.redis.url);
 
         if let Some(ref mut banking) = config.external_services.banking_api {
@@ -537,6 +804,11 @@ impl Default for Config {
             feature_flags: FeatureFlagsConfig::default(),
             external_services: ExternalServicesConfig::default(),
             compliance: ComplianceConfig::default(),
+            monetary_policy: MonetaryPolicyConfig::default(),
+            fees: FeeConfig::default(),
+            crl: CrlConfig::default(),
+            ocsp: OcspConfig::default(),
+            settlement: SettlementConfig::default(),
         }
     }
 }
@@ -556,6 +828,12 @@ impl Default for DatabaseConfig {
             slow_query_threshold: 1000,
             connection_retry_attempts: 3,
             connection_retry_delay: 1000,
+            write_batch_size: default_write_batch_size(),
+            write_flush_interval: default_write_flush_interval(),
+            replica_url: None,
+            ledger_spill_path: None,
+            ledger_max_in_memory_entries: default_ledger_max_in_memory_entries(),
+            replica_health_check_interval: default_replica_health_check_interval(),
         }
     }
 }
@@ -593,6 +871,12 @@ impl Default for SecurityConfig {
             api_key_length: 32,
             password_policy: PasswordPolicyConfig::default(),
             rate_limiting: RateLimitingConfig::default(),
+            fraud_auto_freeze: AutoFreezePolicy::default(),
+            fraud_model: FraudModelConfig::default(),
+            transaction_limits: TransactionLimits::default(),
+            key_rotation_days: default_key_rotation_days(),
+            key_rotation_check_interval: default_key_rotation_check_interval(),
+            administrators: Vec::new(),
         }
     }
 }
@@ -619,6 +903,8 @@ impl Default for RateLimitingConfig {
             window_size: 60,
             cleanup_interval: 300,
             whitelist_ips: vec!["127.0.0.1".to_string()],
+            exempt_principals: Vec::new(),
+            exempt_roles: Vec::new(),
         }
     }
 }
@@ -708,6 +994,9 @@ impl Default for AlertsConfig {
             email_recipients: vec![],
             slack_webhook: None,
             thresholds: AlertThresholds::default(),
+            cooldown_secs: default_alert_cooldown_secs(),
+            escalate_after_secs: default_alert_escalate_after_secs(),
+            escalation_recipients: vec![],
         }
     }
 }
@@ -720,6 +1009,9 @@ impl Default for AlertThresholds {
             memory_usage: 0.8,       // 80%
             cpu_usage: 0.8,          // 80%
             disk_usage: 0.9,         // 90%
+            network_latency_ms: default_network_latency_ms(),
+            volume_increase: default_volume_increase(),
+            volume_decrease: default_volume_decrease(),
         }
     }
 }
@@ -757,6 +1049,8 @@ impl Default for ComplianceConfig {
             encryption_at_rest: true,
             encryption_in_transit: true,
             audit_trail_integrity: true,
+            account_dormancy_days: None,
+            transaction_archive_path: None,
         }
     }
 }
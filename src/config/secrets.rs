@@ -5,8 +5,11 @@ use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 use crate::errors::AstorError;
+use crate::security::encryption::{DataAccessRequest, EncryptedData, EncryptionManager};
 
 /// Secret management configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,12 +54,19 @@ pub struct AzureKeyVaultConfig {
     pub tenant_id: String,
 }
 
+/// A cached secret, encrypted when the manager has encryption keys
+enum CachedSecret {
+    Plain(String),
+    Encrypted(EncryptedData),
+}
+
 /// Secret manager for handling sensitive configuration
 pub struct SecretManager {
     provider: SecretsProvider,
-    cache: HashMap<String, String>,
+    cache: HashMap<String, CachedSecret>,
     cache_ttl: std::time::Duration,
     last_refresh: std::time::Instant,
+    encryption: Option<Arc<RwLock<EncryptionManager>>>,
 }
 
 impl SecretManager {
@@ -66,15 +76,34 @@ impl SecretManager {
             cache: HashMap::new(),
             cache_ttl: std::time::Duration::from_secs(300), // 5 minutes
             last_refresh: std::time::Instant::now(),
+            encryption: None,
         }
     }
 
+    /// Keep cached secrets encrypted; each read from the cache is audited
+    pub fn set_encryption(&mut self, encryption: Arc<RwLock<EncryptionManager>>) {
+        self.cache.clear();
+        self.encryption = Some(encryption);
+    }
+
     /// Get secret value by key
     pub async fn get_secret(&mut self, key: &str) -> Result<String, AstorError> {
         // Check cache first
-        if let Some(value) = self.cache.get(key) {
-            if self.last_refresh.elapsed() < self.cache_ttl {
-                return Ok(value.clone());
+        if self.last_refresh.elapsed() < self.cache_ttl {
+            match (self.cache.get(key), &self.encryption) {
+                (Some(CachedSecret::Plain(value)), _) => return Ok(value.clone()),
+                (Some(CachedSecret::Encrypted(encrypted)), Some(encryption)) => {
+                    let access = DataAccessRequest {
+                        accessor: "secret-manager".to_string(),
+                        purpose: format!("read secret {}", key),
+                    };
+                    return encryption
+                        .read()
+                        .await
+                        .decrypt_secret(encrypted, &access)
+                        .await;
+                }
+                _ => {}
             }
         }
 
@@ -89,7 +118,13 @@ impl SecretManager {
         };
 
         // Update cache
-        self.cache.insert(key.to_string(), value.clone());
+        let cached = match &self.encryption {
+            Some(encryption) => {
+                CachedSecret::Encrypted(encryption.read().await.encrypt_secret(&value)?)
+            }
+            None => CachedSecret::Plain(value.clone()),
+        };
+        self.cache.insert(key.to_string(), cached);
         self.last_refresh = std::time::Instant::now();
 
         Ok(value)
@@ -1,12 +1,19 @@
 //! Currency conversion hooks and external API integration placeholders
 
+use chrono::NaiveDate;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use tokio::time::{Duration, Instant};
 
 use crate::database::models::ConversionRecord;
+use crate::database::repositories::ConversionRecordStore;
 use crate::errors::AstorError;
+use crate::fee_calculator::FeeCalculator;
+use crate::ledger::{FeeCollector, FeeSource};
+use crate::regulatory::RegulatoryCompliance;
 
 /// Exchange rate information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,15 +29,136 @@ pub struct ExchangeRate {
     pub daily_change: f64,
 }
 
+/// Default currency all fetched rates are normalized against
+pub const DEFAULT_BASE_CURRENCY: &str = "USD";
+
+/// A direct quote that disagrees with the cross rate derived from the base
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateDivergence {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub direct_rate: f64,
+    pub derived_rate: f64,
+    /// Relative difference between the direct and derived rates
+    pub divergence: f64,
+    pub source: String,
+}
+
+/// Risk limits on customer conversions, valued in the base currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionLimits {
+    /// Largest single conversion a customer may make
+    pub per_transaction_limit: u64,
+    /// Total a customer may convert per UTC day
+    pub daily_limit: u64,
+    /// Conversions at or above this value are screened for AML
+    pub aml_review_threshold: u64,
+}
+
+impl Default for ConversionLimits {
+    fn default() -> Self {
+        Self {
+            per_transaction_limit: 1_000_000,
+            daily_limit: 5_000_000,
+            aml_review_threshold: 10_000,
+        }
+    }
+}
+
+/// A source of live exchange rates
+#[async_trait::async_trait]
+pub trait RateProvider: Send + Sync {
+    /// Name used in the provider order and in pins
+    fn name(&self) -> &str;
+
+    /// Fetch current quotes for the given currencies
+    async fn fetch_rates(&self, supported: &[String]) -> Result<Vec<ExchangeRate>, AstorError>;
+}
+
+/// Which providers live rates are taken from
+///
+/// Providers are tried in `provider_order` until one responds. A pair pinned
+/// to a provider takes its rate only from that provider, in either direction;
+/// pins on a pair win over pins on a single currency, and when both
+/// currencies are pinned the quote's source currency decides.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateSourcePolicy {
+    pub provider_order: Vec<String>,
+    /// Provider by pair, written `FROM/TO`
+    #[serde(default)]
+    pub pinned_pairs: HashMap<String, String>,
+    /// Provider for every pair involving a currency
+    #[serde(default)]
+    pub pinned_currencies: HashMap<String, String>,
+}
+
+impl RateSourcePolicy {
+    pub fn pin_pair(&mut self, from: &str, to: &str, provider: &str) {
+        self.pinned_pairs
+            .insert(format!("{}/{}", from, to), provider.to_string());
+    }
+
+    pub fn pin_currency(&mut self, currency: &str, provider: &str) {
+        self.pinned_currencies
+            .insert(currency.to_string(), provider.to_string());
+    }
+
+    /// The provider a pair is pinned to, if any
+    pub fn pinned_provider(&self, from: &str, to: &str) -> Option<&str> {
+        self.pinned_pairs
+            .get(&format!("{}/{}", from, to))
+            .or_else(|| self.pinned_pairs.get(&format!("{}/{}", to, from)))
+            .or_else(|| self.pinned_currencies.get(from))
+            .or_else(|| self.pinned_currencies.get(to))
+            .map(String::as_str)
+    }
+
+    /// Every provider some pair is pinned to, in name order
+    pub fn pinned_providers(&self) -> BTreeSet<String> {
+        self.pinned_pairs
+            .values()
+            .chain(self.pinned_currencies.values())
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for RateSourcePolicy {
+    fn default() -> Self {
+        Self {
+            provider_order: vec![
+                "exchangerate-api".to_string(),
+                "fixer".to_string(),
+                "currencylayer".to_string(),
+            ],
+            pinned_pairs: HashMap::new(),
+            pinned_currencies: HashMap::new(),
+        }
+    }
+}
+
 /// Currency conversion service
 pub struct ConversionService {
     exchange_rates: HashMap<String, ExchangeRate>,
+    base_currency: String,
+    base_rates: HashMap<String, f64>, // Units of currency per unit of base currency
+    divergence_tolerance: f64,
     supported_currencies: Vec<String>,
     http_client: Client,
     api_keys: HashMap<String, String>,
+    rate_source_policy: RateSourcePolicy,
+    rate_providers: HashMap<String, Arc<dyn RateProvider>>,
     rate_cache_duration: Duration,
     last_update: Option<Instant>,
     conversion_fees: HashMap<String, f64>,
+    fee_calculator: FeeCalculator,
+    network_fees: HashMap<String, u64>, // Flat fee in target currency units
+    limits: ConversionLimits,
+    daily_usage: HashMap<String, (NaiveDate, u64)>, // Customer -> (day, base value converted)
+    compliance: Option<Arc<RwLock<RegulatoryCompliance>>>,
+    fee_collector: Option<FeeCollector>,
+    /// Audit trail every completed conversion is written to
+    conversion_store: Option<Arc<dyn ConversionRecordStore>>,
 }
 
 impl ConversionService {
@@ -46,8 +174,14 @@ impl ConversionService {
         fees.insert("CHF".to_string(), 0.0016); // 0.16% fee
         fees.insert("CNY".to_string(), 0.002); // 0.2% fee
 
+        let mut base_rates = HashMap::new();
+        base_rates.insert(DEFAULT_BASE_CURRENCY.to_string(), 1.0);
+
         Self {
             exchange_rates: HashMap::new(),
+            base_currency: DEFAULT_BASE_CURRENCY.to_string(),
+            base_rates,
+            divergence_tolerance: 0.005, // 0.5%
             supported_currencies: vec![
                 "USD".to_string(),
                 "EUR".to_string(),
@@ -61,20 +195,249 @@ impl ConversionService {
             ],
             http_client: Client::new(),
             api_keys: HashMap::new(),
+            rate_source_policy: RateSourcePolicy::default(),
+            rate_providers: HashMap::new(),
             rate_cache_duration: Duration::from_secs(300), // 5 minutes
             last_update: None,
             conversion_fees: fees,
+            fee_calculator: FeeCalculator::default(),
+            network_fees: HashMap::new(),
+            limits: ConversionLimits::default(),
+            daily_usage: HashMap::new(),
+            compliance: None,
+            fee_collector: None,
+            conversion_store: None,
+        }
+    }
+
+    /// Set per-transaction and daily conversion limits
+    pub fn set_conversion_limits(&mut self, limits: ConversionLimits) {
+        self.limits = limits;
+    }
+
+    pub fn conversion_limits(&self) -> &ConversionLimits {
+        &self.limits
+    }
+
+    /// Screen large conversions through regulatory compliance
+    pub fn set_compliance(&mut self, compliance: Arc<RwLock<RegulatoryCompliance>>) {
+        self.compliance = Some(compliance);
+    }
+
+    /// Credit conversion fees to the ledger's fee-collection account
+    pub fn set_fee_collector(&mut self, fee_collector: FeeCollector) {
+        self.fee_collector = Some(fee_collector);
+    }
+
+    /// Record every completed conversion in `store`, by customer account ID
+    pub fn set_conversion_store(&mut self, store: Arc<dyn ConversionRecordStore>) {
+        self.conversion_store = Some(store);
+    }
+
+    /// Conversions recorded for an account in `[from, to)`, oldest first
+    pub async fn conversion_history(
+        &self,
+        account_id: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ConversionRecord>, AstorError> {
+        let store = self.conversion_store.as_ref().ok_or_else(|| {
+            AstorError::ConversionFailed("No conversion audit trail is configured".to_string())
+        })?;
+        store.conversions_for_account(account_id, from, to).await
+    }
+
+    /// Amount a customer has converted so far today, in the base currency
+    pub fn daily_conversion_total(&self, customer_id: &str) -> u64 {
+        let today = chrono::Utc::now().date_naive();
+        match self.daily_usage.get(customer_id) {
+            Some((day, total)) if *day == today => *total,
+            _ => 0,
         }
     }
 
+    /// Reject a conversion that would breach the customer's limits
+    fn check_conversion_limits(&self, customer_id: &str, value: u64) -> Result<(), AstorError> {
+        if value > self.limits.per_transaction_limit {
+            return Err(AstorError::ConversionLimitExceeded(format!(
+                "{} {} exceeds the per-transaction limit of {}",
+                value, self.base_currency, self.limits.per_transaction_limit
+            )));
+        }
+
+        let converted_today = self.daily_conversion_total(customer_id);
+        if converted_today.saturating_add(value) > self.limits.daily_limit {
+            return Err(AstorError::ConversionLimitExceeded(format!(
+                "customer {} has converted {} of the {} {} daily limit",
+                customer_id, converted_today, self.limits.daily_limit, self.base_currency
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn record_daily_usage(&mut self, customer_id: &str, value: u64) {
+        let total = self
+            .daily_conversion_total(customer_id)
+            .saturating_add(value);
+        self.daily_usage.insert(
+            customer_id.to_string(),
+            (chrono::Utc::now().date_naive(), total),
+        );
+    }
+
+    /// Set the percentage service fee charged when converting into a currency
+    pub fn set_conversion_fee(&mut self, currency: String, fee_rate: f64) {
+        self.conversion_fees.insert(currency, fee_rate);
+    }
+
+    /// Round service fees with the calculator shared with other modules
+    pub fn set_fee_calculator(&mut self, fee_calculator: FeeCalculator) {
+        self.fee_calculator = fee_calculator;
+    }
+
+    /// Set the flat network fee charged when converting into a currency
+    pub fn set_network_fee(&mut self, currency: String, fee: u64) {
+        self.network_fees.insert(currency, fee);
+    }
+
+    /// Set the currency all rates are normalized against
+    pub fn set_base_currency(&mut self, currency: &str) {
+        self.base_currency = currency.to_string();
+        self.normalize_rates();
+    }
+
+    pub fn base_currency(&self) -> &str {
+        &self.base_currency
+    }
+
+    /// Set the relative difference above which direct quotes are reported as
+    /// diverging from derived cross rates
+    pub fn set_divergence_tolerance(&mut self, tolerance: f64) {
+        self.divergence_tolerance = tolerance;
+    }
+
     /// Add or update exchange rate
     pub fn update_exchange_rate(&mut self, rate: ExchangeRate) {
         let key = format!("{}_{}", rate.from_currency, rate.to_currency);
         self.exchange_rates.insert(key, rate);
+        self.normalize_rates();
+    }
+
+    /// Rebuild the per-currency base rates from the direct quotes
+    ///
+    /// Providers quote against different bases (USD, EUR, ASTOR), so each
+    /// currency is priced in the base currency by walking outward from the
+    /// base one hop at a time. Quotes closest to the base win, which keeps
+    /// the result independent of the order rates were fetched in.
+    fn normalize_rates(&mut self) {
+        let mut base_rates = HashMap::new();
+        base_rates.insert(self.base_currency.clone(), 1.0);
+
+        let mut quotes: Vec<&ExchangeRate> = self
+            .exchange_rates
+            .values()
+            .filter(|quote| quote.rate > 0.0 && quote.rate.is_finite())
+            .collect();
+        quotes.sort_by(|a, b| {
+            (&a.from_currency, &a.to_currency).cmp(&(&b.from_currency, &b.to_currency))
+        });
+
+        loop {
+            let known = base_rates.clone();
+            for quote in &quotes {
+                match (
+                    known.get(&quote.from_currency),
+                    known.get(&quote.to_currency),
+                ) {
+                    (Some(from_rate), None) => {
+                        base_rates
+                            .entry(quote.to_currency.clone())
+                            .or_insert(from_rate * quote.rate);
+                    }
+                    (None, Some(to_rate)) => {
+                        base_rates
+                            .entry(quote.from_currency.clone())
+                            .or_insert(to_rate / quote.rate);
+                    }
+                    _ => {}
+                }
+            }
+
+            if base_rates.len() == known.len() {
+                break;
+            }
+        }
+
+        self.base_rates = base_rates;
+    }
+
+    /// Price of one unit of `currency` in the base currency's terms
+    pub fn get_base_rate(&self, currency: &str) -> Option<f64> {
+        self.base_rates.get(currency).copied()
+    }
+
+    /// Derive the rate between two currencies through the base currency
+    pub fn get_cross_rate(&self, from: &str, to: &str) -> Result<f64, AstorError> {
+        match (self.base_rates.get(from), self.base_rates.get(to)) {
+            (Some(from_rate), Some(to_rate)) => Ok(to_rate / from_rate),
+            _ => Err(AstorError::ConversionFailed(format!(
+                "No {}-based rate available for {} to {}",
+                self.base_currency, from, to
+            ))),
+        }
+    }
+
+    /// Compare every direct quote against the cross rate derived from the
+    /// base, reporting those that diverge beyond the tolerance
+    pub fn check_rate_consistency(&self) -> Vec<RateDivergence> {
+        let mut divergences: Vec<RateDivergence> = self
+            .exchange_rates
+            .values()
+            .filter_map(|quote| {
+                let derived_rate = self
+                    .get_cross_rate(&quote.from_currency, &quote.to_currency)
+                    .ok()?;
+                let divergence = (quote.rate - derived_rate).abs() / derived_rate;
+
+                (divergence > self.divergence_tolerance).then(|| RateDivergence {
+                    from_currency: quote.from_currency.clone(),
+                    to_currency: quote.to_currency.clone(),
+                    direct_rate: quote.rate,
+                    derived_rate,
+                    divergence,
+                    source: quote.source.clone(),
+                })
+            })
+            .collect();
+        divergences.sort_by(|a, b| {
+            (&a.from_currency, &a.to_currency).cmp(&(&b.from_currency, &b.to_currency))
+        });
+
+        for divergence in &divergences {
+            tracing::warn!(
+                "Rate {}/{} from {} diverges {:.2}% from derived cross rate ({} vs {})",
+                divergence.from_currency,
+                divergence.to_currency,
+                divergence.source,
+                divergence.divergence * 100.0,
+                divergence.direct_rate,
+                divergence.derived_rate
+            );
+        }
+
+        divergences
     }
 
     /// Get exchange rate between currencies
+    ///
+    /// Rates are derived through the base currency when both currencies are
+    /// priced against it, so chained conversions stay triangle-consistent.
     pub fn get_exchange_rate(&self, from: &str, to: &str) -> Result<f64, AstorError> {
+        if let Ok(rate) = self.get_cross_rate(from, to) {
+            return Ok(rate);
+        }
+
         let key = format!("{}_{}", from, to);
 
         if let Some(rate) = self.exchange_rates.get(&key) {
@@ -104,7 +467,28 @@ impl ConversionService {
         Ok(converted)
     }
 
-    /// Placeholder for external API integration
+    /// Replace the provider order and pair pinning used by `fetch_live_rates`
+    pub fn set_rate_source_policy(&mut self, policy: RateSourcePolicy) {
+        self.rate_source_policy = policy;
+    }
+
+    pub fn rate_source_policy(&self) -> &RateSourcePolicy {
+        &self.rate_source_policy
+    }
+
+    /// Register a rate provider under `provider.name()`, replacing any
+    /// built-in provider of the same name
+    pub fn register_rate_provider(&mut self, provider: Arc<dyn RateProvider>) {
+        self.rate_providers
+            .insert(provider.name().to_string(), provider);
+    }
+
+    /// Refresh rates from the configured providers
+    ///
+    /// General rates come from the first provider in the policy's order that
+    /// responds. Each provider with pinned pairs is also queried, and a
+    /// pinned pair only ever takes its rate from its pinned provider. Rates
+    /// fall back to built-in values only if no provider responds.
     pub async fn fetch_live_rates(&mut self) -> Result<(), AstorError> {
         // Check if cache is still valid
         if let Some(last_update) = self.last_update {
@@ -113,39 +497,88 @@ impl ConversionService {
             }
         }
 
-        // Try multiple providers for redundancy
-        let providers = vec!["exchangerate-api", "fixer", "currencylayer"];
+        let mut fetched: Vec<(String, Vec<ExchangeRate>)> = Vec::new();
+        let mut general_source = None;
 
-        for provider in providers {
+        for provider in self.rate_source_policy.provider_order.clone() {
             match self.fetch_from_provider(&provider).await {
-                Ok(_) => {
-                    self.last_update = Some(Instant::now());
-                    return Ok(());
+                Ok(rates) => {
+                    fetched.push((provider.clone(), rates));
+                    general_source = Some(provider);
+                    break;
                 }
                 Err(e) => {
-                    eprintln!("Failed to fetch from {}: {}", provider, e);
-                    continue;
+                    tracing::warn!("Failed to fetch rates from {}: {}", provider, e);
                 }
             }
         }
 
-        // Fallback to mock rates if all providers fail
-        self.use_fallback_rates();
+        for provider in self.rate_source_policy.pinned_providers() {
+            if fetched.iter().any(|(name, _)| *name == provider) {
+                continue;
+            }
+            match self.fetch_from_provider(&provider).await {
+                Ok(rates) => fetched.push((provider, rates)),
+                Err(e) => tracing::warn!(
+                    "Failed to fetch pinned rates from {}; keeping previous rates: {}",
+                    provider,
+                    e
+                ),
+            }
+        }
+
+        if fetched.is_empty() {
+            // Fallback to mock rates if all providers fail
+            self.use_fallback_rates();
+            return Ok(());
+        }
+
+        for (provider, rates) in fetched {
+            let is_general = general_source.as_deref() == Some(provider.as_str());
+            for rate in rates {
+                let accepted = match self
+                    .rate_source_policy
+                    .pinned_provider(&rate.from_currency, &rate.to_currency)
+                {
+                    Some(pinned) => pinned == provider,
+                    None => is_general,
+                };
+                if accepted {
+                    self.update_exchange_rate(rate);
+                }
+            }
+        }
+
+        self.last_update = Some(Instant::now());
+        self.check_rate_consistency();
         Ok(())
     }
 
     /// Provider-specific rate fetching
-    async fn fetch_from_provider(&mut self, provider: &str) -> Result<(), AstorError> {
+    async fn fetch_from_provider(&self, provider: &str) -> Result<Vec<ExchangeRate>, AstorError> {
+        if let Some(registered) = self.rate_providers.get(provider) {
+            return registered.fetch_rates(&self.supported_currencies).await;
+        }
+
         match provider {
             "exchangerate-api" => self.fetch_from_exchangerate_api().await,
             "fixer" => self.fetch_from_fixer().await,
             "currencylayer" => self.fetch_from_currencylayer().await,
-            _ => Err(AstorError::ConversionFailed("Unknown provider".to_string())),
+            _ => Err(AstorError::ConversionFailed(format!(
+                "Unknown provider {}",
+                provider
+            ))),
         }
     }
 
+    fn api_key(&self, provider: &str) -> Result<&String, AstorError> {
+        self.api_keys.get(provider).ok_or_else(|| {
+            AstorError::ConversionFailed(format!("No API key configured for {}", provider))
+        })
+    }
+
     /// ExchangeRate-API integration
-    async fn fetch_from_exchangerate_api(&mut self) -> Result<(), AstorError> {
+    async fn fetch_from_exchangerate_api(&self) -> Result<Vec<ExchangeRate>, AstorError> {
         let url = "https://api.exchangerate-api.com/v4/latest/USD";
 
         let response: serde_json::Value = self
@@ -158,11 +591,12 @@ impl ConversionService {
             .await
             .map_err(|e| AstorError::ConversionFailed(format!("JSON parsing failed: {}", e)))?;
 
+        let mut fetched = Vec::new();
         if let Some(rates) = response["rates"].as_object() {
             for (currency, rate) in rates {
                 if self.supported_currencies.contains(currency) {
                     let rate_value = rate.as_f64().unwrap_or(0.0);
-                    self.update_exchange_rate(ExchangeRate {
+                    fetched.push(ExchangeRate {
                         from_currency: "USD".to_string(),
                         to_currency: currency.clone(),
                         rate: rate_value,
@@ -177,94 +611,92 @@ impl ConversionService {
             }
         }
 
-        Ok(())
+        Ok(fetched)
     }
 
     /// Fixer.io integration
-    async fn fetch_from_fixer(&mut self) -> Result<(), AstorError> {
-        if let Some(api_key) = self.api_keys.get("fixer") {
-            let url = format!("http://data.fixer.io/api/latest?access_key={}", api_key);
-
-            let response: serde_json::Value = self
-                .http_client
-                .get(&url)
-                .send()
-                .await
-                .map_err(|e| {
-                    AstorError::ConversionFailed(format!("Fixer API request failed: {}", e))
-                })?
-                .json()
-                .await
-                .map_err(|e| AstorError::ConversionFailed(format!("JSON parsing failed: {}", e)))?;
+    async fn fetch_from_fixer(&self) -> Result<Vec<ExchangeRate>, AstorError> {
+        let api_key = self.api_key("fixer")?;
+        let url = format!("http://data.fixer.io/api/latest?access_key={}", api_key);
 
-            if response["success"].as_bool().unwrap_or(false) {
-                if let Some(rates) = response["rates"].as_object() {
-                    for (currency, rate) in rates {
-                        if self.supported_currencies.contains(currency) {
-                            let rate_value = rate.as_f64().unwrap_or(0.0);
-                            self.update_exchange_rate(ExchangeRate {
-                                from_currency: "EUR".to_string(), // Fixer uses EUR as base
-                                to_currency: currency.clone(),
-                                rate: rate_value,
-                                bid: rate_value * 0.999,
-                                ask: rate_value * 1.001,
-                                timestamp: chrono::Utc::now(),
-                                source: "fixer".to_string(),
-                                volatility: 0.01,
-                                daily_change: 0.0,
-                            });
-                        }
+        let response: serde_json::Value = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| AstorError::ConversionFailed(format!("Fixer API request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AstorError::ConversionFailed(format!("JSON parsing failed: {}", e)))?;
+
+        let mut fetched = Vec::new();
+        if response["success"].as_bool().unwrap_or(false) {
+            if let Some(rates) = response["rates"].as_object() {
+                for (currency, rate) in rates {
+                    if self.supported_currencies.contains(currency) {
+                        let rate_value = rate.as_f64().unwrap_or(0.0);
+                        fetched.push(ExchangeRate {
+                            from_currency: "EUR".to_string(), // Fixer uses EUR as base
+                            to_currency: currency.clone(),
+                            rate: rate_value,
+                            bid: rate_value * 0.999,
+                            ask: rate_value * 1.001,
+                            timestamp: chrono::Utc::now(),
+                            source: "fixer".to_string(),
+                            volatility: 0.01,
+                            daily_change: 0.0,
+                        });
                     }
                 }
             }
         }
 
-        Ok(())
+        Ok(fetched)
     }
 
     /// CurrencyLayer integration
-    async fn fetch_from_currencylayer(&mut self) -> Result<(), AstorError> {
-        if let Some(api_key) = self.api_keys.get("currencylayer") {
-            let url = format!("http://api.currencylayer.com/live?access_key={}", api_key);
-
-            let response: serde_json::Value = self
-                .http_client
-                .get(&url)
-                .send()
-                .await
-                .map_err(|e| {
-                    AstorError::ConversionFailed(format!("CurrencyLayer API request failed: {}", e))
-                })?
-                .json()
-                .await
-                .map_err(|e| AstorError::ConversionFailed(format!("JSON parsing failed: {}", e)))?;
-
-            if response["success"].as_bool().unwrap_or(false) {
-                if let Some(quotes) = response["quotes"].as_object() {
-                    for (pair, rate) in quotes {
-                        if pair.starts_with("USD") {
-                            let to_currency = &pair[3..];
-                            if self.supported_currencies.contains(&to_currency.to_string()) {
-                                let rate_value = rate.as_f64().unwrap_or(0.0);
-                                self.update_exchange_rate(ExchangeRate {
-                                    from_currency: "USD".to_string(),
-                                    to_currency: to_currency.to_string(),
-                                    rate: rate_value,
-                                    bid: rate_value * 0.999,
-                                    ask: rate_value * 1.001,
-                                    timestamp: chrono::Utc::now(),
-                                    source: "currencylayer".to_string(),
-                                    volatility: 0.01,
-                                    daily_change: 0.0,
-                                });
-                            }
+    async fn fetch_from_currencylayer(&self) -> Result<Vec<ExchangeRate>, AstorError> {
+        let api_key = self.api_key("currencylayer")?;
+        let url = format!("http://api.currencylayer.com/live?access_key={}", api_key);
+
+        let response: serde_json::Value = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| {
+                AstorError::ConversionFailed(format!("CurrencyLayer API request failed: {}", e))
+            })?
+            .json()
+            .await
+            .map_err(|e| AstorError::ConversionFailed(format!("JSON parsing failed: {}", e)))?;
+
+        let mut fetched = Vec::new();
+        if response["success"].as_bool().unwrap_or(false) {
+            if let Some(quotes) = response["quotes"].as_object() {
+                for (pair, rate) in quotes {
+                    if pair.starts_with("USD") {
+                        let to_currency = &pair[3..];
+                        if self.supported_currencies.contains(&to_currency.to_string()) {
+                            let rate_value = rate.as_f64().unwrap_or(0.0);
+                            fetched.push(ExchangeRate {
+                                from_currency: "USD".to_string(),
+                                to_currency: to_currency.to_string(),
+                                rate: rate_value,
+                                bid: rate_value * 0.999,
+                                ask: rate_value * 1.001,
+                                timestamp: chrono::Utc::now(),
+                                source: "currencylayer".to_string(),
+                                volatility: 0.01,
+                                daily_change: 0.0,
+                            });
                         }
                     }
                 }
             }
         }
 
-        Ok(())
+        Ok(fetched)
     }
 
     /// Fallback rates for when APIs are unavailable
@@ -319,8 +751,13 @@ impl ConversionService {
     }
 
     /// Enhanced conversion with fees and slippage protection
+    ///
+    /// The customer's per-transaction and daily limits are enforced before
+    /// the conversion is priced, and conversions above the AML review
+    /// threshold are screened when a compliance service is configured.
     pub async fn convert_with_fees(
         &mut self,
+        customer_id: &str,
         amount: u64,
         from: &str,
         to: &str,
@@ -331,15 +768,19 @@ impl ConversionService {
                 original_amount: amount,
                 converted_amount: amount,
                 exchange_rate: 1.0,
-                fees: 0,
+                fees: FeeBreakdown::default(),
                 slippage: 0.0,
                 timestamp: chrono::Utc::now(),
+                aml_alert_id: None,
             });
         }
 
         // Ensure we have fresh rates
         self.fetch_live_rates().await?;
 
+        let value = self.convert_amount(amount, from, &self.base_currency)?;
+        self.check_conversion_limits(customer_id, value)?;
+
         let rate_info = self.get_exchange_rate_info(from, to)?;
 
         // Check slippage protection
@@ -352,22 +793,109 @@ impl ConversionService {
             }
         }
 
-        // Calculate conversion
-        let converted_amount = (amount as f64 * rate_info.rate).round() as u64;
+        let mut result = self.calculate_conversion(amount, rate_info, to);
+        let provider = rate_info.source.clone();
+
+        if value >= self.limits.aml_review_threshold {
+            if let Some(compliance) = &self.compliance {
+                result.aml_alert_id = compliance.write().await.check_aml_compliance(
+                    customer_id,
+                    value,
+                    "currency_conversion",
+                )?;
+            }
+        }
+
+        if let Some(fee_collector) = &self.fee_collector {
+            fee_collector
+                .collect(
+                    uuid::Uuid::new_v4().to_string(),
+                    FeeSource::Conversion,
+                    customer_id,
+                    to,
+                    result.fees.total,
+                )
+                .await?;
+        }
+
+        self.record_daily_usage(customer_id, value);
+
+        if let Some(store) = &self.conversion_store {
+            store
+                .record_conversion(&conversion_record(
+                    customer_id,
+                    from,
+                    to,
+                    &result,
+                    &provider,
+                )?)
+                .await?;
+        }
+        Ok(result)
+    }
+
+    /// Price a conversion against the cached quote without fetching
+    ///
+    /// Fails if the quote is older than the rate cache duration, so callers
+    /// never settle against a stale rate.
+    pub fn quote_conversion(
+        &self,
+        amount: u64,
+        from: &str,
+        to: &str,
+    ) -> Result<ConversionResult, AstorError> {
+        let rate_info = self.get_exchange_rate_info(from, to)?;
+
+        let age = chrono::Utc::now() - rate_info.timestamp;
+        if age.to_std().is_ok_and(|age| age > self.rate_cache_duration) {
+            return Err(AstorError::ConversionFailed(format!(
+                "Exchange rate for {} to {} is stale ({}s old)",
+                from,
+                to,
+                age.num_seconds()
+            )));
+        }
+
+        Ok(self.calculate_conversion(amount, rate_info, to))
+    }
+
+    /// Price a conversion at the quoted rate, itemizing every fee component
+    ///
+    /// The customer sells at the bid, so the spread cost is the difference
+    /// between the mid-rate value and the bid value. Service and network fees
+    /// are then charged on the bid value.
+    fn calculate_conversion(
+        &self,
+        amount: u64,
+        rate_info: &ExchangeRate,
+        to: &str,
+    ) -> ConversionResult {
+        let mid_amount = (amount as f64 * rate_info.rate).round() as u64;
+        let bid_amount = (amount as f64 * rate_info.bid.min(rate_info.rate)).round() as u64;
+        let spread = mid_amount.saturating_sub(bid_amount);
 
-        // Calculate fees
         let fee_rate = self.conversion_fees.get(to).unwrap_or(&0.001);
-        let fees = (converted_amount as f64 * fee_rate).round() as u64;
-        let final_amount = converted_amount.saturating_sub(fees);
+        let service_fee = self.fee_calculator.percentage_fee(bid_amount, *fee_rate);
+        let network_fee = self.network_fees.get(to).copied().unwrap_or(0);
+
+        // Fees can never deduct more than the converted value
+        let total = (spread + service_fee + network_fee).min(mid_amount);
+        let fees = FeeBreakdown {
+            spread,
+            service_fee,
+            network_fee,
+            total,
+        };
 
-        Ok(ConversionResult {
+        ConversionResult {
             original_amount: amount,
-            converted_amount: final_amount,
+            converted_amount: mid_amount - total,
             exchange_rate: rate_info.rate,
             fees,
             slippage: rate_info.volatility,
             timestamp: chrono::Utc::now(),
-        })
+            aml_alert_id: None,
+        }
     }
 
     /// Get supported currencies
@@ -410,13 +938,409 @@ pub enum ConversionStatus {
     Failed(String),
 }
 
+/// Itemized fees deducted from a conversion, in target currency units
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeBreakdown {
+    /// Cost of converting at the bid rather than the mid rate
+    pub spread: u64,
+    /// Percentage conversion fee charged by the service
+    pub service_fee: u64,
+    /// Flat fee for settling in the target currency
+    pub network_fee: u64,
+    /// Total deducted from the converted amount
+    pub total: u64,
+}
+
 /// Enhanced conversion result with detailed information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversionResult {
     pub original_amount: u64,
     pub converted_amount: u64,
     pub exchange_rate: f64,
-    pub fees: u64,
+    pub fees: FeeBreakdown,
     pub slippage: f64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// AML alert raised when the conversion was screened
+    #[serde(default)]
+    pub aml_alert_id: Option<String>,
+}
+
+fn to_i64(amount: u64) -> Result<i64, AstorError> {
+    i64::try_from(amount).map_err(|_| {
+        AstorError::ConversionFailed(format!("Amount {} is too large to record", amount))
+    })
+}
+
+/// Audit record of a completed conversion
+fn conversion_record(
+    account_id: &str,
+    from: &str,
+    to: &str,
+    result: &ConversionResult,
+    provider: &str,
+) -> Result<ConversionRecord, AstorError> {
+    Ok(ConversionRecord {
+        id: uuid::Uuid::new_v4(),
+        account_id: account_id.to_string(),
+        from_currency: from.to_string(),
+        to_currency: to.to_string(),
+        original_amount: to_i64(result.original_amount)?,
+        converted_amount: to_i64(result.converted_amount)?,
+        exchange_rate: result.exchange_rate,
+        fees: to_i64(result.fees.total)?,
+        slippage: result.slippage,
+        provider: provider.to_string(),
+        status: "completed".to_string(),
+        created_at: result.timestamp,
+        completed_at: Some(chrono::Utc::now()),
+        metadata: serde_json::json!({
+            "fee_breakdown": result.fees,
+            "aml_alert_id": result.aml_alert_id,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(from: &str, to: &str, rate: f64, source: &str) -> ExchangeRate {
+        ExchangeRate {
+            from_currency: from.to_string(),
+            to_currency: to.to_string(),
+            rate,
+            bid: rate * 0.999,
+            ask: rate * 1.001,
+            timestamp: chrono::Utc::now(),
+            source: source.to_string(),
+            volatility: 0.01,
+            daily_change: 0.0,
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_cross_rates_are_triangle_consistent() {
+        let mut service = ConversionService::new();
+        // USD-based and EUR-based providers mixed together
+        service.update_exchange_rate(quote("USD", "EUR", 0.9, "exchangerate-api"));
+        service.update_exchange_rate(quote("USD", "GBP", 0.8, "exchangerate-api"));
+        service.update_exchange_rate(quote("EUR", "JPY", 160.0, "fixer"));
+
+        let usd_eur = service.get_exchange_rate("USD", "EUR").unwrap();
+        let eur_gbp = service.get_exchange_rate("EUR", "GBP").unwrap();
+        let usd_gbp = service.get_exchange_rate("USD", "GBP").unwrap();
+        assert_close(usd_eur * eur_gbp, usd_gbp);
+
+        let gbp_eur = service.get_exchange_rate("GBP", "EUR").unwrap();
+        let eur_jpy = service.get_exchange_rate("EUR", "JPY").unwrap();
+        let gbp_jpy = service.get_exchange_rate("GBP", "JPY").unwrap();
+        assert_close(gbp_eur * eur_jpy, gbp_jpy);
+        assert_close(gbp_jpy, 180.0);
+
+        assert!(service.check_rate_consistency().is_empty());
+    }
+
+    #[test]
+    fn test_base_currency_is_configurable() {
+        let mut service = ConversionService::new();
+        service.update_exchange_rate(quote("USD", "EUR", 0.9, "exchangerate-api"));
+        service.update_exchange_rate(quote("EUR", "JPY", 160.0, "fixer"));
+
+        service.set_base_currency("EUR");
+        assert_eq!(service.get_base_rate("EUR"), Some(1.0));
+        assert_close(service.get_base_rate("USD").unwrap(), 1.0 / 0.9);
+        assert_close(service.get_cross_rate("USD", "JPY").unwrap(), 144.0);
+    }
+
+    #[test]
+    fn test_diverging_direct_rate_is_reported() {
+        let mut service = ConversionService::new();
+        service.update_exchange_rate(quote("USD", "EUR", 0.9, "exchangerate-api"));
+        service.update_exchange_rate(quote("USD", "GBP", 0.8, "exchangerate-api"));
+        // Derived EUR/GBP is 0.8889; this quote is ~7% off
+        service.update_exchange_rate(quote("EUR", "GBP", 0.95, "fixer"));
+
+        let divergences = service.check_rate_consistency();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].from_currency, "EUR");
+        assert_eq!(divergences[0].to_currency, "GBP");
+        assert_eq!(divergences[0].source, "fixer");
+        assert_close(divergences[0].derived_rate, 0.8 / 0.9);
+
+        // Conversions use the consistent derived rate
+        assert_close(service.get_exchange_rate("EUR", "GBP").unwrap(), 0.8 / 0.9);
+    }
+
+    #[tokio::test]
+    async fn test_fee_breakdown_sums_to_total_deducted() {
+        let mut service = ConversionService::new();
+        service.update_exchange_rate(ExchangeRate {
+            from_currency: "ASTOR".to_string(),
+            to_currency: "EUR".to_string(),
+            rate: 0.85,
+            bid: 0.84,
+            ask: 0.86,
+            timestamp: chrono::Utc::now(),
+            source: "test".to_string(),
+            volatility: 0.01,
+            daily_change: 0.0,
+        });
+        service.set_network_fee("EUR".to_string(), 25);
+        // Keep the cached test rate instead of fetching live rates
+        service.last_update = Some(Instant::now());
+
+        let result = service
+            .convert_with_fees("customer-1", 1_000_000, "ASTOR", "EUR", None)
+            .await
+            .unwrap();
+
+        let fees = &result.fees;
+        assert_eq!(fees.spread, 10_000);
+        assert_eq!(fees.service_fee, 1_008);
+        assert_eq!(fees.network_fee, 25);
+        assert_eq!(
+            fees.spread + fees.service_fee + fees.network_fee,
+            fees.total
+        );
+
+        let mid_amount = (1_000_000f64 * 0.85).round() as u64;
+        assert_eq!(mid_amount - result.converted_amount, fees.total);
+    }
+
+    fn limited_service(limits: ConversionLimits) -> ConversionService {
+        let mut service = ConversionService::new();
+        service.update_exchange_rate(quote("USD", "EUR", 0.9, "test"));
+        service.set_conversion_limits(limits);
+        service.last_update = Some(Instant::now());
+        service
+    }
+
+    #[tokio::test]
+    async fn test_per_transaction_limit_rejected() {
+        let mut service = limited_service(ConversionLimits {
+            per_transaction_limit: 5_000,
+            daily_limit: 100_000,
+            aml_review_threshold: 50_000,
+        });
+
+        let result = service
+            .convert_with_fees("customer-1", 5_001, "USD", "EUR", None)
+            .await;
+        assert!(matches!(
+            result,
+            Err(AstorError::ConversionLimitExceeded(_))
+        ));
+        assert_eq!(service.daily_conversion_total("customer-1"), 0);
+
+        assert!(service
+            .convert_with_fees("customer-1", 5_000, "USD", "EUR", None)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cumulative_daily_limit_rejected() {
+        let mut service = limited_service(ConversionLimits {
+            per_transaction_limit: 5_000,
+            daily_limit: 12_000,
+            aml_review_threshold: 50_000,
+        });
+
+        for _ in 0..2 {
+            service
+                .convert_with_fees("customer-1", 5_000, "USD", "EUR", None)
+                .await
+                .unwrap();
+        }
+        assert_eq!(service.daily_conversion_total("customer-1"), 10_000);
+
+        let result = service
+            .convert_with_fees("customer-1", 5_000, "USD", "EUR", None)
+            .await;
+        assert!(matches!(
+            result,
+            Err(AstorError::ConversionLimitExceeded(_))
+        ));
+
+        // Limits are tracked per customer
+        assert!(service
+            .convert_with_fees("customer-2", 5_000, "USD", "EUR", None)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_large_conversion_screened_for_aml() {
+        let mut service = limited_service(ConversionLimits {
+            per_transaction_limit: 1_000_000,
+            daily_limit: 1_000_000,
+            aml_review_threshold: 10_000,
+        });
+        service.set_compliance(Arc::new(RwLock::new(RegulatoryCompliance::new())));
+
+        let small = service
+            .convert_with_fees("customer-1", 9_000, "USD", "EUR", None)
+            .await
+            .unwrap();
+        assert!(small.aml_alert_id.is_none());
+
+        let large = service
+            .convert_with_fees("customer-1", 20_000, "USD", "EUR", None)
+            .await
+            .unwrap();
+        assert!(large.aml_alert_id.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_conversion_fee_credited_to_fee_account() {
+        let mut ledger = crate::ledger::Ledger::new();
+        let (fee_collector, mut fee_postings) = FeeCollector::new("fee-collection".to_string());
+        let mut service = limited_service(ConversionLimits::default());
+        service.set_network_fee("EUR".to_string(), 25);
+        service.set_fee_collector(fee_collector);
+
+        let first = service
+            .convert_with_fees("customer-1", 100_000, "USD", "EUR", None)
+            .await
+            .unwrap();
+        let second = service
+            .convert_with_fees("customer-2", 50_000, "USD", "EUR", None)
+            .await
+            .unwrap();
+        assert!(first.fees.total > 0);
+
+        assert_eq!(fee_postings.post_to(&mut ledger).unwrap(), 2);
+        match &ledger.get_entries()[0].entry_type {
+            crate::ledger::LedgerEntryType::FeeCollection {
+                source,
+                payer,
+                fee_account,
+                currency,
+                amount,
+                ..
+            } => {
+                assert_eq!(*source, FeeSource::Conversion);
+                assert_eq!(payer, "customer-1");
+                assert_eq!(fee_account, "fee-collection");
+                assert_eq!(currency, "EUR");
+                assert_eq!(*amount, first.fees.total);
+            }
+            other => panic!("expected a fee collection entry, got {:?}", other),
+        }
+
+        // Fee revenue reconciles against what customers were charged
+        assert_eq!(
+            ledger.fees_collected("fee-collection", "EUR").unwrap(),
+            first.fees.total + second.fees.total
+        );
+        assert!(ledger.verify_integrity().unwrap());
+    }
+
+    struct StaticProvider {
+        name: &'static str,
+        rates: Vec<ExchangeRate>,
+    }
+
+    #[async_trait::async_trait]
+    impl RateProvider for StaticProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn fetch_rates(
+            &self,
+            _supported: &[String],
+        ) -> Result<Vec<ExchangeRate>, AstorError> {
+            Ok(self.rates.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pinned_pair_resolves_from_its_provider() {
+        let mut service = ConversionService::new();
+        service.register_rate_provider(Arc::new(StaticProvider {
+            name: "fast",
+            rates: vec![
+                quote("USD", "EUR", 0.5, "fast"),
+                quote("USD", "GBP", 0.8, "fast"),
+            ],
+        }));
+        service.register_rate_provider(Arc::new(StaticProvider {
+            name: "contracted",
+            rates: vec![
+                quote("EUR", "USD", 1.25, "contracted"),
+                quote("GBP", "USD", 2.0, "contracted"),
+            ],
+        }));
+
+        let mut policy = RateSourcePolicy {
+            provider_order: vec!["fast".to_string(), "contracted".to_string()],
+            ..RateSourcePolicy::default()
+        };
+        policy.pin_currency("EUR", "contracted");
+        service.set_rate_source_policy(policy);
+
+        service.fetch_live_rates().await.unwrap();
+
+        // "fast" answered first and supplies unpinned pairs, but EUR comes
+        // only from the contracted provider
+        assert_close(service.get_exchange_rate("USD", "EUR").unwrap(), 0.8);
+        assert_close(service.get_exchange_rate("USD", "GBP").unwrap(), 0.8);
+        assert!(service
+            .exchange_rates
+            .values()
+            .filter(|rate| rate.from_currency == "EUR" || rate.to_currency == "EUR")
+            .all(|rate| rate.source == "contracted"));
+        assert!(!service.exchange_rates.contains_key("GBP_USD"));
+    }
+
+    #[tokio::test]
+    async fn test_conversion_is_recorded_in_audit_trail() {
+        use crate::database::repositories::MemoryConversionStore;
+
+        let mut service = ConversionService::new();
+        service.update_exchange_rate(quote("ASTOR", "EUR", 0.85, "test"));
+        service.last_update = Some(Instant::now());
+        service.set_conversion_store(Arc::new(MemoryConversionStore::new()));
+
+        // Account IDs are not necessarily UUIDs
+        let account_id = "acct-alice".to_string();
+        let before = chrono::Utc::now() - chrono::Duration::minutes(1);
+        let result = service
+            .convert_with_fees(&account_id, 10_000, "ASTOR", "EUR", None)
+            .await
+            .unwrap();
+        let after = chrono::Utc::now() + chrono::Duration::minutes(1);
+
+        let history = service
+            .conversion_history(&account_id, before, after)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        let record = &history[0];
+        assert_eq!(record.account_id, account_id);
+        assert_eq!(record.from_currency, "ASTOR");
+        assert_eq!(record.to_currency, "EUR");
+        assert_eq!(record.original_amount, 10_000);
+        assert_eq!(record.converted_amount as u64, result.converted_amount);
+        assert_eq!(record.fees as u64, result.fees.total);
+        assert_eq!(record.exchange_rate, result.exchange_rate);
+        assert_eq!(record.provider, "test");
+
+        // Outside the date range, and for other accounts, nothing is returned
+        assert!(service
+            .conversion_history(&account_id, after, after + chrono::Duration::days(1))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(service
+            .conversion_history("acct-bob", before, after)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -4,13 +4,28 @@ pub mod models;
 pub mod migrations;
 pub mod repositories;
 
+use crate::config::DatabaseConfig;
 use crate::errors::AstorError;
+use sqlx::postgres::PgPoolOptions;
 use sqlx::{PgPool, Row};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
 
 /// Database connection pool wrapper
+///
+/// Writes always go to the primary. Read-only queries go to the replica
+/// when one is configured and reachable, and fall back to the primary
+/// otherwise, including when a query on the replica fails. Replicas lag the
+/// primary slightly, so a read that must see a write just made should use
+/// `write_pool()`.
 #[derive(Clone)]
 pub struct Database {
     pool: PgPool,
+    replica: Option<PgPool>,
+    replica_available: Arc<AtomicBool>,
 }
 
 impl Database {
@@ -20,14 +35,120 @@ impl Database {
             AstorError::DatabaseError(format!("Failed to connect to database: {}", e))
         })?;
 
-        Ok(Self { pool })
+        Ok(Self::from_pools(pool, None))
+    }
+
+    /// Connect to a primary and a read replica
+    ///
+    /// An unreachable replica does not stop startup; reads use the primary
+    /// until a health check finds the replica available.
+    pub async fn with_replica(database_url: &str, replica_url: &str) -> Result<Self, AstorError> {
+        let primary = Self::new(database_url).await?.pool;
+
+        match PgPool::connect(replica_url).await {
+            Ok(replica) => Ok(Self::from_pools(primary, Some(replica))),
+            Err(e) => {
+                tracing::warn!("Read replica unavailable, reading from primary: {}", e);
+                let replica = PgPoolOptions::new()
+                    .connect_lazy(replica_url)
+                    .map_err(|e| {
+                        AstorError::DatabaseError(format!("Invalid replica URL: {}", e))
+                    })?;
+                let database = Self::from_pools(primary, Some(replica));
+                database.replica_available.store(false, Ordering::Relaxed);
+                Ok(database)
+            }
+        }
+    }
+
+    /// Connect using the configured primary and optional replica
+    pub async fn from_config(config: &DatabaseConfig) -> Result<Self, AstorError> {
+        match &config.replica_url {
+            Some(replica_url) => Self::with_replica(&config.url, replica_url).await,
+            None => Self::new(&config.url).await,
+        }
+    }
+
+    /// Pools for the configured primary and optional replica that connect
+    /// on first use, for components set up before the database is needed
+    pub fn connect_lazy(config: &DatabaseConfig) -> Result<Self, AstorError> {
+        let lazy_pool = |url: &str| {
+            PgPoolOptions::new()
+                .max_connections(config.max_connections)
+                .connect_lazy(url)
+                .map_err(|e| AstorError::DatabaseError(format!("Invalid database URL: {}", e)))
+        };
+        let replica = config.replica_url.as_deref().map(lazy_pool).transpose()?;
+        Ok(Self::from_pools(lazy_pool(&config.url)?, replica))
+    }
+
+    /// Wrap existing pools
+    pub fn from_pools(primary: PgPool, replica: Option<PgPool>) -> Self {
+        Self {
+            replica_available: Arc::new(AtomicBool::new(replica.is_some())),
+            pool: primary,
+            replica,
+        }
     }
 
     /// Get database pool reference
     pub fn pool(&self) -> &PgPool {
+        self.write_pool()
+    }
+
+    /// Pool for writes and reads that must see them: always the primary
+    pub fn write_pool(&self) -> &PgPool {
         &self.pool
     }
 
+    /// Pool for read-only queries: the replica if available, else the primary
+    pub fn read_pool(&self) -> &PgPool {
+        match &self.replica {
+            Some(replica) if self.replica_available.load(Ordering::Relaxed) => replica,
+            _ => &self.pool,
+        }
+    }
+
+    /// Run a read-only query on the read pool
+    ///
+    /// A query that fails on the replica is retried on the primary, and a
+    /// replica that cannot be reached is taken out of read routing until a
+    /// health check finds it available again. Missing rows are not failures
+    /// and are returned as they are.
+    pub async fn read<'a, T, F, Fut>(&'a self, query: F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(&'a PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let replica = self
+            .replica
+            .as_ref()
+            .filter(|_| self.replica_available.load(Ordering::Relaxed));
+        let Some(replica) = replica else {
+            return query(&self.pool).await;
+        };
+
+        match query(replica).await {
+            Err(e) if !matches!(e, sqlx::Error::RowNotFound) => {
+                tracing::warn!("Read failed on the replica, retrying on the primary: {}", e);
+                if is_connection_error(&e) {
+                    self.set_replica_available(false);
+                }
+                query(&self.pool).await
+            }
+            result => result,
+        }
+    }
+
+    fn set_replica_available(&self, available: bool) {
+        let was_available = self.replica_available.swap(available, Ordering::Relaxed);
+        if was_available && !available {
+            tracing::warn!("Read replica unavailable; routing reads to the primary");
+        } else if !was_available && available {
+            tracing::info!("Read replica available again; routing reads to it");
+        }
+    }
+
     /// Run database migrations
     pub async fn migrate(&self) -> Result<(), AstorError> {
         sqlx::migrate!("./migrations")
@@ -38,11 +159,125 @@ impl Database {
     }
 
     /// Health check
+    ///
+    /// Only the primary is required. A failing replica is taken out of read
+    /// routing until it passes again.
     pub async fn health_check(&self) -> Result<(), AstorError> {
         sqlx::query("SELECT 1")
             .fetch_one(&self.pool)
             .await
             .map_err(|e| AstorError::DatabaseError(format!("Health check failed: {}", e)))?;
+
+        if let Some(replica) = &self.replica {
+            let replica_ok = sqlx::query("SELECT 1").fetch_one(replica).await.is_ok();
+            self.set_replica_available(replica_ok);
+        }
         Ok(())
     }
+
+    /// Run the health check every `interval` until the returned task is
+    /// aborted, so a recovered replica is routed reads again
+    pub fn spawn_health_checks(&self, interval: Duration) -> JoinHandle<()> {
+        let database = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval.max(Duration::from_secs(1)));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = database.health_check().await {
+                    tracing::error!("{}", e);
+                }
+            }
+        })
+    }
+}
+
+/// Whether an error means the server could not be reached, as opposed to
+/// the query itself failing
+fn is_connection_error(error: &sqlx::Error) -> bool {
+    matches!(
+        error,
+        sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lazy_pool(database: &str) -> PgPool {
+        PgPoolOptions::new()
+            .connect_lazy(&format!("postgresql://localhost/{}", database))
+            .unwrap()
+    }
+
+    fn database_name(pool: &PgPool) -> Option<String> {
+        pool.connect_options().get_database().map(str::to_string)
+    }
+
+    #[tokio::test]
+    async fn test_reads_use_replica_when_configured() {
+        let database = Database::from_pools(lazy_pool("primary"), Some(lazy_pool("replica")));
+        assert_eq!(
+            database_name(database.read_pool()).as_deref(),
+            Some("replica")
+        );
+        assert_eq!(
+            database_name(database.write_pool()).as_deref(),
+            Some("primary")
+        );
+
+        // Reads fall back to the primary while the replica is down
+        database.set_replica_available(false);
+        assert_eq!(
+            database_name(database.read_pool()).as_deref(),
+            Some("primary")
+        );
+        database.set_replica_available(true);
+        assert_eq!(
+            database_name(database.read_pool()).as_deref(),
+            Some("replica")
+        );
+
+        let primary_only = Database::from_pools(lazy_pool("primary"), None);
+        assert_eq!(
+            database_name(primary_only.read_pool()).as_deref(),
+            Some("primary")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_replica_read_falls_back_to_primary() {
+        let database = Database::from_pools(lazy_pool("primary"), Some(lazy_pool("replica")));
+
+        // A missing row on the replica is an answer, not a failure
+        let result: Result<String, _> = database
+            .read(|_| async { Err(sqlx::Error::RowNotFound) })
+            .await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+        assert_eq!(
+            database_name(database.read_pool()).as_deref(),
+            Some("replica")
+        );
+
+        let served_by = database
+            .read(|pool| async move {
+                match database_name(pool).as_deref() {
+                    Some("replica") => Err(sqlx::Error::PoolTimedOut),
+                    _ => Ok(database_name(pool)),
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(served_by.as_deref(), Some("primary"));
+
+        // The unreachable replica stays out of routing until a health check
+        assert_eq!(
+            database_name(database.read_pool()).as_deref(),
+            Some("primary")
+        );
+    }
 }
@@ -45,6 +45,8 @@ pub struct TransactionModel {
     pub status: String,
     pub signature: Option<Vec<u8>>,
     pub metadata: serde_json::Value,
+    /// `TransactionVisibility::as_str` of who may see the transaction
+    pub visibility: String,
     pub created_at: DateTime<Utc>,
     pub processed_at: Option<DateTime<Utc>>,
 }
@@ -92,7 +94,7 @@ pub struct ConfigModel {
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ConversionRecord {
     pub id: Uuid,
-    pub account_id: Uuid,
+    pub account_id: String,
     pub from_currency: String,
     pub to_currency: String,
     pub original_amount: i64,
@@ -1,6 +1,7 @@
 //! Account repository for database operations
 
 use crate::database::models::AccountModel;
+use crate::database::Database;
 use crate::errors::AstorError;
 use chrono::Utc;
 use sqlx::PgPool;
@@ -8,11 +9,24 @@ use uuid::Uuid;
 
 pub struct AccountRepository {
     pool: PgPool,
+    /// Routes read-only queries to a replica when one is configured
+    database: Database,
 }
 
 impl AccountRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            database: Database::from_pools(pool.clone(), None),
+            pool,
+        }
+    }
+
+    /// Write to the primary and read through the database's read routing
+    pub fn from_database(database: &Database) -> Self {
+        Self {
+            pool: database.write_pool().clone(),
+            database: database.clone(),
+        }
     }
 
     /// Create a new account
@@ -44,9 +58,13 @@ impl AccountRepository {
 
     /// Get account by ID
     pub async fn get_account(&self, account_id: Uuid) -> Result<AccountModel, AstorError> {
-        let account = sqlx::query_as::<_, AccountModel>("SELECT * FROM accounts WHERE id = $1")
-            .bind(account_id)
-            .fetch_one(&self.pool)
+        let account = self
+            .database
+            .read(|pool| {
+                sqlx::query_as::<_, AccountModel>("SELECT * FROM accounts WHERE id = $1")
+                    .bind(account_id)
+                    .fetch_one(pool)
+            })
             .await
             .map_err(|e| match e {
                 sqlx::Error::RowNotFound => AstorError::AccountNotFound(account_id.to_string()),
@@ -111,26 +129,31 @@ impl AccountRepository {
         limit: i64,
         offset: i64,
     ) -> Result<Vec<AccountModel>, AstorError> {
-        let accounts = sqlx::query_as::<_, AccountModel>(
-            r#"
-            SELECT * FROM accounts 
-            ORDER BY created_at DESC 
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| AstorError::DatabaseError(format!("Failed to list accounts: {}", e)))?;
+        let accounts = self
+            .database
+            .read(|pool| {
+                sqlx::query_as::<_, AccountModel>(
+                    r#"
+                    SELECT * FROM accounts 
+                    ORDER BY created_at DESC 
+                    LIMIT $1 OFFSET $2
+                    "#,
+                )
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| AstorError::DatabaseError(format!("Failed to list accounts: {}", e)))?;
 
         Ok(accounts)
     }
 
     /// Get total account count
     pub async fn count_accounts(&self) -> Result<i64, AstorError> {
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM accounts")
-            .fetch_one(&self.pool)
+        let count: (i64,) = self
+            .database
+            .read(|pool| sqlx::query_as("SELECT COUNT(*) FROM accounts").fetch_one(pool))
             .await
             .map_err(|e| AstorError::DatabaseError(format!("Failed to count accounts: {}", e)))?;
 
@@ -0,0 +1,125 @@
+//! Audit trail of currency conversions
+//!
+//! Every completed conversion is written as a `ConversionRecord` so FX
+//! activity can be reviewed per account over a date range.
+
+use crate::database::models::ConversionRecord;
+use crate::errors::AstorError;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+
+/// Where conversion records are written and queried
+#[async_trait::async_trait]
+pub trait ConversionRecordStore: Send + Sync {
+    async fn record_conversion(&self, record: &ConversionRecord) -> Result<(), AstorError>;
+
+    /// Conversions by `account_id` created in `[from, to)`, oldest first
+    async fn conversions_for_account(
+        &self,
+        account_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ConversionRecord>, AstorError>;
+}
+
+#[derive(Clone)]
+pub struct ConversionRepository {
+    pool: PgPool,
+}
+
+impl ConversionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl ConversionRecordStore for ConversionRepository {
+    async fn record_conversion(&self, record: &ConversionRecord) -> Result<(), AstorError> {
+        sqlx::query(
+            r#"
+            INSERT INTO conversion_records (id, account_id, from_currency, to_currency, original_amount, converted_amount, exchange_rate, fees, slippage, provider, status, created_at, completed_at, metadata)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(record.id)
+        .bind(&record.account_id)
+        .bind(&record.from_currency)
+        .bind(&record.to_currency)
+        .bind(record.original_amount)
+        .bind(record.converted_amount)
+        .bind(record.exchange_rate)
+        .bind(record.fees)
+        .bind(record.slippage)
+        .bind(&record.provider)
+        .bind(&record.status)
+        .bind(record.created_at)
+        .bind(record.completed_at)
+        .bind(&record.metadata)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn conversions_for_account(
+        &self,
+        account_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ConversionRecord>, AstorError> {
+        sqlx::query_as::<_, ConversionRecord>(
+            "SELECT * FROM conversion_records WHERE account_id = $1 AND created_at >= $2 AND created_at < $3 ORDER BY created_at ASC",
+        )
+        .bind(account_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AstorError::DatabaseError(e.to_string()))
+    }
+}
+
+/// Store kept in memory, for tests and ephemeral nodes
+#[derive(Default)]
+pub struct MemoryConversionStore {
+    records: RwLock<Vec<ConversionRecord>>,
+}
+
+impl MemoryConversionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl ConversionRecordStore for MemoryConversionStore {
+    async fn record_conversion(&self, record: &ConversionRecord) -> Result<(), AstorError> {
+        self.records.write().await.push(record.clone());
+        Ok(())
+    }
+
+    async fn conversions_for_account(
+        &self,
+        account_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ConversionRecord>, AstorError> {
+        let mut records: Vec<ConversionRecord> = self
+            .records
+            .read()
+            .await
+            .iter()
+            .filter(|record| {
+                record.account_id == account_id
+                    && record.created_at >= from
+                    && record.created_at < to
+            })
+            .cloned()
+            .collect();
+        records.sort_by_key(|record| record.created_at);
+        Ok(records)
+    }
+}
@@ -3,6 +3,7 @@
 use crate::database::models::LedgerEntryModel;
 use crate::errors::AstorError;
 use chrono::Utc;
+use futures::Stream;
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -87,6 +88,57 @@ impl LedgerRepository {
         Ok(entries)
     }
 
+    /// Get up to `limit` ledger entries above a block height
+    pub async fn get_entries_after(
+        &self,
+        block_height: i64,
+        limit: i64,
+    ) -> Result<Vec<LedgerEntryModel>, AstorError> {
+        let entries = sqlx::query_as::<_, LedgerEntryModel>(
+            r#"
+            SELECT * FROM ledger_entries
+            WHERE block_height > $1
+            ORDER BY block_height ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(block_height)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AstorError::DatabaseError(format!("Failed to get ledger entries: {}", e)))?;
+
+        Ok(entries)
+    }
+
+    /// Stream the ledger in insertion order, `batch_size` entries at a time
+    ///
+    /// The next batch is only queried once the consumer polls for it, so a
+    /// slow consumer holds at most one batch in memory.
+    pub fn stream_entries(
+        &self,
+        batch_size: i64,
+    ) -> impl Stream<Item = Result<Vec<LedgerEntryModel>, AstorError>> + '_ {
+        futures::stream::try_unfold(Some(0), move |after_height| async move {
+            let Some(after_height) = after_height else {
+                return Ok(None);
+            };
+
+            let batch = self.get_entries_after(after_height, batch_size).await?;
+            if batch.is_empty() {
+                return Ok(None);
+            }
+
+            // A short batch means the end of the ledger was reached
+            let next = if (batch.len() as i64) < batch_size {
+                None
+            } else {
+                batch.last().map(|entry| entry.block_height)
+            };
+            Ok(Some((batch, next)))
+        })
+    }
+
     /// Get last ledger entry
     pub async fn get_last_entry(&self) -> Result<Option<LedgerEntryModel>, AstorError> {
         let entry = sqlx::query_as::<_, LedgerEntryModel>(
@@ -3,11 +3,17 @@
 pub mod account_repository;
 pub mod admin_repository;
 pub mod audit_repository;
+pub mod conversion_repository;
 pub mod ledger_repository;
 pub mod transaction_repository;
+pub mod write_batcher;
 
 pub use account_repository::AccountRepository;
 pub use admin_repository::AdminRepository;
 pub use audit_repository::AuditRepository;
+pub use conversion_repository::{
+    ConversionRecordStore, ConversionRepository, MemoryConversionStore,
+};
 pub use ledger_repository::LedgerRepository;
 pub use transaction_repository::TransactionRepository;
+pub use write_batcher::{BatchConfig, BatchSink, TransactionBatcher, WriteBatcher};
@@ -1,18 +1,38 @@
 use crate::database::models::TransactionRecord;
+use crate::database::repositories::write_batcher::BatchSink;
+use crate::database::Database;
 use crate::errors::AstorError;
+use crate::security::Role;
+use crate::transactions::TransactionVisibility;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use sqlx::{PgPool, Row};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
 use uuid::Uuid;
 
+/// Rows per INSERT statement, keeping bind parameters under Postgres' limit
+const INSERT_CHUNK_SIZE: usize = 1000;
+
 #[derive(Clone)]
 pub struct TransactionRepository {
     pool: PgPool,
+    /// Routes read-only queries to a replica when one is configured
+    database: Database,
 }
 
 impl TransactionRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            database: Database::from_pools(pool.clone(), None),
+            pool,
+        }
+    }
+
+    /// Write to the primary and read through the database's read routing
+    pub fn from_database(database: &Database) -> Self {
+        Self {
+            pool: database.write_pool().clone(),
+            database: database.clone(),
+        }
     }
 
     pub async fn create_transaction(
@@ -21,8 +41,8 @@ impl TransactionRepository {
     ) -> Result<(), AstorError> {
         sqlx::query!(
             r#"
-            INSERT INTO transactions (id, from_account, to_account, amount, currency, transaction_type, status, metadata, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO transactions (id, from_account, to_account, amount, currency, transaction_type, status, metadata, visibility, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             "#,
             transaction.id,
             transaction.from_account,
@@ -32,6 +52,7 @@ impl TransactionRepository {
             transaction.transaction_type,
             transaction.status,
             transaction.metadata,
+            transaction.visibility,
             transaction.created_at
         )
         .execute(&self.pool)
@@ -41,9 +62,72 @@ impl TransactionRepository {
         Ok(())
     }
 
-    pub async fn get_transaction(&self, id: Uuid) -> Result<Option<TransactionRecord>, AstorError> {
-        let row = sqlx::query!("SELECT * FROM transactions WHERE id = $1", id)
-            .fetch_optional(&self.pool)
+    /// Insert many transactions in one database transaction
+    ///
+    /// Either every row is written or, on any error, none are.
+    pub async fn create_transactions(
+        &self,
+        transactions: &[TransactionRecord],
+    ) -> Result<(), AstorError> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        let mut db_transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
+
+        for chunk in transactions.chunks(INSERT_CHUNK_SIZE) {
+            let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO transactions (id, from_account, to_account, amount, currency, transaction_type, status, metadata, visibility, created_at) ",
+            );
+            query.push_values(chunk, |mut row, transaction| {
+                row.push_bind(transaction.id)
+                    .push_bind(transaction.from_account)
+                    .push_bind(transaction.to_account)
+                    .push_bind(transaction.amount)
+                    .push_bind(transaction.currency.clone())
+                    .push_bind(transaction.transaction_type.clone())
+                    .push_bind(transaction.status.clone())
+                    .push_bind(transaction.metadata.clone())
+                    .push_bind(transaction.visibility.clone())
+                    .push_bind(transaction.created_at);
+            });
+
+            query
+                .build()
+                .execute(&mut *db_transaction)
+                .await
+                .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
+        }
+
+        db_transaction
+            .commit()
+            .await
+            .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Transaction `id`, if `role` may see it
+    pub async fn get_transaction(
+        &self,
+        id: Uuid,
+        role: &Role,
+    ) -> Result<Option<TransactionRecord>, AstorError> {
+        let visible = Self::visible_to(role);
+        let row = self
+            .database
+            .read(|pool| {
+                sqlx::query!(
+                    "SELECT * FROM transactions WHERE id = $1 AND visibility = ANY($2)",
+                    id,
+                    &visible
+                )
+                .fetch_optional(pool)
+            })
             .await
             .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
 
@@ -57,6 +141,7 @@ impl TransactionRepository {
                 transaction_type: row.transaction_type,
                 status: row.status,
                 metadata: row.metadata,
+                visibility: row.visibility,
                 created_at: row.created_at,
                 updated_at: row.updated_at,
             }))
@@ -65,26 +150,34 @@ impl TransactionRepository {
         }
     }
 
+    /// Transactions involving `account_id` that `role` may see, newest first
     pub async fn get_transactions_by_account(
         &self,
         account_id: Uuid,
+        role: &Role,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<TransactionRecord>, AstorError> {
-        let rows = sqlx::query!(
-            r#"
-            SELECT * FROM transactions 
-            WHERE from_account = $1 OR to_account = $1
-            ORDER BY created_at DESC
-            LIMIT $2 OFFSET $3
-            "#,
-            account_id,
-            limit,
-            offset
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
+        let visible = Self::visible_to(role);
+        let rows = self
+            .database
+            .read(|pool| {
+                sqlx::query!(
+                    r#"
+                    SELECT * FROM transactions 
+                    WHERE (from_account = $1 OR to_account = $1) AND visibility = ANY($2)
+                    ORDER BY created_at DESC
+                    LIMIT $3 OFFSET $4
+                    "#,
+                    account_id,
+                    &visible,
+                    limit,
+                    offset
+                )
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
 
         let transactions = rows
             .into_iter()
@@ -97,6 +190,7 @@ impl TransactionRepository {
                 transaction_type: row.transaction_type,
                 status: row.status,
                 metadata: row.metadata,
+                visibility: row.visibility,
                 created_at: row.created_at,
                 updated_at: row.updated_at,
             })
@@ -105,6 +199,14 @@ impl TransactionRepository {
         Ok(transactions)
     }
 
+    /// Stored visibility names `role` may see
+    fn visible_to(role: &Role) -> Vec<String> {
+        TransactionVisibility::visible_to(role)
+            .iter()
+            .map(|visibility| visibility.as_str().to_string())
+            .collect()
+    }
+
     pub async fn update_transaction_status(
         &self,
         id: Uuid,
@@ -127,19 +229,30 @@ impl TransactionRepository {
         start_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
     ) -> Result<Decimal, AstorError> {
-        let row = sqlx::query!(
-            r#"
-            SELECT COALESCE(SUM(amount), 0) as total_volume
-            FROM transactions 
-            WHERE created_at BETWEEN $1 AND $2 AND status = 'completed'
-            "#,
-            start_date,
-            end_date
-        )
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
+        let row = self
+            .database
+            .read(|pool| {
+                sqlx::query!(
+                    r#"
+                    SELECT COALESCE(SUM(amount), 0) as total_volume
+                    FROM transactions 
+                    WHERE created_at BETWEEN $1 AND $2 AND status = 'completed'
+                    "#,
+                    start_date,
+                    end_date
+                )
+                .fetch_one(pool)
+            })
+            .await
+            .map_err(|e| AstorError::DatabaseError(e.to_string()))?;
 
         Ok(row.total_volume.unwrap_or_default())
     }
 }
+
+#[async_trait::async_trait]
+impl BatchSink<TransactionRecord> for TransactionRepository {
+    async fn write_batch(&self, records: &[TransactionRecord]) -> Result<(), AstorError> {
+        self.create_transactions(records).await
+    }
+}
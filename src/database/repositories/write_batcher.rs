@@ -0,0 +1,333 @@
+//! Buffered, batched database writes
+//!
+//! Records are buffered in memory and written in a single multi-row insert
+//! inside one database transaction, either when the buffer reaches the
+//! configured size or when the flush interval elapses. Anything still
+//! buffered is written on shutdown; records buffered when the process dies
+//! are lost, but a flushed batch is always written completely or not at all.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+
+use crate::config::DatabaseConfig;
+use crate::database::models::TransactionRecord;
+use crate::errors::AstorError;
+
+/// Destination that writes a batch atomically
+#[async_trait::async_trait]
+pub trait BatchSink<T>: Send + Sync {
+    async fn write_batch(&self, records: &[T]) -> Result<(), AstorError>;
+}
+
+/// Batched writer for transaction records
+pub type TransactionBatcher = WriteBatcher<TransactionRecord>;
+
+/// When buffered records are flushed
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// Flush as soon as this many records are buffered
+    pub max_batch_size: usize,
+    /// Flush whatever is buffered at least this often
+    pub flush_interval: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 500,
+            flush_interval: Duration::from_millis(100),
+        }
+    }
+}
+
+impl From<&DatabaseConfig> for BatchConfig {
+    fn from(config: &DatabaseConfig) -> Self {
+        Self {
+            max_batch_size: config.write_batch_size.max(1),
+            flush_interval: Duration::from_millis(config.write_flush_interval.max(1)),
+        }
+    }
+}
+
+/// Buffers records and writes them to a sink in batches
+pub struct WriteBatcher<T> {
+    sink: Arc<dyn BatchSink<T>>,
+    buffer: Arc<Mutex<Vec<T>>>,
+    // Serializes flushes so batches reach the sink in enqueue order
+    flush_lock: Arc<Mutex<()>>,
+    config: BatchConfig,
+    flush_task: Option<JoinHandle<()>>,
+    // Asks the periodic flush to stop once any flush in progress is written
+    stop: Arc<Notify>,
+}
+
+impl<T: Send + 'static> WriteBatcher<T> {
+    pub fn new(sink: Arc<dyn BatchSink<T>>, config: BatchConfig) -> Self {
+        Self {
+            sink,
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            flush_lock: Arc::new(Mutex::new(())),
+            config,
+            flush_task: None,
+            stop: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Start flushing on the configured interval
+    pub fn start(&mut self) {
+        if self.flush_task.is_some() {
+            return;
+        }
+
+        let sink = self.sink.clone();
+        let buffer = self.buffer.clone();
+        let flush_lock = self.flush_lock.clone();
+        let flush_interval = self.config.flush_interval;
+        let stop = self.stop.clone();
+
+        self.flush_task = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(flush_interval);
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = stop.notified() => break,
+                    _ = interval.tick() => {}
+                }
+                if let Err(e) = flush_buffer(sink.as_ref(), &buffer, &flush_lock).await {
+                    tracing::warn!("Periodic batch flush failed: {}", e);
+                }
+            }
+        }));
+    }
+
+    /// Buffer a record, flushing if the batch is full
+    pub async fn enqueue(&self, record: T) -> Result<(), AstorError> {
+        let pending = {
+            let mut buffer = self.buffer.lock().await;
+            buffer.push(record);
+            buffer.len()
+        };
+
+        if pending >= self.config.max_batch_size {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Write everything buffered as one batch, returning how many were written
+    pub async fn flush(&self) -> Result<usize, AstorError> {
+        flush_buffer(self.sink.as_ref(), &self.buffer, &self.flush_lock).await
+    }
+
+    /// Number of records buffered but not yet written
+    pub async fn pending(&self) -> usize {
+        self.buffer.lock().await.len()
+    }
+
+    /// Stop the periodic flush and write anything still buffered
+    ///
+    /// A periodic flush already under way is allowed to finish, so the batch
+    /// it took is written rather than dropped.
+    pub async fn shutdown(mut self) -> Result<usize, AstorError> {
+        if let Some(task) = self.flush_task.take() {
+            self.stop.notify_one();
+            if let Err(e) = task.await {
+                tracing::warn!("Periodic batch flush ended abnormally: {}", e);
+            }
+        }
+        self.flush().await
+    }
+}
+
+impl<T> Drop for WriteBatcher<T> {
+    fn drop(&mut self) {
+        // Not aborted: the task may be writing a batch it took from the buffer
+        if self.flush_task.take().is_some() {
+            self.stop.notify_one();
+        }
+    }
+}
+
+/// Take the buffered records and write them; on failure they are put back
+/// ahead of anything enqueued in the meantime so nothing is dropped
+async fn flush_buffer<T>(
+    sink: &dyn BatchSink<T>,
+    buffer: &Mutex<Vec<T>>,
+    flush_lock: &Mutex<()>,
+) -> Result<usize, AstorError> {
+    let _flushing = flush_lock.lock().await;
+
+    let batch = std::mem::take(&mut *buffer.lock().await);
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    match sink.write_batch(&batch).await {
+        Ok(()) => Ok(batch.len()),
+        Err(e) => {
+            let mut buffer = buffer.lock().await;
+            let newer = std::mem::replace(&mut *buffer, batch);
+            buffer.extend(newer);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Stands in for the database: each successful batch is committed whole
+    #[derive(Default)]
+    struct MemorySink {
+        committed: std::sync::Mutex<Vec<Vec<u32>>>,
+        fail: AtomicBool,
+    }
+
+    impl MemorySink {
+        fn committed_records(&self) -> Vec<u32> {
+            self.committed
+                .lock()
+                .unwrap()
+                .iter()
+                .flatten()
+                .copied()
+                .collect()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BatchSink<u32> for MemorySink {
+        async fn write_batch(&self, records: &[u32]) -> Result<(), AstorError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(AstorError::DatabaseError("connection reset".to_string()));
+            }
+            self.committed.lock().unwrap().push(records.to_vec());
+            Ok(())
+        }
+    }
+
+    fn batcher(sink: &Arc<MemorySink>, max_batch_size: usize) -> WriteBatcher<u32> {
+        WriteBatcher::new(
+            sink.clone(),
+            BatchConfig {
+                max_batch_size,
+                flush_interval: Duration::from_secs(3600),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_flush_writes_buffered_records_as_one_batch() {
+        let sink = Arc::new(MemorySink::default());
+        let batcher = batcher(&sink, 10);
+
+        for id in 1..=4 {
+            batcher.enqueue(id).await.unwrap();
+        }
+        assert!(sink.committed_records().is_empty());
+
+        assert_eq!(batcher.flush().await.unwrap(), 4);
+        assert_eq!(*sink.committed.lock().unwrap(), vec![vec![1, 2, 3, 4]]);
+        assert_eq!(batcher.pending().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_writes_nothing_and_keeps_records() {
+        let sink = Arc::new(MemorySink::default());
+        let batcher = batcher(&sink, 10);
+        for id in 1..=3 {
+            batcher.enqueue(id).await.unwrap();
+        }
+
+        sink.fail.store(true, Ordering::SeqCst);
+        assert!(batcher.flush().await.is_err());
+        assert!(sink.committed_records().is_empty());
+
+        batcher.enqueue(4).await.unwrap();
+        sink.fail.store(false, Ordering::SeqCst);
+        batcher.flush().await.unwrap();
+        assert_eq!(sink.committed_records(), vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_crash_before_flush_loses_only_unflushed_records() {
+        let sink = Arc::new(MemorySink::default());
+        let batcher = batcher(&sink, 3);
+
+        for id in 1..=5 {
+            batcher.enqueue(id).await.unwrap();
+        }
+        assert_eq!(batcher.pending().await, 2);
+
+        // Simulate the process dying without a clean shutdown
+        drop(batcher);
+
+        assert_eq!(sink.committed_records(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_remaining_records() {
+        let sink = Arc::new(MemorySink::default());
+        let mut batcher = batcher(&sink, 3);
+        batcher.start();
+
+        for id in 1..=5 {
+            batcher.enqueue(id).await.unwrap();
+        }
+        assert_eq!(batcher.shutdown().await.unwrap(), 2);
+
+        assert_eq!(sink.committed_records(), vec![1, 2, 3, 4, 5]);
+    }
+
+    /// Holds each batch until released, so a flush can be caught mid-write
+    struct SlowSink {
+        inner: MemorySink,
+        release: Notify,
+    }
+
+    #[async_trait::async_trait]
+    impl BatchSink<u32> for SlowSink {
+        async fn write_batch(&self, records: &[u32]) -> Result<(), AstorError> {
+            self.release.notified().await;
+            self.inner.write_batch(records).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_during_periodic_flush_keeps_the_taken_batch() {
+        let sink = Arc::new(SlowSink {
+            inner: MemorySink::default(),
+            release: Notify::new(),
+        });
+        let mut batcher = WriteBatcher::new(
+            sink.clone(),
+            BatchConfig {
+                max_batch_size: 10,
+                flush_interval: Duration::from_millis(10),
+            },
+        );
+        for id in 1..=3 {
+            batcher.enqueue(id).await.unwrap();
+        }
+        batcher.start();
+
+        // Let the periodic flush take the batch and block in the sink
+        while batcher.pending().await > 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        batcher.enqueue(4).await.unwrap();
+
+        let shutdown = tokio::spawn(batcher.shutdown());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        sink.release.notify_one();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        sink.release.notify_one();
+
+        assert_eq!(shutdown.await.unwrap().unwrap(), 1);
+        assert_eq!(sink.inner.committed_records(), vec![1, 2, 3, 4]);
+    }
+}
@@ -16,6 +16,13 @@ pub enum AstorError {
     #[error("Insufficient funds for transaction")]
     InsufficientFunds,
 
+    #[error("Insufficient available balance: requested {requested}, available {available} of ledger balance {ledger_balance}")]
+    InsufficientAvailableBalance {
+        requested: u64,
+        available: u64,
+        ledger_balance: u64,
+    },
+
     #[error("Invalid signature")]
     InvalidSignature,
 
@@ -37,6 +44,9 @@ pub enum AstorError {
     #[error("Commercial banking error: {0}")]
     CommercialBankingError(String),
 
+    #[error("Banking network error: {0}")]
+    BankingNetworkError(String),
+
     #[error("Payment processing error: {0}")]
     PaymentError(String),
 
@@ -69,4 +79,68 @@ pub enum AstorError {
 
     #[error("Database error: {0}")]
     DatabaseError(String),
+
+    #[error("Configuration error: {0}")]
+    ConfigurationError(String),
+
+    #[error("Invalid operation: {0}")]
+    InvalidOperation(String),
+
+    #[error("Contract compilation failed at line {line}, column {column}: {message}")]
+    ContractCompileError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("Node is syncing with the network; try again once synced")]
+    NodeSyncing,
+
+    #[error("Transaction queue is full ({0} pending)")]
+    TransactionQueueFull(usize),
+
+    #[error("Service is at capacity; retry after {retry_after_secs}s")]
+    ServiceBusy { retry_after_secs: u64 },
+
+    #[error("Conversion limit exceeded: {0}")]
+    ConversionLimitExceeded(String),
+
+    #[error("Startup aborted: dependency '{dependency}' is unhealthy: {reason}")]
+    DependencyUnhealthy { dependency: String, reason: String },
+
+    #[error("Unsupported schema version {found}; this build reads up to version {supported}")]
+    UnsupportedSchemaVersion { found: u32, supported: u32 },
+
+    #[error("Fee {offered} is below the current base fee of {required}")]
+    FeeTooLow { offered: u64, required: u64 },
+
+    #[error("Merchant {merchant_id} does not accept {currency}")]
+    CurrencyNotAccepted {
+        merchant_id: String,
+        currency: String,
+    },
+
+    #[error("Transaction amount {amount} exceeds the limit of {limit} for this account")]
+    TransactionLimitExceeded { amount: u64, limit: u64 },
+
+    #[error("Account {0} is dormant and must be re-verified before use")]
+    AccountDormant(String),
+
+    #[error("Transfer nonce {received} does not match the expected nonce {expected}")]
+    NonceMismatch { expected: u64, received: u64 },
+
+    #[error("Account {0} is frozen")]
+    AccountFrozen(String),
+
+    #[error("Account {0} is closed")]
+    AccountClosed(String),
+
+    #[error("Concurrent modification: {0}")]
+    ConcurrentModification(String),
+
+    #[error("Incompatible peer {peer_id}: {reason}")]
+    IncompatiblePeer { peer_id: String, reason: String },
+
+    #[error("Unsupported signature scheme: {0}")]
+    UnsupportedSignatureScheme(String),
 }
@@ -0,0 +1,153 @@
+//! Shared percentage fee arithmetic
+//!
+//! Payment, conversion and bridge fees are all computed here so that a given
+//! amount and rate yield the same fee in every module, and fee ledgers
+//! reconcile to the unit. Rates are fixed to nine decimal places and the fee
+//! is computed in integers, so the result never depends on floating-point
+//! representation.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Rates are applied with this many fractional units per whole
+const RATE_SCALE: u128 = 1_000_000_000;
+
+/// How a fractional fee is rounded to whole units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeeRounding {
+    /// Halves round up
+    #[default]
+    HalfUp,
+    /// Halves round to the even unit (banker's rounding)
+    HalfEven,
+    /// Always in the payer's favour
+    Down,
+    /// Always in the collector's favour
+    Up,
+}
+
+/// Computes percentage fees under one rounding policy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeCalculator {
+    rounding: FeeRounding,
+}
+
+impl FeeCalculator {
+    pub const fn new(rounding: FeeRounding) -> Self {
+        Self { rounding }
+    }
+
+    /// The calculator every module should share, per the fee configuration
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.fees.rounding)
+    }
+
+    pub fn rounding(&self) -> FeeRounding {
+        self.rounding
+    }
+
+    /// Fee of `rate` (a fraction, e.g. `0.001` for 0.1%) on `amount`
+    ///
+    /// Negative or non-finite rates charge nothing.
+    pub fn percentage_fee(&self, amount: u64, rate: f64) -> u64 {
+        if !rate.is_finite() || rate <= 0.0 {
+            return 0;
+        }
+
+        let scaled_rate = (rate * RATE_SCALE as f64).round() as u128;
+        let product = amount as u128 * scaled_rate;
+        let (whole, remainder) = (product / RATE_SCALE, product % RATE_SCALE);
+        let half = RATE_SCALE / 2;
+
+        let round_up = match self.rounding {
+            FeeRounding::HalfUp => remainder >= half,
+            FeeRounding::HalfEven => remainder > half || (remainder == half && whole % 2 == 1),
+            FeeRounding::Down => false,
+            FeeRounding::Up => remainder > 0,
+        };
+        let fee = if round_up { whole + 1 } else { whole };
+        fee.min(u64::MAX as u128) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{ConversionService, ExchangeRate};
+    use crate::interoperability::InteroperabilityManager;
+    use crate::payment_processing::FeeStructure;
+
+    #[tokio::test]
+    async fn test_same_inputs_yield_same_fee_in_every_module() {
+        // 0.1% of 2,500 is exactly 2.5 units, where rounding policies differ
+        let amount = 2_500;
+        let rate = 0.001;
+
+        for (rounding, expected) in [
+            (FeeRounding::HalfUp, 3),
+            (FeeRounding::HalfEven, 2),
+            (FeeRounding::Down, 2),
+            (FeeRounding::Up, 3),
+        ] {
+            let calculator = FeeCalculator::new(rounding);
+            assert_eq!(calculator.percentage_fee(amount, rate), expected);
+
+            let payment_fee = FeeStructure {
+                transaction_fee_percent: rate,
+                fixed_fee: 0,
+                monthly_fee: 0,
+            }
+            .transaction_fee(amount, &calculator);
+
+            let mut conversion = ConversionService::new();
+            conversion.set_fee_calculator(calculator);
+            conversion.set_conversion_fee("USD".to_string(), rate);
+            conversion.set_network_fee("USD".to_string(), 0);
+            conversion.update_exchange_rate(ExchangeRate {
+                from_currency: "ASTOR".to_string(),
+                to_currency: "USD".to_string(),
+                rate: 1.0,
+                bid: 1.0,
+                ask: 1.0,
+                timestamp: chrono::Utc::now(),
+                source: "test".to_string(),
+                volatility: 0.0,
+                daily_change: 0.0,
+            });
+            let conversion_fee = conversion
+                .quote_conversion(amount, "ASTOR", "USD")
+                .unwrap()
+                .fees
+                .service_fee;
+
+            let mut bridges = InteroperabilityManager::new();
+            bridges.set_fee_calculator(calculator);
+            let bridge_id = bridges
+                .create_bridge(
+                    "astor-eth".to_string(),
+                    "astor".to_string(),
+                    "ethereum".to_string(),
+                    "0xbridge".to_string(),
+                    vec!["validator-1".to_string()],
+                )
+                .await
+                .unwrap();
+            let tx_id = bridges
+                .initiate_cross_chain_transfer(
+                    bridge_id,
+                    "alice".to_string(),
+                    "0xalice".to_string(),
+                    amount,
+                    "0xsource".to_string(),
+                )
+                .await
+                .unwrap();
+            let bridge_fee = bridges.get_transaction(tx_id).unwrap().fee;
+
+            assert_eq!(payment_fee, expected, "{:?}", rounding);
+            assert_eq!(conversion_fee, expected, "{:?}", rounding);
+            assert_eq!(bridge_fee, expected, "{:?}", rounding);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Dynamic transaction base fee
+//!
+//! The base fee follows recent block fullness in the style of EIP-1559: a
+//! block fuller than the target raises the fee for the next block, an emptier
+//! one lowers it, by at most `1 / max_change_denominator` per block. Transfers
+//! must offer at least the current base fee, so spam gets more expensive
+//! during congestion while an idle network drifts back to `min_base_fee`.
+
+use serde::{Deserialize, Serialize};
+
+/// Base fee adjustment parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeMarketConfig {
+    /// Transactions per block at which the base fee holds steady
+    pub target_block_size: usize,
+    /// Largest block the network produces; fuller reports are clamped to this
+    pub max_block_size: usize,
+    /// Bounds each adjustment to `base_fee / max_change_denominator`
+    pub max_change_denominator: u64,
+    /// The base fee never drops below this
+    pub min_base_fee: u64,
+}
+
+impl Default for FeeMarketConfig {
+    fn default() -> Self {
+        Self {
+            target_block_size: 500,
+            max_block_size: 1_000,
+            max_change_denominator: 8,
+            min_base_fee: 0,
+        }
+    }
+}
+
+/// Tracks the protocol base fee from one block to the next
+#[derive(Debug, Clone)]
+pub struct FeeMarket {
+    config: FeeMarketConfig,
+    base_fee: u64,
+}
+
+impl FeeMarket {
+    pub fn new(config: FeeMarketConfig) -> Self {
+        Self {
+            base_fee: config.min_base_fee,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &FeeMarketConfig {
+        &self.config
+    }
+
+    /// Replace the adjustment parameters, keeping the current base fee
+    /// within the new minimum
+    pub fn set_config(&mut self, config: FeeMarketConfig) {
+        self.base_fee = self.base_fee.max(config.min_base_fee);
+        self.config = config;
+    }
+
+    /// Minimum fee a transaction must offer to be accepted
+    pub fn current_base_fee(&self) -> u64 {
+        self.base_fee
+    }
+
+    /// Adjust the base fee for a block holding `transaction_count` transactions
+    ///
+    /// Any block off target moves the fee by at least one unit, so a low fee
+    /// can still rise and integer rounding cannot pin it above the minimum.
+    /// Returns the new base fee.
+    pub fn record_block(&mut self, transaction_count: usize) -> u64 {
+        let target = self.config.target_block_size.max(1) as u128;
+        let used = transaction_count.min(self.config.max_block_size) as u128;
+        let denominator = self.config.max_change_denominator.max(1) as u128;
+        let base_fee = self.base_fee as u128;
+
+        self.base_fee = if used > target {
+            let delta = (base_fee * (used - target) / target / denominator).max(1);
+            u64::try_from(base_fee + delta).unwrap_or(u64::MAX)
+        } else if used < target {
+            let delta = (base_fee * (target - used) / target / denominator).max(1);
+            (base_fee.saturating_sub(delta) as u64).max(self.config.min_base_fee)
+        } else {
+            self.base_fee
+        };
+
+        tracing::debug!(
+            "Block with {} transactions; base fee now {}",
+            transaction_count,
+            self.base_fee
+        );
+        self.base_fee
+    }
+}
+
+impl Default for FeeMarket {
+    fn default() -> Self {
+        Self::new(FeeMarketConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market() -> FeeMarket {
+        FeeMarket::new(FeeMarketConfig {
+            target_block_size: 50,
+            max_block_size: 100,
+            max_change_denominator: 8,
+            min_base_fee: 10,
+        })
+    }
+
+    #[test]
+    fn test_full_blocks_raise_and_empty_blocks_lower_base_fee() {
+        let mut market = market();
+        assert_eq!(market.current_base_fee(), 10);
+
+        let mut previous = market.current_base_fee();
+        for _ in 0..20 {
+            let fee = market.record_block(100);
+            assert!(fee > previous);
+            previous = fee;
+        }
+        // At most 12.5% per block
+        assert!(previous <= (10.0 * 1.125f64.powi(20)).ceil() as u64);
+
+        for _ in 0..5 {
+            let fee = market.record_block(0);
+            assert!(fee < previous);
+            previous = fee;
+        }
+
+        for _ in 0..100 {
+            market.record_block(0);
+        }
+        assert_eq!(market.current_base_fee(), 10);
+    }
+
+    #[test]
+    fn test_target_block_keeps_base_fee_and_oversized_blocks_are_clamped() {
+        let mut market = market();
+        market.record_block(100);
+        let fee = market.current_base_fee();
+
+        assert_eq!(market.record_block(50), fee);
+
+        let mut clamped = market.clone();
+        assert_eq!(market.record_block(100), clamped.record_block(10_000));
+    }
+}
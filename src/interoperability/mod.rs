@@ -1,7 +1,10 @@
 //! Cross-chain interoperability for Astor Currency
 //! Enables bridging with other blockchain networks
 
+use crate::central_bank::DEFAULT_CURRENCY;
 use crate::errors::AstorResult;
+use crate::fee_calculator::FeeCalculator;
+use crate::ledger::{FeeCollector, FeeSource};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -18,11 +21,36 @@ pub struct CrossChainBridge {
     pub target_chain: String,
     pub bridge_contract: String,
     pub validators: Vec<String>,
+    /// Confirmations required for any transfer over this bridge
     pub min_confirmations: u32,
+    /// Stricter requirements for larger transfers
+    #[serde(default)]
+    pub confirmation_tiers: Vec<ConfirmationTier>,
     pub fee_rate: f64,
     pub active: bool,
 }
 
+/// Confirmation depth required for transfers of at least `min_amount`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmationTier {
+    pub min_amount: u64,
+    pub confirmations: u32,
+}
+
+impl CrossChainBridge {
+    /// Confirmations a transfer of `amount` needs before it is executed
+    ///
+    /// The highest requirement among the bridge minimum and every tier the
+    /// amount reaches applies, so a tier can never lower the bar.
+    pub fn required_confirmations(&self, amount: u64) -> u32 {
+        self.confirmation_tiers
+            .iter()
+            .filter(|tier| amount >= tier.min_amount)
+            .map(|tier| tier.confirmations)
+            .fold(self.min_confirmations, u32::max)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrossChainTransaction {
     pub id: Uuid,
@@ -32,13 +60,16 @@ pub struct CrossChainTransaction {
     pub from_address: String,
     pub to_address: String,
     pub amount: u64,
+    /// Bridge fee withheld from the amount
+    #[serde(default)]
+    pub fee: u64,
     pub status: TransactionStatus,
     pub confirmations: u32,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransactionStatus {
     Pending,
     Confirmed,
@@ -51,7 +82,11 @@ pub enum TransactionStatus {
 pub struct InteroperabilityManager {
     bridges: HashMap<Uuid, CrossChainBridge>,
     pending_transactions: HashMap<Uuid, CrossChainTransaction>,
+    /// Completed transfers, kept so users can keep tracking them
+    completed_transactions: HashMap<Uuid, CrossChainTransaction>,
     validators: validators::ValidatorPool,
+    fee_collector: Option<FeeCollector>,
+    fee_calculator: FeeCalculator,
 }
 
 impl InteroperabilityManager {
@@ -59,10 +94,23 @@ impl InteroperabilityManager {
         Self {
             bridges: HashMap::new(),
             pending_transactions: HashMap::new(),
+            completed_transactions: HashMap::new(),
             validators: validators::ValidatorPool::new(),
+            fee_collector: None,
+            fee_calculator: FeeCalculator::default(),
         }
     }
 
+    /// Credit bridge fees to the ledger's fee-collection account
+    pub fn set_fee_collector(&mut self, fee_collector: FeeCollector) {
+        self.fee_collector = Some(fee_collector);
+    }
+
+    /// Round bridge fees with the calculator shared with other modules
+    pub fn set_fee_calculator(&mut self, fee_calculator: FeeCalculator) {
+        self.fee_calculator = fee_calculator;
+    }
+
     pub async fn create_bridge(
         &mut self,
         name: String,
@@ -81,6 +129,7 @@ impl InteroperabilityManager {
             bridge_contract,
             validators,
             min_confirmations: 12,
+            confirmation_tiers: Vec::new(),
             fee_rate: 0.001,
             active: true,
         };
@@ -89,6 +138,23 @@ impl InteroperabilityManager {
         Ok(bridge_id)
     }
 
+    /// Set the base confirmation depth and amount tiers for a bridge
+    pub fn set_confirmation_requirements(
+        &mut self,
+        bridge_id: Uuid,
+        min_confirmations: u32,
+        confirmation_tiers: Vec<ConfirmationTier>,
+    ) -> AstorResult<()> {
+        let bridge = self
+            .bridges
+            .get_mut(&bridge_id)
+            .ok_or_else(|| crate::errors::AstorError::NotFound("Bridge not found".to_string()))?;
+
+        bridge.min_confirmations = min_confirmations;
+        bridge.confirmation_tiers = confirmation_tiers;
+        Ok(())
+    }
+
     pub async fn initiate_cross_chain_transfer(
         &mut self,
         bridge_id: Uuid,
@@ -109,6 +175,10 @@ impl InteroperabilityManager {
         }
 
         let transaction_id = Uuid::new_v4();
+        let fee = self
+            .fee_calculator
+            .percentage_fee(amount, bridge.fee_rate)
+            .min(amount);
         let transaction = CrossChainTransaction {
             id: transaction_id,
             bridge_id,
@@ -117,6 +187,7 @@ impl InteroperabilityManager {
             from_address,
             to_address,
             amount,
+            fee,
             status: TransactionStatus::Pending,
             confirmations: 0,
             created_at: chrono::Utc::now(),
@@ -144,7 +215,7 @@ impl InteroperabilityManager {
 
             let bridge = self.bridges.get(&transaction.bridge_id).unwrap();
 
-            if confirmations >= bridge.min_confirmations {
+            if confirmations >= bridge.required_confirmations(transaction.amount) {
                 transaction.status = TransactionStatus::Confirmed;
                 self.execute_cross_chain_transfer(tx_id).await?;
             }
@@ -163,11 +234,71 @@ impl InteroperabilityManager {
             transaction.target_tx_hash = Some(target_tx_hash);
             transaction.status = TransactionStatus::Completed;
             transaction.completed_at = Some(chrono::Utc::now());
+
+            if let Some(fee_collector) = &self.fee_collector {
+                fee_collector
+                    .collect(
+                        tx_id.to_string(),
+                        FeeSource::Bridge,
+                        &transaction.from_address,
+                        DEFAULT_CURRENCY,
+                        transaction.fee,
+                    )
+                    .await?;
+            }
+        }
+
+        // Completed transfers leave the pending set but stay queryable
+        if let Some(transaction) = self.pending_transactions.remove(&tx_id) {
+            self.completed_transactions.insert(tx_id, transaction);
         }
 
         Ok(())
     }
 
+    /// A cross-chain transaction, pending or completed
+    pub fn get_transaction(&self, tx_id: Uuid) -> Option<&CrossChainTransaction> {
+        self.pending_transactions
+            .get(&tx_id)
+            .or_else(|| self.completed_transactions.get(&tx_id))
+    }
+
+    /// Transactions sent from or to `address`, oldest first
+    pub fn list_by_address(&self, address: &str) -> Vec<&CrossChainTransaction> {
+        self.sorted_transactions(|transaction| {
+            transaction.from_address == address || transaction.to_address == address
+        })
+    }
+
+    /// Transactions over `bridge_id`, optionally only those in `status_filter`,
+    /// oldest first
+    pub fn list_by_bridge(
+        &self,
+        bridge_id: Uuid,
+        status_filter: Option<TransactionStatus>,
+    ) -> Vec<&CrossChainTransaction> {
+        self.sorted_transactions(|transaction| {
+            transaction.bridge_id == bridge_id
+                && status_filter
+                    .as_ref()
+                    .map_or(true, |status| &transaction.status == status)
+        })
+    }
+
+    fn sorted_transactions(
+        &self,
+        filter: impl Fn(&CrossChainTransaction) -> bool,
+    ) -> Vec<&CrossChainTransaction> {
+        let mut transactions: Vec<&CrossChainTransaction> = self
+            .pending_transactions
+            .values()
+            .chain(self.completed_transactions.values())
+            .filter(|transaction| filter(transaction))
+            .collect();
+        transactions.sort_by_key(|transaction| transaction.created_at);
+        transactions
+    }
+
     async fn submit_to_target_chain(
         &self,
         transaction: &CrossChainTransaction,
@@ -178,3 +309,126 @@ impl InteroperabilityManager {
         Ok(tx_hash)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_large_transfer_needs_more_confirmations() {
+        let mut manager = InteroperabilityManager::new();
+        let bridge_id = manager
+            .create_bridge(
+                "astor-eth".to_string(),
+                "astor".to_string(),
+                "ethereum".to_string(),
+                "0xbridge".to_string(),
+                vec!["validator-1".to_string()],
+            )
+            .await
+            .unwrap();
+        manager
+            .set_confirmation_requirements(
+                bridge_id,
+                12,
+                vec![ConfirmationTier {
+                    min_amount: 1_000_000,
+                    confirmations: 64,
+                }],
+            )
+            .unwrap();
+
+        let small = manager
+            .initiate_cross_chain_transfer(
+                bridge_id,
+                "alice".to_string(),
+                "0xalice".to_string(),
+                500,
+                "0xsmall".to_string(),
+            )
+            .await
+            .unwrap();
+        let large = manager
+            .initiate_cross_chain_transfer(
+                bridge_id,
+                "bob".to_string(),
+                "0xbob".to_string(),
+                5_000_000,
+                "0xlarge".to_string(),
+            )
+            .await
+            .unwrap();
+
+        manager.process_confirmations(small, 12).await.unwrap();
+        manager.process_confirmations(large, 12).await.unwrap();
+        assert!(matches!(
+            manager.get_transaction(small).unwrap().status,
+            TransactionStatus::Completed
+        ));
+        assert!(matches!(
+            manager.get_transaction(large).unwrap().status,
+            TransactionStatus::Pending
+        ));
+
+        manager.process_confirmations(large, 64).await.unwrap();
+        assert!(matches!(
+            manager.get_transaction(large).unwrap().status,
+            TransactionStatus::Completed
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_completed_transfer_stays_queryable_by_address_and_bridge() {
+        let mut manager = InteroperabilityManager::new();
+        let bridge_id = manager
+            .create_bridge(
+                "astor-eth".to_string(),
+                "astor".to_string(),
+                "ethereum".to_string(),
+                "0xbridge".to_string(),
+                vec!["validator-1".to_string()],
+            )
+            .await
+            .unwrap();
+
+        let completed = manager
+            .initiate_cross_chain_transfer(
+                bridge_id,
+                "alice".to_string(),
+                "0xalice".to_string(),
+                500,
+                "0xfirst".to_string(),
+            )
+            .await
+            .unwrap();
+        let pending = manager
+            .initiate_cross_chain_transfer(
+                bridge_id,
+                "alice".to_string(),
+                "0xalice".to_string(),
+                700,
+                "0xsecond".to_string(),
+            )
+            .await
+            .unwrap();
+        manager.process_confirmations(completed, 12).await.unwrap();
+
+        assert_eq!(
+            manager.get_transaction(completed).unwrap().status,
+            TransactionStatus::Completed
+        );
+
+        let by_address: Vec<Uuid> = manager
+            .list_by_address("0xalice")
+            .iter()
+            .map(|transaction| transaction.id)
+            .collect();
+        assert_eq!(by_address, vec![completed, pending]);
+
+        let done = manager.list_by_bridge(bridge_id, Some(TransactionStatus::Completed));
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].id, completed);
+        assert_eq!(manager.list_by_bridge(bridge_id, None).len(), 2);
+        assert!(manager.list_by_bridge(Uuid::new_v4(), None).is_empty());
+    }
+}
@@ -2,14 +2,23 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tokio::sync::mpsc;
 
+use crate::central_bank::DEFAULT_CURRENCY;
+use crate::config::Config;
+use crate::conversion::ConversionResult;
 use crate::errors::AstorError;
+use crate::ledger_store::LedgerStore;
+use crate::schema::{legacy_schema_version, Versioned};
 use crate::security::hash_data;
+use crate::transactions::TransactionMemo;
 
 /// Ledger entry for recording transactions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LedgerEntry {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub id: String,
     pub entry_type: LedgerEntryType,
     pub timestamp: DateTime<Utc>,
@@ -17,6 +26,19 @@ pub struct LedgerEntry {
     pub previous_hash: String,
 }
 
+impl Versioned for LedgerEntry {
+    const SCHEMA_VERSION: u32 = 2;
+
+    fn migrate(
+        _from_version: u32,
+        _payload: &mut serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), AstorError> {
+        // Version 2 only introduced the version tag; the hash chain covers the
+        // entry contents, so older entries must not be rewritten
+        Ok(())
+    }
+}
+
 /// Types of ledger entries
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LedgerEntryType {
@@ -40,25 +62,686 @@ pub enum LedgerEntryType {
         action: String,
         target: String,
     },
+    /// Fee revenue credited to a fee-collection account
+    ///
+    /// Fees are withheld from what the payer receives, so the entry credits
+    /// the fee account in the fee's own currency without moving balances.
+    FeeCollection {
+        transaction_id: String,
+        source: FeeSource,
+        payer: String,
+        fee_account: String,
+        currency: String,
+        amount: u64,
+    },
+    /// Transfer paid in one currency and received in another
+    ///
+    /// Recorded together with the `FxConversion` entry that priced it, under
+    /// the same transaction ID. Only default-currency legs move ledger
+    /// balances.
+    CrossCurrencyTransfer {
+        transaction_id: String,
+        from: String,
+        to: String,
+        debit_currency: String,
+        debit_amount: u64,
+        credit_currency: String,
+        credit_amount: u64,
+    },
+    /// Exchange applied in flight to a cross-currency transfer
+    FxConversion {
+        transaction_id: String,
+        from_currency: String,
+        to_currency: String,
+        source_amount: u64,
+        converted_amount: u64,
+        exchange_rate: f64,
+        fees: u64,
+    },
+    /// Memo attached to a transaction; encrypted memos are stored only as
+    /// ciphertext
+    TransactionMemo {
+        transaction_id: String,
+        memo: TransactionMemo,
+    },
+}
+
+impl LedgerEntryType {
+    /// Transaction this entry records, if any
+    pub fn transaction_id(&self) -> Option<&str> {
+        match self {
+            LedgerEntryType::Issuance { transaction_id, .. }
+            | LedgerEntryType::Transfer { transaction_id, .. }
+            | LedgerEntryType::FeeCollection { transaction_id, .. }
+            | LedgerEntryType::CrossCurrencyTransfer { transaction_id, .. }
+            | LedgerEntryType::FxConversion { transaction_id, .. }
+            | LedgerEntryType::TransactionMemo { transaction_id, .. } => Some(transaction_id),
+            LedgerEntryType::AccountCreation { .. } | LedgerEntryType::AdminAction { .. } => None,
+        }
+    }
+
+    /// Funds this entry moves out of and into accounts, as
+    /// `(direction, account, amount)`
+    ///
+    /// Entries that move no funds have no legs.
+    pub fn legs(&self) -> Vec<(EntryDirection, &str, u64)> {
+        match self {
+            LedgerEntryType::Issuance {
+                recipient, amount, ..
+            } => vec![(EntryDirection::Credit, recipient.as_str(), *amount)],
+            LedgerEntryType::Transfer {
+                from, to, amount, ..
+            } => vec![
+                (EntryDirection::Debit, from.as_str(), *amount),
+                (EntryDirection::Credit, to.as_str(), *amount),
+            ],
+            LedgerEntryType::FeeCollection {
+                payer,
+                fee_account,
+                amount,
+                ..
+            } => vec![
+                (EntryDirection::Debit, payer.as_str(), *amount),
+                (EntryDirection::Credit, fee_account.as_str(), *amount),
+            ],
+            LedgerEntryType::CrossCurrencyTransfer {
+                from,
+                to,
+                debit_amount,
+                credit_amount,
+                ..
+            } => vec![
+                (EntryDirection::Debit, from.as_str(), *debit_amount),
+                (EntryDirection::Credit, to.as_str(), *credit_amount),
+            ],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Side of an entry an account is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryDirection {
+    Debit,
+    Credit,
+}
+
+/// Entries returned by `Ledger::query_entries` when no limit is given
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// Filter and page for `Ledger::query_entries`
+///
+/// Filters left unset match everything. The account, direction and amount
+/// filters match entries with a leg satisfying all of them (see
+/// `LedgerEntryType::legs`), so setting any of them excludes entries that
+/// move no funds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerQuery {
+    pub account_id: Option<String>,
+    pub direction: Option<EntryDirection>,
+    /// Inclusive
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive
+    pub until: Option<DateTime<Utc>>,
+    /// Inclusive
+    pub min_amount: Option<u64>,
+    /// Inclusive
+    pub max_amount: Option<u64>,
+    /// Matching entries to skip
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for LedgerQuery {
+    fn default() -> Self {
+        Self {
+            account_id: None,
+            direction: None,
+            from: None,
+            until: None,
+            min_amount: None,
+            max_amount: None,
+            offset: 0,
+            limit: DEFAULT_QUERY_LIMIT,
+        }
+    }
+}
+
+impl LedgerQuery {
+    fn matches(&self, entry: &LedgerEntry) -> bool {
+        if self.from.is_some_and(|from| entry.timestamp < from)
+            || self.until.is_some_and(|until| entry.timestamp >= until)
+        {
+            return false;
+        }
+        if self.account_id.is_none()
+            && self.direction.is_none()
+            && self.min_amount.is_none()
+            && self.max_amount.is_none()
+        {
+            return true;
+        }
+
+        entry
+            .entry_type
+            .legs()
+            .into_iter()
+            .any(|(direction, account, amount)| {
+                self.account_id.as_deref().map_or(true, |id| id == account)
+                    && self.direction.map_or(true, |d| d == direction)
+                    && self.min_amount.map_or(true, |min| amount >= min)
+                    && self.max_amount.map_or(true, |max| amount <= max)
+            })
+    }
+}
+
+/// Service that charged a fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeeSource {
+    Payment,
+    Conversion,
+    Bridge,
+    /// Base fee paid on a transfer
+    Transfer,
+}
+
+/// Fee a service charged, waiting to be recorded in the ledger
+#[derive(Debug, Clone)]
+struct FeePosting {
+    transaction_id: String,
+    source: FeeSource,
+    payer: String,
+    currency: String,
+    amount: u64,
+}
+
+/// Shared handle services use to post the fees they charge to the ledger
+///
+/// Services charge fees without access to the ledger, so fees are queued
+/// here and recorded by whoever owns the ledger through `FeePostings`.
+#[derive(Clone)]
+pub struct FeeCollector {
+    postings: mpsc::UnboundedSender<FeePosting>,
+    fee_account: String,
+}
+
+/// Fees queued by a `FeeCollector`, waiting to be recorded
+pub struct FeePostings {
+    postings: mpsc::UnboundedReceiver<FeePosting>,
+    fee_account: String,
+    /// Fee the ledger refused last time, recorded before any other
+    retry: Option<FeePosting>,
+}
+
+impl FeeCollector {
+    /// Collector crediting `fee_account`, and the queue its fees arrive on
+    pub fn new(fee_account: String) -> (Self, FeePostings) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            Self {
+                postings: sender,
+                fee_account: fee_account.clone(),
+            },
+            FeePostings {
+                postings: receiver,
+                fee_account,
+                retry: None,
+            },
+        )
+    }
+
+    pub fn fee_account(&self) -> &str {
+        &self.fee_account
+    }
+
+    /// Queue a fee for the fee-collection account; zero fees are not recorded
+    pub async fn collect(
+        &self,
+        transaction_id: String,
+        source: FeeSource,
+        payer: &str,
+        currency: &str,
+        amount: u64,
+    ) -> Result<(), AstorError> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        self.postings
+            .send(FeePosting {
+                transaction_id,
+                source,
+                payer: payer.to_string(),
+                currency: currency.to_string(),
+                amount,
+            })
+            .map_err(|_| {
+                AstorError::LedgerError("Fee ledger is no longer accepting fees".to_string())
+            })
+    }
+}
+
+impl FeePostings {
+    pub fn fee_account(&self) -> &str {
+        &self.fee_account
+    }
+
+    /// Record every fee queued so far, returning how many were recorded
+    ///
+    /// A fee the ledger refuses is kept and retried first on the next call.
+    pub fn post_to(&mut self, ledger: &mut Ledger) -> Result<usize, AstorError> {
+        let mut posted = 0;
+        while let Some(posting) = self.retry.take().or_else(|| self.postings.try_recv().ok()) {
+            if let Err(e) = ledger.record_fee_collection(
+                posting.transaction_id.clone(),
+                posting.source,
+                &posting.payer,
+                &self.fee_account,
+                &posting.currency,
+                posting.amount,
+            ) {
+                self.retry = Some(posting);
+                return Err(e);
+            }
+            posted += 1;
+        }
+        Ok(posted)
+    }
+}
+
+/// Block of ledger entries awaiting finality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingBlock {
+    pub height: u64,
+    pub block_hash: String,
+    pub entries: Vec<LedgerEntryType>,
+}
+
+/// Spilled entries loaded per read when scanning the full ledger
+const SPILL_LOAD_CHUNK: usize = 1024;
+
+/// Feature flag enabling accounting invariant checks on the ledger
+pub const INVARIANT_CHECKS_FLAG: &str = "ledger_invariant_checks";
+
+/// What the ledger does when an accounting invariant is violated
+///
+/// Checking sums every balance after each money-moving operation, so it is
+/// meant for development and testing, not production.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvariantCheckMode {
+    #[default]
+    Off,
+    /// Log violations and carry on
+    Log,
+    /// Panic at the operation that broke the invariant
+    Panic,
+}
+
+impl InvariantCheckMode {
+    /// Off unless the invariant-check feature flag is set; violations panic
+    /// in development and testing and are logged elsewhere
+    pub fn from_config(config: &Config) -> Self {
+        if !config.is_feature_enabled(INVARIANT_CHECKS_FLAG) {
+            InvariantCheckMode::Off
+        } else if config.environment.is_development()
+            || config.environment == crate::config::Environment::Testing
+        {
+            InvariantCheckMode::Panic
+        } else {
+            InvariantCheckMode::Log
+        }
+    }
+}
+
+/// Point up to which the hash chain has been verified
+///
+/// `sequence` counts the entries covered, so the first entry after the
+/// checkpoint is entry `sequence + 1`, and `running_hash` is the hash it must
+/// chain from. Checkpoints only speed up routine checks; they do not replace
+/// full verification, since entries before a checkpoint are trusted rather
+/// than rehashed by `verify_integrity_from`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerCheckpoint {
+    pub sequence: u64,
+    pub running_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Default-currency balance changes of one cross-currency transfer leg
+#[derive(Default)]
+struct CurrencyLegEffects {
+    /// New balances, by account
+    balances: Vec<(String, u64)>,
+    burned: u64,
+    converted_in: u64,
 }
 
 /// Secure, tamper-evident ledger
+///
+/// With a spill store configured, every entry is written to the store before
+/// it is added and only the most recent entries are held in memory; older
+/// ones are loaded back from the store in chunks by the methods that scan
+/// the whole ledger.
 pub struct Ledger {
+    /// Most recent entries; with a spill store, the store holds every entry
     entries: Vec<LedgerEntry>,
+    spill_store: Option<Box<dyn LedgerStore>>,
+    max_in_memory_entries: Option<usize>,
     account_balances: HashMap<String, u64>,
     total_supply: u64,
+    finality_depth: u64,
+    pending_blocks: VecDeque<PendingBlock>,
+    tip_height: u64,
+    finalized_height: u64,
+    /// Default currency converted away into other currencies
+    burned: u64,
+    /// Default currency received from conversions out of other currencies
+    converted_in: u64,
+    invariant_checks: InvariantCheckMode,
+    /// Running hash at each checkpoint, by sequence
+    checkpoints: BTreeMap<u64, String>,
 }
 
 impl Ledger {
     /// Create a new ledger
     pub fn new() -> Self {
+        Self::with_finality_depth(0)
+    }
+
+    /// Create a ledger that only commits a block's entries once
+    /// `finality_depth` subsequent blocks have been applied on top of it
+    pub fn with_finality_depth(finality_depth: u64) -> Self {
         Self {
             entries: Vec::new(),
+            spill_store: None,
+            max_in_memory_entries: None,
             account_balances: HashMap::new(),
             total_supply: 0,
+            finality_depth,
+            pending_blocks: VecDeque::new(),
+            tip_height: 0,
+            finalized_height: 0,
+            burned: 0,
+            converted_in: 0,
+            invariant_checks: InvariantCheckMode::Off,
+            checkpoints: BTreeMap::new(),
         }
     }
 
+    /// Check accounting invariants after every money-moving operation
+    pub fn set_invariant_checks(&mut self, mode: InvariantCheckMode) {
+        self.invariant_checks = mode;
+    }
+
+    /// Verify that the books balance
+    ///
+    /// Issued supply plus currency converted in must equal the sum of all
+    /// balances plus currency burned by conversion. Balances are unsigned, so
+    /// a debit that went negative shows up as a balance larger than all the
+    /// currency in existence.
+    pub fn check_invariants(&self) -> Result<(), AstorError> {
+        let in_existence = self.total_supply as u128 + self.converted_in as u128;
+
+        if let Some((account_id, balance)) = self
+            .account_balances
+            .iter()
+            .find(|(_, balance)| **balance as u128 > in_existence)
+        {
+            return Err(AstorError::LedgerError(format!(
+                "Invariant violated: account {} balance {} exceeds all {} units in existence (negative balance?)",
+                account_id, balance, in_existence
+            )));
+        }
+
+        let balances: u128 = self
+            .account_balances
+            .values()
+            .map(|balance| *balance as u128)
+            .sum();
+        if in_existence != balances + self.burned as u128 {
+            return Err(AstorError::LedgerError(format!(
+                "Invariant violated: supply {} + converted in {} != balances {} + burned {}",
+                self.total_supply, self.converted_in, balances, self.burned
+            )));
+        }
+        Ok(())
+    }
+
+    /// Act on an invariant violation according to the configured mode
+    fn enforce_invariants(&self, operation: &str) {
+        if self.invariant_checks == InvariantCheckMode::Off {
+            return;
+        }
+        if let Err(e) = self.check_invariants() {
+            match self.invariant_checks {
+                InvariantCheckMode::Panic => panic!("{} after {}", e, operation),
+                _ => tracing::error!("{} after {}", e, operation),
+            }
+        }
+    }
+
+    /// Apply a consensus block at the next height
+    ///
+    /// The block's entries only affect balances once the block is final.
+    /// The block is rejected, leaving the ledger unchanged, unless all its
+    /// entries can be committed on top of the blocks still pending. Returns
+    /// the blocks that became final, oldest first.
+    pub fn apply_block(
+        &mut self,
+        height: u64,
+        block_hash: String,
+        entries: Vec<LedgerEntryType>,
+    ) -> Result<Vec<PendingBlock>, AstorError> {
+        if height != self.tip_height + 1 {
+            return Err(AstorError::LedgerError(format!(
+                "Block height {} does not extend tip {}",
+                height, self.tip_height
+            )));
+        }
+        self.validate_block(&entries)?;
+
+        self.pending_blocks.push_back(PendingBlock {
+            height,
+            block_hash,
+            entries,
+        });
+        self.tip_height = height;
+
+        let mut finalized = Vec::new();
+        while let Some(block) = self.pending_blocks.front() {
+            if block.height + self.finality_depth > self.tip_height {
+                break;
+            }
+
+            let block = self.pending_blocks.pop_front().unwrap();
+            for entry in block.entries.iter().cloned() {
+                self.commit_entry(entry)?;
+            }
+            self.finalized_height = block.height;
+            finalized.push(block);
+        }
+
+        Ok(finalized)
+    }
+
+    /// Commit the pending blocks and then `entries` to a scratch copy of the
+    /// committed state, failing on the first entry that would be rejected
+    fn validate_block(&self, entries: &[LedgerEntryType]) -> Result<(), AstorError> {
+        let mut scratch = Ledger {
+            entries: self.entries.last().cloned().into_iter().collect(),
+            spill_store: None,
+            max_in_memory_entries: None,
+            account_balances: self.account_balances.clone(),
+            total_supply: self.total_supply,
+            finality_depth: self.finality_depth,
+            pending_blocks: VecDeque::new(),
+            tip_height: self.tip_height,
+            finalized_height: self.finalized_height,
+            burned: self.burned,
+            converted_in: self.converted_in,
+            invariant_checks: InvariantCheckMode::Off,
+            checkpoints: BTreeMap::new(),
+        };
+
+        let pending = self
+            .pending_blocks
+            .iter()
+            .flat_map(|block| block.entries.iter());
+        for entry in pending.chain(entries) {
+            scratch
+                .commit_entry(entry.clone())
+                .map_err(|e| AstorError::LedgerError(format!("Block rejected: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Roll back non-final blocks above `fork_height` after a reorg
+    ///
+    /// Returns the reverted blocks so their transactions can be resubmitted.
+    pub fn handle_reorg(&mut self, fork_height: u64) -> Result<Vec<PendingBlock>, AstorError> {
+        if fork_height < self.finalized_height {
+            return Err(AstorError::LedgerError(format!(
+                "Cannot reorg to height {} below finalized height {}",
+                fork_height, self.finalized_height
+            )));
+        }
+
+        let mut reverted = Vec::new();
+        while self
+            .pending_blocks
+            .back()
+            .map_or(false, |block| block.height > fork_height)
+        {
+            reverted.push(self.pending_blocks.pop_back().unwrap());
+        }
+        reverted.reverse();
+
+        if !reverted.is_empty() {
+            tracing::warn!(
+                "Reorg to height {} reverted {} non-final blocks",
+                fork_height,
+                reverted.len()
+            );
+        }
+
+        self.tip_height = self.tip_height.min(fork_height);
+        Ok(reverted)
+    }
+
+    /// Commit a finalized entry through the regular recording paths
+    fn commit_entry(&mut self, entry: LedgerEntryType) -> Result<(), AstorError> {
+        match entry {
+            LedgerEntryType::Issuance {
+                transaction_id,
+                issuer,
+                recipient,
+                amount,
+            } => self.record_issuance(transaction_id, &issuer, &recipient, amount),
+            LedgerEntryType::Transfer {
+                transaction_id,
+                from,
+                to,
+                amount,
+            } => self.record_transfer(transaction_id, &from, &to, amount),
+            LedgerEntryType::AccountCreation { account_id } => {
+                self.record_account_creation(account_id)
+            }
+            LedgerEntryType::AdminAction {
+                admin_id,
+                action,
+                target,
+            } => self.record_admin_action(admin_id, action, target),
+            LedgerEntryType::FeeCollection {
+                transaction_id,
+                source,
+                payer,
+                fee_account,
+                currency,
+                amount,
+            } => self.record_fee_collection(
+                transaction_id,
+                source,
+                &payer,
+                &fee_account,
+                &currency,
+                amount,
+            ),
+            entry @ LedgerEntryType::CrossCurrencyTransfer { .. } => {
+                self.record_currency_transfer_leg(entry)
+            }
+            entry @ LedgerEntryType::FxConversion { .. } => self.add_entry(entry),
+            LedgerEntryType::TransactionMemo {
+                transaction_id,
+                memo,
+            } => self.record_memo(transaction_id, memo),
+        }
+    }
+
+    /// Get the configured finality depth
+    pub fn get_finality_depth(&self) -> u64 {
+        self.finality_depth
+    }
+
+    /// Get the height of the most recent final block
+    pub fn get_finalized_height(&self) -> u64 {
+        self.finalized_height
+    }
+
+    /// Get the height of the most recently applied block, final or not
+    pub fn get_tip_height(&self) -> u64 {
+        self.tip_height
+    }
+
+    /// Get the blocks still awaiting finality
+    pub fn get_pending_blocks(&self) -> impl Iterator<Item = &PendingBlock> {
+        self.pending_blocks.iter()
+    }
+
+    /// Get account balance including non-final blocks
+    ///
+    /// This balance is informational only; spend checks must use
+    /// `get_account_balance`, which only reflects final blocks.
+    pub fn get_pending_balance(&self, account_id: &str) -> u64 {
+        let mut balance = self.get_account_balance(account_id) as i128;
+
+        for entry in self.pending_blocks.iter().flat_map(|b| b.entries.iter()) {
+            match entry {
+                LedgerEntryType::Issuance {
+                    recipient, amount, ..
+                } if recipient == account_id => balance += *amount as i128,
+                LedgerEntryType::Transfer {
+                    from, to, amount, ..
+                } => {
+                    if from == account_id {
+                        balance -= *amount as i128;
+                    }
+                    if to == account_id {
+                        balance += *amount as i128;
+                    }
+                }
+                LedgerEntryType::CrossCurrencyTransfer {
+                    from,
+                    to,
+                    debit_currency,
+                    debit_amount,
+                    credit_currency,
+                    credit_amount,
+                    ..
+                } => {
+                    if from == account_id && debit_currency == DEFAULT_CURRENCY {
+                        balance -= *debit_amount as i128;
+                    }
+                    if to == account_id && credit_currency == DEFAULT_CURRENCY {
+                        balance += *credit_amount as i128;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        balance.max(0) as u64
+    }
+
     /// Record currency issuance
     pub fn record_issuance(
         &mut self,
@@ -74,23 +757,21 @@ impl Ledger {
             amount,
         };
 
-        self.add_entry(entry_type)?;
-
-        // Update total supply
-        self.total_supply = self
+        let total_supply = self
             .total_supply
             .checked_add(amount)
             .ok_or_else(|| AstorError::LedgerError("Total supply overflow".to_string()))?;
-
-        // Update recipient balance
         let balance = self
-            .account_balances
-            .entry(recipient.to_string())
-            .or_insert(0);
-        *balance = balance
+            .get_account_balance(recipient)
             .checked_add(amount)
             .ok_or_else(|| AstorError::LedgerError("Account balance overflow".to_string()))?;
 
+        self.add_entry(entry_type)?;
+
+        self.total_supply = total_supply;
+        self.account_balances.insert(recipient.to_string(), balance);
+
+        self.enforce_invariants("issuance");
         Ok(())
     }
 
@@ -109,25 +790,156 @@ impl Ledger {
             amount,
         };
 
+        let from_balance = self
+            .get_account_balance(from)
+            .checked_sub(amount)
+            .ok_or_else(|| AstorError::LedgerError("Insufficient balance in ledger".to_string()))?;
+        // A transfer to self leaves the balance unchanged
+        let to_balance = if from == to {
+            self.get_account_balance(to)
+        } else {
+            self.get_account_balance(to)
+                .checked_add(amount)
+                .ok_or_else(|| AstorError::LedgerError("Account balance overflow".to_string()))?
+        };
+
         self.add_entry(entry_type)?;
 
-        // Update balances
-        let from_balance = self.account_balances.entry(from.to_string()).or_insert(0);
-        if *from_balance < amount {
-            return Err(AstorError::LedgerError(
-                "Insufficient balance in ledger".to_string(),
-            ));
+        self.account_balances.insert(from.to_string(), from_balance);
+        self.account_balances.insert(to.to_string(), to_balance);
+
+        self.enforce_invariants("transfer");
+        Ok(())
+    }
+
+    /// Record a transfer converted in flight and the FX conversion behind
+    /// it as two linked entries
+    pub fn record_cross_currency_transfer(
+        &mut self,
+        transaction_id: String,
+        from: &str,
+        to: &str,
+        from_currency: &str,
+        to_currency: &str,
+        conversion: &ConversionResult,
+    ) -> Result<(), AstorError> {
+        let from_currency = from_currency.to_uppercase();
+        let to_currency = to_currency.to_uppercase();
+
+        self.record_currency_transfer_leg(LedgerEntryType::CrossCurrencyTransfer {
+            transaction_id: transaction_id.clone(),
+            from: from.to_string(),
+            to: to.to_string(),
+            debit_currency: from_currency.clone(),
+            debit_amount: conversion.original_amount,
+            credit_currency: to_currency.clone(),
+            credit_amount: conversion.converted_amount,
+        })?;
+
+        self.add_entry(LedgerEntryType::FxConversion {
+            transaction_id,
+            from_currency,
+            to_currency,
+            source_amount: conversion.original_amount,
+            converted_amount: conversion.converted_amount,
+            exchange_rate: conversion.exchange_rate,
+            fees: conversion.fees.total,
+        })
+    }
+
+    /// Fail unless `record_cross_currency_transfer` would accept the
+    /// transfer, without recording anything
+    pub fn check_cross_currency_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        from_currency: &str,
+        to_currency: &str,
+        conversion: &ConversionResult,
+    ) -> Result<(), AstorError> {
+        self.currency_leg_effects(&LedgerEntryType::CrossCurrencyTransfer {
+            transaction_id: String::new(),
+            from: from.to_string(),
+            to: to.to_string(),
+            debit_currency: from_currency.to_uppercase(),
+            debit_amount: conversion.original_amount,
+            credit_currency: to_currency.to_uppercase(),
+            credit_amount: conversion.converted_amount,
+        })
+        .map(|_| ())
+    }
+
+    /// Default-currency balance changes of a cross-currency leg, checked
+    /// against the current balances but not applied
+    fn currency_leg_effects(
+        &self,
+        entry: &LedgerEntryType,
+    ) -> Result<CurrencyLegEffects, AstorError> {
+        let mut effects = CurrencyLegEffects::default();
+        if let LedgerEntryType::CrossCurrencyTransfer {
+            from,
+            to,
+            debit_currency,
+            debit_amount,
+            credit_currency,
+            credit_amount,
+            ..
+        } = entry
+        {
+            if debit_currency == DEFAULT_CURRENCY {
+                let balance = self
+                    .get_account_balance(from)
+                    .checked_sub(*debit_amount)
+                    .ok_or_else(|| {
+                        AstorError::LedgerError("Insufficient balance in ledger".to_string())
+                    })?;
+                effects.balances.push((from.clone(), balance));
+                effects.burned = *debit_amount;
+            }
+            if credit_currency == DEFAULT_CURRENCY {
+                let balance = self
+                    .get_account_balance(to)
+                    .checked_add(*credit_amount)
+                    .ok_or_else(|| {
+                        AstorError::LedgerError("Account balance overflow".to_string())
+                    })?;
+                effects.balances.push((to.clone(), balance));
+                effects.converted_in = *credit_amount;
+            }
         }
-        *from_balance -= amount;
+        Ok(effects)
+    }
 
-        let to_balance = self.account_balances.entry(to.to_string()).or_insert(0);
-        *to_balance = to_balance
-            .checked_add(amount)
-            .ok_or_else(|| AstorError::LedgerError("Account balance overflow".to_string()))?;
+    fn record_currency_transfer_leg(&mut self, entry: LedgerEntryType) -> Result<(), AstorError> {
+        let effects = self.currency_leg_effects(&entry)?;
+        self.add_entry(entry)?;
 
+        self.account_balances.extend(effects.balances);
+        self.burned = self.burned.saturating_add(effects.burned);
+        self.converted_in = self.converted_in.saturating_add(effects.converted_in);
+        self.enforce_invariants("cross-currency transfer");
         Ok(())
     }
 
+    /// Conversion recorded for a cross-currency transfer
+    pub fn fx_conversion_for(
+        &self,
+        transaction_id: &str,
+    ) -> Result<Option<LedgerEntry>, AstorError> {
+        let mut found = None;
+        self.try_for_each_entry(|entry| {
+            if matches!(
+                &entry.entry_type,
+                LedgerEntryType::FxConversion { transaction_id: id, .. } if id == transaction_id
+            ) {
+                found = Some(entry.clone());
+                return false;
+            }
+            true
+        })?;
+        Ok(found)
+    }
+
     /// Record account creation
     pub fn record_account_creation(&mut self, account_id: String) -> Result<(), AstorError> {
         let entry_type = LedgerEntryType::AccountCreation { account_id };
@@ -149,28 +961,235 @@ impl Ledger {
         self.add_entry(entry_type)
     }
 
-    /// Add a new entry to the ledger
-    fn add_entry(&mut self, entry_type: LedgerEntryType) -> Result<(), AstorError> {
-        let entry_id = uuid::Uuid::new_v4().to_string();
-        let timestamp = Utc::now();
-        let previous_hash = self.get_last_hash();
+    /// Record a fee credited to a fee-collection account
+    pub fn record_fee_collection(
+        &mut self,
+        transaction_id: String,
+        source: FeeSource,
+        payer: &str,
+        fee_account: &str,
+        currency: &str,
+        amount: u64,
+    ) -> Result<(), AstorError> {
+        let entry_type = LedgerEntryType::FeeCollection {
+            transaction_id,
+            source,
+            payer: payer.to_string(),
+            fee_account: fee_account.to_string(),
+            currency: currency.to_uppercase(),
+            amount,
+        };
+        self.add_entry(entry_type)
+    }
+
+    /// Record the memo attached to a transaction
+    pub fn record_memo(
+        &mut self,
+        transaction_id: String,
+        memo: TransactionMemo,
+    ) -> Result<(), AstorError> {
+        let entry_type = LedgerEntryType::TransactionMemo {
+            transaction_id,
+            memo,
+        };
+        self.add_entry(entry_type)
+    }
+
+    /// Memo recorded for a transaction, as stored in the ledger
+    pub fn memo_for(&self, transaction_id: &str) -> Result<Option<TransactionMemo>, AstorError> {
+        let mut found = None;
+        self.try_for_each_entry(|entry| match &entry.entry_type {
+            LedgerEntryType::TransactionMemo {
+                transaction_id: id,
+                memo,
+            } if id == transaction_id => {
+                found = Some(memo.clone());
+                false
+            }
+            _ => true,
+        })?;
+        Ok(found)
+    }
 
-        // Calculate hash for this entry
-        let entry_data = format!("{}{:?}{}", entry_id, entry_type, timestamp);
-        let hash = hash_data(format!("{}{}", previous_hash, entry_data).as_bytes());
+    /// Total fees credited to an account in a currency, for reconciling fee
+    /// revenue against the services that charged it
+    pub fn fees_collected(&self, fee_account: &str, currency: &str) -> Result<u64, AstorError> {
+        let mut total = 0u64;
+        self.try_for_each_entry(|entry| {
+            if let LedgerEntryType::FeeCollection {
+                fee_account: account,
+                currency: fee_currency,
+                amount,
+                ..
+            } = &entry.entry_type
+            {
+                if account == fee_account && fee_currency.eq_ignore_ascii_case(currency) {
+                    total = total.saturating_add(*amount);
+                }
+            }
+            true
+        })?;
+        Ok(total)
+    }
 
-        let entry = LedgerEntry {
-            id: entry_id,
+    /// Add a new entry to the ledger
+    ///
+    /// With a spill store, the entry is only added once the store has
+    /// persisted it.
+    fn add_entry(&mut self, entry_type: LedgerEntryType) -> Result<(), AstorError> {
+        let mut entry = LedgerEntry {
+            schema_version: LedgerEntry::SCHEMA_VERSION,
+            id: uuid::Uuid::new_v4().to_string(),
             entry_type,
-            timestamp,
-            hash,
-            previous_hash,
+            timestamp: Utc::now(),
+            hash: String::new(),
+            previous_hash: self.get_last_hash(),
         };
+        entry.hash = Self::entry_hash(&entry);
 
+        if let Some(store) = self.spill_store.as_mut() {
+            store.append(std::slice::from_ref(&entry))?;
+        }
         self.entries.push(entry);
+        self.trim_in_memory_entries();
+        Ok(())
+    }
+
+    /// Persist every entry to `store`, keeping at most
+    /// `max_in_memory_entries` of the most recent ones in memory
+    ///
+    /// Entries already in the ledger are written to an empty store. A store
+    /// that already holds entries, e.g. from an earlier run, can only be
+    /// attached to an empty ledger: its hash chain is verified and its
+    /// entries are replayed to rebuild balances and supply, and new entries
+    /// chain onto the last of them.
+    pub fn set_spill_store(
+        &mut self,
+        mut store: Box<dyn LedgerStore>,
+        max_in_memory_entries: usize,
+    ) -> Result<(), AstorError> {
+        if max_in_memory_entries == 0 {
+            return Err(AstorError::InvalidOperation(
+                "At least one ledger entry must be kept in memory".to_string(),
+            ));
+        }
+        if self.spill_store.is_some() {
+            return Err(AstorError::InvalidOperation(
+                "Ledger already has a spill store".to_string(),
+            ));
+        }
+        if store.is_empty() {
+            store.append(&self.entries)?;
+        } else {
+            if !self.entries.is_empty() {
+                return Err(AstorError::InvalidOperation(
+                    "Only an empty ledger can be restored from a spill store".to_string(),
+                ));
+            }
+            self.restore_from(store.as_ref())?;
+            let len = store.len();
+            self.entries = store.load(len.saturating_sub(max_in_memory_entries)..len)?;
+        }
+
+        self.spill_store = Some(store);
+        self.max_in_memory_entries = Some(max_in_memory_entries);
+        self.trim_in_memory_entries();
+        Ok(())
+    }
+
+    /// Rebuild balances, supply and the conversion counters by replaying the
+    /// entries in `store`, verifying their hash chain on the way
+    fn restore_from(&mut self, store: &dyn LedgerStore) -> Result<(), AstorError> {
+        let mut replay = Ledger::new();
+        let mut previous_hash = "genesis".to_string();
+        let mut start = 0;
+        while start < store.len() {
+            let end = (start + SPILL_LOAD_CHUNK).min(store.len());
+            for entry in store.load(start..end)? {
+                if entry.previous_hash != previous_hash || entry.hash != Self::entry_hash(&entry) {
+                    return Err(AstorError::LedgerError(format!(
+                        "Spilled ledger entry {} breaks the hash chain",
+                        entry.id
+                    )));
+                }
+                previous_hash = entry.hash;
+                replay.commit_entry(entry.entry_type)?;
+            }
+            // Only the replayed balances are kept, not the re-recorded entries
+            replay.entries.clear();
+            start = end;
+        }
+
+        self.account_balances = replay.account_balances;
+        self.total_supply = replay.total_supply;
+        self.burned = replay.burned;
+        self.converted_in = replay.converted_in;
+        Ok(())
+    }
+
+    /// Drop the oldest in-memory entries beyond `max_in_memory_entries`;
+    /// they remain in the spill store
+    fn trim_in_memory_entries(&mut self) {
+        if let Some(max) = self.max_in_memory_entries {
+            let excess = self.entries.len().saturating_sub(max);
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Entries only held in the spill store, no longer in memory
+    pub fn spilled_count(&self) -> usize {
+        self.spill_store
+            .as_ref()
+            .map_or(0, |store| store.len().saturating_sub(self.entries.len()))
+    }
+
+    /// Total entries recorded, in memory or spilled
+    pub fn entry_count(&self) -> usize {
+        self.spilled_count() + self.entries.len()
+    }
+
+    /// Visit every entry in ledger order, loading spilled entries in chunks,
+    /// until `f` returns false
+    pub fn try_for_each_entry(
+        &self,
+        f: impl FnMut(&LedgerEntry) -> bool,
+    ) -> Result<(), AstorError> {
+        self.try_for_each_entry_from(0, f)
+    }
+
+    /// `try_for_each_entry` starting at zero-based position `from`
+    fn try_for_each_entry_from(
+        &self,
+        from: usize,
+        mut f: impl FnMut(&LedgerEntry) -> bool,
+    ) -> Result<(), AstorError> {
+        let spilled = self.spilled_count();
+        if let Some(store) = &self.spill_store {
+            let mut start = from;
+            while start < spilled {
+                let end = (start + SPILL_LOAD_CHUNK).min(spilled);
+                for entry in store.load(start..end)? {
+                    if !f(&entry) {
+                        return Ok(());
+                    }
+                }
+                start = end;
+            }
+        }
+
+        for entry in self.entries.iter().skip(from.saturating_sub(spilled)) {
+            if !f(entry) {
+                return Ok(());
+            }
+        }
         Ok(())
     }
 
+    fn entry_hash(entry: &LedgerEntry) -> String {
+        let entry_data = format!("{}{:?}{}", entry.id, entry.entry_type, entry.timestamp);
+        hash_data(format!("{}{}", entry.previous_hash, entry_data).as_bytes())
+    }
+
     /// Get the hash of the last entry (for chaining)
     fn get_last_hash(&self) -> String {
         self.entries
@@ -179,41 +1198,178 @@ impl Ledger {
             .unwrap_or_else(|| "genesis".to_string())
     }
 
-    /// Verify ledger integrity
+    /// Verify ledger integrity, including spilled entries
+    ///
+    /// Rehashes every entry. This is the only check that covers entries
+    /// before a checkpoint, so it should still be run periodically even when
+    /// routine checks use `verify_integrity_from`.
     pub fn verify_integrity(&self) -> Result<bool, AstorError> {
-        if self.entries.is_empty() {
-            return Ok(true);
-        }
+        let (verified, _) = self.verify_chain(0, "genesis".to_string())?;
+        Ok(verified == self.entry_count() as u64)
+    }
 
-        for (i, entry) in self.entries.iter().enumerate() {
-            let expected_previous_hash = if i == 0 {
-                "genesis".to_string()
-            } else {
-                self.entries[i - 1].hash.clone()
-            };
+    /// Verify only the entries after the checkpoint at `checkpoint_seq`
+    ///
+    /// Entries up to the checkpoint are trusted, apart from checking that the
+    /// last of them still carries the checkpoint's running hash; tampering
+    /// further back is only found by `verify_integrity`. A sequence of zero
+    /// verifies the whole chain.
+    pub fn verify_integrity_from(&self, checkpoint_seq: u64) -> Result<bool, AstorError> {
+        let running_hash = if checkpoint_seq == 0 {
+            "genesis".to_string()
+        } else {
+            self.checkpoints
+                .get(&checkpoint_seq)
+                .cloned()
+                .ok_or_else(|| {
+                    AstorError::InvalidOperation(format!(
+                        "No ledger checkpoint at sequence {}",
+                        checkpoint_seq
+                    ))
+                })?
+        };
 
-            if entry.previous_hash != expected_previous_hash {
+        if checkpoint_seq > 0 {
+            let mut anchored = false;
+            self.try_for_each_entry_from(checkpoint_seq as usize - 1, |entry| {
+                anchored = entry.hash == running_hash;
+                false
+            })?;
+            if !anchored {
                 return Ok(false);
             }
+        }
 
-            // Verify entry hash
-            let entry_data = format!("{}{:?}{}", entry.id, entry.entry_type, entry.timestamp);
-            let expected_hash =
-                hash_data(format!("{}{}", entry.previous_hash, entry_data).as_bytes());
+        let (verified, _) = self.verify_chain(checkpoint_seq, running_hash)?;
+        Ok(verified == self.entry_count() as u64)
+    }
 
-            if entry.hash != expected_hash {
-                return Ok(false);
+    /// Checkpoint the chain as verified up to its tip
+    ///
+    /// Verifies incrementally from the latest checkpoint. If an entry fails
+    /// to verify, the checkpoint stops just before it, so a checkpoint never
+    /// covers a broken entry.
+    pub fn create_checkpoint(&mut self) -> Result<LedgerCheckpoint, AstorError> {
+        let (from, running_hash) = self
+            .checkpoints
+            .iter()
+            .next_back()
+            .map(|(sequence, hash)| (*sequence, hash.clone()))
+            .unwrap_or((0, "genesis".to_string()));
+
+        let (sequence, running_hash) = self.verify_chain(from, running_hash)?;
+        if sequence < self.entry_count() as u64 {
+            tracing::error!(
+                "Ledger entry {} failed verification; checkpoint stops before it",
+                sequence + 1
+            );
+        }
+        self.checkpoints.insert(sequence, running_hash.clone());
+        Ok(LedgerCheckpoint {
+            sequence,
+            running_hash,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Checkpoints created so far, oldest first
+    pub fn checkpoints(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.checkpoints
+            .iter()
+            .map(|(sequence, hash)| (*sequence, hash.as_str()))
+    }
+
+    /// Verify entries after the first `from`, chaining from `running_hash`
+    ///
+    /// Returns how many entries in total are verified and the hash of the
+    /// last one; stops at the first entry that fails.
+    fn verify_chain(&self, from: u64, running_hash: String) -> Result<(u64, String), AstorError> {
+        let mut verified = from;
+        let mut expected_previous_hash = running_hash;
+
+        self.try_for_each_entry_from(from as usize, |entry| {
+            if entry.previous_hash != expected_previous_hash
+                || entry.hash != Self::entry_hash(entry)
+            {
+                return false;
             }
+            expected_previous_hash = entry.hash.clone();
+            verified += 1;
+            true
+        })?;
+
+        Ok((verified, expected_previous_hash))
+    }
+
+    /// Merkle root over the entry hashes, in ledger order
+    ///
+    /// Odd nodes are paired with themselves. An empty ledger has the hash of
+    /// no data as its root.
+    pub fn merkle_root(&self) -> Result<String, AstorError> {
+        let mut level: Vec<String> = Vec::with_capacity(self.entry_count());
+        self.try_for_each_entry(|entry| {
+            level.push(entry.hash.clone());
+            true
+        })?;
+        if level.is_empty() {
+            return Ok(hash_data(&[]));
         }
 
-        Ok(true)
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).unwrap_or(&pair[0]);
+                    hash_data(format!("{}{}", pair[0], right).as_bytes())
+                })
+                .collect();
+        }
+        Ok(level.remove(0))
     }
 
-    /// Get all ledger entries
+    /// Ledger entries held in memory
+    ///
+    /// This is every entry unless a spill store is configured, in which case
+    /// it is only the most recent ones; use `try_for_each_entry` to see all.
     pub fn get_entries(&self) -> &[LedgerEntry] {
         &self.entries
     }
 
+    /// Iterate over in-memory ledger entries in the order they were recorded
+    pub fn iter(&self) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries.iter()
+    }
+
+    /// Page of entries matching `query`, in ledger order
+    ///
+    /// Ledger order is the order entries were recorded and chained, so the
+    /// same query returns the same page until new entries are recorded.
+    /// Spilled entries are scanned in chunks and only matching entries are
+    /// cloned; the scan stops once the page is full or the time range ends.
+    pub fn query_entries(&self, query: LedgerQuery) -> Result<Vec<LedgerEntry>, AstorError> {
+        let mut page = Vec::with_capacity(query.limit.min(DEFAULT_QUERY_LIMIT));
+        if query.limit == 0 {
+            return Ok(page);
+        }
+
+        let mut skipped = 0;
+        self.try_for_each_entry(|entry| {
+            if query.until.is_some_and(|until| entry.timestamp >= until) {
+                return false;
+            }
+            if !query.matches(entry) {
+                return true;
+            }
+            if skipped < query.offset {
+                skipped += 1;
+                return true;
+            }
+            page.push(entry.clone());
+            page.len() < query.limit
+        })?;
+        Ok(page)
+    }
+
     /// Get total supply
     pub fn get_total_supply(&self) -> u64 {
         self.total_supply
@@ -223,4 +1379,584 @@ impl Ledger {
     pub fn get_account_balance(&self, account_id: &str) -> u64 {
         self.account_balances.get(account_id).copied().unwrap_or(0)
     }
+
+    /// Reconstruct an account balance as of a past point in time
+    ///
+    /// Replays every recorded entry with a timestamp at or before `as_of`,
+    /// spilled or not, independently of the current balance table.
+    pub fn balance_at(&self, account_id: &str, as_of: DateTime<Utc>) -> Result<u64, AstorError> {
+        let mut balance: u64 = 0;
+
+        self.try_for_each_entry(|entry| {
+            if entry.timestamp > as_of {
+                return false;
+            }
+            match &entry.entry_type {
+                LedgerEntryType::Issuance {
+                    recipient, amount, ..
+                } if recipient == account_id => balance = balance.saturating_add(*amount),
+                LedgerEntryType::Transfer {
+                    from, to, amount, ..
+                } => {
+                    if from == account_id {
+                        balance = balance.saturating_sub(*amount);
+                    }
+                    if to == account_id {
+                        balance = balance.saturating_add(*amount);
+                    }
+                }
+                LedgerEntryType::CrossCurrencyTransfer {
+                    from,
+                    to,
+                    debit_currency,
+                    debit_amount,
+                    credit_currency,
+                    credit_amount,
+                    ..
+                } => {
+                    if from == account_id && debit_currency == DEFAULT_CURRENCY {
+                        balance = balance.saturating_sub(*debit_amount);
+                    }
+                    if to == account_id && credit_currency == DEFAULT_CURRENCY {
+                        balance = balance.saturating_add(*credit_amount);
+                    }
+                }
+                _ => {}
+            }
+            true
+        })?;
+
+        Ok(balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issuance(recipient: &str, amount: u64) -> LedgerEntryType {
+        LedgerEntryType::Issuance {
+            transaction_id: uuid::Uuid::new_v4().to_string(),
+            issuer: "root".to_string(),
+            recipient: recipient.to_string(),
+            amount,
+        }
+    }
+
+    fn transfer(from: &str, to: &str, amount: u64) -> LedgerEntryType {
+        LedgerEntryType::Transfer {
+            transaction_id: uuid::Uuid::new_v4().to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+        }
+    }
+
+    /// Timestamp of the most recently recorded entry
+    fn last_timestamp(ledger: &Ledger) -> DateTime<Utc> {
+        // Keep entry timestamps distinct so each checkpoint is unambiguous
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        ledger.get_entries().last().unwrap().timestamp
+    }
+
+    #[test]
+    fn test_balance_at_replays_history() {
+        let mut ledger = Ledger::new();
+        let before_history = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        ledger
+            .record_issuance("tx1".to_string(), "root", "alice", 1_000)
+            .unwrap();
+        let after_issuance = last_timestamp(&ledger);
+
+        ledger
+            .record_transfer("tx2".to_string(), "alice", "bob", 300)
+            .unwrap();
+        let after_first_transfer = last_timestamp(&ledger);
+
+        ledger
+            .record_transfer("tx3".to_string(), "bob", "alice", 50)
+            .unwrap();
+        ledger
+            .record_issuance("tx4".to_string(), "root", "bob", 200)
+            .unwrap();
+        let after_all = last_timestamp(&ledger);
+
+        assert_eq!(ledger.balance_at("alice", before_history).unwrap(), 0);
+        assert_eq!(ledger.balance_at("alice", after_issuance).unwrap(), 1_000);
+        assert_eq!(ledger.balance_at("bob", after_issuance).unwrap(), 0);
+        assert_eq!(
+            ledger.balance_at("alice", after_first_transfer).unwrap(),
+            700
+        );
+        assert_eq!(ledger.balance_at("bob", after_first_transfer).unwrap(), 300);
+        assert_eq!(ledger.balance_at("alice", after_all).unwrap(), 750);
+        assert_eq!(ledger.balance_at("bob", after_all).unwrap(), 450);
+
+        // Replaying the full history matches the live balances
+        for account in ["alice", "bob"] {
+            assert_eq!(
+                ledger.balance_at(account, Utc::now()).unwrap(),
+                ledger.get_account_balance(account)
+            );
+        }
+    }
+
+    #[test]
+    fn test_blocks_only_spendable_after_finality_depth() {
+        let mut ledger = Ledger::with_finality_depth(2);
+
+        ledger
+            .apply_block(1, "b1".to_string(), vec![issuance("alice", 100)])
+            .unwrap();
+        assert_eq!(ledger.get_account_balance("alice"), 0);
+        assert_eq!(ledger.get_pending_balance("alice"), 100);
+
+        ledger.apply_block(2, "b2".to_string(), vec![]).unwrap();
+        assert_eq!(ledger.get_account_balance("alice"), 0);
+
+        let finalized = ledger.apply_block(3, "b3".to_string(), vec![]).unwrap();
+        assert_eq!(
+            finalized.iter().map(|b| b.height).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(ledger.get_account_balance("alice"), 100);
+        assert_eq!(ledger.get_total_supply(), 100);
+    }
+
+    #[test]
+    fn test_block_with_invalid_entry_is_rejected_whole() {
+        let mut ledger = Ledger::with_finality_depth(1);
+        ledger
+            .apply_block(1, "b1".to_string(), vec![issuance("alice", 100)])
+            .unwrap();
+
+        // The overdraft depends on the pending issuance, and fails after the
+        // valid transfer ahead of it in the same block
+        let result = ledger.apply_block(
+            2,
+            "b2".to_string(),
+            vec![transfer("alice", "bob", 60), transfer("alice", "carol", 60)],
+        );
+        assert!(result.is_err());
+        assert_eq!(ledger.get_pending_blocks().count(), 1);
+        assert_eq!(ledger.get_finalized_height(), 0);
+        assert_eq!(ledger.get_account_balance("alice"), 0);
+
+        // The height is still free, and the valid part commits on its own
+        ledger
+            .apply_block(2, "b2".to_string(), vec![transfer("alice", "bob", 60)])
+            .unwrap();
+        ledger.apply_block(3, "b3".to_string(), vec![]).unwrap();
+        assert_eq!(ledger.get_account_balance("alice"), 40);
+        assert_eq!(ledger.get_account_balance("bob"), 60);
+        assert!(ledger.verify_integrity().unwrap());
+    }
+
+    #[test]
+    fn test_reorg_shallower_than_finality_depth_reverts_pending_blocks() {
+        let mut ledger = Ledger::with_finality_depth(3);
+
+        ledger
+            .apply_block(1, "b1".to_string(), vec![issuance("alice", 100)])
+            .unwrap();
+        ledger.apply_block(2, "b2".to_string(), vec![]).unwrap();
+        ledger.apply_block(3, "b3".to_string(), vec![]).unwrap();
+        ledger.apply_block(4, "b4".to_string(), vec![]).unwrap();
+        assert_eq!(ledger.get_finalized_height(), 1);
+        assert_eq!(ledger.get_account_balance("alice"), 100);
+
+        ledger
+            .apply_block(5, "b5".to_string(), vec![transfer("alice", "bob", 60)])
+            .unwrap();
+        assert_eq!(ledger.get_pending_balance("bob"), 60);
+
+        // A competing chain forks off at height 3, two blocks deep
+        let reverted = ledger.handle_reorg(3).unwrap();
+        assert_eq!(
+            reverted.iter().map(|b| b.height).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        assert_eq!(ledger.get_pending_balance("bob"), 0);
+        assert_eq!(ledger.get_account_balance("alice"), 100);
+
+        // The replacement chain continues from the fork point
+        ledger
+            .apply_block(
+                4,
+                "b4-fork".to_string(),
+                vec![transfer("alice", "carol", 30)],
+            )
+            .unwrap();
+        assert_eq!(ledger.get_pending_balance("carol"), 30);
+
+        // Final blocks can never be reorged away
+        assert!(ledger.handle_reorg(0).is_err());
+    }
+
+    #[test]
+    fn test_iter_yields_entries_in_insertion_order() {
+        let mut ledger = Ledger::new();
+        ledger
+            .record_issuance("tx1".to_string(), "root", "alice", 1_000)
+            .unwrap();
+        ledger
+            .record_transfer("tx2".to_string(), "alice", "bob", 300)
+            .unwrap();
+        ledger.record_account_creation("carol".to_string()).unwrap();
+
+        let kinds: Vec<&str> = ledger
+            .iter()
+            .map(|entry| match &entry.entry_type {
+                LedgerEntryType::Issuance { .. } => "issuance",
+                LedgerEntryType::Transfer { .. } => "transfer",
+                LedgerEntryType::AccountCreation { .. } => "account_creation",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(kinds, vec!["issuance", "transfer", "account_creation"]);
+    }
+
+    #[test]
+    fn test_spilled_entries_answer_like_in_memory() {
+        let mut ledger = Ledger::new();
+        let mut checkpoints = Vec::new();
+        for i in 0..10 {
+            ledger
+                .record_issuance(format!("tx-i{}", i), "root", "alice", 100)
+                .unwrap();
+            ledger
+                .record_transfer(format!("tx-t{}", i), "alice", "bob", 30)
+                .unwrap();
+            checkpoints.push(last_timestamp(&ledger));
+        }
+        ledger
+            .record_memo(
+                "tx-t0".to_string(),
+                TransactionMemo::Plaintext("rent".to_string()),
+            )
+            .unwrap();
+
+        let balances = |ledger: &Ledger| -> Vec<u64> {
+            checkpoints
+                .iter()
+                .flat_map(|at| ["alice", "bob"].map(|a| ledger.balance_at(a, *at).unwrap()))
+                .collect()
+        };
+        let in_memory_balances = balances(&ledger);
+        let in_memory_root = ledger.merkle_root().unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("astor-ledger-spill-{}.jsonl", uuid::Uuid::new_v4()));
+        ledger
+            .set_spill_store(
+                Box::new(crate::ledger_store::FileLedgerStore::create(&path).unwrap()),
+                3,
+            )
+            .unwrap();
+        assert_eq!(ledger.get_entries().len(), 3);
+        assert_eq!(ledger.spilled_count(), 18);
+        assert_eq!(ledger.entry_count(), 21);
+
+        assert_eq!(balances(&ledger), in_memory_balances);
+        assert_eq!(ledger.merkle_root().unwrap(), in_memory_root);
+        assert!(ledger.verify_integrity().unwrap());
+        assert_eq!(
+            ledger.memo_for("tx-t0").unwrap(),
+            Some(TransactionMemo::Plaintext("rent".to_string()))
+        );
+
+        // New entries chain onto the in-memory tail and keep spilling
+        ledger
+            .record_transfer("tx-late".to_string(), "bob", "alice", 10)
+            .unwrap();
+        assert_eq!(ledger.spilled_count(), 19);
+        assert!(ledger.verify_integrity().unwrap());
+        for account in ["alice", "bob"] {
+            assert_eq!(
+                ledger.balance_at(account, Utc::now()).unwrap(),
+                ledger.get_account_balance(account)
+            );
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_reopened_spill_store_restores_ledger() {
+        let path =
+            std::env::temp_dir().join(format!("astor-ledger-spill-{}.jsonl", uuid::Uuid::new_v4()));
+        let open = |path: &std::path::Path| -> Box<dyn LedgerStore> {
+            Box::new(crate::ledger_store::FileLedgerStore::open(path).unwrap())
+        };
+
+        let mut ledger = Ledger::new();
+        ledger.set_spill_store(open(&path), 2).unwrap();
+        ledger
+            .record_issuance("tx-1".to_string(), "root", "alice", 100)
+            .unwrap();
+        for i in 2..=4 {
+            ledger
+                .record_transfer(format!("tx-{}", i), "alice", "bob", 10)
+                .unwrap();
+        }
+        // A rejected transfer records nothing
+        assert!(ledger
+            .record_transfer("tx-5".to_string(), "bob", "carol", 1_000)
+            .is_err());
+        let root = ledger.merkle_root().unwrap();
+        drop(ledger);
+
+        // An interrupted write leaves a partial line behind
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, b"{\"schema_version\":2,").unwrap();
+        drop(file);
+
+        let mut restarted = Ledger::new();
+        restarted.set_spill_store(open(&path), 2).unwrap();
+        assert_eq!(restarted.entry_count(), 4);
+        assert_eq!(restarted.merkle_root().unwrap(), root);
+        assert_eq!(restarted.get_total_supply(), 100);
+        assert_eq!(restarted.get_account_balance("alice"), 70);
+        assert_eq!(restarted.get_account_balance("bob"), 30);
+
+        restarted
+            .record_transfer("tx-6".to_string(), "bob", "alice", 5)
+            .unwrap();
+        assert!(restarted.verify_integrity().unwrap());
+
+        // A ledger that already has entries cannot take over a used store
+        let mut other = Ledger::new();
+        other
+            .record_issuance("tx-7".to_string(), "root", "dave", 1)
+            .unwrap();
+        assert!(other.set_spill_store(open(&path), 2).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_restored_ledger_keeps_conversion_counters() {
+        let path =
+            std::env::temp_dir().join(format!("astor-ledger-spill-{}.jsonl", uuid::Uuid::new_v4()));
+        let open = |path: &std::path::Path| -> Box<dyn LedgerStore> {
+            Box::new(crate::ledger_store::FileLedgerStore::open(path).unwrap())
+        };
+        let conversion = |original_amount, converted_amount| ConversionResult {
+            original_amount,
+            converted_amount,
+            exchange_rate: 1.0,
+            fees: Default::default(),
+            slippage: 0.0,
+            timestamp: Utc::now(),
+            aml_alert_id: None,
+        };
+
+        let mut ledger = Ledger::new();
+        ledger.set_invariant_checks(InvariantCheckMode::Panic);
+        ledger.set_spill_store(open(&path), 2).unwrap();
+        ledger
+            .record_issuance("tx-1".to_string(), "root", "alice", 1_000)
+            .unwrap();
+        ledger
+            .record_cross_currency_transfer(
+                "tx-2".to_string(),
+                "alice",
+                "bob",
+                DEFAULT_CURRENCY,
+                "USD",
+                &conversion(300, 240),
+            )
+            .unwrap();
+        ledger
+            .record_cross_currency_transfer(
+                "tx-3".to_string(),
+                "bob",
+                "carol",
+                "USD",
+                DEFAULT_CURRENCY,
+                &conversion(100, 120),
+            )
+            .unwrap();
+        drop(ledger);
+
+        let mut restarted = Ledger::new();
+        restarted.set_spill_store(open(&path), 2).unwrap();
+        assert_eq!(restarted.burned, 300);
+        assert_eq!(restarted.converted_in, 120);
+        assert_eq!(restarted.get_account_balance("alice"), 700);
+        assert_eq!(restarted.get_account_balance("carol"), 120);
+        restarted.check_invariants().unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_broken_operation_trips_invariant_checker() {
+        let mut ledger = Ledger::new();
+        ledger.set_invariant_checks(InvariantCheckMode::Panic);
+        ledger
+            .record_issuance("tx-1".to_string(), "root", "alice", 1_000)
+            .unwrap();
+        ledger
+            .record_transfer("tx-2".to_string(), "alice", "bob", 400)
+            .unwrap();
+        ledger.check_invariants().unwrap();
+
+        // A buggy credit that creates money out of nothing
+        *ledger.account_balances.get_mut("bob").unwrap() += 50;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            ledger.record_transfer("tx-3".to_string(), "alice", "carol", 100)
+        }));
+        assert!(result.is_err());
+
+        // A debit that wrapped below zero is caught too
+        let mut ledger = Ledger::new();
+        ledger
+            .record_issuance("tx-4".to_string(), "root", "alice", 1_000)
+            .unwrap();
+        ledger
+            .account_balances
+            .insert("alice".to_string(), 1_000u64.wrapping_sub(1_001));
+        assert!(matches!(
+            ledger.check_invariants(),
+            Err(AstorError::LedgerError(message)) if message.contains("negative")
+        ));
+    }
+
+    #[test]
+    fn test_query_entries_filters_and_pages_in_ledger_order() {
+        let mut ledger = Ledger::new();
+        ledger
+            .set_spill_store(Box::new(crate::ledger_store::MemoryLedgerStore::new()), 2)
+            .unwrap();
+        ledger
+            .record_issuance("tx-0".to_string(), "root", "alice", 10_000)
+            .unwrap();
+        let window_start = last_timestamp(&ledger);
+        for (tx, amount) in [("tx-1", 100), ("tx-2", 2_000), ("tx-3", 300), ("tx-4", 400)] {
+            ledger
+                .record_transfer(tx.to_string(), "alice", "bob", amount)
+                .unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let window_end = Utc::now();
+        ledger
+            .record_transfer("tx-5".to_string(), "bob", "alice", 50)
+            .unwrap();
+        ledger
+            .record_transfer("tx-6".to_string(), "alice", "bob", 600)
+            .unwrap();
+
+        let transaction_ids = |entries: Vec<LedgerEntry>| -> Vec<String> {
+            entries
+                .into_iter()
+                .map(|entry| match entry.entry_type {
+                    LedgerEntryType::Transfer { transaction_id, .. }
+                    | LedgerEntryType::Issuance { transaction_id, .. } => transaction_id,
+                    other => panic!("unexpected entry {:?}", other),
+                })
+                .collect()
+        };
+
+        // Debits against alice in the window, including spilled entries
+        let debits = LedgerQuery {
+            account_id: Some("alice".to_string()),
+            direction: Some(EntryDirection::Debit),
+            from: Some(window_start),
+            until: Some(window_end),
+            ..LedgerQuery::default()
+        };
+        assert!(ledger.spilled_count() > 0);
+        assert_eq!(
+            transaction_ids(ledger.query_entries(debits.clone()).unwrap()),
+            vec!["tx-1", "tx-2", "tx-3", "tx-4"]
+        );
+
+        // Paging walks the same order without gaps or repeats
+        let first = LedgerQuery { limit: 3, ..debits };
+        let second = LedgerQuery {
+            offset: 3,
+            ..first.clone()
+        };
+        assert_eq!(
+            transaction_ids(ledger.query_entries(first).unwrap()),
+            vec!["tx-1", "tx-2", "tx-3"]
+        );
+        assert_eq!(
+            transaction_ids(ledger.query_entries(second).unwrap()),
+            vec!["tx-4"]
+        );
+
+        let mid_sized = LedgerQuery {
+            account_id: Some("alice".to_string()),
+            min_amount: Some(300),
+            max_amount: Some(600),
+            ..LedgerQuery::default()
+        };
+        assert_eq!(
+            transaction_ids(ledger.query_entries(mid_sized).unwrap()),
+            vec!["tx-3", "tx-4", "tx-6"]
+        );
+
+        let credits = LedgerQuery {
+            account_id: Some("alice".to_string()),
+            direction: Some(EntryDirection::Credit),
+            ..LedgerQuery::default()
+        };
+        assert_eq!(
+            transaction_ids(ledger.query_entries(credits).unwrap()),
+            vec!["tx-0", "tx-5"]
+        );
+        assert_eq!(
+            ledger.query_entries(LedgerQuery::default()).unwrap().len(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_verify_from_checkpoint_skips_earlier_entries_but_full_verify_does_not() {
+        let mut ledger = Ledger::new();
+        ledger
+            .set_spill_store(Box::new(crate::ledger_store::MemoryLedgerStore::new()), 6)
+            .unwrap();
+        ledger
+            .record_issuance("tx-1".to_string(), "root", "alice", 1_000)
+            .unwrap();
+        for i in 2..=5 {
+            ledger
+                .record_transfer(format!("tx-{}", i), "alice", "bob", 10)
+                .unwrap();
+        }
+
+        let checkpoint = ledger.create_checkpoint().unwrap();
+        assert_eq!(checkpoint.sequence, 5);
+        for i in 6..=8 {
+            ledger
+                .record_transfer(format!("tx-{}", i), "alice", "bob", 10)
+                .unwrap();
+        }
+        assert_eq!(ledger.spilled_count(), 2);
+        assert!(ledger.verify_integrity_from(checkpoint.sequence).unwrap());
+        assert!(ledger.verify_integrity_from(4).is_err());
+
+        // Tampering before the checkpoint is invisible to the incremental
+        // check but caught by a full verification
+        ledger.entries[1].entry_type = transfer("alice", "mallory", 10);
+        assert!(ledger.verify_integrity_from(checkpoint.sequence).unwrap());
+        assert!(!ledger.verify_integrity().unwrap());
+        assert!(!ledger.verify_integrity_from(0).unwrap());
+
+        // Tampering after it is caught, and later checkpoints stop short of it
+        let last = ledger.entries.len() - 1;
+        ledger.entries[last].entry_type = transfer("alice", "mallory", 10);
+        assert!(!ledger.verify_integrity_from(checkpoint.sequence).unwrap());
+        let next = ledger.create_checkpoint().unwrap();
+        assert_eq!(next.sequence, 7);
+        assert_eq!(ledger.checkpoints().count(), 2);
+    }
 }
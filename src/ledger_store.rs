@@ -0,0 +1,216 @@
+//! Persistent storage for ledger entries spilled out of memory
+//!
+//! A ledger configured with a store appends every entry here before adding
+//! it, and keeps only its most recent entries in memory. Stored entries are immutable and kept
+//! in ledger order, so they are addressed by position.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::errors::AstorError;
+use crate::ledger::LedgerEntry;
+use crate::schema::from_versioned_json;
+
+/// Append-only store of ledger entries, addressed by position
+pub trait LedgerStore: Send + Sync {
+    /// Append entries after those already stored
+    fn append(&mut self, entries: &[LedgerEntry]) -> Result<(), AstorError>;
+
+    /// Load the stored entries at `range`, in ledger order
+    fn load(&self, range: Range<usize>) -> Result<Vec<LedgerEntry>, AstorError>;
+
+    /// Number of entries stored
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn check_range(range: &Range<usize>, len: usize) -> Result<(), AstorError> {
+    if range.start > range.end || range.end > len {
+        return Err(AstorError::LedgerError(format!(
+            "Ledger store range {:?} out of bounds for {} entries",
+            range, len
+        )));
+    }
+    Ok(())
+}
+
+fn io_error(e: std::io::Error) -> AstorError {
+    AstorError::LedgerError(format!("Ledger store I/O failed: {}", e))
+}
+
+/// Store kept in memory, for tests and ephemeral nodes
+#[derive(Default)]
+pub struct MemoryLedgerStore {
+    entries: Vec<LedgerEntry>,
+}
+
+impl MemoryLedgerStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LedgerStore for MemoryLedgerStore {
+    fn append(&mut self, entries: &[LedgerEntry]) -> Result<(), AstorError> {
+        self.entries.extend_from_slice(entries);
+        Ok(())
+    }
+
+    fn load(&self, range: Range<usize>) -> Result<Vec<LedgerEntry>, AstorError> {
+        check_range(&range, self.entries.len())?;
+        Ok(self.entries[range].to_vec())
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Store writing one JSON entry per line to a file
+///
+/// Only the byte offset of each entry is kept in memory. Entry hashes cover
+/// exchange rates, so serde_json's `float_roundtrip` feature is required for
+/// reloaded entries to verify.
+pub struct FileLedgerStore {
+    path: PathBuf,
+    file: File,
+    offsets: Vec<u64>,
+    end: u64,
+}
+
+impl FileLedgerStore {
+    /// Create a store at `path`, replacing any file already there
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, AstorError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(io_error)?;
+
+        Ok(Self {
+            path,
+            file,
+            offsets: Vec::new(),
+            end: 0,
+        })
+    }
+
+    /// Open the store at `path` for appending, creating it if missing
+    ///
+    /// Entries already in the file are indexed. A trailing line left
+    /// incomplete by an interrupted write is cut off.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AstorError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .map_err(io_error)?;
+
+        let mut offsets = Vec::new();
+        let mut end = 0u64;
+        let mut reader = BufReader::new(File::open(&path).map_err(io_error)?);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line).map_err(io_error)?;
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            offsets.push(end);
+            end += read as u64;
+        }
+
+        if file.metadata().map_err(io_error)?.len() > end {
+            tracing::warn!(
+                "Discarding incomplete entry at the end of ledger store {}",
+                path.display()
+            );
+            file.set_len(end).map_err(io_error)?;
+        }
+
+        Ok(Self {
+            path,
+            file,
+            offsets,
+            end,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl LedgerStore for FileLedgerStore {
+    fn append(&mut self, entries: &[LedgerEntry]) -> Result<(), AstorError> {
+        let mut buffer = Vec::new();
+        let mut offsets = Vec::with_capacity(entries.len());
+        for entry in entries {
+            offsets.push(self.end + buffer.len() as u64);
+            serde_json::to_writer(&mut buffer, entry)?;
+            buffer.push(b'\n');
+        }
+
+        // A failed write may leave part of the buffer behind; cut it off so
+        // the file keeps ending at the last stored entry
+        if let Err(e) = self
+            .file
+            .write_all(&buffer)
+            .and_then(|_| self.file.sync_data())
+        {
+            let end = self.end;
+            if let Err(truncate) = self
+                .file
+                .set_len(end)
+                .and_then(|_| self.file.seek(SeekFrom::Start(end)))
+            {
+                tracing::error!(
+                    "Could not discard partial write to ledger store {}: {}",
+                    self.path.display(),
+                    truncate
+                );
+            }
+            return Err(io_error(e));
+        }
+        self.end += buffer.len() as u64;
+        self.offsets.extend(offsets);
+        Ok(())
+    }
+
+    fn load(&self, range: Range<usize>) -> Result<Vec<LedgerEntry>, AstorError> {
+        check_range(&range, self.offsets.len())?;
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut file = File::open(&self.path).map_err(io_error)?;
+        file.seek(SeekFrom::Start(self.offsets[range.start]))
+            .map_err(io_error)?;
+
+        let mut entries = Vec::with_capacity(range.len());
+        let mut lines = BufReader::new(file).lines();
+        for _ in range {
+            let line = lines
+                .next()
+                .ok_or_else(|| {
+                    AstorError::LedgerError("Ledger store file is truncated".to_string())
+                })?
+                .map_err(io_error)?;
+            entries.push(from_versioned_json(line.as_bytes())?);
+        }
+        Ok(entries)
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len()
+    }
+}
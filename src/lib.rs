@@ -16,19 +16,30 @@ pub mod config;
 pub mod conversion;
 pub mod database;
 pub mod errors;
+pub mod fee_calculator;
+pub mod fee_market;
 pub mod interoperability;
 pub mod ledger;
+pub mod ledger_store;
 pub mod monitoring;
 pub mod network;
 pub mod payment_processing;
+pub mod receipts;
 pub mod regulatory;
+pub mod schema;
 pub mod security;
 pub mod smart_contracts;
 pub mod transactions;
 
-pub use accounts::AccountManager;
+pub use accounts::{
+    AccountManager, AccountSpec, AccountStatus, BalanceAlertRule, ImportMode, ImportResult,
+    ImportRowStatus, Notifier,
+};
 pub use admin::AdminManager;
-pub use banking_network::{BankStatus, BankingNetwork, RegisteredBank};
+pub use banking_network::{
+    BankDirectory, BankDirectoryEntry, BankStatus, BankingNetwork, DirectoryFormat, RegisteredBank,
+    SettlementOutcome,
+};
 pub use central_bank::CentralBank;
 pub use certificate_authority::{
     AstorCertificateAuthority, Certificate, CertificateAuthorityConfig, CertificateSigningRequest,
@@ -37,13 +48,40 @@ pub use certificate_authority::{
 pub use cli::{CentralBankCli, CliHandler};
 pub use commercial_banking::CommercialBank;
 pub use errors::AstorError;
-pub use ledger::Ledger;
+pub use ledger::{FeeCollector, FeeSource, InvariantCheckMode, Ledger};
 pub use monitoring::MonitoringSystem;
 pub use network::{NetworkManager, NetworkStatus};
 pub use payment_processing::PaymentProcessor;
 pub use regulatory::RegulatoryCompliance;
-pub use security::{KeyPair, Signature};
-pub use transactions::TransactionManager;
+pub use security::{ExternalSigner, KeyPair, Signature, Signer};
+pub use transactions::{TransactionManager, TransactionObserver, TransactionVisibility};
+
+/// Queued transactions settled per scheduler tick
+pub const PENDING_TRANSACTIONS_PER_TICK: usize = 1_000;
+
+/// Minimum time between scheduler sweeps for dormant accounts
+const DORMANCY_SWEEP_INTERVAL_SECS: i64 = 3_600;
+
+/// Minimum time between scheduler passes archiving expired transactions
+const TRANSACTION_PRUNE_INTERVAL_SECS: i64 = 86_400;
+
+/// Periodic work enabled by configuration, and when it last ran
+#[derive(Default)]
+struct ScheduledTasks {
+    /// Accounts idle this long are marked dormant; no sweep when unset
+    dormancy_period: Option<chrono::Duration>,
+    last_dormancy_sweep: Option<chrono::DateTime<chrono::Utc>>,
+    /// Interest on reserves is paid this often; only on request when unset
+    reserve_interest_interval: Option<chrono::Duration>,
+    /// Settled transactions past retention are moved here; none are
+    /// archived when unset
+    transaction_archive: Option<Box<dyn transactions::TransactionArchive>>,
+    last_transaction_prune: Option<chrono::DateTime<chrono::Utc>>,
+    /// Blocks committed by consensus, once the network is deployed
+    committed_blocks: Option<tokio::sync::broadcast::Receiver<network::consensus::Block>>,
+    /// Network sync state gating transactions, once the network is deployed
+    sync_manager: Option<std::sync::Arc<tokio::sync::RwLock<network::SyncManager>>>,
+}
 
 /// Core Astor system that orchestrates all components
 pub struct AstorSystem {
@@ -57,18 +95,58 @@ pub struct AstorSystem {
     pub payment_processor: PaymentProcessor,
     pub regulatory_compliance: RegulatoryCompliance,
     pub banking_network: BankingNetwork,
-    pub certificate_authority: AstorCertificateAuthority,
+    /// Shared with the scheduled CRL publication task
+    pub certificate_authority: std::sync::Arc<tokio::sync::RwLock<AstorCertificateAuthority>>,
+    /// Scores account operations against the transfers recorded here
+    pub fraud_detector: security::FraudDetector,
+    /// Exchange rates and audited conversions; shared with settlement
+    pub conversion: std::sync::Arc<tokio::sync::RwLock<conversion::ConversionService>>,
+    /// Fee arithmetic shared by every component that charges fees
+    fee_calculator: fee_calculator::FeeCalculator,
+    /// Handed to every component that charges fees
+    fee_collector: FeeCollector,
+    /// Fees charged by those components, waiting to be recorded in the ledger
+    fee_postings: ledger::FeePostings,
+    /// Key that signs published attestations such as proofs of reserve
+    system_signer: std::sync::Arc<dyn Signer>,
+    /// Delivers balance alerts and transfer receipts to account holders
+    notifier: std::sync::Arc<dyn accounts::Notifier>,
+    /// Transfer receipts, when notifications are configured
+    receipts: Option<std::sync::Arc<receipts::ReceiptService>>,
+    scheduled: ScheduledTasks,
 }
 
 impl AstorSystem {
     /// Initialize a new Astor system with a root administrator
+    ///
+    /// The dependencies named in the health check configuration are checked
+    /// first; startup is aborted if any of them is unhealthy.
     pub async fn new(
         root_admin_keypair: KeyPair,
         monitoring_config: config::MonitoringConfig,
     ) -> Result<Self, AstorError> {
+        let dependencies =
+            monitoring::health::startup_dependencies(&monitoring_config.health_check);
+        Self::new_with_dependencies(root_admin_keypair, monitoring_config, dependencies).await
+    }
+
+    /// Initialize a new Astor system after checking the given dependencies,
+    /// in order
+    pub async fn new_with_dependencies(
+        root_admin_keypair: KeyPair,
+        monitoring_config: config::MonitoringConfig,
+        dependencies: Vec<std::sync::Arc<dyn monitoring::health::DependencyCheck>>,
+    ) -> Result<Self, AstorError> {
+        monitoring::health::verify_dependencies(
+            &dependencies,
+            std::time::Duration::from_secs(monitoring_config.health_check.timeout.max(1)),
+        )
+        .await?;
+
         let mut admin_manager = AdminManager::new();
         let ledger = Ledger::new();
-        let account_manager = AccountManager::new();
+        let mut account_manager = AccountManager::new();
+        account_manager.set_transaction_limits(security::TransactionLimits::default());
         let transaction_manager = TransactionManager::new();
         let monitoring = MonitoringSystem::new(monitoring_config).await?;
 
@@ -89,9 +167,17 @@ impl AstorSystem {
 
         let ca_config = certificate_authority::ca_core::CaConfig::default();
         let ca_keypair = KeyPair::generate(); // Separate keypair for CA
-        let certificate_authority = AstorCertificateAuthority::new(ca_keypair, ca_config)?;
+        let mut certificate_authority =
+            AstorCertificateAuthority::new(std::sync::Arc::new(ca_keypair), ca_config)?;
+        certificate_authority
+            .subject_policy_mut()
+            .set_bank_directory(banking_network.directory());
+        let certificate_authority =
+            std::sync::Arc::new(tokio::sync::RwLock::new(certificate_authority));
 
         monitoring.start().await?;
+        let (fee_collector, fee_postings) =
+            FeeCollector::new(config::DEFAULT_FEE_ACCOUNT.to_string());
 
         Ok(Self {
             admin_manager,
@@ -105,6 +191,17 @@ impl AstorSystem {
             regulatory_compliance,
             banking_network,
             certificate_authority,
+            fraud_detector: security::FraudDetector::new(),
+            conversion: std::sync::Arc::new(tokio::sync::RwLock::new(
+                conversion::ConversionService::new(),
+            )),
+            fee_calculator: fee_calculator::FeeCalculator::default(),
+            fee_collector,
+            fee_postings,
+            system_signer: std::sync::Arc::new(KeyPair::generate()),
+            notifier: std::sync::Arc::new(accounts::LogNotifier),
+            receipts: None,
+            scheduled: ScheduledTasks::default(),
         })
     }
 
@@ -114,9 +211,16 @@ impl AstorSystem {
         monitoring_config: config::MonitoringConfig,
         network_config: network::NodeConfig,
     ) -> Result<(Self, NetworkManager), AstorError> {
+        monitoring::health::verify_dependencies(
+            &monitoring::health::startup_dependencies(&monitoring_config.health_check),
+            std::time::Duration::from_secs(monitoring_config.health_check.timeout.max(1)),
+        )
+        .await?;
+
         let mut admin_manager = AdminManager::new();
-        let ledger = Ledger::new();
-        let account_manager = AccountManager::new();
+        let ledger = Ledger::with_finality_depth(network_config.finality_depth);
+        let mut account_manager = AccountManager::new();
+        account_manager.set_transaction_limits(security::TransactionLimits::default());
         let transaction_manager = TransactionManager::new();
         let monitoring = MonitoringSystem::new(monitoring_config).await?;
 
@@ -137,9 +241,20 @@ impl AstorSystem {
 
         let ca_config = certificate_authority::ca_core::CaConfig::default();
         let ca_keypair = KeyPair::generate(); // Separate keypair for CA
-        let certificate_authority = AstorCertificateAuthority::new(ca_keypair, ca_config)?;
+        let mut certificate_authority =
+            AstorCertificateAuthority::new(std::sync::Arc::new(ca_keypair), ca_config)?;
+        certificate_authority
+            .subject_policy_mut()
+            .set_bank_directory(banking_network.directory());
+        certificate_authority
+            .subject_policy_mut()
+            .approve_node(network_config.node_id.clone());
+        let certificate_authority =
+            std::sync::Arc::new(tokio::sync::RwLock::new(certificate_authority));
 
         monitoring.start().await?;
+        let (fee_collector, fee_postings) =
+            FeeCollector::new(config::DEFAULT_FEE_ACCOUNT.to_string());
 
         let system = Self {
             admin_manager,
@@ -153,6 +268,17 @@ impl AstorSystem {
             regulatory_compliance,
             banking_network,
             certificate_authority,
+            fraud_detector: security::FraudDetector::new(),
+            conversion: std::sync::Arc::new(tokio::sync::RwLock::new(
+                conversion::ConversionService::new(),
+            )),
+            fee_calculator: fee_calculator::FeeCalculator::default(),
+            fee_collector,
+            fee_postings,
+            system_signer: std::sync::Arc::new(KeyPair::generate()),
+            notifier: std::sync::Arc::new(accounts::LogNotifier),
+            receipts: None,
+            scheduled: ScheduledTasks::default(),
         };
 
         let network_manager = NetworkManager::new(network_config).await?;
@@ -160,6 +286,194 @@ impl AstorSystem {
         Ok((system, network_manager))
     }
 
+    /// Apply the deployment configuration to components the constructors
+    /// built with defaults
+    pub async fn apply_config(&mut self, config: &config::Config) -> Result<(), AstorError> {
+        for admin in &config.security.administrators {
+            self.admin_manager.set_admin(
+                admin.id.clone(),
+                admin.decode_public_key()?,
+                admin.role.clone(),
+            );
+        }
+        if let Some(banking_api) = &config.external_services.banking_api {
+            self.banking_network
+                .set_health_config(banking_api.health_polling.clone());
+            self.banking_network
+                .set_settlement_rail(std::sync::Arc::new(
+                    banking_network::settlement::HttpSettlementRail::new(
+                        std::time::Duration::from_secs(banking_api.timeout),
+                    ),
+                ));
+            self.banking_network
+                .settlement_engine_mut()
+                .set_failure_threshold(banking_api.settlement_failure_threshold);
+        }
+        self.account_manager
+            .set_transaction_limits(config.security.transaction_limits.clone());
+        self.fraud_detector
+            .set_auto_freeze_policy(config.security.fraud_auto_freeze.clone());
+        self.fraud_detector
+            .set_anomaly_detector(security::AnomalyDetector::from_config(
+                &config.security.fraud_model,
+            ));
+        self.fee_calculator = fee_calculator::FeeCalculator::from_config(config);
+        self.payment_processor
+            .set_fee_calculator(self.fee_calculator);
+        // Fees already queued are posted to the account they were charged for
+        self.post_collected_fees()?;
+        (self.fee_collector, self.fee_postings) =
+            FeeCollector::new(config.fees.fee_account.clone());
+        self.payment_processor
+            .set_fee_collector(self.fee_collector.clone());
+        self.banking_network
+            .settlement_engine_mut()
+            .set_fee_calculator(self.fee_calculator);
+        self.transaction_manager
+            .set_fee_market_config(config.fees.market.clone());
+        {
+            let database = database::Database::connect_lazy(&config.database)?;
+            let mut conversion = self.conversion.write().await;
+            conversion.set_fee_calculator(self.fee_calculator);
+            conversion.set_fee_collector(self.fee_collector.clone());
+            conversion.set_conversion_store(std::sync::Arc::new(
+                database::repositories::ConversionRepository::new(database.write_pool().clone()),
+            ));
+        }
+        self.banking_network
+            .settlement_engine_mut()
+            .set_conversion_service(self.conversion.clone());
+        self.banking_network
+            .settlement_engine_mut()
+            .configure(&config.settlement)?;
+        if let Some(path) = &config.database.ledger_spill_path {
+            let store = ledger_store::FileLedgerStore::open(path)?;
+            self.ledger.set_spill_store(
+                Box::new(store),
+                config.database.ledger_max_in_memory_entries,
+            )?;
+        }
+        self.ledger
+            .set_invariant_checks(InvariantCheckMode::from_config(config));
+        self.scheduled.dormancy_period = config
+            .compliance
+            .account_dormancy_days
+            .map(|days| chrono::Duration::days(days.into()));
+        self.transaction_manager
+            .set_retention_days(config.compliance.data_retention_days);
+        if let Some(path) = &config.compliance.transaction_archive_path {
+            self.scheduled.transaction_archive =
+                Some(Box::new(transactions::FileTransactionArchive::open(path)?));
+        }
+        self.scheduled.reserve_interest_interval = config
+            .monetary_policy
+            .reserve_interest_interval_hours
+            .map(|hours| chrono::Duration::hours(hours.into()));
+
+        // Sensitive customer data is encrypted, and every read of it audited
+        let mut encryption = security::EncryptionManager::new(&config.security.encryption_key)?;
+        encryption.set_rotation_period(chrono::Duration::days(
+            config.security.key_rotation_days.into(),
+        ));
+        encryption.set_data_access_auditor(self.monitoring.data_access_auditor());
+        self.regulatory_compliance
+            .set_document_encryption(std::sync::Arc::new(tokio::sync::RwLock::new(encryption)));
+        {
+            let mut certificate_authority = self.certificate_authority.write().await;
+            certificate_authority.configure_crl(&config.crl)?;
+            certificate_authority.configure_ocsp(&config.ocsp)?;
+        }
+        if let Some(notifications) = &config.external_services.notification_service {
+            self.set_notifier(std::sync::Arc::new(receipts::ChannelNotifier::new(
+                notifications.email.clone(),
+                notifications.sms.clone(),
+            )?));
+            let service = std::sync::Arc::new(receipts::ReceiptService::from_config(
+                self.system_signer.clone(),
+                self.notifier.clone(),
+                notifications.receipts.clone(),
+            )?);
+            self.transaction_manager.add_observer(service.clone());
+            self.receipts = Some(service);
+        }
+        Ok(())
+    }
+
+    /// Deliver balance alerts and transfer receipts through `notifier`
+    pub fn set_notifier(&mut self, notifier: std::sync::Arc<dyn accounts::Notifier>) {
+        self.account_manager.set_notifier(notifier.clone());
+        if let Some(receipts) = &self.receipts {
+            receipts.set_notifier(notifier.clone());
+        }
+        self.notifier = notifier;
+    }
+
+    /// Transfer receipts, for account holders to opt in to; `None` unless
+    /// notifications are configured
+    pub fn receipts(&self) -> Option<&std::sync::Arc<receipts::ReceiptService>> {
+        self.receipts.as_ref()
+    }
+
+    /// Start delivering queued transfer receipts on the configured interval
+    pub fn spawn_receipt_delivery(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let receipts = self.receipts.clone()?;
+        let interval = receipts.delivery_interval();
+        Some(receipts.spawn_delivery_task(interval))
+    }
+
+    /// Fee calculator to give conversion services and bridges created
+    /// outside the system, so their fees round like every other
+    pub fn fee_calculator(&self) -> fee_calculator::FeeCalculator {
+        self.fee_calculator
+    }
+
+    /// Fee collector to give bridges created outside the system, so their
+    /// fees reach the ledger
+    pub fn fee_collector(&self) -> FeeCollector {
+        self.fee_collector.clone()
+    }
+
+    /// Record in the ledger the fees charged since the last call, returning
+    /// how many were recorded
+    pub fn post_collected_fees(&mut self) -> Result<usize, AstorError> {
+        self.fee_postings.post_to(&mut self.ledger)
+    }
+
+    /// Start polling bank endpoints, releasing deferred settlements as banks
+    /// recover
+    ///
+    /// Call after configuring the banking network; the polling task works
+    /// on a clone of it.
+    pub fn spawn_bank_health_polling(&self) -> tokio::task::JoinHandle<()> {
+        BankingNetwork::spawn_health_polling(std::sync::Arc::new(self.banking_network.clone()))
+    }
+
+    /// Open accounts in bulk on an administrator's behalf
+    ///
+    /// Opening balances are recorded in the ledger as issuances by
+    /// `admin_id`; see `AccountManager::import_accounts` for how invalid rows
+    /// are handled.
+    pub fn import_accounts(
+        &mut self,
+        admin_id: &str,
+        specs: Vec<AccountSpec>,
+        mode: ImportMode,
+    ) -> Result<ImportResult, AstorError> {
+        self.admin_manager.get_admin(admin_id)?;
+
+        let ledger = &mut self.ledger;
+        Ok(self
+            .account_manager
+            .import_accounts(specs, mode, |account_id, amount| {
+                ledger.record_issuance(
+                    format!("import-{}", account_id),
+                    admin_id,
+                    account_id,
+                    amount,
+                )
+            }))
+    }
+
     /// Issue new Astor units (admin only)
     pub async fn issue_currency(
         &mut self,
@@ -168,6 +482,10 @@ impl AstorSystem {
         amount: u64,
         admin_signature: &Signature,
     ) -> Result<String, AstorError> {
+        self.account_manager
+            .get_account(recipient_account)?
+            .ensure_active()?;
+
         self.monitoring
             .record_business_metric(monitoring::BusinessMetric::CurrencyIssued {
                 amount: amount as i64,
@@ -176,6 +494,7 @@ impl AstorSystem {
             .await;
 
         let decision_id = self.central_bank.issue_currency(
+            central_bank::DEFAULT_CURRENCY,
             amount,
             format!(
                 "Currency issued by admin {} to account {}",
@@ -189,6 +508,563 @@ impl AstorSystem {
         ))
     }
 
+    /// Move funds on the holder's signed authorization, recording the
+    /// transfer under `tx_id` in the transaction manager and the ledger
+    ///
+    /// See `AccountManager::transfer_signed` for what the holder signs. The
+    /// sender pays the current base fee, refused with `FeeTooLow` when above
+    /// `max_fee`. A transfer that is refused once submitted is recorded as
+    /// failed, so `tx_id` cannot be reused.
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_signed(
+        &mut self,
+        from: &str,
+        to: &str,
+        amount: u64,
+        max_fee: u64,
+        nonce: u64,
+        tx_id: &str,
+        signature: &Signature,
+    ) -> Result<String, AstorError> {
+        self.transaction_manager
+            .create_transfer_with_id(tx_id, from, to, amount, max_fee)?;
+        let fee_account = self.fee_collector.fee_account().to_string();
+
+        let result = self
+            .account_manager
+            .verify_transfer_authorization(from, to, amount, nonce, tx_id, signature)
+            .and_then(|()| self.screen_transfer(from, amount))
+            .and_then(|()| {
+                self.account_manager
+                    .transfer_signed(from, to, amount, nonce, tx_id, signature)
+            })
+            .and_then(|()| {
+                let transaction =
+                    self.transaction_manager
+                        .get_transaction(tx_id)
+                        .ok_or_else(|| {
+                            AstorError::InvalidOperation(format!("Transaction {} not found", tx_id))
+                        })?;
+                Self::settle_transfer(
+                    &mut self.ledger,
+                    &self.account_manager,
+                    &fee_account,
+                    transaction,
+                )
+            });
+
+        match result {
+            Ok(()) => {
+                self.transaction_manager.confirm_transaction(tx_id)?;
+                self.record_transfer_for_fraud_scoring(from, amount);
+                Ok(tx_id.to_string())
+            }
+            Err(e) => {
+                self.transaction_manager
+                    .fail_transaction(tx_id, e.to_string())?;
+                Err(e)
+            }
+        }
+    }
+
+    /// One pass of the periodic work the system does on its own
+    pub fn run_scheduled_tasks(&mut self) {
+        let completed = self.process_pending_transactions(PENDING_TRANSACTIONS_PER_TICK);
+        if completed > 0 {
+            tracing::debug!("Scheduler settled {} queued transactions", completed);
+        }
+        let expired = self.expire_unacknowledged_transfers();
+        if !expired.is_empty() {
+            tracing::info!(
+                "Scheduler reversed {} transfers the recipient never acknowledged",
+                expired.len()
+            );
+        }
+        let recurring = self.process_recurring_transfers();
+        if !recurring.executed.is_empty() || !recurring.skipped.is_empty() {
+            tracing::debug!(
+                "Scheduler executed {} recurring transfers and skipped {}",
+                recurring.executed.len(),
+                recurring.skipped.len()
+            );
+        }
+        let now = chrono::Utc::now();
+        self.sweep_dormant_accounts(now);
+        self.prune_transactions_if_due(now);
+        self.pay_reserve_interest_if_due(now);
+        self.execute_due_issuance_tranches(now);
+        self.record_committed_blocks();
+        if let Err(e) = self.post_collected_fees() {
+            tracing::error!("Could not post collected fees to the ledger: {}", e);
+        }
+    }
+
+    /// Adjust the base fee and apply to the ledger each block committed
+    /// since the last tick
+    fn record_committed_blocks(&mut self) {
+        use tokio::sync::broadcast::error::TryRecvError;
+
+        let Some(committed) = self.scheduled.committed_blocks.as_mut() else {
+            return;
+        };
+        let mut blocks = Vec::new();
+        loop {
+            match committed.try_recv() {
+                Ok(block) => blocks.push(block),
+                Err(TryRecvError::Lagged(missed)) => {
+                    tracing::warn!(
+                        "Missed {} committed blocks; base fee may be stale and later blocks will not extend the ledger",
+                        missed
+                    );
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => {
+                    self.scheduled.committed_blocks = None;
+                    break;
+                }
+            }
+        }
+
+        for block in blocks {
+            let base_fee = self
+                .transaction_manager
+                .record_block(block.transactions.len());
+            tracing::debug!("Base fee is {} after block {}", base_fee, block.sequence);
+            if let Err(e) = self.apply_committed_block(&block) {
+                tracing::error!("Ledger rejected block {}: {}", block.sequence, e);
+            }
+        }
+    }
+
+    /// Apply a consensus block to the ledger, and the blocks that became
+    /// final to the local accounts
+    ///
+    /// Transactions settled on this node are already in the ledger and are
+    /// skipped. A different block at a height already applied reverts the
+    /// non-final blocks from that height on. Account balances, which spend
+    /// checks use, only change once a block is `finality_depth` blocks deep.
+    fn apply_committed_block(
+        &mut self,
+        block: &network::consensus::Block,
+    ) -> Result<(), AstorError> {
+        let block_hash = block.hash()?;
+        if block.sequence <= self.ledger.get_tip_height() {
+            let already_applied = self.ledger.get_pending_blocks().any(|pending| {
+                pending.height == block.sequence && pending.block_hash == block_hash
+            });
+            if already_applied {
+                return Ok(());
+            }
+            let reverted = self.ledger.handle_reorg(block.sequence - 1)?;
+            tracing::warn!(
+                "Block {} at height {} replaced {} non-final blocks",
+                block_hash,
+                block.sequence,
+                reverted.len()
+            );
+        }
+
+        let entries = block
+            .transactions
+            .iter()
+            .filter(|tx| self.transaction_manager.get_transaction(&tx.id).is_none())
+            .filter_map(block_entry)
+            .collect();
+        for final_block in self
+            .ledger
+            .apply_block(block.sequence, block_hash, entries)?
+        {
+            for entry in &final_block.entries {
+                self.apply_final_entry(entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Move a final block entry's funds between the local accounts it names
+    fn apply_final_entry(&self, entry: &ledger::LedgerEntryType) {
+        let (debit, credit, amount) = match entry {
+            ledger::LedgerEntryType::Issuance {
+                recipient, amount, ..
+            } => (None, recipient, *amount),
+            ledger::LedgerEntryType::Transfer {
+                from, to, amount, ..
+            } => (Some(from), to, *amount),
+            _ => return,
+        };
+
+        let accounts = &self.account_manager;
+        let result = debit
+            .filter(|account_id| accounts.account_exists(account_id))
+            .map_or(Ok(()), |account_id| {
+                accounts.debit_account(account_id, amount)
+            })
+            .and_then(|()| {
+                if accounts.account_exists(credit) {
+                    accounts.credit_account(credit, amount)
+                } else {
+                    Ok(())
+                }
+            });
+        if let Err(e) = result {
+            tracing::error!(
+                "Final transaction {} could not be applied to local accounts: {}",
+                entry.transaction_id().unwrap_or_default(),
+                e
+            );
+        }
+    }
+
+    /// Pay interest on reserves once the configured interval has passed
+    /// since the period last paid for
+    fn pay_reserve_interest_if_due(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        let Some(interval) = self.scheduled.reserve_interest_interval else {
+            return;
+        };
+        if now - self.central_bank.reserve_interest_paid_through() < interval {
+            return;
+        }
+
+        let payments = self.central_bank.pay_interest_on_reserves_at(now);
+        if !payments.is_empty() {
+            tracing::info!("Paid interest on reserves to {} banks", payments.len());
+        }
+    }
+
+    /// Mint the gradual issuance tranches that have fallen due
+    fn execute_due_issuance_tranches(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        match self.central_bank.execute_due_tranches(now) {
+            Ok(decision_ids) if !decision_ids.is_empty() => {
+                tracing::info!("Minted {} scheduled issuance tranches", decision_ids.len());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Scheduled issuance tranche failed: {}", e),
+        }
+    }
+
+    /// Archive settled transactions past the retention period, at most once
+    /// per prune interval
+    fn prune_transactions_if_due(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        let Some(archive) = self.scheduled.transaction_archive.as_mut() else {
+            return;
+        };
+        let interval = chrono::Duration::seconds(TRANSACTION_PRUNE_INTERVAL_SECS);
+        if self
+            .scheduled
+            .last_transaction_prune
+            .map_or(false, |last| now - last < interval)
+        {
+            return;
+        }
+
+        self.scheduled.last_transaction_prune = Some(now);
+        if let Err(e) = self
+            .transaction_manager
+            .prune_transactions(archive.as_mut())
+        {
+            tracing::error!("Archiving expired transactions failed: {}", e);
+        }
+    }
+
+    /// Mark accounts idle for the configured dormancy period as dormant, at
+    /// most once per sweep interval
+    fn sweep_dormant_accounts(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        let Some(period) = self.scheduled.dormancy_period else {
+            return;
+        };
+        let interval = chrono::Duration::seconds(DORMANCY_SWEEP_INTERVAL_SECS);
+        if self
+            .scheduled
+            .last_dormancy_sweep
+            .map_or(false, |last| now - last < interval)
+        {
+            return;
+        }
+
+        self.scheduled.last_dormancy_sweep = Some(now);
+        self.account_manager.mark_dormant_accounts(now - period);
+    }
+
+    /// Run `run_scheduled_tasks` every `interval` until the task is aborted
+    ///
+    /// Once the network is deployed, each tick first refreshes the sync
+    /// state, so nothing settles while the node is behind.
+    pub fn spawn_scheduler(
+        system: std::sync::Arc<tokio::sync::Mutex<Self>>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let mut system = system.lock().await;
+                if let Some(sync_manager) = system.scheduled.sync_manager.clone() {
+                    system.refresh_sync_state_from(&sync_manager).await;
+                }
+                system.run_scheduled_tasks();
+            }
+        })
+    }
+
+    /// Scheduler tick: execute up to `limit` queued transactions, highest
+    /// priority first, returning how many completed
+    ///
+    /// Nothing runs while the node is syncing.
+    pub fn process_pending_transactions(&mut self, limit: usize) -> usize {
+        if self.transaction_manager.is_node_syncing() {
+            return 0;
+        }
+
+        let fee_account = self.fee_collector.fee_account().to_string();
+        let mut completed = 0;
+        for _ in 0..limit {
+            let Some(tx_id) = self.transaction_manager.next_transaction_to_process() else {
+                break;
+            };
+            let Some(transaction) = self.transaction_manager.get_transaction(&tx_id).cloned()
+            else {
+                continue;
+            };
+            let transaction_type = &transaction.transaction_type;
+
+            let result = match transaction_type {
+                transactions::TransactionType::Transfer { from, to, amount } => self
+                    .screen_transfer(from, *amount)
+                    .and_then(|()| self.account_manager.transfer(from, to, *amount, false))
+                    .and_then(|()| {
+                        Self::settle_transfer(
+                            &mut self.ledger,
+                            &self.account_manager,
+                            &fee_account,
+                            &transaction,
+                        )
+                    }),
+                transactions::TransactionType::Issuance {
+                    issuer,
+                    recipient,
+                    amount,
+                } => self
+                    .account_manager
+                    .credit_account(recipient, *amount)
+                    .and_then(|()| {
+                        self.ledger
+                            .record_issuance(tx_id.clone(), issuer, recipient, *amount)
+                            .map_err(|e| {
+                                if let Err(undo) =
+                                    self.account_manager.debit_account(recipient, *amount)
+                                {
+                                    tracing::error!(
+                                        "Could not undo issuance {} after ledger failure: {}",
+                                        tx_id,
+                                        undo
+                                    );
+                                }
+                                e
+                            })
+                    }),
+                transactions::TransactionType::Conversion { .. } => {
+                    Err(AstorError::InvalidOperation(
+                        "Conversions are settled by the conversion service".to_string(),
+                    ))
+                }
+            };
+
+            if let (Ok(()), transactions::TransactionType::Transfer { from, amount, .. }) =
+                (&result, transaction_type)
+            {
+                self.record_transfer_for_fraud_scoring(from, *amount);
+            }
+            let settled = match result {
+                Ok(()) => self
+                    .transaction_manager
+                    .confirm_transaction(&tx_id)
+                    .map(|()| completed += 1),
+                Err(e) => self
+                    .transaction_manager
+                    .fail_transaction(&tx_id, e.to_string()),
+            };
+            if let Err(e) = settled {
+                tracing::error!("Could not settle transaction {}: {}", tx_id, e);
+            }
+        }
+        completed
+    }
+
+    /// Record a transfer already applied to the accounts, moving the funds
+    /// back if the ledger write fails so balances stay in step with it
+    ///
+    /// Takes the ledger and accounts rather than `self` so it can run inside
+    /// callbacks that borrow the transaction manager.
+    fn record_transfer_or_undo(
+        ledger: &mut Ledger,
+        accounts: &AccountManager,
+        tx_id: &str,
+        from: &str,
+        to: &str,
+        amount: u64,
+    ) -> Result<(), AstorError> {
+        ledger
+            .record_transfer(tx_id.to_string(), from, to, amount)
+            .map_err(|e| {
+                Self::undo_transfer(accounts, tx_id, from, to, amount);
+                e
+            })
+    }
+
+    /// Record a transfer already applied to the accounts and charge the
+    /// sender its base fee, crediting the fee account
+    ///
+    /// If the fee cannot be paid or the transfer cannot be recorded, the
+    /// funds are moved back and the transfer fails. Takes the ledger and
+    /// accounts rather than `self`, like `record_transfer_or_undo`.
+    fn settle_transfer(
+        ledger: &mut Ledger,
+        accounts: &AccountManager,
+        fee_account: &str,
+        transaction: &transactions::Transaction,
+    ) -> Result<(), AstorError> {
+        let transactions::TransactionType::Transfer { from, to, amount } =
+            &transaction.transaction_type
+        else {
+            return Err(AstorError::InvalidOperation(format!(
+                "Transaction {} is not a transfer",
+                transaction.id
+            )));
+        };
+        let (tx_id, fee) = (transaction.id.as_str(), transaction.fee);
+
+        if fee > 0 {
+            if let Err(e) = accounts.transfer(from, fee_account, fee, true) {
+                Self::undo_transfer(accounts, tx_id, from, to, *amount);
+                return Err(e);
+            }
+        }
+        if let Err(e) = Self::record_transfer_or_undo(ledger, accounts, tx_id, from, to, *amount) {
+            if fee > 0 {
+                Self::undo_transfer(accounts, tx_id, from, fee_account, fee);
+            }
+            return Err(e);
+        }
+        if fee == 0 {
+            return Ok(());
+        }
+
+        // The transfer is in the ledger by now, so a fee that cannot be
+        // recorded is given back rather than failing it
+        if let Err(e) = ledger.record_transfer(format!("{}-fee", tx_id), from, fee_account, fee) {
+            tracing::error!("Base fee for {} refunded, ledger refused it: {}", tx_id, e);
+            Self::undo_transfer(accounts, tx_id, from, fee_account, fee);
+            return Ok(());
+        }
+        if let Err(e) = ledger.record_fee_collection(
+            tx_id.to_string(),
+            FeeSource::Transfer,
+            from,
+            fee_account,
+            central_bank::DEFAULT_CURRENCY,
+            fee,
+        ) {
+            tracing::error!("Could not record base fee collection for {}: {}", tx_id, e);
+        }
+        Ok(())
+    }
+
+    /// Move funds back after a transfer could not be completed
+    fn undo_transfer(accounts: &AccountManager, tx_id: &str, from: &str, to: &str, amount: u64) {
+        if let Err(undo) = accounts.transfer(to, from, amount, false) {
+            tracing::error!(
+                "Could not undo transfer {} after ledger failure: {}",
+                tx_id,
+                undo
+            );
+        }
+    }
+
+    /// Score a transfer against the sender's history before it settles
+    ///
+    /// A score at the auto-freeze threshold freezes the sender's account
+    /// until an administrator unfreezes it; any other high-risk score
+    /// refuses just this transfer.
+    fn screen_transfer(&mut self, from: &str, amount: u64) -> Result<(), AstorError> {
+        let risk_score = self
+            .fraud_detector
+            .assess_risk(&transfer_pattern(from, amount))?;
+        if !self.fraud_detector.should_auto_freeze(&risk_score) {
+            if risk_score.is_high_risk() {
+                tracing::warn!(
+                    "Refused high-risk transfer of {} from {} (risk={:.2}, factors={:?})",
+                    amount,
+                    from,
+                    risk_score.score(),
+                    risk_score.factors()
+                );
+                return Err(AstorError::SecurityViolation(
+                    "High risk transfer refused".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        self.account_manager.freeze_for_review(
+            from,
+            security::FRAUD_AUTO_FREEZE_ADMIN_ID,
+            security::FRAUD_AUTO_FREEZE_REASON,
+        )?;
+        self.ledger.record_admin_action(
+            security::FRAUD_AUTO_FREEZE_ADMIN_ID.to_string(),
+            format!("freeze_account: {}", security::FRAUD_AUTO_FREEZE_REASON),
+            from.to_string(),
+        )?;
+        tracing::error!(
+            "ALERT: account {} auto-frozen for fraud review (transfer of {}, risk={:.2})",
+            from,
+            amount,
+            risk_score.score()
+        );
+        Err(AstorError::SecurityViolation(
+            "Account frozen pending fraud review".to_string(),
+        ))
+    }
+
+    /// Add a completed transfer to the sender's history for fraud scoring
+    fn record_transfer_for_fraud_scoring(&mut self, from: &str, amount: u64) {
+        self.fraud_detector
+            .record_transaction(transfer_pattern(from, amount));
+    }
+
+    /// Scheduler tick: execute standing orders that have fallen due
+    pub fn process_recurring_transfers(&mut self) -> transactions::RecurringRunReport {
+        let ledger = &mut self.ledger;
+        let accounts = &self.account_manager;
+        let fee_account = self.fee_collector.fee_account();
+        self.transaction_manager.process_due_recurring(
+            chrono::Utc::now(),
+            accounts,
+            |transaction| Self::settle_transfer(ledger, accounts, fee_account, transaction),
+        )
+    }
+
+    /// Finalize a transfer on the recipient's signed acknowledgment
+    pub fn acknowledge_receipt(
+        &mut self,
+        tx_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        let ledger = &mut self.ledger;
+        let accounts = &self.account_manager;
+        let fee_account = self.fee_collector.fee_account();
+        self.transaction_manager
+            .acknowledge_receipt(tx_id, signature, accounts, |transaction| {
+                Self::settle_transfer(ledger, accounts, fee_account, transaction)
+            })
+    }
+
+    /// Scheduler tick: reverse transfers whose recipient never acknowledged
+    pub fn expire_unacknowledged_transfers(&mut self) -> Vec<String> {
+        self.transaction_manager
+            .expire_unacknowledged(chrono::Utc::now(), &self.account_manager)
+    }
+
     /// Register a commercial bank
     pub fn register_commercial_bank(
         &mut self,
@@ -200,6 +1076,68 @@ impl AstorSystem {
         Ok(())
     }
 
+    /// Transfer between accounts holding different currencies
+    ///
+    /// The sender is debited in `from_currency`, the recipient credited in
+    /// `to_currency` at the quoted rate, and the ledger records the transfer
+    /// and its FX conversion under the returned transaction ID. The ledger
+    /// is checked before the accounts are touched, and the account changes
+    /// are reversed if the ledger still fails to record the transfer.
+    pub fn transfer_with_conversion(
+        &mut self,
+        from: &str,
+        to: &str,
+        from_currency: &str,
+        to_currency: &str,
+        amount: u64,
+        conversion: &conversion::ConversionService,
+    ) -> Result<String, AstorError> {
+        let quote = conversion.quote_conversion(
+            amount,
+            &from_currency.to_uppercase(),
+            &to_currency.to_uppercase(),
+        )?;
+        self.ledger
+            .check_cross_currency_transfer(from, to, from_currency, to_currency, &quote)?;
+
+        let result = self.account_manager.transfer_with_conversion(
+            from,
+            to,
+            from_currency,
+            to_currency,
+            amount,
+            conversion,
+        )?;
+
+        let transaction_id = uuid::Uuid::new_v4().to_string();
+        self.ledger
+            .record_cross_currency_transfer(
+                transaction_id.clone(),
+                from,
+                to,
+                from_currency,
+                to_currency,
+                &result,
+            )
+            .map_err(|e| {
+                if let Err(undo) = self.account_manager.reverse_transfer_with_conversion(
+                    from,
+                    to,
+                    from_currency,
+                    to_currency,
+                    &result,
+                ) {
+                    tracing::error!(
+                        "Could not undo conversion transfer {} after ledger failure: {}",
+                        transaction_id,
+                        undo
+                    );
+                }
+                e
+            })?;
+        Ok(transaction_id)
+    }
+
     /// Process payment through payment processor
     pub fn process_payment(
         &mut self,
@@ -218,17 +1156,92 @@ impl AstorSystem {
         )
     }
 
-    /// Perform KYC verification
-    pub fn perform_kyc(
+    /// Perform KYC verification, returning the outcome for each document
+    pub async fn perform_kyc(
         &mut self,
         customer_id: String,
         documents: Vec<regulatory::IdentityDocument>,
         verification_level: regulatory::KycLevel,
+    ) -> Result<regulatory::KycVerification, AstorError> {
+        let verification = self
+            .regulatory_compliance
+            .perform_kyc_verification(customer_id.clone(), documents, verification_level)
+            .await?;
+
+        // Transaction limits follow the rating assigned at verification
+        if let Some(risk_rating) = self
+            .regulatory_compliance
+            .customer_risk_rating(&customer_id)
+        {
+            if let Ok(account) = self.account_manager.get_account(&customer_id) {
+                self.account_manager.set_account_profile(
+                    &customer_id,
+                    account.account_type,
+                    risk_rating,
+                )?;
+            }
+        }
+        Ok(verification)
+    }
+
+    /// Freeze an account on an administrator's signed authority
+    ///
+    /// The administrator signs `accounts::account_status_message("freeze",
+    /// ..)` for the account's current version.
+    pub fn freeze_account(
+        &mut self,
+        account_id: &str,
+        admin_id: &str,
+        reason: &str,
+        signature: &Signature,
     ) -> Result<(), AstorError> {
-        self.regulatory_compliance.perform_kyc_verification(
-            customer_id,
-            documents,
-            verification_level,
+        self.account_manager.freeze_account(
+            account_id,
+            &self.admin_manager,
+            admin_id,
+            reason,
+            signature,
+        )?;
+        self.ledger.record_admin_action(
+            admin_id.to_string(),
+            format!("freeze_account: {}", reason),
+            account_id.to_string(),
+        )
+    }
+
+    /// Lift an account freeze on an administrator's signed authority
+    pub fn unfreeze_account(
+        &mut self,
+        account_id: &str,
+        admin_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.account_manager.unfreeze_account(
+            account_id,
+            &self.admin_manager,
+            admin_id,
+            signature,
+        )?;
+        self.ledger.record_admin_action(
+            admin_id.to_string(),
+            "unfreeze_account".to_string(),
+            account_id.to_string(),
+        )
+    }
+
+    /// Close an empty account on an administrator's signed authority
+    pub fn close_account(
+        &mut self,
+        account_id: &str,
+        admin_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        self.account_manager
+            .close_account(account_id, &self.admin_manager, admin_id, signature)?;
+        self.ledger.record_admin_action(
+            admin_id.to_string(),
+            "close_account".to_string(),
+            account_id.to_string(),
         )
     }
 
@@ -238,6 +1251,14 @@ impl AstorSystem {
         network_manager: &NetworkManager,
     ) -> Result<(), AstorError> {
         network_manager.start().await?;
+        self.scheduled.committed_blocks = Some(
+            network_manager
+                .consensus
+                .read()
+                .await
+                .subscribe_committed_blocks(),
+        );
+        self.scheduled.sync_manager = Some(network_manager.sync_manager.clone());
         self.setup_network_handlers(network_manager).await?;
         tracing::info!("Astor currency network deployed successfully");
         Ok(())
@@ -256,6 +1277,26 @@ impl AstorSystem {
         network_manager.get_network_status().await
     }
 
+    /// Refresh the transaction sync gate from the network sync state
+    ///
+    /// While the node is syncing, new transactions are rejected or queued
+    /// according to the transaction manager's sync policy.
+    ///
+    /// The scheduler does this on every tick once the network is deployed.
+    pub async fn refresh_sync_state(&mut self, network_manager: &NetworkManager) -> bool {
+        self.refresh_sync_state_from(&network_manager.sync_manager)
+            .await
+    }
+
+    async fn refresh_sync_state_from(
+        &mut self,
+        sync_manager: &tokio::sync::RwLock<network::SyncManager>,
+    ) -> bool {
+        let is_synced = sync_manager.read().await.is_synced().await;
+        self.transaction_manager.set_node_syncing(!is_synced);
+        is_synced
+    }
+
     /// Register a commercial bank in the banking network
     pub async fn register_bank_in_network(
         &mut self,
@@ -281,6 +1322,44 @@ impl AstorSystem {
         self.banking_network.approve_bank(bank_id).await
     }
 
+    /// Propose suspending a bank, pending admin quorum approval
+    pub async fn propose_bank_suspension(
+        &self,
+        bank_id: &str,
+        reason: String,
+    ) -> Result<String, AstorError> {
+        self.banking_network
+            .propose_suspension(bank_id, reason)
+            .await
+    }
+
+    /// Approve a bank suspension proposal as an administrator
+    pub async fn approve_bank_suspension(
+        &self,
+        proposal_id: &str,
+        admin_id: &str,
+        signature: &Signature,
+    ) -> Result<bool, AstorError> {
+        let admin = self.admin_manager.get_admin(admin_id)?;
+        self.banking_network
+            .approve_suspension(proposal_id, admin, signature)
+            .await
+    }
+
+    /// Emergency-freeze a bank without quorum approval
+    pub async fn emergency_freeze_bank(
+        &self,
+        bank_id: &str,
+        admin_id: &str,
+        signature: &Signature,
+        reason: String,
+    ) -> Result<(), AstorError> {
+        let admin = self.admin_manager.get_admin(admin_id)?;
+        self.banking_network
+            .emergency_freeze_bank(bank_id, admin, signature, reason)
+            .await
+    }
+
     /// Get banking network statistics
     pub async fn get_banking_network_stats(&self) -> banking_network::NetworkStats {
         self.banking_network.get_network_stats().await
@@ -294,6 +1373,8 @@ impl AstorSystem {
         validity_days: u32,
     ) -> Result<Certificate, AstorError> {
         self.certificate_authority
+            .write()
+            .await
             .issue_certificate(csr, certificate_type, validity_days)
             .await
     }
@@ -305,18 +1386,494 @@ impl AstorSystem {
         reason: certificate_authority::crl::RevocationReason,
     ) -> Result<(), AstorError> {
         self.certificate_authority
+            .write()
+            .await
             .revoke_certificate(serial_number, reason)
             .await
     }
 
     /// Get root CA certificate for distribution
-    pub fn get_root_ca_certificate(&self) -> Certificate {
-        self.certificate_authority.get_root_certificate()
+    pub async fn get_root_ca_certificate(&self) -> Certificate {
+        self.certificate_authority
+            .read()
+            .await
+            .get_root_certificate()
+    }
+
+    /// Sign published attestations with a persistent or HSM-held key instead
+    /// of the key generated at startup
+    pub fn set_system_signer(&mut self, signer: std::sync::Arc<dyn Signer>) {
+        self.system_signer = signer;
+    }
+
+    /// Public key verifiers should check published attestations against
+    pub fn system_public_key(&self) -> ed25519_dalek::PublicKey {
+        self.system_signer.public_key()
+    }
+
+    /// Sign a snapshot of issued supply, bank reserves and the ledger root
+    pub fn generate_proof_of_reserve(
+        &self,
+    ) -> Result<central_bank::proof_of_reserve::SignedProofOfReserve, AstorError> {
+        let proof = central_bank::proof_of_reserve::ProofOfReserve::new(
+            &self.central_bank.get_money_supply_stats(),
+            self.ledger.merkle_root()?,
+            self.ledger.entry_count(),
+        );
+        let signed = proof.sign(self.system_signer.as_ref())?;
+
+        tracing::info!(
+            "Generated proof of reserve: supply {}, reserves {}, ledger root {}",
+            signed.proof.total_supply,
+            signed.proof.total_reserves,
+            signed.proof.ledger_root
+        );
+        Ok(signed)
     }
 
     /// Validate certificate chain
-    pub fn validate_certificate(&self, certificate: &Certificate) -> Result<bool, AstorError> {
+    pub async fn validate_certificate(
+        &self,
+        certificate: &Certificate,
+    ) -> Result<certificate_authority::ChainValidationResult, AstorError> {
         self.certificate_authority
+            .read()
+            .await
             .validate_certificate_chain(certificate)
     }
 }
+
+/// Fraud-scoring pattern for a transfer out of `from`
+fn transfer_pattern(from: &str, amount: u64) -> security::TransactionPattern {
+    security::TransactionPattern {
+        user_id: from.to_string(),
+        amount: i64::try_from(amount).unwrap_or(i64::MAX),
+        timestamp: chrono::Utc::now(),
+        ip_address: String::new(),
+        user_agent: String::new(),
+        transaction_type: "transfer".to_string(),
+    }
+}
+
+/// Ledger entry for a transaction carried in a consensus block
+///
+/// Conversions are settled by the conversion service, not from blocks.
+fn block_entry(transaction: &transactions::Transaction) -> Option<ledger::LedgerEntryType> {
+    match &transaction.transaction_type {
+        transactions::TransactionType::Issuance {
+            issuer,
+            recipient,
+            amount,
+        } => Some(ledger::LedgerEntryType::Issuance {
+            transaction_id: transaction.id.clone(),
+            issuer: issuer.clone(),
+            recipient: recipient.clone(),
+            amount: *amount,
+        }),
+        transactions::TransactionType::Transfer { from, to, amount } => {
+            Some(ledger::LedgerEntryType::Transfer {
+                transaction_id: transaction.id.clone(),
+                from: from.clone(),
+                to: to.clone(),
+                amount: *amount,
+            })
+        }
+        transactions::TransactionType::Conversion { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_system() -> AstorSystem {
+        AstorSystem::new_with_dependencies(
+            KeyPair::generate(),
+            config::MonitoringConfig::default(),
+            Vec::new(),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Account holding `balance` in both the account manager and the ledger
+    fn funded_account(system: &mut AstorSystem, key: Option<&KeyPair>, balance: u64) -> String {
+        let account_id = system
+            .account_manager
+            .create_account(key.map(KeyPair::public_key));
+        if balance > 0 {
+            system
+                .account_manager
+                .credit_account(&account_id, balance)
+                .unwrap();
+            system
+                .ledger
+                .record_issuance(format!("fund-{}", account_id), "root", &account_id, balance)
+                .unwrap();
+        }
+        account_id
+    }
+
+    fn block(
+        sequence: u64,
+        transactions: Vec<transactions::Transaction>,
+    ) -> network::consensus::Block {
+        network::consensus::Block {
+            sequence,
+            view: 0,
+            transactions,
+            previous_hash: String::new(),
+            merkle_root: String::new(),
+            timestamp: 0,
+            validator_signatures: std::collections::HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_committed_blocks_become_spendable_once_final() {
+        let mut system = test_system().await;
+        system.ledger = Ledger::with_finality_depth(2);
+        let (blocks, receiver) = tokio::sync::broadcast::channel(8);
+        system.scheduled.committed_blocks = Some(receiver);
+        let bob = funded_account(&mut system, None, 0);
+
+        // Another node issues to one of its accounts and pays bob from it
+        let mut remote = TransactionManager::new();
+        remote.create_issuance("root", "remote-alice", 500).unwrap();
+        remote.create_transfer("remote-alice", &bob, 200).unwrap();
+        blocks
+            .send(block(1, remote.get_all_transactions().to_vec()))
+            .unwrap();
+        blocks.send(block(2, Vec::new())).unwrap();
+        system.run_scheduled_tasks();
+        assert_eq!(system.ledger.get_pending_balance(&bob), 200);
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 0);
+
+        blocks.send(block(3, Vec::new())).unwrap();
+        system.run_scheduled_tasks();
+        assert_eq!(system.ledger.get_finalized_height(), 1);
+        assert_eq!(system.ledger.get_account_balance(&bob), 200);
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 200);
+
+        // A competing block at a height still pending replaces it
+        let mut remote = TransactionManager::new();
+        remote.create_transfer("remote-alice", &bob, 50).unwrap();
+        blocks
+            .send(block(4, remote.get_all_transactions().to_vec()))
+            .unwrap();
+        system.run_scheduled_tasks();
+        assert_eq!(system.ledger.get_pending_balance(&bob), 250);
+
+        blocks.send(block(4, Vec::new())).unwrap();
+        blocks.send(block(5, Vec::new())).unwrap();
+        blocks.send(block(6, Vec::new())).unwrap();
+        system.run_scheduled_tasks();
+        assert_eq!(system.ledger.get_finalized_height(), 4);
+        assert_eq!(system.ledger.get_pending_balance(&bob), 200);
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_high_risk_transfer_freezes_the_sender() {
+        let mut system = test_system().await;
+        system
+            .fraud_detector
+            .set_auto_freeze_policy(security::AutoFreezePolicy {
+                enabled: true,
+                threshold: 0.25,
+            });
+        let key = KeyPair::generate();
+        let alice = funded_account(&mut system, Some(&key), 1_000);
+        let bob = funded_account(&mut system, None, 0);
+
+        // A sender with no history scores 0.3
+        let message = accounts::transfer_message(&alice, &bob, 100, 0, "tx-1");
+        let result = system.transfer_signed(
+            &alice,
+            &bob,
+            100,
+            0,
+            0,
+            "tx-1",
+            &key.sign(message.as_bytes()),
+        );
+        assert!(matches!(result, Err(AstorError::SecurityViolation(_))));
+        let account = system.account_manager.get_account(&alice).unwrap();
+        assert_eq!(account.status, accounts::AccountStatus::Frozen);
+        assert_eq!(
+            account.freeze_reason.as_deref(),
+            Some(security::FRAUD_AUTO_FREEZE_REASON)
+        );
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 0);
+
+        // Queued transfers are screened too
+        let carol = funded_account(&mut system, None, 1_000);
+        system
+            .transaction_manager
+            .create_transfer(&carol, &bob, 100)
+            .unwrap();
+        assert_eq!(system.process_pending_transactions(10), 0);
+        assert_eq!(
+            system.account_manager.get_account(&carol).unwrap().status,
+            accounts::AccountStatus::Frozen
+        );
+
+        // A forged authorization is refused before scoring, so it cannot
+        // get someone else's account frozen
+        let dave = funded_account(&mut system, Some(&KeyPair::generate()), 1_000);
+        let forged = key.sign(accounts::transfer_message(&dave, &bob, 100, 0, "tx-2").as_bytes());
+        assert!(matches!(
+            system.transfer_signed(&dave, &bob, 100, 0, 0, "tx-2", &forged),
+            Err(AstorError::InvalidSignature)
+        ));
+        assert_eq!(
+            system.account_manager.get_account(&dave).unwrap().status,
+            accounts::AccountStatus::Active
+        );
+    }
+
+    #[tokio::test]
+    async fn test_anomalous_transfer_is_acted_on_before_it_settles() {
+        let mut system = test_system().await;
+        system
+            .fraud_detector
+            .set_auto_freeze_policy(security::AutoFreezePolicy {
+                enabled: true,
+                threshold: 0.3,
+            });
+        let alice = funded_account(&mut system, None, 100_000);
+        let bob = funded_account(&mut system, None, 0);
+
+        // Three weeks of small daily payments at this hour
+        let now = chrono::Utc::now();
+        for day in 1..=20 {
+            system
+                .fraud_detector
+                .record_transaction(security::TransactionPattern {
+                    timestamp: now - chrono::Duration::days(day),
+                    ..transfer_pattern(&alice, 100 + day as u64)
+                });
+        }
+
+        system
+            .transaction_manager
+            .create_transfer(&alice, &bob, 110)
+            .unwrap();
+        assert_eq!(system.process_pending_transactions(10), 1);
+
+        // Only the model's amount score lifts this one over the threshold
+        system
+            .transaction_manager
+            .create_transfer(&alice, &bob, 50_000)
+            .unwrap();
+        assert_eq!(system.process_pending_transactions(10), 0);
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 110);
+        assert_eq!(
+            system.account_manager.get_account(&alice).unwrap().status,
+            accounts::AccountStatus::Frozen
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_pays_base_fee_into_the_fee_account() {
+        let mut system = test_system().await;
+        system
+            .transaction_manager
+            .set_fee_market_config(fee_market::FeeMarketConfig {
+                min_base_fee: 5,
+                ..Default::default()
+            });
+        let alice = funded_account(&mut system, None, 1_000);
+        let bob = funded_account(&mut system, None, 0);
+
+        assert!(matches!(
+            system.transaction_manager.create_transfer_with_priority(
+                &alice,
+                &bob,
+                100,
+                transactions::TransactionPriority::Tip(4)
+            ),
+            Err(AstorError::FeeTooLow {
+                offered: 4,
+                required: 5
+            })
+        ));
+        system
+            .transaction_manager
+            .create_transfer_with_priority(
+                &alice,
+                &bob,
+                100,
+                transactions::TransactionPriority::Tip(20),
+            )
+            .unwrap();
+        assert_eq!(system.process_pending_transactions(10), 1);
+
+        let fee_account = config::DEFAULT_FEE_ACCOUNT;
+        assert_eq!(system.account_manager.get_balance(&alice).unwrap(), 895);
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 100);
+        assert_eq!(system.account_manager.get_balance(fee_account).unwrap(), 5);
+        assert_eq!(system.ledger.get_account_balance(&alice), 895);
+        assert_eq!(system.ledger.get_account_balance(fee_account), 5);
+        assert_eq!(
+            system
+                .ledger
+                .fees_collected(fee_account, central_bank::DEFAULT_CURRENCY)
+                .unwrap(),
+            5
+        );
+
+        // A sender who cannot cover the fee keeps the whole balance
+        let carol = funded_account(&mut system, None, 100);
+        system
+            .transaction_manager
+            .create_transfer(&carol, &bob, 100)
+            .unwrap();
+        assert_eq!(system.process_pending_transactions(10), 0);
+        assert_eq!(system.account_manager.get_balance(&carol).unwrap(), 100);
+        assert_eq!(system.ledger.get_account_balance(&carol), 100);
+    }
+
+    #[tokio::test]
+    async fn test_imported_opening_balances_are_issued_in_the_ledger() {
+        let mut system = test_system().await;
+        let spec = AccountSpec {
+            account_id: Some("imported".to_string()),
+            public_key: None,
+            initial_balance: 300,
+        };
+
+        assert!(system
+            .import_accounts("nobody", vec![spec.clone()], ImportMode::AllOrNothing)
+            .is_err());
+        let result = system
+            .import_accounts("root", vec![spec], ImportMode::AllOrNothing)
+            .unwrap();
+
+        assert!(result.is_complete());
+        assert_eq!(system.account_manager.get_balance("imported").unwrap(), 300);
+        assert_eq!(system.ledger.get_account_balance("imported"), 300);
+        assert_eq!(system.ledger.get_total_supply(), 300);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_mints_due_issuance_tranches() {
+        let mut system = test_system().await;
+        let schedule_id = system
+            .central_bank
+            .schedule_gradual_issuance(1_000, 500, 2, "Gradual expansion".to_string())
+            .unwrap();
+
+        system.run_scheduled_tasks();
+        system.run_scheduled_tasks();
+
+        let schedule = system
+            .central_bank
+            .get_issuance_schedule(&schedule_id)
+            .unwrap();
+        assert_eq!(schedule.tranches.len(), 1);
+        assert_eq!(
+            system
+                .central_bank
+                .get_money_supply(central_bank::DEFAULT_CURRENCY),
+            500
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fees_charged_by_services_reach_the_ledger() {
+        let mut system = test_system().await;
+        system
+            .fee_collector()
+            .collect(
+                "bridge-1".to_string(),
+                FeeSource::Bridge,
+                "alice",
+                "ASTOR",
+                25,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            system
+                .ledger
+                .fees_collected(config::DEFAULT_FEE_ACCOUNT, "ASTOR")
+                .unwrap(),
+            0
+        );
+
+        system.run_scheduled_tasks();
+        assert_eq!(
+            system
+                .ledger
+                .fees_collected(config::DEFAULT_FEE_ACCOUNT, "ASTOR")
+                .unwrap(),
+            25
+        );
+    }
+
+    #[tokio::test]
+    async fn test_acknowledged_transfer_settles_once_through_the_scheduler() {
+        let mut system = test_system().await;
+        let recipient_key = KeyPair::generate();
+        let alice = funded_account(&mut system, None, 1_000);
+        let bob = funded_account(&mut system, Some(&recipient_key), 0);
+
+        let tx_id = system
+            .transaction_manager
+            .create_acknowledged_transfer(
+                &alice,
+                &bob,
+                400,
+                0,
+                chrono::Duration::hours(1),
+                &system.account_manager,
+            )
+            .unwrap();
+
+        // The scheduler leaves it alone until the recipient acknowledges
+        system.run_scheduled_tasks();
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 0);
+        assert_eq!(system.account_manager.get_balance(&alice).unwrap(), 1_000);
+
+        let message = accounts::receipt_acknowledgment_message(&tx_id);
+        system
+            .acknowledge_receipt(&tx_id, &recipient_key.sign(message.as_bytes()))
+            .unwrap();
+        system.run_scheduled_tasks();
+        assert_eq!(system.account_manager.get_balance(&alice).unwrap(), 600);
+        assert_eq!(system.account_manager.get_balance(&bob).unwrap(), 400);
+        assert_eq!(system.ledger.get_account_balance(&alice), 600);
+        assert_eq!(system.ledger.get_account_balance(&bob), 400);
+
+        // One whose window has closed is reversed on the next tick
+        let lapsed = system
+            .transaction_manager
+            .create_acknowledged_transfer(
+                &alice,
+                &bob,
+                100,
+                0,
+                chrono::Duration::zero(),
+                &system.account_manager,
+            )
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        system.run_scheduled_tasks();
+        assert!(matches!(
+            system
+                .transaction_manager
+                .get_transaction_status(&lapsed)
+                .unwrap(),
+            transactions::TransactionStatus::Reversed(_)
+        ));
+        assert_eq!(
+            system
+                .account_manager
+                .get_available_balance(&alice)
+                .unwrap(),
+            600
+        );
+        assert_eq!(system.ledger.get_account_balance(&bob), 400);
+    }
+}
@@ -1,8 +1,18 @@
 //! CLI interface for the Astor digital currency system
 
 use astor_currency::{
-    network::NodeConfig, AstorSystem, CentralBankCli, CliHandler, KeyPair, NetworkManager,
+    accounts,
+    cli::output::{
+        self, AccountCreatedOutput, AccountIssuanceOutput, AdminListOutput, ApiServerStartedOutput,
+        BalanceOutput, BankApprovedOutput, BankListOutput, BankRegisteredOutput, ErrorOutput,
+        LedgerVerificationOutput, NodeDeployedOutput, OutputFormat, SystemInitializedOutput,
+        SystemStatsOutput, TransferOutput,
+    },
+    network::{CodecKind, NodeConfig, ReconnectPolicy},
+    AstorCertificateAuthority, AstorError, AstorSystem, CentralBankCli, CliHandler, KeyPair,
+    NetworkManager, Signature,
 };
+use base64::{engine::general_purpose, Engine as _};
 use clap::{Parser, Subcommand};
 use std::net::SocketAddr;
 
@@ -12,6 +22,10 @@ use std::net::SocketAddr;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Print results as decorated text or machine-readable JSON
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -37,8 +51,11 @@ enum Commands {
         recipient: String,
         #[arg(short, long)]
         amount: u64,
+        /// Open the recipient account if it does not exist yet
+        #[arg(long)]
+        create_if_missing: bool,
     },
-    /// Transfer Astor between accounts
+    /// Transfer Astor between accounts on the holder's signature
     Transfer {
         #[arg(short, long)]
         from: String,
@@ -46,9 +63,32 @@ enum Commands {
         to: String,
         #[arg(short, long)]
         amount: u64,
+        /// Most the sender will pay in fees; defaults to the current base fee
+        #[arg(long)]
+        max_fee: Option<u64>,
+        /// Sender's transfer nonce; defaults to the account's next nonce
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Transaction ID the signature covers; generated if omitted
+        #[arg(long)]
+        tx_id: Option<String>,
+        /// File holding the sender's base64 secret key, used to sign the transfer
+        #[arg(
+            long,
+            conflicts_with = "signature",
+            required_unless_present = "signature"
+        )]
+        key_file: Option<String>,
+        /// Base64 signature over the transfer message, made offline by the holder
+        #[arg(long, requires_all = ["nonce", "tx_id"])]
+        signature: Option<String>,
     },
     /// Create a new account
-    CreateAccount,
+    CreateAccount {
+        /// Base64 Ed25519 public key; a new key pair is generated if omitted
+        #[arg(long)]
+        public_key: Option<String>,
+    },
     /// Check account balance
     Balance {
         #[arg(short, long)]
@@ -109,17 +149,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::init();
 
     let cli = Cli::parse();
+    let format = cli.output;
 
-    // For demo purposes, create a system with a root admin
+    // Signs for `root` only until the configuration names the real
+    // administrator keys
     let root_keypair = KeyPair::generate();
 
-    let monitoring_config = astor_currency::config::MonitoringConfig::default();
-    let mut system = AstorSystem::new(root_keypair.clone(), monitoring_config).await?;
+    let config = astor_currency::config::Config::load().unwrap_or_else(|e| {
+        tracing::warn!("Falling back to the default configuration: {}", e);
+        astor_currency::config::Config::default()
+    });
+    let mut system = AstorSystem::new(root_keypair.clone(), config.monitoring.clone()).await?;
+    system.apply_config(&config).await?;
 
     match cli.command {
         Commands::Init => {
-            println!("✅ Astor system initialized successfully!");
-            println!("Root admin public key: {:?}", root_keypair.public_key());
+            output::print(
+                format,
+                &SystemInitializedOutput {
+                    root_public_key: general_purpose::STANDARD
+                        .encode(root_keypair.public_key().as_bytes()),
+                },
+            )?;
         }
 
         Commands::DeployNode {
@@ -128,7 +179,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             network_id,
             max_peers,
         } => {
-            println!("🚀 Deploying Astor network node...");
+            tracing::info!("Deploying Astor network node on {}", listen_addr);
 
             let node_config = NodeConfig {
                 node_id: uuid::Uuid::new_v4().to_string(),
@@ -136,36 +187,81 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 bootstrap_peers,
                 keypair: KeyPair::generate(),
                 max_peers,
-                network_id,
+                network_id: network_id.clone(),
+                reconnect: ReconnectPolicy::default(),
+                finality_depth: 6,
+                protocol_codec: CodecKind::default(),
+                max_block_transactions: 1_000,
+                max_block_bytes: 1024 * 1024,
+                seal_pending_threshold: 1_000,
+                seal_interval_secs: 5,
             };
 
-            let (mut system, network_manager) =
-                AstorSystem::new_with_network(root_keypair.clone(), monitoring_config, node_config)
-                    .await?;
+            let (mut system, network_manager) = AstorSystem::new_with_network(
+                root_keypair.clone(),
+                config.monitoring.clone(),
+                node_config,
+            )
+            .await?;
+            system.apply_config(&config).await?;
 
             // Deploy the network
             system.deploy_network(&network_manager).await?;
-
-            println!("✅ Network node deployed successfully!");
-            println!("Node listening on: {}", listen_addr);
-            println!(
-                "Network ID: {}",
-                network_manager.get_network_status().await.node_id
-            );
+            let bank_health_polling = system.spawn_bank_health_polling();
+            let receipt_delivery = system.spawn_receipt_delivery();
+            let crl_publishing =
+                AstorCertificateAuthority::spawn_crl_task(system.certificate_authority.clone())
+                    .await;
+            let system = std::sync::Arc::new(tokio::sync::Mutex::new(system));
+            let scheduler =
+                AstorSystem::spawn_scheduler(system.clone(), std::time::Duration::from_secs(1));
+
+            output::print(
+                format,
+                &NodeDeployedOutput {
+                    node_id: network_manager.get_network_status().await.node_id,
+                    listen_addr: listen_addr.to_string(),
+                    network_id,
+                },
+            )?;
 
             // Keep the node running
-            println!("Press Ctrl+C to stop the node...");
             tokio::signal::ctrl_c().await?;
-            println!("Shutting down node...");
+            tracing::info!("Shutting down node");
+            scheduler.abort();
+            bank_health_polling.abort();
+            crl_publishing.abort();
+            if let Some(receipt_delivery) = receipt_delivery {
+                receipt_delivery.abort();
+            }
             network_manager.stop().await?;
         }
 
         Commands::CentralBank { cli } => {
-            println!("🏛️  Astor Central Bank Management");
-            println!("================================");
+            if format == OutputFormat::Text {
+                println!("🏛️  Astor Central Bank Management");
+                println!("================================");
+            }
 
-            let mut cli_handler = CliHandler::new(system.central_bank, system.banking_network);
-            cli_handler.handle_command(cli.command).await?;
+            let authorization = cli.authorization()?;
+            let mut cli_handler = CliHandler::new(
+                system.central_bank,
+                system.banking_network,
+                system.admin_manager,
+            );
+            cli_handler.set_output_format(format);
+            cli_handler.open_timelock_store(&cli.timelock_store)?;
+            cli_handler.open_authorization_store(&cli.authorization_store)?;
+            // Operations whose timelock ran out since the last invocation
+            for operation_id in cli_handler
+                .execute_due_operations(chrono::Utc::now())
+                .await?
+            {
+                tracing::info!("Executed timelocked operation {}", operation_id);
+            }
+            cli_handler
+                .handle_command(cli.command, authorization.as_ref())
+                .await?;
         }
 
         Commands::BankingNetwork { action } => match action {
@@ -185,28 +281,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     .register_bank_in_network(name.clone(), license, endpoint, public_key, services)
                     .await?;
 
-                println!("✅ Bank '{}' registered successfully!", name);
-                println!("Bank ID: {}", bank_id);
-                println!("Status: Under Review");
+                output::print(
+                    format,
+                    &BankRegisteredOutput {
+                        bank_id,
+                        bank_name: name,
+                        status: "Under Review".to_string(),
+                    },
+                )?;
             }
 
             BankingNetworkCommands::ListBanks => {
-                println!("🏦 Registered Banks:");
-                println!("(Implementation would list all registered banks)");
+                let banks = system.banking_network.list_banks().await;
+                output::print(format, &BankListOutput { banks })?;
             }
 
             BankingNetworkCommands::ApproveBank { bank_id } => {
                 system.approve_bank_registration(&bank_id).await?;
-                println!("✅ Bank {} approved successfully!", bank_id);
+                output::print(format, &BankApprovedOutput { bank_id })?;
             }
 
             BankingNetworkCommands::NetworkStats => {
                 let stats = system.get_banking_network_stats().await;
-                println!("🏦 Banking Network Statistics:");
-                println!("   Total Banks: {}", stats.total_registered_banks);
-                println!("   Active Banks: {}", stats.active_banks);
-                println!("   Pending Approvals: {}", stats.pending_approvals);
-                println!("   Suspended Banks: {}", stats.suspended_banks);
+                output::print(format, &stats)?;
             }
         },
 
@@ -214,10 +311,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             admin_id,
             recipient,
             amount,
+            create_if_missing,
         } => {
-            // Create recipient account if it doesn't exist
-            let recipient_account = system.account_manager.create_account(None);
-            println!("Created recipient account: {}", recipient_account);
+            let existed = system.account_manager.account_exists(&recipient);
+            if let Err(e) = system
+                .account_manager
+                .ensure_account(&recipient, create_if_missing)
+            {
+                output::print(
+                    format,
+                    &ErrorOutput {
+                        error: format!("{} (pass --create-if-missing to open it)", e),
+                    },
+                )?;
+                return Ok(());
+            }
+            let recipient_account = recipient;
 
             // For demo, sign with root keypair
             let signature = root_keypair.sign(b"issue_currency");
@@ -226,88 +335,174 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .issue_currency(&admin_id, &recipient_account, amount, &signature)
                 .await
             {
-                Ok(tx_id) => {
-                    println!(
-                        "✅ Issued {} ASTOR to account {}",
-                        amount, recipient_account
-                    );
-                    println!("Transaction ID: {}", tx_id);
+                Ok(transaction_id) => output::print(
+                    format,
+                    &AccountIssuanceOutput {
+                        transaction_id,
+                        recipient: recipient_account,
+                        amount,
+                        currency: "ASTOR".to_string(),
+                        created_recipient: !existed,
+                    },
+                )?,
+                Err(e) => output::print(format, &ErrorOutput::new("Failed to issue currency", &e))?,
+            }
+        }
+
+        Commands::Transfer {
+            from,
+            to,
+            amount,
+            max_fee,
+            nonce,
+            tx_id,
+            key_file,
+            signature,
+        } => {
+            let max_fee = max_fee.unwrap_or_else(|| system.transaction_manager.current_base_fee());
+            let tx_id = tx_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            let result = nonce
+                .map(Ok)
+                .unwrap_or_else(|| system.account_manager.transfer_nonce(&from))
+                .and_then(|nonce| {
+                    let signature = match (&signature, &key_file) {
+                        (Some(signature), _) => Signature::from_base64(signature, from.clone())?,
+                        (None, Some(key_file)) => KeyPair::from_file(key_file)?.sign(
+                            accounts::transfer_message(&from, &to, amount, nonce, &tx_id)
+                                .as_bytes(),
+                        ),
+                        (None, None) => {
+                            return Err(AstorError::Unauthorized(
+                                "Pass --key-file or --signature to authorize the transfer"
+                                    .to_string(),
+                            ))
+                        }
+                    };
+                    system.transfer_signed(&from, &to, amount, max_fee, nonce, &tx_id, &signature)
+                });
+
+            match result {
+                Ok(transaction_id) => {
+                    let fee = system
+                        .transaction_manager
+                        .get_transaction(&transaction_id)
+                        .map(|transaction| transaction.fee)
+                        .unwrap_or_default();
+                    output::print(
+                        format,
+                        &TransferOutput {
+                            transaction_id,
+                            from,
+                            to,
+                            amount,
+                            fee,
+                            currency: "ASTOR".to_string(),
+                        },
+                    )?
                 }
-                Err(e) => println!("❌ Failed to issue currency: {}", e),
+                Err(e) => output::print(format, &ErrorOutput::new("Failed to transfer", &e))?,
             }
         }
 
-        Commands::Transfer { from, to, amount } => {
-            // For demo purposes, this would need proper signature handling
-            println!("Transfer functionality requires proper key management in production");
-            println!("Would transfer {} ASTOR from {} to {}", amount, from, to);
+        Commands::CreateAccount {
+            public_key: Some(public_key),
+        } => {
+            let created = general_purpose::STANDARD
+                .decode(public_key.trim())
+                .map_err(|_| {
+                    AstorError::CryptographicError("Public key must be base64 encoded".to_string())
+                })
+                .and_then(|bytes| system.account_manager.create_account_from_bytes(&bytes));
+            match created {
+                Ok(account_id) => output::print(
+                    format,
+                    &AccountCreatedOutput {
+                        account_id,
+                        public_key: None,
+                    },
+                )?,
+                Err(e) => output::print(format, &ErrorOutput::new("Failed to create account", &e))?,
+            }
         }
 
-        Commands::CreateAccount => {
+        Commands::CreateAccount { public_key: None } => {
             let account_keypair = KeyPair::generate();
             let account_id = system
                 .account_manager
                 .create_account(Some(account_keypair.public_key()));
-            println!("✅ Created new account: {}", account_id);
-            println!("Account public key: {:?}", account_keypair.public_key());
+            output::print(
+                format,
+                &AccountCreatedOutput {
+                    account_id,
+                    public_key: Some(
+                        general_purpose::STANDARD.encode(account_keypair.public_key().as_bytes()),
+                    ),
+                },
+            )?;
         }
 
         Commands::Balance { account_id } => match system.account_manager.get_balance(&account_id) {
-            Ok(balance) => println!("Account {} balance: {} ASTOR", account_id, balance),
-            Err(e) => println!("❌ Failed to get balance: {}", e),
+            Ok(balance) => output::print(
+                format,
+                &BalanceOutput {
+                    account_id,
+                    balance,
+                    currency: "ASTOR".to_string(),
+                },
+            )?,
+            Err(e) => output::print(format, &ErrorOutput::new("Failed to get balance", &e))?,
         },
 
         Commands::ListAdmins => {
             let admins = system.admin_manager.list_active_admins();
-            println!("Active administrators:");
-            for admin in admins {
-                println!("  - {} ({}): {:?}", admin.id, admin.role, admin.public_key);
-            }
+            output::print(format, &AdminListOutput::new(&admins))?;
         }
 
         Commands::VerifyLedger => match system.ledger.verify_integrity() {
-            Ok(true) => println!("✅ Ledger integrity verified"),
-            Ok(false) => println!("❌ Ledger integrity check failed"),
-            Err(e) => println!("❌ Error verifying ledger: {}", e),
+            Ok(valid) => output::print(format, &LedgerVerificationOutput { valid })?,
+            Err(e) => output::print(format, &ErrorOutput::new("Error verifying ledger", &e))?,
         },
 
         Commands::Stats => {
-            println!("=== Astor System Statistics ===");
-            println!("Total supply: {} ASTOR", system.ledger.get_total_supply());
-            println!(
-                "Total ledger entries: {}",
-                system.ledger.get_entries().len()
-            );
-            println!(
-                "Active administrators: {}",
-                system.admin_manager.list_active_admins().len()
-            );
-            println!(
-                "Total transactions: {}",
-                system.transaction_manager.get_all_transactions().len()
-            );
-
             let banking_stats = system.get_banking_network_stats().await;
-            println!("Registered banks: {}", banking_stats.total_registered_banks);
-            println!("Active banks: {}", banking_stats.active_banks);
+            output::print(
+                format,
+                &SystemStatsOutput {
+                    total_supply: system.ledger.get_total_supply(),
+                    ledger_entries: system.ledger.entry_count(),
+                    active_administrators: system.admin_manager.list_active_admins().len(),
+                    total_transactions: system.transaction_manager.get_all_transactions().len(),
+                    registered_banks: banking_stats.total_registered_banks,
+                    active_banks: banking_stats.active_banks,
+                },
+            )?;
         }
 
         Commands::NetworkStatus => {
-            println!("Network status requires an active network deployment");
-            println!("Use 'astor deploy-node' to start a network node first");
+            output::print(
+                format,
+                &ErrorOutput {
+                    error: "Network status requires an active network deployment; use 'astor deploy-node' to start a network node first".to_string(),
+                },
+            )?;
         }
 
         Commands::StartApi { bind_addr } => {
-            println!("🌐 Starting Astor API server on {}...", bind_addr);
+            tracing::info!("Starting Astor API server on {}", bind_addr);
 
-            let api_server = astor_currency::api::create_server(system, bind_addr).await?;
+            let api_server = astor_currency::api::create_server(config, bind_addr).await?;
 
-            println!("✅ API server started successfully!");
-            println!("API documentation available at: http://{}/docs", bind_addr);
+            output::print(
+                format,
+                &ApiServerStartedOutput {
+                    bind_addr: bind_addr.to_string(),
+                    docs_url: format!("http://{}/docs", bind_addr),
+                },
+            )?;
 
-            println!("Press Ctrl+C to stop the server...");
             tokio::signal::ctrl_c().await?;
-            println!("Shutting down API server...");
+            tracing::info!("Shutting down API server");
+            api_server.stop();
         }
     }
 
@@ -0,0 +1,344 @@
+//! Alert routing with deduplication and escalation
+//!
+//! Alerts are grouped by signature. While an alert stays active, repeat
+//! firings within the cooldown are counted but not sent again, so a flapping
+//! condition pages once. An alert still firing `escalate_after_secs` after it
+//! first fired is escalated: its severity is raised and the escalation
+//! recipients are added.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::config::AlertsConfig;
+use crate::errors::AstorError;
+
+/// Alert severity, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    /// Next severity up; `Critical` stays critical
+    pub fn escalated(self) -> Self {
+        match self {
+            AlertSeverity::Info => AlertSeverity::Warning,
+            AlertSeverity::Warning | AlertSeverity::Critical => AlertSeverity::Critical,
+        }
+    }
+}
+
+/// Message delivered to on-call recipients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertNotification {
+    pub signature: String,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub recipients: Vec<String>,
+    /// Webhook URLs, including the Slack webhook, to post to
+    pub webhooks: Vec<String>,
+    /// Firings grouped into this notification
+    pub occurrences: u64,
+    pub first_fired_at: DateTime<Utc>,
+    pub escalated: bool,
+}
+
+/// Delivers alert notifications
+#[async_trait]
+pub trait AlertNotifier: Send + Sync {
+    async fn notify(&self, notification: &AlertNotification) -> Result<(), AstorError>;
+}
+
+/// Writes notifications to the service log
+pub struct LogNotifier;
+
+#[async_trait]
+impl AlertNotifier for LogNotifier {
+    async fn notify(&self, notification: &AlertNotification) -> Result<(), AstorError> {
+        tracing::warn!(
+            "ALERT [{:?}] {} ({} occurrences{}) -> {:?}",
+            notification.severity,
+            notification.message,
+            notification.occurrences,
+            if notification.escalated {
+                ", escalated"
+            } else {
+                ""
+            },
+            notification.recipients
+        );
+        Ok(())
+    }
+}
+
+/// State of an alert that has fired and not been resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveAlert {
+    pub signature: String,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub first_fired_at: DateTime<Utc>,
+    pub last_notified_at: DateTime<Utc>,
+    pub occurrences: u64,
+    /// Firings since the last notification
+    pub suppressed: u64,
+    pub escalated: bool,
+}
+
+/// Fires, groups and escalates operational alerts
+pub struct AlertManager {
+    config: AlertsConfig,
+    notifier: Arc<dyn AlertNotifier>,
+    active: Arc<RwLock<HashMap<String, ActiveAlert>>>,
+}
+
+impl AlertManager {
+    pub async fn new(config: &AlertsConfig) -> Result<Self, AstorError> {
+        Ok(Self {
+            config: config.clone(),
+            notifier: Arc::new(LogNotifier),
+            active: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Deliver notifications through a different channel
+    pub fn set_notifier(&mut self, notifier: Arc<dyn AlertNotifier>) {
+        self.notifier = notifier;
+    }
+
+    pub async fn start_monitoring(&self) -> Result<(), AstorError> {
+        if self.config.enabled {
+            tracing::info!(
+                "Alert manager started (cooldown {}s, escalation after {}s)",
+                self.config.cooldown_secs,
+                self.config.escalate_after_secs
+            );
+        }
+        Ok(())
+    }
+
+    /// Fire an alert, returning whether a notification was sent
+    pub async fn fire(
+        &self,
+        signature: &str,
+        severity: AlertSeverity,
+        message: &str,
+    ) -> Result<bool, AstorError> {
+        self.fire_at(signature, severity, message, Utc::now()).await
+    }
+
+    async fn fire_at(
+        &self,
+        signature: &str,
+        severity: AlertSeverity,
+        message: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool, AstorError> {
+        if !self.config.enabled {
+            return Ok(false);
+        }
+
+        let cooldown = Duration::seconds(self.config.cooldown_secs as i64);
+        let escalate_after = Duration::seconds(self.config.escalate_after_secs as i64);
+
+        let notification = {
+            let mut active = self.active.write().await;
+            let alert = active
+                .entry(signature.to_string())
+                .or_insert_with(|| ActiveAlert {
+                    signature: signature.to_string(),
+                    severity,
+                    message: message.to_string(),
+                    first_fired_at: now,
+                    last_notified_at: now,
+                    occurrences: 0,
+                    suppressed: 0,
+                    escalated: false,
+                });
+            let is_new = alert.occurrences == 0;
+            alert.occurrences += 1;
+            alert.severity = alert.severity.max(severity);
+            alert.message = message.to_string();
+
+            let escalate = !alert.escalated && now - alert.first_fired_at >= escalate_after;
+            if escalate {
+                alert.escalated = true;
+                alert.severity = alert.severity.escalated();
+            }
+
+            if !is_new && !escalate && now - alert.last_notified_at < cooldown {
+                alert.suppressed += 1;
+                return Ok(false);
+            }
+
+            alert.last_notified_at = now;
+            alert.suppressed = 0;
+            self.notification_for(alert)
+        };
+
+        self.notifier.notify(&notification).await?;
+        Ok(true)
+    }
+
+    fn notification_for(&self, alert: &ActiveAlert) -> AlertNotification {
+        let mut recipients = self.config.email_recipients.clone();
+        if alert.escalated {
+            for recipient in &self.config.escalation_recipients {
+                if !recipients.contains(recipient) {
+                    recipients.push(recipient.clone());
+                }
+            }
+        }
+
+        AlertNotification {
+            signature: alert.signature.clone(),
+            severity: alert.severity,
+            message: alert.message.clone(),
+            recipients,
+            webhooks: self
+                .config
+                .slack_webhook
+                .iter()
+                .chain(self.config.webhook_url.iter())
+                .cloned()
+                .collect(),
+            occurrences: alert.occurrences,
+            first_fired_at: alert.first_fired_at,
+            escalated: alert.escalated,
+        }
+    }
+
+    /// Clear an alert once its condition has recovered; the next firing
+    /// starts a new alert
+    pub async fn resolve(&self, signature: &str) -> Option<ActiveAlert> {
+        let resolved = self.active.write().await.remove(signature);
+        if resolved.is_some() {
+            tracing::info!("Alert resolved: {}", signature);
+        }
+        resolved
+    }
+
+    pub async fn active_alerts(&self) -> Vec<ActiveAlert> {
+        self.active.read().await.values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: Mutex<Vec<AlertNotification>>,
+    }
+
+    #[async_trait]
+    impl AlertNotifier for RecordingNotifier {
+        async fn notify(&self, notification: &AlertNotification) -> Result<(), AstorError> {
+            self.sent.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    async fn manager() -> (AlertManager, Arc<RecordingNotifier>) {
+        let config = AlertsConfig {
+            enabled: true,
+            email_recipients: vec!["oncall@astor.example".to_string()],
+            slack_webhook: Some("https://hooks.slack.example/ops".to_string()),
+            cooldown_secs: 300,
+            escalate_after_secs: 1800,
+            escalation_recipients: vec!["sre-lead@astor.example".to_string()],
+            ..AlertsConfig::default()
+        };
+        let notifier = Arc::new(RecordingNotifier::default());
+        let mut manager = AlertManager::new(&config).await.unwrap();
+        manager.set_notifier(notifier.clone());
+        (manager, notifier)
+    }
+
+    #[tokio::test]
+    async fn test_repeated_firings_within_cooldown_notify_once() {
+        let (manager, notifier) = manager().await;
+        let start = Utc::now();
+
+        for minute in 0..4 {
+            manager
+                .fire_at(
+                    "db_latency",
+                    AlertSeverity::Warning,
+                    "Database latency high",
+                    start + Duration::minutes(minute),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(notifier.sent.lock().unwrap().len(), 1);
+        assert_eq!(manager.active_alerts().await[0].suppressed, 3);
+
+        // Past the cooldown the grouped alert is sent again
+        assert!(manager
+            .fire_at(
+                "db_latency",
+                AlertSeverity::Warning,
+                "Database latency high",
+                start + Duration::minutes(6),
+            )
+            .await
+            .unwrap());
+        let sent = notifier.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].occurrences, 5);
+        assert_eq!(sent[1].webhooks, vec!["https://hooks.slack.example/ops"]);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_alert_escalates() {
+        let (manager, notifier) = manager().await;
+        let start = Utc::now();
+
+        manager
+            .fire_at("disk", AlertSeverity::Warning, "Disk 91% full", start)
+            .await
+            .unwrap();
+        // Escalation is sent even inside the cooldown
+        manager
+            .fire_at(
+                "disk",
+                AlertSeverity::Warning,
+                "Disk 93% full",
+                start + Duration::minutes(30),
+            )
+            .await
+            .unwrap();
+
+        let sent = notifier.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2);
+        assert!(!sent[0].escalated);
+        assert!(sent[1].escalated);
+        assert_eq!(sent[1].severity, AlertSeverity::Critical);
+        assert!(sent[1]
+            .recipients
+            .contains(&"sre-lead@astor.example".to_string()));
+
+        manager.resolve("disk").await.unwrap();
+        manager
+            .fire_at(
+                "disk",
+                AlertSeverity::Warning,
+                "Disk 91% full",
+                start + Duration::minutes(31),
+            )
+            .await
+            .unwrap();
+        let sent = notifier.sent.lock().unwrap();
+        assert_eq!(sent[2].severity, AlertSeverity::Warning);
+        assert!(!sent[2].escalated);
+    }
+}
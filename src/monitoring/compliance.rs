@@ -7,6 +7,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::errors::AstorError;
+use crate::security::encryption::DataAccessAuditor;
 
 /// Compliance event types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,12 +135,22 @@ pub enum PrivacyRequestStatus {
 }
 
 /// Compliance monitor
+///
+/// Clones share the same event log.
+#[derive(Clone)]
 pub struct ComplianceMonitor {
     events: Arc<RwLock<VecDeque<ComplianceEvent>>>,
     gdpr_compliance: Arc<RwLock<GdprCompliance>>,
     max_events: usize,
 }
 
+#[async_trait::async_trait]
+impl DataAccessAuditor for ComplianceMonitor {
+    async fn record_data_access(&self, event: ComplianceEvent) {
+        self.record_event(event).await;
+    }
+}
+
 impl ComplianceMonitor {
     pub fn new() -> Self {
         Self {
@@ -227,6 +227,10 @@ impl HealthChecker {
 
     /// Manual health check for specific component
     pub async fn check_component(&self, component: &str) -> HealthCheckResult {
+        Self::run_component_check(component).await
+    }
+
+    async fn run_component_check(component: &str) -> HealthCheckResult {
         match component {
             "database" => Self::check_database().await,
             "redis" => Self::check_redis().await,
@@ -242,3 +246,181 @@ impl HealthChecker {
         }
     }
 }
+
+/// A dependency that must be healthy before the system starts
+#[async_trait::async_trait]
+pub trait DependencyCheck: Send + Sync {
+    /// Name reported if startup is aborted because of this dependency
+    fn name(&self) -> &str;
+    async fn check(&self) -> HealthCheckResult;
+}
+
+/// One of the built-in component checks, e.g. `database`
+pub struct ComponentCheck {
+    component: String,
+}
+
+impl ComponentCheck {
+    pub fn new(component: impl Into<String>) -> Self {
+        Self {
+            component: component.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DependencyCheck for ComponentCheck {
+    fn name(&self) -> &str {
+        &self.component
+    }
+
+    async fn check(&self) -> HealthCheckResult {
+        HealthChecker::run_component_check(&self.component).await
+    }
+}
+
+/// Dependencies checked at startup, in the order they are listed in the
+/// health check configuration
+pub fn startup_dependencies(config: &HealthCheckConfig) -> Vec<Arc<dyn DependencyCheck>> {
+    config
+        .checks
+        .iter()
+        .map(|component| {
+            Arc::new(ComponentCheck::new(component.clone())) as Arc<dyn DependencyCheck>
+        })
+        .collect()
+}
+
+/// Check dependencies in order, failing on the first unhealthy one
+///
+/// A check that does not answer within `timeout` counts as unhealthy.
+/// Degraded dependencies are logged but do not block startup.
+pub async fn verify_dependencies(
+    dependencies: &[Arc<dyn DependencyCheck>],
+    timeout: Duration,
+) -> Result<Vec<HealthCheckResult>, AstorError> {
+    let mut results = Vec::with_capacity(dependencies.len());
+
+    for dependency in dependencies {
+        let result = tokio::time::timeout(timeout, dependency.check())
+            .await
+            .unwrap_or_else(|_| HealthCheckResult {
+                name: dependency.name().to_string(),
+                status: HealthStatus::Unhealthy,
+                message: format!("No response within {}ms", timeout.as_millis()),
+                duration_ms: timeout.as_millis() as u64,
+                timestamp: chrono::Utc::now(),
+            });
+
+        match result.status {
+            HealthStatus::Healthy => {
+                tracing::info!("Startup dependency {} is healthy", dependency.name());
+            }
+            HealthStatus::Degraded => {
+                tracing::warn!(
+                    "Startup dependency {} is degraded: {}",
+                    dependency.name(),
+                    result.message
+                );
+            }
+            HealthStatus::Unhealthy => {
+                tracing::error!(
+                    "Aborting startup: dependency {} is unhealthy: {}",
+                    dependency.name(),
+                    result.message
+                );
+                return Err(AstorError::DependencyUnhealthy {
+                    dependency: dependency.name().to_string(),
+                    reason: result.message,
+                });
+            }
+        }
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MonitoringConfig;
+    use crate::security::KeyPair;
+    use crate::AstorSystem;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct StubDependency {
+        name: &'static str,
+        status: HealthStatus,
+        checked: AtomicBool,
+    }
+
+    impl StubDependency {
+        fn new(name: &'static str, status: HealthStatus) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                status,
+                checked: AtomicBool::new(false),
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DependencyCheck for StubDependency {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn check(&self) -> HealthCheckResult {
+            self.checked.store(true, Ordering::SeqCst);
+            HealthCheckResult {
+                name: self.name.to_string(),
+                status: self.status.clone(),
+                message: format!("{} stub", self.name),
+                duration_ms: 0,
+                timestamp: chrono::Utc::now(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_dependency_aborts_startup() {
+        let database = StubDependency::new("database", HealthStatus::Unhealthy);
+        let redis = StubDependency::new("redis", HealthStatus::Healthy);
+
+        let result = AstorSystem::new_with_dependencies(
+            KeyPair::generate(),
+            MonitoringConfig::default(),
+            vec![
+                database.clone() as Arc<dyn DependencyCheck>,
+                redis.clone() as Arc<dyn DependencyCheck>,
+            ],
+        )
+        .await;
+
+        match result {
+            Err(AstorError::DependencyUnhealthy { dependency, reason }) => {
+                assert_eq!(dependency, "database");
+                assert_eq!(reason, "database stub");
+            }
+            Err(other) => panic!("unexpected error: {}", other),
+            Ok(_) => panic!("startup proceeded with an unhealthy database"),
+        }
+        // Later dependencies are not checked once one has failed
+        assert!(!redis.checked.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_degraded_dependency_does_not_block_startup() {
+        let dependencies: Vec<Arc<dyn DependencyCheck>> = vec![
+            StubDependency::new("database", HealthStatus::Healthy),
+            StubDependency::new("disk_space", HealthStatus::Degraded),
+        ];
+
+        let results = verify_dependencies(&dependencies, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].status, HealthStatus::Degraded);
+    }
+}
@@ -3,7 +3,7 @@
 pub mod metrics;
 // pub mod tracing;
 pub mod health;
-// pub mod alerts;
+pub mod alerts;
 pub mod compliance;
 
 use serde::{Deserialize, Serialize};
@@ -66,6 +66,12 @@ impl MonitoringSystem {
         self.compliance_monitor.record_event(event).await;
     }
 
+    /// Auditor that records decryptions of sensitive data as compliance
+    /// events
+    pub fn data_access_auditor(&self) -> Arc<dyn crate::security::DataAccessAuditor> {
+        Arc::new(self.compliance_monitor.clone())
+    }
+
     /// Get system health status
     pub async fn get_health_status(&self) -> health::HealthStatus {
         self.health_checker.get_status().await
@@ -0,0 +1,176 @@
+//! Capability negotiation during the peer handshake
+//!
+//! Each side advertises the range of protocol versions it speaks, its wire
+//! codecs and its optional features as handshake capability strings. A
+//! connection runs at the highest version both support, with the features
+//! both enabled; peers with no version in common are dropped. Peers that
+//! predate negotiation advertise no version and are treated as version 1.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use super::codec::{codecs_from_capabilities, negotiate_codec, supported_codecs, CodecKind};
+use crate::errors::AstorError;
+
+/// Protocol version spoken by this build
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest protocol version this build still accepts
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+const PROTOCOL_CAPABILITY_PREFIX: &str = "protocol:";
+const MIN_PROTOCOL_CAPABILITY_PREFIX: &str = "protocol-min:";
+const FEATURE_CAPABILITY_PREFIX: &str = "feature:";
+
+/// What this node offers in its handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalCapabilities {
+    pub protocol_version: u32,
+    pub min_protocol_version: u32,
+    pub codecs: Vec<CodecKind>,
+    pub features: BTreeSet<String>,
+}
+
+impl LocalCapabilities {
+    /// This build's protocol range with the codecs for `preferred_codec`
+    pub fn new(preferred_codec: CodecKind) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            codecs: supported_codecs(preferred_codec),
+            features: BTreeSet::new(),
+        }
+    }
+
+    /// Advertise an optional feature, used only with peers that also enable it
+    pub fn enable_feature(&mut self, feature: &str) {
+        self.features.insert(feature.to_string());
+    }
+
+    /// Capability strings for the handshake
+    pub fn advertise(&self) -> Vec<String> {
+        let mut capabilities = vec![
+            format!("{}{}", PROTOCOL_CAPABILITY_PREFIX, self.protocol_version),
+            format!(
+                "{}{}",
+                MIN_PROTOCOL_CAPABILITY_PREFIX, self.min_protocol_version
+            ),
+        ];
+        capabilities.extend(self.codecs.iter().map(CodecKind::capability));
+        capabilities.extend(
+            self.features
+                .iter()
+                .map(|feature| format!("{}{}", FEATURE_CAPABILITY_PREFIX, feature)),
+        );
+        capabilities
+    }
+
+    /// Agree on how to talk to a peer from the capabilities it advertised
+    pub fn negotiate(
+        &self,
+        peer_id: &str,
+        remote: &[String],
+    ) -> Result<PeerCapabilities, AstorError> {
+        let remote_version = parse_version(remote, PROTOCOL_CAPABILITY_PREFIX)?.unwrap_or(1);
+        let remote_min_version =
+            parse_version(remote, MIN_PROTOCOL_CAPABILITY_PREFIX)?.unwrap_or(remote_version);
+
+        let protocol_version = self.protocol_version.min(remote_version);
+        if protocol_version < self.min_protocol_version.max(remote_min_version) {
+            return Err(AstorError::IncompatiblePeer {
+                peer_id: peer_id.to_string(),
+                reason: format!(
+                    "no common protocol version (local {}..={}, remote {}..={})",
+                    self.min_protocol_version,
+                    self.protocol_version,
+                    remote_min_version,
+                    remote_version
+                ),
+            });
+        }
+
+        let codec =
+            negotiate_codec(&self.codecs, &codecs_from_capabilities(remote)).map_err(|e| {
+                AstorError::IncompatiblePeer {
+                    peer_id: peer_id.to_string(),
+                    reason: e.to_string(),
+                }
+            })?;
+
+        let features = remote
+            .iter()
+            .filter_map(|capability| capability.strip_prefix(FEATURE_CAPABILITY_PREFIX))
+            .filter(|feature| self.features.contains(*feature))
+            .map(str::to_string)
+            .collect();
+
+        Ok(PeerCapabilities {
+            protocol_version,
+            codec,
+            features,
+        })
+    }
+}
+
+impl Default for LocalCapabilities {
+    fn default() -> Self {
+        Self::new(CodecKind::default())
+    }
+}
+
+/// What a connection with a peer was negotiated to use
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerCapabilities {
+    pub protocol_version: u32,
+    pub codec: CodecKind,
+    /// Features enabled on both sides
+    pub features: BTreeSet<String>,
+}
+
+impl PeerCapabilities {
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.contains(feature)
+    }
+}
+
+fn parse_version(capabilities: &[String], prefix: &str) -> Result<Option<u32>, AstorError> {
+    capabilities
+        .iter()
+        .find_map(|capability| capability.strip_prefix(prefix))
+        .map(|version| {
+            version.parse().map_err(|_| {
+                AstorError::NetworkError(format!(
+                    "Malformed protocol version capability: {}{}",
+                    prefix, version
+                ))
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peers_downgrade_to_common_version_and_features() {
+        let mut local = LocalCapabilities::new(CodecKind::Bincode);
+        local.enable_feature("compact-blocks");
+        local.enable_feature("fee-market");
+
+        let mut remote = LocalCapabilities::new(CodecKind::Json);
+        remote.protocol_version = PROTOCOL_VERSION + 1;
+        remote.enable_feature("fee-market");
+
+        let negotiated = local.negotiate("peer-1", &remote.advertise()).unwrap();
+        assert_eq!(negotiated.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(negotiated.codec, CodecKind::Json);
+        assert!(negotiated.supports("fee-market"));
+        assert!(!negotiated.supports("compact-blocks"));
+
+        // Pre-negotiation peers advertise nothing and run at version 1
+        let legacy = local.negotiate("peer-2", &[]).unwrap();
+        assert_eq!(legacy.protocol_version, 1);
+        assert!(legacy.features.is_empty());
+    }
+}
@@ -0,0 +1,228 @@
+//! Wire codecs for network protocol messages
+//!
+//! Handshakes are always exchanged as JSON. Each side advertises the codecs it
+//! supports in its handshake capabilities and both then switch to the most
+//! compact codec they have in common.
+
+use crate::errors::AstorError;
+use serde::{Deserialize, Serialize};
+
+use super::protocol::NetworkMessage;
+
+/// Capability prefix used to advertise codecs in the handshake
+const CODEC_CAPABILITY_PREFIX: &str = "codec:";
+
+/// Encodes and decodes protocol messages for the wire
+pub trait ProtocolCodec: Send + Sync {
+    fn kind(&self) -> CodecKind;
+    fn encode(&self, message: &NetworkMessage) -> Result<Vec<u8>, AstorError>;
+    fn decode(&self, bytes: &[u8]) -> Result<NetworkMessage, AstorError>;
+}
+
+/// Available wire codecs, ordered from least to most compact
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum CodecKind {
+    /// Human-readable JSON, supported by every peer
+    #[default]
+    Json,
+    /// Compact binary encoding for block and transaction sync
+    Bincode,
+}
+
+impl CodecKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CodecKind::Json => "json",
+            CodecKind::Bincode => "bincode",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(CodecKind::Json),
+            "bincode" => Some(CodecKind::Bincode),
+            _ => None,
+        }
+    }
+
+    pub fn codec(&self) -> Box<dyn ProtocolCodec> {
+        match self {
+            CodecKind::Json => Box::new(JsonCodec),
+            CodecKind::Bincode => Box::new(BincodeCodec),
+        }
+    }
+
+    /// Handshake capability advertising this codec
+    pub fn capability(&self) -> String {
+        format!("{}{}", CODEC_CAPABILITY_PREFIX, self.name())
+    }
+}
+
+/// Codecs a node supports given its preferred codec; JSON is always included
+/// so older peers can still connect
+pub fn supported_codecs(preferred: CodecKind) -> Vec<CodecKind> {
+    let mut codecs = vec![preferred];
+    if preferred != CodecKind::Json {
+        codecs.push(CodecKind::Json);
+    }
+    codecs
+}
+
+/// Read the codecs a peer advertised in its handshake capabilities
+///
+/// Peers that predate codec negotiation advertise none and only speak JSON.
+pub fn codecs_from_capabilities(capabilities: &[String]) -> Vec<CodecKind> {
+    let codecs: Vec<CodecKind> = capabilities
+        .iter()
+        .filter_map(|capability| capability.strip_prefix(CODEC_CAPABILITY_PREFIX))
+        .filter_map(CodecKind::from_name)
+        .collect();
+
+    if codecs.is_empty() {
+        vec![CodecKind::Json]
+    } else {
+        codecs
+    }
+}
+
+/// Choose the codec for a connection
+///
+/// The most compact codec supported by both sides wins. The choice does not
+/// depend on which side initiated, so both peers arrive at the same codec.
+pub fn negotiate_codec(local: &[CodecKind], remote: &[CodecKind]) -> Result<CodecKind, AstorError> {
+    local
+        .iter()
+        .filter(|codec| remote.contains(codec))
+        .max()
+        .copied()
+        .ok_or_else(|| {
+            AstorError::NetworkError(format!(
+                "No common protocol codec (local: {:?}, remote: {:?})",
+                local, remote
+            ))
+        })
+}
+
+pub struct JsonCodec;
+
+impl ProtocolCodec for JsonCodec {
+    fn kind(&self) -> CodecKind {
+        CodecKind::Json
+    }
+
+    fn encode(&self, message: &NetworkMessage) -> Result<Vec<u8>, AstorError> {
+        Ok(serde_json::to_vec(message)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<NetworkMessage, AstorError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+pub struct BincodeCodec;
+
+impl ProtocolCodec for BincodeCodec {
+    fn kind(&self) -> CodecKind {
+        CodecKind::Bincode
+    }
+
+    fn encode(&self, message: &NetworkMessage) -> Result<Vec<u8>, AstorError> {
+        bincode::serialize(message)
+            .map_err(|e| AstorError::NetworkError(format!("Failed to encode message: {}", e)))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<NetworkMessage, AstorError> {
+        bincode::deserialize(bytes)
+            .map_err(|e| AstorError::NetworkError(format!("Failed to decode message: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::protocol::{MessagePayload, MessageType, ProtocolHandler};
+
+    fn sample_message() -> NetworkMessage {
+        ProtocolHandler::create_message(
+            "node-a".to_string(),
+            Some("node-b".to_string()),
+            MessageType::Sync,
+            MessagePayload::Sync {
+                request_type: crate::network::protocol::SyncRequestType::BlockResponse,
+                data: vec![7; 256],
+            },
+        )
+    }
+
+    fn assert_round_trip(codec: &dyn ProtocolCodec) -> usize {
+        let message = sample_message();
+        let bytes = codec.encode(&message).unwrap();
+        let decoded = codec.decode(&bytes).unwrap();
+
+        assert_eq!(decoded.id, message.id);
+        assert_eq!(decoded.from, message.from);
+        assert_eq!(decoded.to, message.to);
+        assert_eq!(decoded.timestamp, message.timestamp);
+        match decoded.payload {
+            MessagePayload::Sync { data, .. } => assert_eq!(data, vec![7; 256]),
+            other => panic!("unexpected payload {:?}", other),
+        }
+
+        bytes.len()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        assert_round_trip(&JsonCodec);
+    }
+
+    #[test]
+    fn test_bincode_round_trip() {
+        let binary_len = assert_round_trip(&BincodeCodec);
+        let json_len = assert_round_trip(&JsonCodec);
+        assert!(binary_len < json_len);
+    }
+
+    #[test]
+    fn test_peers_agree_on_codec() {
+        let binary_node = supported_codecs(CodecKind::Bincode);
+        let json_node = supported_codecs(CodecKind::Json);
+
+        // Both sides reach the same answer regardless of who initiates
+        assert_eq!(
+            negotiate_codec(&binary_node, &binary_node).unwrap(),
+            CodecKind::Bincode
+        );
+        assert_eq!(
+            negotiate_codec(&binary_node, &json_node).unwrap(),
+            CodecKind::Json
+        );
+        assert_eq!(
+            negotiate_codec(&json_node, &binary_node).unwrap(),
+            CodecKind::Json
+        );
+
+        assert!(negotiate_codec(&[CodecKind::Bincode], &[CodecKind::Json]).is_err());
+    }
+
+    #[test]
+    fn test_capabilities_advertise_codecs() {
+        let capabilities: Vec<String> = supported_codecs(CodecKind::Bincode)
+            .iter()
+            .map(CodecKind::capability)
+            .chain(std::iter::once("consensus".to_string()))
+            .collect();
+        assert_eq!(
+            codecs_from_capabilities(&capabilities),
+            vec![CodecKind::Bincode, CodecKind::Json]
+        );
+
+        // Peers from before codec negotiation only speak JSON
+        assert_eq!(
+            codecs_from_capabilities(&["consensus".to_string()]),
+            vec![CodecKind::Json]
+        );
+    }
+}
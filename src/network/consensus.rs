@@ -1,13 +1,41 @@
 //! Consensus mechanism for the Astor network using Practical Byzantine Fault Tolerance (pBFT)
+//!
+//! # Finality
+//!
+//! A block is final once it holds commit votes from a quorum of the
+//! validator set: 2f + 1 of n = 3f + 1 validators, or more generally all but
+//! the f = (n - 1) / 3 faults the network tolerates. Votes are signed over
+//! [`vote_message`] and only count when the signature verifies against the
+//! validator's registered key. Each validator votes once per height; a
+//! second vote for a different block at that height is rejected.
+//!
+//! # Tie-breaking
+//!
+//! Competing proposals at the same height are resolved by a fixed rule that
+//! depends only on the proposals and votes received, never on arrival order
+//! or on map iteration order:
+//!
+//! 1. the quorum-backed proposal with the most distinct validators wins;
+//! 2. among proposals with equal backing, the one whose block hash (see
+//!    [`Block::hash`]) is lexicographically lowest wins.
+//!
+//! Honest nodes that have seen the same proposals and votes therefore always
+//! finalize the same block at a given height.
 
 use super::NodeConfig;
 use crate::errors::AstorError;
-use crate::ledger::Transaction;
-use crate::security::{KeyPair, Signature};
+use crate::security::{hash_data, KeyPair, Signature};
+use crate::transactions::Transaction;
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::PublicKey;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+
+/// Committed blocks buffered for each subscriber before it starts lagging
+const COMMITTED_BLOCK_CHANNEL_CAPACITY: usize = 64;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConsensusState {
@@ -54,11 +82,18 @@ pub struct ConsensusEngine {
     current_view: u64,
     current_sequence: u64,
     is_primary: bool,
-    validators: Arc<RwLock<HashSet<String>>>,
+    /// Public key of each validator, by node ID
+    validators: Arc<RwLock<HashMap<String, PublicKey>>>,
     pending_transactions: Arc<RwLock<Vec<Transaction>>>,
     prepare_messages: Arc<RwLock<HashMap<String, ConsensusMessage>>>,
     commit_messages: Arc<RwLock<HashMap<String, ConsensusMessage>>>,
     committed_blocks: Arc<RwLock<Vec<Block>>>,
+    /// Candidate blocks awaiting finalization, by height then block hash
+    proposals: Arc<RwLock<HashMap<u64, HashMap<String, Proposal>>>>,
+    /// Start of the current sealing interval
+    last_sealed_at: Arc<RwLock<DateTime<Utc>>>,
+    /// Announces each block as it is finalized
+    committed_tx: broadcast::Sender<Block>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +107,36 @@ pub struct Block {
     pub validator_signatures: HashMap<String, Signature>,
 }
 
+impl Block {
+    /// Identity of the block that validators vote on
+    ///
+    /// Covers everything except `validator_signatures`, which each node
+    /// collects on its own, so every node derives the same hash.
+    pub fn hash(&self) -> Result<String, AstorError> {
+        let header = serde_json::to_vec(&(
+            self.sequence,
+            self.view,
+            &self.transactions,
+            &self.previous_hash,
+            &self.merkle_root,
+            self.timestamp,
+        ))?;
+        Ok(hash_data(&header))
+    }
+}
+
+/// Bytes a validator signs to vote for `block_hash` at `sequence`
+pub fn vote_message(sequence: u64, block_hash: &str) -> Vec<u8> {
+    format!("commit:{}:{}", sequence, block_hash).into_bytes()
+}
+
+/// A candidate block and the signed votes for it, by validator
+#[derive(Clone)]
+struct Proposal {
+    block: Block,
+    votes: HashMap<String, Signature>,
+}
+
 impl ConsensusEngine {
     pub async fn new(config: NodeConfig) -> Result<Self, AstorError> {
         Ok(Self {
@@ -80,14 +145,25 @@ impl ConsensusEngine {
             current_view: 0,
             current_sequence: 0,
             is_primary: false,
-            validators: Arc::new(RwLock::new(HashSet::new())),
+            validators: Arc::new(RwLock::new(HashMap::new())),
             pending_transactions: Arc::new(RwLock::new(Vec::new())),
             prepare_messages: Arc::new(RwLock::new(HashMap::new())),
             commit_messages: Arc::new(RwLock::new(HashMap::new())),
             committed_blocks: Arc::new(RwLock::new(Vec::new())),
+            proposals: Arc::new(RwLock::new(HashMap::new())),
+            last_sealed_at: Arc::new(RwLock::new(Utc::now())),
+            committed_tx: broadcast::channel(COMMITTED_BLOCK_CHANNEL_CAPACITY).0,
         })
     }
 
+    /// Receive every block committed from now on
+    ///
+    /// A subscriber that falls more than a few dozen blocks behind misses
+    /// the oldest ones and is told how many it lagged.
+    pub fn subscribe_committed_blocks(&self) -> broadcast::Receiver<Block> {
+        self.committed_tx.subscribe()
+    }
+
     pub async fn start(&mut self) -> Result<(), AstorError> {
         // Initialize validator set
         self.initialize_validators().await?;
@@ -111,6 +187,15 @@ impl ConsensusEngine {
     }
 
     pub async fn add_transaction(&self, transaction: Transaction) -> Result<(), AstorError> {
+        // A transaction that can never fit in a block would stall sealing
+        let size = serde_json::to_vec(&transaction)?.len();
+        if size > self.config.max_block_bytes {
+            return Err(AstorError::TransactionValidationFailed(format!(
+                "Transaction {} is {} bytes, larger than the {} byte block limit",
+                transaction.id, size, self.config.max_block_bytes
+            )));
+        }
+
         let mut pending = self.pending_transactions.write().await;
         pending.push(transaction);
 
@@ -118,7 +203,13 @@ impl ConsensusEngine {
         if self.is_primary && pending.len() >= 10 {
             self.initiate_consensus_round().await?;
         }
+        drop(pending);
 
+        // The transaction is queued either way; a failed seal is retried by
+        // the next transaction or sealing tick
+        if let Err(e) = self.seal_if_due().await {
+            tracing::error!("Block sealing failed: {}", e);
+        }
         Ok(())
     }
 
@@ -145,14 +236,36 @@ impl ConsensusEngine {
 
     async fn initialize_validators(&self) -> Result<(), AstorError> {
         let mut validators = self.validators.write().await;
-        validators.insert(self.config.node_id.clone());
+        validators.insert(
+            self.config.node_id.clone(),
+            self.config.keypair.public_key(),
+        );
         // Add other known validators from config or discovery
         Ok(())
     }
 
+    /// Admit a validator whose votes count towards finality
+    pub async fn add_validator(&self, node_id: String, public_key: PublicKey) {
+        self.validators.write().await.insert(node_id, public_key);
+    }
+
+    /// Votes needed to finalize a block with the current validator set
+    pub async fn quorum(&self) -> usize {
+        let validators = self.validators.read().await.len();
+        let tolerated_faults = validators.saturating_sub(1) / 3;
+        validators - tolerated_faults
+    }
+
+    /// Sign this node's vote for `block_hash` at `sequence`
+    pub fn sign_vote(&self, sequence: u64, block_hash: &str) -> Signature {
+        self.config
+            .keypair
+            .sign(&vote_message(sequence, block_hash))
+    }
+
     async fn update_primary_status(&mut self) {
         let validators = self.validators.read().await;
-        let mut validator_list: Vec<_> = validators.iter().collect();
+        let mut validator_list: Vec<_> = validators.keys().collect();
         validator_list.sort();
 
         if let Some(primary) =
@@ -177,7 +290,7 @@ impl ConsensusEngine {
             return Ok(());
         }
 
-        let transactions = pending.drain(..).collect::<Vec<_>>();
+        let transactions = self.take_block_transactions(&mut pending)?;
         let digest = self.calculate_digest(&transactions);
 
         let pre_prepare = ConsensusMessage::PrePrepare {
@@ -194,6 +307,281 @@ impl ConsensusEngine {
         Ok(())
     }
 
+    /// Seal the next block from pending transactions
+    ///
+    /// Takes transactions in arrival order up to the configured count and
+    /// byte limits; the rest stay pending for the next block. The block is
+    /// proposed for the next height with this node's vote and committed only
+    /// once `finalize_height` finds a quorum. Returns the block if it was
+    /// finalized straight away, and `None` when nothing is pending or the
+    /// block awaits votes from other validators. Nothing more is sealed
+    /// while a proposal for the next height is outstanding.
+    pub async fn seal_block(&self) -> Result<Option<Block>, AstorError> {
+        self.seal_block_at(Utc::now()).await
+    }
+
+    /// Seal a block if the pending count or the sealing interval is reached
+    ///
+    /// Whichever of `seal_pending_threshold` and `seal_interval_secs` is hit
+    /// first triggers the seal. An interval that elapses with nothing
+    /// pending seals nothing. Only the primary seals blocks.
+    pub async fn seal_if_due(&self) -> Result<Option<Block>, AstorError> {
+        self.seal_if_due_at(Utc::now()).await
+    }
+
+    pub async fn seal_if_due_at(&self, now: DateTime<Utc>) -> Result<Option<Block>, AstorError> {
+        if !self.is_primary {
+            return Ok(None);
+        }
+        let pending = self.pending_transactions.read().await.len();
+        if pending == 0 {
+            return Ok(None);
+        }
+
+        let interval = Duration::seconds(self.config.seal_interval_secs as i64);
+        let interval_elapsed = now - *self.last_sealed_at.read().await >= interval;
+        if pending < self.config.seal_pending_threshold && !interval_elapsed {
+            return Ok(None);
+        }
+        self.seal_block_at(now).await
+    }
+
+    /// Check every second whether a block is due until the task is aborted
+    pub fn spawn_block_sealing(
+        engine: Arc<RwLock<ConsensusEngine>>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = engine.read().await.seal_if_due().await {
+                    tracing::error!("Automatic block sealing failed: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn seal_block_at(&self, now: DateTime<Utc>) -> Result<Option<Block>, AstorError> {
+        let sequence = self.get_block_height().await + 1;
+        if self.proposals.read().await.contains_key(&sequence) {
+            return Ok(None);
+        }
+
+        let transactions = {
+            let mut pending = self.pending_transactions.write().await;
+            self.take_block_transactions(&mut pending)?
+        };
+        if transactions.is_empty() {
+            return Ok(None);
+        }
+
+        let previous_hash = match self.get_latest_block().await {
+            Some(previous) => hash_data(&serde_json::to_vec(&previous)?),
+            None => "0".repeat(64),
+        };
+        let block = Block {
+            sequence,
+            view: self.current_view,
+            merkle_root: self.calculate_digest(&transactions),
+            transactions,
+            previous_hash,
+            timestamp: now.timestamp() as u64,
+            validator_signatures: HashMap::new(),
+        };
+        *self.last_sealed_at.write().await = now;
+
+        let block_hash = match self.propose_block(block.clone()).await {
+            Ok(block_hash) => block_hash,
+            Err(e) => {
+                // Back to the front of the queue for the next seal
+                self.pending_transactions
+                    .write()
+                    .await
+                    .splice(0..0, block.transactions);
+                return Err(e);
+            }
+        };
+        tracing::info!(
+            "Proposed block {} at height {} with {} transactions",
+            block_hash,
+            sequence,
+            block.transactions.len()
+        );
+
+        if self
+            .validators
+            .read()
+            .await
+            .contains_key(&self.config.node_id)
+        {
+            let signature = self.sign_vote(sequence, &block_hash);
+            self.record_vote(sequence, &block_hash, &self.config.node_id, &signature)
+                .await?;
+            self.broadcast_consensus_message(ConsensusMessage::Commit {
+                view: self.current_view,
+                sequence,
+                digest: block_hash,
+                node_id: self.config.node_id.clone(),
+                signature,
+            })
+            .await?;
+        }
+        self.finalize_height(sequence).await
+    }
+
+    /// Register a candidate block for the next height, returning its hash
+    ///
+    /// Proposing the same block twice is harmless.
+    pub async fn propose_block(&self, block: Block) -> Result<String, AstorError> {
+        let next_height = self.get_block_height().await + 1;
+        if block.sequence != next_height {
+            return Err(AstorError::NetworkError(format!(
+                "Proposal for height {} but the next height is {}",
+                block.sequence, next_height
+            )));
+        }
+
+        let block_hash = block.hash()?;
+        self.proposals
+            .write()
+            .await
+            .entry(block.sequence)
+            .or_default()
+            .entry(block_hash.clone())
+            .or_insert_with(|| Proposal {
+                block,
+                votes: HashMap::new(),
+            });
+        Ok(block_hash)
+    }
+
+    /// Record a validator's signed vote for a proposed block
+    ///
+    /// Rejects votes from nodes outside the validator set, votes whose
+    /// signature does not verify, and a second vote by the same validator
+    /// for a different block at the same height.
+    pub async fn record_vote(
+        &self,
+        sequence: u64,
+        block_hash: &str,
+        node_id: &str,
+        signature: &Signature,
+    ) -> Result<(), AstorError> {
+        let public_key = self
+            .validators
+            .read()
+            .await
+            .get(node_id)
+            .copied()
+            .ok_or_else(|| AstorError::Unauthorized(format!("{} is not a validator", node_id)))?;
+        signature.verify(&public_key, &vote_message(sequence, block_hash))?;
+
+        let mut proposals = self.proposals.write().await;
+        let candidates = proposals.get_mut(&sequence).ok_or_else(|| {
+            AstorError::NetworkError(format!(
+                "Vote for unknown block {} at height {}",
+                block_hash, sequence
+            ))
+        })?;
+        if let Some((voted_for, _)) = candidates
+            .iter()
+            .find(|(hash, proposal)| *hash != block_hash && proposal.votes.contains_key(node_id))
+        {
+            return Err(AstorError::NetworkError(format!(
+                "Validator {} already voted for block {} at height {}",
+                node_id, voted_for, sequence
+            )));
+        }
+        let proposal = candidates.get_mut(block_hash).ok_or_else(|| {
+            AstorError::NetworkError(format!(
+                "Vote for unknown block {} at height {}",
+                block_hash, sequence
+            ))
+        })?;
+        proposal
+            .votes
+            .insert(node_id.to_string(), signature.clone());
+        Ok(())
+    }
+
+    /// Finalize the winning proposal at the next height
+    ///
+    /// Only proposals with a quorum of votes are eligible, and the winner
+    /// among them is chosen by the tie-break rule in the module
+    /// documentation. The finalized block carries the votes as its validator
+    /// signatures. Returns `None`, keeping the proposals, while no proposal
+    /// at `sequence` has a quorum.
+    pub async fn finalize_height(&self, sequence: u64) -> Result<Option<Block>, AstorError> {
+        let quorum = self.quorum().await;
+        let mut blocks = self.committed_blocks.write().await;
+        let next_height = blocks.len() as u64 + 1;
+        if sequence != next_height {
+            return Err(AstorError::NetworkError(format!(
+                "Cannot finalize height {}; the next height is {}",
+                sequence, next_height
+            )));
+        }
+
+        let mut proposals = self.proposals.write().await;
+        let Some(block_hash) = proposals.get(&sequence).and_then(|candidates| {
+            candidates
+                .iter()
+                .filter(|(_, proposal)| proposal.votes.len() >= quorum)
+                .max_by_key(|(block_hash, proposal)| {
+                    (proposal.votes.len(), Reverse((*block_hash).clone()))
+                })
+                .map(|(block_hash, _)| block_hash.clone())
+        }) else {
+            return Ok(None);
+        };
+        let mut candidates = proposals.remove(&sequence).unwrap_or_default();
+        let Some(winner) = candidates.remove(&block_hash) else {
+            return Ok(None);
+        };
+
+        tracing::info!(
+            "Finalized block {} at height {} with {} of {} required votes",
+            block_hash,
+            sequence,
+            winner.votes.len(),
+            quorum
+        );
+        let mut block = winner.block;
+        block.validator_signatures = winner.votes;
+        blocks.push(block.clone());
+        let _ = self.committed_tx.send(block.clone());
+        Ok(Some(block))
+    }
+
+    /// Remove the longest prefix of `pending` that fits the block limits
+    fn take_block_transactions(
+        &self,
+        pending: &mut Vec<Transaction>,
+    ) -> Result<Vec<Transaction>, AstorError> {
+        let mut count = 0;
+        let mut bytes = 0;
+
+        for transaction in pending.iter() {
+            let size = serde_json::to_vec(transaction)?.len();
+            if count == self.config.max_block_transactions
+                || bytes + size > self.config.max_block_bytes
+            {
+                break;
+            }
+            count += 1;
+            bytes += size;
+        }
+
+        if count < pending.len() {
+            tracing::debug!(
+                "Block limit reached; carrying {} transactions to the next block",
+                pending.len() - count
+            );
+        }
+
+        Ok(pending.drain(..count).collect())
+    }
+
     async fn handle_pre_prepare(&self, message: ConsensusMessage) -> Result<(), AstorError> {
         // Validate and process pre-prepare message
         // Send prepare message if valid
@@ -205,8 +593,28 @@ impl ConsensusEngine {
         Ok(())
     }
 
+    /// Count a commit vote and finalize its height once a quorum is reached
+    ///
+    /// The commit's digest is the hash of the block voted for.
     async fn handle_commit(&self, message: ConsensusMessage) -> Result<(), AstorError> {
-        // Collect commit messages and finalize block
+        let ConsensusMessage::Commit {
+            sequence,
+            digest,
+            node_id,
+            signature,
+            ..
+        } = message
+        else {
+            return Err(AstorError::NetworkError(
+                "Expected a commit message".to_string(),
+            ));
+        };
+
+        self.record_vote(sequence, &digest, &node_id, &signature)
+            .await?;
+        if sequence == self.get_block_height().await + 1 {
+            self.finalize_height(sequence).await?;
+        }
         Ok(())
     }
 
@@ -242,4 +650,406 @@ impl ConsensusEngine {
         let blocks = self.committed_blocks.read().await;
         blocks.len() as u64
     }
+
+    /// Height of the most recent block considered irreversible
+    pub async fn get_finalized_height(&self) -> u64 {
+        self.get_block_height()
+            .await
+            .saturating_sub(self.config.finality_depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{CodecKind, ReconnectPolicy};
+    use crate::transactions::TransactionManager;
+
+    fn test_config(max_block_transactions: usize, max_block_bytes: usize) -> NodeConfig {
+        NodeConfig {
+            node_id: "validator-1".to_string(),
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            bootstrap_peers: vec![],
+            keypair: KeyPair::generate(),
+            max_peers: 8,
+            network_id: "astor-test".to_string(),
+            reconnect: ReconnectPolicy::default(),
+            finality_depth: 6,
+            protocol_codec: CodecKind::default(),
+            max_block_transactions,
+            max_block_bytes,
+            seal_pending_threshold: 1_000,
+            seal_interval_secs: 60,
+        }
+    }
+
+    fn transactions(count: usize) -> Vec<Transaction> {
+        let mut manager = TransactionManager::new();
+        for _ in 0..count {
+            manager.create_transfer("alice", "bob", 100).unwrap();
+        }
+        manager.get_all_transactions().to_vec()
+    }
+
+    async fn seal_all(engine: &ConsensusEngine) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        while let Some(block) = engine.seal_block().await.unwrap() {
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    #[tokio::test]
+    async fn test_excess_transactions_carry_to_next_block() {
+        let engine = ConsensusEngine::new(test_config(10, 1024 * 1024))
+            .await
+            .unwrap();
+        let submitted = transactions(25);
+        for transaction in submitted.clone() {
+            engine.add_transaction(transaction).await.unwrap();
+        }
+
+        let blocks = seal_all(&engine).await;
+        let sizes: Vec<usize> = blocks.iter().map(|b| b.transactions.len()).collect();
+        assert_eq!(sizes, vec![10, 10, 5]);
+
+        // Arrival order is preserved across blocks
+        let sealed: Vec<&str> = blocks
+            .iter()
+            .flat_map(|b| b.transactions.iter().map(|tx| tx.id.as_str()))
+            .collect();
+        let expected: Vec<&str> = submitted.iter().map(|tx| tx.id.as_str()).collect();
+        assert_eq!(sealed, expected);
+
+        assert_eq!(blocks[0].sequence, 1);
+        assert_eq!(
+            blocks[1].previous_hash,
+            hash_data(&serde_json::to_vec(&blocks[0]).unwrap())
+        );
+        assert_eq!(engine.get_block_height().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_sealed_blocks() {
+        let engine = ConsensusEngine::new(test_config(10, 1024 * 1024))
+            .await
+            .unwrap();
+        let mut committed = engine.subscribe_committed_blocks();
+        for transaction in transactions(15) {
+            engine.add_transaction(transaction).await.unwrap();
+        }
+
+        seal_all(&engine).await;
+
+        let mut sizes = Vec::new();
+        while let Ok(block) = committed.try_recv() {
+            sizes.push(block.transactions.len());
+        }
+        assert_eq!(sizes, vec![10, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_blocks_respect_byte_limit() {
+        let submitted = transactions(7);
+        let size = serde_json::to_vec(&submitted[0]).unwrap().len();
+
+        // Room for three transactions, but not four
+        let engine = ConsensusEngine::new(test_config(1_000, size * 3 + size / 2))
+            .await
+            .unwrap();
+        for transaction in submitted {
+            engine.add_transaction(transaction).await.unwrap();
+        }
+
+        let blocks = seal_all(&engine).await;
+        let sizes: Vec<usize> = blocks.iter().map(|b| b.transactions.len()).collect();
+        assert_eq!(sizes, vec![3, 3, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_transaction_rejected() {
+        let engine = ConsensusEngine::new(test_config(10, 16)).await.unwrap();
+        let transaction = transactions(1).remove(0);
+
+        assert!(engine.add_transaction(transaction).await.is_err());
+        assert!(engine.seal_block().await.unwrap().is_none());
+    }
+
+    fn candidate(view: u64, transactions: Vec<Transaction>) -> Block {
+        Block {
+            sequence: 1,
+            view,
+            merkle_root: format!("digest_{}", transactions.len()),
+            transactions,
+            previous_hash: "0".repeat(64),
+            timestamp: 1_700_000_000,
+            validator_signatures: HashMap::new(),
+        }
+    }
+
+    fn validator_keys(count: usize) -> Vec<(String, KeyPair)> {
+        (1..=count)
+            .map(|i| (format!("validator-{}", i), KeyPair::generate()))
+            .collect()
+    }
+
+    async fn engine_with_validators(validators: &[(String, KeyPair)]) -> ConsensusEngine {
+        let engine = ConsensusEngine::new(test_config(10, 1024 * 1024))
+            .await
+            .unwrap();
+        for (node_id, keypair) in validators {
+            engine
+                .add_validator(node_id.clone(), keypair.public_key())
+                .await;
+        }
+        engine
+    }
+
+    fn vote(keypair: &KeyPair, sequence: u64, block_hash: &str) -> Signature {
+        keypair.sign(&vote_message(sequence, block_hash))
+    }
+
+    #[tokio::test]
+    async fn test_proposals_finalize_the_same_block_on_every_node() {
+        let candidates = vec![
+            candidate(0, transactions(1)),
+            candidate(0, transactions(2)),
+            candidate(1, transactions(1)),
+        ];
+        let hashes: Vec<String> = candidates.iter().map(|b| b.hash().unwrap()).collect();
+        let validators = validator_keys(4);
+        let votes = [(2, 0), (2, 1), (0, 3), (2, 2)];
+
+        let mut finalized = Vec::new();
+        for reversed in [false, true] {
+            let engine = engine_with_validators(&validators).await;
+            let mut order: Vec<usize> = (0..candidates.len()).collect();
+            let mut vote_order = votes.to_vec();
+            if reversed {
+                order.reverse();
+                vote_order.reverse();
+            }
+
+            for index in order {
+                engine
+                    .propose_block(candidates[index].clone())
+                    .await
+                    .unwrap();
+            }
+            for (index, validator) in vote_order {
+                let (node_id, keypair) = &validators[validator];
+                engine
+                    .record_vote(
+                        1,
+                        &hashes[index],
+                        node_id,
+                        &vote(keypair, 1, &hashes[index]),
+                    )
+                    .await
+                    .unwrap();
+            }
+            let block = engine.finalize_height(1).await.unwrap().unwrap();
+            finalized.push(block.hash().unwrap());
+            assert_eq!(block.validator_signatures.len(), 3);
+            assert_eq!(engine.get_block_height().await, 1);
+        }
+
+        assert_eq!(finalized[0], finalized[1]);
+        assert_eq!(finalized[0], hashes[2]);
+    }
+
+    #[tokio::test]
+    async fn test_block_needs_a_quorum_of_signed_votes() {
+        let validators = validator_keys(4);
+        let engine = engine_with_validators(&validators).await;
+        assert_eq!(engine.quorum().await, 3);
+
+        let block_hash = engine
+            .propose_block(candidate(0, transactions(1)))
+            .await
+            .unwrap();
+        let other_hash = engine
+            .propose_block(candidate(1, transactions(1)))
+            .await
+            .unwrap();
+
+        // Outsiders and forged signatures do not count
+        let outsider = KeyPair::generate();
+        assert!(engine
+            .record_vote(1, &block_hash, "outsider", &vote(&outsider, 1, &block_hash))
+            .await
+            .is_err());
+        let (node_id, _) = &validators[0];
+        assert!(engine
+            .record_vote(1, &block_hash, node_id, &vote(&outsider, 1, &block_hash))
+            .await
+            .is_err());
+
+        // Repeated votes count once
+        for _ in 0..3 {
+            let (node_id, keypair) = &validators[0];
+            engine
+                .record_vote(1, &block_hash, node_id, &vote(keypair, 1, &block_hash))
+                .await
+                .unwrap();
+        }
+        let (node_id, keypair) = &validators[1];
+        engine
+            .record_vote(1, &block_hash, node_id, &vote(keypair, 1, &block_hash))
+            .await
+            .unwrap();
+        assert!(engine.finalize_height(1).await.unwrap().is_none());
+
+        // A validator cannot vote for two blocks at one height
+        assert!(engine
+            .record_vote(1, &other_hash, node_id, &vote(keypair, 1, &other_hash))
+            .await
+            .is_err());
+
+        // The third distinct validator's commit finalizes the block
+        let (node_id, keypair) = &validators[2];
+        engine
+            .handle_consensus_message(ConsensusMessage::Commit {
+                view: 0,
+                sequence: 1,
+                digest: block_hash.clone(),
+                node_id: node_id.clone(),
+                signature: vote(keypair, 1, &block_hash),
+            })
+            .await
+            .unwrap();
+        assert_eq!(engine.get_block_height().await, 1);
+        assert_eq!(
+            engine.get_latest_block().await.unwrap().hash().unwrap(),
+            block_hash
+        );
+    }
+
+    #[tokio::test]
+    async fn test_block_sealed_on_reaching_pending_threshold() {
+        let mut config = test_config(1_000, 1024 * 1024);
+        config.seal_pending_threshold = 5;
+        let mut engine = ConsensusEngine::new(config).await.unwrap();
+        engine.start().await.unwrap();
+        let submitted = transactions(5);
+
+        for transaction in submitted[..4].iter().cloned() {
+            engine.add_transaction(transaction).await.unwrap();
+        }
+        assert_eq!(engine.get_block_height().await, 0);
+
+        engine.add_transaction(submitted[4].clone()).await.unwrap();
+        assert_eq!(engine.get_block_height().await, 1);
+        assert_eq!(
+            engine.get_latest_block().await.unwrap().transactions.len(),
+            5
+        );
+        assert!(engine.pending_transactions.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_block_sealed_on_interval_elapsing() {
+        let mut engine = ConsensusEngine::new(test_config(1_000, 1024 * 1024))
+            .await
+            .unwrap();
+        engine.start().await.unwrap();
+        let started = *engine.last_sealed_at.read().await;
+        for transaction in transactions(2) {
+            engine.add_transaction(transaction).await.unwrap();
+        }
+
+        assert!(engine
+            .seal_if_due_at(started + Duration::seconds(59))
+            .await
+            .unwrap()
+            .is_none());
+
+        let block = engine
+            .seal_if_due_at(started + Duration::seconds(60))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.transactions.len(), 2);
+        assert!(engine.pending_transactions.read().await.is_empty());
+
+        // The interval restarts from the seal, and an empty interval seals nothing
+        assert!(engine
+            .seal_if_due_at(started + Duration::seconds(200))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(engine.get_block_height().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_sealed_block_waits_for_a_quorum_of_validators() {
+        let mut engine = ConsensusEngine::new(test_config(10, 1024 * 1024))
+            .await
+            .unwrap();
+        engine.start().await.unwrap();
+        let peers = validator_keys(4).split_off(1);
+        for (node_id, keypair) in &peers {
+            engine
+                .add_validator(node_id.clone(), keypair.public_key())
+                .await;
+        }
+        let mut committed = engine.subscribe_committed_blocks();
+        for transaction in transactions(15) {
+            engine.add_transaction(transaction).await.unwrap();
+        }
+
+        // Proposed with this node's vote alone, short of the quorum of 3
+        assert!(engine.seal_block().await.unwrap().is_none());
+        assert_eq!(engine.get_block_height().await, 0);
+        assert!(committed.try_recv().is_err());
+        // Nothing more is sealed until the proposal is decided
+        assert!(engine.seal_block().await.unwrap().is_none());
+        assert_eq!(engine.pending_transactions.read().await.len(), 5);
+
+        let block_hash = engine.proposals.read().await[&1]
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        for (node_id, keypair) in &peers[..2] {
+            engine
+                .handle_consensus_message(ConsensusMessage::Commit {
+                    view: 0,
+                    sequence: 1,
+                    digest: block_hash.clone(),
+                    node_id: node_id.clone(),
+                    signature: vote(keypair, 1, &block_hash),
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(engine.get_block_height().await, 1);
+        let block = committed.try_recv().unwrap();
+        assert_eq!(block.transactions.len(), 10);
+        assert_eq!(block.validator_signatures.len(), 3);
+        assert!(block
+            .validator_signatures
+            .contains_key(&engine.config.node_id));
+    }
+
+    #[tokio::test]
+    async fn test_only_primary_seals_blocks() {
+        let mut config = test_config(1_000, 1024 * 1024);
+        config.seal_pending_threshold = 1;
+        let engine = ConsensusEngine::new(config).await.unwrap();
+        let started = *engine.last_sealed_at.read().await;
+
+        engine
+            .add_transaction(transactions(1).remove(0))
+            .await
+            .unwrap();
+        assert!(engine
+            .seal_if_due_at(started + Duration::seconds(600))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(engine.get_block_height().await, 0);
+        assert_eq!(engine.pending_transactions.read().await.len(), 1);
+    }
 }
@@ -16,6 +16,39 @@ pub struct PeerInfo {
     pub last_seen: u64,
     pub reputation: i32,
     pub capabilities: Vec<String>,
+    /// Most recent measured round-trip time
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    /// Block height the peer last reported
+    #[serde(default)]
+    pub reported_height: u64,
+    /// Accumulated penalty points for protocol violations
+    #[serde(default)]
+    pub misbehavior_score: u32,
+}
+
+/// Per-peer diagnostics for operators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerDetail {
+    pub id: String,
+    pub address: SocketAddr,
+    pub last_seen: u64,
+    pub latency_ms: Option<u64>,
+    pub reported_height: u64,
+    pub misbehavior_score: u32,
+}
+
+impl From<&PeerInfo> for PeerDetail {
+    fn from(peer: &PeerInfo) -> Self {
+        Self {
+            id: peer.id.clone(),
+            address: peer.address,
+            last_seen: peer.last_seen,
+            latency_ms: peer.latency_ms,
+            reported_height: peer.reported_height,
+            misbehavior_score: peer.misbehavior_score,
+        }
+    }
 }
 
 pub struct PeerDiscovery {
@@ -98,6 +131,9 @@ impl PeerDiscovery {
                         .as_secs(),
                     reputation: 100,
                     capabilities: vec!["consensus".to_string(), "sync".to_string()],
+                    latency_ms: None,
+                    reported_height: 0,
+                    misbehavior_score: 0,
                 };
 
                 self.add_peer(peer_info).await?;
@@ -179,6 +215,36 @@ impl PeerDiscovery {
         Ok(())
     }
 
+    /// Record a measured round-trip time to a peer
+    pub async fn record_peer_latency(&self, peer_id: &str, latency: std::time::Duration) {
+        let mut peers = self.known_peers.write().await;
+        if let Some(peer) = peers.get_mut(peer_id) {
+            peer.latency_ms = Some(latency.as_millis() as u64);
+        }
+    }
+
+    /// Record the block height a peer reported
+    pub async fn update_peer_height(&self, peer_id: &str, height: u64) {
+        let mut peers = self.known_peers.write().await;
+        if let Some(peer) = peers.get_mut(peer_id) {
+            peer.reported_height = height;
+        }
+    }
+
+    /// Penalize a peer for a protocol violation
+    pub async fn record_peer_misbehavior(&self, peer_id: &str, points: u32) {
+        let mut peers = self.known_peers.write().await;
+        if let Some(peer) = peers.get_mut(peer_id) {
+            peer.misbehavior_score = peer.misbehavior_score.saturating_add(points);
+            tracing::warn!(
+                "Peer {} misbehaved (+{}), score now {}",
+                peer_id,
+                points,
+                peer.misbehavior_score
+            );
+        }
+    }
+
     pub async fn cleanup_stale_peers(&self) -> Result<(), AstorError> {
         let mut peers = self.known_peers.write().await;
         let current_time = std::time::SystemTime::now()
@@ -2,22 +2,30 @@
 //!
 //! Provides node discovery, consensus mechanisms, and network synchronization
 
+pub mod capabilities;
+pub mod codec;
 pub mod consensus;
 pub mod discovery;
 pub mod node;
 pub mod protocol;
 pub mod sync;
 
+pub use capabilities::{LocalCapabilities, PeerCapabilities, PROTOCOL_VERSION};
+pub use codec::{CodecKind, ProtocolCodec};
 pub use consensus::{ConsensusEngine, ConsensusMessage, ConsensusState};
-pub use discovery::{PeerDiscovery, PeerInfo};
-pub use node::{AstorNode, NodeConfig, NodeInfo, NodeStatus};
+pub use discovery::{PeerDetail, PeerDiscovery, PeerInfo};
+pub use node::{
+    AstorNode, ConnectionMetrics, ConnectionState, NodeConfig, NodeInfo, NodeStatus,
+    ReconnectPolicy,
+};
 pub use protocol::{MessageType, NetworkMessage, ProtocolHandler};
 pub use sync::{NetworkSync, SyncManager};
 
 use crate::errors::AstorError;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 
 /// Network manager that coordinates all networking operations
 pub struct NetworkManager {
@@ -26,16 +34,22 @@ pub struct NetworkManager {
     pub discovery: Arc<RwLock<PeerDiscovery>>,
     pub sync_manager: Arc<RwLock<SyncManager>>,
     pub protocol_handler: Arc<RwLock<ProtocolHandler>>,
+    /// Periodic block sealing, running while the network is started
+    sealing_task: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl NetworkManager {
     /// Create a new network manager
     pub async fn new(config: NodeConfig) -> Result<Self, AstorError> {
+        config.validate()?;
+
         let node = Arc::new(RwLock::new(AstorNode::new(config.clone()).await?));
         let consensus = Arc::new(RwLock::new(ConsensusEngine::new(config.clone()).await?));
         let discovery = Arc::new(RwLock::new(PeerDiscovery::new(config.clone()).await?));
         let sync_manager = Arc::new(RwLock::new(SyncManager::new().await?));
-        let protocol_handler = Arc::new(RwLock::new(ProtocolHandler::new().await?));
+        let mut protocol_handler = ProtocolHandler::new().await?;
+        protocol_handler.set_local_capabilities(node.read().await.local_capabilities());
+        let protocol_handler = Arc::new(RwLock::new(protocol_handler));
 
         Ok(Self {
             node,
@@ -43,6 +57,7 @@ impl NetworkManager {
             discovery,
             sync_manager,
             protocol_handler,
+            sealing_task: Mutex::new(None),
         })
     }
 
@@ -56,6 +71,10 @@ impl NetworkManager {
 
         // Start consensus engine
         self.consensus.write().await.start().await?;
+        let sealing = ConsensusEngine::spawn_block_sealing(self.consensus.clone());
+        if let Some(previous) = self.sealing_task.lock().await.replace(sealing) {
+            previous.abort();
+        }
 
         // Start sync manager
         self.sync_manager.write().await.start().await?;
@@ -66,12 +85,36 @@ impl NetworkManager {
     /// Stop all network services
     pub async fn stop(&self) -> Result<(), AstorError> {
         self.sync_manager.write().await.stop().await?;
+        if let Some(sealing) = self.sealing_task.lock().await.take() {
+            sealing.abort();
+        }
         self.consensus.write().await.stop().await?;
         self.discovery.write().await.stop().await?;
         self.node.write().await.stop().await?;
         Ok(())
     }
 
+    /// Handle a peer whose connection was lost
+    ///
+    /// The node drops the connection and schedules any reconnect; the
+    /// capabilities negotiated with the peer are forgotten so a reconnect
+    /// negotiates afresh. Returns the scheduled reconnect delay.
+    pub async fn handle_peer_disconnected(
+        &self,
+        peer_id: &str,
+    ) -> Result<Option<std::time::Duration>, AstorError> {
+        self.protocol_handler
+            .read()
+            .await
+            .remove_peer(peer_id)
+            .await;
+        self.node
+            .read()
+            .await
+            .handle_peer_disconnected(peer_id)
+            .await
+    }
+
     /// Get network status
     pub async fn get_network_status(&self) -> NetworkStatus {
         let node = self.node.read().await;
@@ -85,6 +128,23 @@ impl NetworkManager {
             is_synced: self.sync_manager.read().await.is_synced(),
         }
     }
+
+    /// Get per-peer diagnostics, lowest latency first
+    ///
+    /// Peers without a latency measurement are listed last.
+    pub async fn get_peer_details(&self) -> Vec<PeerDetail> {
+        let peers = self.discovery.read().await.get_all_peers().await;
+        let mut details: Vec<PeerDetail> = peers.iter().map(PeerDetail::from).collect();
+
+        details.sort_by(|a, b| {
+            a.latency_ms
+                .is_none()
+                .cmp(&b.latency_ms.is_none())
+                .then(a.latency_ms.cmp(&b.latency_ms))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        details
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -94,3 +154,100 @@ pub struct NetworkStatus {
     pub consensus_state: ConsensusState,
     pub is_synced: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::KeyPair;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    fn test_config() -> NodeConfig {
+        NodeConfig {
+            node_id: "test-node".to_string(),
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            bootstrap_peers: vec!["127.0.0.1:9".parse().unwrap()],
+            keypair: KeyPair::generate(),
+            max_peers: 8,
+            network_id: "astor-test".to_string(),
+            reconnect: ReconnectPolicy::default(),
+            finality_depth: 6,
+            protocol_codec: CodecKind::default(),
+            max_block_transactions: 1_000,
+            max_block_bytes: 1024 * 1024,
+            seal_pending_threshold: 1_000,
+            seal_interval_secs: 5,
+        }
+    }
+
+    fn fake_peer(id: &str, address: &str) -> PeerInfo {
+        PeerInfo {
+            id: id.to_string(),
+            address: address.parse::<SocketAddr>().unwrap(),
+            public_key: vec![0; 32],
+            last_seen: 1_700_000_000,
+            reputation: 100,
+            capabilities: vec![],
+            latency_ms: None,
+            reported_height: 0,
+            misbehavior_score: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_peer_details_sorted_by_latency() {
+        let manager = NetworkManager::new(test_config()).await.unwrap();
+
+        {
+            let discovery = manager.discovery.read().await;
+            for (id, address, latency, height) in [
+                ("slow", "10.0.0.1:7000", Some(250), 90),
+                ("fast", "10.0.0.2:7000", Some(12), 120),
+                ("unmeasured", "10.0.0.3:7000", None, 0),
+                ("medium", "10.0.0.4:7000", Some(80), 118),
+            ] {
+                discovery.add_peer(fake_peer(id, address)).await.unwrap();
+                if let Some(latency) = latency {
+                    discovery
+                        .record_peer_latency(id, Duration::from_millis(latency))
+                        .await;
+                }
+                discovery.update_peer_height(id, height).await;
+            }
+            discovery.record_peer_misbehavior("slow", 15).await;
+        }
+
+        let details = manager.get_peer_details().await;
+        let ids: Vec<&str> = details.iter().map(|peer| peer.id.as_str()).collect();
+        assert_eq!(ids, vec!["fast", "medium", "slow", "unmeasured"]);
+
+        assert_eq!(details[0].latency_ms, Some(12));
+        assert_eq!(details[0].reported_height, 120);
+        assert_eq!(details[0].address, "10.0.0.2:7000".parse().unwrap());
+        assert_eq!(details[2].reported_height, 90);
+        assert_eq!(details[2].misbehavior_score, 15);
+        assert_eq!(details[3].latency_ms, None);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_handler_uses_configured_codec_and_forgets_disconnected_peers() {
+        let mut config = test_config();
+        config.protocol_codec = CodecKind::Bincode;
+        let manager = NetworkManager::new(config).await.unwrap();
+
+        let remote = LocalCapabilities::new(CodecKind::Bincode).advertise();
+        let handler = manager.protocol_handler.read().await;
+        let negotiated = handler.negotiate_peer("peer-1", &remote).await.unwrap();
+        assert_eq!(negotiated.codec, CodecKind::Bincode);
+        drop(handler);
+
+        manager.handle_peer_disconnected("peer-1").await.unwrap();
+        assert!(manager
+            .protocol_handler
+            .read()
+            .await
+            .peer_capabilities("peer-1")
+            .await
+            .is_none());
+    }
+}
@@ -1,15 +1,26 @@
 //! Core node implementation for the Astor network
 
+use super::capabilities::LocalCapabilities;
+use super::codec::CodecKind;
+use super::protocol::{MessagePayload, MessageType, NetworkMessage as ProtocolMessage};
 use crate::errors::AstorError;
 use crate::security::KeyPair;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc, RwLock};
 use uuid::Uuid;
 
+/// Largest handshake frame accepted from a peer
+const MAX_HANDSHAKE_BYTES: usize = 64 * 1024;
+
+/// How long a peer has to answer our handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeConfig {
     pub node_id: String,
@@ -18,6 +29,203 @@ pub struct NodeConfig {
     pub keypair: KeyPair,
     pub max_peers: usize,
     pub network_id: String,
+    #[serde(default)]
+    pub reconnect: ReconnectPolicy,
+    /// Number of subsequent blocks required before a block is final
+    #[serde(default = "default_finality_depth")]
+    pub finality_depth: u64,
+    /// Preferred wire codec; JSON is always offered as a fallback
+    #[serde(default)]
+    pub protocol_codec: CodecKind,
+    /// Maximum number of transactions sealed into one block
+    #[serde(default = "default_max_block_transactions")]
+    pub max_block_transactions: usize,
+    /// Maximum serialized size of a block's transactions in bytes
+    #[serde(default = "default_max_block_bytes")]
+    pub max_block_bytes: usize,
+    /// A block is sealed as soon as this many transactions are pending
+    #[serde(default = "default_seal_pending_threshold")]
+    pub seal_pending_threshold: usize,
+    /// A block is sealed once this long has passed since the last one,
+    /// if anything is pending
+    #[serde(default = "default_seal_interval_secs")]
+    pub seal_interval_secs: u64,
+}
+
+fn default_finality_depth() -> u64 {
+    6
+}
+
+fn default_max_block_transactions() -> usize {
+    1_000
+}
+
+fn default_max_block_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_seal_pending_threshold() -> usize {
+    1_000
+}
+
+fn default_seal_interval_secs() -> u64 {
+    5
+}
+
+impl NodeConfig {
+    /// Validate the configuration, rejecting settings that would leave the
+    /// node unable to join or stay in the network
+    pub fn validate(&self) -> Result<(), AstorError> {
+        if self.node_id.trim().is_empty() {
+            return Err(AstorError::ConfigurationError(
+                "Node ID must not be empty".to_string(),
+            ));
+        }
+
+        if self.network_id.trim().is_empty() {
+            return Err(AstorError::ConfigurationError(
+                "Network ID must not be empty".to_string(),
+            ));
+        }
+
+        if self.max_peers == 0 {
+            return Err(AstorError::ConfigurationError(
+                "max_peers must be > 0, otherwise the node can never connect".to_string(),
+            ));
+        }
+
+        if self.max_block_transactions == 0 || self.max_block_bytes == 0 {
+            return Err(AstorError::ConfigurationError(
+                "Block limits must be > 0, otherwise no block can be sealed".to_string(),
+            ));
+        }
+
+        if self.seal_pending_threshold == 0 || self.seal_interval_secs == 0 {
+            return Err(AstorError::ConfigurationError(
+                "Seal threshold and interval must be > 0".to_string(),
+            ));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for peer in &self.bootstrap_peers {
+            if Self::is_same_endpoint(&self.listen_addr, peer) {
+                return Err(AstorError::ConfigurationError(format!(
+                    "Bootstrap peer {} conflicts with listen address {}",
+                    peer, self.listen_addr
+                )));
+            }
+            if !seen.insert(*peer) {
+                return Err(AstorError::ConfigurationError(format!(
+                    "Bootstrap peer {} is listed more than once",
+                    peer
+                )));
+            }
+        }
+
+        let reconnect = &self.reconnect;
+        if reconnect.initial_delay_ms == 0 {
+            return Err(AstorError::ConfigurationError(
+                "Reconnect initial delay must be > 0".to_string(),
+            ));
+        }
+        if reconnect.max_delay_ms < reconnect.initial_delay_ms {
+            return Err(AstorError::ConfigurationError(
+                "Reconnect max delay must be >= initial delay".to_string(),
+            ));
+        }
+        if reconnect.multiplier.is_nan() || reconnect.multiplier < 1.0 {
+            return Err(AstorError::ConfigurationError(
+                "Reconnect multiplier must be >= 1.0".to_string(),
+            ));
+        }
+        if !(0.0..1.0).contains(&reconnect.jitter_ratio) {
+            return Err(AstorError::ConfigurationError(
+                "Reconnect jitter ratio must be in [0.0, 1.0)".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether a peer address points back at our own listener
+    fn is_same_endpoint(listen_addr: &SocketAddr, peer: &SocketAddr) -> bool {
+        if listen_addr.port() != peer.port() {
+            return false;
+        }
+
+        listen_addr.ip() == peer.ip()
+            || (listen_addr.ip().is_unspecified()
+                && (peer.ip().is_loopback() || peer.ip().is_unspecified()))
+    }
+}
+
+/// Exponential backoff policy used when reconnecting to dropped bootstrap peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconnectPolicy {
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub multiplier: f64,
+    /// Fraction of the delay to randomize, e.g. 0.2 gives +/-20%
+    pub jitter_ratio: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 500,
+            max_delay_ms: 60_000,
+            multiplier: 2.0,
+            jitter_ratio: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Backoff delay before the given (1-based) reconnect attempt, without jitter
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let delay_ms = self.initial_delay_ms as f64 * self.multiplier.powi(exponent);
+        Duration::from_millis(delay_ms.min(self.max_delay_ms as f64) as u64)
+    }
+
+    /// Backoff delay with jitter applied, still capped at the max interval
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt).as_millis() as f64;
+        let jitter = if self.jitter_ratio > 0.0 {
+            rand::random::<f64>() * 2.0 * self.jitter_ratio - self.jitter_ratio
+        } else {
+            0.0
+        };
+        let delay_ms = (base * (1.0 + jitter)).clamp(0.0, self.max_delay_ms as f64);
+        Duration::from_millis(delay_ms as u64)
+    }
+}
+
+/// Connection state of a bootstrap peer
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connected,
+    Reconnecting { attempt: u32, delay: Duration },
+    Disconnected,
+}
+
+/// Pending reconnect to a dropped peer
+#[derive(Debug, Clone)]
+pub struct ReconnectState {
+    pub attempt: u32,
+    pub delay: Duration,
+    pub next_attempt_at: Instant,
+}
+
+/// Connection-state metrics exposed by the node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionMetrics {
+    pub connected_peers: usize,
+    pub pending_reconnects: usize,
+    pub disconnections: u64,
+    pub reconnect_attempts: u64,
+    pub successful_reconnects: u64,
+    pub failed_reconnects: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,9 +251,16 @@ pub struct AstorNode {
     config: NodeConfig,
     status: NodeStatus,
     peers: Arc<RwLock<HashMap<String, PeerConnection>>>,
-    listener: Option<TcpListener>,
+    /// Address the listener is bound to, once started
+    local_addr: Option<SocketAddr>,
+    /// Loop accepting inbound connections, once started
+    accept_task: Option<tokio::task::JoinHandle<()>>,
     message_sender: mpsc::UnboundedSender<NetworkMessage>,
     message_receiver: Option<mpsc::UnboundedReceiver<NetworkMessage>>,
+    peer_addresses: Arc<RwLock<HashMap<String, SocketAddr>>>,
+    reconnects: Arc<RwLock<HashMap<SocketAddr, ReconnectState>>>,
+    connection_metrics: Arc<RwLock<ConnectionMetrics>>,
+    peer_codecs: Arc<RwLock<HashMap<String, CodecKind>>>,
 }
 
 #[derive(Debug)]
@@ -56,6 +271,159 @@ pub struct PeerConnection {
     pub is_outbound: bool,
 }
 
+/// Everything a connection task needs to handshake with a peer and record
+/// it, so reconnects register peers exactly like first connections
+#[derive(Clone)]
+struct PeerRegistrar {
+    node_id: String,
+    network_id: String,
+    handshake: MessagePayload,
+    local_capabilities: LocalCapabilities,
+    max_peers: usize,
+    peers: Arc<RwLock<HashMap<String, PeerConnection>>>,
+    peer_addresses: Arc<RwLock<HashMap<String, SocketAddr>>>,
+    peer_codecs: Arc<RwLock<HashMap<String, CodecKind>>>,
+    connection_metrics: Arc<RwLock<ConnectionMetrics>>,
+}
+
+impl PeerRegistrar {
+    /// Dial `addr`, exchange JSON handshakes and register the peer under the
+    /// node ID it announced, returning that ID
+    async fn connect(&self, addr: SocketAddr) -> Result<String, AstorError> {
+        let stream = AstorNode::dial_peer(addr).await?;
+        self.register(stream, addr, true).await
+    }
+
+    /// Answer the handshake of a peer that dialed in and register it
+    ///
+    /// Inbound peers connect from an ephemeral port, so their address is not
+    /// kept for reconnects; the peer redials us if the connection drops.
+    async fn accept(&self, stream: TcpStream, addr: SocketAddr) -> Result<String, AstorError> {
+        if self.peers.read().await.len() >= self.max_peers {
+            return Err(AstorError::NetworkError(format!(
+                "Refusing {}: already connected to {} peers",
+                addr, self.max_peers
+            )));
+        }
+        self.register(stream, addr, false).await
+    }
+
+    /// Exchange handshakes over `stream` and record the peer
+    async fn register(
+        &self,
+        mut stream: TcpStream,
+        addr: SocketAddr,
+        is_outbound: bool,
+    ) -> Result<String, AstorError> {
+        let reply = tokio::time::timeout(HANDSHAKE_TIMEOUT, self.exchange_handshake(&mut stream))
+            .await
+            .map_err(|_| {
+                AstorError::NetworkError(format!("Handshake with {} timed out", addr))
+            })??;
+
+        let MessagePayload::Handshake {
+            node_id,
+            version,
+            capabilities,
+            public_key,
+        } = reply.payload
+        else {
+            return Err(AstorError::NetworkError(format!(
+                "Peer {} did not answer with a handshake",
+                addr
+            )));
+        };
+        if node_id == self.node_id {
+            return Err(AstorError::NetworkError(format!(
+                "Peer {} is this node",
+                addr
+            )));
+        }
+
+        let codec = self
+            .local_capabilities
+            .negotiate(&node_id, &capabilities)?
+            .codec;
+        tracing::info!("Using {} codec for peer {}", codec.name(), node_id);
+
+        self.peer_codecs
+            .write()
+            .await
+            .insert(node_id.clone(), codec);
+        if is_outbound {
+            self.peer_addresses
+                .write()
+                .await
+                .insert(node_id.clone(), addr);
+        }
+        let mut peers = self.peers.write().await;
+        peers.insert(
+            node_id.clone(),
+            PeerConnection {
+                info: NodeInfo {
+                    id: node_id.clone(),
+                    addr,
+                    public_key,
+                    version,
+                    network_id: self.network_id.clone(),
+                    capabilities,
+                },
+                stream,
+                last_seen: Instant::now(),
+                is_outbound,
+            },
+        );
+        let connected_peers = peers.len();
+        drop(peers);
+        self.connection_metrics.write().await.connected_peers = connected_peers;
+        Ok(node_id)
+    }
+
+    /// Send our handshake and read the peer's, as length-prefixed JSON frames
+    async fn exchange_handshake(
+        &self,
+        stream: &mut TcpStream,
+    ) -> Result<ProtocolMessage, AstorError> {
+        let handshake = ProtocolMessage {
+            id: Uuid::new_v4().to_string(),
+            message_type: MessageType::Handshake,
+            from: self.node_id.clone(),
+            to: None,
+            payload: self.handshake.clone(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            signature: None,
+        };
+        write_frame(stream, &CodecKind::Json.codec().encode(&handshake)?).await?;
+        CodecKind::Json.codec().decode(&read_frame(stream).await?)
+    }
+}
+
+fn io_error(e: std::io::Error) -> AstorError {
+    AstorError::NetworkError(format!("Peer connection failed: {}", e))
+}
+
+async fn write_frame(stream: &mut TcpStream, bytes: &[u8]) -> Result<(), AstorError> {
+    stream
+        .write_u32(bytes.len() as u32)
+        .await
+        .map_err(io_error)?;
+    stream.write_all(bytes).await.map_err(io_error)?;
+    stream.flush().await.map_err(io_error)
+}
+
+async fn read_frame(stream: &mut TcpStream) -> Result<Vec<u8>, AstorError> {
+    let len = stream.read_u32().await.map_err(io_error)? as usize;
+    if len > MAX_HANDSHAKE_BYTES {
+        return Err(AstorError::NetworkError(format!(
+            "Handshake frame of {} bytes exceeds the {} byte limit",
+            len, MAX_HANDSHAKE_BYTES
+        )));
+    }
+    let mut bytes = vec![0; len];
+    stream.read_exact(&mut bytes).await.map_err(io_error)?;
+    Ok(bytes)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkMessage {
     pub from: String,
@@ -68,15 +436,22 @@ pub struct NetworkMessage {
 
 impl AstorNode {
     pub async fn new(config: NodeConfig) -> Result<Self, AstorError> {
+        config.validate()?;
+
         let (message_sender, message_receiver) = mpsc::unbounded_channel();
 
         Ok(Self {
             config,
             status: NodeStatus::Stopped,
             peers: Arc::new(RwLock::new(HashMap::new())),
-            listener: None,
+            local_addr: None,
+            accept_task: None,
             message_sender,
             message_receiver: Some(message_receiver),
+            peer_addresses: Arc::new(RwLock::new(HashMap::new())),
+            reconnects: Arc::new(RwLock::new(HashMap::new())),
+            connection_metrics: Arc::new(RwLock::new(ConnectionMetrics::default())),
+            peer_codecs: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
@@ -87,12 +462,11 @@ impl AstorNode {
         let listener = TcpListener::bind(&self.config.listen_addr)
             .await
             .map_err(|e| AstorError::NetworkError(format!("Failed to bind listener: {}", e)))?;
-
-        self.listener = Some(listener);
+        self.local_addr = Some(listener.local_addr().map_err(io_error)?);
         self.status = NodeStatus::Running;
 
         // Start connection handler
-        self.start_connection_handler().await?;
+        self.start_connection_handler(listener);
 
         // Connect to bootstrap peers
         self.connect_to_bootstrap_peers().await?;
@@ -103,10 +477,18 @@ impl AstorNode {
     pub async fn stop(&mut self) -> Result<(), AstorError> {
         self.status = NodeStatus::Stopping;
 
+        if let Some(accept_task) = self.accept_task.take() {
+            accept_task.abort();
+        }
+
         // Close all peer connections
         let mut peers = self.peers.write().await;
         peers.clear();
 
+        // Cancel pending reconnects; the backoff tasks exit once their entry is gone
+        self.reconnects.write().await.clear();
+        self.peer_addresses.write().await.clear();
+
         self.status = NodeStatus::Stopped;
         Ok(())
     }
@@ -119,29 +501,251 @@ impl AstorNode {
         self.status.clone()
     }
 
-    async fn start_connection_handler(&self) -> Result<(), AstorError> {
-        // Implementation for handling incoming connections
-        Ok(())
+    /// Address the node listens on, once started
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
     }
 
+    /// Accept inbound connections until the node stops, answering each
+    /// peer's handshake on its own task so a slow peer holds up no other
+    fn start_connection_handler(&mut self, listener: TcpListener) {
+        let registrar = self.registrar();
+        self.accept_task = Some(tokio::spawn(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        tracing::warn!("Failed to accept connection: {}", e);
+                        continue;
+                    }
+                };
+                let registrar = registrar.clone();
+                tokio::spawn(async move {
+                    match registrar.accept(stream, addr).await {
+                        Ok(peer_id) => tracing::info!("Accepted peer {} from {}", peer_id, addr),
+                        Err(e) => tracing::warn!("Rejected connection from {}: {}", addr, e),
+                    }
+                });
+            }
+        }));
+    }
+
+    /// Connect to each bootstrap peer, retrying with backoff those that
+    /// cannot be reached yet
     async fn connect_to_bootstrap_peers(&self) -> Result<(), AstorError> {
         for peer_addr in &self.config.bootstrap_peers {
             if let Err(e) = self.connect_to_peer(*peer_addr).await {
                 tracing::warn!("Failed to connect to bootstrap peer {}: {}", peer_addr, e);
+                self.schedule_reconnect(*peer_addr).await;
             }
         }
         Ok(())
     }
 
     async fn connect_to_peer(&self, addr: SocketAddr) -> Result<(), AstorError> {
-        let stream = TcpStream::connect(addr)
+        let peer_id = self.registrar().connect(addr).await?;
+        tracing::info!("Connected to peer {} at {}", peer_id, addr);
+        Ok(())
+    }
+
+    fn registrar(&self) -> PeerRegistrar {
+        PeerRegistrar {
+            node_id: self.config.node_id.clone(),
+            network_id: self.config.network_id.clone(),
+            handshake: self.handshake_payload(),
+            local_capabilities: self.local_capabilities(),
+            max_peers: self.config.max_peers,
+            peers: self.peers.clone(),
+            peer_addresses: self.peer_addresses.clone(),
+            peer_codecs: self.peer_codecs.clone(),
+            connection_metrics: self.connection_metrics.clone(),
+        }
+    }
+
+    async fn dial_peer(addr: SocketAddr) -> Result<TcpStream, AstorError> {
+        TcpStream::connect(addr)
             .await
-            .map_err(|e| AstorError::NetworkError(format!("Failed to connect to peer: {}", e)))?;
+            .map_err(|e| AstorError::NetworkError(format!("Failed to connect to peer: {}", e)))
+    }
 
-        // Perform handshake and add peer
-        // Implementation details...
+    /// Handle a peer whose connection was lost
+    ///
+    /// The peer is removed and, if it is a bootstrap peer, a reconnect is
+    /// scheduled with exponential backoff. Returns the scheduled delay.
+    pub async fn handle_peer_disconnected(
+        &self,
+        peer_id: &str,
+    ) -> Result<Option<Duration>, AstorError> {
+        self.peers.write().await.remove(peer_id);
+        self.peer_codecs.write().await.remove(peer_id);
+        let addr = self.peer_addresses.write().await.remove(peer_id);
 
-        Ok(())
+        {
+            let mut metrics = self.connection_metrics.write().await;
+            metrics.disconnections += 1;
+            metrics.connected_peers = self.peers.read().await.len();
+        }
+
+        tracing::warn!("Lost connection to peer {}", peer_id);
+
+        match addr {
+            Some(addr) if self.config.bootstrap_peers.contains(&addr) => {
+                Ok(Some(self.schedule_reconnect(addr).await))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Schedule the next reconnect attempt to a peer, increasing the backoff
+    /// if an attempt is already pending
+    pub async fn schedule_reconnect(&self, addr: SocketAddr) -> Duration {
+        let mut reconnects = self.reconnects.write().await;
+        let is_new = !reconnects.contains_key(&addr);
+        let attempt = reconnects.get(&addr).map_or(1, |state| state.attempt + 1);
+        let delay = self.config.reconnect.delay_for_attempt(attempt);
+
+        reconnects.insert(
+            addr,
+            ReconnectState {
+                attempt,
+                delay,
+                next_attempt_at: Instant::now() + delay,
+            },
+        );
+        drop(reconnects);
+
+        self.connection_metrics.write().await.pending_reconnects =
+            self.reconnects.read().await.len();
+
+        tracing::info!(
+            "Scheduling reconnect to {} (attempt {}) in {:?}",
+            addr,
+            attempt,
+            delay
+        );
+
+        if is_new {
+            self.spawn_reconnect_task(addr);
+        }
+
+        delay
+    }
+
+    fn spawn_reconnect_task(&self, addr: SocketAddr) {
+        let reconnects = self.reconnects.clone();
+        let metrics = self.connection_metrics.clone();
+        let policy = self.config.reconnect.clone();
+        let registrar = self.registrar();
+
+        tokio::spawn(async move {
+            loop {
+                let next_attempt_at = match reconnects.read().await.get(&addr) {
+                    Some(state) => state.next_attempt_at,
+                    None => return, // Cancelled
+                };
+                tokio::time::sleep_until(next_attempt_at.into()).await;
+
+                if !reconnects.read().await.contains_key(&addr) {
+                    return;
+                }
+
+                metrics.write().await.reconnect_attempts += 1;
+
+                match registrar.connect(addr).await {
+                    Ok(peer_id) => {
+                        reconnects.write().await.remove(&addr);
+                        let mut metrics = metrics.write().await;
+                        metrics.successful_reconnects += 1;
+                        metrics.pending_reconnects = reconnects.read().await.len();
+                        tracing::info!("Reconnected to peer {} at {}", peer_id, addr);
+                        return;
+                    }
+                    Err(e) => {
+                        metrics.write().await.failed_reconnects += 1;
+                        let mut reconnects = reconnects.write().await;
+                        if let Some(state) = reconnects.get_mut(&addr) {
+                            state.attempt += 1;
+                            state.delay = policy.delay_for_attempt(state.attempt);
+                            state.next_attempt_at = Instant::now() + state.delay;
+                            tracing::warn!(
+                                "Reconnect to {} failed: {}; retrying in {:?}",
+                                addr,
+                                e,
+                                state.delay
+                            );
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Get the connection state of a bootstrap peer
+    pub async fn get_connection_state(&self, addr: &SocketAddr) -> ConnectionState {
+        if let Some(state) = self.reconnects.read().await.get(addr) {
+            return ConnectionState::Reconnecting {
+                attempt: state.attempt,
+                delay: state.delay,
+            };
+        }
+
+        if self.peer_addresses.read().await.values().any(|a| a == addr) {
+            ConnectionState::Connected
+        } else {
+            ConnectionState::Disconnected
+        }
+    }
+
+    /// Get connection-state metrics
+    pub async fn get_connection_metrics(&self) -> ConnectionMetrics {
+        let mut metrics = self.connection_metrics.read().await.clone();
+        metrics.connected_peers = self.peers.read().await.len();
+        metrics.pending_reconnects = self.reconnects.read().await.len();
+        metrics
+    }
+
+    /// Protocol versions and codecs this node offers, from its configuration
+    pub fn local_capabilities(&self) -> LocalCapabilities {
+        LocalCapabilities::new(self.config.protocol_codec)
+    }
+
+    /// Handshake payload advertising this node's protocol versions and codecs
+    pub fn handshake_payload(&self) -> MessagePayload {
+        MessagePayload::Handshake {
+            node_id: self.config.node_id.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            capabilities: self.local_capabilities().advertise(),
+            public_key: self.config.keypair.public_key().as_bytes().to_vec(),
+        }
+    }
+
+    /// Agree on a wire codec with a peer from the capabilities in its handshake
+    pub async fn negotiate_peer_codec(
+        &self,
+        peer_id: &str,
+        capabilities: &[String],
+    ) -> Result<CodecKind, AstorError> {
+        let codec = self
+            .local_capabilities()
+            .negotiate(peer_id, capabilities)?
+            .codec;
+
+        self.peer_codecs
+            .write()
+            .await
+            .insert(peer_id.to_string(), codec);
+        tracing::info!("Using {} codec for peer {}", codec.name(), peer_id);
+        Ok(codec)
+    }
+
+    /// Codec negotiated with a peer; JSON until the handshake completes
+    pub async fn get_peer_codec(&self, peer_id: &str) -> CodecKind {
+        self.peer_codecs
+            .read()
+            .await
+            .get(peer_id)
+            .copied()
+            .unwrap_or_default()
     }
 
     pub async fn broadcast_message(&self, message: NetworkMessage) -> Result<(), AstorError> {
@@ -181,3 +785,309 @@ impl AstorNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(bootstrap: SocketAddr) -> NodeConfig {
+        NodeConfig {
+            node_id: "test-node".to_string(),
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            bootstrap_peers: vec![bootstrap],
+            keypair: KeyPair::generate(),
+            max_peers: 8,
+            network_id: "astor-test".to_string(),
+            reconnect: ReconnectPolicy {
+                initial_delay_ms: 60_000,
+                max_delay_ms: 600_000,
+                multiplier: 2.0,
+                jitter_ratio: 0.0,
+            },
+            finality_depth: 6,
+            protocol_codec: CodecKind::Bincode,
+            max_block_transactions: 1_000,
+            max_block_bytes: 1024 * 1024,
+            seal_pending_threshold: 1_000,
+            seal_interval_secs: 5,
+        }
+    }
+
+    fn handshake_capabilities(node: &AstorNode) -> Vec<String> {
+        match node.handshake_payload() {
+            MessagePayload::Handshake { capabilities, .. } => capabilities,
+            other => panic!("unexpected payload {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshake_negotiates_shared_codec() {
+        let bootstrap: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let binary_node = AstorNode::new(test_config(bootstrap)).await.unwrap();
+        let mut json_config = test_config(bootstrap);
+        json_config.node_id = "json-node".to_string();
+        json_config.protocol_codec = CodecKind::Json;
+        let json_node = AstorNode::new(json_config).await.unwrap();
+
+        let from_binary = handshake_capabilities(&binary_node);
+        let from_json = handshake_capabilities(&json_node);
+
+        assert_eq!(
+            binary_node
+                .negotiate_peer_codec("json-node", &from_json)
+                .await
+                .unwrap(),
+            CodecKind::Json
+        );
+        assert_eq!(
+            json_node
+                .negotiate_peer_codec("test-node", &from_binary)
+                .await
+                .unwrap(),
+            CodecKind::Json
+        );
+        assert_eq!(
+            binary_node
+                .negotiate_peer_codec("binary-peer", &from_binary)
+                .await
+                .unwrap(),
+            CodecKind::Bincode
+        );
+        assert_eq!(
+            binary_node.get_peer_codec("binary-peer").await,
+            CodecKind::Bincode
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dropped_peer_schedules_reconnect_with_backoff() {
+        let bootstrap: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let node = AstorNode::new(test_config(bootstrap)).await.unwrap();
+        node.peer_addresses
+            .write()
+            .await
+            .insert("peer-1".to_string(), bootstrap);
+
+        let first = node
+            .handle_peer_disconnected("peer-1")
+            .await
+            .unwrap()
+            .expect("reconnect should be scheduled for a bootstrap peer");
+        assert_eq!(first, Duration::from_millis(60_000));
+        assert_eq!(
+            node.get_connection_state(&bootstrap).await,
+            ConnectionState::Reconnecting {
+                attempt: 1,
+                delay: first
+            }
+        );
+
+        let second = node.schedule_reconnect(bootstrap).await;
+        assert!(second > first);
+
+        let metrics = node.get_connection_metrics().await;
+        assert_eq!(metrics.disconnections, 1);
+        assert_eq!(metrics.pending_reconnects, 1);
+    }
+
+    /// Accept one connection and answer its handshake as `peer`
+    async fn spawn_fake_peer(peer: AstorNode) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let reply = peer.registrar().exchange_handshake(&mut stream).await;
+            assert!(matches!(
+                reply.unwrap().payload,
+                MessagePayload::Handshake { .. }
+            ));
+            // Keep the connection open until the test ends
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_connect_handshakes_and_records_peer() {
+        let mut peer_config = test_config("127.0.0.1:9".parse().unwrap());
+        peer_config.node_id = "peer-node".to_string();
+        peer_config.protocol_codec = CodecKind::Json;
+        let peer_addr = spawn_fake_peer(AstorNode::new(peer_config).await.unwrap()).await;
+
+        let node = AstorNode::new(test_config(peer_addr)).await.unwrap();
+        node.connect_to_peer(peer_addr).await.unwrap();
+
+        assert_eq!(node.get_peer_count().await, 1);
+        assert_eq!(
+            node.get_connection_state(&peer_addr).await,
+            ConnectionState::Connected
+        );
+        assert_eq!(node.get_peer_codec("peer-node").await, CodecKind::Json);
+        assert_eq!(node.peers.read().await["peer-node"].info.addr, peer_addr);
+
+        // A dropped connection is retried because its address was recorded
+        assert!(node
+            .handle_peer_disconnected("peer-node")
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_registers_peer_again() {
+        let mut peer_config = test_config("127.0.0.1:9".parse().unwrap());
+        peer_config.node_id = "peer-node".to_string();
+        let peer_addr = spawn_fake_peer(AstorNode::new(peer_config).await.unwrap()).await;
+
+        let mut config = test_config(peer_addr);
+        config.reconnect.initial_delay_ms = 10;
+        let node = AstorNode::new(config).await.unwrap();
+        node.schedule_reconnect(peer_addr).await;
+
+        for _ in 0..100 {
+            if node.get_peer_count().await == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(
+            node.get_connection_state(&peer_addr).await,
+            ConnectionState::Connected
+        );
+        assert_eq!(node.get_peer_codec("peer-node").await, CodecKind::Bincode);
+        let metrics = node.get_connection_metrics().await;
+        assert_eq!(metrics.successful_reconnects, 1);
+        assert_eq!(metrics.pending_reconnects, 0);
+    }
+
+    #[tokio::test]
+    async fn test_started_node_accepts_peers_and_retries_unreachable_bootstrap() {
+        let unreachable: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let mut node = AstorNode::new(test_config(unreachable)).await.unwrap();
+        node.start().await.unwrap();
+        assert!(matches!(
+            node.get_connection_state(&unreachable).await,
+            ConnectionState::Reconnecting { attempt: 1, .. }
+        ));
+
+        let listen_addr = node.local_addr().unwrap();
+        let mut dialer_config = test_config(listen_addr);
+        dialer_config.node_id = "dialer-node".to_string();
+        dialer_config.protocol_codec = CodecKind::Json;
+        let dialer = AstorNode::new(dialer_config).await.unwrap();
+        dialer.connect_to_peer(listen_addr).await.unwrap();
+        assert_eq!(dialer.get_peer_count().await, 1);
+
+        for _ in 0..100 {
+            if node.get_peer_count().await == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let peers = node.peers.read().await;
+        assert!(!peers["dialer-node"].is_outbound);
+        drop(peers);
+        assert_eq!(node.get_peer_codec("dialer-node").await, CodecKind::Json);
+
+        // Inbound peers redial us themselves
+        assert_eq!(
+            node.handle_peer_disconnected("dialer-node").await.unwrap(),
+            None
+        );
+        node.stop().await.unwrap();
+    }
+
+    fn assert_invalid(config: NodeConfig, expected: &str) {
+        match config.validate() {
+            Err(AstorError::ConfigurationError(message)) => assert!(
+                message.contains(expected),
+                "expected '{}' in '{}'",
+                expected,
+                message
+            ),
+            other => panic!("expected configuration error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_valid_node_config() {
+        let config = test_config("10.0.0.2:7000".parse().unwrap());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_node_config_rejects_empty_node_id() {
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.node_id = " ".to_string();
+        assert_invalid(config, "Node ID");
+    }
+
+    #[test]
+    fn test_node_config_rejects_empty_network_id() {
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.network_id = String::new();
+        assert_invalid(config, "Network ID");
+    }
+
+    #[test]
+    fn test_node_config_rejects_zero_max_peers() {
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.max_peers = 0;
+        assert_invalid(config, "max_peers");
+    }
+
+    #[test]
+    fn test_node_config_rejects_zero_block_limits() {
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.max_block_transactions = 0;
+        assert_invalid(config, "Block limits");
+
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.max_block_bytes = 0;
+        assert_invalid(config, "Block limits");
+    }
+
+    #[test]
+    fn test_node_config_rejects_bootstrap_peer_matching_listen_addr() {
+        let mut config = test_config("127.0.0.1:7000".parse().unwrap());
+        config.listen_addr = "0.0.0.0:7000".parse().unwrap();
+        assert_invalid(config, "conflicts with listen address");
+    }
+
+    #[test]
+    fn test_node_config_rejects_duplicate_bootstrap_peers() {
+        let peer: SocketAddr = "10.0.0.2:7000".parse().unwrap();
+        let mut config = test_config(peer);
+        config.bootstrap_peers.push(peer);
+        assert_invalid(config, "more than once");
+    }
+
+    #[test]
+    fn test_node_config_rejects_invalid_reconnect_policy() {
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.reconnect.initial_delay_ms = 0;
+        assert_invalid(config, "initial delay");
+
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.reconnect.max_delay_ms = 10;
+        assert_invalid(config, "max delay");
+
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.reconnect.multiplier = 0.5;
+        assert_invalid(config, "multiplier");
+
+        let mut config = test_config("10.0.0.2:7000".parse().unwrap());
+        config.reconnect.jitter_ratio = 1.5;
+        assert_invalid(config, "jitter");
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = ReconnectPolicy::default();
+        assert_eq!(
+            policy.base_delay(50),
+            Duration::from_millis(policy.max_delay_ms)
+        );
+        assert!(policy.delay_for_attempt(50) <= Duration::from_millis(policy.max_delay_ms));
+    }
+}
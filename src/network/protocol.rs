@@ -1,11 +1,13 @@
 //! Network protocol definitions and message handling
 
+use super::capabilities::{LocalCapabilities, PeerCapabilities};
 use crate::errors::AstorError;
 use crate::ledger::Transaction;
 use crate::security::Signature;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MessageType {
@@ -84,6 +86,9 @@ pub struct ProtocolHandler {
     message_handlers: HashMap<MessageType, Box<dyn MessageHandler + Send + Sync>>,
     outbound_sender: mpsc::UnboundedSender<NetworkMessage>,
     inbound_receiver: Option<mpsc::UnboundedReceiver<NetworkMessage>>,
+    local_capabilities: LocalCapabilities,
+    /// Capabilities agreed in each connected peer's handshake
+    peer_capabilities: Arc<RwLock<HashMap<String, PeerCapabilities>>>,
 }
 
 pub trait MessageHandler {
@@ -99,6 +104,8 @@ impl ProtocolHandler {
             message_handlers: HashMap::new(),
             outbound_sender,
             inbound_receiver: Some(inbound_receiver),
+            local_capabilities: LocalCapabilities::default(),
+            peer_capabilities: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // Register default message handlers
@@ -121,7 +128,69 @@ impl ProtocolHandler {
         Ok(())
     }
 
+    /// Capabilities offered to peers in the handshake
+    pub fn set_local_capabilities(&mut self, capabilities: LocalCapabilities) {
+        self.local_capabilities = capabilities;
+    }
+
+    pub fn local_capabilities(&self) -> &LocalCapabilities {
+        &self.local_capabilities
+    }
+
+    /// Negotiate with a peer from its handshake capabilities
+    ///
+    /// A compatible peer is recorded with what was agreed, possibly an older
+    /// protocol version or fewer features than this node offers. An
+    /// incompatible one is forgotten and the error returned so the caller
+    /// drops the connection.
+    pub async fn negotiate_peer(
+        &self,
+        peer_id: &str,
+        capabilities: &[String],
+    ) -> Result<PeerCapabilities, AstorError> {
+        match self.local_capabilities.negotiate(peer_id, capabilities) {
+            Ok(negotiated) => {
+                if negotiated.protocol_version < self.local_capabilities.protocol_version {
+                    tracing::info!(
+                        "Peer {} downgraded to protocol version {}",
+                        peer_id,
+                        negotiated.protocol_version
+                    );
+                }
+                self.peer_capabilities
+                    .write()
+                    .await
+                    .insert(peer_id.to_string(), negotiated.clone());
+                Ok(negotiated)
+            }
+            Err(e) => {
+                tracing::warn!("Dropping peer {}: {}", peer_id, e);
+                self.peer_capabilities.write().await.remove(peer_id);
+                Err(e)
+            }
+        }
+    }
+
+    /// Capabilities negotiated with a peer, if its handshake succeeded
+    pub async fn peer_capabilities(&self, peer_id: &str) -> Option<PeerCapabilities> {
+        self.peer_capabilities.read().await.get(peer_id).cloned()
+    }
+
+    /// Forget a disconnected peer's negotiated capabilities
+    pub async fn remove_peer(&self, peer_id: &str) {
+        self.peer_capabilities.write().await.remove(peer_id);
+    }
+
     pub async fn handle_message(&self, message: NetworkMessage) -> Result<(), AstorError> {
+        if let MessagePayload::Handshake {
+            node_id,
+            capabilities,
+            ..
+        } = &message.payload
+        {
+            self.negotiate_peer(node_id, capabilities).await?;
+        }
+
         if let Some(handler) = self.message_handlers.get(&message.message_type) {
             if let Some(response) = handler.handle(message).await? {
                 self.send_message(response).await?;
@@ -225,3 +294,49 @@ impl MessageHandler for PingHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::capabilities::PROTOCOL_VERSION;
+
+    fn handshake(node_id: &str, capabilities: Vec<String>) -> NetworkMessage {
+        ProtocolHandler::create_message(
+            node_id.to_string(),
+            None,
+            MessageType::Handshake,
+            MessagePayload::Handshake {
+                node_id: node_id.to_string(),
+                version: "0.0.0".to_string(),
+                capabilities,
+                public_key: vec![0; 32],
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_peer_with_unsupported_version_is_rejected() {
+        let handler = ProtocolHandler::new().await.unwrap();
+
+        let future_only = vec![
+            format!("protocol:{}", PROTOCOL_VERSION + 5),
+            format!("protocol-min:{}", PROTOCOL_VERSION + 1),
+        ];
+        let result = handler
+            .handle_message(handshake("node-future", future_only))
+            .await;
+        assert!(matches!(
+            result,
+            Err(AstorError::IncompatiblePeer { ref peer_id, .. }) if peer_id == "node-future"
+        ));
+        assert!(handler.peer_capabilities("node-future").await.is_none());
+
+        let compatible = handler.local_capabilities().advertise();
+        handler
+            .handle_message(handshake("node-current", compatible))
+            .await
+            .unwrap();
+        let negotiated = handler.peer_capabilities("node-current").await.unwrap();
+        assert_eq!(negotiated.protocol_version, PROTOCOL_VERSION);
+    }
+}
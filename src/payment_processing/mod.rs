@@ -6,17 +6,69 @@
 // pub mod swift;
 // pub mod sepa;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
+use crate::central_bank::DEFAULT_CURRENCY;
+use crate::conversion::ConversionService;
 use crate::errors::AstorError;
+use crate::fee_calculator::FeeCalculator;
+use crate::ledger::{FeeCollector, FeeSource};
 
 /// Payment processor
 pub struct PaymentProcessor {
     merchants: HashMap<String, Merchant>,
     payment_methods: HashMap<String, PaymentMethod>,
     transactions: Vec<PaymentTransaction>,
+    config: PaymentProcessorConfig,
+    authorization_slots: Arc<Semaphore>,
+    /// Slots held by payments awaiting authorization, by transaction ID
+    in_flight: HashMap<String, OwnedSemaphorePermit>,
+    fee_collector: Option<FeeCollector>,
+    disputes: HashMap<String, PaymentDispute>,
+    /// Net settled funds owed to each merchant, by merchant ID
+    settlement_balances: HashMap<String, u64>,
+    /// Settled funds withheld from each merchant for open disputes
+    dispute_reserves: HashMap<String, u64>,
+    /// Chargebacks a merchant's settlement balance did not cover, by
+    /// merchant ID, repaid from their next settlements
+    merchant_receivables: HashMap<String, u64>,
+    /// Prices payments for merchants that auto-convert to their settlement currency
+    conversion_service: Option<Arc<ConversionService>>,
+    fee_calculator: FeeCalculator,
+}
+
+/// Payment processor backpressure settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentProcessorConfig {
+    /// Maximum payments awaiting authorization at once
+    pub max_concurrent_authorizations: usize,
+    /// Retry hint returned to callers when the limit is reached
+    pub busy_retry_after_secs: u64,
+    /// How long after a payment a customer may dispute it
+    #[serde(default = "default_dispute_window_days")]
+    pub dispute_window_days: i64,
+    /// Charged to the merchant on top of the reversal when a dispute is lost
+    #[serde(default)]
+    pub chargeback_fee: u64,
+}
+
+fn default_dispute_window_days() -> i64 {
+    120
+}
+
+impl Default for PaymentProcessorConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_authorizations: 64,
+            busy_retry_after_secs: 1,
+            dispute_window_days: default_dispute_window_days(),
+            chargeback_fee: 0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +78,27 @@ pub struct Merchant {
     pub merchant_category_code: String,
     pub settlement_account: String,
     pub fee_structure: FeeStructure,
+    /// Currency the merchant is settled in; always accepted
+    #[serde(default = "default_settlement_currency")]
+    pub settlement_currency: String,
+    /// Other currencies the merchant accepts as-is
+    #[serde(default)]
+    pub accepted_currencies: Vec<String>,
+    /// Convert payments in other currencies to the settlement currency
+    /// instead of rejecting them
+    #[serde(default)]
+    pub auto_convert: bool,
+}
+
+fn default_settlement_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
+}
+
+impl Merchant {
+    pub fn accepts_currency(&self, currency: &str) -> bool {
+        currency == self.settlement_currency
+            || self.accepted_currencies.iter().any(|c| c == currency)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +108,14 @@ pub struct FeeStructure {
     pub monthly_fee: u64,
 }
 
+impl FeeStructure {
+    /// Fee charged on a single payment, never more than the payment itself
+    pub fn transaction_fee(&self, amount: u64, calculator: &FeeCalculator) -> u64 {
+        let percent_fee = calculator.percentage_fee(amount, self.transaction_fee_percent);
+        percent_fee.saturating_add(self.fixed_fee).min(amount)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentMethod {
     pub method_id: String,
@@ -78,13 +159,30 @@ pub struct PaymentTransaction {
     pub customer_id: String,
     pub payment_method_id: String,
     pub amount: u64,
+    /// Merchant fee withheld from the amount at settlement
+    #[serde(default)]
+    pub fee: u64,
     pub currency: String,
+    /// Set when the customer paid in another currency and `amount` is the
+    /// converted value in the merchant's settlement currency
+    #[serde(default)]
+    pub conversion: Option<PaymentConversion>,
     pub status: PaymentStatus,
     pub created_at: DateTime<Utc>,
     pub processed_at: Option<DateTime<Utc>>,
     pub settlement_date: Option<DateTime<Utc>>,
 }
 
+/// What the customer paid before conversion to the settlement currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentConversion {
+    pub original_currency: String,
+    pub original_amount: u64,
+    pub exchange_rate: f64,
+    /// Conversion fees already deducted from the payment amount
+    pub conversion_fees: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PaymentStatus {
     Pending,
@@ -93,17 +191,79 @@ pub enum PaymentStatus {
     Settled,
     Failed(String),
     Refunded,
+    /// Contested by the customer; funds are withheld pending resolution
+    Disputed,
+    /// Reversed after the merchant lost a dispute
+    ChargedBack,
+}
+
+/// Customer dispute against a captured or settled payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentDispute {
+    pub dispute_id: String,
+    pub transaction_id: String,
+    pub merchant_id: String,
+    pub reason: String,
+    /// Settled funds withheld from the merchant while the dispute is open
+    pub reserved_amount: u64,
+    /// Status restored if the merchant wins
+    pub disputed_status: PaymentStatus,
+    pub opened_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub outcome: Option<DisputeOutcome>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisputeOutcome {
+    /// The payment stands and withheld funds are released
+    MerchantWon,
+    /// The payment is reversed and charged back to the merchant
+    CustomerWon,
 }
 
 impl PaymentProcessor {
     pub fn new() -> Self {
+        Self::with_config(PaymentProcessorConfig::default())
+    }
+
+    pub fn with_config(config: PaymentProcessorConfig) -> Self {
         Self {
             merchants: HashMap::new(),
             payment_methods: HashMap::new(),
             transactions: Vec::new(),
+            authorization_slots: Arc::new(Semaphore::new(config.max_concurrent_authorizations)),
+            in_flight: HashMap::new(),
+            fee_collector: None,
+            disputes: HashMap::new(),
+            settlement_balances: HashMap::new(),
+            dispute_reserves: HashMap::new(),
+            merchant_receivables: HashMap::new(),
+            conversion_service: None,
+            fee_calculator: FeeCalculator::default(),
+            config,
         }
     }
 
+    /// Credit merchant fees to the ledger's fee-collection account on settlement
+    pub fn set_fee_collector(&mut self, fee_collector: FeeCollector) {
+        self.fee_collector = Some(fee_collector);
+    }
+
+    /// Round merchant fees with the calculator shared with other modules
+    pub fn set_fee_calculator(&mut self, fee_calculator: FeeCalculator) {
+        self.fee_calculator = fee_calculator;
+    }
+
+    /// Rates used for merchants with `auto_convert` enabled
+    pub fn set_conversion_service(&mut self, conversion_service: Arc<ConversionService>) {
+        self.conversion_service = Some(conversion_service);
+    }
+
+    /// Number of payments currently awaiting authorization
+    pub fn in_flight_authorizations(&self) -> usize {
+        self.in_flight.len()
+    }
+
     /// Register merchant
     pub fn register_merchant(&mut self, merchant: Merchant) -> Result<(), AstorError> {
         self.merchants
@@ -119,6 +279,10 @@ impl PaymentProcessor {
     }
 
     /// Process payment
+    ///
+    /// Payments in a currency the merchant does not accept are rejected with
+    /// `CurrencyNotAccepted`, unless the merchant auto-converts, in which case
+    /// the payment is recorded in the settlement currency at the quoted rate.
     pub fn process_payment(
         &mut self,
         merchant_id: String,
@@ -128,7 +292,7 @@ impl PaymentProcessor {
         currency: String,
     ) -> Result<String, AstorError> {
         // Validate merchant
-        let _merchant = self
+        let merchant = self
             .merchants
             .get(&merchant_id)
             .ok_or_else(|| AstorError::PaymentError("Merchant not found".to_string()))?;
@@ -145,7 +309,58 @@ impl PaymentProcessor {
             ));
         }
 
+        let (amount, currency, conversion) = if merchant.accepts_currency(&currency) {
+            (amount, currency, None)
+        } else {
+            let conversion_service = self
+                .conversion_service
+                .as_ref()
+                .filter(|_| merchant.auto_convert)
+                .ok_or_else(|| AstorError::CurrencyNotAccepted {
+                    merchant_id: merchant_id.clone(),
+                    currency: currency.clone(),
+                })?;
+            let quote = conversion_service.quote_conversion(
+                amount,
+                &currency,
+                &merchant.settlement_currency,
+            )?;
+            if quote.converted_amount == 0 {
+                return Err(AstorError::PaymentError(
+                    "Payment is worth nothing after conversion".to_string(),
+                ));
+            }
+            (
+                quote.converted_amount,
+                merchant.settlement_currency.clone(),
+                Some(PaymentConversion {
+                    original_currency: currency,
+                    original_amount: amount,
+                    exchange_rate: quote.exchange_rate,
+                    conversion_fees: quote.fees.total,
+                }),
+            )
+        };
+
+        // Shed load rather than queue work the card network can't absorb
+        let permit = self
+            .authorization_slots
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| {
+                tracing::warn!(
+                    "Payment authorization limit of {} reached; rejecting payment",
+                    self.config.max_concurrent_authorizations
+                );
+                AstorError::ServiceBusy {
+                    retry_after_secs: self.config.busy_retry_after_secs,
+                }
+            })?;
+
         let transaction_id = uuid::Uuid::new_v4().to_string();
+        let fee = merchant
+            .fee_structure
+            .transaction_fee(amount, &self.fee_calculator);
 
         let transaction = PaymentTransaction {
             transaction_id: transaction_id.clone(),
@@ -153,7 +368,9 @@ impl PaymentProcessor {
             customer_id,
             payment_method_id,
             amount,
+            fee,
             currency,
+            conversion,
             status: PaymentStatus::Pending,
             created_at: Utc::now(),
             processed_at: None,
@@ -161,6 +378,7 @@ impl PaymentProcessor {
         };
 
         self.transactions.push(transaction);
+        self.in_flight.insert(transaction_id.clone(), permit);
 
         // In production, this would:
         // 1. Authorize with card networks
@@ -180,6 +398,25 @@ impl PaymentProcessor {
         {
             transaction.status = PaymentStatus::Authorized;
             transaction.processed_at = Some(Utc::now());
+            self.in_flight.remove(transaction_id);
+            Ok(())
+        } else {
+            Err(AstorError::PaymentError(
+                "Transaction not found".to_string(),
+            ))
+        }
+    }
+
+    /// Mark a payment as failed, e.g. after a card network decline
+    pub fn fail_payment(&mut self, transaction_id: &str, reason: String) -> Result<(), AstorError> {
+        if let Some(transaction) = self
+            .transactions
+            .iter_mut()
+            .find(|t| t.transaction_id == transaction_id)
+        {
+            transaction.status = PaymentStatus::Failed(reason);
+            transaction.processed_at = Some(Utc::now());
+            self.in_flight.remove(transaction_id);
             Ok(())
         } else {
             Err(AstorError::PaymentError(
@@ -211,17 +448,471 @@ impl PaymentProcessor {
     }
 
     /// Settle payments (batch process)
-    pub fn settle_payments(&mut self) -> Result<Vec<String>, AstorError> {
+    ///
+    /// Each payment's fee is credited to the fee-collection account before
+    /// the payment is marked settled.
+    pub async fn settle_payments(&mut self) -> Result<Vec<String>, AstorError> {
         let mut settled_transactions = Vec::new();
 
         for transaction in self.transactions.iter_mut() {
             if matches!(transaction.status, PaymentStatus::Captured) {
+                if let Some(fee_collector) = &self.fee_collector {
+                    fee_collector
+                        .collect(
+                            transaction.transaction_id.clone(),
+                            FeeSource::Payment,
+                            &transaction.customer_id,
+                            &transaction.currency,
+                            transaction.fee,
+                        )
+                        .await?;
+                }
+
                 transaction.status = PaymentStatus::Settled;
                 transaction.settlement_date = Some(Utc::now());
+                let mut proceeds = transaction.amount.saturating_sub(transaction.fee);
+                if let Some(owed) = self.merchant_receivables.get_mut(&transaction.merchant_id) {
+                    let repaid = proceeds.min(*owed);
+                    *owed -= repaid;
+                    proceeds -= repaid;
+                    if *owed == 0 {
+                        self.merchant_receivables.remove(&transaction.merchant_id);
+                    }
+                }
+                *self
+                    .settlement_balances
+                    .entry(transaction.merchant_id.clone())
+                    .or_insert(0) += proceeds;
                 settled_transactions.push(transaction.transaction_id.clone());
             }
         }
 
         Ok(settled_transactions)
     }
+
+    /// Net settled funds owed to a merchant, including any withheld for disputes
+    pub fn settlement_balance(&self, merchant_id: &str) -> u64 {
+        self.settlement_balances
+            .get(merchant_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Chargebacks a merchant still owes beyond their settlement balance
+    pub fn merchant_receivable(&self, merchant_id: &str) -> u64 {
+        self.merchant_receivables
+            .get(merchant_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Settled funds a merchant can be paid out, excluding disputed amounts
+    pub fn available_settlement_balance(&self, merchant_id: &str) -> u64 {
+        let reserved = self.dispute_reserves.get(merchant_id).copied().unwrap_or(0);
+        self.settlement_balance(merchant_id)
+            .saturating_sub(reserved)
+    }
+
+    pub fn get_dispute(&self, dispute_id: &str) -> Option<&PaymentDispute> {
+        self.disputes.get(dispute_id)
+    }
+
+    /// Open a customer dispute against a payment
+    ///
+    /// A captured payment is held out of settlement; for a settled payment
+    /// the merchant's net proceeds are withheld from their settlement balance
+    /// until the dispute is resolved.
+    pub fn open_dispute(
+        &mut self,
+        transaction_id: &str,
+        reason: String,
+    ) -> Result<String, AstorError> {
+        let transaction = self
+            .transactions
+            .iter_mut()
+            .find(|t| t.transaction_id == transaction_id)
+            .ok_or_else(|| AstorError::PaymentError("Transaction not found".to_string()))?;
+
+        let reserved_amount = match transaction.status {
+            PaymentStatus::Captured => 0,
+            PaymentStatus::Settled => transaction.amount.saturating_sub(transaction.fee),
+            _ => {
+                return Err(AstorError::PaymentError(format!(
+                    "Payment in status {:?} cannot be disputed",
+                    transaction.status
+                )))
+            }
+        };
+
+        if Utc::now() > transaction.created_at + Duration::days(self.config.dispute_window_days) {
+            return Err(AstorError::PaymentError(format!(
+                "Dispute window of {} days has passed",
+                self.config.dispute_window_days
+            )));
+        }
+
+        let dispute = PaymentDispute {
+            dispute_id: uuid::Uuid::new_v4().to_string(),
+            transaction_id: transaction_id.to_string(),
+            merchant_id: transaction.merchant_id.clone(),
+            reason,
+            reserved_amount,
+            disputed_status: std::mem::replace(&mut transaction.status, PaymentStatus::Disputed),
+            opened_at: Utc::now(),
+            resolved_at: None,
+            outcome: None,
+        };
+
+        *self
+            .dispute_reserves
+            .entry(dispute.merchant_id.clone())
+            .or_insert(0) += reserved_amount;
+
+        tracing::warn!(
+            "Dispute {} opened on payment {} for merchant {}: {}",
+            dispute.dispute_id,
+            transaction_id,
+            dispute.merchant_id,
+            dispute.reason
+        );
+        let dispute_id = dispute.dispute_id.clone();
+        self.disputes.insert(dispute_id.clone(), dispute);
+        Ok(dispute_id)
+    }
+
+    /// Resolve an open dispute
+    ///
+    /// Withheld funds are released either way. If the customer wins, the
+    /// merchant's net proceeds and the configured chargeback fee are deducted
+    /// from their settlement balance and the payment is marked charged back.
+    /// Whatever the balance does not cover is recorded as a receivable and
+    /// withheld from the merchant's next settlements.
+    pub fn resolve_dispute(
+        &mut self,
+        dispute_id: &str,
+        outcome: DisputeOutcome,
+    ) -> Result<(), AstorError> {
+        let dispute = self
+            .disputes
+            .get_mut(dispute_id)
+            .ok_or_else(|| AstorError::PaymentError("Dispute not found".to_string()))?;
+        if dispute.outcome.is_some() {
+            return Err(AstorError::PaymentError(
+                "Dispute has already been resolved".to_string(),
+            ));
+        }
+
+        let transaction = self
+            .transactions
+            .iter_mut()
+            .find(|t| t.transaction_id == dispute.transaction_id)
+            .ok_or_else(|| AstorError::PaymentError("Transaction not found".to_string()))?;
+
+        if let Some(reserved) = self.dispute_reserves.get_mut(&dispute.merchant_id) {
+            *reserved = reserved.saturating_sub(dispute.reserved_amount);
+        }
+
+        match outcome {
+            DisputeOutcome::MerchantWon => {
+                transaction.status = dispute.disputed_status.clone();
+            }
+            DisputeOutcome::CustomerWon => {
+                let reversal = dispute
+                    .reserved_amount
+                    .saturating_add(self.config.chargeback_fee);
+                let balance = self
+                    .settlement_balances
+                    .entry(dispute.merchant_id.clone())
+                    .or_insert(0);
+                if *balance < reversal {
+                    let shortfall = reversal - *balance;
+                    tracing::warn!(
+                        "Merchant {} settlement balance {} does not cover chargeback of {}; {} recorded as receivable",
+                        dispute.merchant_id,
+                        balance,
+                        reversal,
+                        shortfall
+                    );
+                    *self
+                        .merchant_receivables
+                        .entry(dispute.merchant_id.clone())
+                        .or_insert(0) += shortfall;
+                }
+                *balance = balance.saturating_sub(reversal);
+                transaction.status = PaymentStatus::ChargedBack;
+            }
+        }
+
+        dispute.outcome = Some(outcome);
+        dispute.resolved_at = Some(Utc::now());
+        tracing::info!(
+            "Dispute {} on payment {} resolved: {:?}",
+            dispute_id,
+            dispute.transaction_id,
+            outcome
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn processor(max_concurrent_authorizations: usize) -> PaymentProcessor {
+        let mut processor = PaymentProcessor::with_config(PaymentProcessorConfig {
+            max_concurrent_authorizations,
+            busy_retry_after_secs: 2,
+            chargeback_fee: 25,
+            ..PaymentProcessorConfig::default()
+        });
+        processor
+            .register_merchant(Merchant {
+                merchant_id: "merchant-1".to_string(),
+                business_name: "Corner Shop".to_string(),
+                merchant_category_code: "5411".to_string(),
+                settlement_account: "settlement-1".to_string(),
+                fee_structure: FeeStructure {
+                    transaction_fee_percent: 0.02,
+                    fixed_fee: 30,
+                    monthly_fee: 0,
+                },
+                settlement_currency: "ASTOR".to_string(),
+                accepted_currencies: vec!["EUR".to_string()],
+                auto_convert: false,
+            })
+            .unwrap();
+        processor
+            .add_payment_method(PaymentMethod {
+                method_id: "card-1".to_string(),
+                customer_id: "customer-1".to_string(),
+                method_type: PaymentMethodType::DigitalWallet {
+                    wallet_provider: "AstorPay".to_string(),
+                    wallet_id: "wallet-1".to_string(),
+                },
+                is_active: true,
+                created_at: Utc::now(),
+            })
+            .unwrap();
+        processor
+    }
+
+    fn pay(processor: &mut PaymentProcessor) -> Result<String, AstorError> {
+        processor.process_payment(
+            "merchant-1".to_string(),
+            "customer-1".to_string(),
+            "card-1".to_string(),
+            1_000,
+            "ASTOR".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_saturated_processor_rejects_until_capacity_frees() {
+        let mut processor = processor(2);
+        let first = pay(&mut processor).unwrap();
+        let second = pay(&mut processor).unwrap();
+        assert_eq!(processor.in_flight_authorizations(), 2);
+
+        assert!(matches!(
+            pay(&mut processor),
+            Err(AstorError::ServiceBusy {
+                retry_after_secs: 2
+            })
+        ));
+        assert!(pay(&mut processor).is_err());
+
+        processor.authorize_payment(&first).unwrap();
+        let third = pay(&mut processor).unwrap();
+        assert!(pay(&mut processor).is_err());
+
+        processor
+            .fail_payment(&second, "declined".to_string())
+            .unwrap();
+        processor.authorize_payment(&third).unwrap();
+        assert_eq!(processor.in_flight_authorizations(), 0);
+        assert!(pay(&mut processor).is_ok());
+    }
+
+    async fn settled_payment(processor: &mut PaymentProcessor) -> String {
+        let transaction_id = pay(processor).unwrap();
+        processor.authorize_payment(&transaction_id).unwrap();
+        processor.capture_payment(&transaction_id).unwrap();
+        processor.settle_payments().await.unwrap();
+        transaction_id
+    }
+
+    fn status(processor: &PaymentProcessor, transaction_id: &str) -> PaymentStatus {
+        processor
+            .transactions
+            .iter()
+            .find(|t| t.transaction_id == transaction_id)
+            .unwrap()
+            .status
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_lost_dispute_holds_then_reverses_merchant_funds() {
+        let mut processor = processor(4);
+        let disputed = settled_payment(&mut processor).await;
+        settled_payment(&mut processor).await;
+        // 1,000 less a 2% + 30 fee, twice
+        assert_eq!(processor.settlement_balance("merchant-1"), 1_900);
+
+        let dispute_id = processor
+            .open_dispute(&disputed, "item not received".to_string())
+            .unwrap();
+        assert!(matches!(
+            status(&processor, &disputed),
+            PaymentStatus::Disputed
+        ));
+        assert_eq!(processor.settlement_balance("merchant-1"), 1_900);
+        assert_eq!(processor.available_settlement_balance("merchant-1"), 950);
+        assert!(processor
+            .open_dispute(&disputed, "duplicate".to_string())
+            .is_err());
+
+        processor
+            .resolve_dispute(&dispute_id, DisputeOutcome::CustomerWon)
+            .unwrap();
+        assert!(matches!(
+            status(&processor, &disputed),
+            PaymentStatus::ChargedBack
+        ));
+        assert_eq!(processor.settlement_balance("merchant-1"), 925);
+        assert_eq!(processor.available_settlement_balance("merchant-1"), 925);
+        assert!(processor
+            .resolve_dispute(&dispute_id, DisputeOutcome::MerchantWon)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_uncovered_chargeback_is_repaid_from_later_settlements() {
+        let mut processor = processor(4);
+        let disputed = settled_payment(&mut processor).await;
+        let dispute_id = processor
+            .open_dispute(&disputed, "item not received".to_string())
+            .unwrap();
+
+        // 950 settled against a 950 reversal plus a 25 chargeback fee
+        processor
+            .resolve_dispute(&dispute_id, DisputeOutcome::CustomerWon)
+            .unwrap();
+        assert_eq!(processor.settlement_balance("merchant-1"), 0);
+        assert_eq!(processor.merchant_receivable("merchant-1"), 25);
+
+        settled_payment(&mut processor).await;
+        assert_eq!(processor.merchant_receivable("merchant-1"), 0);
+        assert_eq!(processor.settlement_balance("merchant-1"), 925);
+    }
+
+    #[tokio::test]
+    async fn test_won_dispute_releases_held_funds() {
+        let mut processor = processor(4);
+        let disputed = settled_payment(&mut processor).await;
+
+        let dispute_id = processor
+            .open_dispute(&disputed, "unrecognized charge".to_string())
+            .unwrap();
+        assert_eq!(processor.available_settlement_balance("merchant-1"), 0);
+
+        processor
+            .resolve_dispute(&dispute_id, DisputeOutcome::MerchantWon)
+            .unwrap();
+        assert!(matches!(
+            status(&processor, &disputed),
+            PaymentStatus::Settled
+        ));
+        assert_eq!(processor.available_settlement_balance("merchant-1"), 950);
+        assert_eq!(
+            processor.get_dispute(&dispute_id).unwrap().outcome,
+            Some(DisputeOutcome::MerchantWon)
+        );
+    }
+
+    fn pay_in(processor: &mut PaymentProcessor, currency: &str) -> Result<String, AstorError> {
+        processor.process_payment(
+            "merchant-1".to_string(),
+            "customer-1".to_string(),
+            "card-1".to_string(),
+            1_000,
+            currency.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_unaccepted_currency_is_rejected() {
+        let mut processor = processor(4);
+        assert!(pay_in(&mut processor, "EUR").is_ok());
+
+        match pay_in(&mut processor, "USD") {
+            Err(AstorError::CurrencyNotAccepted {
+                merchant_id,
+                currency,
+            }) => {
+                assert_eq!(merchant_id, "merchant-1");
+                assert_eq!(currency, "USD");
+            }
+            other => panic!("expected CurrencyNotAccepted, got {:?}", other),
+        }
+        assert_eq!(processor.in_flight_authorizations(), 1);
+    }
+
+    #[test]
+    fn test_auto_convert_settles_in_merchant_currency() {
+        let mut processor = processor(4);
+        processor
+            .merchants
+            .get_mut("merchant-1")
+            .unwrap()
+            .auto_convert = true;
+
+        let mut conversion_service = ConversionService::new();
+        conversion_service.update_exchange_rate(crate::conversion::ExchangeRate {
+            from_currency: "USD".to_string(),
+            to_currency: "ASTOR".to_string(),
+            rate: 0.5,
+            bid: 0.5,
+            ask: 0.5,
+            timestamp: Utc::now(),
+            source: "test".to_string(),
+            volatility: 0.0,
+            daily_change: 0.0,
+        });
+        let expected = conversion_service
+            .quote_conversion(1_000, "USD", "ASTOR")
+            .unwrap();
+        processor.set_conversion_service(Arc::new(conversion_service));
+
+        let transaction_id = pay_in(&mut processor, "USD").unwrap();
+        let transaction = processor
+            .transactions
+            .iter()
+            .find(|t| t.transaction_id == transaction_id)
+            .unwrap();
+
+        assert_eq!(transaction.currency, "ASTOR");
+        assert_eq!(transaction.amount, expected.converted_amount);
+        assert_eq!(
+            transaction.fee,
+            FeeStructure {
+                transaction_fee_percent: 0.02,
+                fixed_fee: 30,
+                monthly_fee: 0,
+            }
+            .transaction_fee(expected.converted_amount, &FeeCalculator::default())
+        );
+        let conversion = transaction.conversion.as_ref().unwrap();
+        assert_eq!(conversion.original_currency, "USD");
+        assert_eq!(conversion.original_amount, 1_000);
+        assert_eq!(conversion.conversion_fees, expected.fees.total);
+
+        // Accepted currencies are still taken as-is
+        let eur_id = pay_in(&mut processor, "EUR").unwrap();
+        assert!(processor
+            .transactions
+            .iter()
+            .any(|t| t.transaction_id == eur_id && t.currency == "EUR" && t.conversion.is_none()));
+    }
 }
@@ -0,0 +1,826 @@
+//! Confirmation receipts for outgoing transfers
+//!
+//! Account holders can opt in to a signed receipt for every outgoing transfer,
+//! delivered by email or SMS. Receipts are queued when a transfer completes
+//! and delivered separately, so a slow or failing channel never affects the
+//! transfer itself. Failed deliveries are retried with a growing, capped
+//! backoff until the attempt limit is reached. With a queue file, receipts
+//! not yet delivered and the holders' subscriptions survive a restart.
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::accounts::{BalanceNotification, LogNotifier, Notifier};
+use crate::config::{EmailConfig, SmsConfig};
+use crate::errors::AstorError;
+use crate::security::{Signature, Signer};
+use crate::transactions::{Transaction, TransactionObserver, TransactionType};
+
+/// Where an account holder receives their receipts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiptChannel {
+    Email { address: String },
+    Sms { number: String },
+}
+
+/// Delivery settings for confirmation receipts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiptConfig {
+    /// Deliveries attempted before a receipt is given up on
+    pub max_attempts: u32,
+    /// Wait after the first failed attempt; doubles with each further failure
+    pub retry_backoff_secs: i64,
+    /// Longest wait between attempts, however many have failed
+    pub max_retry_backoff_secs: i64,
+    /// Seconds between delivery runs
+    pub delivery_interval_secs: u64,
+    /// Receipts link to `{verification_base_url}/{transaction_id}`
+    pub verification_base_url: String,
+    /// File undelivered receipts are kept in across restarts; memory only
+    /// when unset
+    pub queue_path: Option<String>,
+}
+
+impl Default for ReceiptConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            retry_backoff_secs: 30,
+            max_retry_backoff_secs: 3_600,
+            delivery_interval_secs: 5,
+            verification_base_url: "https://astor.example/receipts".to_string(),
+            queue_path: None,
+        }
+    }
+}
+
+impl ReceiptConfig {
+    /// Wait before the next attempt once `attempts` deliveries have failed
+    fn backoff_after(&self, attempts: u32) -> Duration {
+        let backoff = self
+            .retry_backoff_secs
+            .saturating_mul(2i64.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_retry_backoff_secs);
+        Duration::seconds(backoff.max(0))
+    }
+}
+
+/// The details of a completed transfer, as signed for the sender
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptDetails {
+    pub transaction_id: String,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    pub transaction_hash: String,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// A signed confirmation of a completed transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionReceipt {
+    pub details: ReceiptDetails,
+    pub key_id: String,
+    /// Base64 Ed25519 signature over the serialized details
+    pub signature: String,
+    pub verification_url: String,
+}
+
+impl TransactionReceipt {
+    /// Check the signature against the issuing node's public key
+    pub fn verify(&self, public_key: &PublicKey) -> Result<(), AstorError> {
+        let signature = Signature::from_base64(&self.signature, self.key_id.clone())?;
+        signature.verify_ignoring_age(public_key, &serde_json::to_vec(&self.details)?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QueuedReceipt {
+    receipt: TransactionReceipt,
+    channel: ReceiptChannel,
+    attempts: u32,
+    next_attempt_at: DateTime<Utc>,
+}
+
+/// Receipts and subscriptions persisted between restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReceiptQueueState {
+    queued: Vec<QueuedReceipt>,
+    abandoned: Vec<TransactionReceipt>,
+    #[serde(default)]
+    subscriptions: HashMap<String, ReceiptChannel>,
+}
+
+/// Signs receipts for opted-in senders and delivers them with retries
+///
+/// Register it with `TransactionManager::add_observer`; completed transfers
+/// only enqueue a receipt, and `deliver_due` does the sending.
+pub struct ReceiptService {
+    signer: Arc<dyn Signer>,
+    notifier: RwLock<Arc<dyn Notifier>>,
+    config: ReceiptConfig,
+    subscriptions: RwLock<HashMap<String, ReceiptChannel>>,
+    queue: Mutex<VecDeque<QueuedReceipt>>,
+    abandoned: Mutex<Vec<TransactionReceipt>>,
+    /// File the queue and subscriptions are saved to after every change,
+    /// if persisted
+    queue_path: Option<PathBuf>,
+}
+
+impl ReceiptService {
+    pub fn new(
+        signer: Arc<dyn Signer>,
+        notifier: Arc<dyn Notifier>,
+        config: ReceiptConfig,
+    ) -> Self {
+        Self {
+            signer,
+            notifier: RwLock::new(notifier),
+            config,
+            subscriptions: RwLock::new(HashMap::new()),
+            queue: Mutex::new(VecDeque::new()),
+            abandoned: Mutex::new(Vec::new()),
+            queue_path: None,
+        }
+    }
+
+    /// Service persisting its queue to `config.queue_path` when set,
+    /// picking up receipts left undelivered by an earlier run
+    pub fn from_config(
+        signer: Arc<dyn Signer>,
+        notifier: Arc<dyn Notifier>,
+        config: ReceiptConfig,
+    ) -> Result<Self, AstorError> {
+        let queue_path = config.queue_path.clone();
+        let mut service = Self::new(signer, notifier, config);
+        if let Some(path) = queue_path {
+            service.open_queue(path)?;
+        }
+        Ok(service)
+    }
+
+    /// Persist the queue to `path`, loading the receipts and subscriptions
+    /// already there
+    pub fn open_queue(&mut self, path: impl AsRef<Path>) -> Result<(), AstorError> {
+        let path = path.as_ref().to_path_buf();
+        let state: ReceiptQueueState = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ReceiptQueueState::default(),
+            Err(e) => {
+                return Err(AstorError::InvalidOperation(format!(
+                    "Failed to read receipt queue {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        if !state.queued.is_empty() {
+            tracing::info!("Resuming delivery of {} receipts", state.queued.len());
+        }
+        self.queue.lock().unwrap().extend(state.queued);
+        self.abandoned.lock().unwrap().extend(state.abandoned);
+        self.subscriptions
+            .write()
+            .unwrap()
+            .extend(state.subscriptions);
+        self.queue_path = Some(path);
+        Ok(())
+    }
+
+    /// Write the queue to its file, replacing it atomically
+    fn save_queue(&self) -> Result<(), AstorError> {
+        let Some(path) = &self.queue_path else {
+            return Ok(());
+        };
+        let state = ReceiptQueueState {
+            queued: self.queue.lock().unwrap().iter().cloned().collect(),
+            abandoned: self.abandoned.lock().unwrap().clone(),
+            subscriptions: self.subscriptions.read().unwrap().clone(),
+        };
+        let staging = path.with_extension("tmp");
+        std::fs::write(&staging, serde_json::to_vec(&state)?)
+            .and_then(|()| std::fs::rename(&staging, path))
+            .map_err(|e| {
+                AstorError::InvalidOperation(format!(
+                    "Failed to write receipt queue {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    /// Deliver receipts through `notifier` from now on
+    pub fn set_notifier(&self, notifier: Arc<dyn Notifier>) {
+        *self.notifier.write().unwrap() = notifier;
+    }
+
+    /// Seconds between delivery runs
+    pub fn delivery_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.delivery_interval_secs.max(1))
+    }
+
+    /// Send `account_id` a receipt for each of its outgoing transfers
+    pub fn opt_in(&self, account_id: &str, channel: ReceiptChannel) -> Result<(), AstorError> {
+        self.subscriptions
+            .write()
+            .unwrap()
+            .insert(account_id.to_string(), channel);
+        self.save_queue()
+    }
+
+    pub fn opt_out(&self, account_id: &str) -> Result<(), AstorError> {
+        self.subscriptions.write().unwrap().remove(account_id);
+        self.save_queue()
+    }
+
+    pub fn channel_for(&self, account_id: &str) -> Option<ReceiptChannel> {
+        self.subscriptions.read().unwrap().get(account_id).cloned()
+    }
+
+    /// Receipts waiting for their first delivery or a retry
+    pub fn pending_count(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    /// Receipts given up on after `max_attempts` failed deliveries
+    pub fn abandoned(&self) -> Vec<TransactionReceipt> {
+        self.abandoned.lock().unwrap().clone()
+    }
+
+    fn issue_receipt(&self, details: ReceiptDetails) -> Result<TransactionReceipt, AstorError> {
+        let signature = self.signer.sign(&serde_json::to_vec(&details)?)?;
+        Ok(TransactionReceipt {
+            verification_url: format!(
+                "{}/{}",
+                self.config.verification_base_url.trim_end_matches('/'),
+                details.transaction_id
+            ),
+            details,
+            key_id: self.signer.key_id().to_string(),
+            signature: signature.to_base64(),
+        })
+    }
+
+    /// Attempt every receipt that is due at `now`; returns how many were delivered
+    ///
+    /// Failures are requeued with backoff, or abandoned once they reach the
+    /// attempt limit. Each delivery runs on the blocking pool, so a notifier
+    /// waiting on a mail server or gateway does not hold up the runtime.
+    pub async fn deliver_due(&self, now: DateTime<Utc>) -> usize {
+        let due: Vec<QueuedReceipt> = {
+            let mut queue = self.queue.lock().unwrap();
+            let (due, waiting): (Vec<_>, VecDeque<_>) = queue
+                .drain(..)
+                .partition(|queued| queued.next_attempt_at <= now);
+            *queue = waiting;
+            due
+        };
+
+        if due.is_empty() {
+            return 0;
+        }
+
+        let notifier = self.notifier.read().unwrap().clone();
+        let mut delivered = 0;
+        for mut queued in due {
+            queued.attempts += 1;
+            let sent = {
+                let notifier = notifier.clone();
+                let channel = queued.channel.clone();
+                let receipt = queued.receipt.clone();
+                tokio::task::spawn_blocking(move || notifier.send_receipt(&channel, &receipt))
+                    .await
+                    .unwrap_or_else(|e| {
+                        Err(AstorError::NetworkError(format!(
+                            "Receipt delivery task failed: {}",
+                            e
+                        )))
+                    })
+            };
+            match sent {
+                Ok(()) => delivered += 1,
+                Err(e) if queued.attempts >= self.config.max_attempts => {
+                    tracing::error!(
+                        "Giving up on receipt for {} after {} attempts: {}",
+                        queued.receipt.details.transaction_id,
+                        queued.attempts,
+                        e
+                    );
+                    self.abandoned.lock().unwrap().push(queued.receipt);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Receipt delivery for {} failed (attempt {}): {}",
+                        queued.receipt.details.transaction_id,
+                        queued.attempts,
+                        e
+                    );
+                    queued.next_attempt_at = now + self.config.backoff_after(queued.attempts);
+                    self.queue.lock().unwrap().push_back(queued);
+                }
+            }
+        }
+        if let Err(e) = self.save_queue() {
+            tracing::error!("{}", e);
+        }
+        delivered
+    }
+
+    /// Deliver due receipts every `interval` until the task is aborted
+    pub fn spawn_delivery_task(
+        self: Arc<Self>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.deliver_due(Utc::now()).await;
+            }
+        })
+    }
+}
+
+impl TransactionObserver for ReceiptService {
+    fn on_completed(&self, transaction: &Transaction) -> Result<(), AstorError> {
+        let TransactionType::Transfer { from, to, amount } = &transaction.transaction_type else {
+            return Ok(());
+        };
+        let Some(channel) = self.channel_for(from) else {
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        let receipt = self.issue_receipt(ReceiptDetails {
+            transaction_id: transaction.id.clone(),
+            from: from.clone(),
+            to: to.clone(),
+            amount: *amount,
+            transaction_hash: transaction.hash.clone(),
+            completed_at: now,
+        })?;
+        self.queue.lock().unwrap().push_back(QueuedReceipt {
+            receipt,
+            channel,
+            attempts: 0,
+            next_attempt_at: now,
+        });
+        self.save_queue()
+    }
+}
+
+/// Seconds to wait on the mail server or SMS gateway before giving up
+const DELIVERY_TIMEOUT_SECS: u64 = 30;
+
+/// Delivers receipts by email through an SMTP relay and by SMS through an
+/// HTTP gateway
+///
+/// Sending blocks until the server answers, so call it off the async
+/// runtime, as `ReceiptService::deliver_due` does. The relay is reached
+/// without TLS, so it should be a local MTA; `use_tls` is refused rather
+/// than sending credentials in the clear. Balance alerts carry no delivery
+/// address and are logged.
+pub struct ChannelNotifier {
+    email: EmailConfig,
+    sms: Option<SmsConfig>,
+    http: reqwest::Client,
+    runtime: tokio::runtime::Handle,
+}
+
+impl ChannelNotifier {
+    /// Notifier for the configured channels; must be created on the runtime
+    /// that SMS requests run on
+    pub fn new(email: EmailConfig, sms: Option<SmsConfig>) -> Result<Self, AstorError> {
+        if email.use_tls {
+            return Err(AstorError::ConfigurationError(
+                "SMTP over TLS is not supported; relay receipts through a local MTA".to_string(),
+            ));
+        }
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+            AstorError::ConfigurationError(
+                "Channel notifier must be created on the async runtime".to_string(),
+            )
+        })?;
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| AstorError::ConfigurationError(e.to_string()))?;
+        Ok(Self {
+            email,
+            sms,
+            http,
+            runtime,
+        })
+    }
+
+    fn send_email(&self, to: &str, subject: &str, body: &str) -> Result<(), AstorError> {
+        if to.contains(['\r', '\n']) || self.email.from_address.contains(['\r', '\n']) {
+            return Err(AstorError::InvalidOperation(format!(
+                "Invalid email address: {:?}",
+                to
+            )));
+        }
+        let relay = format!("{}:{}", self.email.smtp_host, self.email.smtp_port);
+        let smtp_error =
+            |e: std::io::Error| AstorError::NetworkError(format!("SMTP relay {}: {}", relay, e));
+        let stream = TcpStream::connect(&relay).map_err(smtp_error)?;
+        let timeout = Some(std::time::Duration::from_secs(DELIVERY_TIMEOUT_SECS));
+        stream.set_read_timeout(timeout).map_err(smtp_error)?;
+        stream.set_write_timeout(timeout).map_err(smtp_error)?;
+        let mut session = SmtpSession {
+            reader: BufReader::new(stream.try_clone().map_err(smtp_error)?),
+            writer: stream,
+        };
+
+        session.expect(220)?;
+        session.command("EHLO astor", 250)?;
+        if !self.email.username.is_empty() {
+            let credentials = general_purpose::STANDARD.encode(format!(
+                "\0{}\0{}",
+                self.email.username, self.email.password
+            ));
+            session.command(&format!("AUTH PLAIN {}", credentials), 235)?;
+        }
+        session.command(&format!("MAIL FROM:<{}>", self.email.from_address), 250)?;
+        session.command(&format!("RCPT TO:<{}>", to), 250)?;
+        session.command("DATA", 354)?;
+
+        let mut message = format!(
+            "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\nDate: {}\r\n\r\n",
+            self.email.from_address,
+            to,
+            subject,
+            Utc::now().to_rfc2822()
+        );
+        for line in body.lines() {
+            // Dot-stuffing, so a line of "." does not end the message early
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        message.push('.');
+        session.command(&message, 250)?;
+        session.command("QUIT", 221)
+    }
+
+    fn send_sms(&self, to: &str, body: &str) -> Result<(), AstorError> {
+        let sms = self.sms.as_ref().ok_or_else(|| {
+            AstorError::ConfigurationError("No SMS gateway is configured".to_string())
+        })?;
+        let request = self
+            .http
+            .post(&sms.provider)
+            .bearer_auth(&sms.api_key)
+            .json(&serde_json::json!({
+                "from": sms.from_number,
+                "to": to,
+                "body": body,
+            }));
+        self.runtime.block_on(async {
+            request
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(|e| AstorError::NetworkError(format!("SMS gateway: {}", e)))
+        })
+    }
+}
+
+impl Notifier for ChannelNotifier {
+    fn notify(&self, notification: &BalanceNotification) -> Result<(), AstorError> {
+        LogNotifier.notify(notification)
+    }
+
+    fn send_receipt(
+        &self,
+        channel: &ReceiptChannel,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), AstorError> {
+        let details = &receipt.details;
+        match channel {
+            ReceiptChannel::Email { address } => self.send_email(
+                address,
+                &format!("Astor transfer receipt {}", details.transaction_id),
+                &format!(
+                    "You sent {} ASTOR from {} to {} at {}.\n\nTransaction: {}\nVerify: {}\nSignature ({}): {}\n",
+                    details.amount,
+                    details.from,
+                    details.to,
+                    details.completed_at.to_rfc3339(),
+                    details.transaction_id,
+                    receipt.verification_url,
+                    receipt.key_id,
+                    receipt.signature
+                ),
+            ),
+            ReceiptChannel::Sms { number } => self.send_sms(
+                number,
+                &format!(
+                    "Astor: sent {} ASTOR to {}. Verify: {}",
+                    details.amount, details.to, receipt.verification_url
+                ),
+            ),
+        }
+    }
+}
+
+/// One SMTP conversation with the relay
+struct SmtpSession {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl SmtpSession {
+    /// Send one line and check the reply code
+    fn command(&mut self, line: &str, expected: u16) -> Result<(), AstorError> {
+        self.writer
+            .write_all(format!("{}\r\n", line).as_bytes())
+            .map_err(|e| AstorError::NetworkError(format!("SMTP write failed: {}", e)))?;
+        self.expect(expected)
+    }
+
+    /// Read a possibly multi-line reply and check its code
+    fn expect(&mut self, expected: u16) -> Result<(), AstorError> {
+        loop {
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| AstorError::NetworkError(format!("SMTP read failed: {}", e)))?;
+            if read == 0 {
+                return Err(AstorError::NetworkError(
+                    "SMTP relay closed the connection".to_string(),
+                ));
+            }
+            let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+            if code != Some(expected) {
+                return Err(AstorError::NetworkError(format!(
+                    "SMTP relay replied {:?}, expected {}",
+                    line.trim_end(),
+                    expected
+                )));
+            }
+            // "250-" continues the reply; "250 " ends it
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::KeyPair;
+    use crate::transactions::{TransactionManager, TransactionStatus};
+
+    /// Fails the first `failures` deliveries, then records each one sent
+    struct FlakySender {
+        failures: Mutex<u32>,
+        attempts: Mutex<u32>,
+        sent: Mutex<Vec<(ReceiptChannel, TransactionReceipt)>>,
+    }
+
+    impl Notifier for FlakySender {
+        fn notify(&self, _notification: &BalanceNotification) -> Result<(), AstorError> {
+            Ok(())
+        }
+
+        fn send_receipt(
+            &self,
+            channel: &ReceiptChannel,
+            receipt: &TransactionReceipt,
+        ) -> Result<(), AstorError> {
+            *self.attempts.lock().unwrap() += 1;
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(AstorError::NetworkError(
+                    "SMTP relay unavailable".to_string(),
+                ));
+            }
+            self.sent
+                .lock()
+                .unwrap()
+                .push((channel.clone(), receipt.clone()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_completed_transfer_enqueues_one_receipt_and_retries_failed_delivery() {
+        let keypair = Arc::new(KeyPair::generate());
+        let sender = Arc::new(FlakySender {
+            failures: Mutex::new(1),
+            attempts: Mutex::new(0),
+            sent: Mutex::new(Vec::new()),
+        });
+        let config = ReceiptConfig::default();
+        let service = Arc::new(ReceiptService::new(
+            keypair.clone(),
+            sender.clone(),
+            config.clone(),
+        ));
+        let channel = ReceiptChannel::Email {
+            address: "alice@example.com".to_string(),
+        };
+        service.opt_in("alice", channel.clone()).unwrap();
+
+        let mut manager = TransactionManager::new();
+        manager.add_observer(service.clone());
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+        // Not opted in, and not completed: neither produces a receipt
+        let other = manager.create_transfer("bob", "carol", 50).unwrap();
+        let failed = manager.create_transfer("alice", "carol", 25).unwrap();
+        manager.confirm_transaction(&tx_id).unwrap();
+        manager.confirm_transaction(&other).unwrap();
+        manager
+            .fail_transaction(&failed, "insufficient funds".to_string())
+            .unwrap();
+        assert_eq!(service.pending_count(), 1);
+
+        // The first attempt fails; the transfer stays completed
+        let now = Utc::now();
+        assert_eq!(service.deliver_due(now).await, 0);
+        assert_eq!(service.pending_count(), 1);
+        assert_eq!(
+            manager.get_transaction_status(&tx_id).unwrap(),
+            &TransactionStatus::Completed
+        );
+
+        // Nothing is retried before the backoff elapses
+        assert_eq!(service.deliver_due(now).await, 0);
+        assert_eq!(*sender.attempts.lock().unwrap(), 1);
+
+        let retry_at = now + Duration::seconds(config.retry_backoff_secs);
+        assert_eq!(service.deliver_due(retry_at).await, 1);
+        assert_eq!(service.pending_count(), 0);
+        assert!(service.abandoned().is_empty());
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        let (sent_channel, receipt) = &sent[0];
+        assert_eq!(sent_channel, &channel);
+        assert_eq!(receipt.details.transaction_id, tx_id);
+        assert_eq!(receipt.details.amount, 100);
+        assert!(receipt.verification_url.ends_with(&tx_id));
+        receipt.verify(&keypair.public_key()).unwrap();
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let config = ReceiptConfig::default();
+        assert_eq!(config.backoff_after(1), Duration::seconds(30));
+        assert_eq!(config.backoff_after(3), Duration::seconds(120));
+        assert_eq!(config.backoff_after(8), Duration::seconds(3_600));
+        // No overflow however many attempts have failed
+        assert_eq!(config.backoff_after(u32::MAX), Duration::seconds(3_600));
+    }
+
+    #[tokio::test]
+    async fn test_undelivered_receipts_survive_restart() {
+        let path =
+            std::env::temp_dir().join(format!("astor-receipts-{}.json", uuid::Uuid::new_v4()));
+        let config = ReceiptConfig {
+            queue_path: Some(path.to_string_lossy().into_owned()),
+            ..ReceiptConfig::default()
+        };
+        let keypair = Arc::new(KeyPair::generate());
+        let sender = || {
+            Arc::new(FlakySender {
+                failures: Mutex::new(0),
+                attempts: Mutex::new(0),
+                sent: Mutex::new(Vec::new()),
+            })
+        };
+        let channel = ReceiptChannel::Sms {
+            number: "+15550100".to_string(),
+        };
+
+        let service = Arc::new(
+            ReceiptService::from_config(keypair.clone(), sender(), config.clone()).unwrap(),
+        );
+        service.opt_in("alice", channel.clone()).unwrap();
+        let mut manager = TransactionManager::new();
+        manager.add_observer(service.clone());
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+        manager.confirm_transaction(&tx_id).unwrap();
+        assert_eq!(service.pending_count(), 1);
+
+        // The node stops before delivering; the next run picks the receipt up
+        let restarted_sender = sender();
+        let restarted =
+            ReceiptService::from_config(keypair, restarted_sender.clone(), config).unwrap();
+        assert_eq!(restarted.pending_count(), 1);
+        assert_eq!(restarted.channel_for("alice"), Some(channel));
+        assert_eq!(restarted.deliver_due(Utc::now()).await, 1);
+        assert_eq!(
+            restarted_sender.sent.lock().unwrap()[0]
+                .1
+                .details
+                .transaction_id,
+            tx_id
+        );
+
+        let state: ReceiptQueueState =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert!(state.queued.is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_channel_notifier_emails_receipt_through_smtp_relay() {
+        // A relay that accepts every command and keeps the message data
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let relay = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut transcript = Vec::new();
+            let mut in_data = false;
+            writer.write_all(b"220 relay ready\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        transcript.push(line);
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-relay\r\n250 8BITMIME\r\n"
+                } else if line == "QUIT" {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    transcript.push(line);
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).unwrap();
+            }
+            transcript
+        });
+
+        let notifier = ChannelNotifier::new(
+            EmailConfig {
+                smtp_host: "127.0.0.1".to_string(),
+                smtp_port: port,
+                username: String::new(),
+                password: String::new(),
+                from_address: "receipts@astor.example".to_string(),
+                use_tls: false,
+            },
+            None,
+        )
+        .unwrap();
+        let receipt = ReceiptService::new(
+            Arc::new(KeyPair::generate()),
+            Arc::new(LogNotifier),
+            ReceiptConfig::default(),
+        )
+        .issue_receipt(ReceiptDetails {
+            transaction_id: "tx-1".to_string(),
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: 100,
+            transaction_hash: "hash".to_string(),
+            completed_at: Utc::now(),
+        })
+        .unwrap();
+        let channel = ReceiptChannel::Email {
+            address: "alice@example.com".to_string(),
+        };
+
+        tokio::task::spawn_blocking(move || notifier.send_receipt(&channel, &receipt))
+            .await
+            .unwrap()
+            .unwrap();
+        let transcript = relay.join().unwrap();
+        assert!(transcript.contains(&"MAIL FROM:<receipts@astor.example>".to_string()));
+        assert!(transcript.contains(&"RCPT TO:<alice@example.com>".to_string()));
+        assert!(transcript.contains(&"Subject: Astor transfer receipt tx-1".to_string()));
+        assert!(transcript
+            .iter()
+            .any(|line| line.starts_with("Verify: https://astor.example/receipts/tx-1")));
+    }
+}
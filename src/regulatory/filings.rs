@@ -0,0 +1,141 @@
+//! FinCEN filing layouts for Currency Transaction Reports (FinCEN Form 112)
+//! and Suspicious Activity Reports (FinCEN Form 111)
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{AmlAlertType, DocumentType, IdentityDocument};
+
+/// Cash amount above which a Currency Transaction Report must be filed
+pub const CTR_THRESHOLD: u64 = 10_000;
+
+/// Financial institution filing the report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilingInstitution {
+    pub legal_name: String,
+    pub tin: String,
+    pub primary_regulator: String,
+    pub address: String,
+    pub city: String,
+    pub state: String,
+    pub zip_code: String,
+    pub country: String,
+    pub contact_office: String,
+    pub contact_phone: String,
+}
+
+/// Form of identification used to identify the person or subject
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilingIdentification {
+    pub id_type: String,
+    pub id_number: String,
+    pub issuing_country: String,
+}
+
+impl From<&IdentityDocument> for FilingIdentification {
+    fn from(document: &IdentityDocument) -> Self {
+        let id_type = match document.document_type {
+            DocumentType::Passport => "Passport",
+            DocumentType::DriversLicense => "Driver's license/State ID",
+            DocumentType::NationalId => "Foreign national ID",
+            DocumentType::UtilityBill | DocumentType::BankStatement => "Other",
+        };
+
+        Self {
+            id_type: id_type.to_string(),
+            id_number: document.document_number.clone(),
+            issuing_country: document.issuing_country.clone(),
+        }
+    }
+}
+
+/// Currency Transaction Report (FinCEN Form 112)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyTransactionReport {
+    pub form_type: String,
+    /// Part IV: financial institution where the transaction took place
+    pub filing_institution: FilingInstitution,
+    /// Part I: person on whose behalf the transaction was conducted
+    pub person: CtrPerson,
+    /// Part II: amount and type of transaction
+    pub transaction: CtrTransaction,
+    pub prepared_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CtrPerson {
+    pub customer_id: String,
+    pub identification: Option<FilingIdentification>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CtrTransaction {
+    pub transaction_id: String,
+    pub transaction_date: NaiveDate,
+    pub cash_in_amount: u64,
+    pub cash_out_amount: u64,
+    pub total_amount: u64,
+}
+
+/// Suspicious Activity Report (FinCEN Form 111)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspiciousActivityReport {
+    pub form_type: String,
+    /// Part IV: filing institution contact information
+    pub filing_institution: FilingInstitution,
+    /// Part I: subject information
+    pub subject: SarSubject,
+    /// Part II: suspicious activity information
+    pub activity: SarActivity,
+    /// Part V: narrative description of the suspicious activity
+    pub narrative: String,
+    pub prepared_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarSubject {
+    pub customer_id: String,
+    pub identification: Option<FilingIdentification>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarActivity {
+    pub alert_id: String,
+    pub activity_date_from: NaiveDate,
+    pub activity_date_to: NaiveDate,
+    pub amount_involved: Option<u64>,
+    pub category: String,
+    pub subcategory: String,
+}
+
+/// SAR activity category and subcategory for an AML alert type
+pub fn sar_category(alert_type: &AmlAlertType) -> (&'static str, &'static str) {
+    match alert_type {
+        AmlAlertType::RapidTransactionSequence => (
+            "Structuring",
+            "Multiple transactions below BSA recordkeeping threshold or below CTR threshold",
+        ),
+        AmlAlertType::CumulativeVolumeExceeded => {
+            ("Structuring", "Transaction(s) below CTR threshold")
+        }
+        AmlAlertType::SuspiciousTransactionPattern => (
+            "Money Laundering",
+            "Transaction with no apparent economic, business, or lawful purpose",
+        ),
+        AmlAlertType::HighValueTransaction => (
+            "Other Suspicious Activities",
+            "Suspicious use of high-value transactions",
+        ),
+        AmlAlertType::UnusualGeographicActivity => (
+            "Other Suspicious Activities",
+            "Suspicious use of informal value transfer system",
+        ),
+        AmlAlertType::PoliticallyExposedPerson => (
+            "Other Suspicious Activities",
+            "Suspected public/private corruption (domestic or foreign)",
+        ),
+        AmlAlertType::SanctionsListMatch => {
+            ("Other Suspicious Activities", "Suspected sanctions evasion")
+        }
+    }
+}
@@ -4,12 +4,21 @@
 // pub mod aml;
 // pub mod tax_reporting;
 // pub mod international_compliance;
+pub mod filings;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 use crate::errors::AstorError;
+use crate::security::encryption::{DataAccessRequest, EncryptedData, EncryptionManager};
+
+use filings::{CtrPerson, CtrTransaction, FilingIdentification, SarActivity, SarSubject};
+pub use filings::{
+    CurrencyTransactionReport, FilingInstitution, SuspiciousActivityReport, CTR_THRESHOLD,
+};
 
 /// KYC (Know Your Customer) verification
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,11 +26,33 @@ pub struct KycVerification {
     pub customer_id: String,
     pub verification_level: KycLevel,
     pub identity_documents: Vec<IdentityDocument>,
+    /// Outcome for each submitted document, in submission order
+    #[serde(default)]
+    pub document_results: Vec<DocumentVerification>,
     pub verification_status: VerificationStatus,
     pub verified_at: Option<DateTime<Utc>>,
     pub risk_rating: RiskRating,
 }
 
+impl KycVerification {
+    /// Documents that failed verification, with the reason for each
+    pub fn failed_documents(&self) -> Vec<&DocumentVerification> {
+        self.document_results
+            .iter()
+            .filter(|result| matches!(result.status, VerificationStatus::Rejected(_)))
+            .collect()
+    }
+}
+
+/// Verification outcome of a single identity document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentVerification {
+    pub document_type: DocumentType,
+    pub document_number: String,
+    /// `Verified`, or `Rejected` with the reason it failed
+    pub status: VerificationStatus,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum KycLevel {
     Basic,      // Basic identity verification
@@ -32,7 +63,12 @@ pub enum KycLevel {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdentityDocument {
     pub document_type: DocumentType,
+    /// Number as submitted; only its last four characters are kept once
+    /// the number is stored encrypted
     pub document_number: String,
+    /// Full number, encrypted and bound to the customer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_document_number: Option<EncryptedData>,
     pub issuing_country: String,
     pub expiry_date: Option<DateTime<Utc>>,
     pub verified: bool,
@@ -47,7 +83,7 @@ pub enum DocumentType {
     BankStatement,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VerificationStatus {
     Pending,
     Verified,
@@ -55,13 +91,81 @@ pub enum VerificationStatus {
     RequiresReview,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum RiskRating {
     Low,
+    /// Also the rating of customers not yet assessed
+    #[default]
     Medium,
     High,
 }
 
+/// Amount above which a transaction raises a high-value alert
+pub const HIGH_VALUE_THRESHOLD: u64 = 10_000;
+
+/// Stricter high-value threshold for accounts carrying a regulatory tag
+pub const ENHANCED_HIGH_VALUE_THRESHOLD: u64 = 3_000;
+
+/// Rolling-window limit on a customer's aggregate activity
+///
+/// Exceeding `max_volume` raises `CumulativeVolumeExceeded`; exceeding
+/// `max_transactions` raises `RapidTransactionSequence`. Each alert is raised
+/// when the window first crosses its limit, not for every transaction after.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AmlWindowRule {
+    pub window_secs: i64,
+    pub max_volume: u64,
+    pub max_transactions: Option<usize>,
+}
+
+impl AmlWindowRule {
+    pub fn window(&self) -> Duration {
+        Duration::seconds(self.window_secs)
+    }
+}
+
+/// Daily and weekly aggregate limits
+pub fn default_aml_window_rules() -> Vec<AmlWindowRule> {
+    vec![
+        AmlWindowRule {
+            window_secs: 24 * 3600,
+            max_volume: 25_000,
+            max_transactions: Some(20),
+        },
+        AmlWindowRule {
+            window_secs: 7 * 24 * 3600,
+            max_volume: 75_000,
+            max_transactions: None,
+        },
+    ]
+}
+
+/// Regulatory category placing an account under ongoing monitoring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RegulatoryTag {
+    /// Politically Exposed Person
+    Pep,
+    HighRiskJurisdiction,
+    EnhancedMonitoring,
+}
+
+impl RegulatoryTag {
+    /// Alert raised for every transaction on an account with this tag
+    fn alert(&self) -> (AmlAlertType, AlertSeverity) {
+        match self {
+            RegulatoryTag::Pep => (AmlAlertType::PoliticallyExposedPerson, AlertSeverity::High),
+            RegulatoryTag::HighRiskJurisdiction => (
+                AmlAlertType::UnusualGeographicActivity,
+                AlertSeverity::Medium,
+            ),
+            RegulatoryTag::EnhancedMonitoring => (
+                AmlAlertType::SuspiciousTransactionPattern,
+                AlertSeverity::Low,
+            ),
+        }
+    }
+}
+
 /// AML (Anti-Money Laundering) monitoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AmlAlert {
@@ -73,6 +177,9 @@ pub struct AmlAlert {
     pub created_at: DateTime<Utc>,
     pub status: AlertStatus,
     pub assigned_to: Option<String>,
+    /// Transaction amount that triggered the alert, if any
+    #[serde(default)]
+    pub amount: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +187,8 @@ pub enum AmlAlertType {
     SuspiciousTransactionPattern,
     HighValueTransaction,
     RapidTransactionSequence,
+    /// Aggregate volume over a monitoring window exceeded its limit
+    CumulativeVolumeExceeded,
     UnusualGeographicActivity,
     PoliticallyExposedPerson,
     SanctionsListMatch,
@@ -142,6 +251,13 @@ pub struct RegulatoryCompliance {
     aml_alerts: Vec<AmlAlert>,
     tax_reports: Vec<TaxReport>,
     sanctions_list: Vec<String>,
+    filing_institution: Option<FilingInstitution>,
+    account_tags: HashMap<String, BTreeSet<RegulatoryTag>>,
+    aml_window_rules: Vec<AmlWindowRule>,
+    /// Recent (time, amount) per customer, oldest first, kept for the longest window
+    transaction_history: HashMap<String, VecDeque<(DateTime<Utc>, u64)>>,
+    /// Keys identity document numbers are stored under, when configured
+    document_encryption: Option<Arc<RwLock<EncryptionManager>>>,
 }
 
 impl RegulatoryCompliance {
@@ -151,76 +267,382 @@ impl RegulatoryCompliance {
             aml_alerts: Vec::new(),
             tax_reports: Vec::new(),
             sanctions_list: Vec::new(),
+            filing_institution: None,
+            account_tags: HashMap::new(),
+            aml_window_rules: default_aml_window_rules(),
+            transaction_history: HashMap::new(),
+            document_encryption: None,
         }
     }
 
+    /// Store identity document numbers encrypted with `encryption`
+    pub fn set_document_encryption(&mut self, encryption: Arc<RwLock<EncryptionManager>>) {
+        self.document_encryption = Some(encryption);
+    }
+
+    pub fn set_aml_window_rules(&mut self, rules: Vec<AmlWindowRule>) {
+        self.aml_window_rules = rules;
+    }
+
+    pub fn aml_window_rules(&self) -> &[AmlWindowRule] {
+        &self.aml_window_rules
+    }
+
+    /// Flag an account for enhanced AML monitoring
+    pub fn tag_account(&mut self, customer_id: &str, tag: RegulatoryTag) {
+        self.account_tags
+            .entry(customer_id.to_string())
+            .or_default()
+            .insert(tag);
+        tracing::info!("Account {} tagged {:?}", customer_id, tag);
+    }
+
+    pub fn untag_account(&mut self, customer_id: &str, tag: RegulatoryTag) {
+        if let Some(tags) = self.account_tags.get_mut(customer_id) {
+            tags.remove(&tag);
+            if tags.is_empty() {
+                self.account_tags.remove(customer_id);
+            }
+        }
+    }
+
+    /// Regulatory tags on an account, most significant first
+    pub fn account_tags(&self, customer_id: &str) -> Vec<RegulatoryTag> {
+        self.account_tags
+            .get(customer_id)
+            .map(|tags| tags.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// AML alerts raised for a customer, oldest first
+    pub fn get_aml_alerts(&self, customer_id: &str) -> Vec<&AmlAlert> {
+        self.aml_alerts
+            .iter()
+            .filter(|alert| alert.customer_id == customer_id)
+            .collect()
+    }
+
+    /// Set the institution details included in regulatory filings
+    pub fn set_filing_institution(&mut self, institution: FilingInstitution) {
+        self.filing_institution = Some(institution);
+    }
+
+    /// Risk rating assigned at the customer's KYC verification, if any
+    pub fn customer_risk_rating(&self, customer_id: &str) -> Option<RiskRating> {
+        self.kyc_verifications
+            .get(customer_id)
+            .map(|verification| verification.risk_rating)
+    }
+
     /// Perform KYC verification
-    pub fn perform_kyc_verification(
+    ///
+    /// Each document is verified on its own. The customer is verified only
+    /// if every document passes and rejected if none does; a mix needs
+    /// manual review. The returned verification names each failed document.
+    ///
+    /// With document encryption configured, document numbers are stored
+    /// encrypted and only their masked form is kept in the clear.
+    pub async fn perform_kyc_verification(
         &mut self,
         customer_id: String,
-        documents: Vec<IdentityDocument>,
+        mut documents: Vec<IdentityDocument>,
         verification_level: KycLevel,
-    ) -> Result<(), AstorError> {
+    ) -> Result<KycVerification, AstorError> {
         let risk_rating = self.assess_customer_risk(&customer_id, &documents)?;
 
+        let now = Utc::now();
+        let mut document_results: Vec<DocumentVerification> = documents
+            .iter()
+            .map(|document| DocumentVerification {
+                document_type: document.document_type.clone(),
+                document_number: document.document_number.clone(),
+                status: Self::verify_document(document, now),
+            })
+            .collect();
+
+        if let Some(encryption) = &self.document_encryption {
+            let encryption = encryption.read().await;
+            for (document, result) in documents.iter_mut().zip(&mut document_results) {
+                document.encrypted_document_number = Some(encryption.encrypt_kyc_field(
+                    &customer_id,
+                    "document_number",
+                    &document.document_number,
+                )?);
+                document.document_number = mask_document_number(&document.document_number);
+                result.document_number = document.document_number.clone();
+            }
+        }
+
+        let verified_count = document_results
+            .iter()
+            .filter(|result| result.status == VerificationStatus::Verified)
+            .count();
+        let verification_status = if document_results.is_empty() {
+            VerificationStatus::Pending
+        } else if verified_count == document_results.len() {
+            VerificationStatus::Verified
+        } else if verified_count == 0 {
+            VerificationStatus::Rejected("No identity document could be verified".to_string())
+        } else {
+            VerificationStatus::RequiresReview
+        };
+
+        for failed in document_results
+            .iter()
+            .filter(|result| result.status != VerificationStatus::Verified)
+        {
+            tracing::warn!(
+                "KYC document {:?} {} for {} failed: {:?}",
+                failed.document_type,
+                failed.document_number,
+                customer_id,
+                failed.status
+            );
+        }
+
         let verification = KycVerification {
             customer_id: customer_id.clone(),
             verification_level,
             identity_documents: documents,
-            verification_status: VerificationStatus::Pending,
-            verified_at: None,
+            document_results,
+            verified_at: (verification_status == VerificationStatus::Verified).then_some(now),
+            verification_status,
             risk_rating,
         };
 
-        self.kyc_verifications.insert(customer_id, verification);
-        Ok(())
+        self.kyc_verifications
+            .insert(customer_id, verification.clone());
+        Ok(verification)
+    }
+
+    /// Latest KYC verification of a customer
+    pub fn get_kyc_verification(&self, customer_id: &str) -> Option<&KycVerification> {
+        self.kyc_verifications.get(customer_id)
+    }
+
+    /// Full number of a customer's identity document
+    ///
+    /// Decrypting a stored number is recorded as a data access.
+    pub async fn reveal_document_number(
+        &self,
+        customer_id: &str,
+        document: &IdentityDocument,
+        access: &DataAccessRequest,
+    ) -> Result<String, AstorError> {
+        let Some(encrypted) = &document.encrypted_document_number else {
+            return Ok(document.document_number.clone());
+        };
+        let encryption = self.document_encryption.as_ref().ok_or_else(|| {
+            AstorError::CryptographicError("Document encryption is not configured".to_string())
+        })?;
+        encryption
+            .read()
+            .await
+            .decrypt_kyc_field(encrypted, customer_id, access)
+            .await
+    }
+
+    /// Verify a single document as of `now`
+    fn verify_document(document: &IdentityDocument, now: DateTime<Utc>) -> VerificationStatus {
+        if document.document_number.trim().is_empty() {
+            return VerificationStatus::Rejected("Document number is missing".to_string());
+        }
+        if let Some(expiry_date) = document.expiry_date {
+            if expiry_date <= now {
+                return VerificationStatus::Rejected(format!(
+                    "Document expired on {}",
+                    expiry_date.format("%Y-%m-%d")
+                ));
+            }
+        }
+        if !document.verified {
+            return VerificationStatus::Rejected(
+                "Document could not be verified with the issuer".to_string(),
+            );
+        }
+        VerificationStatus::Verified
     }
 
     /// Check for AML violations
+    ///
+    /// Tagged accounts are held to a lower high-value threshold, and every
+    /// transaction on them raises an alert for their most significant tag.
+    /// The transaction is also added to the customer's history and checked
+    /// against the rolling-window rules. Returns the first alert raised.
     pub fn check_aml_compliance(
         &mut self,
         customer_id: &str,
         transaction_amount: u64,
         transaction_pattern: &str,
     ) -> Result<Option<String>, AstorError> {
+        self.check_aml_compliance_at(
+            customer_id,
+            transaction_amount,
+            transaction_pattern,
+            Utc::now(),
+        )
+    }
+
+    fn check_aml_compliance_at(
+        &mut self,
+        customer_id: &str,
+        transaction_amount: u64,
+        transaction_pattern: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<String>, AstorError> {
+        let tags = self.account_tags(customer_id);
+        let (threshold, high_value_severity) = if tags.is_empty() {
+            (HIGH_VALUE_THRESHOLD, AlertSeverity::Medium)
+        } else {
+            (ENHANCED_HIGH_VALUE_THRESHOLD, AlertSeverity::High)
+        };
+
+        let mut alert_id = None;
+
         // Check for high-value transactions
-        if transaction_amount > 10000 {
-            // $10,000 threshold
-            let alert = AmlAlert {
-                alert_id: uuid::Uuid::new_v4().to_string(),
-                customer_id: customer_id.to_string(),
-                alert_type: AmlAlertType::HighValueTransaction,
-                severity: AlertSeverity::Medium,
-                description: format!("High-value transaction: {} ASTOR", transaction_amount),
-                created_at: Utc::now(),
-                status: AlertStatus::Open,
-                assigned_to: None,
-            };
+        if transaction_amount > threshold {
+            alert_id = Some(self.raise_alert(
+                customer_id,
+                AmlAlertType::HighValueTransaction,
+                high_value_severity,
+                format!("High-value transaction: {} ASTOR", transaction_amount),
+                transaction_amount,
+            ));
+        } else if self.sanctions_list.contains(&customer_id.to_string()) {
+            // Check sanctions list
+            alert_id = Some(self.raise_alert(
+                customer_id,
+                AmlAlertType::SanctionsListMatch,
+                AlertSeverity::Critical,
+                "Customer matches sanctions list".to_string(),
+                transaction_amount,
+            ));
+        }
 
-            let alert_id = alert.alert_id.clone();
-            self.aml_alerts.push(alert);
-            return Ok(Some(alert_id));
+        if let Some(tag) = tags.first() {
+            let (alert_type, severity) = tag.alert();
+            let tag_alert_id = self.raise_alert(
+                customer_id,
+                alert_type,
+                severity,
+                format!(
+                    "{} ASTOR {} transaction on account tagged {:?}",
+                    transaction_amount, transaction_pattern, tag
+                ),
+                transaction_amount,
+            );
+            alert_id.get_or_insert(tag_alert_id);
         }
 
-        // Check sanctions list
-        if self.sanctions_list.contains(&customer_id.to_string()) {
-            let alert = AmlAlert {
-                alert_id: uuid::Uuid::new_v4().to_string(),
-                customer_id: customer_id.to_string(),
-                alert_type: AmlAlertType::SanctionsListMatch,
-                severity: AlertSeverity::Critical,
-                description: "Customer matches sanctions list".to_string(),
-                created_at: Utc::now(),
-                status: AlertStatus::Open,
-                assigned_to: None,
-            };
+        for window_alert_id in self.check_window_rules(customer_id, transaction_amount, now) {
+            alert_id.get_or_insert(window_alert_id);
+        }
+
+        Ok(alert_id)
+    }
+
+    /// Record a transaction and raise alerts for any window it pushes over a limit
+    fn check_window_rules(
+        &mut self,
+        customer_id: &str,
+        transaction_amount: u64,
+        now: DateTime<Utc>,
+    ) -> Vec<String> {
+        let longest_window = self
+            .aml_window_rules
+            .iter()
+            .map(AmlWindowRule::window)
+            .max()
+            .unwrap_or_else(Duration::zero);
+
+        let history = self
+            .transaction_history
+            .entry(customer_id.to_string())
+            .or_default();
+        while history
+            .front()
+            .map_or(false, |(at, _)| *at <= now - longest_window)
+        {
+            history.pop_front();
+        }
+        history.push_back((now, transaction_amount));
 
-            let alert_id = alert.alert_id.clone();
-            self.aml_alerts.push(alert);
-            return Ok(Some(alert_id));
+        let mut crossings = Vec::new();
+        for rule in &self.aml_window_rules {
+            let window_start = now - rule.window();
+            let (count, volume) = history
+                .iter()
+                .filter(|(at, _)| *at > window_start)
+                .fold((0usize, 0u64), |(count, volume), (_, amount)| {
+                    (count + 1, volume.saturating_add(*amount))
+                });
+            let previous_volume = volume - transaction_amount;
+
+            // A single transaction is covered by the high-value check
+            if count > 1 && volume > rule.max_volume && previous_volume <= rule.max_volume {
+                crossings.push((
+                    AmlAlertType::CumulativeVolumeExceeded,
+                    format!(
+                        "{} ASTOR across {} transactions in {}h exceeds the {} ASTOR limit",
+                        volume,
+                        count,
+                        rule.window_secs / 3600,
+                        rule.max_volume
+                    ),
+                ));
+            }
+            if let Some(max_transactions) = rule.max_transactions {
+                if count == max_transactions + 1 {
+                    crossings.push((
+                        AmlAlertType::RapidTransactionSequence,
+                        format!(
+                            "{} transactions in {}h exceeds the limit of {}",
+                            count,
+                            rule.window_secs / 3600,
+                            max_transactions
+                        ),
+                    ));
+                }
+            }
         }
 
-        Ok(None)
+        crossings
+            .into_iter()
+            .map(|(alert_type, description)| {
+                self.raise_alert(
+                    customer_id,
+                    alert_type,
+                    AlertSeverity::Medium,
+                    description,
+                    transaction_amount,
+                )
+            })
+            .collect()
+    }
+
+    fn raise_alert(
+        &mut self,
+        customer_id: &str,
+        alert_type: AmlAlertType,
+        severity: AlertSeverity,
+        description: String,
+        amount: u64,
+    ) -> String {
+        let alert = AmlAlert {
+            alert_id: uuid::Uuid::new_v4().to_string(),
+            customer_id: customer_id.to_string(),
+            alert_type,
+            severity,
+            description,
+            created_at: Utc::now(),
+            status: AlertStatus::Open,
+            assigned_to: None,
+            amount: Some(amount),
+        };
+
+        let alert_id = alert.alert_id.clone();
+        self.aml_alerts.push(alert);
+        alert_id
     }
 
     /// Generate tax report
@@ -248,6 +670,138 @@ impl RegulatoryCompliance {
         Ok(report_id)
     }
 
+    /// Export a Currency Transaction Report for a reported transaction
+    ///
+    /// The transaction must appear in a generated tax report and exceed the
+    /// CTR threshold.
+    pub async fn export_ctr(
+        &self,
+        transaction_id: &str,
+    ) -> Result<CurrencyTransactionReport, AstorError> {
+        let transaction = self
+            .tax_reports
+            .iter()
+            .flat_map(|report| report.customer_transactions.iter())
+            .find(|t| t.transaction_id == transaction_id)
+            .ok_or_else(|| {
+                AstorError::ComplianceError(format!(
+                    "No reported transaction found: {}",
+                    transaction_id
+                ))
+            })?;
+
+        if transaction.amount <= CTR_THRESHOLD {
+            return Err(AstorError::ComplianceError(format!(
+                "Transaction {} of {} does not exceed the CTR threshold of {}",
+                transaction_id, transaction.amount, CTR_THRESHOLD
+            )));
+        }
+
+        let is_cash_out = transaction
+            .transaction_type
+            .to_lowercase()
+            .contains("withdraw");
+        let (cash_in_amount, cash_out_amount) = if is_cash_out {
+            (0, transaction.amount)
+        } else {
+            (transaction.amount, 0)
+        };
+
+        Ok(CurrencyTransactionReport {
+            form_type: "FinCEN CTR".to_string(),
+            filing_institution: self.require_filing_institution()?,
+            person: CtrPerson {
+                customer_id: transaction.customer_id.clone(),
+                identification: self
+                    .filing_identification(&transaction.customer_id, "CTR")
+                    .await?,
+            },
+            transaction: CtrTransaction {
+                transaction_id: transaction.transaction_id.clone(),
+                transaction_date: transaction.timestamp.date_naive(),
+                cash_in_amount,
+                cash_out_amount,
+                total_amount: transaction.amount,
+            },
+            prepared_at: Utc::now(),
+        })
+    }
+
+    /// Export a Suspicious Activity Report for an AML alert
+    pub async fn export_sar(&self, alert_id: &str) -> Result<SuspiciousActivityReport, AstorError> {
+        let alert = self
+            .aml_alerts
+            .iter()
+            .find(|a| a.alert_id == alert_id)
+            .ok_or_else(|| {
+                AstorError::ComplianceError(format!("AML alert not found: {}", alert_id))
+            })?;
+
+        let (category, subcategory) = filings::sar_category(&alert.alert_type);
+        let activity_date = alert.created_at.date_naive();
+
+        Ok(SuspiciousActivityReport {
+            form_type: "FinCEN SAR".to_string(),
+            filing_institution: self.require_filing_institution()?,
+            subject: SarSubject {
+                customer_id: alert.customer_id.clone(),
+                identification: self
+                    .filing_identification(&alert.customer_id, "SAR")
+                    .await?,
+            },
+            activity: SarActivity {
+                alert_id: alert.alert_id.clone(),
+                activity_date_from: activity_date,
+                activity_date_to: activity_date,
+                amount_involved: alert.amount,
+                category: category.to_string(),
+                subcategory: subcategory.to_string(),
+            },
+            narrative: format!(
+                "{} (alert type: {:?}, severity: {:?}, status: {:?})",
+                alert.description, alert.alert_type, alert.severity, alert.status
+            ),
+            prepared_at: Utc::now(),
+        })
+    }
+
+    fn require_filing_institution(&self) -> Result<FilingInstitution, AstorError> {
+        self.filing_institution.clone().ok_or_else(|| {
+            AstorError::ComplianceError("Filing institution is not configured".to_string())
+        })
+    }
+
+    /// First verified identity document on file for a customer, with its
+    /// full number
+    async fn filing_identification(
+        &self,
+        customer_id: &str,
+        filing: &str,
+    ) -> Result<Option<FilingIdentification>, AstorError> {
+        let Some(document) = self
+            .kyc_verifications
+            .get(customer_id)
+            .and_then(|verification| {
+                verification
+                    .identity_documents
+                    .iter()
+                    .find(|document| document.verified)
+            })
+        else {
+            return Ok(None);
+        };
+
+        let access = DataAccessRequest {
+            accessor: "regulatory-filings".to_string(),
+            purpose: format!("{} filing", filing),
+        };
+        let mut identification = FilingIdentification::from(document);
+        identification.id_number = self
+            .reveal_document_number(customer_id, document, &access)
+            .await?;
+        Ok(Some(identification))
+    }
+
     /// Assess customer risk rating
     fn assess_customer_risk(
         &self,
@@ -266,3 +820,379 @@ impl RegulatoryCompliance {
         }
     }
 }
+
+/// Mask all but the last four characters of a document number
+fn mask_document_number(number: &str) -> String {
+    let chars: Vec<char> = number.chars().collect();
+    let visible = if chars.len() > 4 { 4 } else { 0 };
+    let (hidden, shown) = chars.split_at(chars.len() - visible);
+    "*".repeat(hidden.len()) + &shown.iter().collect::<String>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn compliance() -> RegulatoryCompliance {
+        verified_customer(RegulatoryCompliance::new()).await
+    }
+
+    async fn verified_customer(mut compliance: RegulatoryCompliance) -> RegulatoryCompliance {
+        compliance.set_filing_institution(FilingInstitution {
+            legal_name: "First Bank of Astoria".to_string(),
+            tin: "12-3456789".to_string(),
+            primary_regulator: "FDIC".to_string(),
+            address: "1 Treasury Plaza".to_string(),
+            city: "Astoria".to_string(),
+            state: "OR".to_string(),
+            zip_code: "97103".to_string(),
+            country: "US".to_string(),
+            contact_office: "BSA Compliance".to_string(),
+            contact_phone: "555-0100".to_string(),
+        });
+        compliance
+            .perform_kyc_verification(
+                "customer-1".to_string(),
+                vec![IdentityDocument {
+                    document_type: DocumentType::Passport,
+                    document_number: "P1234567".to_string(),
+                    encrypted_document_number: None,
+                    issuing_country: "US".to_string(),
+                    expiry_date: None,
+                    verified: true,
+                }],
+                KycLevel::Basic,
+            )
+            .await
+            .unwrap();
+        compliance
+    }
+
+    fn report_transaction(
+        compliance: &mut RegulatoryCompliance,
+        transaction_type: &str,
+        amount: u64,
+    ) {
+        let now = Utc::now();
+        compliance
+            .generate_tax_report(
+                ReportingPeriod {
+                    start_date: now,
+                    end_date: now,
+                    tax_year: 2026,
+                },
+                vec![TaxableTransaction {
+                    transaction_id: "tx-1".to_string(),
+                    customer_id: "customer-1".to_string(),
+                    transaction_type: transaction_type.to_string(),
+                    amount,
+                    tax_implications: TaxImplications {
+                        is_taxable: false,
+                        tax_category: None,
+                        withholding_required: false,
+                        reporting_threshold_met: true,
+                    },
+                    timestamp: now,
+                }],
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ctr_populated_from_transaction() {
+        let mut compliance = compliance().await;
+        report_transaction(&mut compliance, "cash_withdrawal", 15_000);
+
+        let ctr = compliance.export_ctr("tx-1").await.unwrap();
+        assert_eq!(ctr.filing_institution.tin, "12-3456789");
+        assert_eq!(ctr.person.customer_id, "customer-1");
+        let identification = ctr.person.identification.as_ref().unwrap();
+        assert_eq!(identification.id_type, "Passport");
+        assert_eq!(identification.id_number, "P1234567");
+        assert_eq!(ctr.transaction.transaction_id, "tx-1");
+        assert_eq!(ctr.transaction.cash_in_amount, 0);
+        assert_eq!(ctr.transaction.cash_out_amount, 15_000);
+        assert_eq!(ctr.transaction.total_amount, 15_000);
+        assert_eq!(ctr.transaction.transaction_date, Utc::now().date_naive());
+
+        let json = serde_json::to_value(&ctr).unwrap();
+        assert_eq!(json["form_type"], "FinCEN CTR");
+        assert_eq!(json["transaction"]["total_amount"], 15_000);
+    }
+
+    #[tokio::test]
+    async fn test_document_numbers_stored_encrypted() {
+        use crate::monitoring::compliance::{ComplianceMonitor, ComplianceReportType};
+
+        let monitor = Arc::new(ComplianceMonitor::new());
+        let mut encryption = EncryptionManager::new("kyc-test-master-key").unwrap();
+        encryption.set_data_access_auditor(monitor.clone());
+        let mut compliance = RegulatoryCompliance::new();
+        compliance.set_document_encryption(Arc::new(RwLock::new(encryption)));
+        let start = Utc::now();
+
+        let mut compliance = verified_customer(compliance).await;
+        let verification = compliance.get_kyc_verification("customer-1").unwrap();
+        let document = &verification.identity_documents[0];
+        assert_eq!(document.document_number, "****4567");
+        assert!(document.encrypted_document_number.is_some());
+        assert_eq!(verification.document_results[0].document_number, "****4567");
+        assert!(!serde_json::to_string(verification)
+            .unwrap()
+            .contains("P1234567"));
+
+        // Filings carry the full number, and reading it is audited
+        report_transaction(&mut compliance, "cash_deposit", 15_000);
+        let ctr = compliance.export_ctr("tx-1").await.unwrap();
+        assert_eq!(ctr.person.identification.unwrap().id_number, "P1234567");
+        let report = monitor
+            .generate_report(ComplianceReportType::GDPR, start, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(report.summary.data_access_events, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ctr_requires_amount_above_threshold() {
+        let mut compliance = compliance().await;
+        report_transaction(&mut compliance, "cash_deposit", CTR_THRESHOLD);
+
+        assert!(compliance.export_ctr("tx-1").await.is_err());
+        assert!(compliance.export_ctr("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sar_populated_from_alert() {
+        let mut compliance = compliance().await;
+        let alert_id = compliance
+            .check_aml_compliance("customer-1", 25_000, "single")
+            .unwrap()
+            .unwrap();
+
+        let sar = compliance.export_sar(&alert_id).await.unwrap();
+        assert_eq!(sar.subject.customer_id, "customer-1");
+        assert_eq!(
+            sar.subject.identification.as_ref().unwrap().id_number,
+            "P1234567"
+        );
+        assert_eq!(sar.activity.alert_id, alert_id);
+        assert_eq!(sar.activity.amount_involved, Some(25_000));
+        assert_eq!(sar.activity.category, "Other Suspicious Activities");
+        assert!(sar.narrative.contains("High-value transaction"));
+        assert_eq!(sar.filing_institution.legal_name, "First Bank of Astoria");
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_filing_institution() {
+        let mut compliance = RegulatoryCompliance::new();
+        let alert_id = compliance
+            .check_aml_compliance("customer-1", 25_000, "single")
+            .unwrap()
+            .unwrap();
+
+        assert!(compliance.export_sar(&alert_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pep_tagged_transaction_raises_pep_alert() {
+        let mut compliance = compliance().await;
+        assert_eq!(
+            compliance
+                .check_aml_compliance("customer-1", 500, "transfer")
+                .unwrap(),
+            None
+        );
+
+        compliance.tag_account("customer-1", RegulatoryTag::Pep);
+        let alert_id = compliance
+            .check_aml_compliance("customer-1", 500, "transfer")
+            .unwrap()
+            .expect("PEP transactions always raise an alert");
+
+        let alerts = compliance.get_aml_alerts("customer-1");
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].alert_id, alert_id);
+        assert!(matches!(
+            alerts[0].alert_type,
+            AmlAlertType::PoliticallyExposedPerson
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tagged_account_uses_stricter_threshold() {
+        let mut compliance = compliance().await;
+        compliance
+            .check_aml_compliance("customer-2", 5_000, "transfer")
+            .unwrap();
+        assert!(compliance.get_aml_alerts("customer-2").is_empty());
+
+        compliance.tag_account("customer-2", RegulatoryTag::HighRiskJurisdiction);
+        compliance
+            .check_aml_compliance("customer-2", 5_000, "transfer")
+            .unwrap();
+
+        let alerts = compliance.get_aml_alerts("customer-2");
+        assert_eq!(alerts.len(), 2);
+        assert!(matches!(
+            alerts[0].alert_type,
+            AmlAlertType::HighValueTransaction
+        ));
+        assert!(matches!(alerts[0].severity, AlertSeverity::High));
+        assert!(matches!(
+            alerts[1].alert_type,
+            AmlAlertType::UnusualGeographicActivity
+        ));
+
+        compliance.untag_account("customer-2", RegulatoryTag::HighRiskJurisdiction);
+        assert!(compliance.account_tags("customer-2").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cumulative_window_volume_raises_alert() {
+        let mut compliance = compliance().await;
+        compliance.set_aml_window_rules(vec![AmlWindowRule {
+            window_secs: 24 * 3600,
+            max_volume: 10_000,
+            max_transactions: Some(5),
+        }]);
+        let start = Utc::now();
+        let at_hour = |hours: i64| start + Duration::hours(hours);
+
+        // Each transaction is well under the high-value threshold
+        for hour in 0..2 {
+            assert_eq!(
+                compliance
+                    .check_aml_compliance_at("customer-1", 4_000, "transfer", at_hour(hour))
+                    .unwrap(),
+                None
+            );
+        }
+        let alert_id = compliance
+            .check_aml_compliance_at("customer-1", 4_000, "transfer", at_hour(2))
+            .unwrap()
+            .expect("12,000 in a day exceeds the 10,000 limit");
+
+        let alerts = compliance.get_aml_alerts("customer-1");
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].alert_id, alert_id);
+        assert!(matches!(
+            alerts[0].alert_type,
+            AmlAlertType::CumulativeVolumeExceeded
+        ));
+
+        // Already over the limit; no repeat alert for the same window
+        compliance
+            .check_aml_compliance_at("customer-1", 1_000, "transfer", at_hour(3))
+            .unwrap();
+        assert_eq!(compliance.get_aml_alerts("customer-1").len(), 1);
+
+        // The earlier transactions have aged out of the window
+        compliance
+            .check_aml_compliance_at("customer-1", 4_000, "transfer", at_hour(30))
+            .unwrap();
+        assert_eq!(compliance.get_aml_alerts("customer-1").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rapid_sequence_raises_alert_once() {
+        let mut compliance = compliance().await;
+        compliance.set_aml_window_rules(vec![AmlWindowRule {
+            window_secs: 3600,
+            max_volume: u64::MAX,
+            max_transactions: Some(3),
+        }]);
+        let start = Utc::now();
+
+        for minute in 0..6 {
+            compliance
+                .check_aml_compliance_at(
+                    "customer-1",
+                    10,
+                    "transfer",
+                    start + Duration::minutes(minute),
+                )
+                .unwrap();
+        }
+
+        let alerts = compliance.get_aml_alerts("customer-1");
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(
+            alerts[0].alert_type,
+            AmlAlertType::RapidTransactionSequence
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_partially_verified_documents_require_review() {
+        let mut compliance = RegulatoryCompliance::new();
+        let document =
+            |document_type, document_number: &str, expiry_date, verified| IdentityDocument {
+                document_type,
+                document_number: document_number.to_string(),
+                encrypted_document_number: None,
+                issuing_country: "US".to_string(),
+                expiry_date,
+                verified,
+            };
+
+        let verification = compliance
+            .perform_kyc_verification(
+                "customer-2".to_string(),
+                vec![
+                    document(DocumentType::Passport, "P7654321", None, true),
+                    document(
+                        DocumentType::DriversLicense,
+                        "D1234",
+                        Some(Utc::now() - Duration::days(1)),
+                        true,
+                    ),
+                    document(DocumentType::UtilityBill, "U42", None, false),
+                ],
+                KycLevel::Basic,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            verification.verification_status,
+            VerificationStatus::RequiresReview
+        );
+        assert!(verification.verified_at.is_none());
+        assert_eq!(
+            verification.document_results[0].status,
+            VerificationStatus::Verified
+        );
+
+        let failed: Vec<&str> = verification
+            .failed_documents()
+            .iter()
+            .map(|result| result.document_number.as_str())
+            .collect();
+        assert_eq!(failed, vec!["D1234", "U42"]);
+        assert!(matches!(
+            &verification.document_results[1].status,
+            VerificationStatus::Rejected(reason) if reason.starts_with("Document expired")
+        ));
+
+        // The same outcome is kept for later lookups
+        assert_eq!(
+            compliance
+                .get_kyc_verification("customer-2")
+                .unwrap()
+                .verification_status,
+            VerificationStatus::RequiresReview
+        );
+
+        // A customer whose documents all pass is verified outright
+        let verified = compliance
+            .perform_kyc_verification(
+                "customer-3".to_string(),
+                vec![document(DocumentType::NationalId, "N1", None, true)],
+                KycLevel::Basic,
+            )
+            .await
+            .unwrap();
+        assert_eq!(verified.verification_status, VerificationStatus::Verified);
+        assert!(verified.failed_documents().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Schema versioning for persisted records
+//!
+//! Serialized records carry a `schema_version`. Payloads written before the
+//! field existed have none and are treated as version 1. Loading through
+//! [`from_versioned_json`] upgrades an older payload one version at a time
+//! before decoding it, so stored financial data keeps loading as formats
+//! evolve. Payloads from a newer version than this build understands are
+//! rejected rather than decoded with fields silently dropped.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::errors::AstorError;
+
+/// Field holding a record's schema version
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// A persisted type whose serialized form can be upgraded
+pub trait Versioned: DeserializeOwned {
+    /// Version written by this build
+    const SCHEMA_VERSION: u32;
+
+    /// Upgrade a payload from `from_version` to `from_version + 1`
+    ///
+    /// The version field itself is updated by the caller.
+    fn migrate(from_version: u32, payload: &mut Map<String, Value>) -> Result<(), AstorError>;
+}
+
+/// Version assumed for payloads written before versioning was introduced
+pub(crate) fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// Schema version recorded in a payload, defaulting to version 1
+pub fn schema_version_of(payload: &Value) -> Result<u32, AstorError> {
+    match payload.get(SCHEMA_VERSION_FIELD) {
+        None | Some(Value::Null) => Ok(legacy_schema_version()),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| {
+                AstorError::InvalidOperation(format!("Invalid schema version: {}", version))
+            }),
+    }
+}
+
+/// Upgrade a payload to the current version of `T` and decode it
+pub fn upgrade<T: Versioned>(mut payload: Value) -> Result<T, AstorError> {
+    let mut version = schema_version_of(&payload)?;
+    if version > T::SCHEMA_VERSION {
+        return Err(AstorError::UnsupportedSchemaVersion {
+            found: version,
+            supported: T::SCHEMA_VERSION,
+        });
+    }
+
+    let fields = payload.as_object_mut().ok_or_else(|| {
+        AstorError::InvalidOperation("Versioned payload must be a JSON object".to_string())
+    })?;
+
+    while version < T::SCHEMA_VERSION {
+        T::migrate(version, fields)?;
+        version += 1;
+        fields.insert(SCHEMA_VERSION_FIELD.to_string(), Value::from(version));
+    }
+
+    Ok(serde_json::from_value(payload)?)
+}
+
+/// Decode JSON written by any supported version of `T`
+pub fn from_versioned_json<T: Versioned>(json: &[u8]) -> Result<T, AstorError> {
+    upgrade(serde_json::from_slice(json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::LedgerEntry;
+    use crate::transactions::{Transaction, TransactionPriority, TransactionStatus};
+
+    #[test]
+    fn test_v1_transaction_payload_is_migrated() {
+        // Written before status history and priority were recorded
+        let v1 = serde_json::json!({
+            "schema_version": 1,
+            "id": "tx-1",
+            "transaction_type": {
+                "Transfer": { "from": "alice", "to": "bob", "amount": 250 }
+            },
+            "timestamp": "2024-01-15T10:30:00Z",
+            "status": "Completed",
+            "hash": "abc123",
+        });
+
+        let transaction: Transaction =
+            from_versioned_json(&serde_json::to_vec(&v1).unwrap()).unwrap();
+
+        assert_eq!(transaction.schema_version, Transaction::SCHEMA_VERSION);
+        assert_eq!(transaction.id, "tx-1");
+        assert!(matches!(transaction.status, TransactionStatus::Completed));
+        assert!(transaction.status_history.is_empty());
+        assert_eq!(transaction.priority, TransactionPriority::default());
+    }
+
+    #[test]
+    fn test_untagged_payload_is_treated_as_v1_and_newer_rejected() {
+        let mut entry = serde_json::json!({
+            "id": "entry-1",
+            "entry_type": {
+                "Issuance": {
+                    "transaction_id": "tx-1",
+                    "issuer": "central_bank",
+                    "recipient": "alice",
+                    "amount": 100
+                }
+            },
+            "timestamp": "2024-01-15T10:30:00Z",
+            "hash": "def456",
+            "previous_hash": "genesis",
+        });
+        assert_eq!(schema_version_of(&entry).unwrap(), 1);
+
+        let upgraded: LedgerEntry = upgrade(entry.clone()).unwrap();
+        assert_eq!(upgraded.schema_version, LedgerEntry::SCHEMA_VERSION);
+        assert_eq!(upgraded.hash, "def456");
+
+        entry[SCHEMA_VERSION_FIELD] = Value::from(LedgerEntry::SCHEMA_VERSION + 1);
+        assert!(matches!(
+            upgrade::<LedgerEntry>(entry),
+            Err(AstorError::UnsupportedSchemaVersion { .. })
+        ));
+    }
+}
@@ -0,0 +1,237 @@
+//! API keys for service-to-service callers
+//!
+//! Keys are bound to a `Role` and an optional set of scopes. Only a SHA-256
+//! hash of each secret is stored; the plaintext key is returned once, when it
+//! is created.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::auth::Role;
+use super::crypto::{generate_secure_random, hash_data};
+use crate::errors::AstorError;
+
+/// Prefix identifying Astor API keys in logs and secret scanners
+const API_KEY_PREFIX: &str = "astor";
+
+/// Stored API key metadata; never contains the plaintext secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub key_id: String,
+    pub name: String,
+    pub role: Role,
+    /// Empty means the key is not restricted beyond its role
+    pub scopes: Vec<String>,
+    secret_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Utc::now() >= expires_at)
+    }
+
+    /// Whether the key may be used for `scope`
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.is_empty() || self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// Identity attached to a request authenticated with an API key
+#[derive(Debug, Clone)]
+pub struct ApiKeyPrincipal {
+    pub key_id: String,
+    pub role: Role,
+    pub scopes: Vec<String>,
+}
+
+/// Issues, validates and revokes API keys
+pub struct ApiKeyManager {
+    keys: HashMap<String, ApiKey>,
+    key_length: usize,
+}
+
+impl ApiKeyManager {
+    /// Create a manager minting secrets of `key_length` random bytes
+    pub fn new(key_length: usize) -> Self {
+        Self {
+            keys: HashMap::new(),
+            key_length,
+        }
+    }
+
+    /// Mint a new key, returning the plaintext key and its stored record
+    ///
+    /// The plaintext is not retained and cannot be recovered later.
+    pub fn create_key(
+        &mut self,
+        name: String,
+        role: Role,
+        scopes: Vec<String>,
+        ttl: Option<Duration>,
+    ) -> Result<(String, ApiKey), AstorError> {
+        if self.key_length < 16 {
+            return Err(AstorError::ConfigurationError(
+                "API keys must be at least 16 bytes".to_string(),
+            ));
+        }
+
+        let key_id = Uuid::new_v4().simple().to_string();
+        let secret = hex::encode(generate_secure_random(self.key_length));
+        let now = Utc::now();
+
+        let key = ApiKey {
+            key_id: key_id.clone(),
+            name,
+            role,
+            scopes,
+            secret_hash: hash_data(secret.as_bytes()),
+            created_at: now,
+            expires_at: ttl.map(|ttl| now + ttl),
+            revoked_at: None,
+            last_used_at: None,
+        };
+
+        tracing::info!(
+            "Created API key {} ({}) for role {:?}",
+            key_id,
+            key.name,
+            key.role
+        );
+        self.keys.insert(key_id.clone(), key.clone());
+
+        Ok((format!("{}_{}_{}", API_KEY_PREFIX, key_id, secret), key))
+    }
+
+    /// Check a presented key and record its use
+    pub fn validate_key(&mut self, presented: &str) -> Result<ApiKeyPrincipal, AstorError> {
+        let invalid = || AstorError::Unauthorized("Invalid API key".to_string());
+
+        let (key_id, secret) = presented
+            .strip_prefix(API_KEY_PREFIX)
+            .and_then(|rest| rest.strip_prefix('_'))
+            .and_then(|rest| rest.split_once('_'))
+            .ok_or_else(invalid)?;
+
+        let key = self.keys.get_mut(key_id).ok_or_else(invalid)?;
+        if hash_data(secret.as_bytes()) != key.secret_hash {
+            return Err(invalid());
+        }
+        if key.is_revoked() {
+            return Err(AstorError::Unauthorized(format!(
+                "API key {} has been revoked",
+                key_id
+            )));
+        }
+        if key.is_expired() {
+            return Err(AstorError::Unauthorized(format!(
+                "API key {} has expired",
+                key_id
+            )));
+        }
+
+        key.last_used_at = Some(Utc::now());
+        Ok(ApiKeyPrincipal {
+            key_id: key.key_id.clone(),
+            role: key.role.clone(),
+            scopes: key.scopes.clone(),
+        })
+    }
+
+    /// Revoke a key; later requests presenting it are rejected
+    pub fn revoke_key(&mut self, key_id: &str) -> Result<(), AstorError> {
+        let key = self
+            .keys
+            .get_mut(key_id)
+            .ok_or_else(|| AstorError::InvalidOperation(format!("Unknown API key {}", key_id)))?;
+
+        if key.revoked_at.is_none() {
+            key.revoked_at = Some(Utc::now());
+            tracing::warn!("Revoked API key {} ({})", key_id, key.name);
+        }
+        Ok(())
+    }
+
+    /// All keys, including revoked and expired ones, oldest first
+    pub fn list_keys(&self) -> Vec<&ApiKey> {
+        let mut keys: Vec<&ApiKey> = self.keys.values().collect();
+        keys.sort_by_key(|key| key.created_at);
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_key_authorizes_mapped_role() {
+        let mut manager = ApiKeyManager::new(32);
+        let (plaintext, key) = manager
+            .create_key(
+                "settlement-service".to_string(),
+                Role::Operator,
+                vec!["transactions:read".to_string()],
+                None,
+            )
+            .unwrap();
+
+        let principal = manager.validate_key(&plaintext).unwrap();
+        assert_eq!(principal.key_id, key.key_id);
+        assert_eq!(principal.role, Role::Operator);
+        assert!(manager.list_keys()[0].last_used_at.is_some());
+        assert!(manager.list_keys()[0].has_scope("transactions:read"));
+        assert!(!manager.list_keys()[0].has_scope("accounts:write"));
+
+        // A wrong secret for a real key id is rejected
+        let forged = format!("{}x", plaintext);
+        assert!(manager.validate_key(&forged).is_err());
+    }
+
+    #[test]
+    fn test_revoked_key_rejected() {
+        let mut manager = ApiKeyManager::new(32);
+        let (plaintext, key) = manager
+            .create_key(
+                "batch-exporter".to_string(),
+                Role::Auditor,
+                Vec::new(),
+                None,
+            )
+            .unwrap();
+        assert!(manager.validate_key(&plaintext).is_ok());
+
+        manager.revoke_key(&key.key_id).unwrap();
+
+        assert!(matches!(
+            manager.validate_key(&plaintext),
+            Err(AstorError::Unauthorized(_))
+        ));
+        assert!(manager.list_keys()[0].is_revoked());
+    }
+
+    #[test]
+    fn test_expired_key_rejected() {
+        let mut manager = ApiKeyManager::new(32);
+        let (plaintext, _) = manager
+            .create_key(
+                "nightly-job".to_string(),
+                Role::Operator,
+                Vec::new(),
+                Some(Duration::seconds(-1)),
+            )
+            .unwrap();
+
+        assert!(manager.validate_key(&plaintext).is_err());
+    }
+}
@@ -2,7 +2,8 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::errors::AstorError;
@@ -76,10 +77,301 @@ pub enum AuditSeverity {
     Critical,
 }
 
+/// Output formats supported when exporting audit logs to a SIEM
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SiemFormat {
+    /// Pretty-printed JSON array of audit log entries
+    Json,
+    /// ArcSight Common Event Format, one event per line
+    Cef,
+    /// Elastic Common Schema, newline-delimited JSON
+    Ecs,
+}
+
+/// Normalized SIEM fields extracted from a security event
+#[derive(Debug, Clone)]
+struct SiemFields {
+    signature_id: &'static str,
+    name: &'static str,
+    category: &'static str,
+    actor: Option<String>,
+    action: String,
+    outcome: &'static str,
+    source_ip: Option<String>,
+    message: String,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+impl SecurityEvent {
+    /// Get the timestamp of the event, if it carries one
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        match self {
+            SecurityEvent::LoginAttempt { timestamp, .. } => Some(*timestamp),
+            SecurityEvent::PermissionDenied { timestamp, .. } => Some(*timestamp),
+            SecurityEvent::AdminAction { timestamp, .. } => Some(*timestamp),
+            SecurityEvent::SecurityViolation { timestamp, .. } => Some(*timestamp),
+            SecurityEvent::DataAccess { timestamp, .. } => Some(*timestamp),
+            SecurityEvent::SystemEvent { timestamp, .. } => Some(*timestamp),
+            SecurityEvent::HighRiskOperation { .. } => None,
+        }
+    }
+
+    /// Map the event onto SIEM actor/action/outcome fields
+    fn siem_fields(&self) -> SiemFields {
+        match self {
+            SecurityEvent::LoginAttempt {
+                user_id,
+                ip_address,
+                success,
+                timestamp,
+                ..
+            } => SiemFields {
+                signature_id: "login_attempt",
+                name: "Login attempt",
+                category: "authentication",
+                actor: Some(user_id.clone()),
+                action: "login".to_string(),
+                outcome: if *success { "success" } else { "failure" },
+                source_ip: Some(ip_address.clone()),
+                message: format!("Login attempt by {}", user_id),
+                timestamp: Some(*timestamp),
+            },
+            SecurityEvent::PermissionDenied {
+                user_id,
+                resource,
+                action,
+                timestamp,
+            } => SiemFields {
+                signature_id: "permission_denied",
+                name: "Permission denied",
+                category: "iam",
+                actor: Some(user_id.clone()),
+                action: action.clone(),
+                outcome: "failure",
+                source_ip: None,
+                message: format!("Permission denied on {}", resource),
+                timestamp: Some(*timestamp),
+            },
+            SecurityEvent::HighRiskOperation {
+                user_id,
+                operation,
+                risk_score,
+                ip_address,
+            } => SiemFields {
+                signature_id: "high_risk_operation",
+                name: "High risk operation",
+                category: "intrusion_detection",
+                actor: Some(user_id.clone()),
+                action: operation.clone(),
+                outcome: "unknown",
+                source_ip: Some(ip_address.clone()),
+                message: format!("Risk score {:.2}", risk_score),
+                timestamp: None,
+            },
+            SecurityEvent::AdminAction {
+                admin_id,
+                action,
+                target,
+                timestamp,
+            } => SiemFields {
+                signature_id: "admin_action",
+                name: "Administrator action",
+                category: "configuration",
+                actor: Some(admin_id.clone()),
+                action: action.clone(),
+                outcome: "success",
+                source_ip: None,
+                message: format!("Target: {}", target),
+                timestamp: Some(*timestamp),
+            },
+            SecurityEvent::SecurityViolation {
+                user_id,
+                violation_type,
+                details,
+                ip_address,
+                timestamp,
+            } => SiemFields {
+                signature_id: "security_violation",
+                name: "Security violation",
+                category: "intrusion_detection",
+                actor: user_id.clone(),
+                action: violation_type.clone(),
+                outcome: "failure",
+                source_ip: Some(ip_address.clone()),
+                message: details.clone(),
+                timestamp: Some(*timestamp),
+            },
+            SecurityEvent::DataAccess {
+                user_id,
+                resource_type,
+                resource_id,
+                action,
+                timestamp,
+            } => SiemFields {
+                signature_id: "data_access",
+                name: "Data access",
+                category: "database",
+                actor: Some(user_id.clone()),
+                action: action.clone(),
+                outcome: "success",
+                source_ip: None,
+                message: format!("{} {}", resource_type, resource_id),
+                timestamp: Some(*timestamp),
+            },
+            SecurityEvent::SystemEvent {
+                event_type,
+                details,
+                timestamp,
+            } => SiemFields {
+                signature_id: "system_event",
+                name: "System event",
+                category: "process",
+                actor: None,
+                action: event_type.clone(),
+                outcome: "success",
+                source_ip: None,
+                message: details.clone(),
+                timestamp: Some(*timestamp),
+            },
+        }
+    }
+}
+
+impl AuditSeverity {
+    /// CEF severity on the 0-10 scale
+    pub fn cef_severity(&self) -> u8 {
+        match self {
+            AuditSeverity::Info => 3,
+            AuditSeverity::Warning => 5,
+            AuditSeverity::Error => 8,
+            AuditSeverity::Critical => 10,
+        }
+    }
+
+    /// ECS `event.severity` value
+    pub fn ecs_severity(&self) -> u8 {
+        match self {
+            AuditSeverity::Info => 1,
+            AuditSeverity::Warning => 2,
+            AuditSeverity::Error => 3,
+            AuditSeverity::Critical => 4,
+        }
+    }
+}
+
+/// Escape a CEF header field (pipes and backslashes)
+fn escape_cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Escape a CEF extension value (equals signs, backslashes and newlines)
+fn escape_cef_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+impl AuditLogEntry {
+    /// Render the entry as a single CEF line
+    pub fn to_cef(&self) -> String {
+        let fields = self.event.siem_fields();
+        let mut extension = vec![format!("act={}", escape_cef_extension(&fields.action))];
+        if let Some(actor) = &fields.actor {
+            extension.push(format!("suser={}", escape_cef_extension(actor)));
+        }
+        if let Some(ip) = &fields.source_ip {
+            extension.push(format!("src={}", escape_cef_extension(ip)));
+        }
+        if let Some(timestamp) = fields.timestamp {
+            extension.push(format!("rt={}", timestamp.timestamp_millis()));
+        }
+        extension.push(format!("outcome={}", fields.outcome));
+        extension.push(format!("externalId={}", self.id));
+        extension.push(format!("msg={}", escape_cef_extension(&fields.message)));
+
+        format!(
+            "CEF:0|Astoria|{}|{}|{}|{}|{}|{}",
+            escape_cef_header(&self.source),
+            env!("CARGO_PKG_VERSION"),
+            fields.signature_id,
+            fields.name,
+            self.severity.cef_severity(),
+            extension.join(" ")
+        )
+    }
+
+    /// Render the entry as an ECS document
+    pub fn to_ecs(&self) -> serde_json::Value {
+        let fields = self.event.siem_fields();
+        serde_json::json!({
+            "@timestamp": fields.timestamp.map(|t| t.to_rfc3339()),
+            "message": fields.message,
+            "event": {
+                "id": self.id.to_string(),
+                "kind": "event",
+                "category": [fields.category],
+                "code": fields.signature_id,
+                "action": fields.action,
+                "outcome": fields.outcome,
+                "severity": self.severity.ecs_severity(),
+                "provider": self.source,
+            },
+            "user": { "id": fields.actor },
+            "source": { "ip": fields.source_ip },
+            "log": { "level": format!("{:?}", self.severity).to_lowercase() },
+            "labels": self.metadata,
+        })
+    }
+}
+
+/// How many entries of each severity are kept in memory
+///
+/// Each severity has its own buffer, so a flood of informational events can
+/// never push a critical one out.
+#[derive(Debug, Clone)]
+pub struct AuditRetentionConfig {
+    pub info_capacity: usize,
+    pub warning_capacity: usize,
+    pub error_capacity: usize,
+    pub critical_capacity: usize,
+}
+
+impl AuditRetentionConfig {
+    pub fn capacity(&self, severity: &AuditSeverity) -> usize {
+        match severity {
+            AuditSeverity::Info => self.info_capacity,
+            AuditSeverity::Warning => self.warning_capacity,
+            AuditSeverity::Error => self.error_capacity,
+            AuditSeverity::Critical => self.critical_capacity,
+        }
+    }
+}
+
+impl Default for AuditRetentionConfig {
+    fn default() -> Self {
+        Self {
+            info_capacity: 10_000,
+            warning_capacity: 10_000,
+            error_capacity: 50_000,
+            critical_capacity: 50_000,
+        }
+    }
+}
+
+/// Durable storage that audit entries are written to before being evicted
+/// from memory
+#[async_trait::async_trait]
+pub trait AuditLogSink: Send + Sync {
+    async fn persist(&self, entries: &[AuditLogEntry]) -> Result<(), AstorError>;
+}
+
 /// Security audit logger
 pub struct SecurityAuditLogger {
-    logs: VecDeque<AuditLogEntry>,
-    max_logs: usize,
+    logs: BTreeMap<AuditSeverity, VecDeque<AuditLogEntry>>,
+    retention: AuditRetentionConfig,
+    sink: Option<Arc<dyn AuditLogSink>>,
     alert_thresholds: std::collections::HashMap<String, u32>,
 }
 
@@ -91,12 +383,84 @@ impl SecurityAuditLogger {
         alert_thresholds.insert("high_risk_operation".to_string(), 3);
 
         Self {
-            logs: VecDeque::new(),
-            max_logs: 10000, // Keep last 10k logs in memory
+            logs: BTreeMap::new(),
+            retention: AuditRetentionConfig::default(),
+            sink: None,
             alert_thresholds,
         }
     }
 
+    pub fn with_retention(mut self, retention: AuditRetentionConfig) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Persist entries to `sink` before they are evicted from memory
+    pub fn set_sink(&mut self, sink: Arc<dyn AuditLogSink>) {
+        self.sink = Some(sink);
+    }
+
+    pub fn retention(&self) -> &AuditRetentionConfig {
+        &self.retention
+    }
+
+    /// Number of entries of a severity currently held in memory
+    pub fn retained_count(&self, severity: &AuditSeverity) -> usize {
+        self.logs.get(severity).map_or(0, VecDeque::len)
+    }
+
+    /// All in-memory entries, grouped by severity
+    fn entries(&self) -> impl Iterator<Item = &AuditLogEntry> {
+        self.logs.values().flatten()
+    }
+
+    /// Evict the oldest entries of a severity beyond its capacity
+    ///
+    /// Evicted entries are handed to the sink first. If that fails, error and
+    /// critical entries stay in memory past their capacity until a later
+    /// eviction succeeds; info and warning entries are dropped.
+    async fn enforce_retention(&mut self, severity: &AuditSeverity) {
+        let capacity = self.retention.capacity(severity);
+        let Some(buffer) = self.logs.get_mut(severity) else {
+            return;
+        };
+        if buffer.len() <= capacity {
+            return;
+        }
+
+        let overflow = buffer.len() - capacity;
+        let evicted: Vec<AuditLogEntry> = buffer.drain(..overflow).collect();
+
+        let persisted = match &self.sink {
+            Some(sink) => match sink.persist(&evicted).await {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to persist {} {:?} audit entries before eviction: {}",
+                        evicted.len(),
+                        severity,
+                        e
+                    );
+                    false
+                }
+            },
+            None => false,
+        };
+
+        if !persisted && *severity >= AuditSeverity::Error {
+            // Put them back in front so order is preserved
+            for entry in evicted.into_iter().rev() {
+                buffer.push_front(entry);
+            }
+        } else if !persisted {
+            tracing::warn!(
+                "Dropped {} {:?} audit entries without persisting them",
+                evicted.len(),
+                severity
+            );
+        }
+    }
+
     /// Log a security event
     pub async fn log_security_event(&mut self, event: SecurityEvent) -> Result<(), AstorError> {
         let severity = self.determine_severity(&event);
@@ -110,12 +474,13 @@ impl SecurityAuditLogger {
         };
 
         // Add to in-memory log
-        self.logs.push_back(entry.clone());
+        self.logs
+            .entry(entry.severity.clone())
+            .or_default()
+            .push_back(entry.clone());
 
-        // Maintain max size
-        if self.logs.len() > self.max_logs {
-            self.logs.pop_front();
-        }
+        // Maintain per-severity size
+        self.enforce_retention(&entry.severity).await;
 
         // Check for alert conditions
         self.check_alert_conditions(&event).await?;
@@ -161,9 +526,8 @@ impl SecurityAuditLogger {
         // Count recent events of this type
         let recent_count = self
             .logs
-            .iter()
-            .rev()
-            .take(100) // Check last 100 events
+            .values()
+            .flat_map(|buffer| buffer.iter().rev().take(100)) // Check last 100 events per severity
             .filter(|entry| {
                 // Check if event matches type and is recent (last hour)
                 let is_recent = match &entry.event {
@@ -212,8 +576,7 @@ impl SecurityAuditLogger {
         limit: Option<usize>,
     ) -> Vec<&AuditLogEntry> {
         let mut filtered: Vec<&AuditLogEntry> = self
-            .logs
-            .iter()
+            .entries()
             .filter(|entry| {
                 severity_filter
                     .as_ref()
@@ -258,8 +621,7 @@ impl SecurityAuditLogger {
         end_date: DateTime<Utc>,
     ) -> ComplianceReport {
         let relevant_logs: Vec<&AuditLogEntry> = self
-            .logs
-            .iter()
+            .entries()
             .filter(|entry| {
                 let event_time = match &entry.event {
                     SecurityEvent::LoginAttempt { timestamp, .. } => *timestamp,
@@ -276,6 +638,42 @@ impl SecurityAuditLogger {
 
         ComplianceReport::new(relevant_logs, start_date, end_date)
     }
+
+    /// Export audit logs within a time range in a SIEM-ingestible format
+    ///
+    /// Events without a timestamp are always included, since their time
+    /// cannot be determined.
+    pub fn export_audit(
+        &self,
+        format: SiemFormat,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> Result<String, AstorError> {
+        let mut entries: Vec<&AuditLogEntry> = self
+            .entries()
+            .filter(|entry| {
+                entry
+                    .event
+                    .timestamp()
+                    .map_or(true, |t| t >= start_date && t <= end_date)
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.event.timestamp());
+
+        match format {
+            SiemFormat::Json => serde_json::to_string_pretty(&entries).map_err(AstorError::from),
+            SiemFormat::Cef => Ok(entries
+                .iter()
+                .map(|entry| entry.to_cef())
+                .collect::<Vec<_>>()
+                .join("\n")),
+            SiemFormat::Ecs => entries
+                .iter()
+                .map(|entry| serde_json::to_string(&entry.to_ecs()).map_err(AstorError::from))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|lines| lines.join("\n")),
+        }
+    }
 }
 
 /// Compliance report for regulatory requirements
@@ -332,3 +730,144 @@ impl ComplianceReport {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_security_violation_cef_export() {
+        let mut logger = SecurityAuditLogger::new();
+        let now = Utc::now();
+
+        logger
+            .log_security_event(SecurityEvent::SecurityViolation {
+                user_id: Some("user-42".to_string()),
+                violation_type: "sql_injection".to_string(),
+                details: "payload=' OR 1=1".to_string(),
+                ip_address: "10.0.0.7".to_string(),
+                timestamp: now,
+            })
+            .await
+            .unwrap();
+
+        let cef = logger
+            .export_audit(
+                SiemFormat::Cef,
+                now - chrono::Duration::minutes(1),
+                now + chrono::Duration::minutes(1),
+            )
+            .unwrap();
+
+        let header: Vec<&str> = cef.splitn(8, '|').collect();
+        assert_eq!(header[0], "CEF:0");
+        assert_eq!(header[4], "security_violation");
+        assert_eq!(header[6], "8");
+        assert!(header[7].contains("act=sql_injection"));
+        assert!(header[7].contains("suser=user-42"));
+        assert!(header[7].contains("src=10.0.0.7"));
+        assert!(header[7].contains("outcome=failure"));
+        assert!(header[7].contains("msg=payload\\=' OR 1\\=1"));
+    }
+
+    /// Records everything persisted before eviction
+    #[derive(Default)]
+    struct MemorySink {
+        persisted: std::sync::Mutex<Vec<AuditLogEntry>>,
+    }
+
+    #[async_trait::async_trait]
+    impl AuditLogSink for MemorySink {
+        async fn persist(&self, entries: &[AuditLogEntry]) -> Result<(), AstorError> {
+            self.persisted.lock().unwrap().extend_from_slice(entries);
+            Ok(())
+        }
+    }
+
+    fn info_event(n: usize) -> SecurityEvent {
+        SecurityEvent::SystemEvent {
+            event_type: "heartbeat".to_string(),
+            details: format!("tick {}", n),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_info_flood_does_not_evict_critical_event() {
+        let mut logger = SecurityAuditLogger::new().with_retention(AuditRetentionConfig {
+            info_capacity: 50,
+            warning_capacity: 50,
+            error_capacity: 10,
+            critical_capacity: 10,
+        });
+        let sink = Arc::new(MemorySink::default());
+        logger.set_sink(sink.clone());
+
+        logger
+            .log_security_event(SecurityEvent::HighRiskOperation {
+                user_id: "user-7".to_string(),
+                operation: "bulk_withdrawal".to_string(),
+                risk_score: 0.95,
+                ip_address: "10.0.0.9".to_string(),
+            })
+            .await
+            .unwrap();
+
+        for n in 0..1_000 {
+            logger.log_security_event(info_event(n)).await.unwrap();
+        }
+
+        assert_eq!(logger.retained_count(&AuditSeverity::Info), 50);
+        assert_eq!(logger.retained_count(&AuditSeverity::Critical), 1);
+        let critical = logger.get_logs(Some(AuditSeverity::Critical), None);
+        assert_eq!(critical.len(), 1);
+        assert!(matches!(
+            critical[0].event,
+            SecurityEvent::HighRiskOperation { .. }
+        ));
+
+        // Evicted info events were persisted before being dropped
+        let persisted = sink.persisted.lock().unwrap();
+        assert_eq!(persisted.len(), 950);
+        assert!(persisted
+            .iter()
+            .all(|entry| entry.severity == AuditSeverity::Info));
+    }
+
+    #[tokio::test]
+    async fn test_error_events_kept_when_sink_unavailable() {
+        struct FailingSink;
+
+        #[async_trait::async_trait]
+        impl AuditLogSink for FailingSink {
+            async fn persist(&self, _entries: &[AuditLogEntry]) -> Result<(), AstorError> {
+                Err(AstorError::DatabaseError("unavailable".to_string()))
+            }
+        }
+
+        let mut logger = SecurityAuditLogger::new().with_retention(AuditRetentionConfig {
+            info_capacity: 2,
+            warning_capacity: 2,
+            error_capacity: 2,
+            critical_capacity: 2,
+        });
+        logger.set_sink(Arc::new(FailingSink));
+
+        for n in 0..3 {
+            logger
+                .log_security_event(SecurityEvent::SecurityViolation {
+                    user_id: None,
+                    violation_type: "tampering".to_string(),
+                    details: format!("attempt {}", n),
+                    ip_address: "10.0.0.1".to_string(),
+                    timestamp: Utc::now(),
+                })
+                .await
+                .unwrap();
+            logger.log_security_event(info_event(n)).await.unwrap();
+        }
+
+        assert_eq!(logger.retained_count(&AuditSeverity::Error), 3);
+        assert_eq!(logger.retained_count(&AuditSeverity::Info), 2);
+    }
+}
@@ -62,6 +62,22 @@ impl KeyPair {
         })
     }
 
+    /// Read a base64-encoded secret key from `path`
+    pub fn from_file(path: &str) -> Result<Self, AstorError> {
+        let encoded = std::fs::read_to_string(path).map_err(|e| {
+            AstorError::ConfigurationError(format!("Could not read signing key {}: {}", path, e))
+        })?;
+        let secret = general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|_| {
+                AstorError::ConfigurationError(format!(
+                    "Signing key {} must be base64 encoded",
+                    path
+                ))
+            })?;
+        Self::from_bytes(&secret)
+    }
+
     /// Get the public key
     pub fn public_key(&self) -> PublicKey {
         self.keypair.public
@@ -116,6 +132,30 @@ impl Signature {
             .map_err(|_| AstorError::InvalidSignature)
     }
 
+    /// Verify signature without the freshness window
+    ///
+    /// Intended for long-lived signed artifacts (reports, exports) where the
+    /// signature is expected to be checked well after it was produced.
+    pub fn verify_ignoring_age(
+        &self,
+        public_key: &PublicKey,
+        message: &[u8],
+    ) -> Result<(), AstorError> {
+        public_key
+            .verify(message, &self.signature)
+            .map_err(|_| AstorError::InvalidSignature)
+    }
+
+    /// Get the ID of the key that produced this signature
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Get raw signature bytes
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.signature.to_bytes()
+    }
+
     /// Get signature as base64
     pub fn to_base64(&self) -> String {
         general_purpose::STANDARD.encode(self.signature.to_bytes())
@@ -127,7 +167,12 @@ impl Signature {
             .decode(data)
             .map_err(|_| AstorError::CryptographicError("Invalid base64".to_string()))?;
 
-        let signature = ed25519_dalek::Signature::from_bytes(&bytes)
+        Self::from_bytes(&bytes, key_id)
+    }
+
+    /// Create from raw signature bytes
+    pub fn from_bytes(bytes: &[u8], key_id: String) -> Result<Self, AstorError> {
+        let signature = ed25519_dalek::Signature::from_bytes(bytes)
             .map_err(|_| AstorError::CryptographicError("Invalid signature bytes".to_string()))?;
 
         Ok(Self {
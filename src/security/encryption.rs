@@ -1,7 +1,7 @@
 //! Data encryption and decryption utilities
 
 use aes_gcm::{
-    aead::{Aead, AeadCore, KeyInit, OsRng},
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
     Aes256Gcm, Key, Nonce,
 };
 use base64::{engine::general_purpose, Engine as _};
@@ -9,9 +9,13 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use crate::errors::AstorError;
+use crate::monitoring::compliance::ComplianceEvent;
 
 /// Encrypted data container
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +25,10 @@ pub struct EncryptedData {
     pub key_id: String,    // Key identifier used for encryption
     pub algorithm: String, // Encryption algorithm used
     pub created_at: DateTime<Utc>,
+    /// Classification of sensitive data, e.g. `kyc.document_number`;
+    /// `decrypt_sensitive` audits every decryption of it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
 }
 
 impl EncryptedData {
@@ -37,6 +45,7 @@ impl EncryptedData {
             key_id,
             algorithm,
             created_at: Utc::now(),
+            data_type: None,
         }
     }
 
@@ -81,17 +90,34 @@ impl EncryptionKey {
         }
     }
 
-    fn should_rotate(&self) -> bool {
-        let rotation_period = chrono::Duration::days(90);
+    fn should_rotate(&self, rotation_period: chrono::Duration) -> bool {
         Utc::now() - self.created_at > rotation_period
     }
 }
 
+/// Who is decrypting sensitive data, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataAccessRequest {
+    pub accessor: String,
+    pub purpose: String,
+}
+
+/// Receives a `ComplianceEvent::DataAccess` for each audited decryption
+#[async_trait::async_trait]
+pub trait DataAccessAuditor: Send + Sync {
+    async fn record_data_access(&self, event: ComplianceEvent);
+}
+
+/// Default lifetime of an encryption key before it is rotated
+pub const DEFAULT_KEY_ROTATION_DAYS: i64 = 90;
+
 /// Encryption manager for handling data encryption/decryption
 pub struct EncryptionManager {
     keys: HashMap<String, EncryptionKey>,
     active_key_id: String,
     master_key: Vec<u8>,
+    rotation_period: chrono::Duration,
+    auditor: Option<Arc<dyn DataAccessAuditor>>,
 }
 
 impl EncryptionManager {
@@ -113,11 +139,41 @@ impl EncryptionManager {
             keys,
             active_key_id,
             master_key,
+            rotation_period: chrono::Duration::days(DEFAULT_KEY_ROTATION_DAYS),
+            auditor: None,
         })
     }
 
+    /// How long a key stays active before `rotate_keys` replaces it
+    pub fn set_rotation_period(&mut self, rotation_period: chrono::Duration) {
+        self.rotation_period = rotation_period;
+    }
+
+    /// Audit every decryption of sensitive data through `auditor`
+    pub fn set_data_access_auditor(&mut self, auditor: Arc<dyn DataAccessAuditor>) {
+        self.auditor = Some(auditor);
+    }
+
+    /// Identifier of the key new data is encrypted with
+    pub fn active_key_id(&self) -> &str {
+        &self.active_key_id
+    }
+
     /// Encrypt data using active key
     pub fn encrypt(&self, plaintext: &[u8]) -> Result<EncryptedData, AstorError> {
+        self.encrypt_with_context(plaintext, &[])
+    }
+
+    /// Encrypt data bound to `context`, e.g. the ID of the record it belongs to
+    ///
+    /// The context is authenticated but not stored: decryption must supply
+    /// the same bytes, so ciphertext moved to another record fails to
+    /// decrypt. An empty context is equivalent to `encrypt`.
+    pub fn encrypt_with_context(
+        &self,
+        plaintext: &[u8],
+        context: &[u8],
+    ) -> Result<EncryptedData, AstorError> {
         let active_key =
             self.keys
                 .get(&self.active_key_id)
@@ -126,7 +182,7 @@ impl EncryptionManager {
                 ))?;
 
         match active_key.algorithm.as_str() {
-            "AES-256-GCM" => self.encrypt_aes_gcm(plaintext, active_key),
+            "AES-256-GCM" => self.encrypt_aes_gcm(plaintext, context, active_key),
             _ => Err(AstorError::CryptographicError(
                 "Unsupported algorithm".to_string(),
             )),
@@ -135,6 +191,70 @@ impl EncryptionManager {
 
     /// Decrypt data using specified key
     pub fn decrypt(&self, encrypted_data: &EncryptedData) -> Result<Vec<u8>, AstorError> {
+        self.decrypt_with_context(encrypted_data, &[])
+    }
+
+    /// Decrypt data encrypted with `encrypt_with_context`
+    ///
+    /// Fails unless `context` matches the one used at encryption. Sensitive
+    /// data decrypted this way is not audited; use `decrypt_sensitive`.
+    pub fn decrypt_with_context(
+        &self,
+        encrypted_data: &EncryptedData,
+        context: &[u8],
+    ) -> Result<Vec<u8>, AstorError> {
+        if let (Some(data_type), Some(_)) = (&encrypted_data.data_type, &self.auditor) {
+            tracing::warn!("Unaudited decryption of sensitive {} data", data_type);
+        }
+        self.decrypt_unaudited(encrypted_data, context)
+    }
+
+    /// Encrypt sensitive data of class `data_type`, bound to `context`
+    pub fn encrypt_sensitive(
+        &self,
+        plaintext: &str,
+        data_type: &str,
+        context: &str,
+    ) -> Result<EncryptedData, AstorError> {
+        let mut encrypted = self.encrypt_string_with_context(plaintext, context)?;
+        encrypted.data_type = Some(data_type.to_string());
+        Ok(encrypted)
+    }
+
+    /// Decrypt sensitive data, recording who accessed it and why
+    ///
+    /// With an auditor configured, each successful decryption of classified
+    /// data emits a `ComplianceEvent::DataAccess`.
+    pub async fn decrypt_sensitive(
+        &self,
+        encrypted_data: &EncryptedData,
+        context: &str,
+        access: &DataAccessRequest,
+    ) -> Result<String, AstorError> {
+        let plaintext =
+            String::from_utf8(self.decrypt_unaudited(encrypted_data, context.as_bytes())?)
+                .map_err(|e| {
+                    AstorError::CryptographicError(format!("UTF-8 decode error: {}", e))
+                })?;
+
+        if let (Some(data_type), Some(auditor)) = (&encrypted_data.data_type, &self.auditor) {
+            auditor
+                .record_data_access(ComplianceEvent::DataAccess {
+                    user_id: access.accessor.clone(),
+                    data_type: data_type.clone(),
+                    purpose: access.purpose.clone(),
+                    timestamp: Utc::now(),
+                })
+                .await;
+        }
+        Ok(plaintext)
+    }
+
+    fn decrypt_unaudited(
+        &self,
+        encrypted_data: &EncryptedData,
+        context: &[u8],
+    ) -> Result<Vec<u8>, AstorError> {
         let key = self
             .keys
             .get(&encrypted_data.key_id)
@@ -143,13 +263,42 @@ impl EncryptionManager {
             ))?;
 
         match encrypted_data.algorithm.as_str() {
-            "AES-256-GCM" => self.decrypt_aes_gcm(encrypted_data, key),
+            "AES-256-GCM" => self.decrypt_aes_gcm(encrypted_data, context, key),
             _ => Err(AstorError::CryptographicError(
                 "Unsupported algorithm".to_string(),
             )),
         }
     }
 
+    /// Decrypt data, re-encrypting it under the active key if it was
+    /// encrypted with an older one
+    ///
+    /// The re-encrypted container is returned so the caller can store it in
+    /// place of the original, migrating data to the active key as it is read.
+    pub fn decrypt_and_migrate(
+        &self,
+        encrypted_data: &EncryptedData,
+    ) -> Result<(Vec<u8>, Option<EncryptedData>), AstorError> {
+        self.decrypt_and_migrate_with_context(encrypted_data, &[])
+    }
+
+    /// `decrypt_and_migrate` for data bound to `context`; the migrated
+    /// container stays bound to the same context
+    pub fn decrypt_and_migrate_with_context(
+        &self,
+        encrypted_data: &EncryptedData,
+        context: &[u8],
+    ) -> Result<(Vec<u8>, Option<EncryptedData>), AstorError> {
+        let plaintext = self.decrypt_with_context(encrypted_data, context)?;
+        if encrypted_data.key_id == self.active_key_id {
+            return Ok((plaintext, None));
+        }
+
+        let mut migrated = self.encrypt_with_context(&plaintext, context)?;
+        migrated.data_type = encrypted_data.data_type.clone();
+        Ok((plaintext, Some(migrated)))
+    }
+
     /// Encrypt string data
     pub fn encrypt_string(&self, plaintext: &str) -> Result<EncryptedData, AstorError> {
         self.encrypt(plaintext.as_bytes())
@@ -157,19 +306,76 @@ impl EncryptionManager {
 
     /// Decrypt to string
     pub fn decrypt_string(&self, encrypted_data: &EncryptedData) -> Result<String, AstorError> {
-        let decrypted_bytes = self.decrypt(encrypted_data)?;
+        self.decrypt_string_with_context(encrypted_data, "")
+    }
+
+    /// Encrypt a field bound to its record, e.g. a KYC customer ID
+    pub fn encrypt_string_with_context(
+        &self,
+        plaintext: &str,
+        context: &str,
+    ) -> Result<EncryptedData, AstorError> {
+        self.encrypt_with_context(plaintext.as_bytes(), context.as_bytes())
+    }
+
+    /// Decrypt a field to string, checking it belongs to `context`
+    pub fn decrypt_string_with_context(
+        &self,
+        encrypted_data: &EncryptedData,
+        context: &str,
+    ) -> Result<String, AstorError> {
+        let decrypted_bytes = self.decrypt_with_context(encrypted_data, context.as_bytes())?;
         String::from_utf8(decrypted_bytes)
             .map_err(|e| AstorError::CryptographicError(format!("UTF-8 decode error: {}", e)))
     }
 
-    /// Rotate encryption keys
-    pub fn rotate_keys(&mut self) -> Result<(), AstorError> {
+    /// Rotate encryption keys if the active key is older than the rotation
+    /// period, returning whether a rotation happened
+    pub fn rotate_keys(&mut self) -> Result<bool, AstorError> {
         // Check if current key needs rotation
         let current_key = self.keys.get(&self.active_key_id).unwrap();
-        if !current_key.should_rotate() {
-            return Ok(());
+        if !current_key.should_rotate(self.rotation_period) {
+            return Ok(false);
         }
 
+        self.install_new_key();
+        Ok(true)
+    }
+
+    /// Rotate immediately regardless of key age, e.g. after a suspected key
+    /// compromise, returning the new active key ID
+    ///
+    /// Older keys remain available for decryption.
+    pub fn force_rotate(&mut self) -> Result<String, AstorError> {
+        let previous_key_id = self.active_key_id.clone();
+        self.install_new_key();
+        tracing::warn!(
+            "Forced encryption key rotation: {} replaced by {}",
+            previous_key_id,
+            self.active_key_id
+        );
+        Ok(self.active_key_id.clone())
+    }
+
+    /// Rotate on a fixed schedule until the returned task is aborted
+    pub fn spawn_rotation_task(
+        manager: Arc<RwLock<EncryptionManager>>,
+        check_interval: std::time::Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                match manager.write().await.rotate_keys() {
+                    Ok(true) => tracing::info!("Encryption key rotated on schedule"),
+                    Ok(false) => {}
+                    Err(e) => tracing::error!("Scheduled key rotation failed: {}", e),
+                }
+            }
+        })
+    }
+
+    fn install_new_key(&mut self) {
         // Generate new key
         let new_key = EncryptionKey::new("AES-256-GCM".to_string());
         let new_key_id = new_key.id.clone();
@@ -182,15 +388,17 @@ impl EncryptionManager {
         // Add new key and set as active
         self.keys.insert(new_key_id.clone(), new_key);
         self.active_key_id = new_key_id;
-
-        Ok(())
     }
 
     /// Get encryption statistics
     pub fn get_encryption_stats(&self) -> EncryptionStats {
         let total_keys = self.keys.len();
         let active_keys = self.keys.values().filter(|k| k.is_active).count();
-        let keys_needing_rotation = self.keys.values().filter(|k| k.should_rotate()).count();
+        let keys_needing_rotation = self
+            .keys
+            .values()
+            .filter(|k| k.should_rotate(self.rotation_period))
+            .count();
 
         EncryptionStats {
             total_keys,
@@ -219,6 +427,7 @@ impl EncryptionManager {
     fn encrypt_aes_gcm(
         &self,
         plaintext: &[u8],
+        aad: &[u8],
         key: &EncryptionKey,
     ) -> Result<EncryptedData, AstorError> {
         let cipher_key = Key::<Aes256Gcm>::from_slice(&key.key);
@@ -226,7 +435,13 @@ impl EncryptionManager {
         let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
 
         let ciphertext = cipher
-            .encrypt(&nonce, plaintext)
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
             .map_err(|e| AstorError::CryptographicError(format!("AES encryption error: {}", e)))?;
 
         Ok(EncryptedData::new(
@@ -241,6 +456,7 @@ impl EncryptionManager {
     fn decrypt_aes_gcm(
         &self,
         encrypted_data: &EncryptedData,
+        aad: &[u8],
         key: &EncryptionKey,
     ) -> Result<Vec<u8>, AstorError> {
         let cipher_key = Key::<Aes256Gcm>::from_slice(&key.key);
@@ -251,7 +467,13 @@ impl EncryptionManager {
         let nonce = Nonce::from_slice(&nonce_bytes);
 
         cipher
-            .decrypt(nonce, ciphertext.as_ref())
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: &ciphertext,
+                    aad,
+                },
+            )
             .map_err(|e| AstorError::CryptographicError(format!("AES decryption error: {}", e)))
     }
 }
@@ -307,12 +529,41 @@ impl EncryptionManager {
 
     /// Encrypt API keys and secrets
     pub fn encrypt_secret(&self, secret: &str) -> Result<EncryptedData, AstorError> {
-        self.encrypt_string(secret)
+        self.encrypt_sensitive(secret, "secret", "")
     }
 
-    /// Decrypt API keys and secrets
-    pub fn decrypt_secret(&self, encrypted_data: &EncryptedData) -> Result<String, AstorError> {
-        self.decrypt_string(encrypted_data)
+    /// Decrypt API keys and secrets; the access is audited
+    pub async fn decrypt_secret(
+        &self,
+        encrypted_data: &EncryptedData,
+        access: &DataAccessRequest,
+    ) -> Result<String, AstorError> {
+        self.decrypt_sensitive(encrypted_data, "", access).await
+    }
+
+    /// Encrypt a customer's KYC field, bound to that customer
+    pub fn encrypt_kyc_field(
+        &self,
+        customer_id: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<EncryptedData, AstorError> {
+        self.encrypt_sensitive(
+            value,
+            &format!("kyc.{}", field),
+            &format!("kyc:{}", customer_id),
+        )
+    }
+
+    /// Decrypt a customer's KYC field; the access is audited
+    pub async fn decrypt_kyc_field(
+        &self,
+        encrypted_data: &EncryptedData,
+        customer_id: &str,
+        access: &DataAccessRequest,
+    ) -> Result<String, AstorError> {
+        self.decrypt_sensitive(encrypted_data, &format!("kyc:{}", customer_id), access)
+            .await
     }
 }
 
@@ -345,6 +596,45 @@ mod tests {
         assert_ne!(original_key_id, manager.active_key_id);
     }
 
+    #[test]
+    fn test_force_rotate_changes_active_key_immediately() {
+        let mut manager = EncryptionManager::new("test_master_key").unwrap();
+        let original_key_id = manager.active_key_id().to_string();
+        let old_data = manager.encrypt_string("account 42").unwrap();
+
+        let new_key_id = manager.force_rotate().unwrap();
+        assert_ne!(new_key_id, original_key_id);
+        assert_eq!(manager.active_key_id(), new_key_id);
+        assert_eq!(manager.encrypt_string("x").unwrap().key_id, new_key_id);
+
+        // Data under the old key still decrypts and is migrated on read
+        let (plaintext, migrated) = manager.decrypt_and_migrate(&old_data).unwrap();
+        assert_eq!(plaintext, b"account 42");
+        let migrated = migrated.unwrap();
+        assert_eq!(migrated.key_id, new_key_id);
+        assert_eq!(manager.decrypt_string(&migrated).unwrap(), "account 42");
+
+        let (_, unchanged) = manager.decrypt_and_migrate(&migrated).unwrap();
+        assert!(unchanged.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rotation_task_rotates_expired_key() {
+        let mut manager = EncryptionManager::new("test_master_key").unwrap();
+        manager.set_rotation_period(chrono::Duration::zero());
+        let original_key_id = manager.active_key_id().to_string();
+        let manager = Arc::new(RwLock::new(manager));
+
+        let task = EncryptionManager::spawn_rotation_task(
+            manager.clone(),
+            std::time::Duration::from_millis(10),
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        task.abort();
+
+        assert_ne!(manager.read().await.active_key_id(), original_key_id);
+    }
+
     #[test]
     fn test_config_encryption() {
         let manager = EncryptionManager::new("test_master_key").unwrap();
@@ -358,4 +648,82 @@ mod tests {
 
         assert_eq!(config, decrypted);
     }
+
+    #[test]
+    fn test_decrypting_with_mismatched_context_fails() {
+        let manager = EncryptionManager::new("test_master_key").unwrap();
+        let encrypted = manager
+            .encrypt_string_with_context("P1234567", "kyc:customer-1")
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .decrypt_string_with_context(&encrypted, "kyc:customer-1")
+                .unwrap(),
+            "P1234567"
+        );
+        // Swapped onto another customer's record, or read without context
+        assert!(manager
+            .decrypt_string_with_context(&encrypted, "kyc:customer-2")
+            .is_err());
+        assert!(manager.decrypt_string(&encrypted).is_err());
+
+        // Unbound data is unaffected
+        let unbound = manager.encrypt_string("P1234567").unwrap();
+        assert!(manager
+            .decrypt_string_with_context(&unbound, "kyc:customer-1")
+            .is_err());
+        assert_eq!(manager.decrypt_string(&unbound).unwrap(), "P1234567");
+    }
+
+    #[tokio::test]
+    async fn test_kyc_field_decrypt_emits_data_access_event() {
+        use crate::monitoring::compliance::{ComplianceMonitor, ComplianceReportType};
+
+        let monitor = Arc::new(ComplianceMonitor::new());
+        let mut manager = EncryptionManager::new("test_master_key").unwrap();
+        manager.set_data_access_auditor(monitor.clone());
+        let start = Utc::now();
+
+        let document_number = manager
+            .encrypt_kyc_field("customer-1", "document_number", "P1234567")
+            .unwrap();
+        let access = DataAccessRequest {
+            accessor: "analyst-7".to_string(),
+            purpose: "Periodic KYC review".to_string(),
+        };
+        assert_eq!(
+            manager
+                .decrypt_kyc_field(&document_number, "customer-1", &access)
+                .await
+                .unwrap(),
+            "P1234567"
+        );
+        // Failed and unclassified decryptions are not data accesses
+        assert!(manager
+            .decrypt_kyc_field(&document_number, "customer-2", &access)
+            .await
+            .is_err());
+        let note = manager.encrypt_string("not sensitive").unwrap();
+        manager.decrypt_sensitive(&note, "", &access).await.unwrap();
+
+        let report = monitor
+            .generate_report(ComplianceReportType::GDPR, start, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(report.summary.data_access_events, 1);
+        match &report.events[0] {
+            ComplianceEvent::DataAccess {
+                user_id,
+                data_type,
+                purpose,
+                ..
+            } => {
+                assert_eq!(user_id, "analyst-7");
+                assert_eq!(data_type, "kyc.document_number");
+                assert_eq!(purpose, "Periodic KYC review");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
 }
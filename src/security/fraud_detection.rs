@@ -1,6 +1,6 @@
 //! Fraud detection and risk assessment
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -28,6 +28,10 @@ impl RiskScore {
         self.score
     }
 
+    pub fn factors(&self) -> &[RiskFactor] {
+        &self.factors
+    }
+
     pub fn is_high_risk(&self) -> bool {
         self.score > 0.7
     }
@@ -41,6 +45,29 @@ impl RiskScore {
     }
 }
 
+/// Freeze reason recorded when fraud scoring freezes an account
+pub const FRAUD_AUTO_FREEZE_REASON: &str = "fraud-auto-freeze";
+
+/// Acting admin id recorded when fraud scoring freezes an account
+pub const FRAUD_AUTO_FREEZE_ADMIN_ID: &str = "fraud-detection";
+
+/// Automatic account freeze for operations scoring above a critical threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoFreezePolicy {
+    pub enabled: bool,
+    /// Risk score (0.0 to 1.0) at or above which the account is frozen
+    pub threshold: f64,
+}
+
+impl Default for AutoFreezePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.9,
+        }
+    }
+}
+
 /// Risk factors that contribute to overall risk score
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RiskFactor {
@@ -89,6 +116,8 @@ pub struct FraudDetector {
     transaction_history: HashMap<String, Vec<TransactionPattern>>,
     ip_reputation: HashMap<String, f64>,
     user_profiles: HashMap<String, UserProfile>,
+    auto_freeze: AutoFreezePolicy,
+    anomaly_detector: AnomalyDetector,
 }
 
 #[derive(Debug, Clone)]
@@ -106,16 +135,41 @@ impl FraudDetector {
             transaction_history: HashMap::new(),
             ip_reputation: HashMap::new(),
             user_profiles: HashMap::new(),
+            auto_freeze: AutoFreezePolicy::default(),
+            anomaly_detector: AnomalyDetector::new(),
         }
     }
 
-    /// Assess risk for a transaction
-    pub async fn assess_risk(
-        &mut self,
-        user_id: &str,
-        operation: &str,
-        ip_address: &str,
-    ) -> Result<RiskScore, AstorError> {
+    /// Replace the model scoring transactions against each user's history
+    pub fn set_anomaly_detector(&mut self, detector: AnomalyDetector) {
+        self.anomaly_detector = detector;
+    }
+
+    pub fn anomaly_detector(&self) -> &AnomalyDetector {
+        &self.anomaly_detector
+    }
+
+    /// Configure automatic account freezing
+    pub fn set_auto_freeze_policy(&mut self, policy: AutoFreezePolicy) {
+        self.auto_freeze = policy;
+    }
+
+    pub fn auto_freeze_policy(&self) -> &AutoFreezePolicy {
+        &self.auto_freeze
+    }
+
+    /// Check whether a risk score should freeze the account
+    pub fn should_auto_freeze(&self, risk_score: &RiskScore) -> bool {
+        self.auto_freeze.enabled && risk_score.score() >= self.auto_freeze.threshold
+    }
+
+    /// Assess the risk of an operation against the user's recorded history
+    ///
+    /// The operation itself is scored, so it should be passed to
+    /// `record_transaction` only once it has gone through.
+    pub fn assess_risk(&mut self, operation: &TransactionPattern) -> Result<RiskScore, AstorError> {
+        let user_id = operation.user_id.as_str();
+        let ip_address = operation.ip_address.as_str();
         let mut risk_factors = Vec::new();
         let mut total_risk = 0.0;
 
@@ -154,13 +208,29 @@ impl FraudDetector {
                 risk_factors.push(RiskFactor::UnusualTimeOfDay { hour: current_hour });
                 total_risk += 0.1;
             }
+
+            // Score the operation against the user's past transactions
+            if let Some(history) = self.transaction_history.get(user_id) {
+                let features = self.anomaly_detector.extract_features(operation, history);
+                if features.amount_deviation >= 0.5 {
+                    let amounts = history.iter().map(|t| t.amount);
+                    risk_factors.push(RiskFactor::UnusualTransactionAmount {
+                        amount: operation.amount,
+                        typical_range: (
+                            amounts.clone().min().unwrap_or(0),
+                            amounts.max().unwrap_or(0),
+                        ),
+                    });
+                }
+                total_risk += self.anomaly_detector.score(&features) * 0.4;
+            }
         } else {
             // New user - higher risk
             total_risk += 0.3;
         }
 
         // Check for suspicious patterns
-        if self.detect_suspicious_patterns(user_id, operation).await? {
+        if self.detect_suspicious_patterns(user_id, &operation.transaction_type)? {
             risk_factors.push(RiskFactor::SuspiciousPattern {
                 pattern: "Rapid sequential transactions".to_string(),
             });
@@ -231,7 +301,7 @@ impl FraudDetector {
     }
 
     /// Detect suspicious patterns
-    async fn detect_suspicious_patterns(
+    fn detect_suspicious_patterns(
         &self,
         user_id: &str,
         operation: &str,
@@ -270,24 +340,197 @@ impl FraudDetector {
     }
 }
 
-/// Machine learning-based anomaly detection (placeholder for production ML models)
+/// How far a transaction departs from a user's history, each scaled to 0.0..=1.0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransactionFeatures {
+    /// Absolute z-score of the amount against past amounts
+    pub amount_deviation: f64,
+    /// Hours from the nearest hour of day the user has transacted at
+    pub time_of_day_deviation: f64,
+    /// Transactions in the velocity window before this one
+    pub velocity: f64,
+    /// 1.0 if the user has never transacted from this IP
+    pub ip_novelty: f64,
+}
+
+/// Turns a transaction and the user's history into `TransactionFeatures`
+///
+/// With no history there is no baseline, so every feature is 0.0; new users
+/// are scored separately by `FraudDetector`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureExtractor {
+    /// Amount z-score treated as maximally anomalous
+    pub max_zscore: f64,
+    pub velocity_window_secs: i64,
+    /// Transactions within the window treated as maximally anomalous
+    pub max_velocity: u32,
+}
+
+impl Default for FeatureExtractor {
+    fn default() -> Self {
+        Self {
+            max_zscore: 4.0,
+            velocity_window_secs: 3600,
+            max_velocity: 10,
+        }
+    }
+}
+
+impl FeatureExtractor {
+    pub fn extract(
+        &self,
+        transaction: &TransactionPattern,
+        history: &[TransactionPattern],
+    ) -> TransactionFeatures {
+        if history.is_empty() {
+            return TransactionFeatures::default();
+        }
+
+        let count = history.len() as f64;
+        let mean = history.iter().map(|t| t.amount as f64).sum::<f64>() / count;
+        let variance = history
+            .iter()
+            .map(|t| (t.amount as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        let deviation = (transaction.amount as f64 - mean).abs();
+        let zscore = match variance.sqrt() {
+            std_dev if std_dev > 0.0 => deviation / std_dev,
+            // Every past amount was identical; any change is maximal
+            _ if deviation > 0.0 => self.max_zscore,
+            _ => 0.0,
+        };
+
+        let hour = transaction.timestamp.hour();
+        let nearest_hour = history
+            .iter()
+            .map(|t| {
+                let diff = hour.abs_diff(t.timestamp.hour());
+                diff.min(24 - diff)
+            })
+            .min()
+            .unwrap_or(0);
+
+        let window_start = transaction.timestamp - Duration::seconds(self.velocity_window_secs);
+        let recent = history
+            .iter()
+            .filter(|t| t.timestamp > window_start && t.timestamp <= transaction.timestamp)
+            .count();
+
+        // Transactions recorded without an IP say nothing about its novelty
+        let mut known_ips = history
+            .iter()
+            .map(|t| t.ip_address.as_str())
+            .filter(|ip| !ip.is_empty())
+            .peekable();
+        let known_ip = transaction.ip_address.is_empty()
+            || known_ips.peek().is_none()
+            || known_ips.any(|ip| ip == transaction.ip_address);
+
+        TransactionFeatures {
+            amount_deviation: (zscore / self.max_zscore).min(1.0),
+            time_of_day_deviation: nearest_hour as f64 / 12.0,
+            velocity: (recent as f64 / self.max_velocity.max(1) as f64).min(1.0),
+            ip_novelty: if known_ip { 0.0 } else { 1.0 },
+        }
+    }
+}
+
+/// Relative weight of each feature in the anomaly score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyWeights {
+    pub amount: f64,
+    pub time_of_day: f64,
+    pub velocity: f64,
+    pub ip_novelty: f64,
+}
+
+impl Default for AnomalyWeights {
+    fn default() -> Self {
+        Self {
+            amount: 0.4,
+            time_of_day: 0.15,
+            velocity: 0.25,
+            ip_novelty: 0.2,
+        }
+    }
+}
+
+/// Feature extraction and weights of the baseline anomaly model
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FraudModelConfig {
+    pub features: FeatureExtractor,
+    pub weights: AnomalyWeights,
+}
+
+/// Baseline statistical anomaly model
+///
+/// Scores a transaction as the weighted average of its features, from 0.0
+/// (typical for the user) to 1.0. A trained model can replace it later
+/// through `FraudDetector::set_anomaly_detector`.
 pub struct AnomalyDetector {
-    // In production, this would contain trained ML models
+    extractor: FeatureExtractor,
+    weights: AnomalyWeights,
     baseline_metrics: HashMap<String, f64>,
 }
 
 impl AnomalyDetector {
     pub fn new() -> Self {
+        Self::with_config(FeatureExtractor::default(), AnomalyWeights::default())
+    }
+
+    pub fn from_config(config: &FraudModelConfig) -> Self {
+        Self::with_config(config.features.clone(), config.weights.clone())
+    }
+
+    pub fn with_config(extractor: FeatureExtractor, weights: AnomalyWeights) -> Self {
         Self {
+            extractor,
+            weights,
             baseline_metrics: HashMap::new(),
         }
     }
 
-    /// Detect anomalies in transaction patterns
-    pub fn detect_anomaly(&self, _transaction: &TransactionPattern) -> f64 {
-        // Placeholder - in production this would use trained models
-        // to detect statistical anomalies in transaction patterns
-        0.0
+    pub fn set_weights(&mut self, weights: AnomalyWeights) {
+        self.weights = weights;
+    }
+
+    pub fn weights(&self) -> &AnomalyWeights {
+        &self.weights
+    }
+
+    pub fn extract_features(
+        &self,
+        transaction: &TransactionPattern,
+        history: &[TransactionPattern],
+    ) -> TransactionFeatures {
+        self.extractor.extract(transaction, history)
+    }
+
+    /// Combine features into a score from 0.0 to 1.0
+    pub fn score(&self, features: &TransactionFeatures) -> f64 {
+        let weights = &self.weights;
+        let total_weight =
+            weights.amount + weights.time_of_day + weights.velocity + weights.ip_novelty;
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+
+        let weighted = weights.amount * features.amount_deviation
+            + weights.time_of_day * features.time_of_day_deviation
+            + weights.velocity * features.velocity
+            + weights.ip_novelty * features.ip_novelty;
+        (weighted / total_weight).clamp(0.0, 1.0)
+    }
+
+    /// Anomaly score of `transaction` against the user's earlier `history`
+    pub fn detect_anomaly(
+        &self,
+        transaction: &TransactionPattern,
+        history: &[TransactionPattern],
+    ) -> f64 {
+        self.score(&self.extract_features(transaction, history))
     }
 
     /// Update baseline metrics
@@ -295,3 +538,89 @@ impl AnomalyDetector {
         self.baseline_metrics.insert(metric_name.to_string(), value);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn pattern(amount: i64, timestamp: DateTime<Utc>, ip_address: &str) -> TransactionPattern {
+        TransactionPattern {
+            user_id: "alice".to_string(),
+            amount,
+            timestamp,
+            ip_address: ip_address.to_string(),
+            user_agent: "test-agent".to_string(),
+            transaction_type: "transfer".to_string(),
+        }
+    }
+
+    /// Twenty mid-morning transfers of 100-119 from one IP, a day apart
+    fn history() -> Vec<TransactionPattern> {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+        (0..20)
+            .map(|day| pattern(100 + day, start + Duration::days(day), "198.51.100.1"))
+            .collect()
+    }
+
+    #[test]
+    fn test_anomalous_pattern_scores_higher_than_typical() {
+        let history = history();
+        let detector = AnomalyDetector::new();
+        let typical = pattern(
+            110,
+            Utc.with_ymd_and_hms(2024, 3, 21, 10, 30, 0).unwrap(),
+            "198.51.100.1",
+        );
+        let anomalous = pattern(
+            50_000,
+            Utc.with_ymd_and_hms(2024, 3, 21, 22, 0, 0).unwrap(),
+            "203.0.113.9",
+        );
+
+        let typical_score = detector.detect_anomaly(&typical, &history);
+        let anomalous_score = detector.detect_anomaly(&anomalous, &history);
+        assert!(typical_score < 0.1, "typical scored {}", typical_score);
+        assert!(
+            anomalous_score > 0.5,
+            "anomalous scored {}",
+            anomalous_score
+        );
+
+        // The operation being assessed is scored against the user's history
+        let mut fraud_detector = FraudDetector::new();
+        for past in &history {
+            fraud_detector.record_transaction(past.clone());
+        }
+        let typical_risk = fraud_detector
+            .assess_risk(&pattern(110, Utc::now(), "198.51.100.1"))
+            .unwrap();
+        let anomalous_risk = fraud_detector
+            .assess_risk(&pattern(50_000, Utc::now(), "198.51.100.1"))
+            .unwrap();
+        assert!(anomalous_risk.score() > typical_risk.score());
+        assert!(anomalous_risk.factors().iter().any(|factor| matches!(
+            factor,
+            RiskFactor::UnusualTransactionAmount { amount: 50_000, .. }
+        )));
+    }
+
+    #[test]
+    fn test_history_without_ips_does_not_flag_ip_novelty() {
+        let history: Vec<TransactionPattern> = history()
+            .into_iter()
+            .map(|t| TransactionPattern {
+                ip_address: String::new(),
+                ..t
+            })
+            .collect();
+        let probe = pattern(
+            110,
+            Utc.with_ymd_and_hms(2024, 3, 21, 10, 30, 0).unwrap(),
+            "198.51.100.1",
+        );
+
+        let features = FeatureExtractor::default().extract(&probe, &history);
+        assert_eq!(features.ip_novelty, 0.0);
+    }
+}
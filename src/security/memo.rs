@@ -0,0 +1,242 @@
+//! Encrypted transaction memos
+//!
+//! A sealed memo can only be read by the parties named on it. The ledger
+//! keeps the ciphertext, so entries stay auditable without exposing the memo
+//! itself. Compliance staff may decrypt any memo, but every such read is
+//! recorded as a `DataAccess` audit event.
+//!
+//! Memo ciphertext is bound to its transaction ID, so a memo copied onto
+//! another transaction fails to decrypt.
+
+use chrono::Utc;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::audit::{SecurityAuditLogger, SecurityEvent};
+use super::auth::{Permission, Role};
+use super::encryption::EncryptionManager;
+use crate::errors::AstorError;
+use crate::transactions::TransactionMemo;
+
+/// What a reader is shown for a memo
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoView {
+    Plaintext(String),
+    /// Base64 ciphertext, shown to readers not authorized for the memo
+    Ciphertext(String),
+}
+
+/// Seals and opens transaction memos with the shared encryption keys
+pub struct MemoCipher {
+    encryption: Arc<RwLock<EncryptionManager>>,
+}
+
+impl MemoCipher {
+    pub fn new(encryption: Arc<RwLock<EncryptionManager>>) -> Self {
+        Self { encryption }
+    }
+
+    /// Encrypt a memo for `transaction_id` so only `authorized_parties` can
+    /// read it
+    pub async fn seal(
+        &self,
+        transaction_id: &str,
+        plaintext: &str,
+        authorized_parties: Vec<String>,
+    ) -> Result<TransactionMemo, AstorError> {
+        if authorized_parties.is_empty() {
+            return Err(AstorError::InvalidOperation(
+                "An encrypted memo needs at least one authorized party".to_string(),
+            ));
+        }
+
+        let ciphertext = self
+            .encryption
+            .read()
+            .await
+            .encrypt_string_with_context(plaintext, &memo_context(transaction_id))?;
+        Ok(TransactionMemo::Encrypted {
+            ciphertext,
+            authorized_parties,
+        })
+    }
+
+    /// Show a memo to `reader_id`, decrypting it only for authorized parties
+    pub async fn view(
+        &self,
+        memo: &TransactionMemo,
+        transaction_id: &str,
+        reader_id: &str,
+    ) -> Result<MemoView, AstorError> {
+        match memo {
+            TransactionMemo::Plaintext(text) => Ok(MemoView::Plaintext(text.clone())),
+            TransactionMemo::Encrypted {
+                ciphertext,
+                authorized_parties,
+            } => {
+                if authorized_parties.iter().any(|party| party == reader_id) {
+                    let plaintext = self
+                        .encryption
+                        .read()
+                        .await
+                        .decrypt_string_with_context(ciphertext, &memo_context(transaction_id))?;
+                    Ok(MemoView::Plaintext(plaintext))
+                } else {
+                    Ok(MemoView::Ciphertext(ciphertext.data.clone()))
+                }
+            }
+        }
+    }
+
+    /// Decrypt any memo for a compliance review
+    ///
+    /// The officer's role must grant `ViewAuditLogs`. Both granted and
+    /// refused attempts are written to the audit log.
+    pub async fn compliance_decrypt(
+        &self,
+        memo: &TransactionMemo,
+        transaction_id: &str,
+        officer_id: &str,
+        officer_role: &Role,
+        audit_logger: &mut SecurityAuditLogger,
+    ) -> Result<String, AstorError> {
+        if !officer_role.has_permission(&Permission::ViewAuditLogs) {
+            audit_logger
+                .log_security_event(SecurityEvent::PermissionDenied {
+                    user_id: officer_id.to_string(),
+                    resource: format!("transaction_memo:{}", transaction_id),
+                    action: "compliance_decrypt".to_string(),
+                    timestamp: Utc::now(),
+                })
+                .await?;
+            return Err(AstorError::Unauthorized(format!(
+                "{:?} may not decrypt transaction memos",
+                officer_role
+            )));
+        }
+
+        audit_logger
+            .log_security_event(SecurityEvent::DataAccess {
+                user_id: officer_id.to_string(),
+                resource_type: "transaction_memo".to_string(),
+                resource_id: transaction_id.to_string(),
+                action: "compliance_decrypt".to_string(),
+                timestamp: Utc::now(),
+            })
+            .await?;
+
+        match memo {
+            TransactionMemo::Plaintext(text) => Ok(text.clone()),
+            TransactionMemo::Encrypted { ciphertext, .. } => self
+                .encryption
+                .read()
+                .await
+                .decrypt_string_with_context(ciphertext, &memo_context(transaction_id)),
+        }
+    }
+}
+
+/// Encryption context binding a memo to its transaction
+fn memo_context(transaction_id: &str) -> String {
+    format!("memo:{}", transaction_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::Ledger;
+
+    fn cipher() -> MemoCipher {
+        MemoCipher::new(Arc::new(RwLock::new(
+            EncryptionManager::new("memo-test-master-key").unwrap(),
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_memo_readable_only_by_authorized_parties() {
+        let cipher = cipher();
+        let memo = cipher
+            .seal(
+                "tx-1",
+                "Invoice 2024-117",
+                vec!["alice".to_string(), "bob".to_string()],
+            )
+            .await
+            .unwrap();
+
+        let mut ledger = Ledger::new();
+        ledger.record_memo("tx-1".to_string(), memo).unwrap();
+        let stored = ledger.memo_for("tx-1").unwrap().unwrap();
+        assert!(stored.is_encrypted());
+
+        assert_eq!(
+            cipher.view(&stored, "tx-1", "bob").await.unwrap(),
+            MemoView::Plaintext("Invoice 2024-117".to_string())
+        );
+        match cipher.view(&stored, "tx-1", "mallory").await.unwrap() {
+            MemoView::Ciphertext(data) => assert!(!data.contains("Invoice")),
+            other => panic!("unauthorized reader saw {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memo_moved_to_another_transaction_fails_to_decrypt() {
+        let cipher = cipher();
+        let memo = cipher
+            .seal("tx-1", "Invoice 2024-117", vec!["alice".to_string()])
+            .await
+            .unwrap();
+
+        assert!(cipher.view(&memo, "tx-2", "alice").await.is_err());
+        let mut audit_logger = SecurityAuditLogger::new();
+        assert!(cipher
+            .compliance_decrypt(
+                &memo,
+                "tx-2",
+                "auditor-1",
+                &Role::Auditor,
+                &mut audit_logger
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compliance_override_is_audited() {
+        let cipher = cipher();
+        let memo = cipher
+            .seal("tx-2", "Payroll ref 88", vec!["alice".to_string()])
+            .await
+            .unwrap();
+        let mut audit_logger = SecurityAuditLogger::new();
+
+        assert!(matches!(
+            cipher
+                .compliance_decrypt(&memo, "tx-2", "op-1", &Role::Operator, &mut audit_logger)
+                .await,
+            Err(AstorError::Unauthorized(_))
+        ));
+        let plaintext = cipher
+            .compliance_decrypt(
+                &memo,
+                "tx-2",
+                "auditor-1",
+                &Role::Auditor,
+                &mut audit_logger,
+            )
+            .await
+            .unwrap();
+        assert_eq!(plaintext, "Payroll ref 88");
+
+        let logs = audit_logger.get_logs(None, None);
+        assert!(logs.iter().any(|entry| matches!(
+            &entry.event,
+            SecurityEvent::DataAccess { user_id, resource_id, .. }
+                if user_id == "auditor-1" && resource_id == "tx-2"
+        )));
+        assert!(logs.iter().any(|entry| matches!(
+            &entry.event,
+            SecurityEvent::PermissionDenied { user_id, .. } if user_id == "op-1"
+        )));
+    }
+}
@@ -1,21 +1,41 @@
 //! Enhanced security module for production-grade protection
 
+pub mod api_keys;
 pub mod audit;
 pub mod auth;
 pub mod crypto;
 pub mod encryption;
 pub mod fraud_detection;
+pub mod memo;
 pub mod session;
+pub mod signature_scheme;
+pub mod signer;
 pub mod validation;
 
-pub use audit::{SecurityAuditLogger, SecurityEvent};
+pub use api_keys::{ApiKey, ApiKeyManager, ApiKeyPrincipal};
+pub use audit::{
+    AuditLogSink, AuditRetentionConfig, AuditSeverity, SecurityAuditLogger, SecurityEvent,
+    SiemFormat,
+};
 pub use auth::{AccessControl, Permission, Role};
 pub use crypto::{hash_data, KeyPair, Signature};
-pub use encryption::{EncryptedData, EncryptionManager};
-pub use fraud_detection::{FraudDetector, RiskScore};
+pub use encryption::{DataAccessAuditor, DataAccessRequest, EncryptedData, EncryptionManager};
+pub use fraud_detection::{
+    AnomalyDetector, AnomalyWeights, AutoFreezePolicy, FeatureExtractor, FraudDetector,
+    FraudModelConfig, RiskScore, TransactionFeatures, TransactionPattern,
+    FRAUD_AUTO_FREEZE_ADMIN_ID, FRAUD_AUTO_FREEZE_REASON,
+};
+pub use memo::{MemoCipher, MemoView};
 pub use session::{Session, SessionManager};
-pub use validation::{InputValidator, SecurityValidator};
+pub use signature_scheme::{SchemeSignature, SignatureVerifier};
+pub use signer::{ExternalSigner, Signer, SigningBackend};
+pub use validation::{InputValidator, SecurityValidator, TransactionLimitRule, TransactionLimits};
 
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::accounts::AccountManager;
 use crate::errors::AstorError;
 
 /// Security configuration
@@ -30,6 +50,11 @@ pub struct SecurityConfig {
     pub session_timeout: i64,
     pub require_mfa: bool,
     pub encryption_key: String,
+    pub fraud_auto_freeze: AutoFreezePolicy,
+    /// Days an encryption key stays active before it is rotated
+    pub key_rotation_days: u32,
+    /// Seconds between checks for keys due for rotation
+    pub key_rotation_check_interval: u64,
 }
 
 /// Main security manager
@@ -38,38 +63,74 @@ pub struct SecurityManager {
     session_manager: SessionManager,
     audit_logger: SecurityAuditLogger,
     fraud_detector: FraudDetector,
-    encryption_manager: EncryptionManager,
+    encryption_manager: Arc<RwLock<EncryptionManager>>,
+    key_rotation_task: Option<JoinHandle<()>>,
 }
 
 impl SecurityManager {
     pub fn new(config: SecurityConfig) -> Result<Self, AstorError> {
         let session_manager = SessionManager::new(config.session_timeout);
         let audit_logger = SecurityAuditLogger::new();
-        let fraud_detector = FraudDetector::new();
-        let encryption_manager = EncryptionManager::new(&config.encryption_key)?;
+        let mut fraud_detector = FraudDetector::new();
+        fraud_detector.set_auto_freeze_policy(config.fraud_auto_freeze.clone());
+        let mut encryption_manager = EncryptionManager::new(&config.encryption_key)?;
+        encryption_manager
+            .set_rotation_period(chrono::Duration::days(config.key_rotation_days as i64));
 
         Ok(Self {
             config,
             session_manager,
             audit_logger,
             fraud_detector,
-            encryption_manager,
+            encryption_manager: Arc::new(RwLock::new(encryption_manager)),
+            key_rotation_task: None,
         })
     }
 
+    /// Shared handle to the encryption manager
+    pub fn encryption_manager(&self) -> Arc<RwLock<EncryptionManager>> {
+        self.encryption_manager.clone()
+    }
+
+    /// Memo cipher sharing this manager's encryption keys
+    pub fn memo_cipher(&self) -> MemoCipher {
+        MemoCipher::new(self.encryption_manager.clone())
+    }
+
+    /// Start rotating encryption keys in the background on the configured
+    /// schedule
+    pub fn start_key_rotation(&mut self) {
+        if self.key_rotation_task.is_some() {
+            return;
+        }
+
+        self.key_rotation_task = Some(EncryptionManager::spawn_rotation_task(
+            self.encryption_manager.clone(),
+            std::time::Duration::from_secs(self.config.key_rotation_check_interval.max(1)),
+        ));
+    }
+
     /// Comprehensive security check for operations
+    ///
+    /// `amount` is what the operation moves, or 0 for operations that move
+    /// no funds.
     pub async fn security_check(
         &mut self,
         user_id: &str,
         operation: &str,
+        amount: i64,
         ip_address: &str,
         user_agent: &str,
     ) -> Result<(), AstorError> {
         // Check for fraud patterns
-        let risk_score = self
-            .fraud_detector
-            .assess_risk(user_id, operation, ip_address)
-            .await?;
+        let risk_score = self.fraud_detector.assess_risk(&TransactionPattern {
+            user_id: user_id.to_string(),
+            amount,
+            timestamp: chrono::Utc::now(),
+            ip_address: ip_address.to_string(),
+            user_agent: user_agent.to_string(),
+            transaction_type: operation.to_string(),
+        })?;
         if risk_score.is_high_risk() {
             self.audit_logger
                 .log_security_event(SecurityEvent::HighRiskOperation {
@@ -91,4 +152,165 @@ impl SecurityManager {
 
         Ok(())
     }
+
+    /// Score an account operation, freezing the account pending review if
+    /// the risk crosses the auto-freeze threshold
+    ///
+    /// A frozen account stays frozen until it is manually unfrozen.
+    pub async fn screen_account_operation(
+        &mut self,
+        account_manager: &AccountManager,
+        account_id: &str,
+        operation: &str,
+        amount: i64,
+        ip_address: &str,
+    ) -> Result<RiskScore, AstorError> {
+        let risk_score = self.fraud_detector.assess_risk(&TransactionPattern {
+            user_id: account_id.to_string(),
+            amount,
+            timestamp: chrono::Utc::now(),
+            ip_address: ip_address.to_string(),
+            user_agent: String::new(),
+            transaction_type: operation.to_string(),
+        })?;
+
+        if !self.fraud_detector.should_auto_freeze(&risk_score) {
+            return Ok(risk_score);
+        }
+
+        account_manager.freeze_for_review(
+            account_id,
+            FRAUD_AUTO_FREEZE_ADMIN_ID,
+            FRAUD_AUTO_FREEZE_REASON,
+        )?;
+
+        self.audit_logger
+            .log_security_event(SecurityEvent::SecurityViolation {
+                user_id: Some(account_id.to_string()),
+                violation_type: FRAUD_AUTO_FREEZE_REASON.to_string(),
+                details: format!(
+                    "Account frozen pending review after {} scored {:.2}",
+                    operation,
+                    risk_score.score()
+                ),
+                ip_address: ip_address.to_string(),
+                timestamp: chrono::Utc::now(),
+            })
+            .await?;
+        tracing::error!(
+            "ALERT: account {} auto-frozen for fraud review (operation={}, risk={:.2})",
+            account_id,
+            operation,
+            risk_score.score()
+        );
+
+        Err(AstorError::SecurityViolation(
+            "Account frozen pending fraud review".to_string(),
+        ))
+    }
+}
+
+impl Drop for SecurityManager {
+    fn drop(&mut self) {
+        if let Some(task) = self.key_rotation_task.take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(fraud_auto_freeze: AutoFreezePolicy) -> SecurityConfig {
+        SecurityConfig {
+            jwt_secret: "test_secret".to_string(),
+            jwt_expiration: 3600,
+            refresh_token_expiration: 86400,
+            bcrypt_cost: 4,
+            max_login_attempts: 5,
+            lockout_duration: 900,
+            session_timeout: 3600,
+            require_mfa: false,
+            encryption_key: "test_encryption_key".to_string(),
+            fraud_auto_freeze,
+            key_rotation_days: 90,
+            key_rotation_check_interval: 3600,
+        }
+    }
+
+    /// Flag the IP and record a burst of transactions so the next operation
+    /// scores at the maximum risk
+    fn make_high_risk(manager: &mut SecurityManager, account_id: &str, ip_address: &str) {
+        manager.fraud_detector.update_ip_reputation(ip_address, 0.5);
+        for _ in 0..6 {
+            manager
+                .fraud_detector
+                .record_transaction(TransactionPattern {
+                    user_id: account_id.to_string(),
+                    amount: 500,
+                    timestamp: chrono::Utc::now(),
+                    ip_address: ip_address.to_string(),
+                    user_agent: "test-agent".to_string(),
+                    transaction_type: "transfer".to_string(),
+                });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_high_risk_operation_freezes_account() {
+        let mut manager = SecurityManager::new(test_config(AutoFreezePolicy::default())).unwrap();
+        let accounts = AccountManager::new();
+        let account_id = accounts.create_account(None);
+        make_high_risk(&mut manager, &account_id, "203.0.113.7");
+
+        let result = manager
+            .screen_account_operation(&accounts, &account_id, "transfer", 500, "203.0.113.7")
+            .await;
+        assert!(matches!(result, Err(AstorError::SecurityViolation(_))));
+
+        let account = accounts.get_account(&account_id).unwrap();
+        assert!(account.is_frozen());
+        assert_eq!(
+            account.freeze_reason.as_deref(),
+            Some(FRAUD_AUTO_FREEZE_REASON)
+        );
+        assert!(accounts.credit_account(&account_id, 100).is_err());
+
+        let admin = KeyPair::generate();
+        let mut admins = crate::admin::AdminManager::new();
+        admins
+            .add_admin("admin-1".to_string(), admin.public_key())
+            .unwrap();
+        let message =
+            crate::accounts::account_status_message("unfreeze", &account_id, account.version);
+        accounts
+            .unfreeze_account(
+                &account_id,
+                &admins,
+                "admin-1",
+                &admin.sign(message.as_bytes()),
+            )
+            .unwrap();
+        assert!(accounts.credit_account(&account_id, 100).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_auto_freeze_disabled() {
+        let policy = AutoFreezePolicy {
+            enabled: false,
+            ..AutoFreezePolicy::default()
+        };
+        let mut manager = SecurityManager::new(test_config(policy)).unwrap();
+        let accounts = AccountManager::new();
+        let account_id = accounts.create_account(None);
+        make_high_risk(&mut manager, &account_id, "203.0.113.7");
+
+        let risk_score = manager
+            .screen_account_operation(&accounts, &account_id, "transfer", 500, "203.0.113.7")
+            .await
+            .unwrap();
+        assert!(risk_score.is_high_risk());
+        assert!(!accounts.get_account(&account_id).unwrap().is_frozen());
+    }
 }
@@ -0,0 +1,152 @@
+//! Signature scheme negotiation
+//!
+//! Signed artifacts record the algorithm they were signed with, and
+//! verification dispatches on it rather than assuming Ed25519. A scheme can be
+//! known (it parses and round-trips) without being accepted: a verifier only
+//! accepts schemes it is configured for and has an implementation of, and
+//! reports anything else as `UnsupportedSignatureScheme`. Schemes are the
+//! CA's `KeyAlgorithm`s, so certificates and signed payloads name them alike.
+
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::{PublicKey, Verifier};
+use serde::{Deserialize, Serialize};
+
+use super::signer::Signer;
+use crate::certificate_authority::csr::KeyAlgorithm;
+use crate::errors::AstorError;
+
+/// Verifies signatures in whichever accepted scheme they declare
+#[derive(Debug, Clone)]
+pub struct SignatureVerifier {
+    accepted: Vec<KeyAlgorithm>,
+}
+
+impl SignatureVerifier {
+    /// Accept Ed25519 only
+    pub fn new() -> Self {
+        Self {
+            accepted: vec![KeyAlgorithm::Ed25519],
+        }
+    }
+
+    /// Replace the schemes this verifier accepts
+    pub fn set_accepted_schemes(&mut self, schemes: Vec<KeyAlgorithm>) {
+        self.accepted = schemes;
+    }
+
+    pub fn accepts(&self, scheme: KeyAlgorithm) -> bool {
+        scheme.is_implemented() && self.accepted.contains(&scheme)
+    }
+
+    /// Verify `signature` over `message` using the declared `scheme`
+    pub fn verify(
+        &self,
+        scheme: KeyAlgorithm,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), AstorError> {
+        if !self.accepts(scheme) {
+            return Err(AstorError::UnsupportedSignatureScheme(
+                scheme.name().to_string(),
+            ));
+        }
+
+        match scheme {
+            KeyAlgorithm::Ed25519 => {
+                let public_key = PublicKey::from_bytes(public_key).map_err(|_| {
+                    AstorError::CryptographicError("Invalid Ed25519 public key".to_string())
+                })?;
+                let signature = ed25519_dalek::Signature::from_bytes(signature)
+                    .map_err(|_| AstorError::InvalidSignature)?;
+                public_key
+                    .verify(message, &signature)
+                    .map_err(|_| AstorError::InvalidSignature)
+            }
+            KeyAlgorithm::EcdsaP256 => Err(AstorError::UnsupportedSignatureScheme(
+                scheme.name().to_string(),
+            )),
+        }
+    }
+
+    /// Verify a signature whose scheme is given by name, as stored in
+    /// certificates
+    pub fn verify_named(
+        &self,
+        scheme_name: &str,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), AstorError> {
+        let scheme = KeyAlgorithm::from_name(scheme_name)
+            .ok_or_else(|| AstorError::UnsupportedSignatureScheme(scheme_name.to_string()))?;
+        self.verify(scheme, public_key, message, signature)
+    }
+}
+
+impl Default for SignatureVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A detached signature tagged with its scheme
+///
+/// It deliberately carries no public key: the verifier supplies the key it
+/// trusts for the signer, such as the one registered on an account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemeSignature {
+    #[serde(default)]
+    pub scheme: KeyAlgorithm,
+    pub key_id: String,
+    /// Base64 signature bytes
+    pub signature: String,
+}
+
+impl SchemeSignature {
+    /// Sign `message` with an Ed25519 signer
+    pub fn sign_ed25519(signer: &dyn Signer, message: &[u8]) -> Result<Self, AstorError> {
+        let signature = signer.sign(message)?;
+        Ok(Self {
+            scheme: KeyAlgorithm::Ed25519,
+            key_id: signer.key_id().to_string(),
+            signature: signature.to_base64(),
+        })
+    }
+
+    /// Verify over `message` against the signer key the caller trusts
+    pub fn verify(
+        &self,
+        verifier: &SignatureVerifier,
+        public_key: &[u8],
+        message: &[u8],
+    ) -> Result<(), AstorError> {
+        let signature = general_purpose::STANDARD
+            .decode(&self.signature)
+            .map_err(|_| AstorError::CryptographicError("Invalid base64".to_string()))?;
+        verifier.verify(self.scheme, public_key, message, &signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheme_names_round_trip() {
+        for scheme in [KeyAlgorithm::Ed25519, KeyAlgorithm::EcdsaP256] {
+            assert_eq!(KeyAlgorithm::from_name(scheme.name()), Some(scheme));
+        }
+        assert_eq!(KeyAlgorithm::from_name("RSA-1024"), None);
+        assert_eq!(KeyAlgorithm::default(), KeyAlgorithm::Ed25519);
+    }
+
+    #[test]
+    fn test_unaccepted_scheme_is_rejected_even_if_implemented() {
+        let mut verifier = SignatureVerifier::new();
+        verifier.set_accepted_schemes(vec![KeyAlgorithm::EcdsaP256]);
+        assert!(!verifier.accepts(KeyAlgorithm::Ed25519));
+        // Accepted by configuration but not implemented in this build
+        assert!(!verifier.accepts(KeyAlgorithm::EcdsaP256));
+    }
+}
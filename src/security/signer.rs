@@ -0,0 +1,151 @@
+//! Signing abstraction for keys held outside process memory
+//!
+//! The CA and issuance paths sign through `Signer` so root and intermediate
+//! keys can live in an HSM or KMS instead of an in-memory `KeyPair`.
+
+use ed25519_dalek::PublicKey;
+
+use super::crypto::{KeyPair, Signature};
+use crate::errors::AstorError;
+
+/// A key that can produce signatures without exposing private key material
+pub trait Signer: Send + Sync {
+    fn public_key(&self) -> PublicKey;
+    fn key_id(&self) -> &str;
+    fn sign(&self, message: &[u8]) -> Result<Signature, AstorError>;
+}
+
+impl Signer for KeyPair {
+    fn public_key(&self) -> PublicKey {
+        KeyPair::public_key(self)
+    }
+
+    fn key_id(&self) -> &str {
+        KeyPair::key_id(self)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, AstorError> {
+        Ok(KeyPair::sign(self, message))
+    }
+}
+
+/// Transport to a hardware security module or key management service
+pub trait SigningBackend: Send + Sync {
+    /// Sign `message` with the key identified by `key_id`, returning the raw
+    /// Ed25519 signature bytes
+    fn sign_raw(&self, key_id: &str, message: &[u8]) -> Result<Vec<u8>, AstorError>;
+}
+
+/// Signer whose private key never leaves an external device
+///
+/// Only the key identifier and public key are held locally; every signature
+/// is requested from the backend and checked against the public key before
+/// it is returned.
+pub struct ExternalSigner {
+    key_id: String,
+    public_key: PublicKey,
+    backend: Box<dyn SigningBackend>,
+}
+
+impl ExternalSigner {
+    pub fn new(key_id: String, public_key: PublicKey, backend: Box<dyn SigningBackend>) -> Self {
+        Self {
+            key_id,
+            public_key,
+            backend,
+        }
+    }
+}
+
+impl Signer for ExternalSigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, AstorError> {
+        let bytes = self.backend.sign_raw(&self.key_id, message)?;
+        let signature = Signature::from_bytes(&bytes, self.key_id.clone())?;
+
+        // Catch a misconfigured key mapping before the signature is used
+        signature
+            .verify_ignoring_age(&self.public_key, message)
+            .map_err(|_| {
+                AstorError::CryptographicError(format!(
+                    "External signer returned a signature that does not match key {}",
+                    self.key_id
+                ))
+            })?;
+
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate_authority::ca_core::{CaConfig, CertificateAuthority};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Stands in for an HSM: the key stays inside and only signatures leave
+    struct MockHsm {
+        key: KeyPair,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl SigningBackend for MockHsm {
+        fn sign_raw(&self, key_id: &str, message: &[u8]) -> Result<Vec<u8>, AstorError> {
+            assert_eq!(key_id, "hsm-root");
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(self.key.sign(message).to_bytes().to_vec())
+        }
+    }
+
+    fn hsm_signer() -> (ExternalSigner, Arc<AtomicUsize>) {
+        let key = KeyPair::generate();
+        let public_key = key.public_key();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let backend = MockHsm {
+            key,
+            requests: requests.clone(),
+        };
+
+        let signer = ExternalSigner::new("hsm-root".to_string(), public_key, Box::new(backend));
+        (signer, requests)
+    }
+
+    #[test]
+    fn test_ca_issues_through_external_signer() {
+        let (signer, requests) = hsm_signer();
+        let root_ca =
+            CertificateAuthority::new_root(Arc::new(signer), CaConfig::default()).unwrap();
+
+        let certificate = root_ca
+            .issue_ocsp_signing_certificate(KeyPair::generate().public_key(), 30)
+            .unwrap();
+
+        // The only route to a signature was the backend
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(root_ca.verify_issued_certificate(&certificate).unwrap());
+    }
+
+    #[test]
+    fn test_mismatched_external_key_rejected() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let backend = MockHsm {
+            key: KeyPair::generate(),
+            requests,
+        };
+        let signer = ExternalSigner::new(
+            "hsm-root".to_string(),
+            KeyPair::generate().public_key(),
+            Box::new(backend),
+        );
+
+        assert!(Signer::sign(&signer, b"payload").is_err());
+    }
+}
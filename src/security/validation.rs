@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use uuid::Uuid;
 
+use crate::accounts::AccountType;
 use crate::errors::AstorError;
+use crate::regulatory::RiskRating;
 
 /// Input validator for sanitizing and validating user inputs
 pub struct InputValidator {
@@ -213,11 +215,87 @@ impl InputValidator {
     }
 }
 
+/// Per-transaction cap for accounts matching a type and/or risk rating
+///
+/// A rule leaving a field unset matches every value of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionLimitRule {
+    #[serde(default)]
+    pub account_type: Option<AccountType>,
+    #[serde(default)]
+    pub risk_rating: Option<RiskRating>,
+    pub max_amount: u64,
+}
+
+impl TransactionLimitRule {
+    fn matches(&self, account_type: AccountType, risk_rating: RiskRating) -> bool {
+        self.account_type.map_or(true, |t| t == account_type)
+            && self.risk_rating.map_or(true, |r| r == risk_rating)
+    }
+}
+
+/// Per-transaction limits resolved from an account's type and risk rating
+///
+/// When several rules match, the lowest cap applies, so a high-risk merchant
+/// gets the high-risk cap. Accounts no rule matches get `default_max_amount`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionLimits {
+    pub default_max_amount: u64,
+    #[serde(default)]
+    pub rules: Vec<TransactionLimitRule>,
+}
+
+impl TransactionLimits {
+    /// Maximum single transaction for an account
+    pub fn limit_for(&self, account_type: AccountType, risk_rating: RiskRating) -> u64 {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(account_type, risk_rating))
+            .map(|rule| rule.max_amount)
+            .min()
+            .unwrap_or(self.default_max_amount)
+    }
+
+    pub fn check(
+        &self,
+        amount: u64,
+        account_type: AccountType,
+        risk_rating: RiskRating,
+    ) -> Result<(), AstorError> {
+        let limit = self.limit_for(account_type, risk_rating);
+        if amount > limit {
+            return Err(AstorError::TransactionLimitExceeded { amount, limit });
+        }
+        Ok(())
+    }
+}
+
+impl Default for TransactionLimits {
+    fn default() -> Self {
+        Self {
+            default_max_amount: 1_000_000_00, // $1M in cents
+            rules: vec![
+                TransactionLimitRule {
+                    account_type: None,
+                    risk_rating: Some(RiskRating::High),
+                    max_amount: 10_000_00,
+                },
+                TransactionLimitRule {
+                    account_type: Some(AccountType::Merchant),
+                    risk_rating: Some(RiskRating::Low),
+                    max_amount: 5_000_000_00,
+                },
+            ],
+        }
+    }
+}
+
 /// Security validator for business logic and security rules
 pub struct SecurityValidator {
     max_transaction_amount: i64,
     max_daily_transaction_amount: i64,
     allowed_currencies: HashSet<String>,
+    transaction_limits: TransactionLimits,
 }
 
 impl SecurityValidator {
@@ -237,9 +315,19 @@ impl SecurityValidator {
             max_transaction_amount: 1_000_000_00,        // $1M in cents
             max_daily_transaction_amount: 10_000_000_00, // $10M in cents
             allowed_currencies,
+            transaction_limits: TransactionLimits::default(),
         }
     }
 
+    /// Replace the per-account-type transaction limits, e.g. from config
+    pub fn set_transaction_limits(&mut self, limits: TransactionLimits) {
+        self.transaction_limits = limits;
+    }
+
+    pub fn transaction_limits(&self) -> &TransactionLimits {
+        &self.transaction_limits
+    }
+
     /// Validate transaction amount limits
     pub fn validate_transaction_limits(&self, amount: i64) -> Result<(), AstorError> {
         if amount > self.max_transaction_amount {
@@ -252,6 +340,18 @@ impl SecurityValidator {
         Ok(())
     }
 
+    /// Validate an amount against the limit for the sending account's type
+    /// and risk rating
+    pub fn validate_account_transaction_limits(
+        &self,
+        amount: u64,
+        account_type: AccountType,
+        risk_rating: RiskRating,
+    ) -> Result<(), AstorError> {
+        self.transaction_limits
+            .check(amount, account_type, risk_rating)
+    }
+
     /// Validate daily transaction limits
     pub fn validate_daily_limits(
         &self,
@@ -0,0 +1,485 @@
+//! Compiler for Astor smart contract source
+//!
+//! A contract declares events and functions. Function bodies are written in
+//! AstorVM assembly, one instruction per line:
+//!
+//! ```text
+//! event Doubled(amount: u64)
+//!
+//! function double(amount: u64) -> u64 view {
+//!     push 2
+//!     mul
+//! }
+//! ```
+
+use super::{ContractABI, EventSignature, FunctionSignature, Parameter};
+use crate::errors::{AstorError, AstorResult};
+use std::fmt;
+
+const OP_PUSH: u8 = 0x10;
+const OP_HALT: u8 = 0xFF;
+
+/// Compile contract source to AstorVM bytecode
+pub fn compile(source: &str) -> AstorResult<Vec<u8>> {
+    let program = parse(source)?;
+
+    let mut bytecode: Vec<u8> = program
+        .functions
+        .into_iter()
+        .flat_map(|function| function.body)
+        .collect();
+    bytecode.push(OP_HALT);
+
+    Ok(bytecode)
+}
+
+/// Extract the function and event signatures declared in contract source
+pub fn extract_abi(source: &str) -> AstorResult<ContractABI> {
+    let program = parse(source)?;
+
+    Ok(ContractABI {
+        functions: program
+            .functions
+            .into_iter()
+            .map(|function| function.signature)
+            .collect(),
+        events: program.events,
+    })
+}
+
+struct CompiledFunction {
+    signature: FunctionSignature,
+    body: Vec<u8>,
+}
+
+struct Program {
+    functions: Vec<CompiledFunction>,
+    events: Vec<EventSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Number(u64),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Ident(ident) => write!(f, "`{}`", ident),
+            TokenKind::Number(number) => write!(f, "`{}`", number),
+            TokenKind::Symbol(symbol) => write!(f, "`{}`", symbol),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn compile_error(line: usize, column: usize, message: impl Into<String>) -> AstorError {
+    AstorError::ContractCompileError {
+        line,
+        column,
+        message: message.into(),
+    }
+}
+
+/// Split a source line into tokens, ignoring `//` comments
+fn tokenize(line: usize, source: &str) -> AstorResult<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        let column = pos + 1;
+
+        if c.is_whitespace() {
+            pos += 1;
+        } else if c == '/' && chars.get(pos + 1) == Some(&'/') {
+            break;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = pos;
+            while pos < chars.len() && (chars[pos].is_ascii_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            let ident: String = chars[start..pos].iter().collect();
+            tokens.push(Token {
+                kind: TokenKind::Ident(ident),
+                column,
+            });
+        } else if c.is_ascii_digit() {
+            let start = pos;
+            while pos < chars.len() && chars[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            let digits: String = chars[start..pos].iter().collect();
+            let number = digits
+                .parse()
+                .map_err(|_| compile_error(line, column, "number literal is too large"))?;
+            tokens.push(Token {
+                kind: TokenKind::Number(number),
+                column,
+            });
+        } else {
+            let symbol = match c {
+                '(' => "(",
+                ')' => ")",
+                '{' => "{",
+                '}' => "}",
+                ',' => ",",
+                ':' => ":",
+                '-' if chars.get(pos + 1) == Some(&'>') => "->",
+                _ => {
+                    return Err(compile_error(
+                        line,
+                        column,
+                        format!("unexpected character `{}`", c),
+                    ))
+                }
+            };
+            pos += symbol.len();
+            tokens.push(Token {
+                kind: TokenKind::Symbol(symbol),
+                column,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Token cursor over a single source line
+struct Cursor {
+    line: usize,
+    end_column: usize,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(line: usize, source: &str, tokens: Vec<Token>) -> Self {
+        Self {
+            line,
+            end_column: source.chars().count() + 1,
+            tokens,
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token { kind: TokenKind::Symbol(s), .. }) if *s == symbol)
+    }
+
+    fn peek_column(&self) -> usize {
+        self.peek().map_or(self.end_column, |token| token.column)
+    }
+
+    fn unexpected(&self, expected: &str) -> AstorError {
+        match self.peek() {
+            Some(token) => compile_error(
+                self.line,
+                token.column,
+                format!("expected {}, found {}", expected, token.kind),
+            ),
+            None => compile_error(
+                self.line,
+                self.end_column,
+                format!("expected {}, found end of line", expected),
+            ),
+        }
+    }
+
+    fn expect_ident(&mut self, expected: &str) -> AstorResult<(String, usize)> {
+        match self.peek().cloned() {
+            Some(Token {
+                kind: TokenKind::Ident(ident),
+                column,
+            }) => {
+                self.pos += 1;
+                Ok((ident, column))
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> AstorResult<usize> {
+        if self.peek_symbol(symbol) {
+            let column = self.peek_column();
+            self.pos += 1;
+            Ok(column)
+        } else {
+            Err(self.unexpected(&format!("`{}`", symbol)))
+        }
+    }
+
+    fn expect_end(&self) -> AstorResult<()> {
+        match self.peek() {
+            Some(_) => Err(self.unexpected("end of line")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse(source: &str) -> AstorResult<Program> {
+    let mut functions = Vec::new();
+    let mut events = Vec::new();
+    // Function currently being compiled and the position of its opening brace
+    let mut current: Option<(CompiledFunction, usize, usize)> = None;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let tokens = tokenize(line, text)?;
+        if tokens.is_empty() {
+            continue;
+        }
+        let mut cursor = Cursor::new(line, text, tokens);
+
+        if let Some((function, _, _)) = current.as_mut() {
+            if cursor.peek_symbol("}") {
+                cursor.expect_symbol("}")?;
+                cursor.expect_end()?;
+                if let Some((function, _, _)) = current.take() {
+                    functions.push(function);
+                }
+            } else {
+                function.body.extend(parse_instruction(&mut cursor)?);
+                cursor.expect_end()?;
+            }
+            continue;
+        }
+
+        let (keyword, column) = cursor.expect_ident("`function` or `event`")?;
+        match keyword.as_str() {
+            "function" => {
+                let (signature, brace_column) = parse_function_header(&mut cursor)?;
+                let function = CompiledFunction {
+                    signature,
+                    body: Vec::new(),
+                };
+                current = Some((function, line, brace_column));
+            }
+            "event" => {
+                let name = cursor.expect_ident("event name")?.0;
+                let inputs = parse_parameters(&mut cursor)?;
+                cursor.expect_end()?;
+                events.push(EventSignature { name, inputs });
+            }
+            _ => {
+                return Err(compile_error(
+                    line,
+                    column,
+                    format!("expected `function` or `event`, found `{}`", keyword),
+                ))
+            }
+        }
+    }
+
+    if let Some((function, line, column)) = current {
+        return Err(compile_error(
+            line,
+            column,
+            format!(
+                "function `{}` is missing a closing `}}`",
+                function.signature.name
+            ),
+        ));
+    }
+
+    Ok(Program { functions, events })
+}
+
+/// Parse `name(params) [-> type] [view] [payable] {`, returning the column of `{`
+fn parse_function_header(cursor: &mut Cursor) -> AstorResult<(FunctionSignature, usize)> {
+    let name = cursor.expect_ident("function name")?.0;
+    let inputs = parse_parameters(cursor)?;
+
+    let mut outputs = Vec::new();
+    if cursor.peek_symbol("->") {
+        cursor.expect_symbol("->")?;
+        let param_type = cursor.expect_ident("return type")?.0;
+        outputs.push(Parameter {
+            name: "return".to_string(),
+            param_type,
+        });
+    }
+
+    let mut payable = false;
+    let mut view = false;
+    while !cursor.peek_symbol("{") {
+        let (modifier, column) = cursor.expect_ident("`view`, `payable` or `{`")?;
+        match modifier.as_str() {
+            "view" => view = true,
+            "payable" => payable = true,
+            _ => {
+                return Err(compile_error(
+                    cursor.line,
+                    column,
+                    format!("unknown function modifier `{}`", modifier),
+                ))
+            }
+        }
+    }
+
+    let brace_column = cursor.expect_symbol("{")?;
+    cursor.expect_end()?;
+
+    Ok((
+        FunctionSignature {
+            name,
+            inputs,
+            outputs,
+            payable,
+            view,
+        },
+        brace_column,
+    ))
+}
+
+/// Parse `(name: type, ...)`
+fn parse_parameters(cursor: &mut Cursor) -> AstorResult<Vec<Parameter>> {
+    cursor.expect_symbol("(")?;
+
+    let mut parameters = Vec::new();
+    if !cursor.peek_symbol(")") {
+        loop {
+            let name = cursor.expect_ident("parameter name")?.0;
+            cursor.expect_symbol(":")?;
+            let param_type = cursor.expect_ident("parameter type")?.0;
+            parameters.push(Parameter { name, param_type });
+
+            if cursor.peek_symbol(",") {
+                cursor.expect_symbol(",")?;
+            } else if cursor.peek_symbol(")") {
+                break;
+            } else {
+                return Err(cursor.unexpected("`,` or `)`"));
+            }
+        }
+    }
+
+    cursor.expect_symbol(")")?;
+    Ok(parameters)
+}
+
+fn parse_instruction(cursor: &mut Cursor) -> AstorResult<Vec<u8>> {
+    let (mnemonic, column) = cursor.expect_ident("instruction")?;
+
+    let opcode = match mnemonic.to_lowercase().as_str() {
+        "add" => 0x01,
+        "sub" => 0x02,
+        "mul" => 0x03,
+        "div" => 0x04,
+        "load" => 0x20,
+        "store" => 0x21,
+        "jump" => 0x30,
+        "jumpi" => 0x31,
+        "call" => 0x40,
+        "halt" => OP_HALT,
+        "push" => {
+            let operand_column = cursor.peek_column();
+            let operand = match cursor.peek() {
+                Some(Token {
+                    kind: TokenKind::Number(number),
+                    ..
+                }) => *number,
+                _ => return Err(cursor.unexpected("push operand")),
+            };
+            cursor.pos += 1;
+
+            // The VM reads push operands as a single byte
+            let operand = u8::try_from(operand).map_err(|_| {
+                compile_error(
+                    cursor.line,
+                    operand_column,
+                    "push operand must be between 0 and 255",
+                )
+            })?;
+            return Ok(vec![OP_PUSH, operand]);
+        }
+        _ => {
+            return Err(compile_error(
+                cursor.line,
+                column,
+                format!("unknown instruction `{}`", mnemonic),
+            ))
+        }
+    };
+
+    Ok(vec![opcode])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOUBLER: &str = "\
+// Doubles its input
+event Doubled(amount: u64)
+
+function double(amount: u64) -> u64 view {
+    push 2
+    mul
+}
+";
+
+    fn error_position(result: AstorResult<Vec<u8>>) -> (usize, usize, String) {
+        match result {
+            Err(AstorError::ContractCompileError {
+                line,
+                column,
+                message,
+            }) => (line, column, message),
+            other => panic!("expected compile error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_compile_valid_contract() {
+        assert_eq!(compile(DOUBLER).unwrap(), vec![0x10, 2, 0x03, 0xFF]);
+
+        let abi = extract_abi(DOUBLER).unwrap();
+        assert_eq!(abi.functions.len(), 1);
+        assert_eq!(abi.functions[0].name, "double");
+        assert_eq!(abi.functions[0].inputs[0].param_type, "u64");
+        assert!(abi.functions[0].view);
+        assert!(!abi.functions[0].payable);
+        assert_eq!(abi.events[0].name, "Doubled");
+    }
+
+    #[test]
+    fn test_syntax_error_reports_position() {
+        let source = "\
+event Transfer(to: address, amount: u64)
+function transfer(to: address amount: u64) payable {
+    halt
+}
+";
+        let (line, column, message) = error_position(compile(source));
+        assert_eq!((line, column), (2, 31));
+        assert!(message.contains("`amount`"));
+    }
+
+    #[test]
+    fn test_unknown_instruction_reports_position() {
+        let source = "function f() {\n    push 1\n    jmp\n}\n";
+        let (line, column, message) = error_position(compile(source));
+        assert_eq!((line, column), (3, 5));
+        assert!(message.contains("jmp"));
+    }
+
+    #[test]
+    fn test_unclosed_function_reports_opening_brace() {
+        let source = "function f() {\n    halt\n";
+        let (line, column, _) = error_position(compile(source));
+        assert_eq!((line, column), (1, 14));
+    }
+}
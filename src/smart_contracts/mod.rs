@@ -6,8 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+pub mod compiler;
 pub mod vm;
-// pub mod compiler;
 // pub mod stdlib;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
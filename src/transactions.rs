@@ -1,10 +1,23 @@
 //! Transaction management and validation module
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Months, Utc};
+use ed25519_dalek::PublicKey;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::accounts::AccountManager;
 use crate::errors::AstorError;
+use crate::fee_market::{FeeMarket, FeeMarketConfig};
+use crate::ledger::LedgerEntry;
+use crate::schema::{legacy_schema_version, Versioned};
+use crate::security::{EncryptedData, Role, SchemeSignature, Signature, SignatureVerifier, Signer};
 
 /// Transaction types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,24 +44,695 @@ pub enum TransactionType {
 /// Transaction record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub id: String,
     pub transaction_type: TransactionType,
     pub timestamp: DateTime<Utc>,
     pub status: TransactionStatus,
     pub hash: String,
+    /// Status changes since creation, oldest first
+    #[serde(default)]
+    pub status_history: Vec<StatusTransition>,
+    #[serde(default)]
+    pub priority: TransactionPriority,
+    #[serde(default)]
+    pub memo: Option<TransactionMemo>,
+    /// Originator's signature over `hash`, tagged with its scheme
+    #[serde(default)]
+    pub signature: Option<SchemeSignature>,
+    /// Which roles may see this transaction in queries and statements
+    #[serde(default)]
+    pub visibility: TransactionVisibility,
+    /// Base fee charged to the sender when the transfer settles, fixed when
+    /// it is submitted
+    #[serde(default)]
+    pub fee: u64,
+}
+
+impl Transaction {
+    /// Sign the transaction hash with an Ed25519 signer
+    pub fn sign(&mut self, signer: &dyn Signer) -> Result<(), AstorError> {
+        self.signature = Some(SchemeSignature::sign_ed25519(signer, self.hash.as_bytes())?);
+        Ok(())
+    }
+
+    /// Verify the originator's signature using the scheme it declares
+    ///
+    /// `public_key` is the key registered for the originator, not one the
+    /// signature supplies. The hash is recomputed from the transaction body,
+    /// so a body altered after signing fails even if `hash` was left intact.
+    pub fn verify_signature(
+        &self,
+        verifier: &SignatureVerifier,
+        public_key: &PublicKey,
+    ) -> Result<(), AstorError> {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or(AstorError::InvalidSignature)?;
+        let hash = transaction_hash(&self.id, &self.transaction_type);
+        if hash != self.hash {
+            return Err(AstorError::InvalidSignature);
+        }
+        signature.verify(verifier, public_key.as_bytes(), hash.as_bytes())
+    }
+
+    /// Account whose key must sign this transaction; issuance is authorized
+    /// by an administrator instead
+    pub fn originator(&self) -> Option<&str> {
+        match &self.transaction_type {
+            TransactionType::Issuance { .. } => None,
+            TransactionType::Transfer { from, .. } => Some(from),
+            TransactionType::Conversion { account, .. } => Some(account),
+        }
+    }
+}
+
+/// Who may see a transaction, from least to most restricted
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum TransactionVisibility {
+    #[default]
+    Public,
+    /// Auditors and central bank administrators
+    AuditorOnly,
+    /// Central bank and root administrators only
+    AdminOnly,
+}
+
+impl TransactionVisibility {
+    const ALL: [TransactionVisibility; 3] = [
+        TransactionVisibility::Public,
+        TransactionVisibility::AuditorOnly,
+        TransactionVisibility::AdminOnly,
+    ];
+
+    /// Name stored in the `transactions.visibility` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionVisibility::Public => "public",
+            TransactionVisibility::AuditorOnly => "auditor_only",
+            TransactionVisibility::AdminOnly => "admin_only",
+        }
+    }
+
+    /// Every visibility `role` may see, for filtering stored transactions
+    pub fn visible_to(role: &Role) -> Vec<TransactionVisibility> {
+        Self::ALL
+            .into_iter()
+            .filter(|visibility| visibility.is_visible_to(role))
+            .collect()
+    }
+
+    pub fn is_visible_to(&self, role: &Role) -> bool {
+        match self {
+            TransactionVisibility::Public => true,
+            TransactionVisibility::AuditorOnly => matches!(
+                role,
+                Role::Auditor | Role::CentralBankAdmin | Role::RootAdmin
+            ),
+            TransactionVisibility::AdminOnly => {
+                matches!(role, Role::CentralBankAdmin | Role::RootAdmin)
+            }
+        }
+    }
+}
+
+/// Free-text reference attached to a transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransactionMemo {
+    Plaintext(String),
+    /// Readable only by the listed parties, or through an audited compliance
+    /// override; see `security::memo::MemoCipher`
+    Encrypted {
+        ciphertext: EncryptedData,
+        authorized_parties: Vec<String>,
+    },
+}
+
+impl TransactionMemo {
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, TransactionMemo::Encrypted { .. })
+    }
+}
+
+impl Versioned for Transaction {
+    const SCHEMA_VERSION: u32 = 2;
+
+    fn migrate(
+        from_version: u32,
+        payload: &mut serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), AstorError> {
+        match from_version {
+            // Version 1 predates status history and processing priority
+            1 => {
+                payload
+                    .entry("status_history")
+                    .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+                if !payload.contains_key("priority") {
+                    payload.insert(
+                        "priority".to_string(),
+                        serde_json::to_value(TransactionPriority::default())?,
+                    );
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Processing priority of a transaction
+///
+/// Admin-flagged transactions outrank any tip; tipped transactions are
+/// ordered by tip amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TransactionPriority {
+    /// Most the sender will pay in fees; the base fee at submission is
+    /// what is charged
+    Tip(u64),
+    Admin,
+}
+
+impl Default for TransactionPriority {
+    fn default() -> Self {
+        TransactionPriority::Tip(0)
+    }
 }
 
 /// Transaction status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TransactionStatus {
     Pending,
-    Confirmed,
+    Completed,
+    /// Held for review; released back to `Pending`
+    Held(String),
+    Reversed(String),
+    Expired,
     Failed(String),
+    /// Funds held until the recipient acknowledges receipt; settled only by
+    /// the acknowledgment or its expiry, never by the processing queue
+    AwaitingReceipt,
+}
+
+impl TransactionStatus {
+    /// Whether the lifecycle allows moving from this status to `next`
+    pub fn can_transition_to(&self, next: &TransactionStatus) -> bool {
+        use TransactionStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Completed)
+                | (Pending, Held(_))
+                | (Pending, Reversed(_))
+                | (Pending, Expired)
+                | (Pending, Failed(_))
+                | (Pending, AwaitingReceipt)
+                | (AwaitingReceipt, Completed)
+                | (AwaitingReceipt, Reversed(_))
+                | (AwaitingReceipt, Failed(_))
+                | (Held(_), Pending)
+                | (Held(_), Reversed(_))
+                | (Held(_), Expired)
+                | (Held(_), Failed(_))
+                | (Completed, Reversed(_))
+        )
+    }
+
+    /// Terminal statuses accept no further transitions
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TransactionStatus::Reversed(_)
+                | TransactionStatus::Expired
+                | TransactionStatus::Failed(_)
+        )
+    }
+}
+
+/// A recorded change of transaction status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusTransition {
+    pub from: TransactionStatus,
+    pub to: TransactionStatus,
+    pub at: DateTime<Utc>,
+}
+
+/// How new transactions are handled while the node is syncing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncThrottlePolicy {
+    /// Reject new transactions with `AstorError::NodeSyncing`
+    #[default]
+    Reject,
+    /// Hold new transactions and accept them once the node is synced
+    Queue,
+}
+
+/// Processing queue configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionQueueConfig {
+    /// Maximum number of transactions waiting to be processed
+    pub capacity: usize,
+    /// How many later arrivals may be processed ahead of a waiting
+    /// transaction before it is served regardless of priority
+    pub starvation_limit: usize,
+}
+
+impl Default for TransactionQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            starvation_limit: 32,
+        }
+    }
+}
+
+/// Queue depth and throughput counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionQueueMetrics {
+    pub depth: usize,
+    pub capacity: usize,
+    pub admin_depth: usize,
+    pub tipped_depth: usize,
+    pub oldest_enqueued_at: Option<DateTime<Utc>>,
+    pub enqueued_total: u64,
+    pub processed_total: u64,
+    pub rejected_total: u64,
+    /// Transactions served out of priority order to prevent starvation
+    pub starvation_promotions: u64,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedTransaction {
+    tx_id: String,
+    priority: TransactionPriority,
+    enqueued_at: DateTime<Utc>,
+    /// Later arrivals processed ahead of this transaction
+    bypassed: usize,
+}
+
+/// Bounded priority queue of transactions awaiting processing
+///
+/// Higher priorities are served first, ties in arrival order. A transaction
+/// that has been overtaken `starvation_limit` times is served next even if
+/// higher-priority work is waiting.
+#[derive(Debug, Default)]
+pub struct TransactionQueue {
+    config: TransactionQueueConfig,
+    /// Entries in arrival order
+    entries: VecDeque<QueuedTransaction>,
+    metrics: TransactionQueueMetrics,
+}
+
+impl TransactionQueue {
+    pub fn new(config: TransactionQueueConfig) -> Self {
+        Self {
+            config,
+            entries: VecDeque::new(),
+            metrics: TransactionQueueMetrics::default(),
+        }
+    }
+
+    pub fn config(&self) -> &TransactionQueueConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: TransactionQueueConfig) {
+        self.config = config;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add a transaction, rejecting it if the queue is at capacity
+    pub fn try_push(
+        &mut self,
+        tx_id: &str,
+        priority: TransactionPriority,
+    ) -> Result<(), AstorError> {
+        if self.entries.len() >= self.config.capacity {
+            self.metrics.rejected_total += 1;
+            return Err(AstorError::TransactionQueueFull(self.entries.len()));
+        }
+
+        self.push(tx_id, priority);
+        Ok(())
+    }
+
+    /// Add an already admitted transaction, ignoring the capacity bound
+    fn push(&mut self, tx_id: &str, priority: TransactionPriority) {
+        self.entries.push_back(QueuedTransaction {
+            tx_id: tx_id.to_string(),
+            priority,
+            enqueued_at: Utc::now(),
+            bypassed: 0,
+        });
+        self.metrics.enqueued_total += 1;
+    }
+
+    pub fn contains(&self, tx_id: &str) -> bool {
+        self.entries.iter().any(|entry| entry.tx_id == tx_id)
+    }
+
+    /// Drop a transaction that no longer needs processing; returns whether
+    /// it was queued
+    pub fn remove(&mut self, tx_id: &str) -> bool {
+        match self.entries.iter().position(|entry| entry.tx_id == tx_id) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove and return the next transaction to process
+    pub fn pop(&mut self) -> Option<String> {
+        let starving = self
+            .entries
+            .front()
+            .is_some_and(|entry| entry.bypassed >= self.config.starvation_limit);
+
+        let index = if starving {
+            self.metrics.starvation_promotions += 1;
+            0
+        } else {
+            // Earliest arrival wins among equal priorities
+            self.entries
+                .iter()
+                .enumerate()
+                .max_by(
+                    |(a_index, a), (b_index, b)| match a.priority.cmp(&b.priority) {
+                        Ordering::Equal => b_index.cmp(a_index),
+                        ordering => ordering,
+                    },
+                )
+                .map(|(index, _)| index)?
+        };
+
+        for entry in self.entries.iter_mut().take(index) {
+            entry.bypassed += 1;
+        }
+
+        let entry = self.entries.remove(index)?;
+        self.metrics.processed_total += 1;
+        Some(entry.tx_id)
+    }
+
+    /// Current queue depth and counters
+    pub fn metrics(&self) -> TransactionQueueMetrics {
+        let admin_depth = self
+            .entries
+            .iter()
+            .filter(|entry| entry.priority == TransactionPriority::Admin)
+            .count();
+
+        TransactionQueueMetrics {
+            depth: self.entries.len(),
+            capacity: self.config.capacity,
+            admin_depth,
+            tipped_depth: self.entries.len() - admin_depth,
+            oldest_enqueued_at: self.entries.front().map(|entry| entry.enqueued_at),
+            ..self.metrics.clone()
+        }
+    }
+}
+
+/// Default days a settled transaction stays in memory before archiving
+pub const DEFAULT_TRANSACTION_RETENTION_DAYS: u32 = 2555;
+
+/// Most missed occurrences of one standing order executed in a single pass;
+/// older ones are skipped, so a long outage does not drain the payer
+pub const DEFAULT_RECURRING_CATCH_UP_LIMIT: u32 = 10;
+
+/// Cold storage that archived transactions are moved to
+pub trait TransactionArchive: Send {
+    /// Durably store transactions; they are dropped from memory only if
+    /// this succeeds
+    fn archive(&mut self, transactions: &[Transaction]) -> Result<(), AstorError>;
+}
+
+/// Archive appending one JSON transaction per line to a file
+pub struct FileTransactionArchive {
+    path: PathBuf,
+    file: File,
+}
+
+impl FileTransactionArchive {
+    /// Open the archive at `path` for appending, creating it if missing
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AstorError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(archive_io_error)?;
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl TransactionArchive for FileTransactionArchive {
+    fn archive(&mut self, transactions: &[Transaction]) -> Result<(), AstorError> {
+        let mut buffer = Vec::new();
+        for transaction in transactions {
+            serde_json::to_writer(&mut buffer, transaction)?;
+            buffer.push(b'\n');
+        }
+
+        // A failed write may leave part of the batch behind; cut it off so a
+        // retried prune does not archive those transactions twice
+        let end = self.file.metadata().map_err(archive_io_error)?.len();
+        if let Err(e) = self
+            .file
+            .write_all(&buffer)
+            .and_then(|_| self.file.sync_data())
+        {
+            if let Err(truncate) = self.file.set_len(end) {
+                tracing::error!(
+                    "Could not discard partial write to transaction archive {}: {}",
+                    self.path.display(),
+                    truncate
+                );
+            }
+            return Err(archive_io_error(e));
+        }
+        Ok(())
+    }
+}
+
+fn archive_io_error(e: std::io::Error) -> AstorError {
+    AstorError::DatabaseError(format!("Transaction archive I/O failed: {}", e))
+}
+
+/// Integrator hook notified as transactions move through their lifecycle
+///
+/// Callbacks run synchronously on the transaction path, so they should hand
+/// slow work (network calls, analytics writes) off to a task of their own.
+/// Errors and panics are logged and never affect the transaction.
+pub trait TransactionObserver: Send + Sync {
+    /// The transaction was accepted, possibly into the sync queue
+    fn on_created(&self, _transaction: &Transaction) -> Result<(), AstorError> {
+        Ok(())
+    }
+
+    fn on_completed(&self, _transaction: &Transaction) -> Result<(), AstorError> {
+        Ok(())
+    }
+
+    fn on_failed(&self, _transaction: &Transaction, _reason: &str) -> Result<(), AstorError> {
+        Ok(())
+    }
+}
+
+fn new_transaction_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Invoke `callback` on every observer, isolating their errors and panics
+fn notify_observers(
+    observers: &[Arc<dyn TransactionObserver>],
+    event: &str,
+    transaction: &Transaction,
+    callback: impl Fn(&dyn TransactionObserver) -> Result<(), AstorError>,
+) {
+    for observer in observers {
+        match catch_unwind(AssertUnwindSafe(|| callback(observer.as_ref()))) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!(
+                "Transaction observer failed handling {} for {}: {}",
+                event,
+                transaction.id,
+                e
+            ),
+            Err(_) => tracing::error!(
+                "Transaction observer panicked handling {} for {}",
+                event,
+                transaction.id
+            ),
+        }
+    }
+}
+
+/// Running totals for an account's archived transactions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountSummary {
+    pub total_credits: u64,
+    pub total_debits: u64,
+    pub transaction_count: u64,
+    /// Timestamp of the newest transaction folded into this summary
+    pub archived_through: Option<DateTime<Utc>>,
+}
+
+impl AccountSummary {
+    fn record(&mut self, credit: u64, debit: u64, timestamp: DateTime<Utc>) {
+        self.total_credits = self.total_credits.saturating_add(credit);
+        self.total_debits = self.total_debits.saturating_add(debit);
+        self.transaction_count += 1;
+        self.archived_through = self.archived_through.max(Some(timestamp));
+    }
+}
+
+/// Outcome of a pruning pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    pub archived: usize,
+    pub retained: usize,
+    pub cutoff: Option<DateTime<Utc>>,
+}
+
+/// Cadence of a standing order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecurrenceRule {
+    Daily,
+    Weekly,
+    /// Same day of the month, clamped to the last day of shorter months
+    Monthly,
+    /// Fixed interval in seconds
+    Interval(u64),
+}
+
+impl RecurrenceRule {
+    /// Next occurrence after `from`
+    pub fn next_after(&self, from: DateTime<Utc>) -> DateTime<Utc> {
+        self.nth_after(from, 1)
+    }
+
+    /// Occurrence `n` cadences after `start`
+    ///
+    /// Counting from the start keeps monthly orders on their original day:
+    /// an order started on the 31st falls on the 30th in April and back on
+    /// the 31st in May.
+    pub fn nth_after(&self, start: DateTime<Utc>, n: u32) -> DateTime<Utc> {
+        let n = i64::from(n);
+        let offset = match self {
+            RecurrenceRule::Daily => Duration::try_days(n),
+            RecurrenceRule::Weekly => Duration::try_weeks(n),
+            RecurrenceRule::Monthly => {
+                return start
+                    .checked_add_months(Months::new(n as u32))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+            }
+            RecurrenceRule::Interval(seconds) => {
+                let seconds = (*seconds).clamp(1, i64::MAX as u64) as i64;
+                seconds.checked_mul(n).and_then(Duration::try_seconds)
+            }
+        };
+        offset
+            .and_then(|offset| start.checked_add_signed(offset))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+/// Lifecycle of a standing order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecurringStatus {
+    Active,
+    /// The end date has passed
+    Completed,
+    Cancelled,
+}
+
+/// Transfer repeated on a schedule until its end date or cancellation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurringTransfer {
+    pub id: Uuid,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    /// Most each occurrence pays in fees
+    #[serde(default)]
+    pub max_fee: u64,
+    pub rule: RecurrenceRule,
+    /// When the order was set up; occurrences are counted from here
+    pub start: DateTime<Utc>,
+    /// Index of `next_run` counting from `start`
+    pub occurrence: u32,
+    pub next_run: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub status: RecurringStatus,
+    pub executed_count: u64,
+    pub skipped_count: u64,
+}
+
+impl RecurringTransfer {
+    /// Move on to the next occurrence
+    fn advance(&mut self) {
+        self.occurrence = self.occurrence.saturating_add(1);
+        self.next_run = self.rule.nth_after(self.start, self.occurrence);
+    }
+}
+
+/// Outcome of one pass over due standing orders
+#[derive(Debug, Clone, Default)]
+pub struct RecurringRunReport {
+    /// IDs of the transfer transactions executed
+    pub executed: Vec<String>,
+    /// Occurrences skipped, with the reason
+    pub skipped: Vec<(Uuid, String)>,
+}
+
+/// Transfer awaiting the recipient's signed acknowledgment
+///
+/// The amount is held on the sender's account until the recipient
+/// acknowledges, and released back to the sender if the window lapses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingReceipt {
+    pub tx_id: String,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    pub hold_id: String,
+    pub expires_at: DateTime<Utc>,
 }
 
 /// Manages transaction creation and validation
 pub struct TransactionManager {
     transactions: Vec<Transaction>,
+    retention_days: u32,
+    account_summaries: HashMap<String, AccountSummary>,
+    node_syncing: bool,
+    sync_policy: SyncThrottlePolicy,
+    sync_queue: VecDeque<Transaction>,
+    processing_queue: TransactionQueue,
+    fee_market: FeeMarket,
+    recurring: HashMap<Uuid, RecurringTransfer>,
+    recurring_catch_up_limit: u32,
+    /// Acknowledged transfers awaiting receipt, by transaction ID
+    pending_receipts: HashMap<String, PendingReceipt>,
+    observers: Vec<Arc<dyn TransactionObserver>>,
+    /// Minimum visibility of transactions involving each account
+    account_visibility: HashMap<String, TransactionVisibility>,
 }
 
 impl TransactionManager {
@@ -56,10 +740,161 @@ impl TransactionManager {
     pub fn new() -> Self {
         Self {
             transactions: Vec::new(),
+            retention_days: DEFAULT_TRANSACTION_RETENTION_DAYS,
+            account_summaries: HashMap::new(),
+            node_syncing: false,
+            sync_policy: SyncThrottlePolicy::default(),
+            sync_queue: VecDeque::new(),
+            processing_queue: TransactionQueue::new(TransactionQueueConfig::default()),
+            fee_market: FeeMarket::default(),
+            recurring: HashMap::new(),
+            recurring_catch_up_limit: DEFAULT_RECURRING_CATCH_UP_LIMIT,
+            pending_receipts: HashMap::new(),
+            observers: Vec::new(),
+            account_visibility: HashMap::new(),
+        }
+    }
+
+    /// Restrict every later transaction involving `account_id`, such as a
+    /// system account, to at least `visibility`
+    pub fn set_account_visibility(&mut self, account_id: &str, visibility: TransactionVisibility) {
+        self.account_visibility
+            .insert(account_id.to_string(), visibility);
+    }
+
+    /// Change who may see a single transaction
+    pub fn set_transaction_visibility(
+        &mut self,
+        tx_id: &str,
+        visibility: TransactionVisibility,
+    ) -> Result<(), AstorError> {
+        let transaction = self
+            .transactions
+            .iter_mut()
+            .chain(self.sync_queue.iter_mut())
+            .find(|t| t.id == tx_id)
+            .ok_or_else(|| {
+                AstorError::TransactionValidationFailed("Transaction not found".to_string())
+            })?;
+        transaction.visibility = visibility;
+        Ok(())
+    }
+
+    /// Visibility a new transaction gets from the accounts it involves
+    fn visibility_for(&self, transaction_type: &TransactionType) -> TransactionVisibility {
+        parties(transaction_type)
+            .into_iter()
+            .filter_map(|account| self.account_visibility.get(account).copied())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Register an observer for transaction lifecycle events
+    pub fn add_observer(&mut self, observer: Arc<dyn TransactionObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Configure how the base fee reacts to block fullness
+    pub fn set_fee_market_config(&mut self, config: FeeMarketConfig) {
+        self.fee_market.set_config(config);
+    }
+
+    /// Minimum tip a transfer must offer to be accepted
+    pub fn current_base_fee(&self) -> u64 {
+        self.fee_market.current_base_fee()
+    }
+
+    /// Adjust the base fee after a block of `transaction_count` transactions
+    pub fn record_block(&mut self, transaction_count: usize) -> u64 {
+        self.fee_market.record_block(transaction_count)
+    }
+
+    /// Configure the bounded processing queue
+    pub fn set_queue_config(&mut self, config: TransactionQueueConfig) {
+        self.processing_queue.set_config(config);
+    }
+
+    /// Get processing queue depth metrics
+    pub fn queue_metrics(&self) -> TransactionQueueMetrics {
+        self.processing_queue.metrics()
+    }
+
+    /// Take the next pending transaction to process, highest priority first
+    ///
+    /// Transactions that left `Pending` while queued (held, expired, ...)
+    /// are dropped from the queue.
+    pub fn next_transaction_to_process(&mut self) -> Option<String> {
+        while let Some(tx_id) = self.processing_queue.pop() {
+            if self
+                .get_transaction(&tx_id)
+                .is_some_and(|tx| tx.status == TransactionStatus::Pending)
+            {
+                return Some(tx_id);
+            }
+        }
+        None
+    }
+
+    /// Set how new transactions are handled while the node is syncing
+    pub fn set_sync_policy(&mut self, policy: SyncThrottlePolicy) {
+        self.sync_policy = policy;
+    }
+
+    /// Update the node sync state
+    ///
+    /// When the node becomes synced, transactions queued during sync are
+    /// accepted in arrival order.
+    pub fn set_node_syncing(&mut self, syncing: bool) {
+        self.node_syncing = syncing;
+
+        if !syncing && !self.sync_queue.is_empty() {
+            tracing::info!(
+                "Node synced; accepting {} queued transactions",
+                self.sync_queue.len()
+            );
+            // Already admitted under the queue policy, so not subject to the
+            // processing queue bound
+            for transaction in self.sync_queue.drain(..) {
+                self.processing_queue
+                    .push(&transaction.id, transaction.priority);
+                self.transactions.push(transaction);
+            }
+        }
+    }
+
+    /// Check whether the node is currently syncing
+    pub fn is_node_syncing(&self) -> bool {
+        self.node_syncing
+    }
+
+    /// Get transactions held back while the node is syncing
+    pub fn get_queued_transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.sync_queue.iter()
+    }
+
+    /// Accept a new transaction, honouring the sync throttle policy
+    fn submit_transaction(&mut self, transaction: Transaction) -> Result<(), AstorError> {
+        if !self.node_syncing {
+            self.processing_queue
+                .try_push(&transaction.id, transaction.priority)?;
+        } else if self.sync_policy == SyncThrottlePolicy::Reject {
+            return Err(AstorError::NodeSyncing);
+        }
+
+        notify_observers(&self.observers, "creation", &transaction, |observer| {
+            observer.on_created(&transaction)
+        });
+        if self.node_syncing {
+            self.sync_queue.push_back(transaction);
+        } else {
+            self.transactions.push(transaction);
         }
+        Ok(())
     }
 
     /// Create an issuance transaction
+    ///
+    /// Issuance is central bank work and is processed with admin priority.
     pub fn create_issuance(
         &mut self,
         issuer: &str,
@@ -75,25 +910,102 @@ impl TransactionManager {
         };
 
         let transaction = Transaction {
+            schema_version: Transaction::SCHEMA_VERSION,
             id: tx_id.clone(),
             transaction_type: transaction_type.clone(),
             timestamp: Utc::now(),
             status: TransactionStatus::Pending,
             hash: self.calculate_transaction_hash(&tx_id, &transaction_type),
+            status_history: Vec::new(),
+            priority: TransactionPriority::Admin,
+            memo: None,
+            signature: None,
+            visibility: self.visibility_for(&transaction_type),
+            fee: 0,
         };
 
-        self.transactions.push(transaction);
+        self.submit_transaction(transaction)?;
         Ok(tx_id)
     }
 
-    /// Create a transfer transaction
+    /// Create a transfer paying whatever the base fee currently is
+    ///
+    /// Use `create_transfer_with_priority` to cap what the sender pays.
     pub fn create_transfer(
         &mut self,
         from: &str,
         to: &str,
         amount: u64,
     ) -> Result<String, AstorError> {
-        let tx_id = Uuid::new_v4().to_string();
+        let priority = TransactionPriority::Tip(self.current_base_fee());
+        self.create_transfer_with_priority(from, to, amount, priority)
+    }
+
+    /// Create a transfer transaction with an explicit processing priority
+    pub fn create_transfer_with_priority(
+        &mut self,
+        from: &str,
+        to: &str,
+        amount: u64,
+        priority: TransactionPriority,
+    ) -> Result<String, AstorError> {
+        self.submit_transfer(new_transaction_id(), from, to, amount, priority, None)
+    }
+
+    /// Create a transfer under a caller-chosen ID, such as the one a holder
+    /// signed in a transfer authorization, paying at most `max_fee`
+    pub fn create_transfer_with_id(
+        &mut self,
+        tx_id: &str,
+        from: &str,
+        to: &str,
+        amount: u64,
+        max_fee: u64,
+    ) -> Result<String, AstorError> {
+        if self.get_transaction(tx_id).is_some() || self.sync_queue.iter().any(|t| t.id == tx_id) {
+            return Err(AstorError::TransactionValidationFailed(format!(
+                "Transaction {} already exists",
+                tx_id
+            )));
+        }
+
+        let priority = TransactionPriority::Tip(max_fee);
+        self.submit_transfer(tx_id.to_string(), from, to, amount, priority, None)
+    }
+
+    /// Create a transfer carrying a memo, paying whatever the base fee
+    /// currently is
+    pub fn create_transfer_with_memo(
+        &mut self,
+        from: &str,
+        to: &str,
+        amount: u64,
+        memo: TransactionMemo,
+    ) -> Result<String, AstorError> {
+        let priority = TransactionPriority::Tip(self.current_base_fee());
+        self.submit_transfer(new_transaction_id(), from, to, amount, priority, Some(memo))
+    }
+
+    fn submit_transfer(
+        &mut self,
+        tx_id: String,
+        from: &str,
+        to: &str,
+        amount: u64,
+        priority: TransactionPriority,
+        memo: Option<TransactionMemo>,
+    ) -> Result<String, AstorError> {
+        // Admin-flagged transfers are exempt from the fee market
+        let fee = match priority {
+            TransactionPriority::Tip(offered) => {
+                let required = self.current_base_fee();
+                if offered < required {
+                    return Err(AstorError::FeeTooLow { offered, required });
+                }
+                required
+            }
+            TransactionPriority::Admin => 0,
+        };
 
         let transaction_type = TransactionType::Transfer {
             from: from.to_string(),
@@ -102,55 +1014,1510 @@ impl TransactionManager {
         };
 
         let transaction = Transaction {
+            schema_version: Transaction::SCHEMA_VERSION,
             id: tx_id.clone(),
             transaction_type: transaction_type.clone(),
             timestamp: Utc::now(),
             status: TransactionStatus::Pending,
             hash: self.calculate_transaction_hash(&tx_id, &transaction_type),
+            status_history: Vec::new(),
+            priority,
+            memo,
+            signature: None,
+            visibility: self.visibility_for(&transaction_type),
+            fee,
         };
 
-        self.transactions.push(transaction);
+        self.submit_transaction(transaction)?;
         Ok(tx_id)
     }
 
-    /// Confirm a transaction
-    pub fn confirm_transaction(&mut self, tx_id: &str) -> Result<(), AstorError> {
-        if let Some(tx) = self.transactions.iter_mut().find(|t| t.id == tx_id) {
-            tx.status = TransactionStatus::Confirmed;
-            Ok(())
-        } else {
-            Err(AstorError::TransactionValidationFailed(
-                "Transaction not found".to_string(),
-            ))
-        }
+    /// Set up a standing order; the first transfer is due one cadence from now
+    ///
+    /// Each occurrence pays the base fee of the day, up to `max_fee`; one
+    /// whose fee would be higher is skipped.
+    pub fn schedule_recurring(
+        &mut self,
+        from: &str,
+        to: &str,
+        amount: u64,
+        max_fee: u64,
+        schedule: RecurrenceRule,
+        end: Option<DateTime<Utc>>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        let start = Utc::now();
+        let next_run = schedule.next_after(start);
+        self.recurring.insert(
+            id,
+            RecurringTransfer {
+                id,
+                from: from.to_string(),
+                to: to.to_string(),
+                amount,
+                max_fee,
+                rule: schedule,
+                start,
+                occurrence: 1,
+                next_run,
+                end,
+                status: RecurringStatus::Active,
+                executed_count: 0,
+                skipped_count: 0,
+            },
+        );
+
+        tracing::info!(
+            "Scheduled recurring transfer {} of {} from {} to {}",
+            id,
+            amount,
+            from,
+            to
+        );
+        id
     }
 
-    /// Fail a transaction
-    pub fn fail_transaction(&mut self, tx_id: &str, reason: String) -> Result<(), AstorError> {
-        if let Some(tx) = self.transactions.iter_mut().find(|t| t.id == tx_id) {
-            tx.status = TransactionStatus::Failed(reason);
-            Ok(())
-        } else {
-            Err(AstorError::TransactionValidationFailed(
-                "Transaction not found".to_string(),
-            ))
+    /// Stop a standing order; transfers already executed are unaffected
+    pub fn cancel_recurring(&mut self, id: Uuid) -> Result<(), AstorError> {
+        let recurring = self.recurring.get_mut(&id).ok_or_else(|| {
+            AstorError::InvalidOperation(format!("Recurring transfer not found: {}", id))
+        })?;
+        if recurring.status != RecurringStatus::Active {
+            return Err(AstorError::InvalidOperation(format!(
+                "Recurring transfer {} is already {:?}",
+                id, recurring.status
+            )));
         }
+
+        recurring.status = RecurringStatus::Cancelled;
+        tracing::info!("Cancelled recurring transfer {}", id);
+        Ok(())
     }
 
-    /// Get transaction by ID
-    pub fn get_transaction(&self, tx_id: &str) -> Option<&Transaction> {
-        self.transactions.iter().find(|t| t.id == tx_id)
+    pub fn get_recurring(&self, id: Uuid) -> Option<&RecurringTransfer> {
+        self.recurring.get(&id)
     }
 
-    /// Get all transactions
-    pub fn get_all_transactions(&self) -> &[Transaction] {
-        &self.transactions
+    /// Most missed occurrences of one standing order executed in a pass
+    pub fn set_recurring_catch_up_limit(&mut self, limit: u32) {
+        self.recurring_catch_up_limit = limit;
     }
 
-    /// Calculate transaction hash for integrity
-    fn calculate_transaction_hash(&self, tx_id: &str, tx_type: &TransactionType) -> String {
-        use crate::security::hash_data;
-        let data = format!("{}{:?}", tx_id, tx_type);
-        hash_data(data.as_bytes())
+    /// Execute every standing-order occurrence due by `now`
+    ///
+    /// Occurrences missed since the last tick are executed in turn, up to
+    /// the catch-up limit per order; older missed occurrences are skipped.
+    /// One that cannot be paid, for lack of funds or otherwise, is skipped
+    /// with an alert and the order stays active for the next occurrence.
+    /// Nothing runs while the node is syncing; due occurrences wait for the
+    /// next tick.
+    ///
+    /// Once an occurrence's funds have moved, `record` writes its transfer to
+    /// the ledger and charges its fee; if it fails, it must undo the move
+    /// and the occurrence is skipped.
+    pub fn process_due_recurring(
+        &mut self,
+        now: DateTime<Utc>,
+        accounts: &AccountManager,
+        mut record: impl FnMut(&Transaction) -> Result<(), AstorError>,
+    ) -> RecurringRunReport {
+        let mut report = RecurringRunReport::default();
+        if self.node_syncing {
+            return report;
+        }
+
+        let mut due: Vec<Uuid> = self
+            .recurring
+            .values()
+            .filter(|r| r.status == RecurringStatus::Active && r.next_run <= now)
+            .map(|r| r.id)
+            .collect();
+        due.sort_by_key(|id| self.recurring[id].next_run);
+
+        for id in due {
+            self.skip_missed_beyond_catch_up(id, now, &mut report);
+            loop {
+                let recurring = self.recurring[&id].clone();
+                if recurring.next_run > now {
+                    break;
+                }
+                if recurring.end.is_some_and(|end| recurring.next_run > end) {
+                    self.recurring.get_mut(&id).unwrap().status = RecurringStatus::Completed;
+                    break;
+                }
+
+                match self.execute_recurring(&recurring, accounts, &mut record) {
+                    Ok(tx_id) => {
+                        self.recurring.get_mut(&id).unwrap().executed_count += 1;
+                        report.executed.push(tx_id);
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "ALERT: recurring transfer {} from {} skipped: {}",
+                            id,
+                            recurring.from,
+                            e
+                        );
+                        self.recurring.get_mut(&id).unwrap().skipped_count += 1;
+                        report.skipped.push((id, e.to_string()));
+                    }
+                }
+
+                self.recurring.get_mut(&id).unwrap().advance();
+            }
+        }
+
+        report
+    }
+
+    /// Skip the oldest due occurrences of a standing order so that at most
+    /// the catch-up limit of them remain
+    fn skip_missed_beyond_catch_up(
+        &mut self,
+        id: Uuid,
+        now: DateTime<Utc>,
+        report: &mut RecurringRunReport,
+    ) {
+        let limit = self.recurring_catch_up_limit;
+        let recurring = self.recurring.get_mut(&id).unwrap();
+        let last_due = recurring.end.map_or(now, |end| end.min(now));
+
+        // While `limit` more occurrences after the next one are still due,
+        // the next one is too old to execute
+        let mut skipped = 0u64;
+        while recurring.occurrence < u32::MAX
+            && recurring
+                .rule
+                .nth_after(recurring.start, recurring.occurrence.saturating_add(limit))
+                <= last_due
+        {
+            recurring.advance();
+            skipped += 1;
+        }
+        if skipped == 0 {
+            return;
+        }
+
+        recurring.skipped_count += skipped;
+        tracing::warn!(
+            "ALERT: recurring transfer {} from {} missed {} occurrences; only the last {} are executed",
+            id,
+            recurring.from,
+            skipped,
+            limit
+        );
+        report.skipped.push((
+            id,
+            format!("{} missed occurrences exceeded the catch-up limit", skipped),
+        ));
+    }
+
+    /// Record one occurrence as a transfer and move the funds
+    fn execute_recurring(
+        &mut self,
+        recurring: &RecurringTransfer,
+        accounts: &AccountManager,
+        record: &mut impl FnMut(&Transaction) -> Result<(), AstorError>,
+    ) -> Result<String, AstorError> {
+        let source = accounts.get_account(&recurring.from)?;
+        if source.available_balance() < recurring.amount {
+            return Err(AstorError::InsufficientFunds);
+        }
+
+        let priority = TransactionPriority::Tip(recurring.max_fee);
+        let tx_id = self.submit_transfer(
+            new_transaction_id(),
+            &recurring.from,
+            &recurring.to,
+            recurring.amount,
+            priority,
+            None,
+        )?;
+
+        // Any change to the source since the check above fails the transfer
+        // with `ConcurrentModification` instead of acting on a stale balance,
+        // and the occurrence is skipped
+        match accounts
+            .transfer_at_version(
+                &recurring.from,
+                &recurring.to,
+                recurring.amount,
+                source.version,
+                false,
+            )
+            .and_then(|()| match self.get_transaction(&tx_id) {
+                Some(transaction) => record(transaction),
+                None => Err(AstorError::InvalidOperation(format!(
+                    "Transaction {} not found",
+                    tx_id
+                ))),
+            }) {
+            Ok(()) => {
+                self.confirm_transaction(&tx_id)?;
+                Ok(tx_id)
+            }
+            Err(e) => {
+                self.fail_transaction(&tx_id, e.to_string())?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Start a transfer that completes only once the recipient acknowledges it
+    ///
+    /// The amount is held on the sender's account rather than moved. The
+    /// recipient must sign `receipt_acknowledgment_message(tx_id)` within
+    /// `window`; otherwise `expire_unacknowledged` reverses the transfer.
+    /// The sender pays the base fee at submission, up to `max_fee`, when the
+    /// transfer settles.
+    pub fn create_acknowledged_transfer(
+        &mut self,
+        from: &str,
+        to: &str,
+        amount: u64,
+        max_fee: u64,
+        window: Duration,
+        accounts: &AccountManager,
+    ) -> Result<String, AstorError> {
+        if !accounts.account_exists(to) {
+            return Err(AstorError::AccountNotFound(to.to_string()));
+        }
+
+        let priority = TransactionPriority::Tip(max_fee);
+        let tx_id = self.submit_transfer(new_transaction_id(), from, to, amount, priority, None)?;
+        // Out of the processing queue, so only acknowledgment or expiry
+        // settles it
+        self.transition(&tx_id, TransactionStatus::AwaitingReceipt)?;
+
+        let reason = format!("Awaiting receipt acknowledgment for {}", tx_id);
+        let hold_id = match accounts.place_hold(from, amount, &reason) {
+            Ok(hold_id) => hold_id,
+            Err(e) => {
+                self.fail_transaction(&tx_id, e.to_string())?;
+                return Err(e);
+            }
+        };
+
+        self.pending_receipts.insert(
+            tx_id.clone(),
+            PendingReceipt {
+                tx_id: tx_id.clone(),
+                from: from.to_string(),
+                to: to.to_string(),
+                amount,
+                hold_id,
+                expires_at: Utc::now() + window,
+            },
+        );
+        Ok(tx_id)
+    }
+
+    pub fn get_pending_receipt(&self, tx_id: &str) -> Option<&PendingReceipt> {
+        self.pending_receipts.get(tx_id)
+    }
+
+    /// Finalize an acknowledged transfer on the recipient's signature
+    ///
+    /// Once the held amount has moved, `record` writes the transfer to the
+    /// ledger and charges its fee; if it fails, it must undo the move and the
+    /// transfer fails.
+    pub fn acknowledge_receipt(
+        &mut self,
+        tx_id: &str,
+        signature: &Signature,
+        accounts: &AccountManager,
+        record: impl FnOnce(&Transaction) -> Result<(), AstorError>,
+    ) -> Result<(), AstorError> {
+        if self.node_syncing {
+            return Err(AstorError::NodeSyncing);
+        }
+
+        let pending = self.pending_receipts.get(tx_id).ok_or_else(|| {
+            AstorError::InvalidOperation(format!("No transfer awaiting receipt: {}", tx_id))
+        })?;
+        if Utc::now() > pending.expires_at {
+            return Err(AstorError::InvalidOperation(format!(
+                "Acknowledgment window for {} has closed",
+                tx_id
+            )));
+        }
+        accounts.verify_receipt_acknowledgment(&pending.to, tx_id, signature)?;
+
+        let pending = self.pending_receipts.remove(tx_id).unwrap();
+        match accounts.transfer_held(&pending.from, &pending.hold_id, &pending.to) {
+            Ok(_) => match self.get_transaction(tx_id).map_or_else(
+                || {
+                    Err(AstorError::InvalidOperation(format!(
+                        "Transaction {} not found",
+                        tx_id
+                    )))
+                },
+                record,
+            ) {
+                Ok(()) => self.confirm_transaction(tx_id),
+                Err(e) => {
+                    self.fail_transaction(tx_id, e.to_string())?;
+                    Err(e)
+                }
+            },
+            Err(e) => {
+                // The hold may still exist if the transfer itself was refused
+                let _ = accounts.release_hold(&pending.from, &pending.hold_id);
+                self.fail_transaction(tx_id, e.to_string())?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Reverse acknowledged transfers whose window closed before `now`,
+    /// returning their transaction IDs
+    pub fn expire_unacknowledged(
+        &mut self,
+        now: DateTime<Utc>,
+        accounts: &AccountManager,
+    ) -> Vec<String> {
+        let mut expired: Vec<PendingReceipt> = self
+            .pending_receipts
+            .values()
+            .filter(|pending| pending.expires_at < now)
+            .cloned()
+            .collect();
+        expired.sort_by_key(|pending| pending.expires_at);
+
+        for pending in &expired {
+            self.pending_receipts.remove(&pending.tx_id);
+            if let Err(e) = accounts.release_hold(&pending.from, &pending.hold_id) {
+                tracing::warn!(
+                    "Could not release hold {} for unacknowledged transfer {}: {}",
+                    pending.hold_id,
+                    pending.tx_id,
+                    e
+                );
+            }
+            if let Err(e) = self.reverse_transaction(
+                &pending.tx_id,
+                "Recipient did not acknowledge receipt".to_string(),
+            ) {
+                tracing::warn!("Could not reverse transfer {}: {}", pending.tx_id, e);
+            }
+        }
+
+        expired.into_iter().map(|pending| pending.tx_id).collect()
+    }
+
+    /// Confirm a transaction
+    pub fn confirm_transaction(&mut self, tx_id: &str) -> Result<(), AstorError> {
+        // Finalizing against stale state could conflict with incoming blocks
+        if self.node_syncing {
+            return Err(AstorError::NodeSyncing);
+        }
+
+        self.transition(tx_id, TransactionStatus::Completed)
+    }
+
+    /// Fail a transaction
+    pub fn fail_transaction(&mut self, tx_id: &str, reason: String) -> Result<(), AstorError> {
+        self.transition(tx_id, TransactionStatus::Failed(reason))
+    }
+
+    /// Hold a pending transaction for review
+    pub fn hold_transaction(&mut self, tx_id: &str, reason: String) -> Result<(), AstorError> {
+        self.transition(tx_id, TransactionStatus::Held(reason))
+    }
+
+    /// Release a held transaction back to pending and requeue it
+    pub fn release_transaction(&mut self, tx_id: &str) -> Result<(), AstorError> {
+        self.transition(tx_id, TransactionStatus::Pending)?;
+
+        // Held transactions are dropped from the processing queue
+        if let Some(priority) = self.get_transaction(tx_id).map(|tx| tx.priority) {
+            if !self.processing_queue.contains(tx_id) {
+                self.processing_queue.push(tx_id, priority);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reverse a transaction
+    pub fn reverse_transaction(&mut self, tx_id: &str, reason: String) -> Result<(), AstorError> {
+        self.transition(tx_id, TransactionStatus::Reversed(reason))
+    }
+
+    /// Expire a transaction that was not completed in time
+    pub fn expire_transaction(&mut self, tx_id: &str) -> Result<(), AstorError> {
+        self.transition(tx_id, TransactionStatus::Expired)
+    }
+
+    /// Get the current status of a transaction
+    pub fn get_transaction_status(&self, tx_id: &str) -> Result<&TransactionStatus, AstorError> {
+        self.get_transaction(tx_id)
+            .map(|tx| &tx.status)
+            .ok_or_else(|| {
+                AstorError::TransactionValidationFailed("Transaction not found".to_string())
+            })
+    }
+
+    /// Move a transaction to a new status, recording the transition
+    fn transition(&mut self, tx_id: &str, status: TransactionStatus) -> Result<(), AstorError> {
+        let tx = self
+            .transactions
+            .iter_mut()
+            .find(|t| t.id == tx_id)
+            .ok_or_else(|| {
+                AstorError::TransactionValidationFailed("Transaction not found".to_string())
+            })?;
+
+        if !tx.status.can_transition_to(&status) {
+            return Err(AstorError::TransactionValidationFailed(format!(
+                "Invalid status transition {:?} -> {:?}",
+                tx.status, status
+            )));
+        }
+
+        tx.status_history.push(StatusTransition {
+            from: tx.status.clone(),
+            to: status.clone(),
+            at: Utc::now(),
+        });
+        tx.status = status;
+        // Only pending transactions wait for processing
+        if tx.status != TransactionStatus::Pending {
+            self.processing_queue.remove(tx_id);
+        }
+
+        let tx = &*tx;
+        match &tx.status {
+            TransactionStatus::Completed => {
+                notify_observers(&self.observers, "completion", tx, |observer| {
+                    observer.on_completed(tx)
+                })
+            }
+            TransactionStatus::Failed(reason) => {
+                notify_observers(&self.observers, "failure", tx, |observer| {
+                    observer.on_failed(tx, reason)
+                })
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Get transaction by ID
+    pub fn get_transaction(&self, tx_id: &str) -> Option<&Transaction> {
+        self.transactions.iter().find(|t| t.id == tx_id)
+    }
+
+    /// Get all transactions
+    pub fn get_all_transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// Get a transaction by ID if `role` may see it
+    pub fn get_transaction_for(&self, tx_id: &str, role: &Role) -> Option<&Transaction> {
+        self.get_transaction(tx_id)
+            .filter(|t| t.visibility.is_visible_to(role))
+    }
+
+    /// Whether `role` may see a ledger entry
+    ///
+    /// Entries recording a transaction follow its visibility; other entries
+    /// follow the visibility of the accounts they move funds for.
+    pub fn ledger_entry_visible_to(&self, entry: &LedgerEntry, role: &Role) -> bool {
+        let visibility = match entry
+            .entry_type
+            .transaction_id()
+            .and_then(|tx_id| self.get_transaction(tx_id))
+        {
+            Some(transaction) => transaction.visibility,
+            None => entry
+                .entry_type
+                .legs()
+                .into_iter()
+                .filter_map(|(_, account, _)| self.account_visibility.get(account).copied())
+                .max()
+                .unwrap_or_default(),
+        };
+        visibility.is_visible_to(role)
+    }
+
+    /// Transactions `role` may see, oldest first
+    pub fn transactions_visible_to(&self, role: &Role) -> Vec<&Transaction> {
+        self.transactions
+            .iter()
+            .filter(|t| t.visibility.is_visible_to(role))
+            .collect()
+    }
+
+    /// Transactions involving `account_id` that `role` may see, oldest first
+    pub fn account_statement(&self, account_id: &str, role: &Role) -> Vec<&Transaction> {
+        self.transactions
+            .iter()
+            .filter(|t| t.visibility.is_visible_to(role))
+            .filter(|t| parties(&t.transaction_type).contains(&account_id))
+            .collect()
+    }
+
+    /// Set how long settled transactions are kept in memory, typically
+    /// `ComplianceConfig::data_retention_days`
+    pub fn set_retention_days(&mut self, retention_days: u32) {
+        self.retention_days = retention_days;
+    }
+
+    /// Move settled transactions older than the retention period to cold
+    /// storage, folding completed ones into per-account summaries
+    ///
+    /// Pending and held transactions are never archived. If the archive
+    /// fails nothing is removed.
+    pub fn prune_transactions(
+        &mut self,
+        archive: &mut dyn TransactionArchive,
+    ) -> Result<PruneReport, AstorError> {
+        let cutoff = Utc::now() - chrono::Duration::days(self.retention_days as i64);
+
+        let (expired, retained): (Vec<Transaction>, Vec<Transaction>) =
+            std::mem::take(&mut self.transactions)
+                .into_iter()
+                .partition(|tx| {
+                    tx.timestamp < cutoff
+                        && (tx.status == TransactionStatus::Completed || tx.status.is_final())
+                });
+        self.transactions = retained;
+
+        if expired.is_empty() {
+            return Ok(PruneReport {
+                archived: 0,
+                retained: self.transactions.len(),
+                cutoff: Some(cutoff),
+            });
+        }
+
+        if let Err(e) = archive.archive(&expired) {
+            self.transactions.extend(expired);
+            self.transactions.sort_by_key(|tx| tx.timestamp);
+            return Err(e);
+        }
+
+        for tx in &expired {
+            if tx.status == TransactionStatus::Completed {
+                for (account, credit, debit) in balance_effects(&tx.transaction_type) {
+                    self.account_summaries
+                        .entry(account.to_string())
+                        .or_default()
+                        .record(credit, debit, tx.timestamp);
+                }
+            }
+        }
+
+        tracing::info!(
+            "Archived {} transactions older than {}",
+            expired.len(),
+            cutoff
+        );
+        Ok(PruneReport {
+            archived: expired.len(),
+            retained: self.transactions.len(),
+            cutoff: Some(cutoff),
+        })
+    }
+
+    /// Totals of an account's archived transactions
+    pub fn account_summary(&self, account_id: &str) -> Option<&AccountSummary> {
+        self.account_summaries.get(account_id)
+    }
+
+    /// Balance implied by completed transactions, archived and in memory
+    pub fn compute_balance(&self, account_id: &str) -> u64 {
+        let (mut credits, mut debits) = self
+            .account_summaries
+            .get(account_id)
+            .map_or((0u64, 0u64), |s| (s.total_credits, s.total_debits));
+
+        for tx in self
+            .transactions
+            .iter()
+            .filter(|tx| tx.status == TransactionStatus::Completed)
+        {
+            for (account, credit, debit) in balance_effects(&tx.transaction_type) {
+                if account == account_id {
+                    credits = credits.saturating_add(credit);
+                    debits = debits.saturating_add(debit);
+                }
+            }
+        }
+
+        credits.saturating_sub(debits)
+    }
+
+    /// Calculate transaction hash for integrity
+    fn calculate_transaction_hash(&self, tx_id: &str, tx_type: &TransactionType) -> String {
+        transaction_hash(tx_id, tx_type)
+    }
+
+    /// Verify a transaction's signature against the key registered on its
+    /// originating account
+    pub fn verify_transaction_signature(
+        &self,
+        tx_id: &str,
+        accounts: &AccountManager,
+        verifier: &SignatureVerifier,
+    ) -> Result<(), AstorError> {
+        let transaction = self.get_transaction(tx_id).ok_or_else(|| {
+            AstorError::TransactionValidationFailed("Transaction not found".to_string())
+        })?;
+        let originator = transaction.originator().ok_or_else(|| {
+            AstorError::InvalidOperation(format!(
+                "Transaction {} is not signed by an account holder",
+                tx_id
+            ))
+        })?;
+        let public_key = accounts
+            .get_account(originator)?
+            .public_key
+            .ok_or_else(|| {
+                AstorError::Unauthorized("Account has no public key for verification".to_string())
+            })?;
+        transaction.verify_signature(verifier, &public_key)
+    }
+}
+
+fn transaction_hash(tx_id: &str, tx_type: &TransactionType) -> String {
+    use crate::security::hash_data;
+    let data = format!("{}{:?}", tx_id, tx_type);
+    hash_data(data.as_bytes())
+}
+
+/// Accounts a transaction involves
+fn parties(transaction_type: &TransactionType) -> Vec<&str> {
+    match transaction_type {
+        TransactionType::Issuance {
+            issuer, recipient, ..
+        } => vec![issuer.as_str(), recipient.as_str()],
+        TransactionType::Transfer { from, to, .. } => vec![from.as_str(), to.as_str()],
+        TransactionType::Conversion { account, .. } => vec![account.as_str()],
+    }
+}
+
+/// Default-currency `(account, credit, debit)` legs of a transaction
+fn balance_effects(transaction_type: &TransactionType) -> Vec<(&str, u64, u64)> {
+    match transaction_type {
+        TransactionType::Issuance {
+            recipient, amount, ..
+        } => vec![(recipient.as_str(), *amount, 0)],
+        TransactionType::Transfer { from, to, amount } => {
+            vec![(from.as_str(), 0, *amount), (to.as_str(), *amount, 0)]
+        }
+        // Conversions move value between currencies of a single account
+        TransactionType::Conversion { .. } => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate_authority::csr::KeyAlgorithm;
+    use crate::security::KeyPair;
+
+    #[test]
+    fn test_transactions_rejected_while_syncing() {
+        let mut manager = TransactionManager::new();
+        manager.set_node_syncing(true);
+
+        let result = manager.create_transfer("alice", "bob", 100);
+        assert!(matches!(result, Err(AstorError::NodeSyncing)));
+        assert!(manager.get_all_transactions().is_empty());
+
+        manager.set_node_syncing(false);
+
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+        assert!(manager.confirm_transaction(&tx_id).is_ok());
+    }
+
+    #[test]
+    fn test_transactions_queued_while_syncing() {
+        let mut manager = TransactionManager::new();
+        manager.set_sync_policy(SyncThrottlePolicy::Queue);
+        manager.set_node_syncing(true);
+
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+        assert!(manager.get_transaction(&tx_id).is_none());
+        assert_eq!(manager.get_queued_transactions().count(), 1);
+        assert!(matches!(
+            manager.confirm_transaction(&tx_id),
+            Err(AstorError::NodeSyncing)
+        ));
+
+        manager.set_node_syncing(false);
+
+        assert!(manager.get_transaction(&tx_id).is_some());
+        assert_eq!(manager.get_queued_transactions().count(), 0);
+        assert!(manager.confirm_transaction(&tx_id).is_ok());
+    }
+
+    #[test]
+    fn test_held_then_released_records_transitions() {
+        let mut manager = TransactionManager::new();
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+
+        manager
+            .hold_transaction(&tx_id, "compliance review".to_string())
+            .unwrap();
+        assert_eq!(
+            manager.get_transaction_status(&tx_id).unwrap(),
+            &TransactionStatus::Held("compliance review".to_string())
+        );
+
+        manager.release_transaction(&tx_id).unwrap();
+        manager.confirm_transaction(&tx_id).unwrap();
+        assert_eq!(
+            manager.get_transaction_status(&tx_id).unwrap(),
+            &TransactionStatus::Completed
+        );
+
+        let history = &manager.get_transaction(&tx_id).unwrap().status_history;
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].from, TransactionStatus::Pending);
+        assert_eq!(
+            history[0].to,
+            TransactionStatus::Held("compliance review".to_string())
+        );
+        assert_eq!(history[1].to, TransactionStatus::Pending);
+        assert_eq!(history[2].to, TransactionStatus::Completed);
+        assert!(history[0].at <= history[1].at && history[1].at <= history[2].at);
+    }
+
+    #[test]
+    fn test_invalid_status_transitions_rejected() {
+        let mut manager = TransactionManager::new();
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+
+        // Only held transactions can be released
+        assert!(manager.release_transaction(&tx_id).is_err());
+
+        manager.expire_transaction(&tx_id).unwrap();
+        assert!(manager.confirm_transaction(&tx_id).is_err());
+        assert!(manager
+            .reverse_transaction(&tx_id, "chargeback".to_string())
+            .is_err());
+        assert_eq!(
+            manager
+                .get_transaction(&tx_id)
+                .unwrap()
+                .status_history
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_high_priority_jumps_ahead_without_starving_low_priority() {
+        let mut manager = TransactionManager::new();
+        manager.set_queue_config(TransactionQueueConfig {
+            capacity: 100,
+            starvation_limit: 2,
+        });
+
+        let low = manager.create_transfer("alice", "bob", 10).unwrap();
+        let high = manager
+            .create_transfer_with_priority("carol", "dave", 10, TransactionPriority::Tip(50))
+            .unwrap();
+        assert_eq!(manager.next_transaction_to_process().unwrap(), high);
+
+        // A second arrival overtakes the low-priority transaction
+        let high = manager
+            .create_transfer_with_priority("carol", "dave", 10, TransactionPriority::Admin)
+            .unwrap();
+        assert_eq!(manager.next_transaction_to_process().unwrap(), high);
+
+        // Higher-priority work keeps arriving, but the low-priority
+        // transaction has waited long enough
+        manager
+            .create_transfer_with_priority("carol", "dave", 10, TransactionPriority::Admin)
+            .unwrap();
+        assert_eq!(manager.next_transaction_to_process().unwrap(), low);
+
+        let metrics = manager.queue_metrics();
+        assert_eq!(metrics.depth, 1);
+        assert_eq!(metrics.admin_depth, 1);
+        assert_eq!(metrics.processed_total, 3);
+        assert_eq!(metrics.starvation_promotions, 1);
+    }
+
+    #[test]
+    fn test_full_queue_rejects_transactions() {
+        let mut manager = TransactionManager::new();
+        manager.set_queue_config(TransactionQueueConfig {
+            capacity: 1,
+            starvation_limit: 32,
+        });
+
+        manager.create_transfer("alice", "bob", 10).unwrap();
+        assert!(matches!(
+            manager.create_transfer("alice", "bob", 10),
+            Err(AstorError::TransactionQueueFull(1))
+        ));
+        assert_eq!(manager.get_all_transactions().len(), 1);
+        assert_eq!(manager.queue_metrics().rejected_total, 1);
+
+        manager.next_transaction_to_process().unwrap();
+        assert!(manager.create_transfer("alice", "bob", 10).is_ok());
+    }
+
+    #[test]
+    fn test_held_transactions_leave_queue_until_released() {
+        let mut manager = TransactionManager::new();
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+        manager
+            .hold_transaction(&tx_id, "compliance review".to_string())
+            .unwrap();
+        assert!(manager.next_transaction_to_process().is_none());
+
+        manager.release_transaction(&tx_id).unwrap();
+        assert_eq!(manager.next_transaction_to_process().unwrap(), tx_id);
+    }
+
+    #[test]
+    fn test_settled_transactions_leave_queue_and_free_capacity() {
+        let mut manager = TransactionManager::new();
+        manager.set_queue_config(TransactionQueueConfig {
+            capacity: 2,
+            starvation_limit: 32,
+        });
+
+        let completed = manager.create_transfer("alice", "bob", 10).unwrap();
+        let failed = manager.create_transfer("alice", "bob", 10).unwrap();
+        manager.confirm_transaction(&completed).unwrap();
+        manager
+            .fail_transaction(&failed, "insufficient funds".to_string())
+            .unwrap();
+        assert_eq!(manager.queue_metrics().depth, 0);
+
+        // Releasing twice cannot queue the same transaction twice
+        let held = manager.create_transfer("alice", "bob", 10).unwrap();
+        manager
+            .hold_transaction(&held, "compliance review".to_string())
+            .unwrap();
+        manager.release_transaction(&held).unwrap();
+        manager
+            .hold_transaction(&held, "second review".to_string())
+            .unwrap();
+        manager.release_transaction(&held).unwrap();
+        assert_eq!(manager.queue_metrics().depth, 1);
+        assert!(manager.create_transfer("alice", "bob", 10).is_ok());
+    }
+
+    #[derive(Default)]
+    struct MemoryArchive {
+        stored: Vec<Transaction>,
+    }
+
+    impl TransactionArchive for MemoryArchive {
+        fn archive(&mut self, transactions: &[Transaction]) -> Result<(), AstorError> {
+            self.stored.extend_from_slice(transactions);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_pruning_preserves_current_balances() {
+        let mut manager = TransactionManager::new();
+        manager.set_retention_days(30);
+
+        let issuance = manager.create_issuance("root", "alice", 1_000).unwrap();
+        let old_transfer = manager.create_transfer("alice", "bob", 300).unwrap();
+        let recent_transfer = manager.create_transfer("bob", "alice", 50).unwrap();
+        let pending = manager.create_transfer("alice", "bob", 10).unwrap();
+        for tx_id in [&issuance, &old_transfer, &recent_transfer] {
+            manager.confirm_transaction(tx_id).unwrap();
+        }
+
+        // Age everything except the recent transfer past the retention window
+        for tx in manager.transactions.iter_mut() {
+            if tx.id != recent_transfer {
+                tx.timestamp = Utc::now() - chrono::Duration::days(45);
+            }
+        }
+
+        let alice_before = manager.compute_balance("alice");
+        let bob_before = manager.compute_balance("bob");
+        assert_eq!(alice_before, 750);
+        assert_eq!(bob_before, 250);
+
+        let mut archive = MemoryArchive::default();
+        let report = manager.prune_transactions(&mut archive).unwrap();
+        assert_eq!(report.archived, 2);
+        assert_eq!(archive.stored.len(), 2);
+
+        // The pending transaction stays in memory regardless of age
+        assert!(manager.get_transaction(&pending).is_some());
+        assert!(manager.get_transaction(&old_transfer).is_none());
+        assert!(manager.get_transaction(&recent_transfer).is_some());
+
+        assert_eq!(manager.compute_balance("alice"), alice_before);
+        assert_eq!(manager.compute_balance("bob"), bob_before);
+        assert_eq!(
+            manager.account_summary("alice").unwrap().transaction_count,
+            2
+        );
+    }
+
+    #[test]
+    fn test_file_archive_appends_pruned_transactions() {
+        let path = std::env::temp_dir().join(format!(
+            "astor-transaction-archive-{}.jsonl",
+            Uuid::new_v4()
+        ));
+        let mut manager = TransactionManager::new();
+        manager.set_retention_days(30);
+        for _ in 0..2 {
+            let tx_id = manager.create_issuance("root", "alice", 100).unwrap();
+            manager.confirm_transaction(&tx_id).unwrap();
+        }
+        for tx in manager.transactions.iter_mut() {
+            tx.timestamp = Utc::now() - chrono::Duration::days(45);
+        }
+
+        let mut archive = FileTransactionArchive::open(&path).unwrap();
+        assert_eq!(
+            manager.prune_transactions(&mut archive).unwrap().archived,
+            2
+        );
+        assert_eq!(
+            manager.prune_transactions(&mut archive).unwrap().archived,
+            0
+        );
+
+        // Reopening appends after what is already archived
+        let tx_id = manager.create_issuance("root", "bob", 100).unwrap();
+        manager.confirm_transaction(&tx_id).unwrap();
+        manager.transactions[0].timestamp = Utc::now() - chrono::Duration::days(45);
+        let mut archive = FileTransactionArchive::open(&path).unwrap();
+        manager.prune_transactions(&mut archive).unwrap();
+
+        let archived: Vec<Transaction> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(archived.len(), 3);
+        assert_eq!(archived[2].id, tx_id);
+        assert_eq!(manager.compute_balance("alice"), 200);
+    }
+
+    #[test]
+    fn test_transfers_must_cover_base_fee() {
+        let mut manager = TransactionManager::new();
+        manager.create_transfer("alice", "bob", 100).unwrap();
+
+        for _ in 0..10 {
+            manager.record_block(1_000);
+        }
+        let required = manager.current_base_fee();
+        assert!(required > 0);
+
+        assert!(matches!(
+            manager.create_transfer_with_priority(
+                "alice",
+                "bob",
+                100,
+                TransactionPriority::default()
+            ),
+            Err(AstorError::FeeTooLow { offered: 0, .. })
+        ));
+        assert!(manager
+            .create_transfer_with_priority(
+                "alice",
+                "bob",
+                100,
+                TransactionPriority::Tip(required - 1)
+            )
+            .is_err());
+        // The sender is charged the base fee, not the most it offered
+        let capped = manager
+            .create_transfer_with_priority(
+                "alice",
+                "bob",
+                100,
+                TransactionPriority::Tip(required * 2),
+            )
+            .unwrap();
+        assert_eq!(manager.get_transaction(&capped).unwrap().fee, required);
+        let paying = manager.create_transfer("alice", "bob", 100).unwrap();
+        assert_eq!(manager.get_transaction(&paying).unwrap().fee, required);
+        let admin = manager
+            .create_transfer_with_priority("alice", "bob", 100, TransactionPriority::Admin)
+            .unwrap();
+        assert_eq!(manager.get_transaction(&admin).unwrap().fee, 0);
+    }
+
+    #[test]
+    fn test_recurring_transfers_follow_cadence_and_skip_when_unfunded() {
+        let mut manager = TransactionManager::new();
+        let accounts = AccountManager::new();
+        let alice = accounts.create_account(None);
+        let bob = accounts.create_account(None);
+        accounts.credit_account(&alice, 250).unwrap();
+
+        let start = Utc::now();
+        let rent = manager.schedule_recurring(&alice, &bob, 100, 0, RecurrenceRule::Daily, None);
+        let weekly = manager.schedule_recurring(
+            &alice,
+            &bob,
+            1,
+            0,
+            RecurrenceRule::Weekly,
+            Some(start + Duration::days(10)),
+        );
+
+        // Nothing is due before the first cadence
+        assert!(manager
+            .process_due_recurring(start + Duration::hours(12), &accounts, |_| Ok(()))
+            .executed
+            .is_empty());
+
+        // Days 1 and 2 are paid, day 3 finds only 50 left and is skipped
+        let report =
+            manager.process_due_recurring(start + Duration::hours(84), &accounts, |_| Ok(()));
+        assert_eq!(report.executed.len(), 2);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, rent);
+        assert_eq!(accounts.get_balance(&bob).unwrap(), 200);
+
+        // The order survives the skip and resumes once funded
+        accounts.credit_account(&alice, 1_000).unwrap();
+        let report =
+            manager.process_due_recurring(start + Duration::hours(180), &accounts, |_| Ok(()));
+        assert_eq!(report.executed.len(), 5);
+        assert_eq!(manager.get_recurring(rent).unwrap().executed_count, 6);
+        assert_eq!(manager.get_recurring(rent).unwrap().skipped_count, 1);
+        assert_eq!(manager.get_recurring(weekly).unwrap().executed_count, 1);
+        for tx_id in &report.executed {
+            assert_eq!(
+                manager.get_transaction_status(tx_id).unwrap(),
+                &TransactionStatus::Completed
+            );
+        }
+
+        manager.cancel_recurring(rent).unwrap();
+        assert!(manager.cancel_recurring(rent).is_err());
+        let report =
+            manager.process_due_recurring(start + Duration::days(30), &accounts, |_| Ok(()));
+        assert!(report.executed.is_empty());
+        assert_eq!(
+            manager.get_recurring(weekly).unwrap().status,
+            RecurringStatus::Completed
+        );
+        assert_eq!(accounts.get_balance(&bob).unwrap(), 601);
+    }
+
+    #[test]
+    fn test_monthly_recurrence_keeps_original_day() {
+        let start = DateTime::parse_from_rfc3339("2025-01-31T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let dates: Vec<String> = (1..=4)
+            .map(|n| {
+                RecurrenceRule::Monthly
+                    .nth_after(start, n)
+                    .format("%Y-%m-%d")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            dates,
+            ["2025-02-28", "2025-03-31", "2025-04-30", "2025-05-31"]
+        );
+    }
+
+    #[test]
+    fn test_recurring_catch_up_after_downtime_is_bounded() {
+        let mut manager = TransactionManager::new();
+        manager.set_recurring_catch_up_limit(3);
+        let accounts = AccountManager::new();
+        let alice = accounts.create_account(None);
+        let bob = accounts.create_account(None);
+        accounts.credit_account(&alice, 10_000).unwrap();
+
+        let start = Utc::now();
+        let rent = manager.schedule_recurring(&alice, &bob, 100, 0, RecurrenceRule::Daily, None);
+
+        // Ten days pass without a tick; only the three latest are paid
+        let report = manager.process_due_recurring(
+            start + Duration::days(10) + Duration::hours(1),
+            &accounts,
+            |_| Ok(()),
+        );
+        assert_eq!(report.executed.len(), 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, rent);
+        assert_eq!(accounts.get_balance(&bob).unwrap(), 300);
+
+        let recurring = manager.get_recurring(rent).unwrap();
+        assert_eq!(recurring.skipped_count, 7);
+        assert_eq!(recurring.occurrence, 11);
+        assert!(recurring.next_run > start + Duration::days(10) + Duration::hours(1));
+    }
+
+    #[test]
+    fn test_recurring_occurrence_skipped_when_ledger_write_fails() {
+        let mut manager = TransactionManager::new();
+        let accounts = AccountManager::new();
+        let alice = accounts.create_account(None);
+        let bob = accounts.create_account(None);
+        accounts.credit_account(&alice, 1_000).unwrap();
+
+        let start = Utc::now();
+        let rent = manager.schedule_recurring(&alice, &bob, 100, 0, RecurrenceRule::Daily, None);
+
+        let mut recorded = Vec::new();
+        let report =
+            manager.process_due_recurring(start + Duration::hours(25), &accounts, |transaction| {
+                // Undo the move as the system ledger does on a failed write
+                if let TransactionType::Transfer { from, to, amount } =
+                    &transaction.transaction_type
+                {
+                    accounts.transfer(to, from, *amount, false)?;
+                }
+                recorded.push(transaction.id.clone());
+                Err(AstorError::LedgerError("disk full".to_string()))
+            });
+
+        assert!(report.executed.is_empty());
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, rent);
+        assert!(matches!(
+            manager.get_transaction_status(&recorded[0]).unwrap(),
+            TransactionStatus::Failed(reason) if reason.contains("disk full")
+        ));
+        assert_eq!(accounts.get_balance(&alice).unwrap(), 1_000);
+        assert_eq!(accounts.get_balance(&bob).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_signature_verified_by_declared_scheme() {
+        let mut manager = TransactionManager::new();
+        let tx_id = manager.create_transfer("alice", "bob", 100).unwrap();
+        let mut transaction = manager.get_transaction(&tx_id).unwrap().clone();
+
+        let keypair = KeyPair::generate();
+        let public_key = keypair.public_key();
+        transaction.sign(&keypair).unwrap();
+        let verifier = SignatureVerifier::new();
+        transaction
+            .verify_signature(&verifier, &public_key)
+            .unwrap();
+
+        let mut tampered = transaction.clone();
+        tampered.hash = "forged".to_string();
+        assert!(matches!(
+            tampered.verify_signature(&verifier, &public_key),
+            Err(AstorError::InvalidSignature)
+        ));
+
+        // Altering the body invalidates the signature even with the hash intact
+        let mut tampered = transaction.clone();
+        tampered.transaction_type = TransactionType::Transfer {
+            from: "alice".to_string(),
+            to: "mallory".to_string(),
+            amount: 100,
+        };
+        assert!(matches!(
+            tampered.verify_signature(&verifier, &public_key),
+            Err(AstorError::InvalidSignature)
+        ));
+
+        let mut ecdsa = transaction;
+        ecdsa.signature.as_mut().unwrap().scheme = KeyAlgorithm::EcdsaP256;
+        match ecdsa.verify_signature(&verifier, &public_key) {
+            Err(AstorError::UnsupportedSignatureScheme(scheme)) => {
+                assert_eq!(scheme, "ECDSA-P256")
+            }
+            other => panic!("expected unsupported scheme error, got {:?}", other),
+        }
+    }
+
+    fn acknowledgment_fixture() -> (AccountManager, KeyPair, String, String) {
+        let accounts = AccountManager::new();
+        let recipient_key = KeyPair::generate();
+        let alice = accounts.create_account(None);
+        let bob = accounts.create_account(Some(recipient_key.public_key()));
+        accounts.credit_account(&alice, 1_000).unwrap();
+        (accounts, recipient_key, alice, bob)
+    }
+
+    #[test]
+    fn test_transfer_finalized_only_on_recipient_acknowledgment() {
+        let (accounts, recipient_key, alice, bob) = acknowledgment_fixture();
+        let mut manager = TransactionManager::new();
+
+        let tx_id = manager
+            .create_acknowledged_transfer(&alice, &bob, 400, 0, Duration::hours(1), &accounts)
+            .unwrap();
+        assert_eq!(accounts.get_balance(&alice).unwrap(), 1_000);
+        assert_eq!(accounts.get_available_balance(&alice).unwrap(), 600);
+        assert_eq!(accounts.get_balance(&bob).unwrap(), 0);
+
+        // Only the recipient's key can acknowledge
+        let message = crate::accounts::receipt_acknowledgment_message(&tx_id);
+        let forged = KeyPair::generate().sign(message.as_bytes());
+        assert!(manager
+            .acknowledge_receipt(&tx_id, &forged, &accounts, |_| Ok(()))
+            .is_err());
+
+        // The queue never settles it ahead of the acknowledgment
+        assert_eq!(manager.next_transaction_to_process(), None);
+        assert_eq!(
+            manager.get_transaction_status(&tx_id).unwrap(),
+            &TransactionStatus::AwaitingReceipt
+        );
+
+        let acknowledgment = recipient_key.sign(message.as_bytes());
+        let mut recorded = None;
+        manager
+            .acknowledge_receipt(&tx_id, &acknowledgment, &accounts, |transaction| {
+                recorded = Some(transaction.transaction_type.clone());
+                Ok(())
+            })
+            .unwrap();
+        assert!(matches!(
+            recorded,
+            Some(TransactionType::Transfer { ref from, ref to, amount: 400 })
+                if *from == alice && *to == bob
+        ));
+        assert_eq!(accounts.get_balance(&alice).unwrap(), 600);
+        assert_eq!(accounts.get_available_balance(&alice).unwrap(), 600);
+        assert_eq!(accounts.get_balance(&bob).unwrap(), 400);
+        assert!(matches!(
+            manager.get_transaction_status(&tx_id).unwrap(),
+            TransactionStatus::Completed
+        ));
+        assert!(manager.get_pending_receipt(&tx_id).is_none());
+    }
+
+    #[test]
+    fn test_unacknowledged_transfer_reverses_after_window() {
+        let (accounts, recipient_key, alice, bob) = acknowledgment_fixture();
+        let mut manager = TransactionManager::new();
+
+        let tx_id = manager
+            .create_acknowledged_transfer(&alice, &bob, 400, 0, Duration::hours(1), &accounts)
+            .unwrap();
+
+        assert!(manager
+            .expire_unacknowledged(Utc::now(), &accounts)
+            .is_empty());
+        let expired = manager.expire_unacknowledged(Utc::now() + Duration::hours(2), &accounts);
+        assert_eq!(expired, vec![tx_id.clone()]);
+
+        assert_eq!(accounts.get_balance(&alice).unwrap(), 1_000);
+        assert_eq!(accounts.get_available_balance(&alice).unwrap(), 1_000);
+        assert_eq!(accounts.get_balance(&bob).unwrap(), 0);
+        assert!(matches!(
+            manager.get_transaction_status(&tx_id).unwrap(),
+            TransactionStatus::Reversed(_)
+        ));
+
+        // Too late to acknowledge now
+        let message = crate::accounts::receipt_acknowledgment_message(&tx_id);
+        assert!(manager
+            .acknowledge_receipt(
+                &tx_id,
+                &recipient_key.sign(message.as_bytes()),
+                &accounts,
+                |_| Ok(())
+            )
+            .is_err());
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl TransactionObserver for RecordingObserver {
+        fn on_created(&self, transaction: &Transaction) -> Result<(), AstorError> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("created:{}", transaction.id));
+            Ok(())
+        }
+
+        fn on_completed(&self, transaction: &Transaction) -> Result<(), AstorError> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("completed:{}", transaction.id));
+            Ok(())
+        }
+
+        fn on_failed(&self, transaction: &Transaction, reason: &str) -> Result<(), AstorError> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("failed:{}:{}", transaction.id, reason));
+            Ok(())
+        }
+    }
+
+    struct FaultyObserver;
+
+    impl TransactionObserver for FaultyObserver {
+        fn on_created(&self, _transaction: &Transaction) -> Result<(), AstorError> {
+            panic!("observer bug");
+        }
+
+        fn on_completed(&self, _transaction: &Transaction) -> Result<(), AstorError> {
+            Err(AstorError::NetworkError("external ledger down".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_observers_receive_lifecycle_callbacks_in_order() {
+        let mut manager = TransactionManager::new();
+        let recorder = Arc::new(RecordingObserver::default());
+        // A misbehaving observer must not disturb the others or the core path
+        manager.add_observer(Arc::new(FaultyObserver));
+        manager.add_observer(recorder.clone());
+
+        let completed = manager.create_transfer("alice", "bob", 100).unwrap();
+        let failed = manager.create_transfer("bob", "carol", 50).unwrap();
+        manager.confirm_transaction(&completed).unwrap();
+        manager
+            .fail_transaction(&failed, "insufficient funds".to_string())
+            .unwrap();
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![
+                format!("created:{}", completed),
+                format!("created:{}", failed),
+                format!("completed:{}", completed),
+                format!("failed:{}:insufficient funds", failed),
+            ]
+        );
+        assert_eq!(
+            manager.get_transaction_status(&completed).unwrap(),
+            &TransactionStatus::Completed
+        );
+    }
+
+    #[test]
+    fn test_admin_only_transaction_hidden_from_operator() {
+        let mut manager = TransactionManager::new();
+        manager.set_account_visibility("treasury", TransactionVisibility::AdminOnly);
+
+        let public = manager.create_transfer("alice", "bob", 100).unwrap();
+        let restricted = manager.create_transfer("treasury", "bob", 5_000).unwrap();
+        assert_eq!(
+            manager.get_transaction(&restricted).unwrap().visibility,
+            TransactionVisibility::AdminOnly
+        );
+
+        assert!(manager
+            .get_transaction_for(&restricted, &Role::Operator)
+            .is_none());
+        assert!(manager
+            .get_transaction_for(&restricted, &Role::Auditor)
+            .is_none());
+        assert!(manager
+            .get_transaction_for(&restricted, &Role::RootAdmin)
+            .is_some());
+
+        let ids = |transactions: Vec<&Transaction>| -> Vec<String> {
+            transactions.iter().map(|t| t.id.clone()).collect()
+        };
+        assert_eq!(
+            ids(manager.account_statement("bob", &Role::Operator)),
+            vec![public.clone()]
+        );
+        assert_eq!(
+            ids(manager.account_statement("bob", &Role::RootAdmin)),
+            vec![public.clone(), restricted.clone()]
+        );
+
+        // Visibility can also be set per transaction
+        manager
+            .set_transaction_visibility(&public, TransactionVisibility::AuditorOnly)
+            .unwrap();
+        assert!(manager.transactions_visible_to(&Role::Operator).is_empty());
+        assert_eq!(manager.transactions_visible_to(&Role::Auditor).len(), 1);
+    }
+
+    #[test]
+    fn test_ledger_entries_follow_transaction_visibility() {
+        let mut manager = TransactionManager::new();
+        manager.set_account_visibility("treasury", TransactionVisibility::AdminOnly);
+        let public = manager.create_transfer("alice", "bob", 100).unwrap();
+        let restricted = manager.create_transfer("treasury", "bob", 5_000).unwrap();
+
+        let mut ledger = crate::ledger::Ledger::new();
+        ledger
+            .record_issuance("mint".to_string(), "admin", "treasury", 10_000)
+            .unwrap();
+        ledger
+            .record_issuance("seed".to_string(), "admin", "alice", 100)
+            .unwrap();
+        ledger.record_transfer(public, "alice", "bob", 100).unwrap();
+        ledger
+            .record_transfer(restricted, "treasury", "bob", 5_000)
+            .unwrap();
+
+        let visible = |role: &Role| -> Vec<u64> {
+            ledger
+                .get_entries()
+                .iter()
+                .filter(|entry| manager.ledger_entry_visible_to(entry, role))
+                .flat_map(|entry| entry.entry_type.legs())
+                .filter(|(direction, _, _)| *direction == crate::ledger::EntryDirection::Credit)
+                .map(|(_, _, amount)| amount)
+                .collect()
+        };
+        // The treasury issuance is hidden through the account, the transfer
+        // through the transaction
+        assert_eq!(visible(&Role::Operator), vec![100, 100]);
+        assert_eq!(visible(&Role::RootAdmin), vec![10_000, 100, 100, 5_000]);
+
+        assert_eq!(
+            TransactionVisibility::visible_to(&Role::Auditor),
+            vec![
+                TransactionVisibility::Public,
+                TransactionVisibility::AuditorOnly
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signature_must_come_from_the_originating_account() {
+        let accounts = AccountManager::new();
+        let alice_key = KeyPair::generate();
+        let alice = accounts.create_account(Some(alice_key.public_key()));
+        let bob = accounts.create_account(None);
+        let mut manager = TransactionManager::new();
+        let tx_id = manager.create_transfer(&alice, &bob, 100).unwrap();
+        let verifier = SignatureVerifier::new();
+
+        // A valid signature from any other key does not authorize the transfer
+        let intruder = KeyPair::generate();
+        let transaction = manager
+            .transactions
+            .iter_mut()
+            .find(|t| t.id == tx_id)
+            .unwrap();
+        transaction.sign(&intruder).unwrap();
+        assert!(matches!(
+            manager.verify_transaction_signature(&tx_id, &accounts, &verifier),
+            Err(AstorError::InvalidSignature)
+        ));
+
+        let transaction = manager
+            .transactions
+            .iter_mut()
+            .find(|t| t.id == tx_id)
+            .unwrap();
+        transaction.sign(&alice_key).unwrap();
+        manager
+            .verify_transaction_signature(&tx_id, &accounts, &verifier)
+            .unwrap();
     }
 }